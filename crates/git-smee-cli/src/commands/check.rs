@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use git_smee_core::{
+    ErrorCode,
+    installer::{self, HookDriftKind},
+    repository,
+};
+
+use crate::{
+    config_path::read_config_file, diagnostics::expected_hook_script_options, output::print_error,
+};
+
+/// Exit status of `git smee check` when every hook file matches the config.
+pub(crate) const CHECK_CLEAN: i32 = 0;
+/// Exit status of `git smee check` when any hook file drifted.
+pub(crate) const CHECK_DRIFT: i32 = 1;
+/// Exit status of `git smee check` when the check could not be done, e.g.
+/// outside a repository or with an invalid config. Matches clap's status for
+/// usage errors.
+pub(crate) const CHECK_ERROR: i32 = 2;
+
+/// `git smee check`: compares the installed hook files against the wrappers
+/// `install` would write and returns the exit status to end with.
+pub(crate) fn run_check(config_path: &Path) -> i32 {
+    match check(config_path) {
+        Ok(true) => CHECK_CLEAN,
+        Ok(false) => CHECK_DRIFT,
+        Err(error) => {
+            print_error(error.as_ref(), config_path);
+            CHECK_ERROR
+        }
+    }
+}
+
+fn check(config_path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let config = read_config_file(config_path)?;
+    let repository_root = repository::find_git_root()?;
+    let hooks_dir = repository::resolve_git_path(
+        &repository_root,
+        installer::FileSystemHookInstaller::HOOKS_GIT_PATH_KEY,
+    )?;
+    let report = installer::verify_hooks(
+        &config,
+        &hooks_dir,
+        &expected_hook_script_options(config_path, &repository_root),
+    );
+    if report.is_clean() {
+        println!("git-smee check: installed hooks match the config");
+        return Ok(true);
+    }
+    for drift in &report.drift {
+        println!("{}: {} ({})", drift.phase, drift.kind, drift.path.display());
+        if let HookDriftKind::Modified { diff, .. } = &drift.kind {
+            for line in diff.lines() {
+                println!("    {line}");
+            }
+        }
+    }
+    let fix = if report
+        .drift
+        .iter()
+        .any(|drift| drift.kind == HookDriftKind::Unmanaged)
+    {
+        "git smee install --force"
+    } else {
+        "git smee install"
+    };
+    println!(
+        "git-smee check: {} problem(s) with installed hooks; run {fix} [{}]",
+        report.drift.len(),
+        ErrorCode::InstallDrift
+    );
+    Ok(false)
+}
@@ -0,0 +1,59 @@
+use git_smee_core::{
+    executor::{self, PruneLimits, STATE_GIT_PATH},
+    repository,
+};
+
+/// `git smee clean`: prunes `.git/smee` to the configured limits, or empties
+/// it with `--all`.
+pub(crate) fn run_clean(all: bool, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let repository_root = repository::find_git_root()?;
+    let state_dir = repository::resolve_git_path(&repository_root, STATE_GIT_PATH)?;
+    let limits = if all {
+        PruneLimits::everything()
+    } else {
+        PruneLimits::from_git_config(&repository_root)?
+    };
+    let pruned = executor::prune_state(&state_dir, &limits, dry_run)?;
+    let (remove, free) = if dry_run {
+        ("Would remove", "Would free")
+    } else {
+        ("Removed", "Freed")
+    };
+    if pruned.is_empty() {
+        println!(
+            "Nothing to remove in {}; {} kept",
+            state_dir.display(),
+            format_bytes(pruned.kept_bytes)
+        );
+        return Ok(());
+    }
+    for path in &pruned.removed {
+        println!("{remove} {}", path.display());
+    }
+    if pruned.removed_log_lines > 0 {
+        println!(
+            "{remove} {} audit log and analytics line(s)",
+            pruned.removed_log_lines
+        );
+    }
+    println!(
+        "{free} {} in {}; {} kept",
+        format_bytes(pruned.freed_bytes),
+        state_dir.display(),
+        format_bytes(pruned.kept_bytes)
+    );
+    Ok(())
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = KIB * 1024;
+    const GIB: u64 = MIB * 1024;
+    match bytes {
+        bytes if bytes >= GIB => format!("{:.1} GiB", bytes as f64 / GIB as f64),
+        bytes if bytes >= MIB => format!("{:.1} MiB", bytes as f64 / MIB as f64),
+        bytes if bytes >= KIB => format!("{:.1} KiB", bytes as f64 / KIB as f64),
+        bytes => format!("{bytes} B"),
+    }
+}
@@ -0,0 +1,53 @@
+use std::{fs, path::Path};
+
+use git_smee_core::config::{self, edit, format};
+
+pub(crate) fn run_config_get(
+    config_path: &Path,
+    key: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = read_config(config_path)?;
+    println!("{}", edit::get_value(&source, key)?);
+    Ok(())
+}
+
+pub(crate) fn run_config_set(
+    config_path: &Path,
+    key: &str,
+    value: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = read_config(config_path)?;
+    let updated = edit::set_value(&source, key, value)?;
+    fs::write(config_path, updated)?;
+    println!("Set {key} in {}", config_path.display());
+    Ok(())
+}
+
+pub(crate) fn run_config_fmt(
+    config_path: &Path,
+    check: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = read_config(config_path)?;
+    let formatted = format::format_config(&source)?;
+    if formatted == source {
+        println!("{} is already formatted", config_path.display());
+        return Ok(());
+    }
+    if check {
+        return Err(format!(
+            "{} is not formatted; run `git smee config fmt` to fix it",
+            config_path.display()
+        )
+        .into());
+    }
+    fs::write(config_path, formatted)?;
+    println!("Formatted {}", config_path.display());
+    Ok(())
+}
+
+fn read_config(config_path: &Path) -> Result<String, config::Error> {
+    if !config_path.is_file() {
+        return Err(config::Error::MissingFile);
+    }
+    fs::read_to_string(config_path).map_err(config::Error::ReadError)
+}
@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+
+use git_smee_core::{SmeeConfig, config::diff, repository};
+
+use crate::config_path::{install_command, read_config_file};
+
+/// `git smee diff-config <rev>`: lists how hooks behave differently with the
+/// config in the working tree than with the one committed at `rev`.
+pub(crate) fn run_diff_config(
+    config_path: &Path,
+    rev: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let (relative, old, new) = configs_since(config_path, rev)?;
+    let changes = diff::diff_configs(&old, &new)?;
+    if changes.is_empty() {
+        println!("No behavior changes in {} since {rev}", relative.display());
+        return Ok(());
+    }
+    println!("Behavior changes in {} since {rev}:", relative.display());
+    for change in &changes {
+        println!("  {change}");
+    }
+    if old.installable_phases() != new.installable_phases() {
+        let install =
+            install_command(config_path).unwrap_or_else(|_| "git smee install".to_string());
+        eprintln!(
+            "git-smee: note: the set of installed phases changes; run `{install}` after applying this config"
+        );
+    }
+    Ok(())
+}
+
+/// The config's path in the repository, the config committed at `rev`, and
+/// the one in the working tree. A config missing at either end is empty.
+pub(crate) fn configs_since(
+    config_path: &Path,
+    rev: &str,
+) -> Result<(PathBuf, SmeeConfig, SmeeConfig), Box<dyn std::error::Error>> {
+    let repository_root = repository::find_git_root()?;
+    let relative = path_in_repository(config_path, &repository_root)?;
+    let old = match repository::file_at_revision(&repository_root, rev, &relative)? {
+        Some(contents) => SmeeConfig::from_toml_str(&String::from_utf8_lossy(&contents))
+            .map_err(|error| format!("the config at {rev} is invalid: {error}"))?,
+        None => SmeeConfig::default(),
+    };
+    let new = if config_path.is_file() {
+        read_config_file(config_path)?
+    } else {
+        SmeeConfig::default()
+    };
+    Ok((relative, old, new))
+}
+
+/// `config_path` relative to the repository root, as Git names it in a commit.
+fn path_in_repository(
+    config_path: &Path,
+    repository_root: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if config_path.is_relative() {
+        return Ok(config_path.to_path_buf());
+    }
+    let outside = || format!("{} is outside the repository", config_path.display());
+    let file_name = config_path.file_name().ok_or_else(outside)?;
+    let directory = config_path
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .ok_or_else(outside)?;
+    let relative = directory
+        .strip_prefix(repository_root.canonicalize()?)
+        .map_err(|_| outside())?;
+    Ok(relative.join(file_name))
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use git_smee_core::{
+    SmeeConfig,
+    config::{HookDefinition, LifeCyclePhase},
+    executor,
+    redaction::redact_command,
+    repository,
+};
+
+use crate::prompt;
+
+/// Offers to run the `fixable` command of the first failed command in
+/// `summary`. Returns `true` when the fix ran and the phase should be checked
+/// again.
+pub(crate) fn offer_fix(
+    config: &SmeeConfig,
+    phase: LifeCyclePhase,
+    summary: &executor::HookRunSummary,
+    executor_config: &executor::ExecutorConfig,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let Some((hook, fix)) = fixable_failure(config, phase, summary) else {
+        return Ok(false);
+    };
+    let context = [format!(
+        "git-smee: {phase} command `{}` failed; suggested fix: `{}`",
+        redact_command(&hook.command),
+        redact_command(&fix),
+    )];
+    if !prompt::confirm(&context, "Run the suggested fix and re-check?")? {
+        return Ok(false);
+    }
+
+    let repository_root = repository::find_git_root()?;
+    let staged = if hook.restage {
+        repository::staged_paths(&repository_root)?
+    } else {
+        Vec::new()
+    };
+    let fix_config = SmeeConfig {
+        hooks: HashMap::from([(phase, vec![fix_hook(&hook, &fix)])]),
+        settings: config.settings.clone(),
+        tasks: Default::default(),
+        defaults: Default::default(),
+        install: Default::default(),
+    };
+    // A clone starts a fresh deduplication session: the fix must run even if
+    // an identical command passed in an earlier phase.
+    let fix_summary = executor::execute_hook_with_executor_config(
+        &fix_config,
+        phase,
+        &[],
+        None,
+        &executor_config.clone(),
+    )?;
+    if let Some(error) = fix_summary.error() {
+        return Err(Box::new(error));
+    }
+    if hook.restage {
+        repository::stage_paths(&repository_root, &staged)?;
+        eprintln!("git-smee: re-staged {} file(s)", staged.len());
+    }
+    Ok(true)
+}
+
+/// The first blocking failure of the run, if its config entry names a fix.
+/// Entries are looked up with task references expanded, the way they ran.
+fn fixable_failure(
+    config: &SmeeConfig,
+    phase: LifeCyclePhase,
+    summary: &executor::HookRunSummary,
+) -> Option<(HookDefinition, String)> {
+    let failure = summary.first_failure()?;
+    let hook = config
+        .phase_entries(phase)?
+        .into_iter()
+        .nth(failure.config_index())?;
+    let fix = hook.fixable.clone()?;
+    Some((hook, fix))
+}
+
+/// The fix runs like a hook entry of its own, so the machine policy, the audit
+/// log, and the failed command's timeout apply to it as well.
+fn fix_hook(hook: &HookDefinition, fix: &str) -> HookDefinition {
+    HookDefinition {
+        name: Some(match hook.name.as_deref() {
+            Some(name) => format!("{name}-fix"),
+            None => "fix".to_string(),
+        }),
+        command: fix.to_string(),
+        timeout: hook.timeout,
+        grace_period: hook.grace_period,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use git_smee_core::config::HookTimeout;
+
+    use super::*;
+
+    #[test]
+    fn given_failed_hook_when_building_fix_then_name_and_limits_carry_over() {
+        let hook = HookDefinition {
+            name: Some("fmt".to_string()),
+            command: "cargo fmt --check".to_string(),
+            timeout: Some(HookTimeout::Seconds(60)),
+            fixable: Some("cargo fmt".to_string()),
+            restage: true,
+            ..Default::default()
+        };
+
+        let fix = fix_hook(&hook, "cargo fmt");
+
+        assert_eq!(fix.name.as_deref(), Some("fmt-fix"));
+        assert_eq!(fix.command, "cargo fmt");
+        assert_eq!(fix.timeout, Some(HookTimeout::Seconds(60)));
+        assert_eq!(fix.fixable, None);
+        assert!(!fix.restage);
+    }
+}
@@ -1,9 +1,22 @@
-use std::{env, path::Path};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 use clap::ValueEnum;
-use git_smee_core::{config, installer, installer::HookInstaller, repository};
+use git_smee_core::{
+    config, installer, installer::HookInstaller, repository, shell_quote::sh_quote,
+};
 
-use crate::config_path::is_default_config_path;
+use crate::config_path::{
+    install_command, is_default_config_path, normalize_config_path_for_hook_script,
+    read_config_file,
+};
+
+/// Executable named in committed hook wrappers. Wrappers under a repo-tracked
+/// `hooks_path` are shared by every clone, so they find git-smee on `PATH`
+/// instead of embedding this machine's install location.
+const PORTABLE_GIT_SMEE_EXECUTABLE: &str = "git-smee";
 
 #[derive(Clone, Debug, ValueEnum)]
 pub(crate) enum InitTemplate {
@@ -65,18 +78,36 @@ command = "echo 'replace me with your pre-commit check'"
 # command = "./scripts/test"
 "#;
 
+/// Options for `git smee init --hooks-path`.
+pub(crate) struct HooksPathInit<'a> {
+    /// Repo-relative directory for the committed hook wrappers.
+    pub(crate) hooks_path: &'a str,
+    /// Script that new clones run to set `core.hooksPath`.
+    pub(crate) bootstrap_script: Option<&'a Path>,
+}
+
 pub(crate) fn run_init(
     config_path: &Path,
     force: bool,
     template: &InitTemplate,
+    hooks_path_init: Option<&HooksPathInit<'_>>,
+    install: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     repository::ensure_in_repo_root()?;
-    let installer = installer::FileSystemHookInstaller::from_default_with_force(force)?;
+    let installer = installer::FileSystemHookInstaller::from_path_with_hooks_path(
+        PathBuf::from("./"),
+        hooks_path_init.map(|init| init.hooks_path),
+        force,
+    )?;
     println!(
         "Initializing {} configuration file...",
         config_path.display()
     );
-    let template_config = template.config_content()?;
+    let mut template_config = template.config_content()?;
+    if let Some(init) = hooks_path_init {
+        template_config =
+            config::edit::set_value(&template_config, "settings.hooks_path", init.hooks_path)?;
+    }
     let template_config = installer::with_managed_header(&template_config);
 
     if is_default_config_path(config_path, &env::current_dir()?) {
@@ -84,6 +115,91 @@ pub(crate) fn run_init(
     } else {
         installer::write_config_file(config_path, &template_config, force)?;
     }
+    let install_command = install_command(config_path)?;
+    if let Some(init) = hooks_path_init {
+        install_committed_hooks(config_path, &installer, init)?;
+    } else if install {
+        // `--force` replaces the config, not unmanaged hooks someone else wrote.
+        crate::commands::install::run_install(
+            config_path,
+            crate::commands::install::InstallFlags::default(),
+        )?;
+        println!(
+            "Edit {} to change the commands; run `{install_command}` again after adding a phase.",
+            config_path.display()
+        );
+    } else {
+        println!("Next steps:");
+        println!(
+            "  1. Edit {} to define the commands for each hook.",
+            config_path.display()
+        );
+        println!("  2. Run `{install_command}` to install the hook wrappers.");
+    }
+    Ok(())
+}
+
+/// Writes portable wrappers into `hooks_path` and points `core.hooksPath` at
+/// it, so the wrappers can be committed and shared.
+fn install_committed_hooks(
+    config_path: &Path,
+    installer: &installer::FileSystemHookInstaller,
+    init: &HooksPathInit<'_>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = read_config_file(config_path)?;
+    let hook_script_options = installer::HookScriptOptions::new(
+        PathBuf::from(PORTABLE_GIT_SMEE_EXECUTABLE),
+        normalize_config_path_for_hook_script(config_path, &env::current_dir()?)?,
+    );
+    installer::install_hooks_with_options(&config, installer, &hook_script_options)?;
+    let enable_command = enable_hooks_path_command(init.hooks_path);
+    if let Some(bootstrap_script) = init.bootstrap_script {
+        add_bootstrap_step(bootstrap_script, &enable_command)?;
+        println!(
+            "Added `{enable_command}` to {}.",
+            bootstrap_script.display()
+        );
+    }
+    println!(
+        "Hook wrappers written to {}/ and core.hooksPath set. Commit them with {}; new clones enable them with `{enable_command}`.",
+        init.hooks_path,
+        config_path.display(),
+    );
+    Ok(())
+}
+
+/// The shell command that points a fresh clone at the committed wrappers.
+fn enable_hooks_path_command(hooks_path: &str) -> String {
+    format!("git config core.hooksPath {}", sh_quote(hooks_path))
+}
+
+/// Appends `command` to a shell bootstrap script, creating the script if
+/// needed. Running init again does not add the line twice.
+fn add_bootstrap_step(script: &Path, command: &str) -> std::io::Result<()> {
+    let existing = match fs::read_to_string(script) {
+        Ok(existing) => existing,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            "#!/usr/bin/env sh\nset -e\n".to_string()
+        }
+        Err(error) => return Err(error),
+    };
+    if existing.lines().any(|line| line.trim() == command) {
+        return Ok(());
+    }
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    fs::write(script, format!("{existing}{separator}{command}\n"))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut permissions = fs::metadata(script)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o111);
+        fs::set_permissions(script, permissions)?;
+    }
     Ok(())
 }
 
@@ -99,6 +215,41 @@ mod tests {
         assert_eq!(InitTemplate::Generic.to_string(), "generic");
     }
 
+    #[test]
+    fn given_existing_bootstrap_script_when_adding_step_then_line_is_appended_once() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let script = temp_dir.path().join("bootstrap.sh");
+        fs::write(&script, "#!/bin/sh\nnpm ci").expect("write script");
+
+        add_bootstrap_step(&script, "git config core.hooksPath hooks").expect("first add");
+        add_bootstrap_step(&script, "git config core.hooksPath hooks").expect("second add");
+
+        assert_eq!(
+            fs::read_to_string(&script).expect("read script"),
+            "#!/bin/sh\nnpm ci\ngit config core.hooksPath hooks\n"
+        );
+    }
+
+    #[test]
+    fn given_hooks_path_with_shell_characters_when_building_enable_command_then_it_is_quoted() {
+        assert_eq!(
+            enable_hooks_path_command("hooks"),
+            "git config core.hooksPath hooks"
+        );
+        assert_eq!(
+            enable_hooks_path_command("team hooks/it's $HOME"),
+            "git config core.hooksPath 'team hooks/it'\"'\"'s $HOME'"
+        );
+    }
+
+    #[test]
+    fn snapshot_config_of_every_init_template() {
+        for template in InitTemplate::value_variants() {
+            let content = template.config_content().expect("template content");
+            insta::assert_snapshot!(format!("init_template_{template}"), content);
+        }
+    }
+
     #[test]
     fn non_minimal_templates_include_expected_hooks() {
         let rust = InitTemplate::Rust.config_content().expect("rust template");
@@ -1,22 +1,130 @@
-use std::{env, path::Path};
+use std::{
+    env,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use git_smee_core::{installer, repository};
+use git_smee_core::{
+    config::LifeCyclePhase,
+    installer::{self, PlannedHookAction},
+    repository,
+};
 
 use crate::config_path::{normalize_config_path_for_hook_script, read_config_file};
 
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const CYAN: &str = "\x1b[36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Flags of `git smee install`.
+#[derive(Debug, Default)]
+pub(crate) struct InstallFlags<'a> {
+    pub(crate) force: bool,
+    pub(crate) chain: bool,
+    pub(crate) dry_run: bool,
+    /// Phases to install instead of all of them.
+    pub(crate) phases: &'a [String],
+}
+
 pub(crate) fn run_install(
     config_path: &Path,
-    force: bool,
+    flags: InstallFlags<'_>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let InstallFlags {
+        force,
+        chain,
+        dry_run,
+        phases,
+    } = flags;
     repository::ensure_in_repo_root()?;
-    let installer = installer::FileSystemHookInstaller::from_default_with_force(force)?;
+    let config = read_config_file(config_path)?;
+    for warning in config.warnings() {
+        eprintln!("git-smee: warning: {warning}");
+    }
+    let mut installer = installer::FileSystemHookInstaller::from_path_with_hooks_path(
+        PathBuf::from("./"),
+        config.settings.hooks_path.as_deref(),
+        force,
+    )?
+    .with_chain_existing(chain);
+    if !phases.is_empty() {
+        let phases = phases
+            .iter()
+            .map(|phase| LifeCyclePhase::from_str(phase))
+            .collect::<Result<Vec<_>, _>>()?;
+        installer = installer.with_only_phases(phases);
+    }
     let config_path_for_hooks =
         normalize_config_path_for_hook_script(config_path, &env::current_dir()?)?;
     let hook_script_options =
         installer::HookScriptOptions::new(env::current_exe()?, config_path_for_hooks);
+    if dry_run {
+        let changes =
+            installer::plan_hooks_with_options(&config, &installer, &hook_script_options)?;
+        print_planned_changes(&changes, use_color());
+        return Ok(());
+    }
     println!("Installing hooks...");
-    let config = read_config_file(config_path)?;
     installer::install_hooks_with_options(&config, &installer, &hook_script_options)?;
     println!("Hooks installed successfully.");
     Ok(())
 }
+
+fn print_planned_changes(changes: &[installer::PlannedHookChange], color: bool) {
+    for change in changes {
+        let path = change.path.display();
+        match &change.action {
+            PlannedHookAction::Create => println!("would create {path}"),
+            PlannedHookAction::Unchanged => println!("unchanged    {path}"),
+            PlannedHookAction::Remove => println!("would remove {path}"),
+            PlannedHookAction::Update { diff } => {
+                println!("would update {path}");
+                for line in diff.lines() {
+                    println!("{}", colorize_diff_line(line, color));
+                }
+            }
+        }
+    }
+    println!("Dry run: no files were changed.");
+}
+
+/// Colors diff output only for a terminal, and never when `NO_COLOR` is set.
+fn use_color() -> bool {
+    io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+fn colorize_diff_line(line: &str, color: bool) -> String {
+    let style = match line {
+        _ if !color => return line.to_string(),
+        line if line.starts_with("+++") || line.starts_with("---") => BOLD,
+        line if line.starts_with('+') => GREEN,
+        line if line.starts_with('-') => RED,
+        line if line.starts_with("@@") => CYAN,
+        line => return line.to_string(),
+    };
+    format!("{style}{line}{RESET}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_color_when_colorizing_diff_lines_then_additions_and_removals_are_styled() {
+        assert_eq!(colorize_diff_line("+new", true), "\x1b[32m+new\x1b[0m");
+        assert_eq!(colorize_diff_line("-old", true), "\x1b[31m-old\x1b[0m");
+        assert_eq!(
+            colorize_diff_line("@@ -1 +1 @@", true),
+            "\x1b[36m@@ -1 +1 @@\x1b[0m"
+        );
+        assert_eq!(
+            colorize_diff_line("--- a/pre-commit", true),
+            "\x1b[1m--- a/pre-commit\x1b[0m"
+        );
+        assert_eq!(colorize_diff_line(" same", true), " same");
+        assert_eq!(colorize_diff_line("+new", false), "+new");
+    }
+}
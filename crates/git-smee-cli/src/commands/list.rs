@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use git_smee_core::{
+    executor::hook_label,
+    installer::{self, FileSystemHookInstaller, HookInstallState},
+    repository,
+};
+
+use crate::config_path::{install_command, read_config_file};
+
+/// `git smee list`: prints each configured phase, the commands it runs, and
+/// whether its wrapper is installed.
+pub(crate) fn run_list(config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let repository_root = repository::find_git_root()?;
+    let config = read_config_file(config_path)?;
+    let hooks_dir = repository::resolve_git_path(
+        &repository_root,
+        FileSystemHookInstaller::HOOKS_GIT_PATH_KEY,
+    )?;
+    let statuses = installer::phase_install_statuses(&config, &hooks_dir);
+    if statuses.is_empty() {
+        println!("No phases configured in {}", config_path.display());
+        return Ok(());
+    }
+    for status in &statuses {
+        println!(
+            "{} ({}: {})",
+            status.phase,
+            status.state,
+            status.path.display()
+        );
+        for (index, entry) in status.entries.iter().enumerate() {
+            println!("  {}: {}", hook_label(index, entry), entry.command);
+        }
+    }
+    if statuses
+        .iter()
+        .any(|status| status.state == HookInstallState::NotInstalled)
+    {
+        let install =
+            install_command(config_path).unwrap_or_else(|_| "git smee install".to_string());
+        eprintln!("git-smee: note: run `{install}` to install the missing wrappers");
+    }
+    Ok(())
+}
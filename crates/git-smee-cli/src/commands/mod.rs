@@ -1,4 +1,22 @@
+pub(crate) mod check;
+pub(crate) mod clean;
+pub(crate) mod config;
+pub(crate) mod diff_config;
+pub(crate) mod fix;
 pub(crate) mod init;
 pub(crate) mod install;
+pub(crate) mod list;
 pub(crate) mod migrate_hooks;
+pub(crate) mod notify;
+pub(crate) mod owners;
+pub(crate) mod render;
 pub(crate) mod run;
+pub(crate) mod serve;
+pub(crate) mod simulate;
+pub(crate) mod stats;
+pub(crate) mod task;
+pub(crate) mod toggle;
+pub(crate) mod trust;
+pub(crate) mod uninstall;
+pub(crate) mod validate;
+pub(crate) mod why;
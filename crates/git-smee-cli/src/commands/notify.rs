@@ -0,0 +1,87 @@
+use std::env;
+
+use git_smee_core::{
+    repository::{self, CommitSummary},
+    webhook::Webhook,
+};
+use serde::Serialize;
+
+/// Webhook URL used by `git smee notify commit` when `--webhook` is not given.
+const NOTIFY_WEBHOOK_ENV: &str = "GIT_SMEE_NOTIFY_WEBHOOK";
+
+/// How many changed paths the summary names before it says "and N more".
+const LISTED_FILES: usize = 5;
+
+#[derive(Debug, Serialize)]
+struct CommitPayload<'a> {
+    event: &'static str,
+    sha: &'a str,
+    short_sha: &'a str,
+    subject: &'a str,
+    author: &'a str,
+    branch: Option<&'a str>,
+    files_changed: usize,
+    files: Vec<String>,
+}
+
+/// `git smee notify commit`: prints a one-line summary of `HEAD` and, when a
+/// webhook URL is set, posts the commit metadata to it. Meant for a
+/// `post-commit` hook, so a failing webhook only warns: the commit is already
+/// made and nothing can be undone by failing.
+pub(crate) fn run_notify_commit(webhook: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let repository_root = repository::find_git_root()?;
+    let commit = repository::head_commit_summary(&repository_root)?;
+    println!("{}", commit_summary_text(&commit));
+
+    let url = webhook
+        .map(str::to_string)
+        .or_else(|| env::var(NOTIFY_WEBHOOK_ENV).ok())
+        .filter(|url| !url.trim().is_empty());
+    if let Some(url) = url {
+        let webhook = Webhook::new(url)?;
+        let payload = serde_json::to_vec(&commit_payload(&commit))?;
+        if let Err(error) = webhook.post_json(&payload) {
+            eprintln!("git-smee: warning: {error}");
+        }
+    }
+    Ok(())
+}
+
+fn commit_summary_text(commit: &CommitSummary) -> String {
+    let mut text = format!("{} {}", commit.short_sha, commit.subject);
+    let count = commit.files.len();
+    if count == 0 {
+        return text;
+    }
+    let mut listed = commit
+        .files
+        .iter()
+        .take(LISTED_FILES)
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if count > LISTED_FILES {
+        listed.push_str(&format!(", and {} more", count - LISTED_FILES));
+    }
+    let noun = if count == 1 { "file" } else { "files" };
+    text.push_str(&format!("\n  {count} {noun} changed: {listed}"));
+    text
+}
+
+fn commit_payload(commit: &CommitSummary) -> CommitPayload<'_> {
+    CommitPayload {
+        event: "commit",
+        sha: &commit.sha,
+        short_sha: &commit.short_sha,
+        subject: &commit.subject,
+        author: &commit.author,
+        branch: commit.branch.as_deref(),
+        files_changed: commit.files.len(),
+        files: commit
+            .files
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect(),
+    }
+}
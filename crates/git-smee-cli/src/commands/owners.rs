@@ -0,0 +1,67 @@
+use std::path::Path;
+
+use git_smee_core::{config::owners, repository};
+
+use crate::{commands::diff_config::configs_since, config_path::read_config_file};
+
+/// `git smee owners`: lists the config's commands by owner, or with `since`
+/// who should review each command changed since that revision.
+pub(crate) fn run_owners(
+    config_path: &Path,
+    since: Option<&str>,
+    require_owner: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let Some(rev) = since else {
+        print_owners(&owners::owned_entries(&read_config_file(config_path)?));
+        return Ok(());
+    };
+    let (relative, old, new) = configs_since(config_path, rev)?;
+    let requests = owners::review_requests(&old, &new)?;
+    if requests.is_empty() {
+        println!("No commands changed in {} since {rev}", relative.display());
+        return Ok(());
+    }
+    println!("Commands changed in {} since {rev}:", relative.display());
+    let mut unowned = 0;
+    for request in &requests {
+        println!("  {}", request.change);
+        if request.owners.is_empty() {
+            unowned += 1;
+            println!("    review: (no owner)");
+        } else {
+            println!("    review: {}", request.owners.join(", "));
+        }
+    }
+    if unowned > 0 {
+        let message = format!(
+            "{unowned} changed command(s) have no owner to review them; set `owner` on them"
+        );
+        if require_owner {
+            return Err(message.into());
+        }
+        eprintln!("git-smee: warning: {message}");
+    }
+    Ok(())
+}
+
+fn print_owners(entries: &[owners::OwnedEntry]) {
+    if entries.is_empty() {
+        println!("No commands configured");
+        return;
+    }
+    let mut owner_names: Vec<Option<&str>> =
+        entries.iter().map(|entry| entry.owner.as_deref()).collect();
+    // Owners by name, then the commands nobody owns.
+    owner_names.sort_by_key(|owner| (owner.is_none(), *owner));
+    owner_names.dedup();
+    for owner in owner_names {
+        println!("{}", owner.unwrap_or("(no owner)"));
+        for entry in entries
+            .iter()
+            .filter(|entry| entry.owner.as_deref() == owner)
+        {
+            println!("  {}: {}  {}", entry.run, entry.label, entry.command);
+        }
+    }
+}
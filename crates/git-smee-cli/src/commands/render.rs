@@ -0,0 +1,31 @@
+use std::{env, path::Path, str::FromStr};
+
+use git_smee_core::{config::LifeCyclePhase, installer, repository};
+
+use crate::config_path::{normalize_config_path_for_hook_script, read_config_file};
+
+/// `git smee render <phase>`: prints the wrapper `install` would write for
+/// `phase`, without writing it.
+pub(crate) fn run_render(
+    config_path: &Path,
+    phase: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let phase = LifeCyclePhase::from_str(phase)?;
+    let config = read_config_file(config_path)?;
+    if !config.installable_phases().contains(&phase) {
+        eprintln!(
+            "Note: {phase} is not installed by {}; install would not write this wrapper.",
+            config_path.display()
+        );
+    }
+    let config_path_for_hooks =
+        normalize_config_path_for_hook_script(config_path, &env::current_dir()?)?;
+    let hook_script_options =
+        installer::HookScriptOptions::new(env::current_exe()?, config_path_for_hooks);
+    print!(
+        "{}",
+        installer::render_hook_script(&config, phase, &hook_script_options)
+    );
+    Ok(())
+}
@@ -1,43 +1,631 @@
 use std::{
     env,
     io::{self, IsTerminal, Read},
-    path::Path,
+    path::{Path, PathBuf},
     str::FromStr,
+    sync::Arc,
 };
 
-use git_smee_core::{config::LifeCyclePhase, executor, repository};
+use git_smee_core::{
+    SmeeConfig,
+    config::{HookDefinition, HookTimeout, LifeCyclePhase},
+    executor::{self, Event, EventSink, RunJournal},
+    opt_out::OptOutList,
+    redaction::redact_secret_values,
+    repository,
+};
+use serde::Serialize;
 
-use crate::config_path::read_config_file;
+use crate::{
+    commands::{fix, trust::ensure_snapshot_trusted},
+    config_path::{
+        install_command, read_config_file, read_config_snapshot, warn_if_config_changed,
+    },
+    diagnostics::{HookInspectionState, inspect_hook},
+    output::OutputFormat,
+};
 
 const DEFAULT_MAX_HOOK_STDIN_BYTES: u64 = 10 * 1024 * 1024;
 const HOOK_STDIN_LIMIT_ENV: &str = "GIT_SMEE_HOOK_STDIN_LIMIT_BYTES";
 const DEFAULT_HOOK_STDIN_LIMIT_DISPLAY: &str = "10 MiB";
+const SHOW_COMMANDS_ENV: &str = "GIT_SMEE_SHOW_COMMANDS";
+
+/// Flags of `git smee run` that change how its phases run.
+#[derive(Debug, Default)]
+pub(crate) struct RunFlags<'a> {
+    pub(crate) show_commands: bool,
+    pub(crate) trust: bool,
+    pub(crate) events_socket: Option<&'a Path>,
+    pub(crate) simulated_failures: &'a [String],
+    /// `<base>..<head>` commits to run `pre-push` against instead of stdin.
+    pub(crate) range: Option<&'a str>,
+}
 
 pub(crate) fn run_hook(
     config_path: &Path,
-    hook: &str,
+    phase_names: &[String],
+    hook_args: &[String],
+    flags: RunFlags<'_>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let RunFlags {
+        show_commands,
+        trust,
+        events_socket,
+        simulated_failures,
+        range,
+    } = flags;
+    repository::ensure_in_repo_root()?;
+    install_cancellation_handler();
+    let (phases, hook_args) = resolve_phases_and_hook_args(phase_names, hook_args)?;
+    let stdin_payload = match range {
+        Some(range) => Some(range_stdin(&phases, range)?),
+        None => read_hook_stdin_for_phases(&phases)?,
+    };
+    let snapshot = read_config_snapshot(config_path)?;
+    ensure_snapshot_trusted(&snapshot, trust)?;
+    check_simulated_failures(snapshot.config(), &phases, simulated_failures)?;
+    // Pruning and resuming are conveniences: a git dir that cannot store run
+    // state must not keep hooks from running.
+    let _ = executor::prune_state_if_due(&env::current_dir()?);
+    let journal = RunJournal::start(
+        &env::current_dir()?,
+        &phases,
+        hook_args,
+        stdin_payload.as_deref(),
+    )
+    .ok();
+    let invocations: Vec<_> = phases
+        .iter()
+        .map(|&phase| PhaseInvocation {
+            phase,
+            hook_args: hook_args.to_vec(),
+        })
+        .collect();
+    let result = run_journaled(
+        snapshot.config(),
+        &invocations,
+        stdin_payload.as_deref(),
+        &executor_config(show_commands, events_socket)
+            .with_simulated_failures(simulated_failures.to_vec()),
+        journal,
+    );
+    warn_if_config_changed(&snapshot);
+    note_missing_wrappers(config_path, snapshot.config(), &phases);
+    result
+}
+
+/// After a manual run, points out phases Git will not run on its own because
+/// their wrapper is missing or was replaced, so a passing `git smee run` is
+/// not mistaken for working hooks. Phases in `settings.never_install` are
+/// left out; problems finding the hooks directory are left to `doctor`.
+fn note_missing_wrappers(config_path: &Path, config: &SmeeConfig, phases: &[LifeCyclePhase]) {
+    let Ok(repository_root) = repository::find_git_root() else {
+        return;
+    };
+    let Ok(hooks_dir) = repository::resolve_git_path(
+        &repository_root,
+        git_smee_core::installer::FileSystemHookInstaller::HOOKS_GIT_PATH_KEY,
+    ) else {
+        return;
+    };
+    let install = install_command(config_path).unwrap_or_else(|_| "git smee install".to_string());
+    for &phase in phases {
+        if config.settings.never_install.contains(&phase) {
+            continue;
+        }
+        let inspection = inspect_hook(&repository_root, &hooks_dir, phase);
+        match inspection.state() {
+            HookInspectionState::Missing => eprintln!(
+                "git-smee: note: no {phase} hook is installed, so Git won't run these commands automatically; run `{install}`"
+            ),
+            HookInspectionState::Unmanaged => eprintln!(
+                "git-smee: note: {} is not a git-smee wrapper, so Git runs it instead of these commands; run `{install} --force` to replace it",
+                inspection.display_path()
+            ),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RunListing {
+    phases: Vec<PhaseListing>,
+}
+
+#[derive(Debug, Serialize)]
+struct PhaseListing {
+    phase: String,
+    commands: Vec<CommandListing>,
+}
+
+#[derive(Debug, Serialize)]
+struct CommandListing {
+    label: String,
+    command: String,
+    parallel: bool,
+    group: Option<String>,
+    mutex: Option<String>,
+    timeout: Option<String>,
+    severity: &'static str,
+    requires: Vec<String>,
+}
+
+/// `git smee run <phase>... --list`: prints the commands the phases would run,
+/// task references expanded, without running or trust-checking anything.
+pub(crate) fn run_list(
+    config_path: &Path,
+    phase_names: &[String],
     hook_args: &[String],
+    format: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
     repository::ensure_in_repo_root()?;
-    let phase = LifeCyclePhase::from_str(hook)?;
-    let stdin_payload = read_hook_stdin_for_phase(phase)?;
+    let (phases, _) = resolve_phases_and_hook_args(phase_names, hook_args)?;
     let config = read_config_file(config_path)?;
-    let summary =
-        executor::execute_hook_with_summary(&config, phase, hook_args, stdin_payload.as_deref())?;
-    for line in summary.text_lines(phase) {
-        println!("{line}");
+    let listing = RunListing {
+        phases: phases
+            .into_iter()
+            .map(|phase| PhaseListing {
+                phase: phase.to_string(),
+                commands: config
+                    .phase_entries(phase)
+                    .unwrap_or_default()
+                    .iter()
+                    .enumerate()
+                    .map(|(config_index, hook)| CommandListing {
+                        label: executor::hook_label(config_index, hook),
+                        command: redact_secret_values(&hook.command),
+                        parallel: hook.parallel_execution_allowed,
+                        group: hook.group.clone(),
+                        mutex: hook.mutex.clone(),
+                        timeout: hook.timeout.map(|timeout| match timeout {
+                            HookTimeout::Seconds(seconds) => format!("{seconds}s"),
+                            HookTimeout::Unbounded => "none".to_string(),
+                        }),
+                        severity: hook.severity.as_str(),
+                        requires: hook.requires.iter().map(ToString::to_string).collect(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&listing)?),
+        OutputFormat::Text => print_run_listing(&listing),
     }
-    if let Some(error) = summary.error() {
-        return Err(Box::new(error));
+    Ok(())
+}
+
+fn print_run_listing(listing: &RunListing) {
+    for phase in &listing.phases {
+        println!("{}: {} command(s)", phase.phase, phase.commands.len());
+        for command in &phase.commands {
+            let mut settings = vec![if command.parallel {
+                "parallel".to_string()
+            } else {
+                "sequential".to_string()
+            }];
+            settings.extend(command.group.iter().map(|group| format!("group {group}")));
+            settings.extend(command.mutex.iter().map(|mutex| format!("mutex {mutex}")));
+            settings.extend(
+                command
+                    .timeout
+                    .iter()
+                    .map(|timeout| format!("timeout {timeout}")),
+            );
+            if command.severity != "error" {
+                settings.push(format!("severity {}", command.severity));
+            }
+            if !command.requires.is_empty() {
+                settings.push(format!("requires {}", command.requires.join(", ")));
+            }
+            println!(
+                "  - {}: {} ({})",
+                command.label,
+                command.command,
+                settings.join(", ")
+            );
+        }
+    }
+}
+
+/// `git smee resume`: continues an interrupted run, skipping the commands that
+/// already passed. Without a run ID the most recent unfinished run is resumed.
+pub(crate) fn run_resume(
+    config_path: &Path,
+    run_id: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    install_cancellation_handler();
+    let journal = RunJournal::open(&env::current_dir()?, run_id)?;
+    let snapshot = read_config_snapshot(config_path)?;
+    ensure_snapshot_trusted(&snapshot, false)?;
+    let phases = journal.phases();
+    println!(
+        "Resuming run {} ({})",
+        journal.run_id(),
+        phases
+            .iter()
+            .map(|phase| phase.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let hook_args = journal.hook_args();
+    let stdin_payload = journal.stdin_payload();
+    let invocations: Vec<_> = phases
+        .into_iter()
+        .map(|phase| PhaseInvocation {
+            phase,
+            hook_args: hook_args.clone(),
+        })
+        .collect();
+    let result = run_journaled(
+        snapshot.config(),
+        &invocations,
+        stdin_payload.as_deref(),
+        &executor_config(false, None),
+        Some(journal),
+    );
+    warn_if_config_changed(&snapshot);
+    result
+}
+
+/// Rejects `--simulate-failure` labels that match no command of `phases`, so a
+/// typo does not quietly turn the rehearsal into a normal run.
+fn check_simulated_failures(
+    config: &SmeeConfig,
+    phases: &[LifeCyclePhase],
+    simulated_failures: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let labels: Vec<_> = phases
+        .iter()
+        .flat_map(|&phase| {
+            config
+                .phase_entries(phase)
+                .unwrap_or_default()
+                .iter()
+                .enumerate()
+                .map(|(config_index, hook)| executor::hook_label(config_index, hook))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    for label in simulated_failures {
+        if !labels.contains(label) {
+            return Err(format!(
+                "--simulate-failure {label}: no command with that name in {}",
+                phases
+                    .iter()
+                    .map(|phase| phase.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .into());
+        }
+        eprintln!("git-smee: simulating a failure of {label}; it will not run");
     }
     Ok(())
 }
 
-fn read_hook_stdin_for_phase(phase: LifeCyclePhase) -> io::Result<Option<Vec<u8>>> {
+/// Runs the phases, then drops the run state on success. A failed run of
+/// several phases also tells the user how to resume it.
+fn run_journaled(
+    config: &SmeeConfig,
+    invocations: &[PhaseInvocation],
+    stdin_payload: Option<&[u8]>,
+    executor_config: &executor::ExecutorConfig,
+    journal: Option<RunJournal>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = run_phase_sequence(
+        config,
+        invocations,
+        stdin_payload,
+        executor_config,
+        journal.as_ref(),
+    );
+    match (journal, &result) {
+        (Some(journal), Ok(())) => journal.finish()?,
+        (Some(journal), Err(_)) if invocations.len() > 1 => {
+            let run_id = journal.run_id();
+            eprintln!(
+                "git-smee: run {run_id} did not complete; resume it with `git smee resume {run_id}`"
+            );
+        }
+        _ => {}
+    }
+    result
+}
+
+/// Builds the executor configuration for CLI runs. Command echoing can be
+/// enabled with `--show-commands` or, for runs started by Git through the
+/// installed wrappers, with `GIT_SMEE_SHOW_COMMANDS=1`; progress events go to
+/// `--events-socket` or `GIT_SMEE_EVENTS_SOCKET` the same way.
+pub(crate) fn executor_config(
+    show_commands: bool,
+    events_socket: Option<&Path>,
+) -> executor::ExecutorConfig {
+    let executor_config = executor::ExecutorConfig::new()
+        .with_show_commands(show_commands || env_flag_enabled(SHOW_COMMANDS_ENV))
+        .with_stdout_terminal(io::stdout().is_terminal())
+        .with_deduplication();
+    let executor_config = match user_opt_out() {
+        Some(opt_out) => executor_config.with_opt_out(opt_out),
+        None => executor_config,
+    };
+    match connect_event_sink(events_socket) {
+        Some(events) => executor_config.with_event_sink(events),
+        None => executor_config,
+    }
+}
+
+/// The user's opt-out list. CI runs every command of the shared config, and a
+/// broken list is reported rather than allowed to keep hooks from running.
+fn user_opt_out() -> Option<OptOutList> {
+    if env_flag_enabled("CI") {
+        return None;
+    }
+    OptOutList::load_user_list().unwrap_or_else(|error| {
+        eprintln!("git-smee: warning: {error}; running every hook command");
+        None
+    })
+}
+
+/// A client that is not listening must not keep hooks from running, so a
+/// failed connection is reported and the run goes on without events.
+fn connect_event_sink(events_socket: Option<&Path>) -> Option<Arc<EventSink>> {
+    let path = match events_socket {
+        Some(path) => path.to_path_buf(),
+        None => {
+            PathBuf::from(env::var_os(executor::EVENTS_SOCKET_ENV).filter(|path| !path.is_empty())?)
+        }
+    };
+    match EventSink::connect(&path) {
+        Ok(events) => Some(Arc::new(events)),
+        Err(error) => {
+            eprintln!(
+                "git-smee: warning: cannot send progress events to {}: {error}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+/// Whether a boolean env switch such as `GIT_SMEE_SHOW_COMMANDS` is turned on.
+pub(crate) fn env_flag_enabled(key: &str) -> bool {
+    env::var(key).is_ok_and(|value| {
+        matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// Routes Ctrl-C (and `SIGTERM` sent to git-smee) to the executor so running
+/// hooks get a `SIGTERM` and their grace period instead of vanishing mid-run.
+pub(crate) fn install_cancellation_handler() {
+    // A handler can only be installed once per process; a second attempt is
+    // harmless and leaves the first one in place.
+    let _ = ctrlc::set_handler(executor::request_cancellation);
+}
+
+/// A single phase to execute as part of a [`run_phase_sequence`] call.
+pub(crate) struct PhaseInvocation {
+    pub(crate) phase: LifeCyclePhase,
+    pub(crate) hook_args: Vec<String>,
+}
+
+/// Runs phases in order, printing each phase summary and, for more than one
+/// phase, a combined run summary. Stops at the first failing phase.
+pub(crate) fn run_phase_sequence(
+    config: &SmeeConfig,
+    invocations: &[PhaseInvocation],
+    stdin_payload: Option<&[u8]>,
+    executor_config: &executor::ExecutorConfig,
+    journal: Option<&RunJournal>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let phases: Vec<_> = invocations
+        .iter()
+        .map(|invocation| invocation.phase)
+        .collect();
+    let events = executor_config.event_sink();
+    if let Some(events) = events {
+        events.emit(&Event::RunStarted {
+            run_id: journal.map(RunJournal::run_id),
+            phases: phases.iter().map(ToString::to_string).collect(),
+        });
+    }
+    let result = run_phases(config, invocations, stdin_payload, executor_config, journal);
+    if let Some(events) = events {
+        events.emit(&Event::RunFinished {
+            success: result.is_ok(),
+        });
+    }
+    result
+}
+
+fn run_phases(
+    config: &SmeeConfig,
+    invocations: &[PhaseInvocation],
+    stdin_payload: Option<&[u8]>,
+    executor_config: &executor::ExecutorConfig,
+    journal: Option<&RunJournal>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let phases: Vec<_> = invocations
+        .iter()
+        .map(|invocation| invocation.phase)
+        .collect();
+    let mut outcomes = Vec::with_capacity(invocations.len());
+    for invocation in invocations {
+        let stdin_payload = phase_stdin(stdin_payload, invocation.phase, phases.len());
+        let mut fix_offered = false;
+        let summary = loop {
+            let summary = executor::execute_hook_with_journal(
+                config,
+                invocation.phase,
+                &invocation.hook_args,
+                stdin_payload,
+                executor_config,
+                journal,
+            )?;
+            print_phase_summary(invocation.phase, &summary, executor_config.show_commands());
+            // Offer the fix once per phase so a fix that does not help cannot
+            // keep the user in a loop.
+            if summary.first_failure().is_some() && !fix_offered {
+                fix_offered = true;
+                if fix::offer_fix(config, invocation.phase, &summary, executor_config)? {
+                    continue;
+                }
+            }
+            break summary;
+        };
+        print_environment_snapshot(
+            config,
+            &config.phase_entries(invocation.phase).unwrap_or_default(),
+            &summary,
+        );
+        let error = summary.error();
+        outcomes.push((invocation.phase, error.is_none()));
+        if let Some(error) = error {
+            print_multi_phase_summary(&phases, &outcomes);
+            return Err(Box::new(error));
+        }
+    }
+    print_multi_phase_summary(&phases, &outcomes);
+    Ok(())
+}
+
+/// Prints a phase's summary; `verbose` adds each command's CPU time and peak
+/// RSS.
+pub(crate) fn print_phase_summary(
+    phase: impl std::fmt::Display,
+    summary: &executor::HookRunSummary,
+    verbose: bool,
+) {
+    let lines = if verbose {
+        summary.verbose_text_lines(&phase)
+    } else {
+        summary.text_lines(&phase)
+    };
+    for line in lines {
+        println!("{line}");
+    }
+    match summary.warning_count() {
+        0 => {}
+        1 => eprintln!(
+            "Warning: 1 {phase} hook command failed but does not block (severity = \"warning\")"
+        ),
+        count => eprintln!(
+            "Warning: {count} {phase} hook commands failed but do not block (severity = \"warning\")"
+        ),
+    }
+    if let Some(path) = summary.opt_out_file() {
+        eprintln!(
+            "git-smee: note: skipped {} {phase} hook command(s) you opted out of in {path}; CI still runs them",
+            summary.opted_out_count()
+        );
+    }
+    match summary.over_budget_count() {
+        0 => {}
+        count => eprintln!(
+            "Warning: {phase} used up its time budget (settings.{phase}.max_duration); {count} hook command(s) skipped"
+        ),
+    }
+}
+
+/// Prints the environment snapshot under a failed run's summary when
+/// `settings.env_snapshot` asks for it.
+pub(crate) fn print_environment_snapshot(
+    config: &SmeeConfig,
+    hooks: &[HookDefinition],
+    summary: &executor::HookRunSummary,
+) {
+    if !config.settings.env_snapshot || summary.first_failure().is_none() {
+        return;
+    }
+    for line in executor::environment_snapshot(hooks).text_lines() {
+        println!("{line}");
+    }
+}
+
+/// Resolves the `run` positionals before `--` into the phases to execute;
+/// `hook_args`, given after `--`, are forwarded to every phase. A hook
+/// argument that happens to name a phase is therefore never run as one.
+///
+/// Wrappers installed before they passed `--` call `run <phase> <args>...`.
+/// Only that exact shape is taken as such a call: nothing after `--`, a first
+/// phase Git passes arguments to, and a second positional that is not a phase
+/// name. Any other unknown name is rejected, so a mistyped phase is not
+/// silently forwarded as an argument.
+fn resolve_phases_and_hook_args<'a>(
+    phase_names: &'a [String],
+    hook_args: &'a [String],
+) -> Result<(Vec<LifeCyclePhase>, &'a [String]), git_smee_core::config::Error> {
+    let mut phases = Vec::with_capacity(phase_names.len());
+    for (index, name) in phase_names.iter().enumerate() {
+        match LifeCyclePhase::from_str(name) {
+            Ok(phase) => phases.push(phase),
+            Err(_) if index == 1 && hook_args.is_empty() && phases[0].accepts_args() => {
+                return Ok((phases, &phase_names[1..]));
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    Ok((phases, hook_args))
+}
+
+/// The stdin a phase receives. A single phase gets the payload as is; in a run
+/// of several phases only those Git feeds stdin to get it, so `pre-push`
+/// refs are not replayed into `pre-commit`.
+fn phase_stdin(
+    stdin_payload: Option<&[u8]>,
+    phase: LifeCyclePhase,
+    phase_count: usize,
+) -> Option<&[u8]> {
+    match stdin_payload {
+        Some(_) if phase_count > 1 && !phase.reads_stdin() => Some(&[]),
+        payload => payload,
+    }
+}
+
+fn print_multi_phase_summary(phases: &[LifeCyclePhase], outcomes: &[(LifeCyclePhase, bool)]) {
+    if phases.len() < 2 {
+        return;
+    }
+    let failed = outcomes.iter().filter(|(_, succeeded)| !succeeded).count();
+    println!(
+        "Run summary: {} phases, {} completed, {} skipped, {} failed",
+        phases.len(),
+        outcomes.len(),
+        phases.len() - outcomes.len(),
+        failed,
+    );
+    for (index, phase) in phases.iter().enumerate() {
+        let status = match outcomes.get(index) {
+            Some((_, true)) => "ok",
+            Some((_, false)) => "failed",
+            None => "skipped",
+        };
+        println!("  - {phase}: {status}");
+    }
+}
+
+/// The `pre-push` stdin for pushing the commits of `range`, so pipelines can
+/// run the hook definitions outside of a push.
+fn range_stdin(
+    phases: &[LifeCyclePhase],
+    range: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if !phases.contains(&LifeCyclePhase::PrePush) {
+        return Err(format!("--range {range}: only pre-push runs against a commit range").into());
+    }
+    let range = repository::resolve_commit_range(&env::current_dir()?, range)?;
+    Ok(range.pre_push_line().into_bytes())
+}
+
+fn read_hook_stdin_for_phases(phases: &[LifeCyclePhase]) -> io::Result<Option<Vec<u8>>> {
     // proc-receive is an interactive pkt-line protocol: Git waits for the hook to
     // answer before closing stdin, so buffering until EOF would deadlock before
     // the configured command is spawned. Let the command inherit stdin instead.
-    if phase == LifeCyclePhase::ProcReceive {
+    if phases.contains(&LifeCyclePhase::ProcReceive) {
         return Ok(None);
     }
     read_hook_stdin()
@@ -96,6 +684,83 @@ fn stdin_sentinel_read_limit(max_hook_stdin_bytes: u64) -> u64 {
 mod tests {
     use super::*;
 
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn positionals_before_double_dash_are_phases_and_args_after_it_are_forwarded() {
+        let phase_names = strings(&["pre-commit", "commit-msg"]);
+        let hook_args = strings(&["pre-push", ".git/COMMIT_EDITMSG"]);
+
+        let (phases, forwarded) = resolve_phases_and_hook_args(&phase_names, &hook_args).unwrap();
+
+        assert_eq!(
+            phases,
+            vec![LifeCyclePhase::PreCommit, LifeCyclePhase::CommitMsg]
+        );
+        assert_eq!(forwarded, hook_args.as_slice());
+    }
+
+    #[test]
+    fn given_args_after_double_dash_when_a_positional_is_not_a_phase_then_it_is_rejected() {
+        let phase_names = strings(&["pre-commit", "commit-mesg"]);
+
+        assert!(resolve_phases_and_hook_args(&phase_names, &strings(&["x"])).is_err());
+    }
+
+    #[test]
+    fn given_legacy_wrapper_call_without_double_dash_then_trailing_positionals_are_hook_args() {
+        let phase_names = strings(&["pre-push", "origin", "pre-commit"]);
+
+        let (phases, forwarded) = resolve_phases_and_hook_args(&phase_names, &[]).unwrap();
+
+        assert_eq!(phases, vec![LifeCyclePhase::PrePush]);
+        assert_eq!(forwarded, &phase_names[1..]);
+    }
+
+    #[test]
+    fn given_mistyped_phase_without_double_dash_then_it_is_rejected() {
+        for phase_names in [
+            strings(&["pre-commit", "commit-mesg"]),
+            strings(&["pre-commit", "commit-msg", "pre-pussh"]),
+            strings(&["pre-push", "pre-commit", "origin"]),
+        ] {
+            assert!(
+                matches!(
+                    resolve_phases_and_hook_args(&phase_names, &[]),
+                    Err(git_smee_core::config::Error::UnknownLifeCyclePhase(_))
+                ),
+                "{phase_names:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_first_phase_is_rejected() {
+        assert!(resolve_phases_and_hook_args(&strings(&["pre-commmit"]), &[]).is_err());
+        assert!(resolve_phases_and_hook_args(&strings(&["pre-commmit", "origin"]), &[]).is_err());
+    }
+
+    #[test]
+    fn given_several_phases_when_passing_stdin_then_only_stdin_phases_get_the_payload() {
+        let payload: &[u8] = b"refs/heads/main abc refs/heads/main def\n";
+
+        assert_eq!(
+            phase_stdin(Some(payload), LifeCyclePhase::PreCommit, 1),
+            Some(payload)
+        );
+        assert_eq!(
+            phase_stdin(Some(payload), LifeCyclePhase::PreCommit, 2),
+            Some(&[][..])
+        );
+        assert_eq!(
+            phase_stdin(Some(payload), LifeCyclePhase::PrePush, 2),
+            Some(payload)
+        );
+        assert_eq!(phase_stdin(None, LifeCyclePhase::PreCommit, 2), None);
+    }
+
     #[test]
     fn default_limit_uses_human_readable_display() {
         assert_eq!(
@@ -0,0 +1,343 @@
+//! `git smee serve`: a long-running JSON-RPC 2.0 server on stdin/stdout for
+//! editor integrations, so an extension can validate the config on every
+//! keystroke and start or cancel runs without spawning a process each time.
+//!
+//! Messages are single-line JSON objects separated by newlines. Runs execute
+//! in a child `git smee run` so their hooks' output goes to stderr instead of
+//! corrupting the protocol stream, and so a `cancel` can stop one run the way
+//! Ctrl-C would without affecting the others.
+
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use git_smee_core::{
+    SmeeConfig,
+    config::LifeCyclePhase,
+    executor::{self, Verdict},
+    repository,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::config_path::read_config_file;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Server-defined error for requests that were well-formed but failed.
+const REQUEST_FAILED: i64 = -32000;
+
+const RUN_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.into(),
+        }
+    }
+
+    fn failed(error: impl ToString) -> Self {
+        Self {
+            code: REQUEST_FAILED,
+            message: error.to_string(),
+        }
+    }
+}
+
+type Runs = Arc<Mutex<HashMap<String, Arc<Mutex<Child>>>>>;
+
+struct Server {
+    config_path: PathBuf,
+    repository_root: PathBuf,
+    output: Arc<Mutex<io::Stdout>>,
+    runs: Runs,
+    /// Threads that respond to `run` requests once their run ends.
+    responders: Mutex<Vec<JoinHandle<()>>>,
+}
+
+pub(crate) fn run_serve(config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let server = Server {
+        config_path: config_path.to_path_buf(),
+        repository_root: repository::find_git_root()?,
+        output: Arc::new(Mutex::new(io::stdout())),
+        runs: Arc::default(),
+        responders: Mutex::default(),
+    };
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !server.handle(&line) {
+            return Ok(());
+        }
+    }
+    // Without a shutdown request, runs still in progress finish and get their
+    // response, so piping a fixed list of requests works.
+    server.wait_for_runs();
+    Ok(())
+}
+
+impl Server {
+    /// Handles one message. Returns `false` once the client asked the server
+    /// to shut down.
+    fn handle(&self, line: &str) -> bool {
+        let request = match serde_json::from_str::<Value>(line) {
+            Err(error) => {
+                self.respond_error(Value::Null, PARSE_ERROR, error.to_string());
+                return true;
+            }
+            Ok(message) => match Request::deserialize(&message) {
+                Ok(request) => request,
+                Err(error) => {
+                    let id = message.get("id").cloned().unwrap_or(Value::Null);
+                    self.respond_error(id, INVALID_REQUEST, error.to_string());
+                    return true;
+                }
+            },
+        };
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            "validate" => self.validate(&request.params),
+            "plan" => self.plan(&request.params),
+            "run" => match &id {
+                // The response is sent when the run ends.
+                Some(id) => match self.start_run(id.clone(), &request.params) {
+                    Ok(()) => return true,
+                    Err(error) => Err(error),
+                },
+                None => Err(RpcError::invalid_params("run must be a request with an id")),
+            },
+            "cancel" => self.cancel(&request.params),
+            "shutdown" => {
+                self.stop_all_runs();
+                self.wait_for_runs();
+                if let Some(id) = id {
+                    self.respond(id, Value::Null);
+                }
+                return false;
+            }
+            method => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("unknown method '{method}'"),
+            }),
+        };
+        // Notifications get no response, not even for errors.
+        if let Some(id) = id {
+            match result {
+                Ok(value) => self.respond(id, value),
+                Err(error) => self.respond_error(id, error.code, error.message),
+            }
+        }
+        true
+    }
+
+    /// `validate {text?}`: checks the config file, or `text` in its place.
+    fn validate(&self, params: &Value) -> Result<Value, RpcError> {
+        let parsed = match params.get("text") {
+            Some(Value::String(text)) => SmeeConfig::from_toml_str(text),
+            Some(_) => return Err(RpcError::invalid_params("text must be a string")),
+            None => read_config_file(&self.config_path),
+        };
+        Ok(match parsed {
+            Ok(config) => json!({
+                "valid": true,
+                "warnings": config
+                    .warnings()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
+            }),
+            Err(error) => json!({
+                "valid": false,
+                "error": error.to_string(),
+                "warnings": [],
+            }),
+        })
+    }
+
+    /// `plan {phase}`: what a run of `phase` would do, as `git smee why`.
+    fn plan(&self, params: &Value) -> Result<Value, RpcError> {
+        let phase = phase_param(params)?;
+        let config = read_config_file(&self.config_path).map_err(RpcError::failed)?;
+        let explanation = executor::explain_phase(&config, phase, &self.repository_root)
+            .map_err(RpcError::failed)?;
+        Ok(json!({
+            "phase": phase.as_str(),
+            "phase_verdict": explanation.phase_verdict.as_ref().map(verdict_json),
+            "commands": explanation
+                .commands
+                .iter()
+                .map(|command| {
+                    json!({
+                        "label": command.label,
+                        "command": command.command,
+                        "verdict": verdict_json(&command.verdict),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        }))
+    }
+
+    /// `run {phase, args?}`: runs `phase` in a child `git smee run` and
+    /// responds with its result once it ends.
+    fn start_run(&self, id: Value, params: &Value) -> Result<(), RpcError> {
+        let phase = phase_param(params)?;
+        let args: Vec<String> = match params.get("args") {
+            None => Vec::new(),
+            Some(args) => serde_json::from_value(args.clone())
+                .map_err(|_| RpcError::invalid_params("args must be an array of strings"))?,
+        };
+        let key = id.to_string();
+        if lock(&self.runs).contains_key(&key) {
+            return Err(RpcError::invalid_params(format!(
+                "a run with id {key} is already active"
+            )));
+        }
+        let child = Command::new(env::current_exe().map_err(RpcError::failed)?)
+            .arg("--config")
+            .arg(&self.config_path)
+            .arg("run")
+            .arg(phase.as_str())
+            .arg("--")
+            .args(&args)
+            .current_dir(&self.repository_root)
+            .stdin(Stdio::null())
+            .stdout(io::stderr())
+            .spawn()
+            .map_err(RpcError::failed)?;
+        let child = Arc::new(Mutex::new(child));
+        lock(&self.runs).insert(key.clone(), Arc::clone(&child));
+
+        let runs = Arc::clone(&self.runs);
+        let output = Arc::clone(&self.output);
+        let responder = thread::spawn(move || {
+            let status = loop {
+                match lock(&child).try_wait() {
+                    Ok(Some(status)) => break Ok(status),
+                    Ok(None) => thread::sleep(RUN_POLL_INTERVAL),
+                    Err(error) => break Err(error),
+                }
+            };
+            let cancelled = lock(&runs).remove(&key).is_none();
+            let message = match status {
+                Ok(status) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": {
+                        "success": status.success(),
+                        "exit_code": status.code(),
+                        "cancelled": cancelled,
+                    },
+                }),
+                Err(error) => error_message(id, REQUEST_FAILED, error.to_string()),
+            };
+            send(&output, &message);
+        });
+        lock(&self.responders).push(responder);
+        Ok(())
+    }
+
+    /// `cancel {id}`: stops the run started by the request with `id`.
+    fn cancel(&self, params: &Value) -> Result<Value, RpcError> {
+        let id = params
+            .get("id")
+            .ok_or_else(|| RpcError::invalid_params("cancel needs the id of a run request"))?;
+        // Removing the run first marks its response as cancelled.
+        let Some(child) = lock(&self.runs).remove(&id.to_string()) else {
+            return Ok(json!({ "cancelled": false }));
+        };
+        executor::request_child_stop(&mut lock(&child)).map_err(RpcError::failed)?;
+        Ok(json!({ "cancelled": true }))
+    }
+
+    /// Stops every active run; their responses report them as cancelled.
+    fn stop_all_runs(&self) {
+        let runs: Vec<_> = lock(&self.runs).drain().map(|(_, child)| child).collect();
+        for child in runs {
+            let _ = executor::request_child_stop(&mut lock(&child));
+        }
+    }
+
+    fn wait_for_runs(&self) {
+        let responders: Vec<_> = lock(&self.responders).drain(..).collect();
+        for responder in responders {
+            let _ = responder.join();
+        }
+    }
+
+    fn respond(&self, id: Value, result: Value) {
+        send(
+            &self.output,
+            &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        );
+    }
+
+    fn respond_error(&self, id: Value, code: i64, message: String) {
+        send(&self.output, &error_message(id, code, message));
+    }
+}
+
+fn phase_param(params: &Value) -> Result<LifeCyclePhase, RpcError> {
+    let phase = params
+        .get("phase")
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::invalid_params("phase must be a string"))?;
+    LifeCyclePhase::from_str(phase).map_err(|error| RpcError::invalid_params(error.to_string()))
+}
+
+fn verdict_json(verdict: &Verdict) -> Value {
+    match verdict {
+        Verdict::Run { note } => json!({ "kind": "run", "note": note }),
+        Verdict::Skip { reason } => json!({ "kind": "skip", "reason": reason }),
+        Verdict::Fail { reason } => json!({ "kind": "fail", "reason": reason }),
+    }
+}
+
+fn error_message(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Writes `message` as one line. A client that closed stdout cannot be told
+/// anything anymore, so write errors are ignored.
+fn send(output: &Mutex<io::Stdout>, message: &Value) {
+    let mut output = lock(output);
+    let _ = writeln!(output, "{message}").and_then(|()| output.flush());
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
@@ -0,0 +1,128 @@
+use std::{fs, path::Path};
+
+use git_smee_core::{SmeeConfig, config::LifeCyclePhase, repository};
+
+use crate::{
+    commands::{
+        run::{self, PhaseInvocation, run_phase_sequence},
+        trust::ensure_snapshot_trusted,
+    },
+    config_path::{read_config_snapshot, warn_if_config_changed},
+};
+
+/// Runs the commit-time hook phases the way `git commit -m <message>` would,
+/// without creating a commit.
+///
+/// The message is written to a temporary file that stands in for
+/// `.git/COMMIT_EDITMSG`, so the real file is never touched. Hooks see the
+/// current index as the staged set. Phases without configured hooks are skipped,
+/// matching Git's behavior for missing hook files.
+pub(crate) fn run_simulate_commit(
+    config_path: &Path,
+    message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    run::install_cancellation_handler();
+    let snapshot = read_config_snapshot(config_path)?;
+    ensure_snapshot_trusted(&snapshot, false)?;
+    let config = snapshot.config();
+    let message_file = tempfile::Builder::new()
+        .prefix("git-smee-COMMIT_EDITMSG-")
+        .tempfile()?;
+    fs::write(message_file.path(), commit_message_content(message))?;
+    let message_path = message_file.path().to_string_lossy().to_string();
+
+    let invocations = commit_invocations(config, &message_path);
+    if invocations.is_empty() {
+        println!("No commit hooks configured; nothing to simulate.");
+        return Ok(());
+    }
+
+    println!("Simulating commit hooks (no commit will be created)...");
+    let result = run_phase_sequence(
+        config,
+        &invocations,
+        None,
+        &run::executor_config(false, None),
+        None,
+    );
+    warn_if_config_changed(&snapshot);
+    if result.is_ok() {
+        println!("Simulated commit message:");
+        print!("{}", fs::read_to_string(message_file.path())?);
+    }
+    result
+}
+
+fn commit_invocations(config: &SmeeConfig, message_path: &str) -> Vec<PhaseInvocation> {
+    [
+        (LifeCyclePhase::PreCommit, vec![]),
+        (
+            LifeCyclePhase::PrepareCommitMsg,
+            vec![message_path.to_string(), "message".to_string()],
+        ),
+        (LifeCyclePhase::CommitMsg, vec![message_path.to_string()]),
+    ]
+    .into_iter()
+    .filter(|(phase, _)| config.hooks.contains_key(phase))
+    .map(|(phase, hook_args)| PhaseInvocation { phase, hook_args })
+    .collect()
+}
+
+fn commit_message_content(message: &str) -> String {
+    if message.ends_with('\n') {
+        message.to_string()
+    } else {
+        format!("{message}\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use git_smee_core::config::HookDefinition;
+
+    use super::*;
+
+    #[test]
+    fn commit_invocations_follow_git_order_and_skip_unconfigured_phases() {
+        let mut hooks = HashMap::new();
+        for phase in [LifeCyclePhase::CommitMsg, LifeCyclePhase::PreCommit] {
+            hooks.insert(
+                phase,
+                vec![HookDefinition {
+                    command: "true".to_string(),
+                    parallel_execution_allowed: false,
+                    ..Default::default()
+                }],
+            );
+        }
+        let config = SmeeConfig {
+            hooks,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
+
+        let invocations = commit_invocations(&config, "/tmp/msg");
+
+        assert_eq!(
+            invocations
+                .iter()
+                .map(|invocation| (invocation.phase, invocation.hook_args.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (LifeCyclePhase::PreCommit, vec![]),
+                (LifeCyclePhase::CommitMsg, vec!["/tmp/msg".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn commit_message_content_ends_with_newline() {
+        assert_eq!(commit_message_content("fix"), "fix\n");
+        assert_eq!(commit_message_content("fix\n"), "fix\n");
+    }
+}
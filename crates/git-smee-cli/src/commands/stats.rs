@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+use git_smee_core::{
+    executor::{self, ANALYTICS_KEY, HookStatistics, METRICS_FILE_KEY},
+    repository,
+};
+
+/// Output formats of `git smee stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum StatsFormat {
+    /// One line per hook with run counts and durations.
+    #[default]
+    Text,
+    /// Stable, pretty-printed JSON for tooling.
+    Json,
+    /// Prometheus text exposition format, for scraping.
+    Prometheus,
+}
+
+/// `git smee stats`: aggregates the analytics store into per-hook run
+/// counts, failures, and durations.
+pub(crate) fn run_stats(format: StatsFormat) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let repository_root = repository::find_git_root()?;
+    let analytics_log = executor::analytics_log(&repository_root)?;
+    let stats = HookStatistics::load(analytics_log.path())?;
+    if stats.skipped_lines > 0 {
+        eprintln!(
+            "git-smee: warning: skipped {} unreadable line(s) in {}",
+            stats.skipped_lines,
+            analytics_log.path().display()
+        );
+    }
+    match format {
+        StatsFormat::Prometheus => print!("{}", stats.to_prometheus()),
+        StatsFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+        StatsFormat::Text if stats.hooks.is_empty() => println!(
+            "No hook runs recorded in {}. Record runs with `git config {ANALYTICS_KEY} true` or `git config {METRICS_FILE_KEY} <path>`.",
+            analytics_log.path().display()
+        ),
+        StatsFormat::Text => {
+            let rows = stats
+                .hooks
+                .iter()
+                .map(|hook| {
+                    [
+                        hook.phase.clone(),
+                        hook.hook.clone(),
+                        hook.runs().to_string(),
+                        hook.failures.to_string(),
+                        format_duration(hook.mean_duration()),
+                        format_duration(hook.max_duration),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            let header = ["phase", "hook", "runs", "failures", "mean", "max"].map(String::from);
+            let widths: Vec<usize> = (0..header.len())
+                .map(|column| {
+                    std::iter::once(&header)
+                        .chain(&rows)
+                        .map(|row| row[column].chars().count())
+                        .max()
+                        .unwrap_or_default()
+                })
+                .collect();
+            for row in std::iter::once(&header).chain(&rows) {
+                let line = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{cell:<width$}"))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                println!("{}", line.trim_end());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `git smee stats reset`: deletes the analytics store.
+pub(crate) fn run_stats_reset() -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let repository_root = repository::find_git_root()?;
+    let analytics_log = executor::analytics_log(&repository_root)?;
+    match executor::reset_analytics(&repository_root)? {
+        0 => println!(
+            "No hook runs recorded in {}",
+            analytics_log.path().display()
+        ),
+        removed => println!(
+            "Removed {removed} recorded run(s) from {}",
+            analytics_log.path().display()
+        ),
+    }
+    Ok(())
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration.as_secs() > 0 {
+        format!("{:.2}s", duration.as_secs_f64())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
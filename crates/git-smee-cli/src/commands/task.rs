@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use git_smee_core::{executor, repository};
+
+use crate::{
+    commands::{
+        run::{
+            executor_config, install_cancellation_handler, print_environment_snapshot,
+            print_phase_summary,
+        },
+        trust::ensure_snapshot_trusted,
+    },
+    config_path::{read_config_snapshot, warn_if_config_changed},
+};
+
+/// `git smee task <name>`: runs a `[tasks]` entry. Without a name, lists the
+/// configured tasks.
+pub(crate) fn run_task(
+    config_path: &Path,
+    task: Option<&str>,
+    task_args: &[String],
+    show_commands: bool,
+    trust: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let snapshot = read_config_snapshot(config_path)?;
+    let config = snapshot.config();
+    let Some(task) = task else {
+        if config.tasks.is_empty() {
+            println!(
+                "No tasks configured; add a [[tasks.<name>]] entry to {}",
+                config_path.display()
+            );
+        }
+        for (name, hooks) in &config.tasks {
+            match hooks.len() {
+                1 => println!("{name} (1 command)"),
+                count => println!("{name} ({count} commands)"),
+            }
+        }
+        return Ok(());
+    };
+    ensure_snapshot_trusted(&snapshot, trust)?;
+    install_cancellation_handler();
+    let executor_config = executor_config(show_commands, None);
+    let summary = executor::execute_task(config, task, task_args, &executor_config);
+    warn_if_config_changed(&snapshot);
+    let summary = summary?;
+    print_phase_summary(
+        format!("task {task}"),
+        &summary,
+        executor_config.show_commands(),
+    );
+    print_environment_snapshot(
+        config,
+        &config.task_entries(task).unwrap_or_default(),
+        &summary,
+    );
+    match summary.error() {
+        Some(error) => Err(Box::new(error)),
+        None => Ok(()),
+    }
+}
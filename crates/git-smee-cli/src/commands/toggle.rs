@@ -0,0 +1,59 @@
+use std::str::FromStr;
+
+use git_smee_core::{
+    config::LifeCyclePhase,
+    repository,
+    toggle::{self, ToggleScope},
+};
+
+pub(crate) fn run_enable(phase: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let scope = parse_scope(phase)?;
+    let repository_root = repository::find_git_root()?;
+    toggle::enable(&repository_root, scope)?;
+    println!("{}", describe(scope, "enabled"));
+    Ok(())
+}
+
+pub(crate) fn run_disable(phase: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let scope = parse_scope(phase)?;
+    let repository_root = repository::find_git_root()?;
+    toggle::disable(&repository_root, scope)?;
+    println!("{}", describe(scope, "disabled"));
+    Ok(())
+}
+
+fn parse_scope(phase: Option<&str>) -> Result<ToggleScope, Box<dyn std::error::Error>> {
+    match phase {
+        None => Ok(ToggleScope::AllPhases),
+        Some(phase) => Ok(ToggleScope::Phase(LifeCyclePhase::from_str(phase)?)),
+    }
+}
+
+fn describe(scope: ToggleScope, state: &str) -> String {
+    match scope {
+        ToggleScope::AllPhases => format!("git-smee hooks {state} for this repository"),
+        ToggleScope::Phase(phase) => format!("git-smee {phase} hooks {state} for this repository"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_unknown_phase_when_parsing_scope_then_error_is_returned() {
+        assert!(parse_scope(Some("pre-nothing")).is_err());
+    }
+
+    #[test]
+    fn given_phase_scope_when_describing_then_phase_is_named() {
+        assert_eq!(
+            describe(ToggleScope::Phase(LifeCyclePhase::PrePush), "disabled"),
+            "git-smee pre-push hooks disabled for this repository"
+        );
+        assert_eq!(
+            describe(ToggleScope::AllPhases, "enabled"),
+            "git-smee hooks enabled for this repository"
+        );
+    }
+}
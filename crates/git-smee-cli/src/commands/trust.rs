@@ -0,0 +1,88 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use git_smee_core::{
+    config::ConfigSnapshot,
+    trust::{self, TrustState, TrustStore},
+};
+
+use crate::{commands::run::env_flag_enabled, prompt};
+
+/// Skips the trust check entirely, e.g. on CI machines that run untrusted
+/// pull requests in throwaway sandboxes anyway. `CI=true` has the same effect.
+const TRUST_ALL_ENV: &str = "GIT_SMEE_TRUST_ALL";
+
+/// `git smee trust`: records the current contents of the config as trusted.
+pub(crate) fn run_trust(config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let (config_path, contents) = read_config_for_trust(config_path)?;
+    let digest = trust::config_digest(&contents);
+    TrustStore::at_default_location()?.trust(&config_path, &digest)?;
+    println!("Trusted {} (sha256 {digest})", config_path.display());
+    Ok(())
+}
+
+/// Refuses to continue unless the config a run read is trusted. The digest
+/// covers exactly the bytes the run parsed, so the file cannot change between
+/// the check and the run.
+///
+/// Untrusted configs are trusted on the spot with `--trust` or after the user
+/// confirms an interactive prompt on the terminal; otherwise the run fails with
+/// [`trust::Error::Untrusted`].
+pub(crate) fn ensure_snapshot_trusted(
+    snapshot: &ConfigSnapshot,
+    trust_flag: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if env_flag_enabled(TRUST_ALL_ENV) || env_flag_enabled("CI") {
+        return Ok(());
+    }
+    let config_path = fs::canonicalize(snapshot.path())?;
+    ensure_contents_trusted(&config_path, snapshot.contents(), trust_flag)
+}
+
+fn ensure_contents_trusted(
+    config_path: &Path,
+    contents: &[u8],
+    trust_flag: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let digest = trust::config_digest(contents);
+    let store = TrustStore::at_default_location()?;
+    let state = store.state(config_path, &digest)?;
+    if state == TrustState::Trusted {
+        return Ok(());
+    }
+    let reason = match state {
+        TrustState::Changed { .. } => "changed since it was last trusted",
+        _ => "not trusted on this machine yet",
+    };
+    if trust_flag || prompt_for_trust(config_path, reason, contents)? {
+        store.trust(config_path, &digest)?;
+        return Ok(());
+    }
+    Err(trust::Error::Untrusted {
+        config_path: config_path.display().to_string(),
+        reason: reason.to_string(),
+    }
+    .into())
+}
+
+fn read_config_for_trust(config_path: &Path) -> Result<(PathBuf, Vec<u8>), io::Error> {
+    let config_path = fs::canonicalize(config_path)?;
+    let contents = fs::read(&config_path)?;
+    Ok((config_path, contents))
+}
+
+/// Shows the config and asks whether to trust it.
+fn prompt_for_trust(config_path: &Path, reason: &str, contents: &[u8]) -> io::Result<bool> {
+    let mut context = vec![
+        format!("git-smee: {} is {reason}.", config_path.display()),
+        "Its hooks run these commands on your machine. Review them before trusting it:".to_string(),
+    ];
+    context.extend(
+        String::from_utf8_lossy(contents)
+            .lines()
+            .map(|line| format!("  | {line}")),
+    );
+    prompt::confirm(&context, "Trust this config and run its hooks?")
+}
@@ -0,0 +1,49 @@
+use std::path::{Path, PathBuf};
+
+use git_smee_core::{installer, repository};
+
+use crate::config_path::read_config_file;
+
+/// `git smee uninstall`: removes the hook wrappers git-smee installed and
+/// leaves every other hook file in place.
+pub(crate) fn run_uninstall(config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    // Without a readable config the hooks are looked for in Git's effective
+    // hooks directory, which is where a custom `hooks_path` points anyway.
+    let hooks_path = read_config_file(config_path)
+        .ok()
+        .and_then(|config| config.settings.hooks_path);
+    let installer = installer::FileSystemHookInstaller::from_path_with_hooks_path(
+        PathBuf::from("./"),
+        hooks_path.as_deref(),
+        false,
+    )?;
+    let hooks_dir = installer.effective_hooks_dir().display().to_string();
+    let removed = installer::uninstall_hooks(&installer)?;
+    if removed.is_empty() {
+        println!("No git-smee hooks installed in {hooks_dir}");
+        return Ok(());
+    }
+    for path in &removed {
+        println!("Removed {}", path.display());
+        // The wrapper's path is only taken again by a chained original that
+        // uninstalling moved back.
+        if path.exists() {
+            println!(
+                "Restored {} from {}",
+                path.display(),
+                installer::chained_hook_path(path).display()
+            );
+        }
+    }
+    println!("Hooks uninstalled successfully.");
+    if let Some(core_hooks_path) =
+        repository::git_config_path(&repository::find_git_root()?, "core.hooksPath")?
+    {
+        eprintln!(
+            "git-smee: note: core.hooksPath still points at {}; run `git config --unset core.hooksPath` to use Git's default hooks directory again",
+            core_hooks_path.display()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::{fs, path::Path};
+
+use clap::ValueEnum;
+use git_smee_core::{
+    config::{
+        diagnostics::{self, Diagnostic, DiagnosticSeverity},
+        owners,
+    },
+    redaction::redact_secret_values,
+};
+use serde_json::json;
+
+use crate::{commands::diff_config::configs_since, output::print_error};
+
+/// Exit status of `git smee validate` when the config has no problems, or
+/// only warnings without `--strict`.
+pub(crate) const VALIDATE_CLEAN: i32 = 0;
+/// Exit status of `git smee validate` when the config has problems.
+pub(crate) const VALIDATE_PROBLEMS: i32 = 1;
+/// Exit status of `git smee validate` when the config could not be read.
+pub(crate) const VALIDATE_ERROR: i32 = 2;
+
+/// Output formats of `git smee validate`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ValidateFormat {
+    /// One line per problem, then a count.
+    #[default]
+    Text,
+    /// Stable, pretty-printed JSON for tooling.
+    Json,
+}
+
+/// `git smee validate`: reports every problem in the config and returns the
+/// exit status to end with. With `since`, changed commands without an owner
+/// are reported as well.
+pub(crate) fn run_validate(
+    config_path: &Path,
+    format: ValidateFormat,
+    strict: bool,
+    since: Option<&str>,
+) -> i32 {
+    match validate(config_path, since) {
+        Ok(diagnostics) => {
+            let errors = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Error)
+                .count();
+            let warnings = diagnostics.len() - errors;
+            let valid = errors == 0 && (!strict || warnings == 0);
+            match format {
+                ValidateFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&json!({
+                        "config_path": config_path.display().to_string(),
+                        "valid": valid,
+                        "errors": errors,
+                        "warnings": warnings,
+                        "diagnostics": diagnostics,
+                    }))
+                    .unwrap_or_default()
+                ),
+                ValidateFormat::Text => {
+                    for diagnostic in &diagnostics {
+                        println!("{}", redact_secret_values(&diagnostic.to_string()));
+                    }
+                    if diagnostics.is_empty() {
+                        println!("{} is valid", config_path.display());
+                    } else {
+                        println!(
+                            "{}: {errors} error(s), {warnings} warning(s)",
+                            config_path.display()
+                        );
+                    }
+                }
+            }
+            if valid {
+                VALIDATE_CLEAN
+            } else {
+                VALIDATE_PROBLEMS
+            }
+        }
+        Err(error) => {
+            print_error(error.as_ref(), config_path);
+            VALIDATE_ERROR
+        }
+    }
+}
+
+fn validate(
+    config_path: &Path,
+    since: Option<&str>,
+) -> Result<Vec<Diagnostic>, Box<dyn std::error::Error>> {
+    let source = fs::read_to_string(config_path)
+        .map_err(|error| format!("cannot read {}: {error}", config_path.display()))?;
+    let mut diagnostics = diagnostics::diagnose(&source);
+    let has_errors = diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == DiagnosticSeverity::Error);
+    if let Some(rev) = since
+        && !has_errors
+    {
+        let (_, old, new) = configs_since(config_path, rev)?;
+        for request in owners::review_requests(&old, &new)? {
+            if request.owners.is_empty() {
+                diagnostics.push(Diagnostic::warning(
+                    "unowned-change",
+                    format!(
+                        "{} since {rev}, and no owner reviews it",
+                        request
+                            .change
+                            .to_string()
+                            .trim_start_matches(['+', '-', '~', ' '])
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(diagnostics)
+}
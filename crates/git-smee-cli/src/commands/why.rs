@@ -0,0 +1,114 @@
+use std::{path::Path, str::FromStr};
+
+use git_smee_core::{
+    config::LifeCyclePhase,
+    executor::{self, PhaseExplanation, Verdict},
+    installer::FileSystemHookInstaller,
+    repository,
+};
+
+use crate::{
+    config_path::read_config_file,
+    diagnostics::{ExpectedHookScript, HookInspectionState, inspect_hook},
+};
+
+/// `git smee why <phase>`: explains, per command, whether a run of `phase`
+/// would start it right now and, if not, which setting or repository state
+/// prevents it. Nothing is run.
+pub(crate) fn run_why(config_path: &Path, phase: &str) -> Result<(), Box<dyn std::error::Error>> {
+    repository::ensure_in_repo_root()?;
+    let phase = LifeCyclePhase::from_str(phase)?;
+    let config = read_config_file(config_path)?;
+    let repository_root = repository::find_git_root()?;
+    let explanation = executor::explain_phase(&config, phase, &repository_root)?;
+
+    println!("{}", headline(&explanation));
+    let trigger = if config.settings.never_install.contains(&phase) {
+        format!(
+            "never triggers {phase}: it is listed in settings.never_install (git smee run {phase} still runs it)"
+        )
+    } else {
+        git_trigger(config_path, &repository_root, phase)?
+    };
+    println!("  git: {trigger}");
+    for command in &explanation.commands {
+        let verdict = explanation
+            .phase_verdict
+            .as_ref()
+            .unwrap_or(&command.verdict);
+        println!(
+            "  - {} ({}): {}",
+            command.label,
+            command.command,
+            verdict_text(verdict)
+        );
+    }
+    Ok(())
+}
+
+fn headline(explanation: &PhaseExplanation) -> String {
+    let phase = explanation.phase;
+    match &explanation.phase_verdict {
+        Some(verdict) => format!("{phase}: {}", verdict_text(verdict)),
+        None => {
+            let running = explanation
+                .commands
+                .iter()
+                .filter(|command| matches!(command.verdict, Verdict::Run { .. }))
+                .count();
+            format!(
+                "{phase}: {running} of {} command(s) would run",
+                explanation.commands.len()
+            )
+        }
+    }
+}
+
+fn verdict_text(verdict: &Verdict) -> String {
+    match verdict {
+        Verdict::Run { note: None } => "runs".to_string(),
+        Verdict::Run { note: Some(note) } => format!("runs; {note}"),
+        Verdict::Skip { reason } => format!("skipped: {reason}"),
+        Verdict::Fail { reason } => format!("fails: {reason}"),
+    }
+}
+
+/// Whether Git hands `phase` to git-smee, judged from the wrapper in the
+/// effective hooks directory.
+fn git_trigger(
+    config_path: &Path,
+    repository_root: &Path,
+    phase: LifeCyclePhase,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let hooks_dir =
+        repository::resolve_git_path(repository_root, FileSystemHookInstaller::HOOKS_GIT_PATH_KEY)?;
+    let inspection = inspect_hook(repository_root, &hooks_dir, phase);
+    let path = inspection.display_path();
+    Ok(match inspection.state() {
+        HookInspectionState::Missing => {
+            format!("does not trigger {phase}: no wrapper at {path} (run git smee install)")
+        }
+        HookInspectionState::InvalidPath => {
+            format!("cannot run {phase}: {path} is not a file")
+        }
+        HookInspectionState::Unmanaged => {
+            format!("runs the unmanaged hook at {path} instead of git-smee")
+        }
+        HookInspectionState::Unreadable { error } => {
+            format!("cannot read the hook at {path} ({error})")
+        }
+        HookInspectionState::Managed { content } => {
+            let stale_reasons =
+                ExpectedHookScript::from_current_process(config_path, repository_root)
+                    .stale_reasons(content);
+            if stale_reasons.is_empty() {
+                format!("triggers git-smee through {path}")
+            } else {
+                format!(
+                    "triggers git-smee through {path}, but the wrapper is stale ({}); run git smee install",
+                    stale_reasons.join(", ")
+                )
+            }
+        }
+    })
+}
@@ -6,7 +6,11 @@ use std::{
     str::FromStr,
 };
 
-use git_smee_core::{DEFAULT_CONFIG_FILE_NAME, SmeeConfig, config};
+use git_smee_core::{
+    DEFAULT_CONFIG_FILE_NAME, SmeeConfig,
+    config::{self, ConfigSnapshot},
+    shell_quote::sh_quote,
+};
 
 pub(crate) fn resolve_config_path(cli_config: Option<PathBuf>, invocation_dir: &Path) -> PathBuf {
     if let Some(path) = cli_config {
@@ -63,6 +67,22 @@ pub(crate) fn read_config_file(config_path: &Path) -> Result<SmeeConfig, config:
     config::SmeeConfig::try_from(config_path)
 }
 
+/// Reads the config once for a run; see [`ConfigSnapshot`].
+pub(crate) fn read_config_snapshot(config_path: &Path) -> Result<ConfigSnapshot, config::Error> {
+    ConfigSnapshot::read(config_path)
+}
+
+/// Warns when the config file was edited while a run executed from
+/// `snapshot`, since the run did not pick up the edit.
+pub(crate) fn warn_if_config_changed(snapshot: &ConfigSnapshot) {
+    if snapshot.changed_on_disk() {
+        eprintln!(
+            "git-smee: warning: {} changed while hooks were running; this run used the config as it was when the run started",
+            snapshot.path().display()
+        );
+    }
+}
+
 pub(crate) fn is_default_config_path(config_path: &Path, repository_root: &Path) -> bool {
     if config_path == Path::new(DEFAULT_CONFIG_FILE_NAME)
         || config_path == repository_root.join(DEFAULT_CONFIG_FILE_NAME)
@@ -81,6 +101,21 @@ pub(crate) fn is_default_config_path(config_path: &Path, repository_root: &Path)
     normalize_path_lexically(config_path) == normalize_path_lexically(&default_config_path)
 }
 
+/// The `git smee install` invocation for the config at `config_path`, as
+/// shown in hints.
+pub(crate) fn install_command(config_path: &Path) -> io::Result<String> {
+    Ok(
+        if is_default_config_path(config_path, &env::current_dir()?) {
+            "git smee install".to_string()
+        } else {
+            format!(
+                "git smee --config {} install",
+                sh_quote(&config_path.to_string_lossy())
+            )
+        },
+    )
+}
+
 fn normalize_path_lexically(path: &Path) -> PathBuf {
     let mut normalized = PathBuf::new();
     for component in path.components() {
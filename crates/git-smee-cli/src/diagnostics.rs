@@ -3,7 +3,10 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use git_smee_core::{config::LifeCyclePhase, installer};
+use git_smee_core::{
+    config::LifeCyclePhase,
+    installer::{self, HookScriptOptions},
+};
 
 use crate::config_path::normalize_config_path_for_hook_script;
 
@@ -40,10 +43,22 @@ impl ExpectedHookScript {
     }
 }
 
+/// Options `git smee install` run from this process would render wrappers
+/// with, to compare installed wrappers against.
+pub(crate) fn expected_hook_script_options(
+    config_path: &Path,
+    repository_root: &Path,
+) -> HookScriptOptions {
+    HookScriptOptions::new(
+        env::current_exe().unwrap_or_else(|_| PathBuf::from("git-smee")),
+        normalize_config_path_for_hook_script(config_path, repository_root)
+            .unwrap_or_else(|_| config_path.to_path_buf()),
+    )
+}
+
 #[derive(Debug)]
 pub(crate) struct HookInspection {
     phase: LifeCyclePhase,
-    path: PathBuf,
     display_path: String,
     state: HookInspectionState,
 }
@@ -53,10 +68,6 @@ impl HookInspection {
         self.phase
     }
 
-    pub(crate) fn path(&self) -> &Path {
-        &self.path
-    }
-
     pub(crate) fn display_path(&self) -> &str {
         &self.display_path
     }
@@ -103,7 +114,6 @@ pub(crate) fn inspect_hook(
 
     HookInspection {
         phase,
-        path,
         display_path,
         state,
     }
@@ -1,13 +1,21 @@
-use std::path::Path;
+use std::{env, path::Path};
 
-use git_smee_core::{installer, repository};
+use git_smee_core::{
+    ErrorCode, SmeeConfig,
+    config::LifeCyclePhase,
+    installer::{self, HookDriftKind, HooksPathConflict},
+    platform, repository,
+};
 use serde::Serialize;
 
 use crate::{
     config_path::read_config_file,
-    diagnostics::{ExpectedHookScript, HookInspectionState, inspect_hook},
+    diagnostics::{ExpectedHookScript, expected_hook_script_options},
+    output::OutputFormat,
 };
 
+const INSTALL_FIX: &str = "git smee install";
+
 #[derive(Debug, Serialize)]
 struct DoctorReport {
     status: DoctorStatus,
@@ -17,6 +25,7 @@ struct DoctorReport {
     ok: Vec<String>,
     warnings: Vec<String>,
     errors: Vec<String>,
+    checks: Vec<DoctorCheck>,
 }
 
 #[derive(Debug, Serialize)]
@@ -27,12 +36,77 @@ enum DoctorStatus {
     Error,
 }
 
-pub(crate) fn run_doctor(config_path: &Path, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+/// A single doctor finding with a stable identifier and, where one exists, the
+/// command that fixes it, so tooling can offer one-click remediation.
+#[derive(Debug, Serialize)]
+struct DoctorCheck {
+    id: &'static str,
+    level: DoctorStatus,
+    /// Error code of the failure class, for warnings and errors that have one.
+    code: Option<ErrorCode>,
+    phase: Option<String>,
+    message: String,
+    fix: Option<String>,
+}
+
+impl DoctorReport {
+    fn pass(&mut self, id: &'static str, phase: Option<LifeCyclePhase>, message: String) {
+        self.ok.push(message.clone());
+        self.push_check(id, DoctorStatus::Ok, None, phase, message, None);
+    }
+
+    fn warn(
+        &mut self,
+        id: &'static str,
+        code: Option<ErrorCode>,
+        phase: Option<LifeCyclePhase>,
+        message: String,
+        fix: Option<&str>,
+    ) {
+        self.warnings.push(with_code(&message, code));
+        self.push_check(id, DoctorStatus::Warning, code, phase, message, fix);
+    }
+
+    fn fail(
+        &mut self,
+        id: &'static str,
+        code: Option<ErrorCode>,
+        phase: Option<LifeCyclePhase>,
+        message: String,
+        fix: Option<&str>,
+    ) {
+        self.errors.push(with_code(&message, code));
+        self.push_check(id, DoctorStatus::Error, code, phase, message, fix);
+    }
+
+    fn push_check(
+        &mut self,
+        id: &'static str,
+        level: DoctorStatus,
+        code: Option<ErrorCode>,
+        phase: Option<LifeCyclePhase>,
+        message: String,
+        fix: Option<&str>,
+    ) {
+        self.checks.push(DoctorCheck {
+            id,
+            level,
+            code,
+            phase: phase.map(|phase| phase.to_string()),
+            message,
+            fix: fix.map(str::to_string),
+        });
+    }
+}
+
+pub(crate) fn run_doctor(
+    config_path: &Path,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
     let report = build_doctor_report(config_path);
-    if json {
-        println!("{}", serde_json::to_string_pretty(&report)?);
-    } else {
-        print_doctor_report(&report);
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Text => print_doctor_report(&report),
     }
     if report.errors.is_empty() {
         Ok(())
@@ -50,18 +124,26 @@ fn build_doctor_report(config_path: &Path) -> DoctorReport {
         ok: Vec::new(),
         warnings: Vec::new(),
         errors: Vec::new(),
+        checks: Vec::new(),
     };
 
     let repository_root = match repository::find_git_root() {
         Ok(root) => {
-            report.ok.push("inside a Git repository".to_string());
+            report.pass("repository", None, "inside a Git repository".to_string());
             report.repository_root = Some(root.display().to_string());
+            check_binary_on_path(&mut report);
             root
         }
         Err(error) => {
-            report.errors.push(format!(
-                "not inside a Git repository; run git smee doctor from a repository ({error})"
-            ));
+            report.fail(
+                "repository",
+                Some(ErrorCode::NotInRepository),
+                None,
+                format!(
+                    "not inside a Git repository; run git smee doctor from a repository ({error})"
+                ),
+                None,
+            );
             return finish_doctor_report(report);
         }
     };
@@ -73,97 +155,302 @@ fn build_doctor_report(config_path: &Path) -> DoctorReport {
         Ok(path) => {
             report.hooks_dir = Some(path.display().to_string());
             if path.exists() && path.is_dir() {
-                report
-                    .ok
-                    .push(format!("hooks directory exists at {}", path.display()));
+                report.pass(
+                    "hooks-directory",
+                    None,
+                    format!("hooks directory exists at {}", path.display()),
+                );
             } else if path.exists() {
-                report.errors.push(format!(
-                    "effective hooks path is not a directory: {}; fix core.hooksPath or remove the file",
-                    path.display()
-                ));
+                report.fail(
+                    "hooks-directory",
+                    Some(ErrorCode::InstallFailed),
+                    None,
+                    format!(
+                        "effective hooks path is not a directory: {}; fix core.hooksPath or remove the file",
+                        path.display()
+                    ),
+                    None,
+                );
             } else {
-                report.warnings.push(format!(
-                    "hooks directory does not exist yet at {}; run git smee install to create it",
-                    path.display()
-                ));
+                report.warn(
+                    "hooks-directory",
+                    Some(ErrorCode::InstallDrift),
+                    None,
+                    format!(
+                        "hooks directory does not exist yet at {}; run git smee install to create it",
+                        path.display()
+                    ),
+                    Some(INSTALL_FIX),
+                );
             }
             path
         }
         Err(error) => {
-            report.errors.push(format!(
-                "could not resolve effective hooks directory; check git core.hooksPath ({error})"
-            ));
+            report.fail(
+                "hooks-directory",
+                Some(error.code()),
+                None,
+                format!(
+                    "could not resolve effective hooks directory; check git core.hooksPath ({error})"
+                ),
+                None,
+            );
             return finish_doctor_report(report);
         }
     };
 
     let config = match read_config_file(config_path) {
         Ok(config) => {
-            report
-                .ok
-                .push(format!("config parses from {}", config_path.display()));
+            report.pass(
+                "config",
+                None,
+                format!("config parses from {}", config_path.display()),
+            );
             if config.hooks.is_empty() {
-                report.errors.push(
+                report.fail(
+                    "config-hooks",
+                    Some(ErrorCode::ConfigInvalid),
+                    None,
                     "configuration contains no hooks; add at least one [[hook-name]] entry"
                         .to_string(),
+                    None,
                 );
             } else {
-                report.ok.push(format!(
-                    "{} configured hook phase(s) are valid",
-                    config.hooks.len()
-                ));
+                report.pass(
+                    "config-hooks",
+                    None,
+                    format!("{} configured hook phase(s) are valid", config.hooks.len()),
+                );
+            }
+            for warning in config.warnings() {
+                report.warn(
+                    "config-ineffective",
+                    None,
+                    warning.phase,
+                    warning.message,
+                    None,
+                );
             }
             config
         }
         Err(error) => {
-            report.errors.push(format!(
-                "config problem at {}: {error}; run git smee init or fix the TOML file",
-                config_path.display()
-            ));
+            report.fail(
+                "config",
+                Some(error.code()),
+                None,
+                format!(
+                    "config problem at {}: {error}; run git smee init or fix the TOML file",
+                    config_path.display()
+                ),
+                (!config_path.exists()).then_some("git smee init"),
+            );
             return finish_doctor_report(report);
         }
     };
 
+    check_hooks_path(&mut report, &repository_root, &config);
+
     let expected_hook_script =
         ExpectedHookScript::from_current_process(config_path, &repository_root);
 
-    let mut phases: Vec<_> = config.hooks.keys().copied().collect();
-    phases.sort_by_key(|phase| phase.as_str());
-    for phase in phases {
-        let inspection = inspect_hook(&repository_root, &hooks_dir, phase);
-        match inspection.state() {
-            HookInspectionState::Missing => report.errors.push(format!(
-                "missing managed wrapper for {phase} at {}; run git smee install",
-                inspection.path().display()
-            )),
-            HookInspectionState::InvalidPath => report.errors.push(format!(
-                "hook path for {phase} is not a regular file: {}; remove it or fix core.hooksPath",
-                inspection.path().display()
-            )),
-            HookInspectionState::Unmanaged => report.errors.push(format!(
-                "unmanaged hook file blocks install for {phase} at {}; move it aside or run git smee install --force",
-                inspection.path().display()
-            )),
-            HookInspectionState::Unreadable { error } => report.errors.push(format!(
-                "cannot read hook wrapper for {phase} at {}: {error}",
-                inspection.path().display()
-            )),
-            HookInspectionState::Managed { content } => {
-                report
-                    .ok
-                    .push(format!("managed wrapper is installed for {phase}"));
-                for stale_reason in expected_hook_script.stale_reasons(content) {
-                    report.warnings.push(format!(
-                        "stale managed wrapper for {phase}: {stale_reason}; run git smee install"
-                    ));
+    let drift_report = installer::verify_hooks(
+        &config,
+        &hooks_dir,
+        &expected_hook_script_options(config_path, &repository_root),
+    );
+    for phase in config.installable_phases() {
+        let mut installed = true;
+        for drift in drift_report.for_phase(phase) {
+            let path = drift.path.display();
+            match &drift.kind {
+                HookDriftKind::Missing => report.fail(
+                    "hook-wrapper",
+                    Some(ErrorCode::InstallDrift),
+                    Some(phase),
+                    format!("missing managed wrapper for {phase} at {path}; run git smee install"),
+                    Some(INSTALL_FIX),
+                ),
+                HookDriftKind::NotAFile => report.fail(
+                    "hook-wrapper",
+                    Some(ErrorCode::InstallDrift),
+                    Some(phase),
+                    format!(
+                        "hook path for {phase} is not a regular file: {path}; remove it or fix core.hooksPath"
+                    ),
+                    None,
+                ),
+                HookDriftKind::Unmanaged => report.fail(
+                    "hook-wrapper",
+                    Some(ErrorCode::UnmanagedHook),
+                    Some(phase),
+                    format!(
+                        "unmanaged hook file blocks install for {phase} at {path}; move it aside or run git smee install --force"
+                    ),
+                    Some("git smee install --force"),
+                ),
+                HookDriftKind::Unreadable { error } => report.fail(
+                    "hook-wrapper",
+                    Some(ErrorCode::InstallDrift),
+                    Some(phase),
+                    format!("cannot read hook wrapper for {phase} at {path}: {error}"),
+                    None,
+                ),
+                HookDriftKind::WrongPermissions { mode } => report.fail(
+                    "hook-wrapper",
+                    Some(ErrorCode::InstallDrift),
+                    Some(phase),
+                    format!(
+                        "managed wrapper for {phase} at {path} is not executable (mode {mode:o}), so Git skips it; run git smee install"
+                    ),
+                    Some(INSTALL_FIX),
+                ),
+                HookDriftKind::Modified { content, .. } => {
+                    let stale_reasons = expected_hook_script.stale_reasons(content);
+                    if stale_reasons.is_empty() {
+                        report.warn(
+                            "hook-wrapper-stale",
+                            Some(ErrorCode::InstallDrift),
+                            Some(phase),
+                            format!(
+                                "stale managed wrapper for {phase}: it differs from the wrapper git smee install writes; run git smee install"
+                            ),
+                            Some(INSTALL_FIX),
+                        );
+                    }
+                    for stale_reason in stale_reasons {
+                        report.warn(
+                            "hook-wrapper-stale",
+                            Some(ErrorCode::InstallDrift),
+                            Some(phase),
+                            format!(
+                                "stale managed wrapper for {phase}: {stale_reason}; run git smee install"
+                            ),
+                            Some(INSTALL_FIX),
+                        );
+                    }
+                    continue;
                 }
+                _ => continue,
             }
+            installed = false;
+        }
+        if installed {
+            report.pass(
+                "hook-wrapper",
+                Some(phase),
+                format!("managed wrapper is installed for {phase}"),
+            );
         }
     }
 
     finish_doctor_report(report)
 }
 
+/// Checks that `git smee` typed in this shell runs the same git-smee version
+/// as the one that writes the hook wrappers.
+fn check_binary_on_path(report: &mut DoctorReport) {
+    let current_exe = env::current_exe().ok();
+    let this_version = env!("CARGO_PKG_VERSION");
+    let Some(binary) = platform::smee_binary_on_path() else {
+        report.warn(
+            "binary-on-path",
+            None,
+            None,
+            format!(
+                "{} is not on PATH, so git smee commands fail in this shell; add the directory of {} to PATH",
+                platform::SMEE_BINARY_NAME,
+                current_exe
+                    .as_deref()
+                    .map_or("the git-smee binary".to_string(), |exe| exe.display().to_string())
+            ),
+            None,
+        );
+        return;
+    };
+    let path = binary.path.display();
+    let is_this_binary = current_exe.as_deref().is_some_and(
+        |exe| matches!((exe.canonicalize(), binary.path.canonicalize()), (Ok(a), Ok(b)) if a == b),
+    );
+    if is_this_binary || binary.version.as_deref() == Some(this_version) {
+        report.pass(
+            "binary-on-path",
+            None,
+            format!("git-smee on PATH at {path} is version {this_version}"),
+        );
+    } else {
+        report.warn(
+            "binary-on-path",
+            None,
+            None,
+            format!(
+                "git-smee on PATH at {path} is version {}, but this binary is {this_version}; git smee and hook wrappers installed by this binary run different versions, so put one of them first on PATH and run git smee install",
+                binary.version.as_deref().unwrap_or("unknown")
+            ),
+            None,
+        );
+    }
+}
+
+/// Checks that `core.hooksPath` sends Git to the directory the config
+/// installs wrappers into.
+fn check_hooks_path(report: &mut DoctorReport, repository_root: &Path, config: &SmeeConfig) {
+    match installer::hooks_path_conflict(repository_root, config) {
+        Ok(None) => report.pass(
+            "hooks-path",
+            None,
+            "core.hooksPath agrees with settings.hooks_path".to_string(),
+        ),
+        Ok(Some(
+            conflict @ (HooksPathConflict::NotPointedAtConfigured { .. }
+            | HooksPathConflict::PointsElsewhere { .. }),
+        )) => report.fail(
+            "hooks-path",
+            Some(ErrorCode::InstallDrift),
+            None,
+            format!("{conflict}; run git smee install to point it at settings.hooks_path"),
+            Some(INSTALL_FIX),
+        ),
+        Ok(Some(conflict @ HooksPathConflict::SetOutsideConfig { .. })) => {
+            let unset = match &conflict {
+                HooksPathConflict::SetOutsideConfig {
+                    scope: Some(scope), ..
+                } if matches!(scope.as_str(), "local" | "global" | "system" | "worktree") => {
+                    Some(format!("git config --{scope} --unset core.hooksPath"))
+                }
+                _ => None,
+            };
+            report.warn(
+                "hooks-path",
+                Some(ErrorCode::InstallDrift),
+                None,
+                format!(
+                    "{conflict}; git-smee installs its wrappers there too. Unset it{} and run git smee install, or set settings.hooks_path to keep it",
+                    unset
+                        .as_deref()
+                        .map_or(String::new(), |unset| format!(" with {unset}"))
+                ),
+                unset.as_deref(),
+            );
+        }
+        Ok(Some(conflict)) => report.warn("hooks-path", None, None, conflict.to_string(), None),
+        Err(error) => report.fail(
+            "hooks-path",
+            Some(error.code()),
+            None,
+            format!("could not read core.hooksPath: {error}"),
+            None,
+        ),
+    }
+}
+
+/// `message` followed by `code`, as listed under warnings and errors.
+fn with_code(message: &str, code: Option<ErrorCode>) -> String {
+    match code {
+        Some(code) => format!("{message} [{code}]"),
+        None => message.to_string(),
+    }
+}
+
 fn finish_doctor_report(mut report: DoctorReport) -> DoctorReport {
     report.status = if !report.errors.is_empty() {
         DoctorStatus::Error
@@ -214,6 +501,7 @@ mod tests {
             ok: vec!["inside a Git repository".to_string()],
             warnings: Vec::new(),
             errors: Vec::new(),
+            checks: Vec::new(),
         });
 
         assert!(matches!(report.status, DoctorStatus::Ok));
@@ -229,8 +517,42 @@ mod tests {
             ok: Vec::new(),
             warnings: vec!["stale wrapper".to_string()],
             errors: vec!["missing wrapper".to_string()],
+            checks: Vec::new(),
         });
 
         assert!(matches!(report.status, DoctorStatus::Error));
     }
+
+    #[test]
+    fn findings_are_mirrored_into_structured_checks() {
+        let mut report = finish_doctor_report(DoctorReport {
+            status: DoctorStatus::Ok,
+            repository_root: None,
+            hooks_dir: None,
+            config_path: ".git-smee.toml".to_string(),
+            ok: Vec::new(),
+            warnings: Vec::new(),
+            errors: Vec::new(),
+            checks: Vec::new(),
+        });
+
+        report.fail(
+            "hook-wrapper",
+            Some(ErrorCode::InstallDrift),
+            Some(LifeCyclePhase::PreCommit),
+            "missing managed wrapper".to_string(),
+            Some(INSTALL_FIX),
+        );
+
+        assert_eq!(
+            report.errors,
+            vec!["missing managed wrapper [SMEE020]".to_string()]
+        );
+        let check = &report.checks[0];
+        assert_eq!(check.id, "hook-wrapper");
+        assert_eq!(check.code, Some(ErrorCode::InstallDrift));
+        assert!(matches!(check.level, DoctorStatus::Error));
+        assert_eq!(check.phase.as_deref(), Some("pre-commit"));
+        assert_eq!(check.fix.as_deref(), Some("git smee install"));
+    }
 }
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Parser;
 
@@ -6,10 +6,13 @@ mod commands;
 mod config_path;
 mod diagnostics;
 mod doctor;
+mod output;
+mod prompt;
 mod status;
 
-use commands::init::InitTemplate;
+use commands::{init::InitTemplate, stats::StatsFormat, validate::ValidateFormat};
 use config_path::resolve_config_path;
+use output::OutputFormat;
 
 #[derive(clap::Parser)]
 #[command(name = "git-smee")]
@@ -31,13 +34,142 @@ enum Command {
     Install {
         #[arg(long, help = "Overwrite existing unmanaged hook files")]
         force: bool,
+        #[arg(
+            long,
+            conflicts_with = "force",
+            help = "Keep existing unmanaged hook files as <hook>.pre-smee and run them after git-smee's hooks"
+        )]
+        chain: bool,
+        #[arg(
+            long,
+            help = "Show which hook files would be created, updated, or removed, with a diff of changed scripts, without writing anything"
+        )]
+        dry_run: bool,
+        #[arg(
+            value_name = "PHASE",
+            help = "Only (re)install the wrappers of these phases, leaving other hook files alone"
+        )]
+        phases: Vec<String>,
     },
-    #[command(name = "run", about = "Run a specific git hook")]
+    #[command(
+        name = "uninstall",
+        about = "Remove the hooks git-smee installed, leaving other hook files untouched"
+    )]
+    Uninstall,
+    #[command(
+        name = "run",
+        about = "Run one or more git hooks",
+        long_about = "Run one or more git hooks.\n\nThe phases are run in order (for example `run pre-commit commit-msg`). Hook arguments go after `--` and are forwarded to every phase, so an argument that happens to name a phase is never run as one: `run commit-msg -- .git/COMMIT_EDITMSG`."
+    )]
     Run {
-        hook: String,
-        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        #[arg(
+            long,
+            help = "Print each resolved command line, working directory, and injected env before running it, and CPU time and peak RSS in the summary (also GIT_SMEE_SHOW_COMMANDS=1)"
+        )]
+        show_commands: bool,
+        #[arg(
+            long,
+            help = "Trust the current contents of the config without prompting and record that decision"
+        )]
+        trust: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Stream JSON progress events to the Unix socket or named pipe at PATH (also GIT_SMEE_EVENTS_SOCKET)"
+        )]
+        events_socket: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Treat the command with this name (or hook-<n>) as failed without running it, to check how failures are handled; repeatable"
+        )]
+        simulate_failure: Vec<String>,
+        #[arg(
+            long,
+            value_name = "BASE..HEAD",
+            help = "Run pre-push against these commits, e.g. origin/main..HEAD, instead of reading the pushed refs from stdin"
+        )]
+        range: Option<String>,
+        #[arg(
+            long,
+            conflicts_with_all = ["show_commands", "trust", "events_socket", "simulate_failure", "range"],
+            help = "Print the commands the phases would run, with their scheduling settings, without running them"
+        )]
+        list: bool,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = OutputFormat::Text,
+            requires = "list",
+            help = "Format of the --list output"
+        )]
+        format: OutputFormat,
+        #[arg(
+            value_name = "PHASE",
+            required = true,
+            help = "Phases to run, in order"
+        )]
+        phases: Vec<String>,
+        #[arg(
+            last = true,
+            allow_hyphen_values = true,
+            value_name = "HOOK_ARGS",
+            help = "Arguments forwarded to every phase, given after --"
+        )]
         hook_args: Vec<String>,
     },
+    #[command(
+        name = "task",
+        about = "Run a named task from the [tasks] section, or list the tasks",
+        long_about = "Run a named task from the [tasks] section, or list the tasks.\n\nTasks are command groups that are not tied to a git phase; they use the same executor as hooks, so parallel entries, timeouts, and mutexes behave the same. Arguments after the task name are forwarded to every command."
+    )]
+    Task {
+        #[arg(
+            long,
+            help = "Print each resolved command line, working directory, and injected env before running it, and CPU time and peak RSS in the summary (also GIT_SMEE_SHOW_COMMANDS=1)"
+        )]
+        show_commands: bool,
+        #[arg(
+            long,
+            help = "Trust the current contents of the config without prompting and record that decision"
+        )]
+        trust: bool,
+        #[arg(help = "Task to run (lists the configured tasks when omitted)")]
+        task: Option<String>,
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        task_args: Vec<String>,
+    },
+    #[command(
+        name = "render",
+        about = "Print the hook wrapper script install would write for a phase"
+    )]
+    Render {
+        #[arg(help = "Lifecycle phase whose wrapper to print, e.g. pre-commit")]
+        phase: String,
+    },
+    #[command(
+        name = "serve",
+        about = "Serve validate, plan, run, and cancel requests as JSON-RPC over stdin/stdout",
+        long_about = "Serve validate, plan, run, and cancel requests as JSON-RPC 2.0 over stdin/stdout.\n\nMeant for editor extensions: one message per line, runs execute in the background with their hook output on stderr, `shutdown` cancels active runs and exits, and when stdin closes the server exits after active runs finish."
+    )]
+    Serve,
+    #[command(
+        name = "why",
+        about = "Explain, per command, whether a phase would run it now and why not",
+        long_about = "Explain, per command, whether a phase would run it now and why not.\n\nChecks the installed wrapper, the enable/disable toggles, nested runs, the machine policy, and each command's requires, severity, and enforce_after without running anything."
+    )]
+    Why {
+        #[arg(help = "Lifecycle phase to explain, e.g. pre-commit")]
+        phase: String,
+    },
+    #[command(
+        name = "resume",
+        about = "Continue an interrupted run, skipping the commands that already passed"
+    )]
+    Resume {
+        #[arg(help = "Run ID printed by the interrupted run (defaults to the most recent one)")]
+        run_id: Option<String>,
+    },
     #[command(
         name = "init",
         about = "Initialize a .git-smee.toml configuration file"
@@ -52,46 +184,392 @@ enum Command {
             help = "Starter template to write"
         )]
         template: InitTemplate,
+        #[arg(
+            long,
+            value_name = "DIR",
+            num_args = 0..=1,
+            default_missing_value = "hooks",
+            help = "Write committable hook wrappers to DIR (default: hooks) and point core.hooksPath at it"
+        )]
+        hooks_path: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            requires = "hooks_path",
+            help = "Append the core.hooksPath step for new clones to this shell script"
+        )]
+        bootstrap_script: Option<PathBuf>,
+        #[arg(
+            long,
+            conflicts_with = "hooks_path",
+            help = "Install the hook wrappers right after writing the config, like git smee install"
+        )]
+        install: bool,
     },
     #[command(name = "doctor", about = "Diagnose git-smee repository setup")]
     Doctor {
-        #[arg(long, help = "Emit a stable JSON diagnostics report")]
+        #[arg(
+            long,
+            conflicts_with = "format",
+            help = "Emit a stable JSON diagnostics report (same as --format json)"
+        )]
         json: bool,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = OutputFormat::Text,
+            help = "Report format"
+        )]
+        format: OutputFormat,
+    },
+    #[command(
+        name = "check",
+        about = "Verify the installed hook files match the config; exits 1 on drift, 2 on errors",
+        long_about = "Verify the installed hook files match the config.\n\nCompares every hook file against the wrapper git smee install would write and reports missing, extra, modified, unmanaged, and non-executable files. Exits 0 when everything matches, 1 when hooks drifted, and 2 when the check could not be done."
+    )]
+    Check,
+    #[command(
+        name = "validate",
+        about = "Report every problem in the config; exits 1 on errors, 2 when it cannot be read",
+        long_about = "Report every problem in the config: syntax errors, unknown phases and fields, empty commands, duplicate entries, and settings that have no effect.\n\nExits 0 when there are no errors, 1 when there are (or warnings with --strict), and 2 when the config cannot be read."
+    )]
+    Validate {
+        #[arg(long, value_enum, default_value_t = ValidateFormat::Text, help = "Output format")]
+        format: ValidateFormat,
+        #[arg(long, help = "Fail on warnings as well as errors")]
+        strict: bool,
+        #[arg(
+            long,
+            value_name = "REV",
+            help = "Also warn about commands changed since this commit that have no owner"
+        )]
+        since: Option<String>,
     },
+    #[command(
+        name = "stats",
+        about = "Summarize hook runs recorded in the local analytics store: counts, failures, and durations",
+        long_about = "Summarize hook runs recorded in the local analytics store: counts, failures, and durations.\n\nRuns are recorded when git config sets smee.analytics to true or smee.metricsFile. The store lives in .git/smee/analytics.jsonl and never leaves the machine."
+    )]
+    Stats {
+        #[command(subcommand)]
+        action: Option<StatsAction>,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = StatsFormat::Text,
+            help = "Output format; prometheus emits counters and a duration histogram"
+        )]
+        format: StatsFormat,
+    },
+    #[command(
+        name = "clean",
+        about = "Prune git-smee's state in .git/smee to smee.stateExpire and smee.stateMaxSize",
+        long_about = "Prune git-smee's state in .git/smee: run state, artifacts, isolated temp dirs, and the audit log.\n\nEntries modified before smee.stateExpire (default: 30.days.ago) are removed, then the oldest go until the rest fits in smee.stateMaxSize (default: 256m). git smee run does this at most once a day on its own."
+    )]
+    Clean {
+        #[arg(long, help = "Remove all state instead of pruning to the limits")]
+        all: bool,
+        #[arg(long, help = "List what would be removed without removing it")]
+        dry_run: bool,
+    },
+    #[command(
+        name = "list",
+        about = "List the configured phases, their commands, and whether each wrapper is installed"
+    )]
+    List,
     #[command(name = "status", about = "Show installed hook coverage and drift")]
     Status {
         #[arg(long, help = "Emit a stable JSON status report")]
         json: bool,
     },
+    #[command(
+        name = "simulate",
+        about = "Run hook phases for a Git operation without performing it"
+    )]
+    Simulate {
+        #[command(subcommand)]
+        operation: SimulateOperation,
+    },
+    #[command(
+        name = "notify",
+        about = "Report a Git operation that just happened, e.g. from a post-commit hook"
+    )]
+    Notify {
+        #[command(subcommand)]
+        event: NotifyEvent,
+    },
     #[command(
         name = "migrate-hooks",
         about = "Suggest git-smee config entries for existing unmanaged Git hooks"
     )]
     MigrateHooks,
+    #[command(
+        name = "trust",
+        about = "Trust the current contents of the config so its hooks may run on this machine"
+    )]
+    Trust,
+    #[command(
+        name = "diff-config",
+        about = "Show how hook behavior changes between the config at a revision and the current one"
+    )]
+    DiffConfig {
+        #[arg(help = "Commit to compare against, e.g. HEAD or origin/main")]
+        rev: String,
+    },
+    #[command(
+        name = "owners",
+        about = "List commands by owner, or who should review the commands changed since a revision"
+    )]
+    Owners {
+        #[arg(
+            long,
+            value_name = "REV",
+            help = "Only list commands changed since this commit, with the owners to review them"
+        )]
+        since: Option<String>,
+        #[arg(
+            long,
+            requires = "since",
+            help = "Fail when a changed command has no owner"
+        )]
+        require_owner: bool,
+    },
+    #[command(name = "config", about = "Read, change, or format the config")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    #[command(
+        name = "enable",
+        about = "Re-enable git-smee hooks for this repository, or for a single phase"
+    )]
+    Enable {
+        #[arg(help = "Lifecycle phase to enable (defaults to all phases)")]
+        phase: Option<String>,
+    },
+    #[command(
+        name = "disable",
+        about = "Disable git-smee hooks for this repository via git config, or for a single phase"
+    )]
+    Disable {
+        #[arg(help = "Lifecycle phase to disable (defaults to all phases)")]
+        phase: Option<String>,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum StatsAction {
+    #[command(
+        name = "reset",
+        about = "Delete every run recorded in the analytics store"
+    )]
+    Reset,
+}
+
+#[derive(clap::Subcommand)]
+enum ConfigAction {
+    #[command(
+        name = "get",
+        about = "Print the value at a dotted key such as pre-commit.0.command"
+    )]
+    Get { key: String },
+    #[command(
+        name = "set",
+        about = "Set the value at a dotted key, keeping the file's comments and formatting"
+    )]
+    Set {
+        key: String,
+        #[arg(
+            allow_hyphen_values = true,
+            help = "TOML literal (4, true, \"text\") or plain text, which is stored as a string"
+        )]
+        value: String,
+    },
+    #[command(
+        name = "fmt",
+        about = "Order tables canonically and normalize key style, keeping comments"
+    )]
+    Fmt {
+        #[arg(
+            long,
+            help = "Fail instead of rewriting when the config is not formatted"
+        )]
+        check: bool,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum SimulateOperation {
+    #[command(
+        name = "commit",
+        about = "Run pre-commit, prepare-commit-msg, and commit-msg against the staged changes without committing"
+    )]
+    Commit {
+        #[arg(short = 'm', long, help = "Commit message passed to the message hooks")]
+        message: String,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum NotifyEvent {
+    #[command(
+        name = "commit",
+        about = "Print the short sha, subject, and changed files of HEAD, and post them to a webhook if one is set"
+    )]
+    Commit {
+        #[arg(
+            long,
+            value_name = "URL",
+            help = "Webhook to post the commit metadata to as JSON (defaults to $GIT_SMEE_NOTIFY_WEBHOOK)"
+        )]
+        webhook: Option<String>,
+    },
 }
 
 fn main() {
-    if let Err(error) = run() {
-        eprintln!("Error: {error}");
+    let cli = Cli::parse();
+    let config_path = match std::env::current_dir() {
+        Ok(invocation_dir) => resolve_config_path(cli.config, &invocation_dir),
+        Err(error) => {
+            eprintln!("{}", output::error_line(&error));
+            std::process::exit(1);
+        }
+    };
+    if let Err(error) = run(cli.command, &config_path) {
+        output::print_error(error.as_ref(), &config_path);
         std::process::exit(1);
     }
 }
 
-fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
-    let invocation_dir = std::env::current_dir()?;
-    let config_path = resolve_config_path(cli.config, &invocation_dir);
-
-    match cli.command {
-        Command::Install { force } => commands::install::run_install(&config_path, force),
-        Command::Run { hook, hook_args } => {
-            commands::run::run_hook(&config_path, &hook, &hook_args)
-        }
-        Command::Initialize { force, template } => {
-            commands::init::run_init(&config_path, force, &template)
+fn run(command: Command, config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::Install {
+            force,
+            chain,
+            dry_run,
+            phases,
+        } => commands::install::run_install(
+            config_path,
+            commands::install::InstallFlags {
+                force,
+                chain,
+                dry_run,
+                phases: &phases,
+            },
+        ),
+        Command::Uninstall => commands::uninstall::run_uninstall(config_path),
+        Command::Run {
+            list: true,
+            format,
+            phases,
+            hook_args,
+            ..
+        } => commands::run::run_list(config_path, &phases, &hook_args, format),
+        Command::Run {
+            show_commands,
+            trust,
+            events_socket,
+            simulate_failure,
+            range,
+            phases,
+            hook_args,
+            ..
+        } => commands::run::run_hook(
+            config_path,
+            &phases,
+            &hook_args,
+            commands::run::RunFlags {
+                show_commands,
+                trust,
+                events_socket: events_socket.as_deref(),
+                simulated_failures: &simulate_failure,
+                range: range.as_deref(),
+            },
+        ),
+        Command::Task {
+            show_commands,
+            trust,
+            task,
+            task_args,
+        } => commands::task::run_task(
+            config_path,
+            task.as_deref(),
+            &task_args,
+            show_commands,
+            trust,
+        ),
+        Command::Render { phase } => commands::render::run_render(config_path, &phase),
+        Command::Serve => commands::serve::run_serve(config_path),
+        Command::Why { phase } => commands::why::run_why(config_path, &phase),
+        Command::Resume { run_id } => commands::run::run_resume(config_path, run_id.as_deref()),
+        Command::Initialize {
+            force,
+            template,
+            hooks_path,
+            bootstrap_script,
+            install,
+        } => {
+            let hooks_path_init =
+                hooks_path
+                    .as_deref()
+                    .map(|hooks_path| commands::init::HooksPathInit {
+                        hooks_path,
+                        bootstrap_script: bootstrap_script.as_deref(),
+                    });
+            commands::init::run_init(
+                config_path,
+                force,
+                &template,
+                hooks_path_init.as_ref(),
+                install,
+            )
         }
-        Command::Doctor { json } => doctor::run_doctor(&config_path, json),
-        Command::Status { json } => status::run_status(&config_path, json),
+        Command::Doctor { json, format } => doctor::run_doctor(config_path, format.or_json(json)),
+        Command::Check => std::process::exit(commands::check::run_check(config_path)),
+        Command::Validate {
+            format,
+            strict,
+            since,
+        } => std::process::exit(commands::validate::run_validate(
+            config_path,
+            format,
+            strict,
+            since.as_deref(),
+        )),
+        Command::List => commands::list::run_list(config_path),
+        Command::Status { json } => status::run_status(config_path, json),
+        Command::Stats {
+            action: Some(StatsAction::Reset),
+            ..
+        } => commands::stats::run_stats_reset(),
+        Command::Stats {
+            action: None,
+            format,
+        } => commands::stats::run_stats(format),
+        Command::Clean { all, dry_run } => commands::clean::run_clean(all, dry_run),
         Command::MigrateHooks => commands::migrate_hooks::run_migrate_hooks(),
+        Command::Trust => commands::trust::run_trust(config_path),
+        Command::DiffConfig { rev } => commands::diff_config::run_diff_config(config_path, &rev),
+        Command::Owners {
+            since,
+            require_owner,
+        } => commands::owners::run_owners(config_path, since.as_deref(), require_owner),
+        Command::Config {
+            action: ConfigAction::Get { key },
+        } => commands::config::run_config_get(config_path, &key),
+        Command::Config {
+            action: ConfigAction::Set { key, value },
+        } => commands::config::run_config_set(config_path, &key, &value),
+        Command::Config {
+            action: ConfigAction::Fmt { check },
+        } => commands::config::run_config_fmt(config_path, check),
+        Command::Enable { phase } => commands::toggle::run_enable(phase.as_deref()),
+        Command::Disable { phase } => commands::toggle::run_disable(phase.as_deref()),
+        Command::Simulate {
+            operation: SimulateOperation::Commit { message },
+        } => commands::simulate::run_simulate_commit(config_path, &message),
+        Command::Notify {
+            event: NotifyEvent::Commit { webhook },
+        } => commands::notify::run_notify_commit(webhook.as_deref()),
     }
 }
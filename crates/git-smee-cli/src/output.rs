@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use git_smee_core::{
+    ErrorCode,
+    messages::{self, MessageCatalog},
+    redaction::redact_secret_values,
+    repository,
+};
+
+use crate::config_path::read_config_file;
+
+/// Output format shared by reporting subcommands.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// Human-readable text.
+    #[default]
+    Text,
+    /// Stable, pretty-printed JSON for tooling.
+    Json,
+}
+
+/// The `Error: ...` line printed for a failed command, ending with the
+/// failure's [`ErrorCode`] when it has one, e.g. `[SMEE010]`.
+pub(crate) fn error_line(error: &(dyn std::error::Error + 'static)) -> String {
+    let message = redact_secret_values(&error.to_string());
+    match ErrorCode::of(error) {
+        Some(code) => format!("Error: {message} [{code}]"),
+        None => format!("Error: {message}"),
+    }
+}
+
+/// Prints the [`error_line`] of `error` to stderr, followed by the guidance
+/// the message catalog has for its code.
+pub(crate) fn print_error(error: &(dyn std::error::Error + 'static), config_path: &Path) {
+    eprintln!("{}", error_line(error));
+    if let Some(code) = ErrorCode::of(error) {
+        eprintln!("help: {}", message_catalog(config_path).message(code));
+    }
+}
+
+/// Catalog for the current locale from `settings.messages_dir`, or English
+/// when the config sets none or cannot be read.
+fn message_catalog(config_path: &Path) -> MessageCatalog {
+    let Some(messages_dir) = read_config_file(config_path)
+        .ok()
+        .and_then(|config| config.settings.messages_dir)
+    else {
+        return MessageCatalog::english();
+    };
+    let Some(locale) = messages::current_locale() else {
+        return MessageCatalog::english();
+    };
+    let root = repository::find_git_root()
+        .ok()
+        .or_else(|| config_path.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    MessageCatalog::load(&root.join(messages_dir), &locale).unwrap_or_else(|error| {
+        eprintln!("git-smee: warning: {error}; using the English messages");
+        MessageCatalog::english()
+    })
+}
+
+impl OutputFormat {
+    /// Resolves a `--format` value together with a legacy `--json` switch.
+    pub(crate) fn or_json(self, json: bool) -> Self {
+        if json { Self::Json } else { self }
+    }
+}
@@ -0,0 +1,38 @@
+use std::{
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+};
+
+/// Prints `context` and asks a yes/no `question` on the controlling terminal.
+///
+/// Hooks started by Git usually have stdin redirected, so the answer is read
+/// from the terminal device instead. Returns `false` without asking when there
+/// is no terminal.
+pub(crate) fn confirm(context: &[String], question: &str) -> io::Result<bool> {
+    if !io::stderr().is_terminal() {
+        return Ok(false);
+    }
+    let Ok(terminal) = open_terminal() else {
+        return Ok(false);
+    };
+    let mut stderr = io::stderr().lock();
+    for line in context {
+        writeln!(stderr, "{line}")?;
+    }
+    write!(stderr, "{question} [y/N] ")?;
+    stderr.flush()?;
+    let mut answer = String::new();
+    io::BufReader::new(terminal).read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+fn open_terminal() -> io::Result<fs::File> {
+    if cfg!(windows) {
+        fs::File::open("CONIN$")
+    } else {
+        fs::File::open("/dev/tty")
+    }
+}
@@ -31,12 +31,53 @@ fn stdin_capture_command(output_path: &Path) -> String {
 fn git(test_repo: &common::TestRepo, args: &[&str]) {
     let status = StdCommand::new("git")
         .current_dir(&test_repo.path)
+        .env(common::TRUST_ALL_ENV, "1")
         .args(args)
         .status()
         .expect("failed to run git command");
     assert!(status.success(), "git {args:?} failed with {status}");
 }
 
+/// Runs git in `test_repo` like a developer would, with the built git-smee on
+/// `PATH`, and leaves checking the outcome to the caller.
+#[cfg(unix)]
+fn git_output(test_repo: &common::TestRepo, args: &[&str]) -> std::process::Output {
+    StdCommand::new("git")
+        .current_dir(&test_repo.path)
+        .env(common::TRUST_ALL_ENV, "1")
+        .env_remove("GIT_SMEE_ACTIVE")
+        .env("PATH", common::path_with_git_smee())
+        .args(args)
+        .output()
+        .expect("failed to run git command")
+}
+
+#[cfg(unix)]
+fn set_git_identity(test_repo: &common::TestRepo) {
+    git(test_repo, &["config", "user.name", "git-smee test"]);
+    git(
+        test_repo,
+        &["config", "user.email", "git-smee@example.invalid"],
+    );
+}
+
+/// Creates a bare repository and adds it to `test_repo` as `origin`.
+#[cfg(unix)]
+fn add_bare_origin(test_repo: &common::TestRepo) -> TempDir {
+    let bare_remote = TempDir::new().expect("failed to create bare remote temp dir");
+    git2::Repository::init_bare(bare_remote.path()).expect("failed to init bare remote");
+    git(
+        test_repo,
+        &[
+            "remote",
+            "add",
+            "origin",
+            bare_remote.path().to_str().expect("utf-8 bare remote path"),
+        ],
+    );
+    bare_remote
+}
+
 #[cfg(windows)]
 fn git_for_windows_sh(test_repo: &common::TestRepo) -> std::path::PathBuf {
     let output = StdCommand::new("git")
@@ -63,7 +104,7 @@ fn git_for_windows_sh(test_repo: &common::TestRepo) -> std::path::PathBuf {
 
 #[test]
 fn given_git_smee_when_help_then_success() {
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.arg("--help").assert().success();
 }
 
@@ -71,7 +112,7 @@ fn given_git_smee_when_help_then_success() {
 fn given_no_unmanaged_hooks_when_migrate_hooks_then_reports_no_suggestions() {
     let test_repo = common::TestRepo::default();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("migrate-hooks")
         .assert()
@@ -101,7 +142,7 @@ fn given_unmanaged_hooks_when_migrate_hooks_then_prints_parseable_toml_suggestio
     )
     .unwrap();
 
-    let output = Command::new(cargo::cargo_bin!("git-smee"))
+    let output = common::git_smee()
         .current_dir(&test_repo.path)
         .arg("migrate-hooks")
         .assert()
@@ -135,7 +176,7 @@ fn given_managed_and_unmanaged_hooks_when_migrate_hooks_then_ignores_managed_wra
     )
     .unwrap();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("migrate-hooks")
         .assert()
@@ -149,11 +190,43 @@ fn given_managed_and_unmanaged_hooks_when_migrate_hooks_then_ignores_managed_wra
         );
 }
 
+#[test]
+fn given_installed_and_unmanaged_hooks_when_uninstalling_then_only_managed_hooks_are_removed() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = \"echo hi\"\n");
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+    fs::write(
+        test_repo.path.join(".git/hooks/post-merge"),
+        "#!/usr/bin/env sh\necho legacy post-merge\n",
+    )
+    .unwrap();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("uninstall")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed").and(predicate::str::contains("pre-commit")));
+    assert!(!test_repo.path.join(".git/hooks/pre-commit").exists());
+    assert!(test_repo.path.join(".git/hooks/post-merge").exists());
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("uninstall")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No git-smee hooks installed"));
+}
+
 #[test]
 fn given_non_repo_dir_when_help_then_success() {
     let non_repo_dir = TempDir::new().unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(non_repo_dir.path())
         .arg("--help")
         .assert()
@@ -165,7 +238,7 @@ fn given_non_repo_dir_when_help_then_success() {
 fn given_non_repo_dir_when_version_then_success() {
     let non_repo_dir = TempDir::new().unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(non_repo_dir.path())
         .arg("--version")
         .assert()
@@ -182,7 +255,7 @@ fn given_non_repo_dir_when_repo_commands_then_not_in_git_repository_error() {
         ["init"].as_slice(),
         ["run", "pre-commit"].as_slice(),
     ] {
-        let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+        let mut cmd = common::git_smee();
         cmd.current_dir(non_repo_dir.path())
             .args(args)
             .assert()
@@ -198,7 +271,7 @@ fn given_non_repo_dir_when_repo_commands_then_not_in_git_repository_error() {
 fn given_git_smee_when_install_then_hooks_are_present() {
     let test_repo = common::TestRepo::default();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("install")
         .assert()
@@ -206,17 +279,121 @@ fn given_git_smee_when_install_then_hooks_are_present() {
     test_repo.assert_hooks_installed(vec![LifeCyclePhase::PreCommit, LifeCyclePhase::PrePush]);
 }
 
+#[test]
+fn given_changed_managed_hook_when_install_dry_run_then_diff_is_shown_and_nothing_is_written() {
+    let test_repo = common::TestRepo::default();
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+    let pre_commit = test_repo.path.join(".git/hooks/pre-commit");
+    let edited = format!(
+        "{}echo local tweak\n",
+        fs::read_to_string(&pre_commit).unwrap()
+    );
+    fs::write(&pre_commit, &edited).unwrap();
+    fs::remove_file(test_repo.path.join(".git/hooks/pre-push")).unwrap();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["install", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("would update"))
+        .stdout(predicate::str::contains("--- a/pre-commit"))
+        .stdout(predicate::str::contains("-echo local tweak"))
+        .stdout(predicate::str::contains("would create"))
+        .stdout(predicate::str::contains("Dry run: no files were changed."))
+        .stdout(predicate::str::contains("\x1b[").not());
+
+    assert_eq!(fs::read_to_string(&pre_commit).unwrap(), edited);
+    assert!(!test_repo.path.join(".git/hooks/pre-push").exists());
+}
+
+#[test]
+fn given_configured_phase_when_render_then_output_matches_installed_wrapper() {
+    let test_repo = common::TestRepo::default();
+    let rendered = common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["render", "pre-commit"])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty())
+        .get_output()
+        .stdout
+        .clone();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+
+    let installed = fs::read(test_repo.path.join(".git/hooks/pre-commit")).unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&rendered),
+        String::from_utf8_lossy(&installed)
+    );
+    assert!(!test_repo.path.join(".git/hooks/post-merge").exists());
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["render", "post-merge"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(MANAGED_FILE_MARKER))
+        .stderr(predicate::str::contains(
+            "Note: post-merge is not installed by",
+        ));
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["render", "pre-comit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown lifecycle phase: pre-comit",
+        ));
+}
+
+#[test]
+fn given_hooks_path_setting_when_install_then_hooks_land_there_and_core_hooks_path_is_set() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[settings]\nhooks_path = \".git-smee/hooks\"\n\n[[pre-commit]]\ncommand = \"true\"\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+
+    assert!(test_repo.path.join(".git-smee/hooks/pre-commit").is_file());
+    assert!(!test_repo.path.join(".git/hooks/pre-commit").exists());
+    let output = StdCommand::new("git")
+        .current_dir(&test_repo.path)
+        .args(["config", "--local", "core.hooksPath"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        ".git-smee/hooks"
+    );
+}
+
 #[test]
 fn given_healthy_repo_when_doctor_then_successful_sections_are_reported() {
     let test_repo = common::TestRepo::default();
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("install")
         .assert()
         .success();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
+        .env("PATH", common::path_with_git_smee())
         .arg("doctor")
         .assert()
         .success()
@@ -234,14 +411,15 @@ fn given_healthy_repo_when_doctor_then_successful_sections_are_reported() {
 #[test]
 fn given_healthy_repo_when_doctor_json_then_stable_json_is_reported() {
     let test_repo = common::TestRepo::default();
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("install")
         .assert()
         .success();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
+        .env("PATH", common::path_with_git_smee())
         .args(["doctor", "--json"])
         .assert()
         .success()
@@ -253,16 +431,95 @@ fn given_healthy_repo_when_doctor_json_then_stable_json_is_reported() {
         );
 }
 
+#[test]
+fn given_missing_wrappers_when_doctor_format_json_then_checks_include_fix_commands() {
+    let test_repo = common::TestRepo::default();
+
+    let output = common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["doctor", "--format", "json"])
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["status"], "error");
+    let checks = report["checks"].as_array().unwrap();
+    let missing_pre_commit = checks
+        .iter()
+        .find(|check| check["id"] == "hook-wrapper" && check["phase"] == "pre-commit")
+        .expect("pre-commit wrapper check should be reported");
+    assert_eq!(missing_pre_commit["level"], "error");
+    assert_eq!(missing_pre_commit["code"], "SMEE020");
+    assert_eq!(missing_pre_commit["fix"], "git smee install");
+    assert!(
+        checks
+            .iter()
+            .any(|check| check["id"] == "repository" && check["level"] == "ok")
+    );
+}
+
+#[test]
+fn given_setting_without_effect_when_installing_and_doctor_then_it_is_warned_about() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-push]]\ncommand = 'cargo test'\nfixable = 'cargo fix'\nrestage = true\n",
+    );
+    let message = "Hook 'pre-push' entry #1: restage has no effect in pre-push";
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(format!(
+            "git-smee: warning: {message}"
+        )));
+
+    let output = common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["doctor", "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let check = report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["id"] == "config-ineffective")
+        .expect("ineffective setting should be reported");
+    assert_eq!(check["level"], "warning");
+    assert_eq!(check["phase"], "pre-push");
+    assert!(check["message"].as_str().unwrap().starts_with(message));
+}
+
+#[test]
+fn given_json_and_format_flags_when_doctor_then_cli_rejects_conflict() {
+    let test_repo = common::TestRepo::default();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["doctor", "--json", "--format", "text"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 #[test]
 fn given_installed_hooks_when_status_then_reports_coverage() {
     let test_repo = common::TestRepo::default();
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("install")
         .assert()
         .success();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("status")
         .assert()
@@ -285,7 +542,7 @@ fn given_missing_and_unmanaged_hooks_when_status_then_reports_next_actions() {
     let pre_commit = test_repo.path.join(".git/hooks/pre-commit");
     fs::write(&pre_commit, "#!/usr/bin/env sh\necho unmanaged\n").unwrap();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("status")
         .assert()
@@ -319,7 +576,7 @@ fn given_stale_and_obsolete_managed_hooks_when_status_then_reports_drift_without
     )
     .unwrap();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("status")
         .assert()
@@ -356,7 +613,7 @@ fn given_marker_only_in_body_when_status_then_treats_hook_as_unmanaged() {
     )
     .unwrap();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("status")
         .assert()
@@ -378,7 +635,7 @@ fn given_unconfigured_marker_only_in_body_when_status_then_does_not_recommend_re
     )
     .unwrap();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("status")
         .assert()
@@ -390,7 +647,7 @@ fn given_unconfigured_marker_only_in_body_when_status_then_does_not_recommend_re
 fn given_drift_when_status_json_then_stable_json_is_reported() {
     let test_repo = common::TestRepo::default();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .args(["status", "--json"])
         .assert()
@@ -409,7 +666,7 @@ fn given_missing_config_when_doctor_then_actionable_error_is_reported() {
     let test_repo = common::TestRepo::default();
     fs::remove_file(test_repo.config_path()).expect("failed to remove config");
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("doctor")
         .assert()
@@ -429,7 +686,7 @@ fn given_malformed_config_when_doctor_then_parse_error_is_reported() {
     let test_repo = common::TestRepo::default();
     test_repo.write_config("[[pre-commit]]\ncommand = ");
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("doctor")
         .assert()
@@ -446,7 +703,7 @@ fn given_unmanaged_hook_when_doctor_then_collision_is_reported() {
     let pre_commit = test_repo.path.join(".git/hooks/pre-commit");
     fs::write(&pre_commit, "#!/usr/bin/env sh\necho unmanaged\n").unwrap();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("doctor")
         .assert()
@@ -467,7 +724,7 @@ fn given_marker_only_in_body_when_doctor_then_hook_is_unmanaged() {
     )
     .unwrap();
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("doctor")
         .assert()
@@ -487,7 +744,7 @@ fn given_custom_hooks_path_when_doctor_then_effective_path_is_reported() {
         .status()
         .expect("failed to configure hooksPath");
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("doctor")
         .assert()
@@ -499,6 +756,82 @@ fn given_custom_hooks_path_when_doctor_then_effective_path_is_reported() {
         );
 }
 
+#[test]
+fn given_core_hooks_path_conflicting_with_config_when_doctor_then_conflict_and_fix_are_reported() {
+    let test_repo = common::TestRepo::default();
+    std::process::Command::new("git")
+        .current_dir(&test_repo.path)
+        .args(["config", "core.hooksPath", ".husky"])
+        .status()
+        .expect("failed to configure hooksPath");
+
+    let output = common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["doctor", "--format", "json"])
+        .assert()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let hooks_path = report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["id"] == "hooks-path")
+        .expect("hooks-path check should be reported");
+    assert_eq!(hooks_path["level"], "warning");
+    assert_eq!(
+        hooks_path["fix"],
+        "git config --local --unset core.hooksPath"
+    );
+    assert!(
+        hooks_path["message"]
+            .as_str()
+            .unwrap()
+            .contains("core.hooksPath in the local git config points Git at")
+    );
+
+    test_repo.write_config(
+        "[settings]\nhooks_path = '.git-smee/hooks'\n\n[[pre-commit]]\ncommand = 'true'\n",
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("doctor")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "run git smee install to point it at settings.hooks_path",
+        ));
+}
+
+#[test]
+fn given_git_smee_missing_from_path_when_doctor_then_path_warning_is_reported() {
+    let test_repo = common::TestRepo::default();
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+
+    let path_without_git_smee = std::env::join_paths(
+        std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default())
+            .filter(|dir| !dir.join("git-smee").exists() && !dir.join("git-smee.exe").exists()),
+    )
+    .unwrap();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("PATH", path_without_git_smee)
+        .arg("doctor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("git-smee doctor: Warning").and(
+            predicate::str::contains(
+                "git-smee is not on PATH, so git smee commands fail in this shell",
+            ),
+        ));
+}
+
 #[test]
 fn given_stale_managed_hook_when_doctor_then_reinstall_warning_is_reported() {
     let test_repo = common::TestRepo::default();
@@ -514,8 +847,13 @@ fn given_stale_managed_hook_when_doctor_then_reinstall_warning_is_reported() {
         format!("#!/usr/bin/env sh\n# {MANAGED_FILE_MARKER}\n/old/git-smee --config old.toml run pre-push\n"),
     )
     .unwrap();
+    #[cfg(unix)]
+    for hook in [&pre_commit, &pre_push] {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(hook, fs::Permissions::from_mode(0o755)).unwrap();
+    }
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("doctor")
         .assert()
@@ -531,60 +869,319 @@ fn given_stale_managed_hook_when_doctor_then_reinstall_warning_is_reported() {
 }
 
 #[test]
-fn given_bare_repo_when_install_then_hooks_are_present() {
-    let bare_repo = TempDir::new().expect("failed to create bare repo temp dir");
-    git2::Repository::init_bare(bare_repo.path()).expect("failed to init bare repo");
-    fs::write(
-        bare_repo.path().join(".git-smee.toml"),
-        r#"
-[[pre-receive]]
-command = "echo bare"
-"#,
-    )
-    .expect("failed to write config");
+fn given_installed_hooks_when_checking_then_exit_status_reports_drift_and_errors() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = 'echo hi'\n");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
-    cmd.current_dir(bare_repo.path())
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("check")
+        .assert()
+        .code(1)
+        .stdout(
+            predicate::str::contains("pre-commit: missing (")
+                .and(predicate::str::contains("run git smee install")),
+        );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
         .arg("install")
         .assert()
         .success();
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("check")
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains(
+            "git-smee check: installed hooks match the config",
+        ));
 
-    assert!(bare_repo.path().join("hooks").join("pre-receive").exists());
+    let pre_commit = test_repo.path.join(".git/hooks/pre-commit");
+    let mut content = fs::read_to_string(&pre_commit).unwrap();
+    content.push_str("echo tampered\n");
+    fs::write(&pre_commit, content).unwrap();
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("check")
+        .assert()
+        .code(1)
+        .stdout(
+            predicate::str::contains("pre-commit: modified (differs from the rendered template)")
+                .and(predicate::str::contains("-echo tampered")),
+        );
+
+    test_repo.write_config("[[pre-commit]\n");
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("check")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Error:"));
 }
 
+#[cfg(unix)]
 #[test]
-fn given_install_when_generating_hook_script_then_wrapper_forwards_hook_arguments() {
-    let test_repo = common::TestRepo::default();
+fn given_non_executable_wrapper_when_doctor_then_permission_error_is_reported() {
+    use std::os::unix::fs::PermissionsExt;
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
-    cmd.current_dir(&test_repo.path)
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = 'echo hi'\n");
+    common::git_smee()
+        .current_dir(&test_repo.path)
         .arg("install")
         .assert()
         .success();
+    let pre_commit = test_repo.path.join(".git/hooks/pre-commit");
+    fs::set_permissions(&pre_commit, fs::Permissions::from_mode(0o644)).unwrap();
 
-    let hook_content =
-        fs::read_to_string(test_repo.path.join(".git/hooks/pre-commit")).expect("missing hook");
-
-    #[cfg(unix)]
-    assert!(hook_content.contains("run pre-commit \"$@\""));
-
-    #[cfg(windows)]
-    assert!(hook_content.contains("run pre-commit \"$@\""));
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("doctor")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "is not executable (mode 644), so Git skips it; run git smee install",
+        ));
 }
 
-#[cfg(windows)]
 #[test]
-fn given_installed_windows_hook_when_invoked_with_metachar_args_then_args_roundtrip() {
+fn given_simulated_failure_when_running_then_named_command_fails_without_running() {
     let test_repo = common::TestRepo::default();
-    let observed = test_repo.path.join("metachar-args.txt");
-    let capture_script = test_repo.path.join("capture-metachar-args.ps1");
-    let observed_for_powershell = observed.to_string_lossy().replace('"', "`\"");
-    fs::write(
-        &capture_script,
-        format!(
-            r#"$values = @($env:GIT_SMEE_HOOK_ARGC)
-for ($i = 1; $i -le [int]$env:GIT_SMEE_HOOK_ARGC; $i++) {{
-  $values += [Environment]::GetEnvironmentVariable("GIT_SMEE_HOOK_ARG_$i")
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'lint'\ncommand = 'echo lint-ran'\n\n[[pre-commit]]\nname = 'tests'\ncommand = 'echo tests-ran'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "--simulate-failure", "tests", "pre-commit"])
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains("lint-ran")
+                .and(predicate::str::contains("tests-ran").not())
+                .and(predicate::str::contains("simulated failure")),
+        )
+        .stderr(
+            predicate::str::contains("simulating a failure of tests; it will not run").and(
+                predicate::str::contains("Hook 'tests' failed (simulated by --simulate-failure)"),
+            ),
+        );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "--simulate-failure", "tset", "pre-commit"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("lint-ran").not())
+        .stderr(predicate::str::contains(
+            "--simulate-failure tset: no command with that name in pre-commit",
+        ));
+}
+
+#[test]
+fn given_run_list_when_listing_phases_then_commands_are_printed_without_running() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[settings.groups]\ncpu = 1\n\n[[pre-commit]]\nname = 'fmt'\ncommand = 'touch ran-fmt'\n\n[[pre-commit]]\ncommand = 'touch ran-test'\nparallel = true\ngroup = 'cpu'\ntimeout = 600\n\n[[commit-msg]]\ncommand = 'touch ran-msg'\nseverity = 'warning'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit", "--list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pre-commit: 2 command(s)"))
+        .stdout(predicate::str::contains(
+            "  - fmt: touch ran-fmt (sequential)",
+        ))
+        .stdout(predicate::str::contains(
+            "  - hook-2: touch ran-test (parallel, group cpu, timeout 600s)",
+        ));
+
+    let output = common::git_smee()
+        .current_dir(&test_repo.path)
+        .args([
+            "run",
+            "--list",
+            "--format",
+            "json",
+            "pre-commit",
+            "commit-msg",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let listing: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(listing["phases"][0]["phase"], "pre-commit");
+    assert_eq!(listing["phases"][0]["commands"][1]["parallel"], true);
+    assert_eq!(listing["phases"][0]["commands"][1]["group"], "cpu");
+    assert_eq!(listing["phases"][1]["commands"][0]["label"], "hook-1");
+    assert_eq!(listing["phases"][1]["commands"][0]["severity"], "warning");
+    for marker in ["ran-fmt", "ran-test", "ran-msg"] {
+        assert!(!test_repo.path.join(marker).exists());
+    }
+}
+
+#[test]
+fn given_success_codes_when_command_exits_with_listed_code_then_run_passes_and_summary_says_so() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'fixer'\ncommand = 'exit 2'\nsuccess_codes = [0, 2]\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "sequential command #1: ok (exit code 2 accepted)",
+        ));
+
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'fixer'\ncommand = 'exit 3'\nsuccess_codes = [0, 2]\n",
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure();
+}
+
+#[cfg(unix)]
+#[test]
+fn given_script_without_exec_bit_when_running_then_mapped_interpreter_runs_it() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config_at("scripts/check.sh", "echo \"$0\" > ran-by-interpreter.txt\n");
+    test_repo.write_config(
+        "[settings.interpreters]\nsh = 'sh'\n\n[[pre-commit]]\nname = 'check'\nscript = 'scripts/check.sh'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(test_repo.path.join("ran-by-interpreter.txt")).unwrap(),
+        "scripts/check.sh\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_group_with_limit_one_when_running_parallel_hooks_then_its_commands_never_overlap() {
+    let test_repo = common::TestRepo::default();
+    let command = "mkdir build.lock && sleep 0.2 && rmdir build.lock";
+    test_repo.write_config(&format!(
+        "[settings]\nparallel_without_tty = true\n\n[settings.groups]\ncpu = 1\n\n[[pre-commit]]\ncommand = '{command}'\nparallel = true\ngroup = 'cpu'\n\n[[pre-commit]]\ncommand = '{command}'\nparallel = true\ngroup = 'cpu'\n"
+    ));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn given_group_without_limit_when_running_then_config_error_names_the_group() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = 'true'\nparallel = true\ngroup = 'io'\n");
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "group 'io' has no limit in [settings.groups]",
+        ));
+}
+
+#[test]
+fn given_unbounded_timeout_when_running_in_ci_then_a_warning_names_the_command() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[defaults.pre-commit]\ntimeout = 60\n\n[[pre-commit]]\nname = 'helper'\ncommand = 'echo helper-ran'\ntimeout = 'none'\n",
+    );
+    let warning = "git-smee: warning: pre-commit command helper has timeout = \"none\" and can hang this CI job";
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("CI", "true")
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("helper-ran"))
+        .stderr(predicate::str::contains(warning));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env_remove("CI")
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(warning).not());
+}
+
+#[test]
+fn given_bare_repo_when_install_then_hooks_are_present() {
+    let bare_repo = TempDir::new().expect("failed to create bare repo temp dir");
+    git2::Repository::init_bare(bare_repo.path()).expect("failed to init bare repo");
+    fs::write(
+        bare_repo.path().join(".git-smee.toml"),
+        r#"
+[[pre-receive]]
+command = "echo bare"
+"#,
+    )
+    .expect("failed to write config");
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(bare_repo.path())
+        .arg("install")
+        .assert()
+        .success();
+
+    assert!(bare_repo.path().join("hooks").join("pre-receive").exists());
+}
+
+#[test]
+fn given_install_when_generating_hook_script_then_wrapper_forwards_hook_arguments() {
+    let test_repo = common::TestRepo::default();
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+
+    let hook_content =
+        fs::read_to_string(test_repo.path.join(".git/hooks/pre-commit")).expect("missing hook");
+
+    #[cfg(unix)]
+    assert!(hook_content.contains("run pre-commit -- \"$@\""));
+
+    #[cfg(windows)]
+    assert!(hook_content.contains("run pre-commit -- \"$@\""));
+}
+
+#[cfg(windows)]
+#[test]
+fn given_installed_windows_hook_when_invoked_with_metachar_args_then_args_roundtrip() {
+    let test_repo = common::TestRepo::default();
+    let observed = test_repo.path.join("metachar-args.txt");
+    let capture_script = test_repo.path.join("capture-metachar-args.ps1");
+    let observed_for_powershell = observed.to_string_lossy().replace('"', "`\"");
+    fs::write(
+        &capture_script,
+        format!(
+            r#"$values = @($env:GIT_SMEE_HOOK_ARGC)
+for ($i = 1; $i -le [int]$env:GIT_SMEE_HOOK_ARGC; $i++) {{
+  $values += [Environment]::GetEnvironmentVariable("GIT_SMEE_HOOK_ARG_$i")
 }}
 [System.IO.File]::WriteAllText("{observed_for_powershell}", ($values -join "`n"))
 "#
@@ -597,7 +1194,7 @@ for ($i = 1; $i -le [int]$env:GIT_SMEE_HOOK_ARGC; $i++) {{
     );
     test_repo.write_config(&format!("[[commit-msg]]\ncommand = {command:?}\n"));
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("install")
         .assert()
@@ -614,6 +1211,7 @@ for ($i = 1; $i -le [int]$env:GIT_SMEE_HOOK_ARGC; $i++) {{
     let sh = git_for_windows_sh(&test_repo);
     let status = StdCommand::new(sh)
         .current_dir(&test_repo.path)
+        .env(common::TRUST_ALL_ENV, "1")
         .arg(&hook)
         .args(args)
         .status()
@@ -640,7 +1238,7 @@ fn given_installed_commit_msg_when_git_commit_runs_then_git_invokes_hook_with_me
     };
     test_repo.write_config(&format!("[[commit-msg]]\ncommand = {command:?}\n"));
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("install")
         .assert()
@@ -671,7 +1269,7 @@ fn given_installed_pre_push_when_git_push_runs_then_git_streams_ref_updates_to_h
     let command = stdin_capture_command(&observed);
     test_repo.write_config(&format!("[[pre-push]]\ncommand = {command:?}\n"));
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("install")
         .assert()
@@ -706,106 +1304,331 @@ fn given_installed_pre_push_when_git_push_runs_then_git_streams_ref_updates_to_h
     );
 }
 
+#[cfg(unix)]
 #[test]
-fn given_invalid_hook_when_run_then_user_friendly_error() {
+fn given_failing_pre_commit_when_git_commit_runs_then_commit_is_blocked() {
     let test_repo = common::TestRepo::default();
-
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
-    cmd.current_dir(&test_repo.path)
-        .args(["run", "not-a-hook"])
+    test_repo.write_config("[[pre-commit]]\ncommand = \"echo lint failed >&2; exit 3\"\n");
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
         .assert()
-        .failure()
-        .stderr(
-            predicate::str::contains("Error: Unknown lifecycle phase: not-a-hook")
-                .and(predicate::str::contains("UnknownLifeCyclePhase").not()),
-        );
-}
+        .success();
+    set_git_identity(&test_repo);
+    fs::write(test_repo.path.join("change.txt"), "change\n").expect("write change");
+    git(&test_repo, &["add", "change.txt"]);
 
-#[test]
-fn given_missing_config_when_install_then_user_friendly_error() {
-    let test_repo = common::TestRepo::default();
-    std::fs::remove_file(test_repo.config_path()).expect("Failed to remove config file");
+    let output = git_output(&test_repo, &["commit", "-m", "blocked"]);
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
-    cmd.current_dir(&test_repo.path)
-        .arg("install")
-        .assert()
-        .failure()
-        .stderr(
-            predicate::str::contains("Error: The specified configuration file is missing")
-                .and(predicate::str::contains("MissingFile").not()),
-        );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("lint failed"),
+        "unexpected stderr: {stderr}"
+    );
+    assert!(stderr.contains("[SMEE010]"), "unexpected stderr: {stderr}");
+    assert!(
+        !git_output(&test_repo, &["rev-parse", "--verify", "HEAD"])
+            .status
+            .success(),
+        "a commit was created despite the failing hook"
+    );
+
+    let bypass = git_output(&test_repo, &["commit", "--no-verify", "-m", "bypassed"]);
+    assert!(bypass.status.success());
 }
 
+#[cfg(unix)]
 #[test]
-fn given_directory_config_when_install_then_user_friendly_not_a_file_error() {
+fn given_hooks_for_each_commit_phase_when_git_commit_runs_then_they_fire_in_git_order() {
     let test_repo = common::TestRepo::default();
-    let config_dir = test_repo.path.join("conf.toml");
-    fs::create_dir_all(&config_dir).expect("failed to create config directory");
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "git diff --cached --name-only > staged.txt; echo pre-commit >> flow.log"
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
-    cmd.current_dir(&test_repo.path)
-        .arg("--config")
-        .arg(&config_dir)
+[[commit-msg]]
+command = "grep -q 'add feature' \"$1\" && echo commit-msg >> flow.log"
+
+[[post-commit]]
+command = "echo post-commit >> flow.log"
+"#,
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
         .arg("install")
         .assert()
-        .failure()
-        .stderr(
-            predicate::str::contains(
-                "Error: The specified configuration path exists but is not a regular file",
-            )
-            .and(predicate::str::contains("NotAFile").not()),
-        );
+        .success();
+    set_git_identity(&test_repo);
+    fs::write(test_repo.path.join("feature.txt"), "feature\n").expect("write feature");
+    git(&test_repo, &["add", "feature.txt"]);
+
+    let output = git_output(&test_repo, &["commit", "-m", "add feature"]);
+
+    assert!(
+        output.status.success(),
+        "git commit failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(test_repo.path.join("flow.log")).expect("flow log"),
+        "pre-commit\ncommit-msg\npost-commit\n"
+    );
+    assert_eq!(
+        fs::read_to_string(test_repo.path.join("staged.txt")).expect("staged files"),
+        "feature.txt\n"
+    );
 }
 
+#[cfg(unix)]
 #[test]
-fn given_invalid_config_when_install_then_validation_error_is_reported() {
+fn given_upstream_named_like_a_phase_when_git_rebase_runs_then_pre_rebase_gets_it_as_argument() {
     let test_repo = common::TestRepo::default();
     test_repo.write_config(
         r#"
-[[pre-commit]]
-command = "cargo test"
+[[pre-rebase]]
+command = "printf '%s\\n' \"$1\" > upstream.txt"
 
 [[pre-commit]]
-command = "   "
+command = "touch pre-commit-ran"
 "#,
     );
-
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
-    cmd.current_dir(&test_repo.path)
+    set_git_identity(&test_repo);
+    git(&test_repo, &["commit", "--allow-empty", "-m", "initial"]);
+    git(&test_repo, &["checkout", "-b", "pre-commit"]);
+    git(&test_repo, &["commit", "--allow-empty", "-m", "upstream"]);
+    git(&test_repo, &["checkout", "-b", "feature", "HEAD~1"]);
+    git(&test_repo, &["commit", "--allow-empty", "-m", "feature"]);
+    common::git_smee()
+        .current_dir(&test_repo.path)
         .arg("install")
         .assert()
-        .failure()
-        .stderr(
-            predicate::str::contains(
-                "Error: Hook 'pre-commit' entry #2: command must not be empty",
-            )
-            .and(predicate::str::contains("EmptyCommand").not()),
-        );
-}
+        .success();
 
-#[test]
-fn given_empty_config_when_install_then_no_hooks_present_error_is_reported() {
-    let test_repo = common::TestRepo::default();
-    test_repo.write_config("");
+    let output = git_output(&test_repo, &["rebase", "pre-commit"]);
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
-    cmd.current_dir(&test_repo.path)
-        .arg("install")
-        .assert()
-        .failure()
-        .stderr(
-            predicate::str::contains("Error: No hooks present in the configuration to install")
-                .and(predicate::str::contains("NoHooksPresent").not()),
-        );
+    assert!(
+        output.status.success(),
+        "git rebase failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(test_repo.path.join("upstream.txt")).expect("pre-rebase args"),
+        "pre-commit\n"
+    );
+    assert!(!test_repo.path.join("pre-commit-ran").exists());
 }
 
+#[cfg(unix)]
 #[test]
-fn given_existing_config_when_init_without_force_then_it_refuses_to_overwrite() {
+fn given_installed_pre_push_when_git_push_runs_then_hook_gets_remote_name_and_url() {
     let test_repo = common::TestRepo::default();
-    let original = fs::read_to_string(test_repo.config_path()).unwrap();
-
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    test_repo.write_config(
+        "[[pre-push]]\ncommand = \"printf '%s\\\\n%s\\\\n' \\\"$1\\\" \\\"$2\\\" > push-args.txt\"\n",
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+    git(&test_repo, &["checkout", "-b", "main"]);
+    set_git_identity(&test_repo);
+    git(&test_repo, &["commit", "--allow-empty", "-m", "initial"]);
+    let bare_remote = add_bare_origin(&test_repo);
+
+    let output = git_output(&test_repo, &["push", "origin", "main"]);
+
+    assert!(
+        output.status.success(),
+        "git push failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let url = bare_remote.path().to_str().expect("utf-8 bare remote path");
+    assert_eq!(
+        fs::read_to_string(test_repo.path.join("push-args.txt")).expect("pre-push args"),
+        format!("origin\n{url}\n")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_failing_pre_push_when_git_push_runs_then_remote_is_not_updated() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-push]]\ncommand = \"echo tests failed >&2; exit 1\"\n");
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+    git(&test_repo, &["checkout", "-b", "main"]);
+    set_git_identity(&test_repo);
+    git(&test_repo, &["commit", "--allow-empty", "-m", "initial"]);
+    let bare_remote = add_bare_origin(&test_repo);
+
+    let output = git_output(&test_repo, &["push", "origin", "main"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("tests failed"),
+        "unexpected stderr: {stderr}"
+    );
+    let remote = git2::Repository::open_bare(bare_remote.path()).expect("open bare remote");
+    assert!(
+        remote.find_reference("refs/heads/main").is_err(),
+        "the push reached the remote despite the failing hook"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_linked_worktree_when_installing_from_subdirectory_then_commits_there_run_hooks() {
+    let test_repo = common::TestRepo::default();
+    git(&test_repo, &["config", "user.name", "git-smee test"]);
+    git(
+        &test_repo,
+        &["config", "user.email", "git-smee@example.invalid"],
+    );
+    git(&test_repo, &["commit", "--allow-empty", "-m", "initial"]);
+    let worktree_parent = TempDir::new().expect("failed to create worktree temp dir");
+    let worktree = worktree_parent.path().join("wt");
+    git(
+        &test_repo,
+        &[
+            "worktree",
+            "add",
+            "-b",
+            "wt",
+            worktree.to_str().expect("utf-8 worktree path"),
+        ],
+    );
+    assert!(worktree.join(".git").is_file());
+    fs::write(
+        worktree.join(".git-smee.toml"),
+        "[[pre-commit]]\ncommand = \"touch hook-ran\"\n",
+    )
+    .expect("failed to write worktree config");
+    let subdirectory = worktree.join("src");
+    fs::create_dir(&subdirectory).expect("failed to create worktree subdirectory");
+
+    common::git_smee()
+        .current_dir(&subdirectory)
+        .arg("install")
+        .assert()
+        .success();
+    assert!(test_repo.hooks_path().join("pre-commit").is_file());
+
+    let status = StdCommand::new("git")
+        .current_dir(&worktree)
+        .env(common::TRUST_ALL_ENV, "1")
+        .env("PATH", common::path_with_git_smee())
+        .args(["commit", "--allow-empty", "-m", "from worktree"])
+        .status()
+        .expect("failed to run git commit");
+    assert!(status.success());
+    assert!(worktree.join("hook-ran").is_file());
+}
+
+#[test]
+fn given_invalid_hook_when_run_then_user_friendly_error() {
+    let test_repo = common::TestRepo::default();
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "not-a-hook"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Error: Unknown lifecycle phase: not-a-hook")
+                .and(predicate::str::contains("UnknownLifeCyclePhase").not()),
+        );
+}
+
+#[test]
+fn given_missing_config_when_install_then_user_friendly_error() {
+    let test_repo = common::TestRepo::default();
+    std::fs::remove_file(test_repo.config_path()).expect("Failed to remove config file");
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Error: The specified configuration file is missing")
+                .and(predicate::str::contains("MissingFile").not()),
+        );
+}
+
+#[test]
+fn given_directory_config_when_install_then_user_friendly_not_a_file_error() {
+    let test_repo = common::TestRepo::default();
+    let config_dir = test_repo.path.join("conf.toml");
+    fs::create_dir_all(&config_dir).expect("failed to create config directory");
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("--config")
+        .arg(&config_dir)
+        .arg("install")
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains(
+                "Error: The specified configuration path exists but is not a regular file",
+            )
+            .and(predicate::str::contains("NotAFile").not()),
+        );
+}
+
+#[test]
+fn given_invalid_config_when_install_then_validation_error_is_reported() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "cargo test"
+
+[[pre-commit]]
+command = "   "
+"#,
+    );
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains(
+                "Error: Hook 'pre-commit' entry #2: command must not be empty",
+            )
+            .and(predicate::str::contains("EmptyCommand").not()),
+        );
+}
+
+#[test]
+fn given_empty_config_when_install_then_no_hooks_present_error_is_reported() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("");
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Error: No hooks present in the configuration to install")
+                .and(predicate::str::contains("NoHooksPresent").not()),
+        );
+}
+
+#[test]
+fn given_existing_config_when_init_without_force_then_it_refuses_to_overwrite() {
+    let test_repo = common::TestRepo::default();
+    let original = fs::read_to_string(test_repo.config_path()).unwrap();
+
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("init")
         .assert()
@@ -828,7 +1651,7 @@ fn given_existing_config_when_init_with_force_then_it_overwrites_config() {
     )
     .unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("init")
         .arg("--force")
@@ -845,7 +1668,7 @@ fn given_rust_template_when_init_then_config_parses_and_installs() {
     let test_repo = common::TestRepo::default();
     fs::remove_file(test_repo.config_path()).expect("failed to remove default config");
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .args(["init", "--template", "rust"])
         .assert()
@@ -859,20 +1682,138 @@ fn given_rust_template_when_init_then_config_parses_and_installs() {
             .contains("cargo clippy --workspace --all-targets --all-features -- -D warnings")
     );
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+    test_repo.assert_hooks_installed(vec![LifeCyclePhase::PreCommit, LifeCyclePhase::PrePush]);
+}
+
+#[test]
+fn given_phase_without_wrapper_when_running_manually_then_a_note_suggests_install() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = 'echo checked'\n");
+    let note = "git-smee: note: no pre-commit hook is installed, so Git won't run these commands automatically; run `git smee install`";
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("checked"))
+        .stderr(predicate::str::contains(note));
+
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("install")
         .assert()
         .success();
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("git-smee: note:").not());
+}
+
+#[test]
+fn given_init_with_install_when_run_then_config_is_written_and_hooks_are_installed() {
+    let test_repo = common::TestRepo::default();
+    fs::remove_file(test_repo.config_path()).expect("failed to remove default config");
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["init", "--template", "rust", "--install"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hooks installed successfully."))
+        .stdout(predicate::str::contains("Next steps:").not());
+
+    assert!(test_repo.config_path().exists());
     test_repo.assert_hooks_installed(vec![LifeCyclePhase::PreCommit, LifeCyclePhase::PrePush]);
 }
 
+#[test]
+fn given_init_without_install_when_run_then_next_steps_name_the_install_command() {
+    let test_repo = common::TestRepo::default();
+    fs::remove_file(test_repo.config_path()).expect("failed to remove default config");
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("init")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Next steps:"))
+        .stdout(predicate::str::contains(
+            "Run `git smee install` to install the hook wrappers.",
+        ));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["--config", "configs/smee.toml", "init"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Run `git smee --config "))
+        .stdout(predicate::str::contains("configs/smee.toml install`"));
+    assert!(!test_repo.path.join(".git/hooks/pre-commit").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn given_hooks_path_when_init_then_committable_wrappers_use_git_smee_from_path() {
+    let test_repo = common::TestRepo::default();
+    fs::remove_file(test_repo.config_path()).expect("failed to remove default config");
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["init", "--hooks-path", "--bootstrap-script", "bootstrap.sh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "new clones enable them with `git config core.hooksPath hooks`",
+        ));
+
+    let initialized = fs::read_to_string(test_repo.config_path()).unwrap();
+    assert!(initialized.contains("[settings]\nhooks_path = \"hooks\"\n"));
+    let bootstrap = fs::read_to_string(test_repo.path.join("bootstrap.sh")).unwrap();
+    assert!(bootstrap.ends_with("\ngit config core.hooksPath hooks\n"));
+    let wrapper = test_repo.path.join("hooks").join("pre-commit");
+    let wrapper_contents = fs::read_to_string(&wrapper).unwrap();
+    assert!(wrapper_contents.contains("GIT_SMEE_BIN='git-smee'"));
+    let output = StdCommand::new("git")
+        .current_dir(&test_repo.path)
+        .args(["config", "--local", "core.hooksPath"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hooks");
+
+    let bin_dir = Path::new(cargo::cargo_bin!("git-smee")).parent().unwrap();
+    let path = std::env::join_paths(
+        std::iter::once(bin_dir.to_path_buf())
+            .chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+    )
+    .unwrap();
+    let output = StdCommand::new(&wrapper)
+        .current_dir(&test_repo.path)
+        .env("PATH", path)
+        .env(common::TRUST_ALL_ENV, "1")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Default pre-commit hook"));
+}
+
 #[test]
 fn given_node_pnpm_template_when_init_then_config_parses_and_installs() {
     let test_repo = common::TestRepo::default();
     fs::remove_file(test_repo.config_path()).expect("failed to remove default config");
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .args(["init", "--template", "node-pnpm"])
         .assert()
@@ -882,7 +1823,7 @@ fn given_node_pnpm_template_when_init_then_config_parses_and_installs() {
     assert!(initialized.contains("pnpm lint"));
     assert!(initialized.contains("pnpm test"));
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("install")
         .assert()
@@ -895,7 +1836,7 @@ fn given_generic_template_when_init_then_config_parses_and_installs() {
     let test_repo = common::TestRepo::default();
     fs::remove_file(test_repo.config_path()).expect("failed to remove default config");
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .args(["init", "--template", "generic"])
         .assert()
@@ -905,7 +1846,7 @@ fn given_generic_template_when_init_then_config_parses_and_installs() {
     assert!(initialized.contains("replace me with your pre-commit check"));
     assert!(initialized.contains("./scripts/test"));
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .arg("install")
         .assert()
@@ -918,7 +1859,7 @@ fn given_unknown_template_when_init_then_error_lists_valid_templates() {
     let test_repo = common::TestRepo::default();
     fs::remove_file(test_repo.config_path()).expect("failed to remove default config");
 
-    Command::new(cargo::cargo_bin!("git-smee"))
+    common::git_smee()
         .current_dir(&test_repo.path)
         .args(["init", "--template", "python"])
         .assert()
@@ -939,7 +1880,7 @@ fn given_unmanaged_hook_when_install_without_force_then_it_fails_and_preserves_h
     let unmanaged = "#!/usr/bin/env sh\necho 'custom unmanaged hook'\n";
     fs::write(&pre_commit, unmanaged).unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("install")
         .assert()
@@ -954,36 +1895,122 @@ fn given_unmanaged_hook_when_install_without_force_then_it_fails_and_preserves_h
 }
 
 #[test]
-fn given_managed_hook_when_install_without_force_then_it_overwrites_managed_hook() {
+fn given_phase_names_when_installing_then_only_those_wrappers_are_written() {
     let test_repo = common::TestRepo::default();
-    let pre_commit = test_repo.path.join(".git").join("hooks").join("pre-commit");
-    let stale_managed =
-        format!("#!/usr/bin/env sh\n# {MANAGED_FILE_MARKER}\necho 'stale managed hook'\n");
-    fs::write(&pre_commit, stale_managed).unwrap();
+    test_repo.write_config(
+        "[[pre-commit]]\ncommand = \"echo commit\"\n\n[[pre-push]]\ncommand = \"echo push\"\n",
+    );
+    let hooks = test_repo.hooks_path();
+    let unmanaged = "#!/bin/sh\necho managed elsewhere\n";
+    fs::write(hooks.join("pre-push"), unmanaged).unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
-    cmd.current_dir(&test_repo.path)
-        .arg("install")
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["install", "pre-commit"])
         .assert()
         .success();
 
-    let updated = fs::read_to_string(pre_commit).unwrap();
-    assert!(updated.contains("run pre-commit"));
+    assert!(
+        fs::read_to_string(hooks.join("pre-commit"))
+            .unwrap()
+            .contains(MANAGED_FILE_MARKER)
+    );
+    assert_eq!(
+        fs::read_to_string(hooks.join("pre-push")).unwrap(),
+        unmanaged
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["install", "post-merge"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Cannot install post-merge: the config has no hooks for it [SMEE003]",
+        ));
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["install", "pre-comit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pre-comit"));
 }
 
+#[cfg(unix)]
 #[test]
-fn given_unmanaged_hook_when_install_with_force_then_it_overwrites_hook() {
-    let test_repo = common::TestRepo::default();
-    let pre_commit = test_repo.path.join(".git").join("hooks").join("pre-commit");
-    fs::write(
-        &pre_commit,
-        "#!/usr/bin/env sh\necho 'custom unmanaged hook'\n",
-    )
-    .unwrap();
+fn given_unmanaged_hook_when_install_with_chain_then_commits_run_both_and_uninstall_restores_it() {
+    use std::os::unix::fs::PermissionsExt;
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
-    cmd.current_dir(&test_repo.path)
-        .arg("install")
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = \"touch smee-ran\"\n");
+    git(&test_repo, &["config", "user.name", "git-smee test"]);
+    git(
+        &test_repo,
+        &["config", "user.email", "git-smee@example.invalid"],
+    );
+    let pre_commit = test_repo.hooks_path().join("pre-commit");
+    let unmanaged = "#!/bin/sh\ntouch original-ran\n";
+    fs::write(&pre_commit, unmanaged).unwrap();
+    fs::set_permissions(&pre_commit, fs::Permissions::from_mode(0o755)).unwrap();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["install", "--chain"])
+        .assert()
+        .success();
+    let status = StdCommand::new("git")
+        .current_dir(&test_repo.path)
+        .env(common::TRUST_ALL_ENV, "1")
+        .env("PATH", common::path_with_git_smee())
+        .args(["commit", "--allow-empty", "-m", "chained"])
+        .status()
+        .expect("failed to run git commit");
+
+    assert!(status.success());
+    assert!(test_repo.path.join("smee-ran").is_file());
+    assert!(test_repo.path.join("original-ran").is_file());
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("uninstall")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Restored ")
+                .and(predicate::str::contains("pre-commit.pre-smee")),
+        );
+    assert_eq!(fs::read_to_string(&pre_commit).unwrap(), unmanaged);
+}
+
+#[test]
+fn given_managed_hook_when_install_without_force_then_it_overwrites_managed_hook() {
+    let test_repo = common::TestRepo::default();
+    let pre_commit = test_repo.path.join(".git").join("hooks").join("pre-commit");
+    let stale_managed =
+        format!("#!/usr/bin/env sh\n# {MANAGED_FILE_MARKER}\necho 'stale managed hook'\n");
+    fs::write(&pre_commit, stale_managed).unwrap();
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+
+    let updated = fs::read_to_string(pre_commit).unwrap();
+    assert!(updated.contains("run pre-commit"));
+}
+
+#[test]
+fn given_unmanaged_hook_when_install_with_force_then_it_overwrites_hook() {
+    let test_repo = common::TestRepo::default();
+    let pre_commit = test_repo.path.join(".git").join("hooks").join("pre-commit");
+    fs::write(
+        &pre_commit,
+        "#!/usr/bin/env sh\necho 'custom unmanaged hook'\n",
+    )
+    .unwrap();
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("install")
         .arg("--force")
         .assert()
         .success();
@@ -1003,7 +2030,7 @@ command = "echo custom"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&custom_config)
@@ -1035,7 +2062,7 @@ command = "echo from-invocation-config"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&nested_dir)
         .arg("--config")
         .arg("configs/custom.toml")
@@ -1068,7 +2095,7 @@ command = "echo env"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("install")
         .env("GIT_SMEE_CONFIG", &env_config)
@@ -1098,7 +2125,7 @@ command = "echo non-utf8-env"
     )
     .expect("failed to write non-UTF-8 config path");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("install")
         .env("GIT_SMEE_CONFIG", &config_path)
@@ -1133,7 +2160,7 @@ command = "echo from-invocation-config"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&nested_dir)
         .arg("install")
         .env("GIT_SMEE_CONFIG", "configs/env-config.toml")
@@ -1172,7 +2199,7 @@ command = "echo from-tilde-cli"
     )
     .unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .env("HOME", fake_home.path())
         .arg("--config")
@@ -1202,7 +2229,7 @@ command = "echo literal-tilde-user"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .env("HOME", fake_home.path())
         .arg("--config")
@@ -1237,7 +2264,7 @@ command = "echo from-tilde-env"
     )
     .unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .env("HOME", fake_home.path())
         .env(
@@ -1269,7 +2296,7 @@ command = "echo literal-tilde-env"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .env("HOME", fake_home.path())
         .env("GIT_SMEE_CONFIG", "~alice/env.toml")
@@ -1302,7 +2329,7 @@ command = "echo cli"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&cli_config)
@@ -1327,7 +2354,7 @@ command = "echo from-custom-config"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&custom_config)
@@ -1349,7 +2376,7 @@ command = "echo from-env-config"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("run")
         .arg("pre-commit")
@@ -1379,7 +2406,7 @@ command = "echo from-invocation-config"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&nested_dir)
         .arg("--config")
         .arg("configs/run-custom.toml")
@@ -1399,7 +2426,7 @@ fn given_successful_hook_when_running_then_cli_stdout_contains_hook_output_and_s
     };
     test_repo.write_config(&format!("[[pre-commit]]\ncommand = {command:?}\n"));
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "pre-commit"])
         .assert()
@@ -1423,7 +2450,7 @@ fn given_sequential_hook_failure_when_running_then_summary_identifies_failing_co
         "[[pre-commit]]\ncommand = \"echo before\"\n\n[[pre-commit]]\ncommand = {fail_command:?}\n\n[[pre-commit]]\ncommand = \"echo skipped\"\n"
     ));
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "pre-commit"])
         .assert()
@@ -1440,6 +2467,32 @@ fn given_sequential_hook_failure_when_running_then_summary_identifies_failing_co
         );
 }
 
+#[test]
+fn given_stdout_is_not_a_terminal_when_running_parallel_hooks_then_all_run_sequentially_in_config_order()
+ {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\ncommand = \"echo one\"\nparallel = true\n\n[[pre-commit]]\ncommand = \"echo two\"\n\n[[pre-commit]]\ncommand = \"echo three\"\nparallel = true\n",
+    );
+
+    let output = common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "sequential: 3 attempted, 0 failed",
+        ))
+        .stdout(predicate::str::contains("parallel: 0 attempted, 0 failed"))
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let position = |word: &str| stdout.find(&format!("{word}\n")).unwrap();
+    assert!(position("one") < position("two") && position("two") < position("three"));
+    assert!(!stdout.contains("[hook-1]"));
+}
+
 #[test]
 fn given_parallel_hooks_when_running_then_summary_reports_parallel_phase() {
     let test_repo = common::TestRepo::default();
@@ -1449,10 +2502,10 @@ fn given_parallel_hooks_when_running_then_summary_reports_parallel_phase() {
         "printf 'parallel-ok\\n'"
     };
     test_repo.write_config(&format!(
-        "[[pre-commit]]\ncommand = {ok_command:?}\nparallel_execution_allowed = true\n\n[[pre-commit]]\ncommand = {ok_command:?}\nparallel_execution_allowed = true\n"
+        "[settings]\nparallel_without_tty = true\n\n[[pre-commit]]\ncommand = {ok_command:?}\nparallel_execution_allowed = true\n\n[[pre-commit]]\ncommand = {ok_command:?}\nparallel_execution_allowed = true\n"
     ));
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "pre-commit"])
         .assert()
@@ -1469,10 +2522,10 @@ fn given_parallel_hook_failure_when_running_then_summary_is_deterministic() {
     let test_repo = common::TestRepo::default();
     let fail_command = if cfg!(windows) { "exit /b 4" } else { "exit 4" };
     test_repo.write_config(&format!(
-        "[[pre-commit]]\ncommand = \"echo sequential-ok\"\n\n[[pre-commit]]\ncommand = {fail_command:?}\nparallel_execution_allowed = true\n\n[[pre-commit]]\ncommand = \"echo maybe-in-flight\"\nparallel_execution_allowed = true\n"
+        "[settings]\nparallel_without_tty = true\n\n[[pre-commit]]\ncommand = \"echo sequential-ok\"\n\n[[pre-commit]]\ncommand = {fail_command:?}\nparallel_execution_allowed = true\n\n[[pre-commit]]\ncommand = \"echo maybe-in-flight\"\nparallel_execution_allowed = true\n"
     ));
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "pre-commit"])
         .assert()
@@ -1486,6 +2539,368 @@ fn given_parallel_hook_failure_when_running_then_summary_is_deterministic() {
         );
 }
 
+#[cfg(unix)]
+#[test]
+fn given_multiple_phases_when_running_then_each_phase_runs_in_order_with_combined_summary() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "echo pre-commit-ran"
+
+[[commit-msg]]
+command = "test \"$1\" = \"COMMIT_EDITMSG\" && echo commit-msg-ran"
+"#,
+    );
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit", "commit-msg", "--", "COMMIT_EDITMSG"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Hook summary: pre-commit")
+                .and(predicate::str::contains("Hook summary: commit-msg"))
+                .and(predicate::str::contains("commit-msg-ran"))
+                .and(predicate::str::contains(
+                    "Run summary: 2 phases, 2 completed, 0 skipped, 0 failed",
+                )),
+        );
+}
+
+#[test]
+fn given_hook_arg_named_like_a_phase_when_running_then_it_is_forwarded_not_run() {
+    let test_repo = common::TestRepo::default();
+    let observed = test_repo.path.join("pre-rebase-upstream.txt");
+    let command = if cfg!(windows) {
+        format!("echo %1> {}", observed.display())
+    } else {
+        format!("printf '%s\\n' \"$1\" > '{}'", observed.display())
+    };
+    test_repo.write_config(&format!(
+        "[[pre-rebase]]\ncommand = {command:?}\n\n[[pre-commit]]\ncommand = \"echo pre-commit-ran\"\n"
+    ));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-rebase", "--", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("pre-commit-ran")
+                .not()
+                .and(predicate::str::contains("Run summary").not()),
+        );
+
+    assert_eq!(
+        normalize_test_newlines(&fs::read_to_string(observed).expect("pre-rebase side effect")),
+        "pre-commit\n"
+    );
+}
+
+#[test]
+fn given_mistyped_second_phase_without_double_dash_when_running_then_run_fails_and_nothing_runs() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\ncommand = \"echo pre-commit-ran\"\n\n[[commit-msg]]\ncommand = \"echo commit-msg-ran\"\n",
+    );
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit", "commit-mesg"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("pre-commit-ran").not())
+        .stderr(predicate::str::contains(
+            "Unknown lifecycle phase: commit-mesg",
+        ));
+}
+
+#[test]
+fn given_failing_first_phase_when_running_multiple_phases_then_later_phases_are_skipped() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 3" } else { "exit 3" };
+    test_repo.write_config(&format!(
+        "[[pre-commit]]\ncommand = {fail_command:?}\n\n[[commit-msg]]\ncommand = \"echo commit-msg-ran\"\n"
+    ));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit", "commit-msg"])
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains("Run summary: 2 phases, 1 completed, 1 skipped, 1 failed")
+                .and(predicate::str::contains("  - pre-commit: failed"))
+                .and(predicate::str::contains("  - commit-msg: skipped"))
+                .and(predicate::str::contains("Hook summary: commit-msg").not()),
+        );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_commit_hooks_when_simulating_commit_then_phases_run_without_creating_commit() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "echo pre-commit-ran"
+
+[[prepare-commit-msg]]
+command = "test \"$2\" = message && echo 'Signed-off-by: sim' >> \"$1\""
+
+[[commit-msg]]
+command = "grep -q 'simulated subject' \"$1\""
+"#,
+    );
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["simulate", "commit", "-m", "simulated subject"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("pre-commit-ran")
+                .and(predicate::str::contains(
+                    "Run summary: 3 phases, 3 completed, 0 skipped, 0 failed",
+                ))
+                .and(predicate::str::contains(
+                    "Simulated commit message:\nsimulated subject\nSigned-off-by: sim",
+                )),
+        );
+
+    let head = StdCommand::new("git")
+        .current_dir(&test_repo.path)
+        .args(["rev-parse", "--verify", "HEAD"])
+        .output()
+        .unwrap();
+    assert!(!head.status.success(), "simulate must not create a commit");
+    assert!(!test_repo.path.join(".git/COMMIT_EDITMSG").exists());
+}
+
+#[test]
+fn given_failing_commit_msg_hook_when_simulating_commit_then_it_fails() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 5" } else { "exit 5" };
+    test_repo.write_config(&format!("[[commit-msg]]\ncommand = {fail_command:?}\n"));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["simulate", "commit", "-m", "msg"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Hook summary: commit-msg"))
+        .stderr(predicate::str::contains("exit code 5"));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_same_command_in_two_phases_when_running_both_then_it_runs_once() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\ncommand = 'echo fmt-ran'\n\n[[pre-push]]\ncommand = 'echo fmt-ran'\n\n[[pre-push]]\ncommand = 'echo push-ran'\n",
+    );
+
+    let output = common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit", "pre-push"])
+        .write_stdin("")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("push-ran")
+                .and(predicate::str::contains(
+                    "total: 1 attempted, 1 skipped (1 deduplicated), 0 failed",
+                ))
+                .and(predicate::str::contains(
+                    "sequential command #1: deduplicated (already passed in pre-commit)",
+                )),
+        )
+        .get_output()
+        .stdout
+        .clone();
+    assert_eq!(
+        String::from_utf8(output)
+            .unwrap()
+            .matches("fmt-ran")
+            .count(),
+        1
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_metrics_file_config_when_running_then_stats_and_metrics_file_report_the_runs() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'lint'\ncommand = 'true'\n\n[[pre-commit]]\nname = 'flaky'\ncommand = 'exit 4'\nseverity = 'warning'\n",
+    );
+    git(
+        &test_repo,
+        &["config", "smee.metricsFile", "metrics/hooks.prom"],
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No hook runs recorded"));
+    for _ in 0..2 {
+        common::git_smee()
+            .current_dir(&test_repo.path)
+            .args(["run", "pre-commit"])
+            .assert()
+            .success();
+    }
+
+    let metrics = fs::read_to_string(test_repo.path.join("metrics/hooks.prom")).unwrap();
+    assert!(metrics.contains(
+        "git_smee_hook_runs_total{phase=\"pre-commit\",hook=\"lint\",status=\"success\"} 2"
+    ));
+    assert!(
+        metrics.contains("git_smee_hook_failures_total{phase=\"pre-commit\",hook=\"flaky\"} 2")
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["stats", "--format", "prometheus"])
+        .assert()
+        .success()
+        .stdout(metrics);
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["stats"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("phase       hook   runs  failures")
+                .and(predicate::str::is_match(r"pre-commit  flaky  2     2 ").unwrap()),
+        );
+}
+
+#[cfg(unix)]
+fn commit_two_files(test_repo: &common::TestRepo) {
+    fs::write(test_repo.path.join("one.txt"), "1").unwrap();
+    fs::write(test_repo.path.join("two.txt"), "2").unwrap();
+    git(test_repo, &["add", "one.txt", "two.txt"]);
+    git(
+        test_repo,
+        &[
+            "-c",
+            "user.name=Notify Test",
+            "-c",
+            "user.email=notify@example.com",
+            "commit",
+            "--quiet",
+            "-m",
+            "add numbers",
+        ],
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_commit_when_notifying_then_short_sha_subject_and_files_are_printed() {
+    let test_repo = common::TestRepo::default();
+    commit_two_files(&test_repo);
+    let short_sha = StdCommand::new("git")
+        .current_dir(&test_repo.path)
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .unwrap()
+        .stdout;
+    let short_sha = String::from_utf8(short_sha).unwrap();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env_remove("GIT_SMEE_NOTIFY_WEBHOOK")
+        .args(["notify", "commit"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{} add numbers\n  2 files changed: one.txt, two.txt\n",
+            short_sha.trim()
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_webhook_when_notifying_commit_then_commit_metadata_is_posted_as_json() {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+
+    if StdCommand::new("curl").arg("--version").output().is_err() {
+        eprintln!("skipping: curl is not installed");
+        return;
+    }
+    let test_repo = common::TestRepo::default();
+    commit_two_files(&test_repo);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hooks/commit", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(length) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                content_length = length.trim().parse().unwrap();
+            }
+            if line.trim().is_empty() {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        (head, body)
+    });
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["notify", "commit", "--webhook", &url])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("warning").not());
+
+    let (head, body) = server.join().unwrap();
+    assert!(head.starts_with("POST /hooks/commit "), "{head}");
+    assert!(head.contains("Content-Type: application/json"), "{head}");
+    let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(payload["event"], "commit");
+    assert_eq!(payload["subject"], "add numbers");
+    assert_eq!(payload["author"], "Notify Test");
+    assert_eq!(payload["files_changed"], 2);
+    assert_eq!(payload["files"], serde_json::json!(["one.txt", "two.txt"]));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_unreachable_webhook_when_notifying_commit_then_it_warns_and_succeeds() {
+    let test_repo = common::TestRepo::default();
+    commit_two_files(&test_repo);
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("GIT_SMEE_NOTIFY_WEBHOOK", "http://127.0.0.1:9/secret-token")
+        .args(["notify", "commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("add numbers"))
+        .stderr(
+            predicate::str::contains("git-smee: warning:")
+                .and(predicate::str::contains("secret-token").not()),
+        );
+}
+
 #[cfg(unix)]
 #[test]
 fn given_hook_args_when_running_then_command_receives_positional_args() {
@@ -1497,7 +2912,7 @@ command = "test \"$1\" = \"COMMIT_EDITMSG\""
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "commit-msg", "COMMIT_EDITMSG"])
         .assert()
@@ -1515,7 +2930,7 @@ command = "if \"%1\"==\"COMMIT_EDITMSG\" (if \"%2\"==\"metadata\" (exit /b 0) el
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "commit-msg", "COMMIT_EDITMSG", "metadata"])
         .assert()
@@ -1543,7 +2958,7 @@ command = "echo from-invocation-env-config"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&nested_dir)
         .arg("run")
         .arg("pre-commit")
@@ -1577,7 +2992,7 @@ fn given_hook_args_when_running_then_command_receives_env_arg_contract() {
         "[[commit-msg]]\ncommand = {assertion_command:?}\n"
     ));
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "commit-msg", "alpha", "beta"])
         .assert()
@@ -1598,7 +3013,7 @@ fn given_stdin_driven_hook_with_multiple_commands_when_running_then_each_command
 
     let stdin_payload = "refs/heads/main 123 refs/remotes/origin/main 456\nrefs/heads/feature abc refs/remotes/origin/feature def\n";
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "pre-push"])
         .write_stdin(stdin_payload)
@@ -1620,7 +3035,7 @@ fn given_oversized_hook_stdin_when_running_then_cli_rejects_with_clear_error() {
     let test_repo = common::TestRepo::default();
     test_repo.write_config("[[pre-push]]\ncommand = \"true\"\n");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .env("GIT_SMEE_HOOK_STDIN_LIMIT_BYTES", "4")
         .args(["run", "pre-push"])
@@ -1638,7 +3053,7 @@ fn given_hook_command_ignores_large_stdin_when_running_then_hook_succeeds() {
     let command = if cfg!(windows) { "exit /b 0" } else { "true" };
     test_repo.write_config(&format!("[[pre-push]]\ncommand = {command:?}\n"));
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "pre-push"])
         .write_stdin(vec![b'x'; 128 * 1024])
@@ -1654,7 +3069,7 @@ fn given_proc_receive_hook_when_running_then_stdin_is_inherited_by_command() {
     test_repo.write_config(&format!("[[proc-receive]]\ncommand = {command:?}\n"));
     let stdin_payload = "version=1\0push-options\n0000\n";
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .args(["run", "proc-receive"])
         .write_stdin(stdin_payload)
@@ -1690,7 +3105,7 @@ fn given_inherited_hook_arg_env_when_running_then_stale_values_are_not_visible()
         "[[commit-msg]]\ncommand = {assertion_command:?}\n"
     ));
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .env("GIT_SMEE_HOOK_ARG_2", "stale-parent-value")
         .args(["run", "commit-msg", "alpha"])
@@ -1708,7 +3123,7 @@ command = "exit 1"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("run")
         .arg("pre-commit")
@@ -1721,8 +3136,116 @@ command = "exit 1"
 }
 
 #[test]
-fn given_bare_repo_when_running_then_hook_executes() {
-    let bare_repo = TempDir::new().expect("failed to create bare repo temp dir");
+fn given_env_snapshot_setting_when_hook_fails_then_snapshot_follows_summary() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        r#"
+[settings]
+env_snapshot = true
+
+[[pre-commit]]
+command = "exit 1"
+requires = ["git", "git-smee-no-such-tool"]
+on_missing = "skip"
+
+[[pre-commit]]
+command = "exit 1"
+requires = ["git"]
+"#,
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains("Environment snapshot:")
+                .and(predicate::str::contains("  platform: "))
+                .and(predicate::str::contains("  PATH: "))
+                .and(predicate::str::is_match("  git: [0-9.]+ at ").unwrap())
+                .and(predicate::str::contains(
+                    "  git-smee-no-such-tool: not found on PATH",
+                )),
+        );
+
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "exit 1"
+"#,
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Environment snapshot:").not());
+}
+
+#[test]
+fn given_failures_when_running_then_errors_end_with_stable_codes() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 1" } else { "exit 1" };
+    test_repo.write_config(&format!("[[pre-commit]]\ncommand = {fail_command:?}\n"));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Error: Hook execution failed with exit code 1 [SMEE010]",
+        ));
+
+    std::fs::remove_file(test_repo.config_path()).expect("failed to remove config");
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("[SMEE001]"));
+}
+
+#[test]
+fn given_messages_dir_when_hook_fails_then_help_comes_from_locale_catalog() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 1" } else { "exit 1" };
+    test_repo.write_config(&format!(
+        "[settings]\nmessages_dir = '.git-smee/messages'\n\n[[pre-commit]]\ncommand = {fail_command:?}\n"
+    ));
+    let messages_dir = test_repo.path.join(".git-smee/messages");
+    fs::create_dir_all(&messages_dir).expect("failed to create messages dir");
+    fs::write(
+        messages_dir.join("de.toml"),
+        "SMEE010 = 'Behebe den gemeldeten Fehler und versuche es erneut.'\n",
+    )
+    .expect("failed to write catalog");
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("GIT_SMEE_LOCALE", "de_DE.UTF-8")
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "[SMEE010]\nhelp: Behebe den gemeldeten Fehler und versuche es erneut.",
+        ));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("GIT_SMEE_LOCALE", "fr")
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "help: Fix what the failing command reported and try again",
+        ));
+}
+
+#[test]
+fn given_bare_repo_when_running_then_hook_executes() {
+    let bare_repo = TempDir::new().expect("failed to create bare repo temp dir");
     git2::Repository::init_bare(bare_repo.path()).expect("failed to init bare repo");
     fs::write(
         bare_repo.path().join(".git-smee.toml"),
@@ -1733,7 +3256,7 @@ command = "echo bare-run"
     )
     .expect("failed to write config");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(bare_repo.path())
         .args(["run", "pre-receive"])
         .assert()
@@ -1773,7 +3296,7 @@ command = "echo custom"
 fn given_default_config_when_installing_then_hook_script_keeps_portable_relative_path() {
     let test_repo = common::TestRepo::default();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("install")
         .assert()
@@ -1800,7 +3323,7 @@ fn given_default_config_when_installing_then_hook_script_keeps_portable_relative
 fn given_default_config_alias_when_installing_then_hook_script_keeps_portable_relative_path() {
     let test_repo = common::TestRepo::default();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg("./.git-smee.toml")
@@ -1832,7 +3355,7 @@ fn given_equivalent_default_config_from_subdir_when_installing_then_hook_script_
     let nested_dir = test_repo.path.join("a/b");
     fs::create_dir_all(&nested_dir).expect("failed to create nested invocation dir");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&nested_dir)
         .arg("--config")
         .arg("../../.git-smee.toml")
@@ -1868,7 +3391,7 @@ command = "echo custom"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&custom_config)
@@ -1903,7 +3426,7 @@ command = "true"
 "#,
     );
 
-    let mut install = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut install = common::git_smee();
     install
         .current_dir(&test_repo.path)
         .arg("install")
@@ -1913,6 +3436,7 @@ command = "true"
     let hook_path = test_repo.path.join(".git/hooks/pre-commit");
     let mut hook = Command::new(hook_path);
     hook.current_dir(&test_repo.path)
+        .env(common::TRUST_ALL_ENV, "1")
         .env("PATH", "/usr/bin:/bin")
         .assert()
         .success();
@@ -1931,7 +3455,7 @@ command = "true"
 "#,
     );
 
-    let mut install = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut install = common::git_smee();
     install
         .current_dir(&test_repo.path)
         .arg("--config")
@@ -1943,6 +3467,7 @@ command = "true"
     let hook_path = test_repo.path.join(".git/hooks/pre-commit");
     let mut hook = Command::new(hook_path);
     hook.current_dir(&test_repo.path)
+        .env(common::TRUST_ALL_ENV, "1")
         .env("PATH", "/usr/bin:/bin")
         .assert()
         .success();
@@ -1954,7 +3479,7 @@ fn given_custom_config_path_when_initializing_then_init_writes_requested_file()
     fs::remove_file(test_repo.config_path()).expect("failed to remove default config");
     let custom_config_path = test_repo.path.join("configs/init-config.toml");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&custom_config_path)
@@ -1976,7 +3501,7 @@ fn given_managed_custom_config_when_init_without_force_then_it_refuses_to_overwr
     );
     let original = fs::read_to_string(&managed_custom).expect("failed to read original config");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&managed_custom)
@@ -1999,7 +3524,7 @@ fn given_custom_config_directory_when_init_then_user_friendly_not_a_file_error()
     let config_dir = test_repo.path.join("configs/directory-target.toml");
     fs::create_dir_all(&config_dir).expect("failed to create config directory");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&config_dir)
@@ -2022,7 +3547,7 @@ fn given_custom_config_directory_when_init_with_force_then_user_friendly_not_a_f
     let config_dir = test_repo.path.join("configs/force-directory-target.toml");
     fs::create_dir_all(&config_dir).expect("failed to create config directory");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&config_dir)
@@ -2053,7 +3578,7 @@ command = "echo nested-install"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&subdir)
         .arg("--config")
         .arg("configs/custom.toml")
@@ -2082,7 +3607,7 @@ command = "echo nested-env-install"
 "#,
     );
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&subdir)
         .env("GIT_SMEE_CONFIG", "configs/env.toml")
         .arg("install")
@@ -2104,7 +3629,7 @@ fn given_relative_config_flag_when_initializing_from_subdir_then_cli_writes_from
     fs::create_dir_all(&subdir).unwrap();
     let custom_config_path = test_repo.path.join("subdir/configs/init.toml");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&subdir)
         .arg("--config")
         .arg("configs/init.toml")
@@ -2124,7 +3649,7 @@ fn given_relative_config_env_when_initializing_from_subdir_then_cli_writes_from_
     fs::create_dir_all(&subdir).unwrap();
     let custom_config_path = test_repo.path.join("subdir/configs/init-env.toml");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&subdir)
         .env("GIT_SMEE_CONFIG", "configs/init-env.toml")
         .arg("init")
@@ -2147,7 +3672,7 @@ fn given_managed_custom_config_when_init_without_force_then_it_refuses_managed_o
     )
     .unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&custom_config_path)
@@ -2172,7 +3697,7 @@ fn given_unmanaged_custom_config_when_init_without_force_then_it_refuses_unmanag
     )
     .unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&custom_config_path)
@@ -2197,7 +3722,7 @@ fn given_managed_custom_config_when_init_with_force_then_it_overwrites_config()
     )
     .unwrap();
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(&test_repo.path)
         .arg("--config")
         .arg(&custom_config_path)
@@ -2216,7 +3741,7 @@ fn given_bare_repo_when_initializing_then_init_writes_default_config() {
     let bare_repo = TempDir::new().expect("failed to create bare repo temp dir");
     git2::Repository::init_bare(bare_repo.path()).expect("failed to init bare repo");
 
-    let mut cmd = Command::new(cargo::cargo_bin!("git-smee"));
+    let mut cmd = common::git_smee();
     cmd.current_dir(bare_repo.path())
         .arg("init")
         .assert()
@@ -2224,3 +3749,1650 @@ fn given_bare_repo_when_initializing_then_init_writes_default_config() {
 
     assert!(bare_repo.path().join(".git-smee.toml").exists());
 }
+
+fn serve_responses(stdout: &[u8]) -> Vec<serde_json::Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn serve_response(responses: &[serde_json::Value], id: i64) -> &serde_json::Value {
+    responses
+        .iter()
+        .find(|response| response["id"] == id)
+        .unwrap_or_else(|| panic!("no response with id {id} in {responses:?}"))
+}
+
+#[test]
+fn given_serve_requests_when_piped_then_each_gets_a_json_rpc_response() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\nname = 'greet'\ncommand = 'echo hello-from-hook'\n");
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"validate"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"validate","params":{"text":"[[pre-commit]]\ncommand = ''\n"}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"plan","params":{"phase":"pre-commit"}}"#,
+        r#"{"jsonrpc":"2.0","id":4,"method":"run","params":{"phase":"pre-commit"}}"#,
+        r#"{"jsonrpc":"2.0","id":5,"method":"format-disk"}"#,
+        "not json",
+    ]
+    .join("\n");
+
+    let output = common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("serve")
+        .write_stdin(requests)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("hello-from-hook"))
+        .get_output()
+        .stdout
+        .clone();
+
+    assert!(!String::from_utf8_lossy(&output).contains("hello-from-hook"));
+    let responses = serve_responses(&output);
+    assert_eq!(responses.len(), 6);
+    assert_eq!(serve_response(&responses, 1)["result"]["valid"], true);
+    let invalid = &serve_response(&responses, 2)["result"];
+    assert_eq!(invalid["valid"], false);
+    assert!(
+        invalid["error"]
+            .as_str()
+            .unwrap()
+            .contains("command must not be empty")
+    );
+    let plan = &serve_response(&responses, 3)["result"];
+    assert_eq!(plan["commands"][0]["label"], "greet");
+    assert_eq!(plan["commands"][0]["verdict"]["kind"], "run");
+    let run = &serve_response(&responses, 4)["result"];
+    assert_eq!(run["success"], true);
+    assert_eq!(run["cancelled"], false);
+    assert_eq!(serve_response(&responses, 5)["error"]["code"], -32601);
+    assert!(
+        responses
+            .iter()
+            .any(|response| response["id"].is_null() && response["error"]["code"] == -32700)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_running_serve_run_when_cancelled_then_it_stops_and_reports_cancellation() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-push]]\ncommand = 'sleep 30'\n");
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":"slow","method":"run","params":{"phase":"pre-push"}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"cancel","params":{"id":"slow"}}"#,
+    ]
+    .join("\n");
+    let started = std::time::Instant::now();
+
+    let output = common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("serve")
+        .write_stdin(requests)
+        .timeout(std::time::Duration::from_secs(20))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+    let responses = serve_responses(&output);
+    assert_eq!(serve_response(&responses, 2)["result"]["cancelled"], true);
+    let run = responses
+        .iter()
+        .find(|response| response["id"] == "slow")
+        .expect("run response");
+    assert_eq!(run["result"]["success"], false);
+    assert_eq!(run["result"]["cancelled"], true);
+}
+
+#[cfg(unix)]
+#[test]
+fn given_events_socket_when_running_then_progress_events_are_streamed() {
+    use std::{io::Read, os::unix::net::UnixListener};
+
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\nname = 'greet'\ncommand = 'echo hello'\n");
+    let socket_dir = TempDir::new().unwrap();
+    let socket_path = socket_dir.path().join("events.sock");
+    let listener = UnixListener::bind(&socket_path).unwrap();
+    let client = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut events = String::new();
+        stream.read_to_string(&mut events).unwrap();
+        events
+    });
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("run")
+        .arg("--events-socket")
+        .arg(&socket_path)
+        .arg("pre-commit")
+        .assert()
+        .success();
+
+    let events: Vec<serde_json::Value> = client
+        .join()
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let kinds: Vec<_> = events.iter().map(|event| event["event"].clone()).collect();
+    assert_eq!(
+        kinds,
+        [
+            "run_started",
+            "phase_started",
+            "command_started",
+            "command_finished",
+            "phase_finished",
+            "run_finished"
+        ]
+    );
+    assert_eq!(events[0]["phases"], serde_json::json!(["pre-commit"]));
+    assert!(events[0]["run_id"].is_string());
+    assert_eq!(events[2]["label"], "greet");
+    assert_eq!(events[3]["outcome"], "success");
+    assert_eq!(events[5]["success"], true);
+}
+
+#[test]
+fn given_unreachable_events_socket_when_running_then_hooks_still_run_with_a_warning() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = 'echo hello'\n");
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env(
+            "GIT_SMEE_EVENTS_SOCKET",
+            test_repo.path.join("no-such.sock"),
+        )
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "git-smee: warning: cannot send progress events to",
+        ));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn given_sandbox_requested_without_bwrap_when_running_then_hooks_run_after_a_warning() {
+    if StdCommand::new("bwrap").arg("--version").output().is_ok() {
+        return;
+    }
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = 'echo sandboxed-or-not'\n");
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("GIT_SMEE_SANDBOX", "1")
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sandboxed-or-not"))
+        .stderr(predicate::str::contains(
+            "git-smee: warning: sandboxing was requested but bwrap is not installed or not on PATH; running pre-commit hooks without a sandbox",
+        ));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn given_hook_without_network_when_running_then_it_only_sees_loopback() {
+    let namespaces_work = StdCommand::new("unshare")
+        .args(["--net", "--map-root-user", "true"])
+        .status()
+        .is_ok_and(|status| status.success());
+    if !namespaces_work {
+        return;
+    }
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\ncommand = \"if grep -v -e 'lo:' -e '|' /proc/net/dev; then exit 1; fi; echo offline\"\nnetwork = false\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("offline"));
+}
+
+#[test]
+fn given_phase_when_asking_why_then_each_command_gets_a_verdict_with_its_reason() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'fmt'\ncommand = 'cargo fmt --check'\n\n[[pre-commit]]\ncommand = 'node lint.js'\nrequires = ['git-smee-no-such-tool']\non_missing = 'skip'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["why", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("pre-commit: 1 of 2 command(s) would run")
+                .and(predicate::str::contains("git: does not trigger pre-commit: no wrapper at"))
+                .and(predicate::str::contains("- fmt (cargo <args redacted>): runs\n"))
+                .and(predicate::str::contains(
+                    "- hook-2 (node <args redacted>): skipped: requires git-smee-no-such-tool: not found on PATH (on_missing = \"skip\")",
+                )),
+        );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["disable", "pre-commit"])
+        .assert()
+        .success();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["why", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(
+                "pre-commit: skipped: disabled by git config smee.pre-commit.disabled",
+            )
+            .and(predicate::str::contains("git: triggers git-smee through"))
+            .and(predicate::str::contains(
+                "- fmt (cargo <args redacted>): skipped: disabled by git config",
+            )),
+        );
+}
+
+#[test]
+fn given_disabled_repository_when_running_hook_then_commands_are_skipped() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 5" } else { "exit 5" };
+    test_repo.write_config(&format!("[[pre-commit]]\ncommand = {fail_command:?}\n"));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("disable")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("git-smee hooks disabled"));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "disabled by git config smee.disabled; 1 skipped",
+        ));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["enable", "pre-commit"])
+        .assert()
+        .success();
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("exit code 5"));
+}
+
+#[test]
+fn given_single_phase_disabled_when_running_other_phase_then_it_still_runs() {
+    let test_repo = common::TestRepo::default();
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["disable", "pre-push"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("git-smee pre-push hooks disabled"));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("disabled by git config").not());
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-push"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "disabled by git config smee.pre-push.disabled",
+        ));
+}
+
+#[test]
+fn given_unknown_phase_when_disabling_then_it_fails() {
+    let test_repo = common::TestRepo::default();
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["disable", "pre-nothing"])
+        .assert()
+        .failure();
+}
+
+#[cfg(unix)]
+#[test]
+fn given_hook_exceeding_timeout_when_running_then_it_is_stopped_and_signal_is_reported() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "trap 'echo cleanup-ran; exit 0' TERM; sleep 30 & wait"
+timeout = 1
+grace_period = 10
+"#,
+    );
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains("cleanup-ran").and(predicate::str::contains(
+                "sequential command #1: timed out after 1s; stopped by SIGTERM",
+            )),
+        )
+        .stderr(predicate::str::contains(
+            "timed out after 1s and was stopped by SIGTERM",
+        ));
+}
+
+/// Runs `git smee run pre-commit` on a pseudo-terminal through util-linux
+/// `script`, typing `input` into it. `None` when `script` is not installed.
+#[cfg(target_os = "linux")]
+fn run_pre_commit_on_pty(
+    test_repo: &common::TestRepo,
+    input: &str,
+) -> Option<std::process::ExitStatus> {
+    use std::io::Write;
+
+    let git_smee = cargo::cargo_bin!("git-smee").to_string_lossy().to_string();
+    let mut child = match StdCommand::new("script")
+        .args(["-qec", &format!("'{git_smee}' run pre-commit"), "/dev/null"])
+        .current_dir(&test_repo.path)
+        .env(common::TRUST_ALL_ENV, "1")
+        .env_remove("GIT_SMEE_ACTIVE")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(_) => return None,
+    };
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let started = std::time::Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            return Some(status);
+        }
+        if started.elapsed() > std::time::Duration::from_secs(20) {
+            child.kill().unwrap();
+            panic!("git smee run hung on the terminal");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn given_hook_reading_the_terminal_when_running_on_a_tty_then_it_gets_the_input() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "read answer </dev/tty; echo \"first=$answer\" >> answers.txt"
+
+[[pre-commit]]
+command = "read answer </dev/tty; echo \"second=$answer\" >> answers.txt"
+timeout = 30
+"#,
+    );
+
+    let Some(status) = run_pre_commit_on_pty(&test_repo, "yes\nno\n") else {
+        eprintln!("skipping: script is not installed");
+        return;
+    };
+
+    assert!(status.success(), "git smee run failed with {status}");
+    assert_eq!(
+        fs::read_to_string(test_repo.path.join("answers.txt")).expect("hook answers"),
+        "first=yes\nsecond=no\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_isolate_tmp_hook_when_running_then_tmpdir_is_under_git_dir_and_removed() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "case \"$TMPDIR\" in */.git/smee/tmp/pre-commit/hook-1) touch \"$TMPDIR/scratch\" ;; *) exit 3 ;; esac"
+parallel_execution_allowed = true
+isolate_tmp = true
+"#,
+    );
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success();
+
+    assert!(
+        !test_repo
+            .path
+            .join(".git/smee/tmp/pre-commit/hook-1")
+            .exists()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_parallel_hooks_when_running_then_output_lines_are_prefixed_with_hook_labels() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        r#"
+[settings]
+parallel_without_tty = true
+
+[[pre-commit]]
+command = "echo sequential-plain"
+
+[[pre-commit]]
+name = "fmt"
+command = "echo formatted; echo fmt-warning >&2"
+parallel_execution_allowed = true
+
+[[pre-commit]]
+command = "echo unnamed"
+parallel_execution_allowed = true
+"#,
+    );
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("[fmt] formatted\n")
+                .and(predicate::str::contains("[hook-3] unnamed\n"))
+                .and(predicate::str::contains("sequential-plain\n"))
+                .and(predicate::str::contains("] sequential-plain").not()),
+        )
+        .stderr(predicate::str::contains("[fmt] fmt-warning\n"));
+}
+
+#[test]
+fn given_failing_warning_severity_hook_when_running_then_run_succeeds_with_warning() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 4" } else { "exit 4" };
+    test_repo.write_config(&format!(
+        "[[pre-commit]]\ncommand = {fail_command:?}\nseverity = \"warning\"\n\n[[pre-commit]]\ncommand = \"echo still-runs\"\n"
+    ));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("still-runs")
+                .and(predicate::str::contains("0 failed, 1 warning"))
+                .and(predicate::str::contains("failed with code 4 (warning)")),
+        )
+        .stderr(predicate::str::contains(
+            "Warning: 1 pre-commit hook command failed but does not block",
+        ));
+}
+
+#[test]
+fn given_description_url_when_hook_fails_then_link_is_printed() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 1" } else { "exit 1" };
+    test_repo.write_config(&format!(
+        "[[pre-commit]]\ncommand = \"echo passes\"\ndescription_url = \"https://wiki.example.com/hooks/echo\"\n\n[[pre-commit]]\ncommand = {fail_command:?}\ndescription_url = \"https://wiki.example.com/hooks/lint\"\n"
+    ));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains(
+                "  see https://wiki.example.com/hooks/lint for how to fix sequential command #2",
+            )
+            .and(predicate::str::contains("hooks/echo").not()),
+        );
+}
+
+#[test]
+fn given_failing_hook_before_enforce_after_date_when_running_then_it_only_warns() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 6" } else { "exit 6" };
+    test_repo.write_config(&format!(
+        "[[pre-commit]]\ncommand = {fail_command:?}\nenforce_after = \"2999-01-01\"\n"
+    ));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "failed with code 6 (warning until 2999-01-01)",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_show_commands_when_running_then_resolved_command_is_echoed_with_secrets_redacted() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "API_TOKEN=abc123 echo shown"
+"#,
+    );
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "--show-commands", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("shown"))
+        .stderr(
+            predicate::str::contains("git-smee: running sequential command #1 (hook-1)")
+                .and(predicate::str::contains(
+                    "  command: API_TOKEN=<redacted> echo shown",
+                ))
+                .and(predicate::str::contains("  cwd: "))
+                .and(predicate::str::contains("GIT_SMEE_HOOK_ARGC=\"0\""))
+                .and(predicate::str::contains("abc123").not()),
+        );
+}
+
+#[test]
+fn given_show_commands_env_when_running_then_commands_are_echoed() {
+    let test_repo = common::TestRepo::default();
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .env("GIT_SMEE_SHOW_COMMANDS", "1")
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "git-smee: running sequential command #1",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_show_commands_when_running_then_summary_adds_cpu_and_peak_rss() {
+    let test_repo = common::TestRepo::default();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("peak rss").not());
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "--show-commands", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(cpu ").and(predicate::str::contains("peak rss")));
+}
+
+#[test]
+fn given_audit_enabled_when_running_hook_then_executions_are_appended_to_audit_log() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 4" } else { "exit 4" };
+    test_repo.write_config(&format!(
+        r#"
+[[pre-commit]]
+name = "greet"
+command = "API_TOKEN=abc123 echo audited"
+
+[[pre-commit]]
+command = {fail_command:?}
+"#
+    ));
+    let audit_log = test_repo.path.join(".git").join("smee").join("audit.jsonl");
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure();
+    assert!(!audit_log.exists());
+
+    git(&test_repo, &["config", "smee.audit", "true"]);
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit", "--", "HEAD"])
+        .assert()
+        .failure();
+
+    let contents = fs::read_to_string(&audit_log).expect("audit log should be written");
+    let records: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("audit line should be JSON"))
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["phase"], "pre-commit");
+    assert_eq!(records[0]["hook"], "greet");
+    assert_eq!(records[0]["command"], "API_TOKEN=<redacted> echo audited");
+    assert_eq!(records[0]["status"], "success");
+    assert_eq!(
+        records[0]["env_keys"],
+        serde_json::json!(["GIT_SMEE_HOOK_ARGC", "GIT_SMEE_HOOK_ARG_1"])
+    );
+    assert!(
+        records[0]["cwd"]
+            .as_str()
+            .is_some_and(|cwd| !cwd.is_empty())
+    );
+    assert!(records[0]["duration_ms"].is_u64());
+    assert_eq!(records[1]["hook"], "hook-2");
+    assert_eq!(records[1]["status"], "exit");
+    assert_eq!(records[1]["exit_code"], 4);
+    assert!(!contents.contains("abc123"));
+}
+
+fn untrusted_git_smee(test_repo: &common::TestRepo, config_home: &Path) -> Command {
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .env_remove(common::TRUST_ALL_ENV)
+        .env_remove("CI")
+        .env("XDG_CONFIG_HOME", config_home)
+        .env("APPDATA", config_home);
+    cmd
+}
+
+#[test]
+fn given_untrusted_config_when_running_non_interactively_then_run_is_refused() {
+    let test_repo = common::TestRepo::default();
+    let config_home = TempDir::new().expect("failed to create config home");
+    let marker = test_repo.path.join("ran.txt");
+    test_repo.write_config("[[pre-commit]]\ncommand = \"echo ran > ran.txt\"\n");
+
+    untrusted_git_smee(&test_repo, config_home.path())
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Refusing to run hooks from untrusted config")
+                .and(predicate::str::contains("not trusted on this machine yet"))
+                .and(predicate::str::contains("git smee trust")),
+        );
+
+    assert!(!marker.exists());
+}
+
+#[test]
+fn given_trust_flag_when_running_then_config_is_trusted_until_it_changes() {
+    let test_repo = common::TestRepo::default();
+    let config_home = TempDir::new().expect("failed to create config home");
+    test_repo.write_config("[[pre-commit]]\ncommand = \"echo first\"\n");
+
+    untrusted_git_smee(&test_repo, config_home.path())
+        .args(["run", "--trust", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("first"));
+    let trust_store = fs::read_to_string(config_home.path().join("git-smee").join("trust"))
+        .expect("trust store should be written");
+    assert!(trust_store.contains(".git-smee.toml"));
+
+    untrusted_git_smee(&test_repo, config_home.path())
+        .args(["run", "pre-commit"])
+        .assert()
+        .success();
+
+    test_repo.write_config("[[pre-commit]]\ncommand = \"echo second\"\n");
+    untrusted_git_smee(&test_repo, config_home.path())
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "changed since it was last trusted",
+        ));
+
+    untrusted_git_smee(&test_repo, config_home.path())
+        .arg("trust")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Trusted ").and(predicate::str::contains("sha256 ")));
+    untrusted_git_smee(&test_repo, config_home.path())
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("second"));
+}
+
+#[test]
+fn given_config_changed_since_trusted_when_simulating_commit_then_hooks_are_refused() {
+    let test_repo = common::TestRepo::default();
+    let config_home = TempDir::new().expect("failed to create config home");
+    let marker = test_repo.path.join("ran.txt");
+    test_repo.write_config("[[pre-commit]]\ncommand = \"echo first\"\n");
+    untrusted_git_smee(&test_repo, config_home.path())
+        .arg("trust")
+        .assert()
+        .success();
+
+    test_repo.write_config("[[pre-commit]]\ncommand = \"echo ran > ran.txt\"\n");
+    untrusted_git_smee(&test_repo, config_home.path())
+        .args(["simulate", "commit", "-m", "msg"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "changed since it was last trusted",
+        ));
+
+    assert!(!marker.exists());
+}
+
+#[test]
+fn given_ci_environment_when_running_untrusted_config_then_trust_check_is_skipped() {
+    let test_repo = common::TestRepo::default();
+    let config_home = TempDir::new().expect("failed to create config home");
+
+    untrusted_git_smee(&test_repo, config_home.path())
+        .env("CI", "true")
+        .args(["run", "pre-commit"])
+        .assert()
+        .success();
+
+    assert!(!config_home.path().join("git-smee").exists());
+}
+
+#[test]
+fn given_policy_file_when_hook_runs_disallowed_executable_then_run_fails_before_executing() {
+    let test_repo = common::TestRepo::default();
+    let policy_dir = TempDir::new().expect("failed to create policy dir");
+    let policy_path = policy_dir.path().join("policy.toml");
+    fs::write(&policy_path, "[commands]\nallow = [\"echo\"]\n").expect("write policy");
+    test_repo.write_config(
+        r#"
+[[pre-commit]]
+command = "echo first > first.txt"
+
+[[pre-commit]]
+command = "echo ok && git-smee-not-approved --fetch"
+"#,
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("GIT_SMEE_POLICY_FILE", &policy_path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Policy violation")
+                .and(predicate::str::contains("'git-smee-not-approved'"))
+                .and(predicate::str::contains(policy_path.to_string_lossy())),
+        );
+    assert!(!test_repo.path.join("first.txt").exists());
+
+    fs::write(
+        &policy_path,
+        "[commands]\nallow = [\"echo\", \"git-smee-not-*\"]\n",
+    )
+    .expect("write policy");
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("GIT_SMEE_POLICY_FILE", &policy_path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Policy violation").not());
+    assert!(test_repo.path.join("first.txt").exists());
+}
+
+#[test]
+fn given_config_when_config_get_then_value_is_printed() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = \"cargo test\"\n");
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["config", "get", "pre-commit.0.command"])
+        .assert()
+        .success()
+        .stdout("cargo test\n");
+}
+
+#[test]
+fn given_missing_key_when_config_get_then_error_is_reported() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = \"cargo test\"\n");
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["config", "get", "pre-push.0.command"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Config key not found: pre-push.0.command",
+        ));
+}
+
+#[test]
+fn given_commented_config_when_config_set_then_only_the_value_changes() {
+    let test_repo = common::TestRepo::default();
+    let original =
+        "# Checks run before every commit\n[[pre-commit]]\ncommand = \"cargo test\" # keep fast\n";
+    test_repo.write_config(original);
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["config", "set", "pre-commit.0.command", "cargo nextest run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Set pre-commit.0.command"));
+
+    assert_eq!(
+        fs::read_to_string(test_repo.config_path()).unwrap(),
+        original.replace("\"cargo test\"", "\"cargo nextest run\"")
+    );
+}
+
+#[test]
+fn given_invalid_value_when_config_set_then_config_is_left_unchanged() {
+    let test_repo = common::TestRepo::default();
+    let original = "[[pre-commit]]\ncommand = \"cargo test\"\n";
+    test_repo.write_config(original);
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["config", "set", "pre-commit.0.timeout", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "timeout must be greater than zero",
+        ));
+
+    assert_eq!(
+        fs::read_to_string(test_repo.config_path()).unwrap(),
+        original
+    );
+}
+
+#[test]
+fn given_fixable_failure_without_terminal_when_running_then_fix_is_not_run() {
+    let test_repo = common::TestRepo::default();
+    let marker = test_repo.path.join("fix-ran");
+    let fail_command = if cfg!(windows) { "exit /b 3" } else { "exit 3" };
+    let fix_command = if cfg!(windows) {
+        format!("type nul > \"{}\"", marker.display())
+    } else {
+        format!("touch '{}'", marker.display())
+    };
+    test_repo.write_config(&format!(
+        "[[pre-commit]]\ncommand = {fail_command:?}\nfixable = {fix_command:?}\nrestage = true\n"
+    ));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("exit code 3"))
+        .stderr(predicate::str::contains("suggested fix").not());
+
+    assert!(!marker.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn given_interrupted_run_when_resuming_then_passed_commands_are_skipped() {
+    let test_repo = common::TestRepo::default();
+    let counter = test_repo.path.join("fmt-runs");
+    let ready = test_repo.path.join("tests-ready");
+    test_repo.write_config(&format!(
+        "[[pre-commit]]\ncommand = \"echo run >> '{}'\"\n\n[[pre-commit]]\ncommand = \"test -f '{}'\"\n\n[[commit-msg]]\ncommand = \"true\"\n",
+        counter.display(),
+        ready.display()
+    ));
+
+    let mut cmd = common::git_smee();
+    let output = cmd
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit", "commit-msg"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let run_id = stderr
+        .split("git smee resume ")
+        .nth(1)
+        .and_then(|rest| rest.split('`').next())
+        .expect("failed run should print a resume hint")
+        .to_string();
+
+    fs::write(&ready, "").unwrap();
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["resume", &run_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Resuming run {run_id} (pre-commit, commit-msg)"
+        )))
+        .stdout(predicate::str::contains("1 attempted, 1 skipped, 0 failed"));
+
+    assert_eq!(fs::read_to_string(&counter).unwrap(), "run\n");
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("resume")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No interrupted run to resume"));
+}
+
+#[test]
+fn given_failing_single_phase_run_when_running_then_no_resume_hint_is_printed() {
+    let test_repo = common::TestRepo::default();
+    let fail_command = if cfg!(windows) { "exit /b 3" } else { "exit 3" };
+    test_repo.write_config(&format!("[[pre-commit]]\ncommand = {fail_command:?}\n"));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("git smee resume").not());
+}
+
+#[test]
+fn given_successful_run_when_resuming_then_no_run_is_left() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\ncommand = \"echo ok\"\n");
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("git smee resume").not());
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["resume", "not-a-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No run found with ID not-a-run"));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_tasks_section_when_running_task_then_commands_run_with_forwarded_args() {
+    let test_repo = common::TestRepo::default();
+    let greeting = test_repo.path.join("greeting");
+    test_repo.write_config(&format!(
+        "[[pre-commit]]\ncommand = \"echo hook\"\n\n[[tasks.greet]]\ncommand = \"echo \\\"$1\\\" > '{}'\"\n\n[[tasks.lint-all]]\ncommand = \"true\"\nparallel_execution_allowed = true\n\n[[tasks.lint-all]]\ncommand = \"false\"\nparallel_execution_allowed = true\n",
+        greeting.display()
+    ));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .arg("task")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("greet (1 command)"))
+        .stdout(predicate::str::contains("lint-all (2 commands)"));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["task", "greet", "hello"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hook summary: task greet"));
+    assert_eq!(fs::read_to_string(&greeting).unwrap(), "hello\n");
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["task", "lint-all"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("2 attempted, 0 skipped, 1 failed"));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["task", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No task named 'pre-commit' in the config",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_hook_that_edits_config_when_running_then_run_uses_snapshot_and_warns() {
+    let test_repo = common::TestRepo::default();
+    let marker = test_repo.path.join("second-ran");
+    test_repo.write_config(&format!(
+        "[[pre-commit]]\ncommand = \"printf '[[pre-commit]]\\\\ncommand = \\\"true\\\"\\\\n' > .git-smee.toml\"\n\n[[pre-commit]]\ncommand = \"touch '{}'\"\n",
+        marker.display()
+    ));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 attempted, 0 skipped, 0 failed"))
+        .stderr(predicate::str::contains(
+            ".git-smee.toml changed while hooks were running; this run used the config as it was when the run started",
+        ));
+    assert!(marker.exists());
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("changed while hooks were running").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn given_hook_command_when_running_then_active_run_is_exported() {
+    let test_repo = common::TestRepo::default();
+    let active = test_repo.path.join("active");
+    test_repo.write_config(&format!(
+        "[[pre-commit]]\ncommand = \"printf '%s' \\\"$GIT_SMEE_ACTIVE\\\" > '{}'\"\n",
+        active.display()
+    ));
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&active).unwrap(), "pre-commit");
+}
+
+#[cfg(unix)]
+#[test]
+fn given_active_run_when_running_nested_hooks_then_nested_runs_setting_decides() {
+    let test_repo = common::TestRepo::default();
+    let marker = test_repo.path.join("nested-ran");
+    let hook = format!(
+        "[[post-commit]]\ncommand = \"touch '{}'\"\n",
+        marker.display()
+    );
+
+    test_repo.write_config(&hook);
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .env("GIT_SMEE_ACTIVE", "pre-commit")
+        .args(["run", "post-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "skipped inside the running pre-commit hooks (GIT_SMEE_ACTIVE is set); 1 skipped",
+        ));
+    assert!(!marker.exists());
+
+    test_repo.write_config(&format!("[settings]\nnested_runs = \"fail\"\n\n{hook}"));
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .env("GIT_SMEE_ACTIVE", "pre-commit")
+        .args(["run", "post-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "post-commit hooks were triggered from inside the running pre-commit hooks",
+        ));
+    assert!(!marker.exists());
+
+    test_repo.write_config(&format!("[settings]\nnested_runs = \"allow\"\n\n{hook}"));
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .env("GIT_SMEE_ACTIVE", "pre-commit")
+        .args(["run", "post-commit"])
+        .assert()
+        .success();
+    assert!(marker.exists());
+}
+
+#[test]
+fn given_phase_defaults_when_running_then_entries_inherit_them_unless_overridden() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[settings]\nparallel_without_tty = true\n\n[defaults.pre-commit]\nparallel_execution_allowed = true\n\n[[pre-commit]]\ncommand = \"echo one\"\n\n[[pre-commit]]\ncommand = \"echo two\"\n\n[[pre-commit]]\ncommand = \"echo three\"\nparallel_execution_allowed = false\n",
+    );
+
+    let mut cmd = common::git_smee();
+    cmd.current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "sequential: 1 attempted, 0 failed",
+        ))
+        .stdout(predicate::str::contains("parallel: 2 attempted, 0 failed"));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_phase_over_its_time_budget_when_running_then_remaining_hooks_are_skipped() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[settings.pre-commit]\nmax_duration = '1s'\n\n[[pre-commit]]\ncommand = 'sleep 1'\n\n[[pre-commit]]\ncommand = 'echo late-ran'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("late-ran")
+                .not()
+                .and(predicate::str::contains(
+                    "warning: phase time budget of 1s used up; not started: sequential command #2",
+                )),
+        )
+        .stderr(predicate::str::contains(
+            "Warning: pre-commit used up its time budget (settings.pre-commit.max_duration); 1 hook command(s) skipped",
+        ));
+
+    test_repo.write_config(
+        "[settings.pre-commit]\nmax_duration = 1\nover_budget = 'fail'\n\n[[pre-commit]]\ncommand = 'sleep 1'\n\n[[pre-commit]]\ncommand = 'echo late-ran'\n",
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("late-ran").not())
+        .stderr(predicate::str::contains(
+            "The phase time budget of 1s was used up before all hooks ran",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_user_opt_out_file_when_running_then_listed_hooks_are_skipped_except_in_ci() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'lint'\ncommand = 'echo lint-ran'\n\n[[pre-commit]]\nname = 'tests'\ncommand = 'echo tests-ran'\n",
+    );
+    let opt_out = test_repo.path.join("opt-out.toml");
+    std::fs::write(&opt_out, "hooks = ['pre-commit:lint']\n").unwrap();
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("GIT_SMEE_OPT_OUT_FILE", &opt_out)
+        .env_remove("CI")
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("tests-ran")
+                .and(predicate::str::contains("lint-ran").not())
+                .and(predicate::str::contains(
+                    "total: 1 attempted, 1 skipped (1 opted out), 0 failed",
+                )),
+        )
+        .stderr(predicate::str::contains(
+            "git-smee: note: skipped 1 pre-commit hook command(s) you opted out of in",
+        ));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .env("GIT_SMEE_OPT_OUT_FILE", &opt_out)
+        .env("CI", "true")
+        .args(["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lint-ran").and(predicate::str::contains("tests-ran")));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_range_when_running_pre_push_then_hooks_read_the_range_as_pushed_refs() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-push]]\ncommand = 'cat'\n\n[[pre-commit]]\ncommand = 'true'\n");
+    git(&test_repo, &["config", "user.name", "git-smee test"]);
+    git(
+        &test_repo,
+        &["config", "user.email", "git-smee@example.invalid"],
+    );
+    git(&test_repo, &["commit", "--allow-empty", "-m", "base"]);
+    git(&test_repo, &["commit", "--allow-empty", "-m", "head"]);
+    let rev_parse = |rev: &str| {
+        let output = StdCommand::new("git")
+            .current_dir(&test_repo.path)
+            .args(["rev-parse", rev])
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+    let (base, head) = (rev_parse("HEAD~1"), rev_parse("HEAD"));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "--range", "HEAD~1..", "pre-push"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "HEAD {head} HEAD~1 {base}"
+        )));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "--range", "HEAD~1..nope", "pre-push"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid commit range 'HEAD~1..nope': 'nope' is not a commit",
+        ));
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "--range", "HEAD~1..HEAD", "pre-commit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "only pre-push runs against a commit range",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_changed_files_placeholder_when_running_pre_push_with_range_then_only_changed_files_are_passed()
+ {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-push]]\ncommand = 'echo linting {changed_files}'\n");
+    git(&test_repo, &["config", "user.name", "git-smee test"]);
+    git(
+        &test_repo,
+        &["config", "user.email", "git-smee@example.invalid"],
+    );
+    fs::write(test_repo.path.join("old.js"), "old\n").unwrap();
+    fs::write(test_repo.path.join("gone.js"), "gone\n").unwrap();
+    git(&test_repo, &["add", "old.js", "gone.js"]);
+    git(&test_repo, &["commit", "-q", "-m", "base"]);
+    fs::write(test_repo.path.join("new file.js"), "new\n").unwrap();
+    git(&test_repo, &["add", "new file.js"]);
+    git(&test_repo, &["rm", "-q", "gone.js"]);
+    git(&test_repo, &["commit", "-q", "-m", "head"]);
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "--range", "HEAD~1..HEAD", "pre-push"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("linting new file.js\n"));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "--range", "HEAD..HEAD", "pre-push"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("linting")
+                .not()
+                .and(predicate::str::contains("skipped (no changed files)")),
+        );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_artifacts_dir_hook_when_running_then_report_is_kept_under_the_git_dir() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'lint'\ncommand = 'echo \"{}\" > \"$SMEE_ARTIFACTS_DIR/lint.json\"'\nartifacts_dir = true\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success();
+
+    let runs: Vec<_> = fs::read_dir(test_repo.path.join(".git/smee/artifacts"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(
+        fs::read_to_string(runs[0].join("pre-commit/lint/lint.json")).unwrap(),
+        "{}\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn given_expired_state_when_cleaning_then_dry_run_lists_it_and_clean_removes_it() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'lint'\ncommand = 'echo ok > \"$SMEE_ARTIFACTS_DIR/lint.txt\"'\nartifacts_dir = true\n",
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success();
+    git(&test_repo, &["config", "smee.stateExpire", "now"]);
+    let artifacts = test_repo.path.join(".git/smee/artifacts");
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["clean", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would remove"))
+        .stdout(predicate::str::contains("Would free"));
+    assert_eq!(fs::read_dir(&artifacts).unwrap().count(), 1);
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("clean")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed"));
+    assert_eq!(fs::read_dir(&artifacts).unwrap().count(), 0);
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["clean", "--all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to remove"));
+}
+
+#[test]
+fn given_unformatted_config_when_running_config_fmt_then_check_fails_and_fmt_rewrites_it() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "# Shared hooks\n\n[[pre-push]]\ncommand='cargo test'\n\n# Fast checks\n[[pre-commit]]\n\"command\" = 'cargo fmt --check'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["config", "fmt", "--check"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not formatted"));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["config", "fmt"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Formatted"));
+    assert_eq!(
+        fs::read_to_string(test_repo.path.join(".git-smee.toml")).unwrap(),
+        "# Shared hooks\n\n# Fast checks\n[[pre-commit]]\ncommand = 'cargo fmt --check'\n\n[[pre-push]]\ncommand = 'cargo test'\n"
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["config", "fmt", "--check"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already formatted"));
+}
+
+#[test]
+fn given_config_changed_since_commit_when_diffing_config_then_behavior_changes_are_listed() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'fmt'\ncommand = 'cargo fmt --check'\n\n[[pre-commit]]\nname = 'test'\ncommand = 'cargo test'\n",
+    );
+    git(&test_repo, &["config", "user.name", "git-smee test"]);
+    git(
+        &test_repo,
+        &["config", "user.email", "git-smee@example.invalid"],
+    );
+    git(&test_repo, &["add", ".git-smee.toml"]);
+    git(&test_repo, &["commit", "--no-verify", "-m", "add config"]);
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'fmt'\ncommand = 'cargo fmt --check'\ntimeout = 30\n\n[[pre-push]]\ncommand = 'cargo test'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["diff-config", "HEAD"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Behavior changes in .git-smee.toml since HEAD:",
+        ))
+        .stdout(predicate::str::contains(
+            "~ pre-commit: fmt changed: timeout (default) -> 30",
+        ))
+        .stdout(predicate::str::contains(
+            "- pre-commit: test removed: cargo test",
+        ))
+        .stdout(predicate::str::contains(
+            "+ pre-push: hook-1 added: cargo test",
+        ))
+        .stderr(predicate::str::contains("run `git smee install`"));
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["diff-config", "no-such-rev"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'no-such-rev' is not a commit"));
+}
+
+#[test]
+fn given_owned_commands_changed_since_commit_when_listing_owners_then_reviewers_are_named() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'fmt'\ncommand = 'cargo fmt --check'\nowner = '@acme/rust'\n",
+    );
+    git(&test_repo, &["config", "user.name", "git-smee test"]);
+    git(
+        &test_repo,
+        &["config", "user.email", "git-smee@example.invalid"],
+    );
+    git(&test_repo, &["add", ".git-smee.toml"]);
+    git(&test_repo, &["commit", "--no-verify", "-m", "add config"]);
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'fmt'\ncommand = 'cargo fmt --check'\nowner = '@acme/rust'\ntimeout = 30\n\n[[pre-push]]\ncommand = 'cargo test'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("owners")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "@acme/rust\n  pre-commit: fmt  cargo fmt --check\n(no owner)\n  pre-push: hook-1  cargo test\n",
+        ));
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["owners", "--since", "HEAD"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "~ pre-commit: fmt changed: timeout (default) -> 30\n    review: @acme/rust",
+        ))
+        .stdout(predicate::str::contains(
+            "+ pre-push: hook-1 added: cargo test\n    review: (no owner)",
+        ))
+        .stderr(predicate::str::contains(
+            "git-smee: warning: 1 changed command(s) have no owner",
+        ));
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["owners", "--since", "HEAD", "--require-owner"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("have no owner to review them"));
+}
+
+#[test]
+fn given_partly_installed_config_when_listing_then_phases_show_commands_and_install_state() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'fmt'\ncommand = 'cargo fmt --check'\n\n[[pre-commit]]\ncommand = 'cargo clippy'\n",
+    );
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("install")
+        .assert()
+        .success();
+    test_repo.write_config(
+        "[[pre-commit]]\nname = 'fmt'\ncommand = 'cargo fmt --check'\n\n[[pre-commit]]\ncommand = 'cargo clippy'\n\n[[pre-push]]\ncommand = 'cargo test'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pre-commit (installed: "))
+        .stdout(predicate::str::contains(
+            "  fmt: cargo fmt --check\n  hook-2: cargo clippy\n",
+        ))
+        .stdout(predicate::str::contains("pre-push (not installed: "))
+        .stdout(predicate::str::contains("  hook-1: cargo test\n"))
+        .stderr(predicate::str::contains(
+            "git-smee: note: run `git smee install` to install the missing wrappers",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn given_analytics_enabled_when_running_then_stats_count_runs_until_reset() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config("[[pre-commit]]\nname = 'lint'\ncommand = 'true'\n");
+    git(&test_repo, &["config", "smee.analytics", "true"]);
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["run", "pre-commit"])
+        .assert()
+        .success();
+
+    let analytics = fs::read_to_string(test_repo.path.join(".git/smee/analytics.jsonl")).unwrap();
+    let record: serde_json::Value = serde_json::from_str(analytics.trim()).unwrap();
+    assert_eq!(record["hook"], "lint");
+    assert_eq!(record["status"], "success");
+    assert!(record.get("command").is_none());
+    assert!(!test_repo.path.join(".git/smee/audit.jsonl").exists());
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"pre-commit  lint  1     0 ").unwrap());
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["stats", "reset"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 1 recorded run(s)"));
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["stats"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("No hook runs recorded in")
+                .and(predicate::str::contains("analytics.jsonl")),
+        );
+}
+
+#[test]
+fn given_config_with_problems_when_validating_then_all_are_reported_and_exit_code_is_one() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-comit]]\ncommand = 'cargo test'\n\n[[pre-push]]\ncommand = ''\n\n[[pre-push]]\ncomand = 'cargo test'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("validate")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "error[unknown-key]: 'pre-comit' is neither a lifecycle phase",
+        ))
+        .stdout(predicate::str::contains(
+            "error[empty-command]: Hook 'pre-push' entry #1",
+        ))
+        .stdout(predicate::str::contains(
+            "error[invalid-entry]: Hook 'pre-push' entry #2: unknown field 'comand'; did you mean 'command'?",
+        ))
+        .stdout(predicate::str::contains(".git-smee.toml: 3 error(s), 0 warning(s)"));
+
+    let output = common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["validate", "--format", "json"])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["valid"], false);
+    assert_eq!(report["diagnostics"][1]["code"], "empty-command");
+}
+
+#[test]
+fn given_config_with_only_warnings_when_validating_then_strict_fails() {
+    let test_repo = common::TestRepo::default();
+    test_repo.write_config(
+        "[[pre-push]]\ncommand = 'cargo test'\n\n[[pre-push]]\ncommand = 'cargo test'\n",
+    );
+
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .arg("validate")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "warning[duplicate-command]: Hook 'pre-push' entries #1 and #2 both run `cargo test`",
+        ));
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["validate", "--strict"])
+        .assert()
+        .code(1);
+    common::git_smee()
+        .current_dir(&test_repo.path)
+        .args(["--config", "missing.toml", "validate"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("cannot read"));
+}
@@ -1,9 +1,35 @@
 use std::{fs, path::PathBuf};
 
+use assert_cmd::{Command, cargo};
 use assert_fs::{TempDir, assert::PathAssert, fixture::ChildPath, prelude::PathChild};
 use git_smee_core::{DEFAULT_CONFIG_FILE_NAME, config::LifeCyclePhase};
 use predicates::path::{exists, is_file};
 
+/// Env switch that skips the config trust check.
+pub const TRUST_ALL_ENV: &str = "GIT_SMEE_TRUST_ALL";
+
+/// `git-smee` command for tests. Config trust is bypassed so tests never read
+/// or write the developer's trust store; trust tests remove the variable again.
+pub fn git_smee() -> Command {
+    let mut command = Command::new(cargo::cargo_bin!("git-smee"));
+    command.env(TRUST_ALL_ENV, "1");
+    // Tests may themselves run from a git-smee hook; a nested run would be
+    // skipped.
+    command.env_remove("GIT_SMEE_ACTIVE");
+    command
+}
+
+/// `PATH` with the built `git-smee` binary first, as in a working install.
+pub fn path_with_git_smee() -> std::ffi::OsString {
+    let binary = PathBuf::from(cargo::cargo_bin!("git-smee"));
+    let binary_dir = binary.parent().expect("binary has a parent directory");
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::join_paths(
+        std::iter::once(binary_dir.to_path_buf()).chain(std::env::split_paths(&path)),
+    )
+    .expect("PATH entries can be joined")
+}
+
 pub struct TestRepo {
     pub path: TempDir,
 }
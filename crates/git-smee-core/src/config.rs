@@ -1,8 +1,7 @@
 use core::fmt;
 use std::{
-    collections::{HashMap, hash_map},
+    collections::{BTreeMap, HashMap, hash_map},
     ffi::OsStr,
-    fs,
     path::Path,
     str::FromStr,
 };
@@ -10,12 +9,334 @@ use std::{
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Deserialize, Serialize)]
+pub mod diagnostics;
+pub mod diff;
+pub mod edit;
+pub mod format;
+pub mod owners;
+mod snapshot;
+
+pub use snapshot::ConfigSnapshot;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct SmeeConfig {
+    /// The `[settings]` table: options for the whole repository rather than a
+    /// single hook.
+    #[serde(default, skip_serializing_if = "Settings::is_default")]
+    pub settings: Settings,
+    /// The `[tasks]` table: named command groups that are not tied to a git
+    /// phase and only run through `git smee task <name>`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tasks: BTreeMap<String, Vec<HookDefinition>>,
+    /// The `[defaults]` table: per-phase values for hook fields, e.g.
+    /// `[defaults.pre-commit]` with `timeout = 60`. When the config file is
+    /// read, they fill in the fields an entry of that phase leaves unset.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub defaults: HashMap<LifeCyclePhase, PhaseDefaults>,
+    /// The `[install]` table: per-phase options for the wrapper `git smee
+    /// install` writes, e.g. `[install.pre-commit]`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub install: HashMap<LifeCyclePhase, PhaseInstall>,
     #[serde(flatten)]
     pub hooks: HashMap<LifeCyclePhase, Vec<HookDefinition>>,
 }
 
+/// Options of a `[install.<phase>]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseInstall {
+    /// `sh` lines the wrapper runs before git-smee itself, e.g.
+    /// `. ./.envrc` to put the tools of a project environment on `PATH`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prepend: Option<String>,
+}
+
+/// Values of a `[defaults.<phase>]` table, keyed by hook field name.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct PhaseDefaults(pub toml::Table);
+
+// Validated defaults only hold hook fields, none of which is a float, so the
+// table never contains a NaN.
+impl Eq for PhaseDefaults {}
+
+// Unknown keys are still rejected: they land in `phases` and fail to parse as
+// a phase name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Settings {
+    /// Directory, relative to the repository root, that hooks are installed
+    /// into instead of Git's default, e.g. `.git-smee/hooks`. Installing points
+    /// `core.hooksPath` at it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks_path: Option<String>,
+    /// Phases that never get a wrapper installed, e.g. expensive maintenance
+    /// tasks that should only run through `git smee run`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub never_install: Vec<LifeCyclePhase>,
+    /// What a hook run does when it starts inside another git-smee run, e.g.
+    /// when a `pre-commit` command commits to a fixture repository.
+    #[serde(default, skip_serializing_if = "NestedRuns::is_skip")]
+    pub nested_runs: NestedRuns,
+    /// Shell that runs hook commands on Windows. Ignored elsewhere, where
+    /// commands always run through `sh -c`.
+    #[serde(default, skip_serializing_if = "WindowsShell::is_cmd")]
+    pub windows_shell: WindowsShell,
+    /// Directory, relative to the repository root, holding translated error
+    /// guidance as `<locale>.toml` files; see [`crate::messages`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub messages_dir: Option<String>,
+    /// Keep running `parallel` commands in parallel when stdout is not a
+    /// terminal, e.g. under CI or a GUI client. By default they then run one
+    /// after another in config order, so logs are not interleaved.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub parallel_without_tty: bool,
+    /// Print a short environment snapshot (platform, Git version, `PATH`,
+    /// and the versions of `requires` tools) under a failed run's summary.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub env_snapshot: bool,
+    /// Concurrency limits of the groups hook entries join with `group`, e.g.
+    /// `io = 8` and `cpu = 1`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, usize>,
+    /// Interpreters for `script` entries by file extension, e.g.
+    /// `py = "python3.12"`. They extend and override
+    /// [`DEFAULT_INTERPRETERS`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub interpreters: BTreeMap<String, String>,
+    /// Where each run's result is posted as JSON, e.g. for a team dashboard.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report: Option<ReportSettings>,
+    /// `[settings.<phase>]` tables, e.g. `[settings.pre-commit]`.
+    #[serde(flatten)]
+    pub phases: HashMap<LifeCyclePhase, PhaseSettings>,
+}
+
+/// Settings of a single phase.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseSettings {
+    /// Time budget of the whole phase. Commands that would start after it is
+    /// used up are not run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<MaxDuration>,
+    /// What happens to commands left over when the budget is used up.
+    #[serde(default, skip_serializing_if = "OverBudget::is_skip")]
+    pub over_budget: OverBudget,
+}
+
+/// What a phase does with the commands it has no time left for.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OverBudget {
+    /// They are skipped with a warning; the phase can still pass.
+    #[default]
+    Skip,
+    /// The phase fails at the first command it has no time left for.
+    Fail,
+}
+
+impl OverBudget {
+    pub const fn is_skip(&self) -> bool {
+        matches!(self, Self::Skip)
+    }
+}
+
+/// A `max_duration`: a number of seconds, or a string such as `"90s"`,
+/// `"5m"`, or `"1h"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxDuration(pub std::time::Duration);
+
+impl fmt::Display for MaxDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}s", self.0.as_secs())
+    }
+}
+
+impl std::str::FromStr for MaxDuration {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let (number, unit_seconds) = match value.char_indices().last() {
+            Some((index, 's')) => (&value[..index], 1),
+            Some((index, 'm')) => (&value[..index], 60),
+            Some((index, 'h')) => (&value[..index], 60 * 60),
+            _ => (value, 1),
+        };
+        let number: u64 = number.trim().parse().map_err(|_| ())?;
+        number
+            .checked_mul(unit_seconds)
+            .map(|seconds| Self(std::time::Duration::from_secs(seconds)))
+            .ok_or(())
+    }
+}
+
+impl Serialize for MaxDuration {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MaxDuration {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MaxDurationVisitor;
+
+        impl serde::de::Visitor<'_> for MaxDurationVisitor {
+            type Value = MaxDuration;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a number of seconds or a duration such as \"90s\" or \"5m\"")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, seconds: u64) -> Result<Self::Value, E> {
+                Ok(MaxDuration(std::time::Duration::from_secs(seconds)))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, seconds: i64) -> Result<Self::Value, E> {
+                u64::try_from(seconds)
+                    .map(|seconds| MaxDuration(std::time::Duration::from_secs(seconds)))
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(seconds), &self))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                value
+                    .parse()
+                    .map_err(|()| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_any(MaxDurationVisitor)
+    }
+}
+
+/// `[settings.report]`: a URL that receives every run's result.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReportSettings {
+    pub url: String,
+    /// Env var holding the shared secret sent with each report. The secret
+    /// itself never goes into the committed config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_env: Option<String>,
+    /// Header that carries the secret, [`DEFAULT_REPORT_SECRET_HEADER`] by
+    /// default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_header: Option<String>,
+    /// Extra attempts after a failed post, [`DEFAULT_REPORT_RETRIES`] by
+    /// default and at most [`MAX_REPORT_RETRIES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+}
+
+/// Placeholder that `pre-push` commands get replaced with the files the push
+/// changes, e.g. `eslint {changed_files}`.
+pub const CHANGED_FILES_PLACEHOLDER: &str = "{changed_files}";
+
+pub const DEFAULT_REPORT_SECRET_HEADER: &str = "X-Git-Smee-Secret";
+pub const DEFAULT_REPORT_RETRIES: u32 = 2;
+/// Retries back off exponentially while Git waits for the hook, so more than
+/// this would hold a commit for minutes when the endpoint is down.
+pub const MAX_REPORT_RETRIES: u32 = 5;
+
+impl ReportSettings {
+    pub fn secret_header(&self) -> &str {
+        self.secret_header
+            .as_deref()
+            .unwrap_or(DEFAULT_REPORT_SECRET_HEADER)
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries.unwrap_or(DEFAULT_REPORT_RETRIES)
+    }
+}
+
+/// How a hook run triggered from inside another git-smee run is handled.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NestedRuns {
+    /// The nested run is skipped and reported as such.
+    #[default]
+    Skip,
+    /// The nested run fails, which fails the git operation that triggered it.
+    Fail,
+    /// The nested run executes like any other.
+    Allow,
+}
+
+impl NestedRuns {
+    pub const fn is_skip(&self) -> bool {
+        matches!(self, Self::Skip)
+    }
+}
+
+/// Shell that runs hook commands on Windows.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowsShell {
+    /// `cmd.exe /C`, with hook arguments as `%1`, `%2`, ...
+    #[default]
+    Cmd,
+    /// Windows PowerShell (`powershell.exe -File`), with hook arguments in
+    /// `$args`.
+    PowerShell,
+    /// PowerShell 7 (`pwsh.exe -File`), with hook arguments in `$args`.
+    Pwsh,
+}
+
+impl WindowsShell {
+    pub const fn is_cmd(&self) -> bool {
+        matches!(self, Self::Cmd)
+    }
+}
+
+/// Interpreters of `script` entries that `[settings.interpreters]` does not
+/// override. Windows installs Python as `python`, not `python3`.
+pub const DEFAULT_INTERPRETERS: [(&str, &str); 3] = [
+    ("py", if cfg!(windows) { "python" } else { "python3" }),
+    ("js", "node"),
+    ("rb", "ruby"),
+];
+
+impl Settings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Interpreter that runs `script`, by its extension, compared without
+    /// regard to case. `None` when the script runs on its own.
+    pub fn interpreter_for(&self, script: &str) -> Option<&str> {
+        let extension = Path::new(script)
+            .extension()?
+            .to_string_lossy()
+            .to_ascii_lowercase();
+        self.interpreters
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(&extension))
+            .map(|(_, interpreter)| interpreter.as_str())
+            .or_else(|| {
+                DEFAULT_INTERPRETERS
+                    .iter()
+                    .find(|(key, _)| *key == extension)
+                    .map(|(_, interpreter)| *interpreter)
+            })
+    }
+
+    /// The `no_shell` command line that runs `script`. A script without an
+    /// interpreter is executed directly, so it needs its exec bit and shebang.
+    pub fn script_command(&self, script: &str) -> String {
+        let script = script.trim();
+        let path = if script.contains(['/', '\\']) {
+            crate::shell_quote::sh_quote(script)
+        } else {
+            crate::shell_quote::sh_quote(&format!("./{script}"))
+        };
+        match self.interpreter_for(script) {
+            Some(interpreter) => format!("{interpreter} {path}"),
+            None => path,
+        }
+    }
+}
+
 impl SmeeConfig {
     /// Load configuration from a TOML file.
     ///
@@ -50,6 +371,17 @@ impl SmeeConfig {
     /// ```
     ///
     pub fn from_toml(path: &Path) -> Result<Self, Error> {
+        ConfigSnapshot::read(path).map(ConfigSnapshot::into_config)
+    }
+
+    /// Parses and validates config text, e.g. an editor buffer that has not
+    /// been saved yet, with the same checks as [`Self::from_toml`].
+    pub fn from_toml_str(contents: &str) -> Result<Self, Error> {
+        Self::from_toml_bytes(contents.as_bytes())
+    }
+
+    /// Checks that `path` names an existing `.toml` file.
+    fn check_config_path(path: &Path) -> Result<(), Error> {
         if !path.exists() {
             return Err(Error::MissingFile);
         }
@@ -60,34 +392,584 @@ impl SmeeConfig {
         if !ext.eq_ignore_ascii_case(OsStr::new("toml")) {
             return Err(Error::NotATomlFileExtension);
         }
-        let data = fs::read(path).map_err(Error::ReadError)?;
-        let config: SmeeConfig = toml::from_slice(&data).map_err(Error::ParseError)?;
+        Ok(())
+    }
+
+    /// Parses and validates config file contents.
+    fn from_toml_bytes(data: &[u8]) -> Result<Self, Error> {
+        let mut config: SmeeConfig = toml::from_slice(data).map_err(Error::ParseError)?;
+        if !config.defaults.is_empty() {
+            config.apply_phase_defaults(data)?;
+        }
         config.validate()?;
         Ok(config)
     }
 
+    /// Re-reads the entries of each phase with `[defaults.<phase>]` from the
+    /// raw `data`, adding the default values for keys an entry does not set.
+    fn apply_phase_defaults(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.validate_phase_defaults()?;
+        let mut raw: toml::Table = toml::from_slice(data)?;
+        for (phase, defaults) in &self.defaults {
+            let Some(toml::Value::Array(entries)) = raw.remove(phase.as_str()) else {
+                continue;
+            };
+            let entries = entries
+                .into_iter()
+                .map(|entry| match entry {
+                    toml::Value::Table(mut entry) => {
+                        for (key, value) in &defaults.0 {
+                            if !spellings(key).iter().any(|key| entry.contains_key(*key)) {
+                                entry.insert(key.clone(), value.clone());
+                            }
+                        }
+                        toml::Value::Table(entry)
+                    }
+                    other => other,
+                })
+                .collect();
+            self.hooks
+                .insert(*phase, toml::Value::Array(entries).try_into()?);
+        }
+        Ok(())
+    }
+
+    /// Checks that every `[defaults.<phase>]` table only holds hook fields
+    /// with valid values, and none that identify an entry.
+    fn validate_phase_defaults(&self) -> Result<(), ValidationError> {
+        for (phase, defaults) in &self.defaults {
+            if let Some(key) = ["command", "task", "name"]
+                .into_iter()
+                .find(|key| defaults.0.contains_key(*key))
+            {
+                return Err(ValidationError::InvalidPhaseDefault {
+                    phase: *phase,
+                    reason: format!("'{key}' cannot have a default"),
+                });
+            }
+            if let Err(error) = toml::Value::Table(defaults.0.clone()).try_into::<HookDefinition>()
+            {
+                return Err(ValidationError::InvalidPhaseDefault {
+                    phase: *phase,
+                    reason: error.message().to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Configured phases that get a hook wrapper, sorted by name. Phases listed
+    /// in `settings.never_install` are left out.
+    /// Lines `install.<phase>.prepend` adds to the wrapper of `phase`.
+    pub fn wrapper_prepend(&self, phase: LifeCyclePhase) -> Option<&str> {
+        self.install
+            .get(&phase)
+            .and_then(|install| install.prepend.as_deref())
+    }
+
+    pub fn installable_phases(&self) -> Vec<LifeCyclePhase> {
+        let mut phases: Vec<_> = self
+            .hooks
+            .keys()
+            .copied()
+            .filter(|phase| !self.settings.never_install.contains(phase))
+            .collect();
+        phases.sort_by_key(|phase| phase.as_str());
+        phases
+    }
+
     pub fn validate(&self) -> Result<(), ValidationError> {
+        if self
+            .settings
+            .hooks_path
+            .as_deref()
+            .is_some_and(|hooks_path| hooks_path.trim().is_empty())
+        {
+            return Err(ValidationError::EmptyHooksPath);
+        }
+        if self
+            .settings
+            .messages_dir
+            .as_deref()
+            .is_some_and(|messages_dir| messages_dir.trim().is_empty())
+        {
+            return Err(ValidationError::EmptyMessagesDir);
+        }
+        if let Some(group) = self
+            .settings
+            .groups
+            .iter()
+            .find_map(|(group, limit)| (*limit == 0).then_some(group))
+        {
+            return Err(ValidationError::ZeroGroupLimit {
+                group: group.clone(),
+            });
+        }
+        if let Some(report) = &self.settings.report {
+            validate_report(report)?;
+        }
+        for (phase, install) in &self.install {
+            if let Some(prepend) = &install.prepend
+                && let Some(reason) = prepend_problem(prepend)
+            {
+                return Err(ValidationError::InvalidPrepend {
+                    phase: *phase,
+                    reason,
+                });
+            }
+        }
+        for (extension, interpreter) in &self.settings.interpreters {
+            let reason = if extension.is_empty() || extension.contains(['.', '/', '\\']) {
+                Some("the key must be a file extension without the dot, e.g. py".to_string())
+            } else {
+                match crate::shell_words::split(interpreter) {
+                    Ok(words) if words.is_empty() => Some("must not be empty".to_string()),
+                    Ok(_) => None,
+                    Err(error) => Some(format!("cannot be split into words: {error}")),
+                }
+            };
+            if let Some(reason) = reason {
+                return Err(ValidationError::InvalidInterpreter {
+                    extension: extension.clone(),
+                    reason,
+                });
+            }
+        }
+        if let Some(phase) = self.settings.phases.iter().find_map(|(phase, settings)| {
+            (settings.max_duration.map(|max| max.0.is_zero()) == Some(true)).then_some(*phase)
+        }) {
+            return Err(ValidationError::ZeroMaxDuration { phase });
+        }
+        self.validate_phase_defaults()?;
         for (phase, hooks) in &self.hooks {
-            if hooks.is_empty() {
-                return Err(ValidationError::EmptyHookEntries {
-                    hook_name: phase.to_string(),
+            self.validate_entries(&phase.to_string(), hooks)?;
+        }
+        for (task, hooks) in &self.tasks {
+            if !is_path_safe_name(task) {
+                return Err(ValidationError::InvalidTaskName { task: task.clone() });
+            }
+            self.validate_entries(&format!("tasks.{task}"), hooks)?;
+        }
+        if let Some(cycle) = self.find_task_cycle() {
+            return Err(ValidationError::TaskCycle {
+                cycle: cycle.join(" -> "),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Settings that pass validation but are ignored where they are used,
+    /// e.g. `restage` without a `fixable` command. Each warning says why the
+    /// setting has no effect.
+    pub fn warnings(&self) -> Vec<ConfigWarning> {
+        let mut warnings = Vec::new();
+        let mut phases: Vec<_> = self.hooks.keys().copied().collect();
+        phases.sort_by_key(|phase| phase.as_str());
+        for phase in phases {
+            for (index, hook) in self.hooks[&phase].iter().enumerate() {
+                let location = format!("Hook '{phase}' entry #{}", index + 1);
+                if hook.restage && phase != LifeCyclePhase::PreCommit {
+                    warnings.push(ConfigWarning {
+                        phase: Some(phase),
+                        message: format!(
+                            "{location}: restage has no effect in {phase}; only pre-commit builds the commit from the staged files"
+                        ),
+                    });
+                }
+                if hook.command.contains(CHANGED_FILES_PLACEHOLDER)
+                    && phase != LifeCyclePhase::PrePush
+                {
+                    warnings.push(ConfigWarning {
+                        phase: Some(phase),
+                        message: format!(
+                            "{location}: {CHANGED_FILES_PLACEHOLDER} is only filled in for pre-push; in {phase} the command is always skipped"
+                        ),
+                    });
+                }
+                entry_warnings(Some(phase), &location, hook, &mut warnings);
+            }
+        }
+        for (task, hooks) in &self.tasks {
+            for (index, hook) in hooks.iter().enumerate() {
+                let location = format!("Hook 'tasks.{task}' entry #{}", index + 1);
+                entry_warnings(None, &location, hook, &mut warnings);
+            }
+        }
+        let mut unused_defaults: Vec<_> = self
+            .defaults
+            .keys()
+            .filter(|phase| !self.hooks.contains_key(phase))
+            .collect();
+        unused_defaults.sort_by_key(|phase| phase.as_str());
+        for phase in unused_defaults {
+            warnings.push(ConfigWarning {
+                phase: Some(*phase),
+                message: format!(
+                    "defaults.{phase} is ignored; there are no {phase} entries to apply it to"
+                ),
+            });
+        }
+        let installable = self.installable_phases();
+        let mut unused_install: Vec<_> = self
+            .install
+            .keys()
+            .filter(|phase| !installable.contains(phase))
+            .collect();
+        unused_install.sort_by_key(|phase| phase.as_str());
+        for phase in unused_install {
+            warnings.push(ConfigWarning {
+                phase: Some(*phase),
+                message: format!("install.{phase} is ignored; no wrapper is installed for {phase}"),
+            });
+        }
+        warnings
+    }
+
+    /// The entries `phase` runs, with task references expanded.
+    pub fn phase_entries(&self, phase: LifeCyclePhase) -> Option<Vec<HookDefinition>> {
+        self.hooks
+            .get(&phase)
+            .map(|hooks| self.expand_task_refs(hooks))
+    }
+
+    /// The entries the task named `task` runs, with task references expanded.
+    pub fn task_entries(&self, task: &str) -> Option<Vec<HookDefinition>> {
+        self.tasks
+            .get(task)
+            .map(|hooks| self.expand_task_refs(hooks))
+    }
+
+    /// Replaces every `task = "..."` entry of `hooks` with the entries of that
+    /// task, recursively. References to unknown tasks and references that
+    /// would recurse into a task being expanded are dropped; validation
+    /// rejects both.
+    pub fn expand_task_refs(&self, hooks: &[HookDefinition]) -> Vec<HookDefinition> {
+        let mut expanded = Vec::with_capacity(hooks.len());
+        self.expand_into(hooks, &mut Vec::new(), &mut expanded);
+        expanded
+    }
+
+    fn expand_into<'a>(
+        &'a self,
+        hooks: &'a [HookDefinition],
+        expanding: &mut Vec<&'a str>,
+        expanded: &mut Vec<HookDefinition>,
+    ) {
+        for hook in hooks {
+            let Some(task) = hook.task.as_deref() else {
+                expanded.push(match &hook.script {
+                    Some(script) => HookDefinition {
+                        command: self.settings.script_command(script),
+                        no_shell: true,
+                        ..hook.clone()
+                    },
+                    None => hook.clone(),
                 });
+                continue;
+            };
+            if let Some(entries) = self.tasks.get(task)
+                && !expanding.contains(&task)
+            {
+                expanding.push(task);
+                self.expand_into(entries, expanding, expanded);
+                expanding.pop();
+            }
+        }
+    }
+
+    /// A chain of task references that leads back to its start, e.g.
+    /// `["a", "b", "a"]`.
+    fn find_task_cycle(&self) -> Option<Vec<String>> {
+        fn visit<'a>(
+            config: &'a SmeeConfig,
+            task: &'a str,
+            path: &mut Vec<&'a str>,
+            done: &mut Vec<&'a str>,
+        ) -> Option<Vec<String>> {
+            if let Some(start) = path.iter().position(|entry| *entry == task) {
+                let mut cycle: Vec<String> = path[start..].iter().map(|t| t.to_string()).collect();
+                cycle.push(task.to_string());
+                return Some(cycle);
+            }
+            if done.contains(&task) {
+                return None;
+            }
+            path.push(task);
+            for hook in config.tasks.get(task).into_iter().flatten() {
+                if let Some(next) = hook.task.as_deref()
+                    && let Some(cycle) = visit(config, next, path, done)
+                {
+                    return Some(cycle);
+                }
             }
+            path.pop();
+            done.push(task);
+            None
+        }
+
+        let mut done = Vec::new();
+        self.tasks
+            .keys()
+            .find_map(|task| visit(self, task, &mut Vec::new(), &mut done))
+    }
+
+    fn validate_entries(
+        &self,
+        hook_name: &str,
+        hooks: &[HookDefinition],
+    ) -> Result<(), ValidationError> {
+        if hooks.is_empty() {
+            return Err(ValidationError::EmptyHookEntries {
+                hook_name: hook_name.to_string(),
+            });
+        }
 
-            for (index, hook_definition) in hooks.iter().enumerate() {
-                if hook_definition.command.trim().is_empty() {
-                    return Err(ValidationError::EmptyCommand {
-                        hook_name: phase.to_string(),
+        for (index, hook_definition) in hooks.iter().enumerate() {
+            if let Some(task) = &hook_definition.task {
+                if hook_definition.script.is_some() {
+                    return Err(ValidationError::ScriptAndCommand {
+                        hook_name: hook_name.to_string(),
+                        entry_index: index + 1,
+                        other: "task",
+                    });
+                }
+                if !hook_definition.command.is_empty() {
+                    return Err(ValidationError::CommandAndTask {
+                        hook_name: hook_name.to_string(),
+                        entry_index: index + 1,
+                    });
+                }
+                if !self.tasks.contains_key(task) {
+                    return Err(ValidationError::UnknownTaskReference {
+                        hook_name: hook_name.to_string(),
                         entry_index: index + 1,
+                        task: task.clone(),
                     });
                 }
+                continue;
+            }
+            if let Some(group) = &hook_definition.group
+                && !self.settings.groups.contains_key(group)
+            {
+                return Err(ValidationError::UnknownGroup {
+                    hook_name: hook_name.to_string(),
+                    entry_index: index + 1,
+                    group: group.clone(),
+                });
             }
+            validate_entry(hook_name, index, hook_definition)?;
         }
-
         Ok(())
     }
 }
 
+/// Why `prepend` cannot go into a wrapper, or `None` when it can.
+fn prepend_problem(prepend: &str) -> Option<String> {
+    if prepend.trim().is_empty() {
+        return Some("prepend must not be empty".to_string());
+    }
+    if prepend.contains(['\0', '\r']) {
+        return Some("prepend must not contain NUL or carriage return characters".to_string());
+    }
+    prepend
+        .lines()
+        .any(|line| line.trim_start().starts_with(PREPEND_DELIMITER_PREFIX))
+        .then(|| {
+            format!("prepend must not contain lines starting with '{PREPEND_DELIMITER_PREFIX}'")
+        })
+}
+
+/// Start of the comment lines that delimit `prepend` lines in a wrapper.
+pub const PREPEND_DELIMITER_PREFIX: &str = "# git-smee:";
+
+fn validate_report(report: &ReportSettings) -> Result<(), ValidationError> {
+    let webhook = crate::webhook::Webhook::new(report.url.clone()).map_err(|error| {
+        ValidationError::InvalidReport {
+            field: "url",
+            reason: error.to_string(),
+        }
+    })?;
+    if report
+        .secret_env
+        .as_deref()
+        .is_some_and(|name| name.is_empty() || name.contains('='))
+    {
+        return Err(ValidationError::InvalidReport {
+            field: "secret_env",
+            reason: "must be the name of an environment variable".to_string(),
+        });
+    }
+    webhook
+        .with_header(report.secret_header(), "")
+        .map_err(|_| ValidationError::InvalidReport {
+            field: "secret_header",
+            reason: "must be a single-line header name without ':'".to_string(),
+        })?;
+    if report.retries() > MAX_REPORT_RETRIES {
+        return Err(ValidationError::InvalidReport {
+            field: "retries",
+            reason: format!("must be at most {MAX_REPORT_RETRIES}"),
+        });
+    }
+    Ok(())
+}
+
+fn validate_entry(
+    hook_name: &str,
+    index: usize,
+    hook_definition: &HookDefinition,
+) -> Result<(), ValidationError> {
+    match &hook_definition.script {
+        Some(_) if !hook_definition.command.is_empty() => {
+            return Err(ValidationError::ScriptAndCommand {
+                hook_name: hook_name.to_string(),
+                entry_index: index + 1,
+                other: "command",
+            });
+        }
+        Some(script) if script.trim().is_empty() => {
+            return Err(ValidationError::EmptyScript {
+                hook_name: hook_name.to_string(),
+                entry_index: index + 1,
+            });
+        }
+        Some(_) => {}
+        None if hook_definition.command.trim().is_empty() => {
+            return Err(ValidationError::EmptyCommand {
+                hook_name: hook_name.to_string(),
+                entry_index: index + 1,
+            });
+        }
+        None => {}
+    }
+    if hook_definition
+        .fixable
+        .as_deref()
+        .is_some_and(|fixable| fixable.trim().is_empty())
+    {
+        return Err(ValidationError::EmptyFixCommand {
+            hook_name: hook_name.to_string(),
+            entry_index: index + 1,
+        });
+    }
+    if let Some(mutex) = &hook_definition.mutex
+        && !is_path_safe_name(mutex)
+    {
+        return Err(ValidationError::InvalidMutexName {
+            hook_name: hook_name.to_string(),
+            entry_index: index + 1,
+            mutex: mutex.clone(),
+        });
+    }
+    if hook_definition.no_shell
+        && let Err(source) = crate::shell_words::split(&hook_definition.command)
+    {
+        return Err(ValidationError::UnparsableCommand {
+            hook_name: hook_name.to_string(),
+            entry_index: index + 1,
+            source,
+        });
+    }
+    if hook_definition.timeout == Some(HookTimeout::Seconds(0)) {
+        return Err(ValidationError::ZeroTimeout {
+            hook_name: hook_name.to_string(),
+            entry_index: index + 1,
+        });
+    }
+    if hook_definition.max_output_bytes == Some(0) {
+        return Err(ValidationError::ZeroMaxOutput {
+            hook_name: hook_name.to_string(),
+            entry_index: index + 1,
+        });
+    }
+    if hook_definition
+        .owner
+        .as_deref()
+        .is_some_and(|owner| owner.trim().is_empty())
+    {
+        return Err(ValidationError::EmptyOwner {
+            hook_name: hook_name.to_string(),
+            entry_index: index + 1,
+        });
+    }
+    if let Some(url) = &hook_definition.description_url
+        && !is_absolute_url(url)
+    {
+        return Err(ValidationError::InvalidDescriptionUrl {
+            hook_name: hook_name.to_string(),
+            entry_index: index + 1,
+            url: url.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Warnings for settings of a single entry that depend only on the entry.
+fn entry_warnings(
+    phase: Option<LifeCyclePhase>,
+    location: &str,
+    hook: &HookDefinition,
+    warnings: &mut Vec<ConfigWarning>,
+) {
+    let mut warn = |message: String| {
+        warnings.push(ConfigWarning {
+            phase,
+            message: format!("{location}: {message}"),
+        })
+    };
+    if hook.restage && hook.fixable.is_none() {
+        warn("restage is ignored without fixable; it re-stages files after the fix runs".into());
+    }
+    if !hook.on_missing.is_fail() && hook.requires.is_empty() {
+        warn("on_missing is ignored without requires; there is nothing to be missing".into());
+    }
+    if hook.group.is_some() && !hook.parallel_execution_allowed {
+        warn(
+            "group has no effect without parallel = true; sequential commands already run one at a time"
+                .into(),
+        );
+    }
+    if hook.enforce_after.is_some() && !hook.severity.is_error() {
+        warn(
+            "enforce_after has no effect with severity = \"warning\"; failures are warnings before and after the date"
+                .into(),
+        );
+    }
+}
+
+/// All keys that set the same hook field as `key`, e.g. `parallel` and its
+/// older spelling `parallel_execution_allowed`.
+fn spellings(key: &str) -> Vec<&str> {
+    match key {
+        "parallel" | "parallel_execution_allowed" => vec!["parallel", "parallel_execution_allowed"],
+        key => vec![key],
+    }
+}
+
+/// Mutex and task names become file and directory names, so they are limited
+/// to characters that are safe in a path component.
+fn is_path_safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Whether `url` looks like `<scheme>://<rest>` without whitespace, e.g.
+/// `https://wiki.example.com/hooks/lint`.
+fn is_absolute_url(url: &str) -> bool {
+    url.split_once("://").is_some_and(|(scheme, rest)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            && !rest.is_empty()
+            && !rest.chars().any(char::is_whitespace)
+    })
+}
+
 impl Default for SmeeConfig {
     fn default() -> Self {
         let mut hash_map: HashMap<LifeCyclePhase, Vec<HookDefinition>> = hash_map::HashMap::new();
@@ -96,9 +978,16 @@ impl Default for SmeeConfig {
             vec![HookDefinition {
                 command: "echo 'Default pre-commit hook'".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
-        Self { hooks: hash_map }
+        Self {
+            settings: Settings::default(),
+            tasks: BTreeMap::new(),
+            defaults: HashMap::new(),
+            install: Default::default(),
+            hooks: hash_map,
+        }
     }
 }
 
@@ -118,126 +1007,614 @@ impl TryFrom<&SmeeConfig> for String {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct HookDefinition {
+    /// Short label for the command, e.g. `fmt`. Used to tag streamed output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Who maintains the command, e.g. `@acme/platform` or an email address.
+    /// `git smee owners` lists commands by owner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Runbook or docs page on fixing a failure of the command, e.g.
+    /// `https://wiki.example.com/hooks/lint`. Printed when the command fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_url: Option<String>,
+    /// Shell command to run. Empty for entries that reference a `task`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub command: String,
-    #[serde(default = "bool::default")]
+    /// Name of a `[tasks]` entry to run in place of this entry, instead of a
+    /// command. The task's entries keep their own settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Script file to run instead of a command, relative to the repository
+    /// root, e.g. `scripts/check.py`. It runs with the interpreter its
+    /// extension maps to in `[settings.interpreters]`, or directly when none
+    /// does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Run the command in the parallel phase. Written as `parallel`; the
+    /// older `parallel_execution_allowed` key is still accepted.
+    #[serde(
+        default = "bool::default",
+        rename = "parallel",
+        alias = "parallel_execution_allowed"
+    )]
     pub parallel_execution_allowed: bool,
+    /// Seconds after which the command is stopped, or `"none"` for no limit
+    /// even when `[defaults]` sets one. Unset means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<HookTimeout>,
+    /// Seconds to wait after `SIGTERM` before escalating to `SIGKILL` when the
+    /// command is stopped by a timeout or cancellation. Defaults to
+    /// [`crate::executor::DEFAULT_GRACE_PERIOD`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_period: Option<u64>,
+    /// Give the command a private, freshly created `TMPDIR` under
+    /// `.git/smee/tmp/<phase>/hook-<n>` that is removed after it finishes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub isolate_tmp: bool,
+    /// Give the command a directory under
+    /// `.git/smee/artifacts/<run-id>/<phase>/<hook>` for reports it wants to
+    /// keep, exported as `SMEE_ARTIFACTS_DIR`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub artifacts_dir: bool,
+    /// Whether a failure blocks the Git operation (`error`) or is only
+    /// reported (`warning`).
+    #[serde(default, skip_serializing_if = "Severity::is_error")]
+    pub severity: Severity,
+    /// Soft-launch window: before this date (UTC) failures are downgraded to
+    /// warnings, from this date on they use `severity`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enforce_after: Option<CalendarDate>,
+    /// Command that fixes what this command checks, e.g. `cargo fmt` for
+    /// `cargo fmt --check`. Offered interactively when the command fails on a
+    /// terminal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixable: Option<String>,
+    /// Re-stage the files that were staged before `fixable` ran, so the fix
+    /// ends up in the commit being made.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub restage: bool,
+    /// Name of a shared resource, e.g. `cargo-target`. Commands with the same
+    /// mutex never run at the same time, in this run or in a concurrent one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mutex: Option<String>,
+    /// Concurrency group, e.g. `io` or `cpu`. At most the group's limit in
+    /// `[settings.groups]` of its parallel commands run at the same time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Tools the command needs, e.g. `["node >= 18", "cargo"]`. They are
+    /// probed before the command runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<Requirement>,
+    /// Whether an unmet `requires` entry fails the command or skips it.
+    #[serde(default, skip_serializing_if = "MissingRequirement::is_fail")]
+    pub on_missing: MissingRequirement,
+    /// Run the command directly instead of through `sh -c` / `cmd.exe /C`.
+    /// The command is split into words with [`crate::shell_words::split`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_shell: bool,
+    /// Bytes of stdout and of stderr forwarded for the command. Beyond the
+    /// cap only the first and last half are kept, joined by an
+    /// `output truncated (N bytes)` marker. Unset means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<u64>,
+    /// `false` runs the command without network access: in a network
+    /// namespace on Linux, elsewhere only with proxy env vars pointing
+    /// nowhere. Unset means the network is available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<bool>,
+    /// Nonzero exit codes that also count as success, e.g. `[0, 2]` for a
+    /// tool that exits 2 after fixing files. 0 always counts as success.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub success_codes: Vec<i32>,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone, Copy)]
-#[serde(rename_all = "kebab-case")]
-pub enum LifeCyclePhase {
-    ApplypatchMsg,
-    PreApplypatch,
-    PostApplypatch,
-    PreCommit,
-    PrepareCommitMsg,
-    CommitMsg,
-    PostCommit,
-    PreMergeCommit,
-    PreRebase,
-    PostCheckout,
-    PostMerge,
-    PostRewrite,
-    PrePush,
-    PreReceive,
-    Update,
-    ProcReceive,
-    PostReceive,
-    ReferenceTransaction,
-    PushToCheckout,
-    PreAutoGc,
-    PostUpdate,
-    FsmonitorWatchman,
-    PostIndexChange,
-}
-
-const ALL_LIFECYCLE_PHASES: [LifeCyclePhase; 23] = [
-    LifeCyclePhase::ApplypatchMsg,
-    LifeCyclePhase::PreApplypatch,
-    LifeCyclePhase::PostApplypatch,
-    LifeCyclePhase::PreCommit,
-    LifeCyclePhase::PrepareCommitMsg,
-    LifeCyclePhase::CommitMsg,
-    LifeCyclePhase::PostCommit,
-    LifeCyclePhase::PreMergeCommit,
-    LifeCyclePhase::PreRebase,
-    LifeCyclePhase::PostCheckout,
-    LifeCyclePhase::PostMerge,
-    LifeCyclePhase::PostRewrite,
-    LifeCyclePhase::PrePush,
-    LifeCyclePhase::PreReceive,
-    LifeCyclePhase::Update,
-    LifeCyclePhase::ProcReceive,
-    LifeCyclePhase::PostReceive,
-    LifeCyclePhase::ReferenceTransaction,
-    LifeCyclePhase::PushToCheckout,
-    LifeCyclePhase::PreAutoGc,
-    LifeCyclePhase::PostUpdate,
-    LifeCyclePhase::FsmonitorWatchman,
-    LifeCyclePhase::PostIndexChange,
-];
+/// Label of the entry at `config_index` of a phase: its `name`, or
+/// `hook-<n>` counting from 1. Tags a hook's streamed output and reports.
+pub fn hook_label(config_index: usize, hook: &HookDefinition) -> String {
+    match hook.name.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => format!("hook-{}", config_index + 1),
+    }
+}
 
-impl LifeCyclePhase {
-    pub const fn all() -> &'static [LifeCyclePhase] {
-        &ALL_LIFECYCLE_PHASES
+impl HookDefinition {
+    /// Severity that applies on `today`, taking `enforce_after` into account.
+    pub fn effective_severity(&self, today: CalendarDate) -> Severity {
+        match self.enforce_after {
+            Some(enforce_after) if today < enforce_after => Severity::Warning,
+            _ => self.severity,
+        }
+    }
+}
+
+/// How a failing hook command affects the overall run.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// A failure fails the run and blocks the Git operation.
+    #[default]
+    Error,
+    /// A failure is reported prominently but does not block.
+    Warning,
+}
+
+impl Severity {
+    pub const fn is_error(&self) -> bool {
+        matches!(self, Self::Error)
     }
 
     pub const fn as_str(self) -> &'static str {
         match self {
-            LifeCyclePhase::ApplypatchMsg => "applypatch-msg",
-            LifeCyclePhase::PreApplypatch => "pre-applypatch",
-            LifeCyclePhase::PostApplypatch => "post-applypatch",
-            LifeCyclePhase::PreCommit => "pre-commit",
-            LifeCyclePhase::PrepareCommitMsg => "prepare-commit-msg",
-            LifeCyclePhase::CommitMsg => "commit-msg",
-            LifeCyclePhase::PostCommit => "post-commit",
-            LifeCyclePhase::PreMergeCommit => "pre-merge-commit",
-            LifeCyclePhase::PreRebase => "pre-rebase",
-            LifeCyclePhase::PostCheckout => "post-checkout",
-            LifeCyclePhase::PostMerge => "post-merge",
-            LifeCyclePhase::PostRewrite => "post-rewrite",
-            LifeCyclePhase::PrePush => "pre-push",
-            LifeCyclePhase::PreReceive => "pre-receive",
-            LifeCyclePhase::Update => "update",
-            LifeCyclePhase::ProcReceive => "proc-receive",
-            LifeCyclePhase::PostReceive => "post-receive",
-            LifeCyclePhase::ReferenceTransaction => "reference-transaction",
-            LifeCyclePhase::PushToCheckout => "push-to-checkout",
-            LifeCyclePhase::PreAutoGc => "pre-auto-gc",
-            LifeCyclePhase::PostUpdate => "post-update",
-            LifeCyclePhase::FsmonitorWatchman => "fsmonitor-watchman",
-            LifeCyclePhase::PostIndexChange => "post-index-change",
+            Self::Error => "error",
+            Self::Warning => "warning",
         }
     }
 }
 
-impl FromStr for LifeCyclePhase {
-    type Err = crate::Error;
+/// What happens to a command whose `requires` are not met.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingRequirement {
+    /// The command fails with a "missing prerequisite" message.
+    #[default]
+    Fail,
+    /// The command is skipped and the run continues.
+    Skip,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::all()
-            .iter()
-            .copied()
-            .find(|phase| phase.as_str() == s)
-            .ok_or_else(|| Error::UnknownLifeCyclePhase(s.to_string()))
+impl MissingRequirement {
+    pub const fn is_fail(&self) -> bool {
+        matches!(self, Self::Fail)
     }
 }
 
-impl fmt::Display for LifeCyclePhase {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.as_str())
+/// A tool a hook command needs on `PATH`, optionally with a minimum or exact
+/// version, e.g. `cargo` or `node >= 18`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Requirement {
+    tool: String,
+    constraint: Option<(VersionOp, Vec<u64>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum VersionOp {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl VersionOp {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Equal => "=",
+            Self::GreaterOrEqual => ">=",
+            Self::Greater => ">",
+        }
     }
 }
 
-#[derive(Debug, Error)]
-pub enum Error {
-    #[error("The specified configuration file is missing")]
-    MissingFile,
-    #[error("The specified configuration path exists but is not a regular file")]
-    NotAFile,
+impl Requirement {
+    pub fn tool(&self) -> &str {
+        &self.tool
+    }
+
+    /// Whether the requirement constrains the tool's version at all.
+    pub fn has_version_constraint(&self) -> bool {
+        self.constraint.is_some()
+    }
+
+    /// Whether `version` (e.g. `[18, 17, 0]`) satisfies the constraint.
+    /// Missing components count as zero, so `18` equals `18.0.0`.
+    pub fn accepts_version(&self, version: &[u64]) -> bool {
+        let Some((op, required)) = &self.constraint else {
+            return true;
+        };
+        let length = version.len().max(required.len());
+        let component = |parts: &[u64], index: usize| parts.get(index).copied().unwrap_or(0);
+        let ordering = (0..length)
+            .map(|index| component(version, index).cmp(&component(required, index)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal);
+        match op {
+            VersionOp::Less => ordering.is_lt(),
+            VersionOp::LessOrEqual => ordering.is_le(),
+            VersionOp::Equal => ordering.is_eq(),
+            VersionOp::GreaterOrEqual => ordering.is_ge(),
+            VersionOp::Greater => ordering.is_gt(),
+        }
+    }
+}
+
+/// Parses a dotted version such as `18.17.0`, ignoring a leading `v`.
+pub fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    version
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<_>>>()
+        .filter(|parts| !parts.is_empty())
+}
+
+impl FromStr for Requirement {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidRequirement(s.to_string());
+        let Some(op_start) = s.find(['<', '>', '=']) else {
+            let tool = s.trim();
+            if tool.is_empty() || tool.contains(char::is_whitespace) {
+                return Err(invalid());
+            }
+            return Ok(Self {
+                tool: tool.to_string(),
+                constraint: None,
+            });
+        };
+        let tool = s[..op_start].trim();
+        let rest = &s[op_start..];
+        let (op, version) = [
+            ("<=", VersionOp::LessOrEqual),
+            (">=", VersionOp::GreaterOrEqual),
+            ("==", VersionOp::Equal),
+            ("<", VersionOp::Less),
+            (">", VersionOp::Greater),
+            ("=", VersionOp::Equal),
+        ]
+        .into_iter()
+        .find_map(|(symbol, op)| rest.strip_prefix(symbol).map(|version| (op, version)))
+        .ok_or_else(invalid)?;
+        if tool.is_empty() || tool.contains(char::is_whitespace) {
+            return Err(invalid());
+        }
+        let version = parse_version(version.trim()).ok_or_else(invalid)?;
+        Ok(Self {
+            tool: tool.to_string(),
+            constraint: Some((op, version)),
+        })
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.tool)?;
+        if let Some((op, version)) = &self.constraint {
+            let version: Vec<String> = version.iter().map(u64::to_string).collect();
+            write!(f, " {} {}", op.as_str(), version.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Requirement {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Requirement {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Declares [`LifeCyclePhase`] from a single variant/hook-name table, so the
+/// serde names, [`LifeCyclePhase::as_str`], [`LifeCyclePhase::all`],
+/// `FromStr`, and `Display` cannot drift apart when a phase is added.
+macro_rules! lifecycle_phases {
+    ($($variant:ident => $name:literal),+ $(,)?) => {
+        #[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone, Copy)]
+        pub enum LifeCyclePhase {
+            $(
+                #[serde(rename = $name)]
+                $variant,
+            )+
+        }
+
+        const ALL_LIFECYCLE_PHASES: &[LifeCyclePhase] = &[$(LifeCyclePhase::$variant),+];
+
+        impl LifeCyclePhase {
+            /// Git's name for the hook, e.g. `pre-commit`.
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $(LifeCyclePhase::$variant => $name,)+
+                }
+            }
+        }
+    };
+}
+
+lifecycle_phases! {
+    ApplypatchMsg => "applypatch-msg",
+    PreApplypatch => "pre-applypatch",
+    PostApplypatch => "post-applypatch",
+    PreCommit => "pre-commit",
+    PrepareCommitMsg => "prepare-commit-msg",
+    CommitMsg => "commit-msg",
+    PostCommit => "post-commit",
+    PreMergeCommit => "pre-merge-commit",
+    PreRebase => "pre-rebase",
+    PostCheckout => "post-checkout",
+    PostMerge => "post-merge",
+    PostRewrite => "post-rewrite",
+    PrePush => "pre-push",
+    PreReceive => "pre-receive",
+    Update => "update",
+    ProcReceive => "proc-receive",
+    PostReceive => "post-receive",
+    ReferenceTransaction => "reference-transaction",
+    PushToCheckout => "push-to-checkout",
+    PreAutoGc => "pre-auto-gc",
+    PostUpdate => "post-update",
+    FsmonitorWatchman => "fsmonitor-watchman",
+    PostIndexChange => "post-index-change",
+}
+
+impl LifeCyclePhase {
+    pub const fn all() -> &'static [LifeCyclePhase] {
+        ALL_LIFECYCLE_PHASES
+    }
+
+    /// Whether Git passes positional arguments to the hook, such as the
+    /// message file for `commit-msg`.
+    pub const fn accepts_args(self) -> bool {
+        !matches!(
+            self,
+            LifeCyclePhase::PreApplypatch
+                | LifeCyclePhase::PostApplypatch
+                | LifeCyclePhase::PreCommit
+                | LifeCyclePhase::PostCommit
+                | LifeCyclePhase::PreMergeCommit
+                | LifeCyclePhase::PreReceive
+                | LifeCyclePhase::ProcReceive
+                | LifeCyclePhase::PostReceive
+                | LifeCyclePhase::PreAutoGc
+        )
+    }
+
+    /// Whether Git feeds the hook data on stdin, such as the refs being pushed
+    /// for `pre-push`.
+    pub const fn reads_stdin(self) -> bool {
+        matches!(
+            self,
+            LifeCyclePhase::PostRewrite
+                | LifeCyclePhase::PrePush
+                | LifeCyclePhase::PreReceive
+                | LifeCyclePhase::ProcReceive
+                | LifeCyclePhase::PostReceive
+                | LifeCyclePhase::ReferenceTransaction
+        )
+    }
+
+    /// One-line summary of when Git runs the hook.
+    pub const fn description(self) -> &'static str {
+        match self {
+            LifeCyclePhase::ApplypatchMsg => "Edit the commit message of a patch",
+            LifeCyclePhase::PreApplypatch => "Run before a patch is applied",
+            LifeCyclePhase::PostApplypatch => "Run after a patch is applied",
+            LifeCyclePhase::PreCommit => "Run before a commit is created",
+            LifeCyclePhase::PrepareCommitMsg => "Prepare the default commit message",
+            LifeCyclePhase::CommitMsg => "Validate or modify the commit message",
+            LifeCyclePhase::PostCommit => "Run after a commit is created",
+            LifeCyclePhase::PreMergeCommit => "Run before a merge commit is created",
+            LifeCyclePhase::PreRebase => "Run before a rebase starts",
+            LifeCyclePhase::PostCheckout => "Run after a checkout",
+            LifeCyclePhase::PostMerge => "Run after a merge",
+            LifeCyclePhase::PostRewrite => "Run after commands that rewrite commits",
+            LifeCyclePhase::PrePush => "Run before a push",
+            LifeCyclePhase::PreReceive => "Run before refs are updated (server-side)",
+            LifeCyclePhase::Update => "Run once per ref update (server-side)",
+            LifeCyclePhase::ProcReceive => "Handle receive-pack commands (server-side)",
+            LifeCyclePhase::PostReceive => "Run after refs are updated (server-side)",
+            LifeCyclePhase::ReferenceTransaction => "Run when reference transaction state changes",
+            LifeCyclePhase::PushToCheckout => {
+                "Run when a push tries to update the checked-out branch"
+            }
+            LifeCyclePhase::PreAutoGc => "Run before automatic garbage collection",
+            LifeCyclePhase::PostUpdate => "Run after refs are updated (server-side)",
+            LifeCyclePhase::FsmonitorWatchman => "Integration with watchman file monitor",
+            LifeCyclePhase::PostIndexChange => "Run after the index is written",
+        }
+    }
+}
+
+impl FromStr for LifeCyclePhase {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|phase| phase.as_str() == s)
+            .ok_or_else(|| Error::UnknownLifeCyclePhase(s.to_string()))
+    }
+}
+
+impl fmt::Display for LifeCyclePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A `YYYY-MM-DD` calendar date, as used by `enforce_after`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl CalendarDate {
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        let valid = (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month);
+        valid.then_some(Self { year, month, day })
+    }
+
+    /// Today's date in UTC.
+    pub fn today_utc() -> Self {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self::from_days_since_unix_epoch(i64::try_from(seconds / 86_400).unwrap_or_default())
+    }
+
+    // Howard Hinnant's `civil_from_days` algorithm.
+    fn from_days_since_unix_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Self {
+            year: i32::try_from(year).unwrap_or(i32::MAX),
+            month: u8::try_from(month).unwrap_or(1),
+            day: u8::try_from(day).unwrap_or(1),
+        }
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl FromStr for CalendarDate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidDate(s.to_string());
+        let mut parts = s.split('-');
+        let (Some(year), Some(month), Some(day), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+        let year = year.parse().map_err(|_| invalid())?;
+        let month = month.parse().map_err(|_| invalid())?;
+        let day = day.parse().map_err(|_| invalid())?;
+        Self::new(year, month, day).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The `timeout` of a hook: a number of seconds, or `"none"` for a command
+/// that is deliberately left without a limit, e.g. an interactive helper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookTimeout {
+    Seconds(u64),
+    Unbounded,
+}
+
+impl HookTimeout {
+    /// How long the command may run, or `None` for no limit.
+    pub fn duration(self) -> Option<std::time::Duration> {
+        match self {
+            Self::Seconds(seconds) => Some(std::time::Duration::from_secs(seconds)),
+            Self::Unbounded => None,
+        }
+    }
+}
+
+impl fmt::Display for HookTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Seconds(seconds) => write!(f, "{seconds}"),
+            Self::Unbounded => write!(f, "\"none\""),
+        }
+    }
+}
+
+impl Serialize for HookTimeout {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Seconds(seconds) => serializer.serialize_u64(*seconds),
+            Self::Unbounded => serializer.serialize_str("none"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HookTimeout {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TimeoutVisitor;
+
+        impl serde::de::Visitor<'_> for TimeoutVisitor {
+            type Value = HookTimeout;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a number of seconds or \"none\"")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, seconds: u64) -> Result<Self::Value, E> {
+                Ok(HookTimeout::Seconds(seconds))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, seconds: i64) -> Result<Self::Value, E> {
+                u64::try_from(seconds)
+                    .map(HookTimeout::Seconds)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(seconds), &self))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                match value {
+                    "none" => Ok(HookTimeout::Unbounded),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(TimeoutVisitor)
+    }
+}
+
+impl Serialize for CalendarDate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CalendarDate {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("The specified configuration file is missing")]
+    MissingFile,
+    #[error("The specified configuration path exists but is not a regular file")]
+    NotAFile,
     #[error("The specified configuration file does not have a readable extension")]
     CanNotReadExtension,
     #[error("The specified configuration file does not have a .toml extension")]
@@ -252,10 +1629,95 @@ pub enum Error {
     ValidationError(#[from] ValidationError),
     #[error("Unknown lifecycle phase: {0}")]
     UnknownLifeCyclePhase(String),
+    #[error("Invalid date '{0}': expected YYYY-MM-DD")]
+    InvalidDate(String),
+    #[error(
+        "Invalid requirement '{0}': expected a tool name, optionally followed by a version constraint such as '>= 18'"
+    )]
+    InvalidRequirement(String),
+    #[error("Invalid config key '{0}': expected dot-separated names such as pre-commit.0.command")]
+    InvalidKeyPath(String),
+    #[error("Config key not found: {0}")]
+    KeyNotFound(String),
+    #[error("Config key '{key}' cannot be set: {reason}")]
+    UneditableKey { key: String, reason: &'static str },
+    #[error("Formatting would change the config's values, so the file was left as is")]
+    FormattingChangedValues,
+}
+
+/// A setting that is valid but has no effect, see [`SmeeConfig::warnings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigWarning {
+    /// Phase the setting belongs to. `None` for task entries.
+    pub phase: Option<LifeCyclePhase>,
+    pub message: String,
 }
 
-#[derive(Debug, Error, PartialEq, Eq)]
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ValidationError {
+    #[error("settings.hooks_path must not be empty")]
+    EmptyHooksPath,
+    #[error("settings.messages_dir must not be empty")]
+    EmptyMessagesDir,
+    #[error("defaults.{phase}: {reason}")]
+    InvalidPhaseDefault {
+        phase: LifeCyclePhase,
+        reason: String,
+    },
+    #[error("Task name '{task}' may only contain letters, digits, '-', '_', and '.'")]
+    InvalidTaskName { task: String },
+    #[error("Hook '{hook_name}' entry #{entry_index}: references unknown task '{task}'")]
+    UnknownTaskReference {
+        hook_name: String,
+        entry_index: usize,
+        task: String,
+    },
+    #[error("Hook '{hook_name}' entry #{entry_index}: set either command or task, not both")]
+    CommandAndTask {
+        hook_name: String,
+        entry_index: usize,
+    },
+    #[error("Hook '{hook_name}' entry #{entry_index}: set either script or {other}, not both")]
+    ScriptAndCommand {
+        hook_name: String,
+        entry_index: usize,
+        other: &'static str,
+    },
+    #[error("Hook '{hook_name}' entry #{entry_index}: script must not be empty")]
+    EmptyScript {
+        hook_name: String,
+        entry_index: usize,
+    },
+    #[error("install.{phase}: {reason}")]
+    InvalidPrepend {
+        phase: LifeCyclePhase,
+        reason: String,
+    },
+    #[error("settings.interpreters.{extension}: {reason}")]
+    InvalidInterpreter { extension: String, reason: String },
+    #[error("settings.groups.{group} must be at least 1")]
+    ZeroGroupLimit { group: String },
+    #[error(
+        "Hook '{hook_name}' entry #{entry_index}: group '{group}' has no limit in [settings.groups]"
+    )]
+    UnknownGroup {
+        hook_name: String,
+        entry_index: usize,
+        group: String,
+    },
+    #[error("settings.{phase}.max_duration must be at least 1s")]
+    ZeroMaxDuration { phase: LifeCyclePhase },
+    #[error("settings.report.{field}: {reason}")]
+    InvalidReport { field: &'static str, reason: String },
+    #[error("Tasks reference each other in a cycle: {cycle}")]
+    TaskCycle { cycle: String },
     #[error("Hook '{hook_name}' has no entries")]
     EmptyHookEntries { hook_name: String },
     #[error("Hook '{hook_name}' entry #{entry_index}: command must not be empty")]
@@ -263,6 +1725,50 @@ pub enum ValidationError {
         hook_name: String,
         entry_index: usize,
     },
+    #[error("Hook '{hook_name}' entry #{entry_index}: fixable must not be empty")]
+    EmptyFixCommand {
+        hook_name: String,
+        entry_index: usize,
+    },
+    #[error(
+        "Hook '{hook_name}' entry #{entry_index}: mutex '{mutex}' may only contain letters, digits, '-', '_', and '.'"
+    )]
+    InvalidMutexName {
+        hook_name: String,
+        entry_index: usize,
+        mutex: String,
+    },
+    #[error(
+        "Hook '{hook_name}' entry #{entry_index}: command cannot be split into words: {source}"
+    )]
+    UnparsableCommand {
+        hook_name: String,
+        entry_index: usize,
+        source: crate::shell_words::Error,
+    },
+    #[error("Hook '{hook_name}' entry #{entry_index}: timeout must be greater than zero")]
+    ZeroTimeout {
+        hook_name: String,
+        entry_index: usize,
+    },
+    #[error("Hook '{hook_name}' entry #{entry_index}: max_output_bytes must be greater than zero")]
+    ZeroMaxOutput {
+        hook_name: String,
+        entry_index: usize,
+    },
+    #[error("Hook '{hook_name}' entry #{entry_index}: owner must not be empty")]
+    EmptyOwner {
+        hook_name: String,
+        entry_index: usize,
+    },
+    #[error(
+        "Hook '{hook_name}' entry #{entry_index}: description_url '{url}' must be an absolute URL, e.g. https://wiki.example.com/hooks/lint"
+    )]
+    InvalidDescriptionUrl {
+        hook_name: String,
+        entry_index: usize,
+        url: String,
+    },
 }
 
 #[cfg(test)]
@@ -298,6 +1804,128 @@ mod tests {
         assert!(!hook_definition.parallel_execution_allowed);
     }
 
+    #[test]
+    fn given_either_parallel_spelling_when_parsing_then_both_set_the_flag_and_short_form_is_written()
+     {
+        for key in ["parallel", "parallel_execution_allowed"] {
+            let config: SmeeConfig = toml::from_str(&format!(
+                "[[pre-push]]\ncommand = 'cargo test'\n{key} = true\n"
+            ))
+            .unwrap();
+            assert!(config.hooks[&LifeCyclePhase::PrePush][0].parallel_execution_allowed);
+        }
+
+        let serialized = String::try_from(&SmeeConfig::default()).unwrap();
+
+        assert!(serialized.contains("parallel = false"));
+        assert!(!serialized.contains("parallel_execution_allowed"));
+    }
+
+    #[test]
+    fn given_parallel_default_and_old_spelling_on_entry_when_reading_then_entry_wins() {
+        let config = SmeeConfig::from_toml_bytes(
+            b"[defaults.pre-commit]\nparallel = true\n\n[[pre-commit]]\ncommand = 'a'\nparallel_execution_allowed = false\n",
+        )
+        .unwrap();
+
+        assert!(!config.hooks[&LifeCyclePhase::PreCommit][0].parallel_execution_allowed);
+    }
+
+    #[test]
+    fn given_config_with_all_fields_when_serializing_and_parsing_then_round_trip_is_equal() {
+        let mut config = SmeeConfig::default();
+        config.hooks.insert(
+            LifeCyclePhase::PrePush,
+            vec![HookDefinition {
+                name: Some("tests".to_string()),
+                owner: Some("@acme/rust".to_string()),
+                description_url: Some("https://wiki.example.com/hooks/tests".to_string()),
+                command: "cargo test".to_string(),
+                task: Some("lint-all".to_string()),
+                script: Some("scripts/check.py".to_string()),
+                parallel_execution_allowed: true,
+                timeout: Some(HookTimeout::Seconds(600)),
+                grace_period: Some(2),
+                isolate_tmp: true,
+                artifacts_dir: true,
+                severity: Severity::Warning,
+                enforce_after: CalendarDate::new(2030, 1, 31),
+                fixable: Some("cargo fmt".to_string()),
+                restage: true,
+                mutex: Some("cargo-target".to_string()),
+                group: Some("cpu".to_string()),
+                requires: vec!["cargo".parse().unwrap(), "node >= 18.2".parse().unwrap()],
+                on_missing: MissingRequirement::Skip,
+                no_shell: true,
+                max_output_bytes: Some(65536),
+                network: Some(false),
+                success_codes: vec![0, 2],
+            }],
+        );
+
+        config.defaults.insert(
+            LifeCyclePhase::PrePush,
+            PhaseDefaults(toml::from_str("timeout = 60\nisolate_tmp = true\n").unwrap()),
+        );
+        config.settings.groups.insert("cpu".to_string(), 1);
+        config.install.insert(
+            LifeCyclePhase::PrePush,
+            PhaseInstall {
+                prepend: Some(". ./.envrc\nexport CI=1\n".to_string()),
+            },
+        );
+        config
+            .settings
+            .interpreters
+            .insert("py".to_string(), "uv run python".to_string());
+        config.settings.windows_shell = WindowsShell::Pwsh;
+        config.settings.report = Some(ReportSettings {
+            url: "https://dash.example.com/hooks".to_string(),
+            secret_env: Some("DASH_SECRET".to_string()),
+            secret_header: Some("X-Dash-Secret".to_string()),
+            retries: Some(4),
+        });
+        config.settings.phases.insert(
+            LifeCyclePhase::PreCommit,
+            PhaseSettings {
+                max_duration: Some(MaxDuration(std::time::Duration::from_secs(90))),
+                over_budget: OverBudget::Fail,
+            },
+        );
+
+        let serialized = String::try_from(&config).unwrap();
+        let parsed: SmeeConfig = toml::from_str(&serialized).unwrap();
+
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.clone(), config);
+    }
+
+    #[test]
+    fn given_readme_hook_table_when_comparing_then_every_phase_is_listed_with_its_metadata() {
+        let readme = include_str!("../../../README.md");
+
+        for phase in LifeCyclePhase::all() {
+            let row = format!(
+                "| `{phase}` | {} | {} | {} |",
+                phase.description(),
+                if phase.accepts_args() { "yes" } else { "no" },
+                if phase.reads_stdin() { "yes" } else { "no" },
+            );
+            assert!(readme.contains(&row), "README is missing row: {row}");
+        }
+    }
+
+    #[test]
+    fn given_stdin_hooks_when_querying_metadata_then_git_protocol_is_reflected() {
+        assert!(LifeCyclePhase::PrePush.reads_stdin());
+        assert!(LifeCyclePhase::PrePush.accepts_args());
+        assert!(!LifeCyclePhase::PreCommit.reads_stdin());
+        assert!(!LifeCyclePhase::PreCommit.accepts_args());
+        assert!(LifeCyclePhase::CommitMsg.accepts_args());
+        assert!(LifeCyclePhase::PreReceive.reads_stdin());
+        assert!(!LifeCyclePhase::PreReceive.accepts_args());
+    }
+
     #[test]
     fn given_uppercase_toml_extension_when_loading_then_config_is_accepted() {
         let dir = tempdir().unwrap();
@@ -375,6 +2003,28 @@ mod tests {
         assert!(serialized_config.contains("pre-commit"))
     }
 
+    #[test]
+    fn given_every_phase_when_round_tripping_names_then_serde_from_str_and_display_agree() {
+        for phase in LifeCyclePhase::all() {
+            let name = phase.as_str();
+            let serialized = toml::Value::try_from(phase).unwrap();
+            let deserialized: LifeCyclePhase =
+                toml::Value::String(name.to_string()).try_into().unwrap();
+
+            assert_eq!(serialized.as_str(), Some(name));
+            assert_eq!(deserialized, *phase);
+            assert_eq!(LifeCyclePhase::from_str(name).unwrap(), *phase);
+            assert_eq!(phase.to_string(), name);
+        }
+    }
+
+    #[test]
+    fn given_phase_list_when_enumerating_then_every_phase_appears_once() {
+        let unique: std::collections::HashSet<_> = LifeCyclePhase::all().iter().collect();
+
+        assert_eq!(unique.len(), LifeCyclePhase::all().len());
+    }
+
     #[test]
     fn given_lifecycle_when_from_str_then_correct_enum_returned() {
         LifeCyclePhase::all().iter().for_each(|phase| {
@@ -430,14 +2080,22 @@ mod tests {
                 HookDefinition {
                     command: "cargo test".to_string(),
                     parallel_execution_allowed: false,
+                    ..Default::default()
                 },
                 HookDefinition {
                     command: "   ".to_string(),
                     parallel_execution_allowed: false,
+                    ..Default::default()
                 },
             ],
         );
-        let config = SmeeConfig { hooks };
+        let config = SmeeConfig {
+            hooks,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
 
         let result = config.validate();
 
@@ -451,33 +2109,852 @@ mod tests {
     }
 
     #[test]
-    fn given_hook_without_entries_when_validating_then_error_contains_hook() {
-        let mut hooks = HashMap::new();
-        hooks.insert(LifeCyclePhase::PrePush, vec![]);
-        let config = SmeeConfig { hooks };
-
-        let result = config.validate();
+    fn given_settings_table_when_parsing_then_settings_are_loaded_apart_from_hooks() {
+        let config: SmeeConfig = toml::from_str(
+            "[settings]\nhooks_path = '.git-smee/hooks'\n\n[[pre-commit]]\ncommand = 'a'\n",
+        )
+        .unwrap();
 
         assert_eq!(
-            result,
-            Err(ValidationError::EmptyHookEntries {
-                hook_name: "pre-push".to_string(),
-            })
+            config.settings.hooks_path.as_deref(),
+            Some(".git-smee/hooks")
         );
+        assert_eq!(config.hooks.len(), 1);
+        assert!(toml::from_str::<SmeeConfig>("[settings]\nhooks_dir = 'x'\n").is_err());
+        let empty: SmeeConfig = toml::from_str("[settings]\nhooks_path = ' '\n").unwrap();
+        assert_eq!(empty.validate(), Err(ValidationError::EmptyHooksPath));
     }
 
     #[test]
-    fn given_valid_config_when_validating_then_success() {
-        let mut hooks = HashMap::new();
-        hooks.insert(
-            LifeCyclePhase::PreCommit,
-            vec![HookDefinition {
-                command: "cargo test".to_string(),
-                parallel_execution_allowed: false,
-            }],
+    fn given_empty_messages_dir_when_validating_then_it_is_rejected() {
+        let config: SmeeConfig =
+            toml::from_str("[settings]\nmessages_dir = ''\n\n[[pre-commit]]\ncommand = 'a'\n")
+                .unwrap();
+
+        assert_eq!(config.validate(), Err(ValidationError::EmptyMessagesDir));
+    }
+
+    #[test]
+    fn given_never_install_when_listing_installable_phases_then_listed_phases_are_left_out() {
+        let config: SmeeConfig = toml::from_str(
+            "[settings]\nnever_install = ['post-checkout']\n\n[[pre-push]]\ncommand = 'a'\n\n[[post-checkout]]\ncommand = 'b'\n\n[[pre-commit]]\ncommand = 'c'\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.installable_phases(),
+            [LifeCyclePhase::PreCommit, LifeCyclePhase::PrePush]
+        );
+        assert!(
+            toml::from_str::<SmeeConfig>("[settings]\nnever_install = ['post-chekout']\n").is_err()
         );
-        let config = SmeeConfig { hooks };
+    }
 
-        assert!(config.validate().is_ok());
+    #[test]
+    fn given_phase_defaults_when_reading_then_unset_fields_are_filled_and_explicit_ones_win() {
+        let config = SmeeConfig::from_toml_bytes(
+            b"[defaults.pre-commit]\nparallel_execution_allowed = true\ntimeout = 60\n\n[[pre-commit]]\ncommand = 'cargo fmt --check'\n\n[[pre-commit]]\ncommand = 'cargo test'\nparallel_execution_allowed = false\ntimeout = 600\n\n[[pre-push]]\ncommand = 'cargo doc'\n",
+        )
+        .unwrap();
+
+        let pre_commit = &config.hooks[&LifeCyclePhase::PreCommit];
+        assert!(pre_commit[0].parallel_execution_allowed);
+        assert_eq!(pre_commit[0].timeout, Some(HookTimeout::Seconds(60)));
+        assert!(!pre_commit[1].parallel_execution_allowed);
+        assert_eq!(pre_commit[1].timeout, Some(HookTimeout::Seconds(600)));
+        let pre_push = &config.hooks[&LifeCyclePhase::PrePush][0];
+        assert!(!pre_push.parallel_execution_allowed);
+        assert_eq!(pre_push.timeout, None);
+    }
+
+    #[test]
+    fn given_timeout_none_when_reading_then_entry_is_unbounded_despite_phase_default() {
+        let config = SmeeConfig::from_toml_bytes(
+            b"[defaults.pre-rebase]\ntimeout = 60\n\n[[pre-rebase]]\ncommand = 'interactive-helper'\ntimeout = 'none'\n",
+        )
+        .unwrap();
+
+        let hook = &config.hooks[&LifeCyclePhase::PreRebase][0];
+        assert_eq!(hook.timeout, Some(HookTimeout::Unbounded));
+        assert_eq!(hook.timeout.and_then(HookTimeout::duration), None);
+        assert!(
+            String::try_from(&config)
+                .unwrap()
+                .contains("timeout = \"none\"")
+        );
+        let error =
+            toml::from_str::<SmeeConfig>("[[pre-push]]\ncommand = 'a'\ntimeout = 'forever'\n")
+                .unwrap_err()
+                .to_string();
+        assert!(error.contains("a number of seconds or \"none\""));
+        assert!(
+            toml::from_str::<SmeeConfig>("[[pre-push]]\ncommand = 'a'\ntimeout = -1\n").is_err()
+        );
+    }
+
+    #[test]
+    fn given_invalid_phase_defaults_when_reading_then_phase_and_reason_are_reported() {
+        let error = |toml: &str| match SmeeConfig::from_toml_bytes(toml.as_bytes()) {
+            Err(Error::ValidationError(error)) => error.to_string(),
+            other => panic!("expected a validation error, got {other:?}"),
+        };
+
+        assert_eq!(
+            error("[defaults.pre-commit]\ncommand = 'make'\n"),
+            "defaults.pre-commit: 'command' cannot have a default"
+        );
+        assert!(
+            error("[defaults.pre-push]\ntimeout = 'soon'\n").starts_with("defaults.pre-push: ")
+        );
+        assert!(error("[defaults.pre-push]\nfail_fast = true\n").contains("fail_fast"));
+        assert!(toml::from_str::<SmeeConfig>("[defaults.pre-comit]\ntimeout = 1\n").is_err());
+    }
+
+    #[test]
+    fn given_settings_without_effect_when_collecting_warnings_then_each_explains_why() {
+        let config = SmeeConfig::from_toml_bytes(
+            b"[defaults.post-merge]\ntimeout = 60\n\n[[pre-push]]\ncommand = 'cargo test'\nfixable = 'cargo fix'\nrestage = true\n\n[[pre-push]]\ncommand = 'cargo doc'\non_missing = 'skip'\nseverity = 'warning'\nenforce_after = '2030-01-01'\n\n[tasks]\nlint = [{ command = 'cargo fmt --check', restage = true }]\n",
+        )
+        .unwrap();
+
+        let warnings: Vec<_> = config.warnings().iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            warnings,
+            [
+                "Hook 'pre-push' entry #1: restage has no effect in pre-push; only pre-commit builds the commit from the staged files",
+                "Hook 'pre-push' entry #2: on_missing is ignored without requires; there is nothing to be missing",
+                "Hook 'pre-push' entry #2: enforce_after has no effect with severity = \"warning\"; failures are warnings before and after the date",
+                "Hook 'tasks.lint' entry #1: restage is ignored without fixable; it re-stages files after the fix runs",
+                "defaults.post-merge is ignored; there are no post-merge entries to apply it to",
+            ]
+        );
+        assert_eq!(config.warnings()[0].phase, Some(LifeCyclePhase::PrePush));
+        assert_eq!(config.warnings()[3].phase, None);
+    }
+
+    #[test]
+    fn given_changed_files_placeholder_outside_pre_push_when_collecting_warnings_then_it_is_reported()
+     {
+        let config = SmeeConfig::from_toml_bytes(
+            b"[[pre-commit]]\ncommand = 'eslint {changed_files}'\n\n[[pre-push]]\ncommand = 'eslint {changed_files}'\n",
+        )
+        .unwrap();
+
+        let warnings: Vec<_> = config.warnings().iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            warnings,
+            [
+                "Hook 'pre-commit' entry #1: {changed_files} is only filled in for pre-push; in pre-commit the command is always skipped",
+            ]
+        );
+    }
+
+    #[test]
+    fn given_settings_that_take_effect_when_collecting_warnings_then_there_are_none() {
+        let config = SmeeConfig::from_toml_bytes(
+            b"[defaults.pre-commit]\ntimeout = 60\n\n[[pre-commit]]\ncommand = 'cargo fmt --check'\nfixable = 'cargo fmt'\nrestage = true\nrequires = ['cargo']\non_missing = 'skip'\nenforce_after = '2030-01-01'\n",
+        )
+        .unwrap();
+
+        assert!(config.warnings().is_empty());
+    }
+
+    #[test]
+    fn given_nested_runs_setting_when_parsing_then_default_is_skip_and_values_are_lowercase() {
+        assert_eq!(SmeeConfig::default().settings.nested_runs, NestedRuns::Skip);
+        let config: SmeeConfig = toml::from_str("[settings]\nnested_runs = 'allow'\n").unwrap();
+        assert_eq!(config.settings.nested_runs, NestedRuns::Allow);
+        assert!(toml::from_str::<SmeeConfig>("[settings]\nnested_runs = 'sometimes'\n").is_err());
+        assert_eq!(
+            String::try_from(&config).unwrap(),
+            "[settings]\nnested_runs = \"allow\"\n"
+        );
+    }
+
+    #[test]
+    fn given_zero_timeout_when_validating_then_error_contains_hook_and_index() {
+        let config: SmeeConfig =
+            toml::from_str("[[pre-push]]\ncommand = 'cargo test'\ntimeout = 0\n").unwrap();
+
+        let result = config.validate();
+
+        assert_eq!(
+            result,
+            Err(ValidationError::ZeroTimeout {
+                hook_name: "pre-push".to_string(),
+                entry_index: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn given_description_url_when_validating_then_only_absolute_urls_are_accepted() {
+        let config: SmeeConfig = toml::from_str(
+            "[[pre-push]]\ncommand = 'cargo test'\ndescription_url = 'https://wiki.example.com/hooks/test'\n",
+        )
+        .unwrap();
+        assert_eq!(config.validate(), Ok(()));
+
+        for url in ["", "wiki/hooks/test", "https://wiki.example.com/a b"] {
+            let mut config = config.clone();
+            config.hooks.get_mut(&LifeCyclePhase::PrePush).unwrap()[0].description_url =
+                Some(url.to_string());
+
+            assert_eq!(
+                config.validate(),
+                Err(ValidationError::InvalidDescriptionUrl {
+                    hook_name: "pre-push".to_string(),
+                    entry_index: 1,
+                    url: url.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn given_blank_owner_when_validating_then_error_contains_hook_and_index() {
+        let config: SmeeConfig =
+            toml::from_str("[[pre-push]]\ncommand = 'cargo test'\nowner = ' '\n").unwrap();
+
+        let result = config.validate();
+
+        assert_eq!(
+            result,
+            Err(ValidationError::EmptyOwner {
+                hook_name: "pre-push".to_string(),
+                entry_index: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn given_zero_max_output_bytes_when_validating_then_error_contains_hook_and_index() {
+        let config: SmeeConfig =
+            toml::from_str("[[pre-push]]\ncommand = 'cargo test'\nmax_output_bytes = 0\n").unwrap();
+
+        let result = config.validate();
+
+        assert_eq!(
+            result,
+            Err(ValidationError::ZeroMaxOutput {
+                hook_name: "pre-push".to_string(),
+                entry_index: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn given_unbalanced_quotes_in_no_shell_command_when_validating_then_error_is_returned() {
+        let config: SmeeConfig =
+            toml::from_str("[[pre-push]]\ncommand = \"lint 'src\"\nno_shell = true\n").unwrap();
+
+        let result = config.validate();
+
+        assert_eq!(
+            result,
+            Err(ValidationError::UnparsableCommand {
+                hook_name: "pre-push".to_string(),
+                entry_index: 1,
+                source: crate::shell_words::Error::UnterminatedSingleQuote,
+            })
+        );
+    }
+
+    #[test]
+    fn given_fixable_when_parsing_then_fix_command_and_restage_are_loaded() {
+        let config: SmeeConfig = toml::from_str(
+            "[[pre-commit]]\ncommand = 'cargo fmt --check'\nfixable = 'cargo fmt'\nrestage = true\n",
+        )
+        .unwrap();
+
+        let hook = &config.hooks[&LifeCyclePhase::PreCommit][0];
+        assert_eq!(hook.fixable.as_deref(), Some("cargo fmt"));
+        assert!(hook.restage);
+        assert_eq!(
+            toml::from_str::<SmeeConfig>("[[pre-commit]]\ncommand = 'a'\nfixable = ' '\n")
+                .unwrap()
+                .validate(),
+            Err(ValidationError::EmptyFixCommand {
+                hook_name: "pre-commit".to_string(),
+                entry_index: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn given_mutex_with_path_characters_when_validating_then_error_names_the_mutex() {
+        let valid: SmeeConfig =
+            toml::from_str("[[pre-commit]]\ncommand = 'a'\nmutex = 'cargo-target'\n").unwrap();
+        let invalid: SmeeConfig =
+            toml::from_str("[[pre-commit]]\ncommand = 'a'\nmutex = '../target'\n").unwrap();
+
+        assert_eq!(valid.validate(), Ok(()));
+        assert_eq!(
+            invalid.validate(),
+            Err(ValidationError::InvalidMutexName {
+                hook_name: "pre-commit".to_string(),
+                entry_index: 1,
+                mutex: "../target".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn given_script_entries_when_expanding_then_the_interpreter_follows_the_extension() {
+        let config = SmeeConfig::from_toml_str(
+            "[settings.interpreters]\nrb = 'bundle exec ruby'\nsh = 'bash'\n\n[[pre-commit]]\nscript = 'scripts/check.PY'\n\n[[pre-commit]]\nscript = 'lint.rb'\n\n[[pre-commit]]\nscript = 'tools/my check.sh'\n\n[[pre-commit]]\nscript = 'scripts/run'\n",
+        )
+        .unwrap();
+
+        let entries = config.phase_entries(LifeCyclePhase::PreCommit).unwrap();
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|hook| hook.command.as_str())
+                .collect::<Vec<_>>(),
+            [
+                format!("{} scripts/check.PY", DEFAULT_INTERPRETERS[0].1).as_str(),
+                "bundle exec ruby ./lint.rb",
+                "bash 'tools/my check.sh'",
+                "scripts/run",
+            ]
+        );
+        assert!(entries.iter().all(|hook| hook.no_shell));
+        assert!(
+            config.hooks[&LifeCyclePhase::PreCommit][0]
+                .command
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn given_install_prepend_when_validating_then_empty_and_delimiter_lines_are_rejected() {
+        let config = |install: &str| -> SmeeConfig {
+            toml::from_str(&format!(
+                "[install.pre-commit]\n{install}\n\n[[pre-commit]]\ncommand = 'a'\n"
+            ))
+            .unwrap()
+        };
+
+        let valid = config("prepend = '. ./.envrc'");
+        assert_eq!(valid.validate(), Ok(()));
+        assert_eq!(
+            valid.wrapper_prepend(LifeCyclePhase::PreCommit),
+            Some(". ./.envrc")
+        );
+        assert_eq!(
+            config("prepend = ' '").validate(),
+            Err(ValidationError::InvalidPrepend {
+                phase: LifeCyclePhase::PreCommit,
+                reason: "prepend must not be empty".to_string(),
+            })
+        );
+        assert!(matches!(
+            config(
+                "prepend = \"\"\"\nexport A=1\n# git-smee: end install.pre-commit.prepend\n\"\"\""
+            )
+            .validate(),
+            Err(ValidationError::InvalidPrepend { .. })
+        ));
+        assert!(toml::from_str::<SmeeConfig>("[install.pre-commit]\nappend = 'x'\n").is_err());
+    }
+
+    #[test]
+    fn given_install_table_for_uninstalled_phase_when_reading_warnings_then_it_is_reported() {
+        let config = SmeeConfig::from_toml_str(
+            "[settings]\nnever_install = ['pre-push']\n\n[install.pre-push]\nprepend = 'a'\n\n[install.commit-msg]\nprepend = 'b'\n\n[[pre-push]]\ncommand = 'a'\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config
+                .warnings()
+                .into_iter()
+                .map(|warning| warning.message)
+                .collect::<Vec<_>>(),
+            [
+                "install.commit-msg is ignored; no wrapper is installed for commit-msg",
+                "install.pre-push is ignored; no wrapper is installed for pre-push",
+            ]
+        );
+    }
+
+    #[test]
+    fn given_script_entries_when_validating_then_script_stands_alone_and_interpreters_parse() {
+        let validate = |source: &str| toml::from_str::<SmeeConfig>(source).unwrap().validate();
+
+        assert_eq!(
+            validate("[[pre-commit]]\nscript = 'check.py'\ncommand = 'a'\n"),
+            Err(ValidationError::ScriptAndCommand {
+                hook_name: "pre-commit".to_string(),
+                entry_index: 1,
+                other: "command",
+            })
+        );
+        assert_eq!(
+            validate("[[pre-commit]]\nscript = ' '\n"),
+            Err(ValidationError::EmptyScript {
+                hook_name: "pre-commit".to_string(),
+                entry_index: 1,
+            })
+        );
+        assert_eq!(
+            validate(
+                "[settings.interpreters]\n'.py' = 'python3'\n\n[[pre-commit]]\nscript = 'check.py'\n"
+            ),
+            Err(ValidationError::InvalidInterpreter {
+                extension: ".py".to_string(),
+                reason: "the key must be a file extension without the dot, e.g. py".to_string(),
+            })
+        );
+        assert!(matches!(
+            validate("[settings.interpreters]\npy = ' '\n"),
+            Err(ValidationError::InvalidInterpreter { .. })
+        ));
+        assert_eq!(validate("[[pre-commit]]\nscript = 'check.py'\n"), Ok(()));
+    }
+
+    #[test]
+    fn given_groups_when_validating_then_each_group_needs_a_positive_limit() {
+        let config = |settings: &str| -> SmeeConfig {
+            toml::from_str(&format!(
+                "{settings}\n[[pre-commit]]\ncommand = 'a'\nparallel = true\ngroup = 'cpu'\n"
+            ))
+            .unwrap()
+        };
+
+        let valid = config("[settings.groups]\nio = 8\ncpu = 1\n");
+        assert_eq!(valid.validate(), Ok(()));
+        assert_eq!(valid.settings.groups["io"], 8);
+        assert!(valid.warnings().is_empty());
+        assert_eq!(
+            config("[settings.groups]\nio = 8\n").validate(),
+            Err(ValidationError::UnknownGroup {
+                hook_name: "pre-commit".to_string(),
+                entry_index: 1,
+                group: "cpu".to_string(),
+            })
+        );
+        assert_eq!(
+            config("[settings.groups]\ncpu = 0\n").validate(),
+            Err(ValidationError::ZeroGroupLimit {
+                group: "cpu".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn given_report_settings_when_validating_then_url_secret_env_and_header_are_checked() {
+        let config = |report: &str| -> SmeeConfig {
+            toml::from_str(&format!(
+                "[settings.report]\n{report}\n[[pre-commit]]\ncommand = 'a'\n"
+            ))
+            .unwrap()
+        };
+
+        let valid = config("url = 'https://dash.example.com/hooks'\nsecret_env = 'DASH_SECRET'\n");
+        assert_eq!(valid.validate(), Ok(()));
+        let report = valid.settings.report.unwrap();
+        assert_eq!(report.secret_header(), DEFAULT_REPORT_SECRET_HEADER);
+        assert_eq!(report.retries(), DEFAULT_REPORT_RETRIES);
+        assert!(matches!(
+            config("url = 'dash.example.com'\n").validate(),
+            Err(ValidationError::InvalidReport { field: "url", .. })
+        ));
+        assert!(matches!(
+            config("url = 'https://dash.example.com'\nsecret_env = 'A=B'\n").validate(),
+            Err(ValidationError::InvalidReport {
+                field: "secret_env",
+                ..
+            })
+        ));
+        assert!(matches!(
+            config("url = 'https://dash.example.com'\nsecret_header = 'X-A: b'\n").validate(),
+            Err(ValidationError::InvalidReport {
+                field: "secret_header",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn given_report_retries_above_cap_when_validating_then_retries_are_rejected() {
+        let config = |retries: u32| -> SmeeConfig {
+            toml::from_str(&format!(
+                "[settings.report]\nurl = 'https://dash.example.com'\nretries = {retries}\n[[pre-commit]]\ncommand = 'a'\n"
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(config(MAX_REPORT_RETRIES).validate(), Ok(()));
+        assert_eq!(
+            config(MAX_REPORT_RETRIES + 1).validate(),
+            Err(ValidationError::InvalidReport {
+                field: "retries",
+                reason: format!("must be at most {MAX_REPORT_RETRIES}"),
+            })
+        );
+    }
+
+    #[test]
+    fn given_phase_settings_when_parsing_then_max_duration_accepts_seconds_and_units() {
+        let config = |settings: &str| -> Result<SmeeConfig, toml::de::Error> {
+            toml::from_str(&format!("{settings}\n[[pre-commit]]\ncommand = 'a'\n"))
+        };
+
+        let parsed = config(
+            "[settings.pre-commit]\nmax_duration = '2m'\nover_budget = 'fail'\n[settings.pre-push]\nmax_duration = 45\n",
+        )
+        .unwrap();
+        assert_eq!(parsed.validate(), Ok(()));
+        let pre_commit = &parsed.settings.phases[&LifeCyclePhase::PreCommit];
+        assert_eq!(pre_commit.max_duration.unwrap().0.as_secs(), 120);
+        assert_eq!(pre_commit.over_budget, OverBudget::Fail);
+        let pre_push = &parsed.settings.phases[&LifeCyclePhase::PrePush];
+        assert_eq!(pre_push.max_duration.unwrap().0.as_secs(), 45);
+        assert_eq!(pre_push.over_budget, OverBudget::Skip);
+        assert_eq!(
+            config("[settings.pre-commit]\nmax_duration = '0s'\n")
+                .unwrap()
+                .validate(),
+            Err(ValidationError::ZeroMaxDuration {
+                phase: LifeCyclePhase::PreCommit,
+            })
+        );
+        assert!(config("[settings.pre-commit]\nmax_duration = 'soon'\n").is_err());
+        assert!(config("[settings.pre-commit]\nbudget = 60\n").is_err());
+        assert!(config("[settings]\nnested_run = 'allow'\n").is_err());
+    }
+
+    #[test]
+    fn given_group_on_sequential_entry_when_collecting_warnings_then_group_is_reported_as_ineffective()
+     {
+        let config: SmeeConfig = toml::from_str(
+            "[settings.groups]\ncpu = 1\n\n[[pre-commit]]\ncommand = 'a'\ngroup = 'cpu'\n",
+        )
+        .unwrap();
+
+        let warnings: Vec<_> = config.warnings().iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            warnings,
+            [
+                "Hook 'pre-commit' entry #1: group has no effect without parallel = true; sequential commands already run one at a time"
+            ]
+        );
+    }
+
+    #[test]
+    fn given_tasks_table_when_parsing_then_tasks_are_loaded_apart_from_phases() {
+        let config: SmeeConfig = toml::from_str(
+            "[[pre-commit]]\ncommand = 'a'\n\n[[tasks.lint-all]]\ncommand = 'cargo clippy'\nparallel_execution_allowed = true\n\n[[tasks.lint-all]]\ncommand = 'cargo fmt --check'\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.hooks.len(), 1);
+        let lint_all = &config.tasks["lint-all"];
+        assert_eq!(lint_all.len(), 2);
+        assert_eq!(lint_all[0].command, "cargo clippy");
+        assert!(lint_all[0].parallel_execution_allowed);
+        assert_eq!(config.validate(), Ok(()));
+        let round_tripped: SmeeConfig =
+            toml::from_str(&String::try_from(&config).unwrap()).unwrap();
+        assert_eq!(round_tripped, config);
+    }
+
+    #[test]
+    fn given_invalid_task_when_validating_then_error_names_the_task() {
+        let bad_name: SmeeConfig =
+            toml::from_str("[[tasks.'../escape']]\ncommand = 'a'\n").unwrap();
+        let empty_command: SmeeConfig = toml::from_str("[[tasks.lint]]\ncommand = ' '\n").unwrap();
+
+        assert_eq!(
+            bad_name.validate(),
+            Err(ValidationError::InvalidTaskName {
+                task: "../escape".to_string(),
+            })
+        );
+        assert_eq!(
+            empty_command.validate(),
+            Err(ValidationError::EmptyCommand {
+                hook_name: "tasks.lint".to_string(),
+                entry_index: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn given_task_references_when_expanding_then_entries_are_inlined_in_order() {
+        let config: SmeeConfig = toml::from_str(
+            "[[pre-push]]\ncommand = 'first'\n\n[[pre-push]]\ntask = 'check'\n\n[[pre-push]]\ncommand = 'last'\n\n[[tasks.check]]\ntask = 'lint'\n\n[[tasks.check]]\ncommand = 'cargo test'\n\n[[tasks.lint]]\ncommand = 'cargo clippy'\nparallel_execution_allowed = true\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.validate(), Ok(()));
+        let commands: Vec<_> = config
+            .phase_entries(LifeCyclePhase::PrePush)
+            .unwrap()
+            .into_iter()
+            .map(|hook| (hook.command, hook.parallel_execution_allowed))
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                ("first".to_string(), false),
+                ("cargo clippy".to_string(), true),
+                ("cargo test".to_string(), false),
+                ("last".to_string(), false),
+            ]
+        );
+        assert_eq!(config.task_entries("check").unwrap().len(), 2);
+        assert_eq!(config.phase_entries(LifeCyclePhase::PreCommit), None);
+    }
+
+    #[test]
+    fn given_invalid_task_references_when_validating_then_error_explains_the_reference() {
+        let unknown: SmeeConfig = toml::from_str("[[pre-push]]\ntask = 'missing'\n").unwrap();
+        let both: SmeeConfig = toml::from_str(
+            "[[pre-push]]\ncommand = 'a'\ntask = 'lint'\n\n[[tasks.lint]]\ncommand = 'b'\n",
+        )
+        .unwrap();
+        let cycle: SmeeConfig = toml::from_str(
+            "[[tasks.a]]\ntask = 'b'\n\n[[tasks.b]]\ncommand = 'x'\n\n[[tasks.b]]\ntask = 'c'\n\n[[tasks.c]]\ntask = 'b'\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            unknown.validate(),
+            Err(ValidationError::UnknownTaskReference {
+                hook_name: "pre-push".to_string(),
+                entry_index: 1,
+                task: "missing".to_string(),
+            })
+        );
+        assert_eq!(
+            both.validate(),
+            Err(ValidationError::CommandAndTask {
+                hook_name: "pre-push".to_string(),
+                entry_index: 1,
+            })
+        );
+        assert_eq!(
+            cycle.validate(),
+            Err(ValidationError::TaskCycle {
+                cycle: "b -> c -> b".to_string(),
+            })
+        );
+        assert_eq!(cycle.task_entries("a").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn given_requirements_when_parsing_then_tools_and_constraints_are_loaded() {
+        let config: SmeeConfig = toml::from_str(
+            "[[pre-commit]]\ncommand = 'a'\nrequires = ['cargo', 'node>=18', 'python3 < 4.0']\n",
+        )
+        .unwrap();
+
+        let requires = &config.hooks[&LifeCyclePhase::PreCommit][0].requires;
+        let rendered: Vec<String> = requires.iter().map(ToString::to_string).collect();
+        assert_eq!(rendered, ["cargo", "node >= 18", "python3 < 4.0"]);
+        assert!(requires[1].accepts_version(&[18, 17, 0]));
+        assert!(!requires[1].accepts_version(&[16, 20, 2]));
+        assert!(!requires[2].accepts_version(&[4]));
+        assert!(requires[0].accepts_version(&[]));
+    }
+
+    #[test]
+    fn given_malformed_requirement_when_parsing_then_error_names_it() {
+        for requirement in ["", "node >=", "node ~ 18", ">= 18", "node >= eighteen"] {
+            assert!(
+                matches!(
+                    Requirement::from_str(requirement),
+                    Err(Error::InvalidRequirement(value)) if value == requirement
+                ),
+                "{requirement:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn given_timeout_and_grace_period_when_parsing_then_values_are_loaded() {
+        let config: SmeeConfig = toml::from_str(
+            "[[pre-push]]\ncommand = 'cargo test'\ntimeout = 120\ngrace_period = 10\n",
+        )
+        .unwrap();
+
+        let hook = &config.hooks[&LifeCyclePhase::PrePush][0];
+        assert_eq!(hook.timeout, Some(HookTimeout::Seconds(120)));
+        assert_eq!(hook.grace_period, Some(10));
+    }
+
+    #[test]
+    fn given_severity_when_parsing_then_warning_is_loaded_and_error_is_default() {
+        let config: SmeeConfig = toml::from_str(
+            "[[pre-commit]]\ncommand = 'a'\nseverity = 'warning'\n\n[[pre-commit]]\ncommand = 'b'\n",
+        )
+        .unwrap();
+
+        let hooks = &config.hooks[&LifeCyclePhase::PreCommit];
+        assert_eq!(hooks[0].severity, Severity::Warning);
+        assert_eq!(hooks[1].severity, Severity::Error);
+    }
+
+    #[test]
+    fn given_unknown_severity_when_parsing_then_error_is_returned() {
+        let result =
+            toml::from_str::<SmeeConfig>("[[pre-commit]]\ncommand = 'a'\nseverity = 'fatal'\n");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn given_enforce_after_in_future_when_resolving_severity_then_failures_are_warnings() {
+        let config: SmeeConfig =
+            toml::from_str("[[pre-commit]]\ncommand = 'new-check'\nenforce_after = '2025-07-01'\n")
+                .unwrap();
+        let hook = &config.hooks[&LifeCyclePhase::PreCommit][0];
+
+        let before = CalendarDate::new(2025, 6, 30).unwrap();
+        let on = CalendarDate::new(2025, 7, 1).unwrap();
+
+        assert_eq!(hook.enforce_after, Some(on));
+        assert_eq!(hook.effective_severity(before), Severity::Warning);
+        assert_eq!(hook.effective_severity(on), Severity::Error);
+    }
+
+    #[test]
+    fn given_malformed_enforce_after_when_parsing_then_error_names_the_value() {
+        for value in ["2025-7-1", "2025-02-30", "next week", "2025-13-01"] {
+            let result = toml::from_str::<SmeeConfig>(&format!(
+                "[[pre-commit]]\ncommand = 'a'\nenforce_after = '{value}'\n"
+            ));
+
+            let error = result
+                .expect_err("malformed date must be rejected")
+                .to_string();
+            assert!(error.contains(value), "{error}");
+        }
+    }
+
+    #[test]
+    fn given_days_since_epoch_when_converting_then_calendar_date_is_correct() {
+        assert_eq!(
+            CalendarDate::from_days_since_unix_epoch(0).to_string(),
+            "1970-01-01"
+        );
+        assert_eq!(
+            CalendarDate::from_days_since_unix_epoch(11_016).to_string(),
+            "2000-02-29"
+        );
+        assert_eq!(
+            CalendarDate::from_days_since_unix_epoch(20_270).to_string(),
+            "2025-07-01"
+        );
+    }
+
+    #[test]
+    fn given_isolate_tmp_when_parsing_then_flag_is_loaded_and_defaults_to_false() {
+        let config: SmeeConfig = toml::from_str(
+            "[[pre-commit]]\ncommand = 'a'\nisolate_tmp = true\n\n[[pre-commit]]\ncommand = 'b'\n",
+        )
+        .unwrap();
+
+        let hooks = &config.hooks[&LifeCyclePhase::PreCommit];
+        assert!(hooks[0].isolate_tmp);
+        assert!(!hooks[1].isolate_tmp);
+    }
+
+    #[test]
+    fn given_hook_without_entries_when_validating_then_error_contains_hook() {
+        let mut hooks = HashMap::new();
+        hooks.insert(LifeCyclePhase::PrePush, vec![]);
+        let config = SmeeConfig {
+            hooks,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
+
+        let result = config.validate();
+
+        assert_eq!(
+            result,
+            Err(ValidationError::EmptyHookEntries {
+                hook_name: "pre-push".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn given_valid_config_when_validating_then_success() {
+        let mut hooks = HashMap::new();
+        hooks.insert(
+            LifeCyclePhase::PreCommit,
+            vec![HookDefinition {
+                command: "cargo test".to_string(),
+                parallel_execution_allowed: false,
+                ..Default::default()
+            }],
+        );
+        let config = SmeeConfig {
+            hooks,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
+
+        assert!(config.validate().is_ok());
+    }
+
+    /// Top-level keys that are not phases, so generated phase names skip them.
+    const NON_PHASE_KEYS: [&str; 4] = ["settings", "defaults", "tasks", "install"];
+
+    proptest::proptest! {
+        #[test]
+        fn from_toml_str_never_panics_and_reports_invalid_toml_as_parse_error(
+            contents in "[\\[\\]a-z_=\"' \n.#{}-]{0,120}"
+        ) {
+            let result = SmeeConfig::from_toml_str(&contents);
+            if toml::from_str::<toml::Table>(&contents).is_err() {
+                proptest::prop_assert!(matches!(result, Err(Error::ParseError(_))));
+            }
+        }
+
+        #[test]
+        fn from_toml_str_never_panics_for_arbitrary_text(contents in proptest::prelude::any::<String>()) {
+            let _ = SmeeConfig::from_toml_str(&contents);
+        }
+
+        #[test]
+        fn arbitrary_hook_commands_parse_as_toml(command in proptest::prelude::any::<String>()) {
+            let contents = format!("[[pre-commit]]\ncommand = {}\n", toml::Value::String(command));
+            let result = SmeeConfig::from_toml_str(&contents);
+            proptest::prop_assert!(!matches!(result, Err(Error::ParseError(_))), "{result:?}");
+        }
+
+        #[test]
+        fn phase_names_parse_only_when_known(name in "[a-z-]{0,24}") {
+            match LifeCyclePhase::from_str(&name) {
+                Ok(phase) => proptest::prop_assert_eq!(phase.as_str(), name.as_str()),
+                Err(Error::UnknownLifeCyclePhase(unknown)) => {
+                    proptest::prop_assert_eq!(&unknown, &name);
+                    proptest::prop_assert!(LifeCyclePhase::all().iter().all(|phase| phase.as_str() != name));
+                }
+                Err(other) => proptest::prop_assert!(false, "unexpected error {other:?}"),
+            }
+        }
+
+        #[test]
+        fn unknown_phase_tables_are_rejected(name in "[a-z][a-z-]{0,20}") {
+            proptest::prop_assume!(!NON_PHASE_KEYS.contains(&name.as_str()));
+            proptest::prop_assume!(LifeCyclePhase::from_str(&name).is_err());
+            let contents = format!("[[{name}]]\ncommand = 'true'\n");
+            proptest::prop_assert!(SmeeConfig::from_toml_str(&contents).is_err());
+        }
     }
 }
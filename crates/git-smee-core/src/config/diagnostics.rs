@@ -0,0 +1,300 @@
+//! Every problem in a config file at once, for `git smee validate`.
+//!
+//! Reading a config stops at its first problem; diagnosing keeps going so CI
+//! can report them all in one pass. Errors are what reading the config
+//! rejects; warnings are settings it accepts that are probably not what was
+//! meant, such as two entries running the same command.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use super::{HookDefinition, LifeCyclePhase, SmeeConfig, ValidationError, validate_entry};
+
+/// Top-level tables that are not phases.
+const TOP_LEVEL_TABLES: [&str; 4] = ["settings", "tasks", "defaults", "install"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for DiagnosticSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        })
+    }
+}
+
+/// One problem found in a config file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    /// Stable name of the check, e.g. `empty-command`, for tooling to match on.
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Error,
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Warning,
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+/// Checks config text and returns its problems, errors first. An empty result
+/// means the config reads without errors or warnings.
+pub fn diagnose(source: &str) -> Vec<Diagnostic> {
+    let table: toml::Table = match toml::from_str(source) {
+        Ok(table) => table,
+        Err(error) => return vec![syntax_error(source, &error)],
+    };
+    let mut diagnostics = Vec::new();
+    for (key, value) in &table {
+        if key == "tasks" {
+            match value.as_table() {
+                Some(tasks) => {
+                    for (task, entries) in tasks {
+                        check_entries(&format!("tasks.{task}"), entries, &mut diagnostics);
+                    }
+                }
+                None => diagnostics.push(Diagnostic::error(
+                    "invalid-table",
+                    "tasks must be a table of [[tasks.<name>]] entries",
+                )),
+            }
+        } else if key.parse::<LifeCyclePhase>().is_ok() {
+            check_entries(key, value, &mut diagnostics);
+        } else if !TOP_LEVEL_TABLES.contains(&key.as_str()) {
+            let candidates = LifeCyclePhase::all()
+                .iter()
+                .map(|phase| phase.as_str())
+                .chain(TOP_LEVEL_TABLES);
+            diagnostics.push(Diagnostic::error(
+                "unknown-key",
+                format!(
+                    "'{key}' is neither a lifecycle phase nor one of settings, tasks, defaults, and install{}",
+                    suggestion(key, candidates)
+                ),
+            ));
+        }
+    }
+    // Checks across entries, e.g. task references, need the whole config;
+    // they only run once the entries themselves are fine, so a broken entry
+    // is not reported twice.
+    if diagnostics.is_empty() {
+        match SmeeConfig::from_toml_str(source) {
+            Ok(config) => {
+                if config.hooks.is_empty() && config.tasks.is_empty() {
+                    diagnostics.push(Diagnostic::warning(
+                        "no-hooks",
+                        "the config has no phases or tasks, so nothing runs",
+                    ));
+                }
+                diagnostics.extend(
+                    config
+                        .warnings()
+                        .into_iter()
+                        .map(|warning| Diagnostic::warning("ineffective-setting", warning.message)),
+                );
+            }
+            Err(error) => diagnostics.push(Diagnostic::error("invalid-config", error.to_string())),
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.severity);
+    diagnostics
+}
+
+/// Checks the entries of the phase or task written as `[[hook_name]]`.
+fn check_entries(hook_name: &str, value: &toml::Value, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(items) = value.as_array() else {
+        diagnostics.push(Diagnostic::error(
+            "invalid-table",
+            format!("'{hook_name}' must be a list of entries, written as [[{hook_name}]]"),
+        ));
+        return;
+    };
+    if items.is_empty() {
+        diagnostics.push(Diagnostic::error(
+            "empty-phase",
+            ValidationError::EmptyHookEntries {
+                hook_name: hook_name.to_string(),
+            }
+            .to_string(),
+        ));
+    }
+    let mut entries: Vec<(usize, HookDefinition)> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let location = format!("Hook '{hook_name}' entry #{}", index + 1);
+        let hook = match item.clone().try_into::<HookDefinition>() {
+            Ok(hook) => hook,
+            Err(error) => {
+                diagnostics.push(Diagnostic::error(
+                    "invalid-entry",
+                    format!("{location}: {}", field_error(error.message())),
+                ));
+                continue;
+            }
+        };
+        if hook.task.is_none()
+            && let Err(error) = validate_entry(hook_name, index, &hook)
+        {
+            let code = match error {
+                ValidationError::EmptyCommand { .. } => "empty-command",
+                _ => "invalid-entry",
+            };
+            diagnostics.push(Diagnostic::error(code, error.to_string()));
+        }
+        entries.push((index, hook));
+    }
+    for (position, (index, hook)) in entries.iter().enumerate() {
+        let earlier = &entries[..position];
+        let same_name = hook.name.as_deref().and_then(|name| {
+            earlier
+                .iter()
+                .find(|(_, other)| other.name.as_deref() == Some(name))
+        });
+        if let (Some((other_index, _)), Some(name)) = (same_name, &hook.name) {
+            diagnostics.push(Diagnostic::warning(
+                "duplicate-name",
+                format!(
+                    "Hook '{hook_name}' entries #{} and #{} are both named '{name}', so their output and summary lines cannot be told apart",
+                    other_index + 1,
+                    index + 1
+                ),
+            ));
+        }
+        let same_command = earlier.iter().find(|(_, other)| {
+            !hook.command.trim().is_empty()
+                && other.command.trim() == hook.command.trim()
+                && other.task == hook.task
+        });
+        if let Some((other_index, _)) = same_command {
+            diagnostics.push(Diagnostic::warning(
+                "duplicate-command",
+                format!(
+                    "Hook '{hook_name}' entries #{} and #{} both run `{}`",
+                    other_index + 1,
+                    index + 1,
+                    hook.command.trim()
+                ),
+            ));
+        }
+    }
+}
+
+fn syntax_error(source: &str, error: &toml::de::Error) -> Diagnostic {
+    let message = match error.span() {
+        Some(span) => format!(
+            "line {}: {}",
+            source[..span.start.min(source.len())]
+                .lines()
+                .count()
+                .max(1),
+            error.message()
+        ),
+        None => error.message().to_string(),
+    };
+    Diagnostic::error("syntax", message)
+}
+
+/// Shortens serde's unknown field message, which lists every field, to the
+/// closest one.
+fn field_error(message: &str) -> String {
+    let Some(rest) = message.strip_prefix("unknown field `") else {
+        return message.to_string();
+    };
+    let Some((field, expected)) = rest.split_once('`') else {
+        return message.to_string();
+    };
+    let candidates = expected.split('`').skip(1).step_by(2);
+    format!("unknown field '{field}'{}", suggestion(field, candidates))
+}
+
+/// `; did you mean 'x'?` for the candidate closest to `key`, when one is close
+/// enough to be a typo.
+fn suggestion<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> String {
+    candidates
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= 2.max(candidate.len() / 4))
+        .min()
+        .map(|(_, candidate)| format!("; did you mean '{candidate}'?"))
+        .unwrap_or_default()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str) -> Vec<String> {
+        diagnose(source).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn given_several_problems_when_diagnosing_then_each_is_reported_with_errors_first() {
+        let source = "[[pre-comit]]\ncommand = \"cargo test\"\n\n[[pre-push]]\ncommand = \"  \"\n\n[[pre-push]]\nname = \"test\"\ncomand = \"cargo test\"\n\n[[commit-msg]]\nname = \"lint\"\ncommand = \"commitlint\"\n\n[[commit-msg]]\nname = \"lint\"\ncommand = \"commitlint\"\n";
+
+        assert_eq!(
+            messages(source),
+            [
+                "error[unknown-key]: 'pre-comit' is neither a lifecycle phase nor one of settings, tasks, defaults, and install; did you mean 'pre-commit'?",
+                "error[empty-command]: Hook 'pre-push' entry #1: command must not be empty",
+                "error[invalid-entry]: Hook 'pre-push' entry #2: unknown field 'comand'; did you mean 'command'?",
+                "warning[duplicate-name]: Hook 'commit-msg' entries #1 and #2 are both named 'lint', so their output and summary lines cannot be told apart",
+                "warning[duplicate-command]: Hook 'commit-msg' entries #1 and #2 both run `commitlint`",
+            ]
+        );
+    }
+
+    #[test]
+    fn given_valid_entries_when_diagnosing_then_cross_entry_checks_and_warnings_apply() {
+        assert_eq!(
+            messages("[[pre-commit]]\ntask = \"lint\"\n"),
+            ["error[invalid-config]: Hook 'pre-commit' entry #1: references unknown task 'lint'"]
+        );
+        assert_eq!(
+            messages("[[pre-push]]\ncommand = \"cargo test\"\nrestage = true\n")[0],
+            "warning[ineffective-setting]: Hook 'pre-push' entry #1: restage has no effect in pre-push; only pre-commit builds the commit from the staged files"
+        );
+        assert!(messages("[[pre-commit]]\ncommand = \"cargo fmt --check\"\n").is_empty());
+        assert_eq!(
+            messages("[[pre-commit]\n"),
+            ["error[syntax]: line 1: unclosed array table, expected `]`"]
+        );
+    }
+}
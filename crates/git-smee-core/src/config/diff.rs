@@ -0,0 +1,317 @@
+//! Behavioral differences between two configs, for `git smee diff-config`.
+//!
+//! Entries of a phase or task are paired by `name`, then by command, then by
+//! position, so inserting an entry does not show up as a change to every entry
+//! after it. Entries are compared after `[defaults]` have been applied, so a
+//! changed default shows on each entry it affects as well as on its own.
+
+use std::{collections::BTreeMap, fmt};
+
+use super::hook_label;
+use super::{Error, HookDefinition, LifeCyclePhase, SmeeConfig};
+
+/// One value that differs; `None` means unset, i.e. the default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub key: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value =
+            |value: &Option<String>| value.clone().unwrap_or_else(|| "(default)".to_string());
+        write!(
+            f,
+            "{} {} -> {}",
+            self.key,
+            value(&self.old),
+            value(&self.new)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigChange {
+    /// A value outside the phases and tasks, e.g. `settings.nested_runs`.
+    Setting(FieldChange),
+    HookAdded {
+        run: String,
+        label: String,
+        command: String,
+    },
+    HookRemoved {
+        run: String,
+        label: String,
+        command: String,
+    },
+    HookChanged {
+        run: String,
+        label: String,
+        fields: Vec<FieldChange>,
+    },
+    /// Entries that are in both configs run in a different order.
+    HooksReordered { run: String },
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Setting(change) => write!(f, "~ {change}"),
+            Self::HookAdded {
+                run,
+                label,
+                command,
+            } => write!(f, "+ {run}: {label} added: {command}"),
+            Self::HookRemoved {
+                run,
+                label,
+                command,
+            } => write!(f, "- {run}: {label} removed: {command}"),
+            Self::HookChanged { run, label, fields } => {
+                let fields: Vec<String> = fields.iter().map(ToString::to_string).collect();
+                write!(f, "~ {run}: {label} changed: {}", fields.join(", "))
+            }
+            Self::HooksReordered { run } => write!(f, "~ {run}: commands run in a different order"),
+        }
+    }
+}
+
+/// Lists what changes in behavior from `old` to `new`: settings and defaults
+/// first, then each phase in the order Git runs them, then tasks as
+/// `task:<name>`.
+pub fn diff_configs(old: &SmeeConfig, new: &SmeeConfig) -> Result<Vec<ConfigChange>, Error> {
+    let mut changes: Vec<ConfigChange> = diff_values(&setting_values(old)?, &setting_values(new)?)
+        .into_iter()
+        .map(ConfigChange::Setting)
+        .collect();
+    for phase in LifeCyclePhase::all() {
+        diff_entries(
+            phase.as_str(),
+            old.hooks.get(phase).map_or(&[], Vec::as_slice),
+            new.hooks.get(phase).map_or(&[], Vec::as_slice),
+            &mut changes,
+        )?;
+    }
+    let mut tasks: Vec<&String> = old.tasks.keys().chain(new.tasks.keys()).collect();
+    tasks.sort();
+    tasks.dedup();
+    for task in tasks {
+        diff_entries(
+            &format!("task:{task}"),
+            old.tasks.get(task).map_or(&[], Vec::as_slice),
+            new.tasks.get(task).map_or(&[], Vec::as_slice),
+            &mut changes,
+        )?;
+    }
+    Ok(changes)
+}
+
+fn diff_entries(
+    run: &str,
+    old: &[HookDefinition],
+    new: &[HookDefinition],
+    changes: &mut Vec<ConfigChange>,
+) -> Result<(), Error> {
+    // Old entry index paired with each new entry.
+    let mut pairs: Vec<Option<usize>> = vec![None; new.len()];
+    let mut paired_old = vec![false; old.len()];
+    for key in [
+        name_key as fn(&HookDefinition) -> Option<String>,
+        command_key,
+    ] {
+        for (old_index, old_hook) in old.iter().enumerate() {
+            let Some(old_key) = key(old_hook).filter(|_| !paired_old[old_index]) else {
+                continue;
+            };
+            let matching = (0..new.len()).find(|&new_index| {
+                pairs[new_index].is_none() && key(&new[new_index]) == Some(old_key.clone())
+            });
+            if let Some(new_index) = matching {
+                pairs[new_index] = Some(old_index);
+                paired_old[old_index] = true;
+            }
+        }
+    }
+    // An entry edited in place keeps its position and its (lack of a) name.
+    for (old_index, old_hook) in old.iter().enumerate() {
+        if !paired_old[old_index]
+            && new.get(old_index).is_some_and(|new_hook| {
+                pairs[old_index].is_none() && new_hook.name == old_hook.name
+            })
+        {
+            pairs[old_index] = Some(old_index);
+            paired_old[old_index] = true;
+        }
+    }
+
+    for (new_index, new_hook) in new.iter().enumerate() {
+        let label = hook_label(new_index, new_hook);
+        match pairs[new_index] {
+            None => changes.push(ConfigChange::HookAdded {
+                run: run.to_string(),
+                label,
+                command: describe(new_hook),
+            }),
+            Some(old_index) => {
+                let fields = diff_values(&hook_values(&old[old_index])?, &hook_values(new_hook)?);
+                if !fields.is_empty() {
+                    changes.push(ConfigChange::HookChanged {
+                        run: run.to_string(),
+                        label,
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    for (old_index, old_hook) in old.iter().enumerate() {
+        if !paired_old[old_index] {
+            changes.push(ConfigChange::HookRemoved {
+                run: run.to_string(),
+                label: hook_label(old_index, old_hook),
+                command: describe(old_hook),
+            });
+        }
+    }
+    let mut kept: Vec<(usize, usize)> = pairs
+        .iter()
+        .enumerate()
+        .filter_map(|(new_index, old_index)| Some(((*old_index)?, new_index)))
+        .collect();
+    kept.sort();
+    if kept.windows(2).any(|pair| pair[0].1 > pair[1].1) {
+        changes.push(ConfigChange::HooksReordered {
+            run: run.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn name_key(hook: &HookDefinition) -> Option<String> {
+    hook.name.clone()
+}
+
+fn command_key(hook: &HookDefinition) -> Option<String> {
+    Some(format!("{}\0{:?}", hook.command, hook.task))
+}
+
+fn describe(hook: &HookDefinition) -> String {
+    match &hook.task {
+        Some(task) => format!("task {task}"),
+        None => hook.command.clone(),
+    }
+}
+
+/// Every value outside the phases and tasks, by dotted key.
+fn setting_values(config: &SmeeConfig) -> Result<BTreeMap<String, String>, Error> {
+    let mut table = toml::Table::try_from(config)?;
+    table.remove("tasks");
+    for phase in LifeCyclePhase::all() {
+        table.remove(phase.as_str());
+    }
+    let mut values = BTreeMap::new();
+    flatten("", &toml::Value::Table(table), &mut values);
+    Ok(values)
+}
+
+fn hook_values(hook: &HookDefinition) -> Result<BTreeMap<String, String>, Error> {
+    let mut values = BTreeMap::new();
+    flatten(
+        "",
+        &toml::Value::Table(toml::Table::try_from(hook)?),
+        &mut values,
+    );
+    Ok(values)
+}
+
+fn flatten(prefix: &str, value: &toml::Value, values: &mut BTreeMap<String, String>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let key = match prefix {
+                    "" => key.clone(),
+                    prefix => format!("{prefix}.{key}"),
+                };
+                flatten(&key, value, values);
+            }
+        }
+        value => {
+            values.insert(prefix.to_string(), value.to_string());
+        }
+    }
+}
+
+fn diff_values(old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) -> Vec<FieldChange> {
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .map(|key| FieldChange {
+            key: key.clone(),
+            old: old.get(key).cloned(),
+            new: new.get(key).cloned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(source: &str) -> SmeeConfig {
+        SmeeConfig::from_toml_str(source).unwrap()
+    }
+
+    #[test]
+    fn given_inserted_and_edited_entries_when_diffing_then_only_real_changes_are_listed() {
+        let old = config(
+            "[[pre-commit]]\nname = \"fmt\"\ncommand = \"cargo fmt --check\"\n\n[[pre-commit]]\ncommand = \"cargo test\"\n",
+        );
+        let new = config(
+            "[settings]\nnested_runs = \"allow\"\n\n[[pre-commit]]\ncommand = \"cargo clippy\"\n\n[[pre-commit]]\nname = \"fmt\"\ncommand = \"cargo fmt --check\"\ntimeout = 30\n\n[[pre-commit]]\ncommand = \"cargo test\"\n",
+        );
+
+        let changes: Vec<String> = diff_configs(&old, &new)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            changes,
+            [
+                "~ settings.nested_runs (default) -> \"allow\"",
+                "+ pre-commit: hook-1 added: cargo clippy",
+                "~ pre-commit: fmt changed: timeout (default) -> 30",
+            ]
+        );
+    }
+
+    #[test]
+    fn given_removed_task_and_reordered_phase_when_diffing_then_both_are_reported() {
+        let old = config(
+            "[[pre-push]]\ncommand = \"a\"\n\n[[pre-push]]\ncommand = \"b\"\n\n[[tasks.lint]]\ncommand = \"eslint .\"\n",
+        );
+        let new = config("[[pre-push]]\ncommand = \"b\"\n\n[[pre-push]]\ncommand = \"a\"\n");
+
+        let changes = diff_configs(&old, &new).unwrap();
+
+        assert_eq!(
+            changes,
+            [
+                ConfigChange::HooksReordered {
+                    run: "pre-push".to_string()
+                },
+                ConfigChange::HookRemoved {
+                    run: "task:lint".to_string(),
+                    label: "hook-1".to_string(),
+                    command: "eslint .".to_string(),
+                },
+            ]
+        );
+        assert!(diff_configs(&new, &new).unwrap().is_empty());
+    }
+}
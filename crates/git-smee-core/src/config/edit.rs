@@ -0,0 +1,298 @@
+//! Read and change single config values addressed by a dotted key path such as
+//! `pre-commit.0.command`. Edits splice the new value into the original text,
+//! so comments and formatting elsewhere in the file are left untouched.
+
+use std::ops::Range;
+
+use toml::de::{DeTable, DeValue};
+
+use super::{Error, SmeeConfig};
+
+/// Returns the value at `key`. Strings are returned without quotes; every other
+/// value is rendered as TOML.
+pub fn get_value(source: &str, key: &str) -> Result<String, Error> {
+    let segments = parse_key(key)?;
+    let root: toml::Table = toml::from_str(source)?;
+    let mut current = None::<&toml::Value>;
+    for segment in &segments {
+        let next = match current {
+            None => root.get(*segment),
+            Some(toml::Value::Table(table)) => table.get(*segment),
+            Some(toml::Value::Array(items)) => {
+                segment.parse::<usize>().ok().and_then(|i| items.get(i))
+            }
+            Some(_) => None,
+        };
+        current = Some(next.ok_or_else(|| Error::KeyNotFound(key.to_string()))?);
+    }
+    Ok(match current {
+        Some(toml::Value::String(value)) => value.clone(),
+        Some(value) => value.to_string(),
+        None => unreachable!("parse_key returns at least one segment"),
+    })
+}
+
+/// Sets `key` to `value` and returns the updated file contents.
+///
+/// `value` is used as a TOML literal when it parses as one (`4`, `true`,
+/// `"text"`, `["a", "b"]`) and as a plain string otherwise. A missing key is
+/// added to its existing table, and a missing top-level table (such as
+/// `settings`) is appended to the end of the file. The result must still be a
+/// valid config.
+pub fn set_value(source: &str, key: &str, value: &str) -> Result<String, Error> {
+    let segments = parse_key(key)?;
+    let literal = value_literal(value);
+    let root = DeTable::parse(source)?;
+    let updated = match locate(source, root.get_ref(), &segments, key)? {
+        Edit::Replace(span) => splice(source, span, &literal),
+        Edit::InsertAfterHeader(header) => {
+            let line = format!(
+                "{} = {literal}\n",
+                key_literal(segments[segments.len() - 1])
+            );
+            match source[header.end..].find('\n') {
+                Some(offset) => {
+                    let at = header.end + offset + 1;
+                    splice(source, at..at, &line)
+                }
+                None => format!("{source}\n{line}"),
+            }
+        }
+        Edit::AppendTable => {
+            let separator = match source {
+                "" => "",
+                source if source.ends_with("\n\n") => "",
+                source if source.ends_with('\n') => "\n",
+                _ => "\n\n",
+            };
+            format!(
+                "{source}{separator}[{}]\n{} = {literal}\n",
+                key_literal(segments[0]),
+                key_literal(segments[1]),
+            )
+        }
+    };
+    let config: SmeeConfig = toml::from_str(&updated)?;
+    config.validate()?;
+    Ok(updated)
+}
+
+enum Edit {
+    Replace(Range<usize>),
+    InsertAfterHeader(Range<usize>),
+    AppendTable,
+}
+
+fn locate(source: &str, root: &DeTable<'_>, segments: &[&str], key: &str) -> Result<Edit, Error> {
+    let uneditable = |reason: &'static str| Error::UneditableKey {
+        key: key.to_string(),
+        reason,
+    };
+    let mut table = root;
+    let mut header = None::<Range<usize>>;
+    let mut position = 0;
+    while position < segments.len() {
+        let segment = segments[position];
+        let is_last = position + 1 == segments.len();
+        let Some(value) = lookup(table, segment) else {
+            return match (header, is_last) {
+                (Some(header), true) if source[header.clone()].starts_with('[') => {
+                    Ok(Edit::InsertAfterHeader(header))
+                }
+                (Some(_), true) => Err(uneditable(
+                    "its table is written inline or with dotted keys",
+                )),
+                (None, true) => Err(uneditable("new top-level values must be tables")),
+                (None, false) if position + 2 == segments.len() => Ok(Edit::AppendTable),
+                _ => Err(Error::KeyNotFound(key.to_string())),
+            };
+        };
+        position += 1;
+        let value = match value.get_ref() {
+            DeValue::Array(items) if !is_last => {
+                let item = segments[position]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| items.get(index))
+                    .ok_or_else(|| Error::KeyNotFound(key.to_string()))?;
+                position += 1;
+                item
+            }
+            _ => value,
+        };
+        match value.get_ref() {
+            DeValue::Table(child) if position < segments.len() => {
+                table = child;
+                header = Some(value.span());
+            }
+            DeValue::Table(_) => return Err(uneditable("tables cannot be replaced as a whole")),
+            DeValue::Array(items)
+                if items
+                    .iter()
+                    .any(|item| matches!(item.get_ref(), DeValue::Table(_))) =>
+            {
+                return Err(uneditable("arrays of tables cannot be replaced as a whole"));
+            }
+            _ if position < segments.len() => return Err(Error::KeyNotFound(key.to_string())),
+            _ => return Ok(Edit::Replace(value.span())),
+        }
+    }
+    unreachable!("the loop returns once the last segment is reached")
+}
+
+fn lookup<'a>(table: &'a DeTable<'_>, segment: &str) -> Option<&'a toml::Spanned<DeValue<'a>>> {
+    table
+        .iter()
+        .find(|(name, _)| name.get_ref().as_ref() == segment)
+        .map(|(_, value)| value)
+}
+
+fn parse_key(key: &str) -> Result<Vec<&str>, Error> {
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|segment| segment.trim().is_empty()) {
+        return Err(Error::InvalidKeyPath(key.to_string()));
+    }
+    Ok(segments)
+}
+
+fn value_literal(value: &str) -> String {
+    match DeValue::parse(value) {
+        Ok(_) => value.trim().to_string(),
+        Err(_) => toml::Value::String(value.to_string()).to_string(),
+    }
+}
+
+pub(super) fn key_literal(segment: &str) -> String {
+    let is_bare = segment
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if is_bare {
+        segment.to_string()
+    } else {
+        toml::Value::String(segment.to_string()).to_string()
+    }
+}
+
+fn splice(source: &str, span: Range<usize>, replacement: &str) -> String {
+    let mut updated = String::with_capacity(source.len() + replacement.len());
+    updated.push_str(&source[..span.start]);
+    updated.push_str(replacement);
+    updated.push_str(&source[span.end..]);
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const CONFIG: &str = "# Team hooks\n[[pre-commit]]\ncommand = \"cargo fmt --check\" # fast\n\n# Slow checks\n[[pre-commit]]\ncommand = \"cargo test\"\nparallel_execution_allowed = true\n";
+
+    #[test]
+    fn given_indexed_key_when_getting_then_value_is_returned_unquoted() {
+        assert_eq!(
+            get_value(CONFIG, "pre-commit.1.command").unwrap(),
+            "cargo test"
+        );
+        assert_eq!(
+            get_value(CONFIG, "pre-commit.1.parallel_execution_allowed").unwrap(),
+            "true"
+        );
+    }
+
+    #[test]
+    fn given_missing_key_when_getting_then_key_not_found_is_returned() {
+        let error = get_value(CONFIG, "pre-commit.2.command").unwrap_err();
+
+        assert!(matches!(error, Error::KeyNotFound(key) if key == "pre-commit.2.command"));
+    }
+
+    #[test]
+    fn given_existing_value_when_setting_then_only_that_value_changes() {
+        let updated =
+            set_value(CONFIG, "pre-commit.0.command", "cargo fmt --all -- --check").unwrap();
+
+        assert_eq!(
+            updated,
+            CONFIG.replace("\"cargo fmt --check\"", "\"cargo fmt --all -- --check\"")
+        );
+    }
+
+    #[test]
+    fn given_missing_key_in_table_when_setting_then_it_is_added_below_the_header() {
+        let updated = set_value(CONFIG, "pre-commit.0.timeout", "30").unwrap();
+
+        assert!(
+            updated
+                .contains("[[pre-commit]]\ntimeout = 30\ncommand = \"cargo fmt --check\" # fast\n")
+        );
+        assert!(updated.contains("# Slow checks"));
+    }
+
+    #[test]
+    fn given_value_that_would_invalidate_config_when_setting_then_error_is_returned() {
+        let error = set_value(CONFIG, "pre-commit.0.timeout", "0").unwrap_err();
+
+        assert!(matches!(error, Error::ValidationError(_)));
+    }
+
+    #[test]
+    fn given_table_key_when_setting_then_edit_is_refused() {
+        let error = set_value(CONFIG, "pre-commit.0", "x").unwrap_err();
+
+        assert!(matches!(error, Error::UneditableKey { .. }));
+    }
+
+    #[test]
+    fn given_empty_segment_when_parsing_key_then_invalid_key_path_is_returned() {
+        assert!(matches!(
+            parse_key("pre-commit..command"),
+            Err(Error::InvalidKeyPath(_))
+        ));
+    }
+
+    #[test]
+    fn given_plain_and_literal_values_when_rendering_then_only_plain_text_is_quoted() {
+        assert_eq!(value_literal("cargo test"), "\"cargo test\"");
+        assert_eq!(value_literal("4"), "4");
+        assert_eq!(value_literal("\"4\""), "\"4\"");
+        assert_eq!(value_literal("[\"a\", \"b\"]"), "[\"a\", \"b\"]");
+    }
+
+    fn command() -> impl Strategy<Value = String> {
+        ("[a-z]", any::<String>()).prop_map(|(start, rest)| start + &rest)
+    }
+
+    proptest! {
+        #[test]
+        fn setting_a_command_changes_only_that_value(
+            commands in proptest::collection::vec(command(), 1..4),
+            index in any::<proptest::sample::Index>(),
+            replacement in command(),
+        ) {
+            let source: String = commands
+                .iter()
+                .enumerate()
+                .map(|(position, command)| {
+                    format!(
+                        "# hook {position}\n[[pre-commit]]\ncommand = {} # [pre-push]\n\n",
+                        toml::Value::String(command.clone())
+                    )
+                })
+                .collect();
+            let index = index.index(commands.len());
+            let key = format!("pre-commit.{index}.command");
+
+            let updated =
+                set_value(&source, &key, &toml::Value::String(replacement.clone()).to_string())
+                    .unwrap();
+
+            let mut expected: toml::Table = toml::from_str(&source).unwrap();
+            expected["pre-commit"][index]["command"] = toml::Value::String(replacement.clone());
+            prop_assert_eq!(toml::from_str::<toml::Table>(&updated).unwrap(), expected);
+            prop_assert_eq!(get_value(&updated, &key).unwrap(), replacement);
+            prop_assert_eq!(updated.lines().count(), source.lines().count());
+        }
+    }
+}
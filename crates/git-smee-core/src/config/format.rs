@@ -0,0 +1,572 @@
+//! Canonical layout for config files, for `git smee config fmt`.
+//!
+//! Tables are ordered `settings`, `defaults`, the phases in the order Git runs
+//! them, then `tasks`, with anything else after them in its original order.
+//! Entries of one phase keep their relative order. Keys are written bare where
+//! TOML allows it, with ` = ` around the value, headers and keys lose their
+//! indentation, trailing whitespace goes, and tables are separated by exactly
+//! one blank line.
+//!
+//! Comments are kept. Those above the first table stay at the top of the file;
+//! elsewhere, comment lines directly above a table header move with it. Values
+//! themselves, including multi-line strings and arrays, are left as written.
+
+use toml::de::DeValue;
+
+use super::{Error, LifeCyclePhase, edit::key_literal};
+
+/// Returns `source` in the canonical layout. Formatting never changes what the
+/// config means; if it would, [`Error::FormattingChangedValues`] is returned
+/// instead.
+pub fn format_config(source: &str) -> Result<String, Error> {
+    let original: toml::Table = toml::from_str(source)?;
+    let lines = logical_lines(source);
+    let first_header = lines.iter().position(|line| line.kind == LineKind::Header);
+    // Comments directly above a header belong to it.
+    let (preamble_end, first_header) = match first_header {
+        Some(index) => (index - attached_comments(&lines[..index]), index),
+        None => (lines.len(), lines.len()),
+    };
+    let preamble = &lines[..preamble_end];
+    let mut leading = lines[preamble_end..first_header].to_vec();
+
+    let mut blocks: Vec<Block> = Vec::new();
+    for line in &lines[first_header..] {
+        if line.kind == LineKind::Header {
+            let mut block = Block {
+                table: line.table.clone(),
+                lines: std::mem::take(&mut leading),
+            };
+            if let Some(previous) = blocks.last_mut() {
+                let attached = attached_comments(&previous.lines);
+                block
+                    .lines
+                    .extend(previous.lines.drain(previous.lines.len() - attached..));
+            }
+            blocks.push(block);
+        }
+        if let Some(block) = blocks.last_mut() {
+            block.lines.push(line.clone());
+        }
+    }
+    blocks.sort_by_key(|block| table_rank(&block.table));
+
+    let mut sections: Vec<String> = Vec::new();
+    let preamble = render_lines(preamble);
+    if !preamble.is_empty() {
+        sections.push(preamble);
+    }
+    sections.extend(blocks.iter().map(|block| render_lines(&block.lines)));
+    let mut formatted = sections.join("\n\n");
+    if !formatted.is_empty() {
+        formatted.push('\n');
+    }
+
+    let reformatted: toml::Table = toml::from_str(&formatted)?;
+    if reformatted != original {
+        return Err(Error::FormattingChangedValues);
+    }
+    Ok(formatted)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    Blank,
+    Comment,
+    Header,
+    KeyValue,
+    /// A line inside a multi-line string, array, or inline table.
+    Continuation,
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    kind: LineKind,
+    text: String,
+    /// First key of a header, e.g. `pre-commit` for `[pre-commit.env]`.
+    table: String,
+}
+
+struct Block {
+    table: String,
+    lines: Vec<Line>,
+}
+
+/// Number of comment lines at the end of `lines`.
+fn attached_comments(lines: &[Line]) -> usize {
+    lines
+        .iter()
+        .rev()
+        .take_while(|line| line.kind == LineKind::Comment)
+        .count()
+}
+
+fn table_rank(table: &str) -> usize {
+    let phases = LifeCyclePhase::all();
+    match table {
+        "settings" => 0,
+        "defaults" | "install" => 1,
+        "tasks" => phases.len() + 2,
+        table => phases
+            .iter()
+            .position(|phase| phase.as_str() == table)
+            .map_or(phases.len() + 3, |index| index + 2),
+    }
+}
+
+/// Joins `lines` with repeated and trailing blank lines dropped.
+fn render_lines(lines: &[Line]) -> String {
+    let mut rendered: Vec<&str> = Vec::new();
+    for line in lines {
+        let repeated_blank = line.kind == LineKind::Blank
+            && rendered.last().is_none_or(|previous| previous.is_empty());
+        if !repeated_blank {
+            rendered.push(&line.text);
+        }
+    }
+    while rendered.last().is_some_and(|line| line.is_empty()) {
+        rendered.pop();
+    }
+    rendered.join("\n")
+}
+
+/// Splits `source` into lines, normalizing those that start a statement.
+fn logical_lines(source: &str) -> Vec<Line> {
+    let mut scanner = Scanner::default();
+    source
+        .lines()
+        .map(|raw| {
+            if !scanner.at_statement_start() {
+                scanner.scan(raw);
+                return line(LineKind::Continuation, raw.to_string());
+            }
+            let trimmed = raw.trim();
+            if trimmed.is_empty() {
+                return line(LineKind::Blank, String::new());
+            }
+            if trimmed.starts_with('#') {
+                return line(LineKind::Comment, trimmed.to_string());
+            }
+            if trimmed.starts_with('[') {
+                return match normalize_header(trimmed) {
+                    Some((text, table)) => Line {
+                        kind: LineKind::Header,
+                        text,
+                        table,
+                    },
+                    None => line(LineKind::Header, trimmed.to_string()),
+                };
+            }
+            scanner.scan(raw);
+            let text = normalize_key_value(raw.trim_start()).unwrap_or_else(|| raw.to_string());
+            // Trailing whitespace inside a multi-line string is content.
+            let text = if scanner.at_statement_start() {
+                text.trim_end().to_string()
+            } else {
+                text
+            };
+            line(LineKind::KeyValue, text)
+        })
+        .collect()
+}
+
+fn line(kind: LineKind, text: String) -> Line {
+    Line {
+        kind,
+        text,
+        table: String::new(),
+    }
+}
+
+/// `[ "pre-commit" ] # x` becomes `[pre-commit] # x`. Returns the header and
+/// its first key, or `None` for a header that does not parse.
+fn normalize_header(trimmed: &str) -> Option<(String, String)> {
+    let (open, close) = if trimmed.starts_with("[[") {
+        ("[[", "]]")
+    } else {
+        ("[", "]")
+    };
+    let (keys, rest) = parse_dotted_key(&trimmed[open.len()..])?;
+    let rest = rest.trim_start().strip_prefix(close)?.trim();
+    let mut header = format!("{open}{}{close}", render_key(&keys));
+    if !rest.is_empty() {
+        header.push(' ');
+        header.push_str(rest);
+    }
+    Some((header, keys[0].clone()))
+}
+
+/// `"command"="x"` becomes `command = "x"`.
+fn normalize_key_value(text: &str) -> Option<String> {
+    let (keys, rest) = parse_dotted_key(text)?;
+    let value = rest.trim_start().strip_prefix('=')?.trim_start();
+    Some(format!("{} = {value}", render_key(&keys)))
+}
+
+fn render_key(keys: &[String]) -> String {
+    keys.iter()
+        .map(|key| key_literal(key))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Parses a dotted key such as `a."b.c".d` at the start of `text` and returns
+/// its decoded parts and the rest of the text.
+fn parse_dotted_key(text: &str) -> Option<(Vec<String>, &str)> {
+    let mut keys = Vec::new();
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        let length = match rest.chars().next()? {
+            '"' => quoted_length(rest, '"', true)?,
+            '\'' => quoted_length(rest, '\'', false)?,
+            _ => rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+                .unwrap_or(rest.len()),
+        };
+        if length == 0 {
+            return None;
+        }
+        let (token, after) = rest.split_at(length);
+        keys.push(decode_key(token)?);
+        rest = after.trim_start();
+        match rest.strip_prefix('.') {
+            Some(after_dot) => rest = after_dot,
+            None => return Some((keys, rest)),
+        }
+    }
+}
+
+/// Length of the single-line string starting at `text`, quotes included.
+fn quoted_length(text: &str, quote: char, escapes: bool) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' if escapes => {
+                chars.next();
+            }
+            c if c == quote => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn decode_key(token: &str) -> Option<String> {
+    if !token.starts_with(['"', '\'']) {
+        return Some(token.to_string());
+    }
+    match DeValue::parse(token).ok()?.into_inner() {
+        DeValue::String(key) => Some(key.into_owned()),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum StringState {
+    #[default]
+    None,
+    MultilineBasic,
+    MultilineLiteral,
+}
+
+/// Tracks multi-line strings and open brackets across lines, to tell lines
+/// that start a statement from lines that continue a value.
+#[derive(Debug, Default)]
+struct Scanner {
+    string: StringState,
+    depth: usize,
+}
+
+impl Scanner {
+    fn at_statement_start(&self) -> bool {
+        self.string == StringState::None && self.depth == 0
+    }
+
+    fn scan(&mut self, line: &str) {
+        let mut rest = line;
+        while !rest.is_empty() {
+            rest = match self.string {
+                StringState::MultilineBasic => self.close_multiline(rest, "\"\"\"", true),
+                StringState::MultilineLiteral => self.close_multiline(rest, "'''", false),
+                StringState::None => {
+                    let Some(index) = rest.find(['#', '"', '\'', '[', ']', '{', '}']) else {
+                        return;
+                    };
+                    let rest = &rest[index..];
+                    match rest.as_bytes()[0] {
+                        b'#' => return,
+                        b'[' | b'{' => {
+                            self.depth += 1;
+                            &rest[1..]
+                        }
+                        b']' | b'}' => {
+                            self.depth = self.depth.saturating_sub(1);
+                            &rest[1..]
+                        }
+                        _ if rest.starts_with("\"\"\"") => {
+                            self.string = StringState::MultilineBasic;
+                            &rest[3..]
+                        }
+                        _ if rest.starts_with("'''") => {
+                            self.string = StringState::MultilineLiteral;
+                            &rest[3..]
+                        }
+                        quote => {
+                            let quote = quote as char;
+                            match quoted_length(rest, quote, quote == '"') {
+                                Some(length) => &rest[length..],
+                                None => return,
+                            }
+                        }
+                    }
+                }
+            };
+        }
+    }
+
+    /// Skips to the end of the open multi-line string in `text`, or to the
+    /// end of `text` when the string goes on.
+    fn close_multiline<'a>(&mut self, text: &'a str, delimiter: &str, escapes: bool) -> &'a str {
+        let mut index = 0;
+        while index < text.len() {
+            if escapes && text[index..].starts_with('\\') {
+                index += 1 + text[index + 1..].chars().next().map_or(0, char::len_utf8);
+                continue;
+            }
+            if text[index..].starts_with(delimiter) {
+                self.string = StringState::None;
+                // Up to two quotes right before the delimiter are content.
+                let quote = delimiter.as_bytes()[0];
+                let extra = text.as_bytes()[index + 3..]
+                    .iter()
+                    .take(2)
+                    .take_while(|&&byte| byte == quote)
+                    .count();
+                return &text[index + 3 + extra..];
+            }
+            index += text[index..].chars().next().map_or(1, char::len_utf8);
+        }
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const TABLES: [&str; 8] = [
+        "zz-custom",
+        "tasks",
+        "pre-push",
+        "commit-msg",
+        "pre-commit",
+        "defaults",
+        "settings",
+        "aa-custom",
+    ];
+
+    /// A line of a table body, rendered by [`render_body_line`].
+    #[derive(Debug, Clone)]
+    enum BodyLine {
+        KeyValue {
+            indent: &'static str,
+            key_style: usize,
+            separator: &'static str,
+            value: String,
+            trailing: &'static str,
+        },
+        Comment(&'static str, String),
+        Blank(&'static str),
+    }
+
+    fn single_line_value() -> impl Strategy<Value = String> {
+        prop_oneof![
+            any::<i64>().prop_map(|number| number.to_string()),
+            any::<bool>().prop_map(|flag| flag.to_string()),
+            any::<String>().prop_map(|text| toml::Value::String(text).to_string()),
+            "[a-z #=\\[\\]{}\"]{0,12}".prop_map(|text| format!("'{text}'")),
+        ]
+    }
+
+    /// Values that span lines, with text that looks like headers, keys, and
+    /// comments inside them.
+    fn multiline_value() -> impl Strategy<Value = String> {
+        let content_line = "[a-z #=\\[\\]{}.']{0,12}( {0,2})";
+        prop_oneof![
+            (proptest::collection::vec(content_line, 0..4), 0..=2usize).prop_map(
+                |(lines, quotes)| {
+                    let content: String = lines.iter().map(|line| format!("{line}\n")).collect();
+                    format!("\"\"\"\n{content}{}\"\"\"", "\"".repeat(quotes))
+                }
+            ),
+            proptest::collection::vec(content_line, 0..4).prop_map(|lines| {
+                let content = lines.join("\n").replace('\'', "\"");
+                format!("'''\n{content}'''")
+            }),
+            proptest::collection::vec(
+                (
+                    any::<String>().prop_map(|text| toml::Value::String(text).to_string()),
+                    prop_oneof![Just(""), Just("  # [not-a-header]"), Just(" # a = 1")],
+                ),
+                0..4
+            )
+            .prop_map(|items| {
+                let items: String = items
+                    .iter()
+                    .map(|(item, comment)| format!("    {item},{comment}\n"))
+                    .collect();
+                format!("[\n{items}]")
+            }),
+            single_line_value().prop_map(|value| format!("{{ a = {value}, \"b.c\" = 1 }}")),
+        ]
+    }
+
+    fn body_line() -> impl Strategy<Value = BodyLine> {
+        prop_oneof![
+            4 => (
+                prop_oneof![Just(""), Just("  "), Just("\t")],
+                0..5usize,
+                prop_oneof![Just(" = "), Just("="), Just("  =\t")],
+                prop_oneof![3 => single_line_value(), 1 => multiline_value()],
+                prop_oneof![Just(""), Just("   "), Just(" # note")],
+            )
+                .prop_map(|(indent, key_style, separator, value, trailing)| {
+                    BodyLine::KeyValue {
+                        indent,
+                        key_style,
+                        separator,
+                        value,
+                        trailing,
+                    }
+                }),
+            1 => (prop_oneof![Just(""), Just("  ")], "[a-z \\[\\]=#\"]{0,12}")
+                .prop_map(|(indent, text)| BodyLine::Comment(indent, text)),
+            1 => prop_oneof![Just(""), Just("   ")].prop_map(BodyLine::Blank),
+        ]
+    }
+
+    fn render_body_line(line: &BodyLine, key_index: usize) -> String {
+        match line {
+            BodyLine::KeyValue {
+                indent,
+                key_style,
+                separator,
+                value,
+                trailing,
+            } => {
+                let key = match key_style {
+                    0 => format!("k{key_index}"),
+                    1 => format!("\"k {key_index}\""),
+                    2 => format!("'k{key_index}'"),
+                    3 => format!("\"k.{key_index}\""),
+                    _ => format!("k{key_index} . sub"),
+                };
+                format!("{indent}{key}{separator}{value}{trailing}")
+            }
+            BodyLine::Comment(indent, text) => format!("{indent}# {text}"),
+            BodyLine::Blank(whitespace) => whitespace.to_string(),
+        }
+    }
+
+    fn header(name: &str, array: bool, style: usize) -> String {
+        let (open, close) = if array { ("[[", "]]") } else { ("[", "]") };
+        match style {
+            0 => format!("{open}{name}{close}"),
+            1 => format!("  {open} {name} {close}"),
+            2 => format!("{open}\"{name}\"{close}"),
+            _ => format!("{open}{name}{close}  # {name}"),
+        }
+    }
+
+    /// Builds a valid TOML file from a preamble and a list of tables, keeping
+    /// every key unique. Phases become arrays of tables; a repeated table name
+    /// becomes a sub-table instead.
+    fn render_config(preamble: &[BodyLine], tables: &[(usize, usize, Vec<BodyLine>)]) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut key_index = 0;
+        let mut render_body = |lines: &mut Vec<String>, body: &[BodyLine]| {
+            for line in body {
+                lines.push(render_body_line(line, key_index));
+                key_index += 1;
+            }
+        };
+        render_body(&mut lines, preamble);
+        let mut seen = Vec::new();
+        for (position, (table, style, body)) in tables.iter().enumerate() {
+            let name = TABLES[*table];
+            let is_phase = LifeCyclePhase::all()
+                .iter()
+                .any(|phase| phase.as_str() == name);
+            let header = if is_phase && (!seen.contains(&name) || position % 2 == 0) {
+                header(name, true, *style)
+            } else if seen.contains(&name) {
+                header(&format!("{name}.sub{position}"), false, *style)
+            } else {
+                header(name, false, *style)
+            };
+            seen.push(name);
+            lines.push(header);
+            render_body(&mut lines, body);
+        }
+        let mut source = lines.join("\n");
+        source.push('\n');
+        source
+    }
+
+    proptest! {
+        #[test]
+        fn formatting_keeps_values_and_is_idempotent(
+            preamble in proptest::collection::vec(body_line(), 0..4),
+            tables in proptest::collection::vec(
+                (0..TABLES.len(), 0..4usize, proptest::collection::vec(body_line(), 0..5)),
+                0..7,
+            ),
+        ) {
+            let source = render_config(&preamble, &tables);
+            let original: toml::Table = toml::from_str(&source)
+                .map_err(|error| TestCaseError::fail(format!("{error}\n{source}")))?;
+
+            let formatted = format_config(&source)
+                .map_err(|error| TestCaseError::fail(format!("{error}\n{source}")))?;
+
+            let reformatted: toml::Table = toml::from_str(&formatted).unwrap();
+            prop_assert_eq!(reformatted, original);
+            prop_assert_eq!(format_config(&formatted).unwrap(), formatted);
+        }
+    }
+
+    #[test]
+    fn given_unordered_config_when_formatting_then_tables_are_ordered_and_comments_kept() {
+        let source = "# Team hooks\n\n# Slow checks\n[[pre-push]]\n  command=\"cargo test\"   \n\n\n\n[[pre-commit]] # fast\n\"command\" = 'cargo fmt --check'\n[pre-commit.env]\nRUST_LOG = \"warn\"\n[[pre-commit]]\ncommand = \"\"\"\ncargo clippy   \n  # not a comment\n[not-a-header]\n\"\"\"\n\n[ settings ]\nfail_fast = true\n";
+
+        let formatted = format_config(source).unwrap();
+
+        assert_eq!(
+            formatted,
+            "# Team hooks\n\n[settings]\nfail_fast = true\n\n[[pre-commit]] # fast\ncommand = 'cargo fmt --check'\n\n[pre-commit.env]\nRUST_LOG = \"warn\"\n\n[[pre-commit]]\ncommand = \"\"\"\ncargo clippy   \n  # not a comment\n[not-a-header]\n\"\"\"\n\n# Slow checks\n[[pre-push]]\ncommand = \"cargo test\"\n"
+        );
+        assert_eq!(format_config(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn given_multiline_array_and_quoted_keys_when_formatting_then_values_are_left_as_written() {
+        let source = "[tasks]\n[[tasks.lint]]\ncommand = \"x\"\nenv = { \"A.B\" = \"1\" }\nrequires = [\n    \"node\",  # runtime\n]\n\n[defaults.pre-commit]\n\"timeout\" = 30\n";
+
+        let formatted = format_config(source).unwrap();
+
+        assert_eq!(
+            formatted,
+            "[defaults.pre-commit]\ntimeout = 30\n\n[tasks]\n\n[[tasks.lint]]\ncommand = \"x\"\nenv = { \"A.B\" = \"1\" }\nrequires = [\n    \"node\",  # runtime\n]\n"
+        );
+    }
+
+    #[test]
+    fn given_config_without_tables_when_formatting_then_trailing_comments_are_kept() {
+        let source = "fail_fast = true\n# Hooks go below\n#   [[pre-commit]]\n";
+
+        assert_eq!(format_config(source).unwrap(), source);
+    }
+}
@@ -0,0 +1,187 @@
+//! Who maintains which command, from the `owner` field of each entry, for
+//! `git smee owners`.
+//!
+//! Compared against an earlier revision, every added, removed, or changed
+//! entry asks for review from its owner, CODEOWNERS-style. An entry whose
+//! owner changed asks both the old and the new owner.
+
+use std::collections::HashMap;
+
+use super::hook_label;
+use super::{
+    Error, HookDefinition, LifeCyclePhase, SmeeConfig,
+    diff::{self, ConfigChange},
+};
+
+/// One entry of a phase or task and its owner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedEntry {
+    /// Phase name, or `task:<name>` for an entry of a task.
+    pub run: String,
+    pub label: String,
+    pub command: String,
+    pub owner: Option<String>,
+}
+
+/// A changed entry and who should review the change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewRequest {
+    pub change: ConfigChange,
+    /// Owners before and after the change, without duplicates. Empty when
+    /// nobody owns the entry.
+    pub owners: Vec<String>,
+}
+
+/// Every entry of `config`: phases in the order Git runs them, then tasks by
+/// name.
+pub fn owned_entries(config: &SmeeConfig) -> Vec<OwnedEntry> {
+    let mut runs: Vec<(String, &[HookDefinition])> = LifeCyclePhase::all()
+        .iter()
+        .filter_map(|phase| {
+            let hooks = config.hooks.get(phase)?;
+            Some((phase.as_str().to_string(), hooks.as_slice()))
+        })
+        .collect();
+    let mut tasks: Vec<_> = config.tasks.iter().collect();
+    tasks.sort_by_key(|(name, _)| *name);
+    runs.extend(
+        tasks
+            .into_iter()
+            .map(|(name, hooks)| (format!("task:{name}"), hooks.as_slice())),
+    );
+    runs.into_iter()
+        .flat_map(|(run, hooks)| {
+            hooks
+                .iter()
+                .enumerate()
+                .map(move |(index, hook)| OwnedEntry {
+                    run: run.clone(),
+                    label: hook_label(index, hook),
+                    command: match (&hook.task, &hook.script) {
+                        (Some(task), _) => format!("task {task}"),
+                        (None, Some(script)) => format!("script {script}"),
+                        (None, None) => hook.command.clone(),
+                    },
+                    owner: hook.owner.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Review requests for the entries added, removed, or changed from `old` to
+/// `new`, in the order of [`diff::diff_configs`]. Settings and reordered
+/// phases belong to no entry and are left out.
+pub fn review_requests(old: &SmeeConfig, new: &SmeeConfig) -> Result<Vec<ReviewRequest>, Error> {
+    let old_owners = owners_by_entry(old);
+    let new_owners = owners_by_entry(new);
+    let owner_of = |owners: &HashMap<(String, String), String>, run: &str, label: &str| {
+        owners.get(&(run.to_string(), label.to_string())).cloned()
+    };
+    let mut requests = Vec::new();
+    for change in diff::diff_configs(old, new)? {
+        let owners = match &change {
+            ConfigChange::HookAdded { run, label, .. } => vec![owner_of(&new_owners, run, label)],
+            ConfigChange::HookRemoved { run, label, .. } => {
+                vec![owner_of(&old_owners, run, label)]
+            }
+            ConfigChange::HookChanged { run, label, fields } => {
+                let previous_owner = match fields.iter().find(|field| field.key == "owner") {
+                    Some(field) => field.old.as_deref().and_then(unquote),
+                    None => owner_of(&new_owners, run, label),
+                };
+                vec![previous_owner, owner_of(&new_owners, run, label)]
+            }
+            ConfigChange::Setting(_) | ConfigChange::HooksReordered { .. } => continue,
+        };
+        let mut unique = Vec::new();
+        for owner in owners.into_iter().flatten() {
+            if !unique.contains(&owner) {
+                unique.push(owner);
+            }
+        }
+        requests.push(ReviewRequest {
+            change,
+            owners: unique,
+        });
+    }
+    Ok(requests)
+}
+
+fn owners_by_entry(config: &SmeeConfig) -> HashMap<(String, String), String> {
+    let mut owners = HashMap::new();
+    for entry in owned_entries(config) {
+        if let Some(owner) = entry.owner {
+            owners.entry((entry.run, entry.label)).or_insert(owner);
+        }
+    }
+    owners
+}
+
+/// The string a TOML value in a [`diff::FieldChange`] holds.
+fn unquote(value: &str) -> Option<String> {
+    match toml::from_str::<toml::Table>(&format!("value = {value}"))
+        .ok()?
+        .remove("value")?
+    {
+        toml::Value::String(value) => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(source: &str) -> SmeeConfig {
+        SmeeConfig::from_toml_str(source).unwrap()
+    }
+
+    #[test]
+    fn given_owned_and_unowned_entries_when_listing_then_phases_come_before_tasks() {
+        let config = config(
+            "[[tasks.lint]]\ncommand = \"eslint .\"\nowner = \"@acme/web\"\n\n[[pre-push]]\ncommand = \"cargo test\"\n\n[[pre-commit]]\nname = \"fmt\"\ncommand = \"cargo fmt --check\"\nowner = \"@acme/rust\"\n",
+        );
+
+        let entries = owned_entries(&config);
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (
+                    entry.run.as_str(),
+                    entry.label.as_str(),
+                    entry.owner.as_deref()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("pre-commit", "fmt", Some("@acme/rust")),
+                ("pre-push", "hook-1", None),
+                ("task:lint", "hook-1", Some("@acme/web")),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_changed_entries_when_requesting_reviews_then_old_and_new_owners_are_asked() {
+        let old = config(
+            "[[pre-commit]]\nname = \"fmt\"\ncommand = \"cargo fmt --check\"\nowner = \"@acme/rust\"\n\n[[pre-push]]\ncommand = \"cargo test\"\n",
+        );
+        let new = config(
+            "[settings]\nnested_runs = \"allow\"\n\n[[pre-commit]]\nname = \"fmt\"\ncommand = \"cargo fmt --check\"\nowner = \"@acme/platform\"\n\n[[pre-push]]\ncommand = \"cargo test --workspace\"\n",
+        );
+
+        let requests = review_requests(&old, &new).unwrap();
+
+        assert_eq!(
+            requests
+                .iter()
+                .map(|request| request.owners.clone())
+                .collect::<Vec<_>>(),
+            [
+                vec!["@acme/rust".to_string(), "@acme/platform".to_string()],
+                Vec::new(),
+            ]
+        );
+        assert!(review_requests(&new, &new).unwrap().is_empty());
+    }
+}
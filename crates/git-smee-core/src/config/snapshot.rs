@@ -0,0 +1,113 @@
+//! The config file as read at the start of a run.
+//!
+//! A run parses the config once and executes every phase from that parse, so a
+//! hook that edits `.git-smee.toml` (or a concurrent edit in an editor) cannot
+//! change which commands run halfway through. The raw bytes are kept so trust
+//! checks hash exactly what was parsed, and so the run can tell afterwards
+//! whether the file on disk no longer matches.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{Error, SmeeConfig};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSnapshot {
+    path: PathBuf,
+    contents: Vec<u8>,
+    config: SmeeConfig,
+}
+
+impl ConfigSnapshot {
+    /// Reads and validates the config at `path`, with the same checks as
+    /// [`SmeeConfig::from_toml`].
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        SmeeConfig::check_config_path(path)?;
+        let contents = fs::read(path).map_err(Error::ReadError)?;
+        let config = SmeeConfig::from_toml_bytes(&contents)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            contents,
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &SmeeConfig {
+        &self.config
+    }
+
+    pub fn into_config(self) -> SmeeConfig {
+        self.config
+    }
+
+    /// The bytes the config was parsed from.
+    pub fn contents(&self) -> &[u8] {
+        &self.contents
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file on disk differs from the snapshot, including when it
+    /// was removed or can no longer be read.
+    pub fn changed_on_disk(&self) -> bool {
+        fs::read(&self.path).map_or(true, |current| current != self.contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::assert;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::config::LifeCyclePhase;
+
+    const CONFIG: &str = "[[pre-commit]]\ncommand = 'cargo fmt --check'\n";
+
+    #[test]
+    fn given_config_file_when_snapshotting_then_parsed_config_and_bytes_are_kept() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".git-smee.toml");
+        fs::write(&path, CONFIG).unwrap();
+
+        let snapshot = ConfigSnapshot::read(&path).unwrap();
+
+        assert!(snapshot.contents() == CONFIG.as_bytes());
+        assert!(
+            snapshot.config().hooks[&LifeCyclePhase::PreCommit][0].command == "cargo fmt --check"
+        );
+        assert!(!snapshot.changed_on_disk());
+    }
+
+    #[test]
+    fn given_config_edited_after_snapshot_when_checking_then_change_is_detected_and_snapshot_kept()
+    {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".git-smee.toml");
+        fs::write(&path, CONFIG).unwrap();
+        let snapshot = ConfigSnapshot::read(&path).unwrap();
+
+        fs::write(&path, "[[pre-commit]]\ncommand = 'rm -rf target'\n").unwrap();
+
+        assert!(snapshot.changed_on_disk());
+        assert!(
+            snapshot.config().hooks[&LifeCyclePhase::PreCommit][0].command == "cargo fmt --check"
+        );
+    }
+
+    #[test]
+    fn given_config_removed_after_snapshot_when_checking_then_it_counts_as_changed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".git-smee.toml");
+        fs::write(&path, CONFIG).unwrap();
+        let snapshot = ConfigSnapshot::read(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+
+        assert!(snapshot.changed_on_disk());
+    }
+}
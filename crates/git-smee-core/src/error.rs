@@ -0,0 +1,581 @@
+//! Crate-wide error type for embedders.
+//!
+//! Each module keeps its own error enum. [`Error`] wraps all of them so callers
+//! can use one `Result` type and branch on [`Error::kind`] instead of matching
+//! every module's variants. [`Error::code`] narrows that down to a stable
+//! [`ErrorCode`] such as `SMEE010` for wrapper tooling and support docs.
+
+use thiserror::Error;
+
+#[cfg(feature = "executor")]
+use crate::executor;
+use crate::{config, installer, messages, platform, policy, repository, trust, webhook};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    Validation(#[from] config::ValidationError),
+    #[error(transparent)]
+    Installer(#[from] installer::Error),
+    #[cfg(feature = "executor")]
+    #[error(transparent)]
+    Executor(#[from] executor::Error),
+    #[error(transparent)]
+    Repository(#[from] repository::Error),
+    #[error(transparent)]
+    Platform(#[from] platform::Error),
+    #[error(transparent)]
+    Trust(#[from] trust::Error),
+    #[error(transparent)]
+    Policy(#[from] policy::Error),
+    #[error(transparent)]
+    Webhook(#[from] webhook::Error),
+    #[error(transparent)]
+    Messages(#[from] messages::Error),
+}
+
+/// Broad category of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The config file is missing, unreadable, or invalid.
+    Config,
+    /// Git could not be run or the repository could not be queried.
+    Repository,
+    /// Hook wrappers or config files could not be written.
+    Install,
+    /// A hook command failed, was stopped, or could not be started.
+    HookFailed,
+    /// The config has not been trusted, or the trust store is unusable.
+    Trust,
+    /// The machine policy rejected a command or could not be loaded.
+    Policy,
+    /// A local filesystem or runtime operation failed.
+    Io,
+}
+
+impl ErrorKind {
+    /// Stable lowercase name, suitable for logs and machine-readable output.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Repository => "repository",
+            Self::Install => "install",
+            Self::HookFailed => "hook-failed",
+            Self::Trust => "trust",
+            Self::Policy => "policy",
+            Self::Io => "io",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Stable identifier of a failure class, printed with CLI errors.
+///
+/// Codes are never reused or renumbered; new failure classes get new codes.
+/// They are grouped by tens: `00x` config, `01x` hook runs, `02x` installed
+/// hooks, `03x` repository, `04x` trust, `05x` policy, and `09x` I/O.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// SMEE001: the config file does not exist.
+    ConfigMissing,
+    /// SMEE002: the config file cannot be read or parsed.
+    ConfigUnreadable,
+    /// SMEE003: the config parses but is invalid, or names an unknown phase
+    /// or task.
+    ConfigInvalid,
+    /// SMEE010: a hook command failed.
+    HookFailed,
+    /// SMEE011: a hook command could not be started.
+    HookNotStarted,
+    /// SMEE012: a tool from a hook's `requires` is missing or too old.
+    MissingPrerequisite,
+    /// SMEE013: a hook command or phase ran out of time or was cancelled.
+    HookStopped,
+    /// SMEE014: hooks were triggered from inside another git-smee run.
+    NestedRun,
+    /// SMEE015: there is no interrupted run to resume.
+    RunNotFound,
+    /// SMEE020: installed hook wrappers differ from the config.
+    InstallDrift,
+    /// SMEE021: hook wrappers or config files could not be written.
+    InstallFailed,
+    /// SMEE022: an unmanaged hook file is in the way.
+    UnmanagedHook,
+    /// SMEE030: not inside a Git repository.
+    NotInRepository,
+    /// SMEE031: Git could not be run or the repository could not be queried.
+    RepositoryQueryFailed,
+    /// SMEE040: the config has not been trusted on this machine.
+    Untrusted,
+    /// SMEE041: the trust store cannot be located, read, or written.
+    TrustStoreFailed,
+    /// SMEE050: the machine policy does not allow a hook command.
+    PolicyViolation,
+    /// SMEE051: the machine policy file cannot be read or parsed.
+    PolicyUnreadable,
+    /// SMEE090: a local filesystem or runtime operation failed.
+    Io,
+}
+
+impl ErrorCode {
+    /// Every code, in numeric order.
+    pub const ALL: [Self; 19] = [
+        Self::ConfigMissing,
+        Self::ConfigUnreadable,
+        Self::ConfigInvalid,
+        Self::HookFailed,
+        Self::HookNotStarted,
+        Self::MissingPrerequisite,
+        Self::HookStopped,
+        Self::NestedRun,
+        Self::RunNotFound,
+        Self::InstallDrift,
+        Self::InstallFailed,
+        Self::UnmanagedHook,
+        Self::NotInRepository,
+        Self::RepositoryQueryFailed,
+        Self::Untrusted,
+        Self::TrustStoreFailed,
+        Self::PolicyViolation,
+        Self::PolicyUnreadable,
+        Self::Io,
+    ];
+
+    /// Parses a code such as `SMEE010`.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|known| known.as_str() == code)
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ConfigMissing => "SMEE001",
+            Self::ConfigUnreadable => "SMEE002",
+            Self::ConfigInvalid => "SMEE003",
+            Self::HookFailed => "SMEE010",
+            Self::HookNotStarted => "SMEE011",
+            Self::MissingPrerequisite => "SMEE012",
+            Self::HookStopped => "SMEE013",
+            Self::NestedRun => "SMEE014",
+            Self::RunNotFound => "SMEE015",
+            Self::InstallDrift => "SMEE020",
+            Self::InstallFailed => "SMEE021",
+            Self::UnmanagedHook => "SMEE022",
+            Self::NotInRepository => "SMEE030",
+            Self::RepositoryQueryFailed => "SMEE031",
+            Self::Untrusted => "SMEE040",
+            Self::TrustStoreFailed => "SMEE041",
+            Self::PolicyViolation => "SMEE050",
+            Self::PolicyUnreadable => "SMEE051",
+            Self::Io => "SMEE090",
+        }
+    }
+
+    /// Code of `error` when it is one of git-smee's error types, e.g. an
+    /// error returned through `Box<dyn Error>`.
+    pub fn of(error: &(dyn std::error::Error + 'static)) -> Option<Self> {
+        error
+            .downcast_ref::<Error>()
+            .map(Error::code)
+            .or_else(|| {
+                error
+                    .downcast_ref::<config::Error>()
+                    .map(config::Error::code)
+            })
+            .or_else(|| {
+                error
+                    .downcast_ref::<config::ValidationError>()
+                    .map(|_| Self::ConfigInvalid)
+            })
+            .or_else(|| {
+                error
+                    .downcast_ref::<installer::Error>()
+                    .map(installer::Error::code)
+            })
+            .or_else(|| {
+                error
+                    .downcast_ref::<repository::Error>()
+                    .map(repository::Error::code)
+            })
+            .or_else(|| {
+                error
+                    .downcast_ref::<platform::Error>()
+                    .map(platform::Error::code)
+            })
+            .or_else(|| error.downcast_ref::<trust::Error>().map(trust::Error::code))
+            .or_else(|| {
+                error
+                    .downcast_ref::<policy::Error>()
+                    .map(policy::Error::code)
+            })
+            .or_else(|| {
+                error
+                    .downcast_ref::<webhook::Error>()
+                    .map(webhook::Error::code)
+            })
+            .or_else(|| {
+                error
+                    .downcast_ref::<messages::Error>()
+                    .map(messages::Error::code)
+            })
+            .or_else(|| Self::of_executor_error(error))
+    }
+
+    #[cfg(feature = "executor")]
+    fn of_executor_error(error: &(dyn std::error::Error + 'static)) -> Option<Self> {
+        error
+            .downcast_ref::<executor::Error>()
+            .map(executor::Error::code)
+    }
+
+    #[cfg(not(feature = "executor"))]
+    fn of_executor_error(_error: &(dyn std::error::Error + 'static)) -> Option<Self> {
+        None
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl serde::Serialize for ErrorCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Error {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Config(error) => error.code(),
+            Self::Validation(_) => ErrorCode::ConfigInvalid,
+            Self::Installer(error) => error.code(),
+            #[cfg(feature = "executor")]
+            Self::Executor(error) => error.code(),
+            Self::Repository(error) => error.code(),
+            Self::Platform(error) => error.code(),
+            Self::Trust(error) => error.code(),
+            Self::Policy(error) => error.code(),
+            Self::Webhook(error) => error.code(),
+            Self::Messages(error) => error.code(),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Config(error) => error.kind(),
+            Self::Validation(_) => ErrorKind::Config,
+            Self::Installer(error) => error.kind(),
+            #[cfg(feature = "executor")]
+            Self::Executor(error) => error.kind(),
+            Self::Repository(error) => error.kind(),
+            Self::Platform(error) => error.kind(),
+            Self::Trust(error) => error.kind(),
+            Self::Policy(error) => error.kind(),
+            Self::Webhook(error) => error.kind(),
+            Self::Messages(error) => error.kind(),
+        }
+    }
+}
+
+impl config::Error {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::Config
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::MissingFile => ErrorCode::ConfigMissing,
+            Self::NotAFile
+            | Self::CanNotReadExtension
+            | Self::NotATomlFileExtension
+            | Self::ReadError(_)
+            | Self::ParseError(_) => ErrorCode::ConfigUnreadable,
+            Self::SerializeError(_) => ErrorCode::Io,
+            _ => ErrorCode::ConfigInvalid,
+        }
+    }
+}
+
+impl installer::Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::FailedToResolveHooksDirectory(_) | Self::InvalidRepositoryRoot { .. } => {
+                ErrorKind::Repository
+            }
+            Self::PlatformError(_) | Self::FailedToResolveCurrentExecutable(_) => ErrorKind::Io,
+            Self::PhaseNotConfigured(_) | Self::PhaseNeverInstalled(_) => ErrorKind::Config,
+            _ => ErrorKind::Install,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::FailedToResolveHooksDirectory(error)
+            | Self::FailedToSetHooksPath { source: error, .. } => error.code(),
+            Self::InvalidRepositoryRoot { .. } => ErrorCode::RepositoryQueryFailed,
+            Self::NoHooksPresent | Self::PhaseNotConfigured(_) | Self::PhaseNeverInstalled(_) => {
+                ErrorCode::ConfigInvalid
+            }
+            Self::RefusingToOverwriteUnmanagedHookFile { .. } | Self::ChainedHookExists { .. } => {
+                ErrorCode::UnmanagedHook
+            }
+            Self::PlatformError(_) | Self::FailedToResolveCurrentExecutable(_) => ErrorCode::Io,
+            _ => ErrorCode::InstallFailed,
+        }
+    }
+}
+
+#[cfg(feature = "executor")]
+impl executor::Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::ExecutionFailed(_)
+            | Self::ExecutionTerminatedBySignal
+            | Self::ExecutionStopped { .. }
+            | Self::NoCommandDefined
+            | Self::CommandSpawnFailed { .. }
+            | Self::MissingPrerequisite { .. }
+            | Self::SimulatedFailure(_)
+            | Self::PhaseBudgetExceeded { .. } => ErrorKind::HookFailed,
+            Self::NoHooksConfigured(_) | Self::UnknownTask(_) | Self::NestedRun { .. } => {
+                ErrorKind::Config
+            }
+            Self::RepositoryQueryFailed(error) => error.kind(),
+            Self::PolicyCheckFailed(error) => error.kind(),
+            Self::RunNotFound(_) | Self::NoRunToResume => ErrorKind::Config,
+            Self::ThreadPoolBuildFailed(_)
+            | Self::AuditLogWriteFailed { .. }
+            | Self::AnalyticsWriteFailed { .. }
+            | Self::MetricsWriteFailed { .. }
+            | Self::RunStateAccessFailed { .. }
+            | Self::StatePruneFailed { .. } => ErrorKind::Io,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::ExecutionFailed(_)
+            | Self::ExecutionTerminatedBySignal
+            | Self::SimulatedFailure(_) => ErrorCode::HookFailed,
+            Self::NoCommandDefined | Self::CommandSpawnFailed { .. } => ErrorCode::HookNotStarted,
+            Self::MissingPrerequisite { .. } => ErrorCode::MissingPrerequisite,
+            Self::ExecutionStopped { .. } | Self::PhaseBudgetExceeded { .. } => {
+                ErrorCode::HookStopped
+            }
+            Self::NestedRun { .. } => ErrorCode::NestedRun,
+            Self::NoHooksConfigured(_) | Self::UnknownTask(_) => ErrorCode::ConfigInvalid,
+            Self::RunNotFound(_) | Self::NoRunToResume => ErrorCode::RunNotFound,
+            Self::RepositoryQueryFailed(error) => error.code(),
+            Self::PolicyCheckFailed(error) => error.code(),
+            Self::ThreadPoolBuildFailed(_)
+            | Self::AuditLogWriteFailed { .. }
+            | Self::AnalyticsWriteFailed { .. }
+            | Self::MetricsWriteFailed { .. }
+            | Self::RunStateAccessFailed { .. }
+            | Self::StatePruneFailed { .. } => ErrorCode::Io,
+        }
+    }
+}
+
+impl repository::Error {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::Repository
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::NotInGitRepository => ErrorCode::NotInRepository,
+            _ => ErrorCode::RepositoryQueryFailed,
+        }
+    }
+}
+
+impl platform::Error {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::Io
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::Io
+    }
+}
+
+impl trust::Error {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::Trust
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Untrusted { .. } => ErrorCode::Untrusted,
+            _ => ErrorCode::TrustStoreFailed,
+        }
+    }
+}
+
+impl policy::Error {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::Policy
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Violation { .. } => ErrorCode::PolicyViolation,
+            _ => ErrorCode::PolicyUnreadable,
+        }
+    }
+}
+
+impl webhook::Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::InvalidUrl(_) | Self::InvalidHeader(_) => ErrorKind::Config,
+            _ => ErrorKind::Io,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidUrl(_) | Self::InvalidHeader(_) => ErrorCode::ConfigInvalid,
+            _ => ErrorCode::Io,
+        }
+    }
+}
+
+impl messages::Error {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::Config
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::UnknownCode { .. } => ErrorCode::ConfigInvalid,
+            _ => ErrorCode::ConfigUnreadable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::assert;
+
+    use super::*;
+    #[cfg(feature = "executor")]
+    use crate::config::LifeCyclePhase;
+
+    #[cfg(feature = "executor")]
+    fn fails_with<E: Into<Error>>(error: E) -> Result<(), Error> {
+        Err(error.into())
+    }
+
+    #[cfg(feature = "executor")]
+    #[test]
+    fn given_module_errors_when_converting_then_kind_reflects_the_category() {
+        let cases = [
+            (fails_with(config::Error::MissingFile), ErrorKind::Config),
+            (
+                fails_with(executor::Error::ExecutionFailed(2)),
+                ErrorKind::HookFailed,
+            ),
+            (
+                fails_with(executor::Error::NoHooksConfigured(
+                    LifeCyclePhase::PreCommit,
+                )),
+                ErrorKind::Config,
+            ),
+            (
+                fails_with(executor::Error::RepositoryQueryFailed(
+                    repository::Error::NotInGitRepository,
+                )),
+                ErrorKind::Repository,
+            ),
+            (
+                fails_with(installer::Error::NoHooksPresent),
+                ErrorKind::Install,
+            ),
+            (
+                fails_with(repository::Error::NotInGitRepository),
+                ErrorKind::Repository,
+            ),
+            (
+                fails_with(trust::Error::NoTrustStoreLocation),
+                ErrorKind::Trust,
+            ),
+        ];
+
+        for (result, expected) in cases {
+            let error = result.unwrap_err();
+            assert!(error.kind() == expected, "{error}");
+        }
+    }
+
+    #[cfg(feature = "executor")]
+    #[test]
+    fn given_module_errors_when_coding_then_codes_are_stable() {
+        let cases = [
+            (fails_with(config::Error::MissingFile), "SMEE001"),
+            (fails_with(executor::Error::ExecutionFailed(2)), "SMEE010"),
+            (
+                fails_with(executor::Error::RepositoryQueryFailed(
+                    repository::Error::NotInGitRepository,
+                )),
+                "SMEE030",
+            ),
+            (
+                fails_with(installer::Error::RefusingToOverwriteUnmanagedHookFile {
+                    path: ".git/hooks/pre-commit".to_string(),
+                }),
+                "SMEE022",
+            ),
+            (fails_with(trust::Error::NoTrustStoreLocation), "SMEE041"),
+        ];
+
+        for (result, expected) in cases {
+            let error = result.unwrap_err();
+            assert!(error.code().as_str() == expected, "{error}");
+        }
+    }
+
+    #[cfg(feature = "executor")]
+    #[test]
+    fn given_boxed_module_error_when_looking_up_code_then_it_is_found() {
+        let boxed: Box<dyn std::error::Error> = Box::new(config::Error::MissingFile);
+        let wrapped: Box<dyn std::error::Error> =
+            Box::new(Error::from(executor::Error::NoRunToResume));
+        let other: Box<dyn std::error::Error> = "doctor found problems".into();
+
+        assert!(ErrorCode::of(boxed.as_ref()) == Some(ErrorCode::ConfigMissing));
+        assert!(ErrorCode::of(wrapped.as_ref()) == Some(ErrorCode::RunNotFound));
+        assert!(ErrorCode::of(other.as_ref()).is_none());
+    }
+
+    #[test]
+    fn given_code_strings_when_parsing_then_every_code_round_trips() {
+        for code in ErrorCode::ALL {
+            assert!(ErrorCode::from_code(code.as_str()) == Some(code));
+        }
+        assert!(ErrorCode::from_code("SMEE999").is_none());
+        assert!(ErrorCode::from_code("smee010").is_none());
+    }
+
+    #[cfg(feature = "executor")]
+    #[test]
+    fn given_wrapped_error_when_displaying_then_inner_message_is_kept() {
+        let error = Error::from(executor::Error::ExecutionFailed(3));
+
+        assert!(error.to_string() == "Hook execution failed with exit code 3");
+        assert!(error.kind().to_string() == "hook-failed");
+    }
+}
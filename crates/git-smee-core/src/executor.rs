@@ -1,24 +1,91 @@
 use thiserror::Error;
 
-mod redaction;
+mod analytics;
+mod artifacts;
+mod audit;
+mod dedup;
+mod events;
+mod explain;
+mod isolation;
+mod journal;
+mod locks;
+mod metrics;
+mod network;
+mod output;
+mod pool;
+pub(crate) mod probe;
+mod report;
+mod resources;
 mod runner;
+mod sandbox;
 mod scheduler;
+mod snapshot;
+mod state;
+mod store;
 mod summary;
-
-use crate::{SmeeConfig, config::LifeCyclePhase, platform::Platform};
-
+#[cfg(unix)]
+mod terminal;
+mod termination;
+
+use crate::{
+    SmeeConfig,
+    config::{LifeCyclePhase, NestedRuns},
+    platform::Platform,
+};
+
+pub use crate::config::hook_label;
+pub use analytics::{
+    ANALYTICS_GIT_PATH, ANALYTICS_KEY, AnalyticsRecord, analytics_log, reset_analytics,
+};
+pub use artifacts::{ARTIFACTS_DIR_ENV, ARTIFACTS_GIT_PATH, KEPT_ARTIFACT_RUNS};
+pub use audit::{AUDIT_KEY, AUDIT_LOG_GIT_PATH};
+pub use events::{EVENTS_SOCKET_ENV, Event, EventSink};
+pub use explain::{CommandExplanation, PhaseExplanation, Verdict, explain_phase};
+pub use isolation::ISOLATED_TMP_GIT_PATH;
+pub use journal::{RUNS_GIT_PATH, RunJournal};
+pub use locks::MUTEX_LOCK_GIT_PATH;
+pub use metrics::{DURATION_BUCKETS, HookStatistics, HookStats, METRICS_FILE_KEY};
+use pool::default_executor_config;
+pub use pool::{DEFAULT_THREAD_NAME_PREFIX, ExecutorConfig};
+pub use resources::ResourceUsage;
+pub use runner::ACTIVE_RUN_ENV;
 use runner::{CommandRunner, PlatformCommandRunner};
-use scheduler::{run_hooks_with_runner, run_hooks_with_runner_with_summary};
+pub use sandbox::{SANDBOX_ENV, SANDBOX_KEY};
+use scheduler::{RunContext, run_hooks_in_context};
+pub use snapshot::{EnvironmentSnapshot, environment_snapshot};
+pub use state::{
+    DEFAULT_STATE_EXPIRY, DEFAULT_STATE_MAX_SIZE, PruneLimits, PrunedState, STATE_EXPIRE_KEY,
+    STATE_GIT_PATH, STATE_MAX_SIZE_KEY, prune_state, prune_state_if_due,
+};
+pub use store::{LoadedRecords, RecordLog};
 pub use summary::{CommandPhase, CommandRun, HookRunSummary};
+pub use termination::{
+    DEFAULT_GRACE_PERIOD, StopReason, StopSignal, request_cancellation, request_child_stop,
+};
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Hook execution failed with exit code {0}")]
     ExecutionFailed(i32),
     #[error("Hook execution was terminated by a signal")]
     ExecutionTerminatedBySignal,
+    #[error("Hook execution {reason} and was stopped by {signal}")]
+    ExecutionStopped {
+        reason: StopReason,
+        signal: StopSignal,
+    },
     #[error("No hooks configured for lifecycle phase: {0}")]
     NoHooksConfigured(LifeCyclePhase),
+    #[error("No task named '{0}' in the config")]
+    UnknownTask(String),
+    #[error(
+        "{phase} hooks were triggered from inside the running {active} hooks; set settings.nested_runs = \"skip\" or \"allow\" to permit this"
+    )]
+    NestedRun {
+        phase: LifeCyclePhase,
+        active: String,
+    },
     #[error("No command defined")]
     NoCommandDefined,
     #[error("Failed to spawn hook command '{command}' via '{shell}': {source}")]
@@ -27,6 +94,47 @@ pub enum Error {
         shell: String,
         source: std::io::Error,
     },
+    #[error("Failed to query the git repository: {0}")]
+    RepositoryQueryFailed(#[from] crate::repository::Error),
+    #[error("Failed to build the parallel hook thread pool: {0}")]
+    ThreadPoolBuildFailed(#[from] rayon::ThreadPoolBuildError),
+    #[error(transparent)]
+    PolicyCheckFailed(#[from] crate::policy::Error),
+    #[error("Failed to append to the audit log at {path}: {source}")]
+    AuditLogWriteFailed {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Failed to write the analytics store at {path}: {source}")]
+    AnalyticsWriteFailed {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("The phase time budget of {max_duration} was used up before all hooks ran")]
+    PhaseBudgetExceeded { max_duration: String },
+    #[error("Failed to write the metrics file at {path}: {source}")]
+    MetricsWriteFailed {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Missing prerequisite {requirement}: {reason}")]
+    MissingPrerequisite { requirement: String, reason: String },
+    #[error("Hook '{0}' failed (simulated by --simulate-failure)")]
+    SimulatedFailure(String),
+    #[error("No run found with ID {0}")]
+    RunNotFound(String),
+    #[error("No interrupted run to resume")]
+    NoRunToResume,
+    #[error("Failed to access the run state at {path}: {source}")]
+    RunStateAccessFailed {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Failed to prune the git-smee state at {path}: {source}")]
+    StatePruneFailed {
+        path: String,
+        source: std::io::Error,
+    },
 }
 
 pub fn execute_hook(smee_config: &SmeeConfig, phase: LifeCyclePhase) -> Result<(), Error> {
@@ -56,17 +164,71 @@ pub fn execute_hook_with_args_and_stdin(
     )
 }
 
+/// Runs the hooks configured for `phase` and returns a per-command summary.
+///
+/// When the phase is switched off through the repository's git config (see
+/// [`crate::toggle`]), nothing is executed and the summary reports the toggle
+/// that disabled it. When `smee.audit` is set, each executed command is
+/// appended to the audit log at [`AUDIT_LOG_GIT_PATH`], and with
+/// `[settings.report]` the run's result is posted to the report URL.
 pub fn execute_hook_with_summary(
     smee_config: &SmeeConfig,
     phase: LifeCyclePhase,
     hook_args: &[String],
     stdin_payload: Option<&[u8]>,
+) -> Result<HookRunSummary, Error> {
+    execute_hook_with_executor_config(
+        smee_config,
+        phase,
+        hook_args,
+        stdin_payload,
+        default_executor_config(),
+    )
+}
+
+/// Like [`execute_hook_with_summary`], but runs parallel commands on the
+/// thread pool owned by `executor_config` instead of the shared default.
+pub fn execute_hook_with_executor_config(
+    smee_config: &SmeeConfig,
+    phase: LifeCyclePhase,
+    hook_args: &[String],
+    stdin_payload: Option<&[u8]>,
+    executor_config: &ExecutorConfig,
+) -> Result<HookRunSummary, Error> {
+    execute_hook_with_journal(
+        smee_config,
+        phase,
+        hook_args,
+        stdin_payload,
+        executor_config,
+        None,
+    )
+}
+
+/// Like [`execute_hook_with_executor_config`], but records each passed command
+/// in `journal` and skips commands the journal says already passed, so an
+/// interrupted run can be resumed.
+pub fn execute_hook_with_journal(
+    smee_config: &SmeeConfig,
+    phase: LifeCyclePhase,
+    hook_args: &[String],
+    stdin_payload: Option<&[u8]>,
+    executor_config: &ExecutorConfig,
+    journal: Option<&RunJournal>,
 ) -> Result<HookRunSummary, Error> {
     let platform = Platform::current();
     let runner = PlatformCommandRunner {
         platform: &platform,
     };
-    execute_hook_with_runner_and_summary(smee_config, phase, &runner, hook_args, stdin_payload)
+    execute_hook_in_repository(
+        smee_config,
+        phase,
+        &runner,
+        hook_args,
+        stdin_payload,
+        executor_config,
+        journal,
+    )
 }
 
 pub fn execute_hook_with_platform(
@@ -96,9 +258,233 @@ pub fn execute_hook_with_platform_and_args_and_stdin(
     let runner = PlatformCommandRunner {
         platform: &platform,
     };
-    execute_hook_with_runner(smee_config, phase, &runner, hook_args, stdin_payload)
+    let summary = execute_hook_in_repository(
+        smee_config,
+        phase,
+        &runner,
+        hook_args,
+        stdin_payload,
+        default_executor_config(),
+        None,
+    )?;
+    match summary.error() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Applies the repository-local git config and the machine policy around a
+/// run: skips disabled phases and runs nested in another git-smee run, refuses
+/// commands the policy does not allow, and appends to the audit log when it is
+/// enabled.
+fn execute_hook_in_repository<R: CommandRunner>(
+    smee_config: &SmeeConfig,
+    phase: LifeCyclePhase,
+    runner: &R,
+    hook_args: &[String],
+    stdin_payload: Option<&[u8]>,
+    executor_config: &ExecutorConfig,
+    journal: Option<&RunJournal>,
+) -> Result<HookRunSummary, Error> {
+    let current_dir = std::env::current_dir().map_err(crate::repository::Error::from)?;
+    let hooks = smee_config.phase_entries(phase);
+    let total_configured = hooks.as_ref().map_or(0, Vec::len);
+    if let Some(disabled_by) = crate::toggle::disabled_by(&current_dir, phase)? {
+        return Ok(HookRunSummary::disabled(total_configured, disabled_by));
+    }
+    if let Some(active) = active_run() {
+        match smee_config.settings.nested_runs {
+            NestedRuns::Skip => return Ok(HookRunSummary::nested(total_configured, active)),
+            NestedRuns::Fail => return Err(Error::NestedRun { phase, active }),
+            NestedRuns::Allow => {}
+        }
+    }
+    if let (Some(policy), Some(hooks)) =
+        (crate::policy::CommandPolicy::load_machine_policy()?, &hooks)
+    {
+        policy.check_hooks(hooks)?;
+    }
+    let recorders = RunRecorders::from_git_config(&current_dir)?;
+    let summary = execute_hook_with_runner_and_executor_config(
+        smee_config,
+        phase,
+        runner,
+        hook_args,
+        stdin_payload,
+        executor_config,
+        journal,
+    )?;
+    let hooks = hooks.as_deref().unwrap_or_default();
+    recorders.record(
+        phase.as_str(),
+        hooks,
+        &summary,
+        hook_args.len(),
+        &current_dir,
+    )?;
+    if let Some(report) = &smee_config.settings.report {
+        report::send_report(report, &report::run_report(phase.as_str(), hooks, &summary));
+    }
+    Ok(summary)
+}
+
+/// Run that the current process was started from, read from
+/// [`ACTIVE_RUN_ENV`].
+fn active_run() -> Option<String> {
+    std::env::var(ACTIVE_RUN_ENV)
+        .ok()
+        .filter(|active| !active.is_empty())
+}
+
+/// Runs the `[tasks]` entry called `task` and returns a per-command summary.
+///
+/// Tasks use the same executor as phases (parallel commands, env, filters,
+/// the machine policy, and the audit log, where they are recorded as
+/// `task:<name>`), but the phase toggles do not apply to them: a task only
+/// runs when asked for by name.
+pub fn execute_task(
+    smee_config: &SmeeConfig,
+    task: &str,
+    hook_args: &[String],
+    executor_config: &ExecutorConfig,
+) -> Result<HookRunSummary, Error> {
+    let platform = Platform::current();
+    let runner = PlatformCommandRunner {
+        platform: &platform,
+    };
+    let hooks = smee_config
+        .task_entries(task)
+        .ok_or_else(|| Error::UnknownTask(task.to_string()))?;
+    if let Some(policy) = crate::policy::CommandPolicy::load_machine_policy()? {
+        policy.check_hooks(&hooks)?;
+    }
+    let current_dir = std::env::current_dir().map_err(crate::repository::Error::from)?;
+    let recorders = RunRecorders::from_git_config(&current_dir)?;
+    let summary = execute_task_with_runner(
+        &hooks,
+        task,
+        &smee_config.settings,
+        &runner,
+        hook_args,
+        executor_config,
+    )?;
+    recorders.record(
+        &format!("task:{task}"),
+        &hooks,
+        &summary,
+        hook_args.len(),
+        &current_dir,
+    )?;
+    if let Some(report) = &smee_config.settings.report {
+        report::send_report(
+            report,
+            &report::run_report(&format!("task:{task}"), &hooks, &summary),
+        );
+    }
+    Ok(summary)
+}
+
+fn execute_task_with_runner<R: CommandRunner>(
+    hooks: &[crate::config::HookDefinition],
+    task: &str,
+    settings: &crate::config::Settings,
+    runner: &R,
+    hook_args: &[String],
+    executor_config: &ExecutorConfig,
+) -> Result<HookRunSummary, Error> {
+    let run_name = format!("task-{task}");
+    let mut context = run_context(&run_name, hooks, settings, executor_config)?;
+    attach_artifacts_root(&mut context, &run_name, hooks, None)?;
+    Ok(run_hooks_in_context(
+        hooks, runner, hook_args, None, &context,
+    ))
+}
+
+/// Points `context` at the run's artifacts directory when one of `hooks`
+/// keeps artifacts. Phases of a journaled run share its run ID; other runs
+/// get a fresh one.
+fn attach_artifacts_root(
+    context: &mut RunContext<'_>,
+    run_name: &str,
+    hooks: &[crate::config::HookDefinition],
+    run_id: Option<String>,
+) -> Result<(), Error> {
+    if artifacts::any_hook_keeps_artifacts(hooks) {
+        let current_dir = std::env::current_dir().map_err(crate::repository::Error::from)?;
+        let artifacts_root = crate::repository::resolve_git_path(&current_dir, ARTIFACTS_GIT_PATH)?;
+        context.artifacts_root = Some(artifacts::run_artifacts_root(
+            &artifacts_root,
+            &run_id.unwrap_or_else(journal::new_run_id),
+            run_name,
+        ));
+    }
+    Ok(())
+}
+
+/// Where a finished run is recorded, read from the git config before it
+/// starts.
+struct RunRecorders {
+    audit_log: Option<RecordLog>,
+    analytics_log: Option<RecordLog>,
+    metrics_file: Option<std::path::PathBuf>,
+}
+
+impl RunRecorders {
+    fn from_git_config(repository_root: &std::path::Path) -> Result<Self, Error> {
+        let metrics_file = metrics::metrics_file_path(repository_root)?;
+        Ok(Self {
+            audit_log: audit::audit_log(repository_root)?,
+            analytics_log: analytics::enabled_analytics_log(
+                repository_root,
+                metrics_file.is_some(),
+            )?,
+            metrics_file,
+        })
+    }
+
+    /// Appends the run to the audit log and the analytics store when they are
+    /// on and, when `smee.metricsFile` is set, rewrites the metrics file from
+    /// the analytics store.
+    fn record(
+        &self,
+        run_name: &str,
+        hooks: &[crate::config::HookDefinition],
+        summary: &HookRunSummary,
+        hook_arg_count: usize,
+        cwd: &std::path::Path,
+    ) -> Result<(), Error> {
+        if let Some(audit_log) = &self.audit_log {
+            let records = audit::audit_records(run_name, hooks, summary, hook_arg_count, cwd);
+            audit_log
+                .append(&records)
+                .map_err(|source| Error::AuditLogWriteFailed {
+                    path: audit_log.path().display().to_string(),
+                    source,
+                })?;
+        }
+        let Some(analytics_log) = &self.analytics_log else {
+            return Ok(());
+        };
+        let records = analytics::analytics_records(run_name, hooks, summary);
+        analytics_log
+            .append(&records)
+            .map_err(|source| Error::AnalyticsWriteFailed {
+                path: analytics_log.path().display().to_string(),
+                source,
+            })?;
+        if let Some(metrics_file) = &self.metrics_file {
+            metrics::write_metrics_file(analytics_log.path(), metrics_file).map_err(|source| {
+                Error::MetricsWriteFailed {
+                    path: metrics_file.display().to_string(),
+                    source,
+                }
+            })?;
+        }
+        Ok(())
+    }
 }
 
+#[cfg(test)]
 fn execute_hook_with_runner<R: CommandRunner>(
     smee_config: &SmeeConfig,
     phase: LifeCyclePhase,
@@ -106,12 +492,15 @@ fn execute_hook_with_runner<R: CommandRunner>(
     hook_args: &[String],
     stdin_payload: Option<&[u8]>,
 ) -> Result<(), Error> {
-    match smee_config.hooks.get(&phase) {
-        None => Err(Error::NoHooksConfigured(phase)),
-        Some(hooks) => run_hooks_with_runner(hooks, runner, hook_args, stdin_payload),
+    let summary =
+        execute_hook_with_runner_and_summary(smee_config, phase, runner, hook_args, stdin_payload)?;
+    match summary.error() {
+        Some(error) => Err(error),
+        None => Ok(()),
     }
 }
 
+#[cfg(test)]
 fn execute_hook_with_runner_and_summary<R: CommandRunner>(
     smee_config: &SmeeConfig,
     phase: LifeCyclePhase,
@@ -119,15 +508,154 @@ fn execute_hook_with_runner_and_summary<R: CommandRunner>(
     hook_args: &[String],
     stdin_payload: Option<&[u8]>,
 ) -> Result<HookRunSummary, Error> {
-    match smee_config.hooks.get(&phase) {
+    execute_hook_with_runner_and_executor_config(
+        smee_config,
+        phase,
+        runner,
+        hook_args,
+        stdin_payload,
+        default_executor_config(),
+        None,
+    )
+}
+
+fn execute_hook_with_runner_and_executor_config<R: CommandRunner>(
+    smee_config: &SmeeConfig,
+    phase: LifeCyclePhase,
+    runner: &R,
+    hook_args: &[String],
+    stdin_payload: Option<&[u8]>,
+    executor_config: &ExecutorConfig,
+    journal: Option<&RunJournal>,
+) -> Result<HookRunSummary, Error> {
+    match smee_config.phase_entries(phase) {
         None => Err(Error::NoHooksConfigured(phase)),
-        Some(hooks) => Ok(run_hooks_with_runner_with_summary(
-            hooks,
-            runner,
-            hook_args,
-            stdin_payload,
-        )),
+        Some(hooks) => {
+            let mut context = run_context(
+                phase.as_str(),
+                &hooks,
+                &smee_config.settings,
+                executor_config,
+            )?;
+            context.journal = journal.map(|journal| (journal, phase));
+            attach_artifacts_root(
+                &mut context,
+                phase.as_str(),
+                &hooks,
+                journal.map(RunJournal::run_id),
+            )?;
+            context.budget = smee_config
+                .settings
+                .phases
+                .get(&phase)
+                .and_then(|settings| {
+                    Some(scheduler::PhaseBudget {
+                        deadline: std::time::Instant::now() + settings.max_duration?.0,
+                        max_duration: settings.max_duration?.0,
+                        over_budget: settings.over_budget,
+                    })
+                });
+            if phase == LifeCyclePhase::PrePush
+                && let Some(stdin_payload) = stdin_payload
+                && hooks.iter().any(|hook| {
+                    hook.command
+                        .contains(crate::config::CHANGED_FILES_PLACEHOLDER)
+                })
+            {
+                let repository_root =
+                    std::env::current_dir().map_err(crate::repository::Error::from)?;
+                context.changed_files = Some(crate::repository::pushed_files(
+                    &repository_root,
+                    stdin_payload,
+                )?);
+            }
+            Ok(run_hooks_in_context(
+                &hooks,
+                runner,
+                hook_args,
+                stdin_payload,
+                &context,
+            ))
+        }
+    }
+}
+
+fn run_context<'a>(
+    run_name: &str,
+    hooks: &[crate::config::HookDefinition],
+    settings: &crate::config::Settings,
+    executor_config: &'a ExecutorConfig,
+) -> Result<RunContext<'a>, Error> {
+    let mut context = RunContext {
+        groups: locks::ConcurrencyGroups::new(settings.groups.clone()),
+        show_commands: executor_config.show_commands(),
+        sequential_only: !executor_config.stdout_is_terminal() && !settings.parallel_without_tty,
+        windows_shell: settings.windows_shell,
+        events: executor_config.event_sink(),
+        simulated_failures: executor_config.simulated_failures(),
+        opt_out: executor_config.opt_out(),
+        dedup: executor_config.command_memo(),
+        run_name: Some(run_name.to_string()),
+        ..Default::default()
+    };
+    if running_in_ci() {
+        for (config_index, hook) in hooks.iter().enumerate() {
+            if hook.timeout == Some(crate::config::HookTimeout::Unbounded) {
+                eprintln!(
+                    "git-smee: warning: {run_name} command {} has timeout = \"none\" and can hang this CI job; give it a timeout for CI",
+                    hook_label(config_index, hook)
+                );
+            }
+        }
     }
+    if !context.sequential_only && hooks.iter().any(|hook| hook.parallel_execution_allowed) {
+        context.thread_pool = Some(executor_config.thread_pool()?);
+    }
+    if isolation::any_hook_isolates_tmp(hooks) {
+        let current_dir = std::env::current_dir().map_err(crate::repository::Error::from)?;
+        let smee_tmp_root =
+            crate::repository::resolve_git_path(&current_dir, ISOLATED_TMP_GIT_PATH)?;
+        context.tmp_root = Some(isolation::phase_tmp_root(&smee_tmp_root, run_name));
+    }
+    if hooks.iter().any(|hook| hook.mutex.is_some()) {
+        let current_dir = std::env::current_dir().map_err(crate::repository::Error::from)?;
+        let lock_dir = crate::repository::resolve_git_path(&current_dir, MUTEX_LOCK_GIT_PATH)?;
+        context.mutexes = locks::HookMutexes::new(Some(lock_dir));
+    }
+    let current_dir = std::env::current_dir().map_err(crate::repository::Error::from)?;
+    if sandbox::sandbox_requested(&current_dir)? {
+        match sandbox::Sandbox::detect(&current_dir) {
+            Ok(sandbox) => context.sandbox = Some(sandbox),
+            Err(reason) => eprintln!(
+                "git-smee: warning: sandboxing was requested but {reason}; running {run_name} hooks without a sandbox"
+            ),
+        }
+    }
+    if hooks.iter().any(|hook| hook.network == Some(false)) {
+        context.network_block = Some(match network::NetworkBlock::detect() {
+            Ok(network_block) => network_block,
+            Err(reason) => {
+                if context.sandbox.is_none() {
+                    eprintln!(
+                        "git-smee: warning: {run_name} hooks set network = false but {reason}; only their proxy env vars block network access"
+                    );
+                }
+                network::NetworkBlock::ProxyEnv
+            }
+        });
+    }
+    Ok(context)
+}
+
+/// Whether the run is part of a CI job, judged by the `CI` env var that CI
+/// services set.
+fn running_in_ci() -> bool {
+    std::env::var("CI").is_ok_and(|value| {
+        !matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "" | "0" | "false" | "no" | "off"
+        )
+    })
 }
 
 #[cfg(test)]
@@ -139,26 +667,36 @@ mod tests {
         io,
         process::Command,
         sync::{Arc, Barrier, Mutex},
-        time::Duration,
+        time::{Duration, SystemTime},
     };
 
     use assert2::assert;
     use proptest::prelude::*;
 
-    use crate::{config::HookDefinition, test_support::process_state_lock};
+    use crate::{
+        config::{CalendarDate, HookDefinition, HookTimeout, OverBudget, Severity, WindowsShell},
+        redaction::{redact_command, redact_secret_values},
+        test_support::process_state_lock,
+    };
 
-    use super::redaction::redact_command;
+    use super::output::copy_lines;
     use super::runner::{
-        apply_hook_arg_env, is_hook_arg_env_key, windows_cmd_quote_hook_arg, windows_command_script,
+        CommandOptions, apply_hook_arg_env, describe_command, is_hook_arg_env_key,
+        powershell_script, windows_command_script,
+    };
+    use super::scheduler::{
+        execute_command, expand_changed_files, run_hooks_with_runner,
+        run_hooks_with_runner_with_summary,
     };
-    use super::scheduler::execute_command;
     use super::summary::{CommandOutcome, CommandRun};
+    use super::termination::{CommandCompletion, CommandExit, TerminationPolicy};
     use super::*;
 
     enum PlannedResult {
         Exit(Option<i32>),
         SpawnError(io::ErrorKind),
         Barrier(Arc<Barrier>, Option<i32>),
+        Stopped(StopReason, StopSignal),
     }
 
     #[derive(Default)]
@@ -168,6 +706,10 @@ mod tests {
         calls: Vec<String>,
         hook_args_calls: Vec<Vec<String>>,
         stdin_calls: Vec<Option<Vec<u8>>>,
+        termination_calls: Vec<TerminationPolicy>,
+        tmp_dir_calls: Vec<Option<std::path::PathBuf>>,
+        thread_names: Vec<Option<String>>,
+        output_labels: HashMap<String, Option<String>>,
     }
 
     struct FakeRunner {
@@ -210,6 +752,22 @@ mod tests {
         fn stdin_calls(&self) -> Vec<Option<Vec<u8>>> {
             self.state.lock().unwrap().stdin_calls.clone()
         }
+
+        fn termination_calls(&self) -> Vec<TerminationPolicy> {
+            self.state.lock().unwrap().termination_calls.clone()
+        }
+
+        fn tmp_dir_calls(&self) -> Vec<Option<std::path::PathBuf>> {
+            self.state.lock().unwrap().tmp_dir_calls.clone()
+        }
+
+        fn thread_names(&self) -> Vec<Option<String>> {
+            self.state.lock().unwrap().thread_names.clone()
+        }
+
+        fn output_label(&self, command: &str) -> Option<String> {
+            self.state.lock().unwrap().output_labels[command].clone()
+        }
     }
 
     impl CommandRunner for FakeRunner {
@@ -218,12 +776,24 @@ mod tests {
             command: &str,
             hook_args: &[String],
             stdin_payload: Option<&[u8]>,
-        ) -> Result<Option<i32>, io::Error> {
+            options: &CommandOptions,
+        ) -> Result<CommandCompletion, io::Error> {
             let outcome = {
                 let mut state = self.state.lock().unwrap();
                 state.calls.push(command.to_string());
                 state.hook_args_calls.push(hook_args.to_vec());
                 state.stdin_calls.push(stdin_payload.map(Vec::from));
+                state.termination_calls.push(options.termination);
+                state.tmp_dir_calls.push(options.tmp_dir.clone());
+                state
+                    .output_labels
+                    .insert(command.to_string(), options.output_label.clone());
+                state
+                    .thread_names
+                    .push(std::thread::current().name().map(str::to_string));
+                if let Some(tmp_dir) = &options.tmp_dir {
+                    assert!(tmp_dir.is_dir(), "isolated TMPDIR must exist while running");
+                }
                 state
                     .outcomes_by_command
                     .get_mut(command)
@@ -232,16 +802,19 @@ mod tests {
                     .unwrap_or_else(|| panic!("no fake outcome configured for command '{command}'"))
             };
             match outcome {
-                PlannedResult::Exit(code) => Ok(code),
+                PlannedResult::Exit(code) => Ok(CommandExit::Exited(code).into()),
                 PlannedResult::SpawnError(kind) => Err(io::Error::new(kind, "spawn failed")),
                 PlannedResult::Barrier(barrier, code) => {
                     barrier.wait();
-                    Ok(code)
+                    Ok(CommandExit::Exited(code).into())
+                }
+                PlannedResult::Stopped(reason, signal) => {
+                    Ok(CommandExit::Stopped { reason, signal }.into())
                 }
             }
         }
 
-        fn shell_display(&self) -> &'static str {
+        fn shell_display(&self, _windows_shell: WindowsShell) -> &'static str {
             self.shell_display
         }
     }
@@ -250,6 +823,10 @@ mod tests {
     fn given_empty_smee_config_when_executing_hook_then_no_hooks_configured_error() {
         let config = SmeeConfig {
             hooks: std::collections::HashMap::new(),
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
         };
 
         let result = execute_hook(&config, LifeCyclePhase::PreCommit);
@@ -259,6 +836,78 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn given_unknown_task_when_executing_task_then_unknown_task_error() {
+        let config = SmeeConfig::default();
+
+        let result = execute_task(&config, "lint-all", &[], default_executor_config());
+        assert!(matches!(result, Err(Error::UnknownTask(task)) if task == "lint-all"));
+    }
+
+    #[test]
+    fn given_task_with_parallel_entries_when_executing_then_all_commands_receive_args() {
+        let hooks = vec![
+            crate::config::HookDefinition {
+                command: "cargo-clippy".to_string(),
+                parallel_execution_allowed: true,
+                ..Default::default()
+            },
+            crate::config::HookDefinition {
+                command: "cargo-fmt-check".to_string(),
+                parallel_execution_allowed: true,
+                ..Default::default()
+            },
+        ];
+        let runner = FakeRunner::with_default_outcomes(vec![
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(1)),
+        ]);
+        let hook_args = vec!["--verbose".to_string()];
+
+        let summary = execute_task_with_runner(
+            &hooks,
+            "lint-all",
+            &Default::default(),
+            &runner,
+            &hook_args,
+            default_executor_config(),
+        )
+        .unwrap();
+
+        let mut calls = runner.calls();
+        calls.sort();
+        assert_eq!(calls, vec!["cargo-clippy", "cargo-fmt-check"]);
+        assert_eq!(runner.hook_args_calls(), vec![hook_args.clone(), hook_args]);
+        assert_eq!(summary.attempted_count(), 2);
+        assert_eq!(summary.failed_count(), 1);
+        assert!(summary.text_lines("task lint-all")[0] == "Hook summary: task lint-all");
+    }
+
+    #[test]
+    fn given_phase_referencing_task_when_executing_then_task_commands_run_in_its_place() {
+        let config: SmeeConfig = toml::from_str(
+            "[[pre-push]]\ntask = 'lint-all'\n\n[[pre-push]]\ncommand = 'run-tests'\n\n[[tasks.lint-all]]\ncommand = 'run-clippy'\n\n[[tasks.lint-all]]\ncommand = 'run-fmt'\n",
+        )
+        .unwrap();
+        let runner = FakeRunner::with_default_outcomes(vec![
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(0)),
+        ]);
+
+        let summary = execute_hook_with_runner_and_summary(
+            &config,
+            LifeCyclePhase::PrePush,
+            &runner,
+            &[],
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(runner.calls(), vec!["run-clippy", "run-fmt", "run-tests"]);
+        assert_eq!(summary.attempted_count(), 3);
+    }
+
     #[test]
     fn given_single_hook_when_executing_then_command_runs() {
         let mut hooks_map = std::collections::HashMap::new();
@@ -267,182 +916,2212 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "run-pre-commit".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
+            }],
+        );
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+
+        let result =
+            execute_hook_with_runner(&config, LifeCyclePhase::PreCommit, &runner, &[], None);
+        assert!(result.is_ok());
+        assert_eq!(runner.calls(), vec!["run-pre-commit"]);
+    }
+
+    #[test]
+    fn given_hook_args_when_executing_then_all_commands_receive_forwarded_args() {
+        let mut hooks_map = std::collections::HashMap::new();
+        hooks_map.insert(
+            LifeCyclePhase::CommitMsg,
+            vec![crate::config::HookDefinition {
+                command: "check-commit-message".to_string(),
+                parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
-        let config = SmeeConfig { hooks: hooks_map };
-        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+        let hook_args = vec!["COMMIT_EDITMSG".to_string(), "message".to_string()];
+
+        let result = execute_hook_with_runner(
+            &config,
+            LifeCyclePhase::CommitMsg,
+            &runner,
+            &hook_args,
+            None,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(runner.calls(), vec!["check-commit-message"]);
+        assert_eq!(runner.hook_args_calls(), vec![hook_args]);
+    }
+
+    #[test]
+    fn given_windows_command_script_when_building_then_command_can_read_batch_parameters() {
+        let script = windows_command_script("if \"%1\"==\"alpha\" exit /b 0");
+
+        assert_eq!(script, "@echo off\r\nif \"%1\"==\"alpha\" exit /b 0\r\n");
+    }
+
+    #[test]
+    fn given_powershell_script_when_building_then_native_exit_codes_are_passed_on() {
+        let script = powershell_script("cargo fmt --check");
+
+        assert_eq!(
+            script,
+            "$ErrorActionPreference = 'Stop'\r\ncargo fmt --check\r\nif ($LASTEXITCODE) { exit $LASTEXITCODE }\r\n"
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn given_powershell_windows_shell_when_running_then_hook_args_are_in_args() {
+        let platform = Platform::Windows;
+        let runner = PlatformCommandRunner {
+            platform: &platform,
+        };
+        let options = CommandOptions {
+            windows_shell: WindowsShell::PowerShell,
+            ..Default::default()
+        };
+        let command = "if ($args[0] -eq 'alpha beta') { exit 0 } else { exit 3 }";
+
+        let matching = runner
+            .run(command, &["alpha beta".to_string()], None, &options)
+            .unwrap();
+        let other = runner
+            .run(command, &["gamma".to_string()], None, &options)
+            .unwrap();
+
+        assert!(matches!(matching.exit, CommandExit::Exited(Some(0))));
+        assert!(matches!(other.exit, CommandExit::Exited(Some(3))));
+        assert_eq!(
+            runner.shell_display(WindowsShell::PowerShell),
+            "powershell.exe -File"
+        );
+    }
+
+    #[test]
+    fn given_summary_success_when_rendering_then_counts_phases_and_durations() {
+        let hooks = vec![
+            HookDefinition {
+                command: "seq-ok".to_string(),
+                parallel_execution_allowed: false,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "parallel-ok".to_string(),
+                parallel_execution_allowed: true,
+                ..Default::default()
+            },
+        ];
+        let runner = FakeRunner::with_command_outcomes(vec![
+            ("seq-ok", vec![PlannedResult::Exit(Some(0))]),
+            ("parallel-ok", vec![PlannedResult::Exit(Some(0))]),
+        ]);
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert_eq!(summary.total_configured(), 2);
+        assert_eq!(summary.attempted_count(), 2);
+        assert_eq!(summary.skipped_count(), 0);
+        assert_eq!(summary.failed_count(), 0);
+        assert_eq!(summary.phase_attempted_count(CommandPhase::Sequential), 1);
+        assert_eq!(summary.phase_failed_count(CommandPhase::Sequential), 0);
+        assert_eq!(summary.phase_attempted_count(CommandPhase::Parallel), 1);
+        assert_eq!(summary.phase_failed_count(CommandPhase::Parallel), 0);
+        assert!(summary.first_failure().is_none());
+        assert!(summary.error().is_none());
+
+        let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
+        assert!(lines.contains("Hook summary: pre-commit"));
+        assert!(lines.contains("total: 2 attempted, 0 skipped, 0 failed in"));
+        assert!(lines.contains("sequential: 1 attempted, 0 failed in"));
+        assert!(lines.contains("parallel: 1 attempted, 0 failed in"));
+        assert!(lines.contains("sequential command #1: ok in"));
+        assert!(lines.contains("parallel command #1: ok in"));
+    }
+
+    #[test]
+    fn given_success_codes_when_command_exits_with_one_then_it_passes_and_summary_shows_the_code() {
+        let hook = |command: &str| HookDefinition {
+            command: command.to_string(),
+            success_codes: vec![0, 2],
+            ..Default::default()
+        };
+        let hooks = vec![hook("fixer"), hook("linter")];
+        let runner = FakeRunner::with_command_outcomes(vec![
+            ("fixer", vec![PlannedResult::Exit(Some(2))]),
+            ("linter", vec![PlannedResult::Exit(Some(1))]),
+        ]);
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert_eq!(summary.failed_count(), 1);
+        assert_eq!(summary.command_runs()[0].exit_code(), Some(2));
+        assert!(summary.command_runs()[0].outcome.kind() == "accepted_exit");
+        assert!(matches!(summary.error(), Some(Error::ExecutionFailed(1))));
+        let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
+        assert!(lines.contains("sequential command #1: ok (exit code 2 accepted) in"));
+        assert!(lines.contains("sequential command #2: failed with code 1 in"));
+    }
+
+    #[test]
+    fn given_summary_sequential_failure_when_rendering_then_reports_skipped_and_first_failure() {
+        let hooks = vec![
+            HookDefinition {
+                command: "seq-fail".to_string(),
+                parallel_execution_allowed: false,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "seq-skipped".to_string(),
+                parallel_execution_allowed: false,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "parallel-skipped".to_string(),
+                parallel_execution_allowed: true,
+                ..Default::default()
+            },
+        ];
+        let runner = FakeRunner::with_command_outcomes(vec![(
+            "seq-fail",
+            vec![PlannedResult::Exit(Some(9))],
+        )]);
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert_eq!(summary.total_configured(), 3);
+        assert_eq!(summary.attempted_count(), 1);
+        assert_eq!(summary.skipped_count(), 2);
+        assert_eq!(summary.failed_count(), 1);
+        assert_eq!(summary.phase_attempted_count(CommandPhase::Sequential), 1);
+        assert_eq!(summary.phase_failed_count(CommandPhase::Sequential), 1);
+        assert_eq!(summary.phase_attempted_count(CommandPhase::Parallel), 0);
+        assert!(matches!(summary.error(), Some(Error::ExecutionFailed(9))));
+
+        let first_failure = summary.first_failure().expect("missing first failure");
+        assert_eq!(first_failure.status_display(), "failed with code 9");
+        assert_eq!(
+            first_failure.failure_display(),
+            "sequential command #1 exited with code 9"
+        );
+    }
+
+    #[test]
+    fn given_summary_parallel_failures_when_rendering_then_first_failure_is_phase_ordered() {
+        let summary = HookRunSummary {
+            total_configured: 2,
+            total_duration: Duration::ZERO,
+            sequential_duration: Duration::ZERO,
+            parallel_duration: Duration::ZERO,
+            command_runs: vec![
+                CommandRun {
+                    phase: CommandPhase::Parallel,
+                    index: 1,
+                    config_index: 1,
+                    started_at: SystemTime::UNIX_EPOCH,
+                    duration: Duration::ZERO,
+                    resources: None,
+                    output_truncated_bytes: 0,
+                    partial_output: Vec::new(),
+                    severity: Severity::Error,
+                    enforce_after: None,
+                    description_url: None,
+                    outcome: CommandOutcome::Exit(7),
+                },
+                CommandRun {
+                    phase: CommandPhase::Parallel,
+                    index: 0,
+                    config_index: 0,
+                    started_at: SystemTime::UNIX_EPOCH,
+                    duration: Duration::ZERO,
+                    resources: None,
+                    output_truncated_bytes: 0,
+                    partial_output: Vec::new(),
+                    severity: Severity::Error,
+                    enforce_after: None,
+                    description_url: None,
+                    outcome: CommandOutcome::Exit(5),
+                },
+            ],
+            disabled_by: None,
+            nested_in: None,
+        };
+
+        assert_eq!(summary.failed_count(), 2);
+        assert_eq!(summary.phase_failed_count(CommandPhase::Parallel), 2);
+        let first_failure = summary.first_failure().expect("missing first failure");
+        assert_eq!(
+            first_failure.failure_display(),
+            "parallel command #1 exited with code 5"
+        );
+        let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
+        assert!(lines.contains("first failure: parallel command #1 exited with code 5"));
+    }
+
+    #[test]
+    fn given_failing_warning_hook_when_running_then_later_hooks_run_and_run_succeeds() {
+        let runner = FakeRunner::with_command_outcomes(vec![
+            ("new-lint", vec![PlannedResult::Exit(Some(2))]),
+            ("cargo test", vec![PlannedResult::Exit(Some(0))]),
+        ]);
+        let hooks = vec![
+            HookDefinition {
+                command: "new-lint".to_string(),
+                severity: Severity::Warning,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "cargo test".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(runner.calls() == vec!["new-lint".to_string(), "cargo test".to_string()]);
+        assert!(summary.error().is_none());
+        assert!(summary.failed_count() == 0);
+        assert!(summary.warning_count() == 1);
+        let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
+        assert!(lines.contains("total: 2 attempted, 0 skipped, 0 failed, 1 warning in"));
+        assert!(lines.contains("sequential command #1: failed with code 2 (warning) in"));
+        assert!(lines.contains(
+            "warning: sequential command #1 exited with code 2 (severity = \"warning\", not blocking)"
+        ));
+        assert!(!lines.contains("first failure"));
+    }
+
+    #[test]
+    fn given_command_passed_in_earlier_phase_when_running_next_phase_then_it_is_deduplicated() {
+        let fmt = HookDefinition {
+            command: "cargo fmt --check".to_string(),
+            ..Default::default()
+        };
+        let tests = HookDefinition {
+            command: "cargo test".to_string(),
+            ..Default::default()
+        };
+        let memo = super::dedup::CommandMemo::default();
+        let context = |run_name: &str| RunContext {
+            dedup: Some(&memo),
+            run_name: Some(run_name.to_string()),
+            ..Default::default()
+        };
+        let runner = FakeRunner::with_default_outcomes(
+            (0..4).map(|_| PlannedResult::Exit(Some(0))).collect(),
+        );
+
+        let pre_commit = run_hooks_in_context(
+            &[fmt.clone(), fmt.clone()],
+            &runner,
+            &[],
+            None,
+            &context("pre-commit"),
+        );
+        let pre_push = run_hooks_in_context(
+            &[fmt.clone(), tests],
+            &runner,
+            &[],
+            None,
+            &context("pre-push"),
+        );
+        let with_other_args = run_hooks_in_context(
+            &[fmt],
+            &runner,
+            &["origin".to_string()],
+            None,
+            &context("post-merge"),
+        );
+
+        assert!(pre_commit.deduplicated_count() == 0);
+        assert!(pre_push.deduplicated_count() == 1);
+        assert!(pre_push.attempted_count() == 1);
+        assert!(pre_push.error().is_none());
+        let lines = pre_push.text_lines(LifeCyclePhase::PrePush).join("\n");
+        assert!(lines.contains("total: 1 attempted, 1 skipped (1 deduplicated), 0 failed"));
+        assert!(
+            lines.contains("sequential command #1: deduplicated (already passed in pre-commit)")
+        );
+        assert!(with_other_args.deduplicated_count() == 0);
+        assert_eq!(
+            runner.calls(),
+            vec![
+                "cargo fmt --check".to_string(),
+                "cargo fmt --check".to_string(),
+                "cargo test".to_string(),
+                "cargo fmt --check".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_failed_command_when_running_next_phase_then_it_is_not_deduplicated() {
+        let hooks = [HookDefinition {
+            command: "cargo test".to_string(),
+            severity: Severity::Warning,
+            ..Default::default()
+        }];
+        let memo = super::dedup::CommandMemo::default();
+        let context = |run_name: &str| RunContext {
+            dedup: Some(&memo),
+            run_name: Some(run_name.to_string()),
+            ..Default::default()
+        };
+        let runner = FakeRunner::with_default_outcomes(
+            (0..2).map(|_| PlannedResult::Exit(Some(1))).collect(),
+        );
+
+        run_hooks_in_context(&hooks, &runner, &[], None, &context("pre-commit"));
+        let pre_push = run_hooks_in_context(&hooks, &runner, &[], None, &context("pre-push"));
+
+        assert!(pre_push.deduplicated_count() == 0);
+        assert!(runner.calls().len() == 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_changed_files_when_running_then_placeholder_is_replaced_with_quoted_paths() {
+        let hooks = vec![HookDefinition {
+            command: "eslint {changed_files}".to_string(),
+            ..Default::default()
+        }];
+        let context = RunContext {
+            changed_files: Some(vec!["src/a b.js".into(), "lib/c.js".into()]),
+            ..Default::default()
+        };
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert!(summary.error().is_none());
+        assert_eq!(
+            runner.calls(),
+            vec!["eslint 'src/a b.js' lib/c.js".to_string()]
+        );
+    }
+
+    #[test]
+    fn given_powershell_windows_shell_when_expanding_changed_files_then_paths_are_single_quoted() {
+        let files = vec![
+            std::path::PathBuf::from("src/$env:USER's 100%.ps1"),
+            "lib/c.js".into(),
+        ];
+
+        let expand = |no_shell, windows_shell| {
+            expand_changed_files(
+                "lint {changed_files}",
+                &files,
+                no_shell,
+                Platform::Windows,
+                windows_shell,
+            )
+        };
+
+        assert_eq!(
+            expand(false, WindowsShell::PowerShell),
+            "lint 'src/$env:USER''s 100%.ps1' 'lib/c.js'"
+        );
+        assert_eq!(
+            expand(false, WindowsShell::Pwsh),
+            expand(false, WindowsShell::PowerShell)
+        );
+        assert_eq!(
+            expand(false, WindowsShell::Cmd),
+            "lint \"src/$env:USER's 100%%.ps1\" lib/c.js"
+        );
+        assert_eq!(
+            expand(true, WindowsShell::PowerShell),
+            "lint 'src/$env:USER'\"'\"'s 100%.ps1' lib/c.js"
+        );
+    }
+
+    #[test]
+    fn given_no_changed_files_when_running_then_placeholder_commands_are_skipped() {
+        let hooks = vec![
+            HookDefinition {
+                command: "eslint {changed_files}".to_string(),
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "cargo test".to_string(),
+                ..Default::default()
+            },
+        ];
+        let runner = FakeRunner::with_default_outcomes(
+            (0..2).map(|_| PlannedResult::Exit(Some(0))).collect(),
+        );
+
+        for changed_files in [Some(Vec::new()), None] {
+            let context = RunContext {
+                changed_files,
+                ..Default::default()
+            };
+            let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+            assert!(summary.error().is_none());
+            assert!(summary.command_runs()[0].outcome.kind() == "no_changed_files");
+            let lines = summary.text_lines(LifeCyclePhase::PrePush).join("\n");
+            assert!(lines.contains("sequential command #1: skipped (no changed files)"));
+        }
+        assert_eq!(
+            runner.calls(),
+            vec!["cargo test".to_string(), "cargo test".to_string()]
+        );
+    }
+
+    #[test]
+    fn given_opt_out_list_when_running_then_listed_commands_are_skipped() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("opt-out.toml");
+        std::fs::write(&path, "hooks = ['clippy', 'pre-push:fmt']\n").unwrap();
+        let opt_out = crate::opt_out::OptOutList::load(&path).unwrap().unwrap();
+        let hooks = vec![
+            HookDefinition {
+                name: Some("fmt".to_string()),
+                command: "cargo fmt --check".to_string(),
+                ..Default::default()
+            },
+            HookDefinition {
+                name: Some("clippy".to_string()),
+                command: "cargo clippy".to_string(),
+                ..Default::default()
+            },
+        ];
+        let context = RunContext {
+            opt_out: Some(&opt_out),
+            run_name: Some("pre-commit".to_string()),
+            ..Default::default()
+        };
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert_eq!(runner.calls(), vec!["cargo fmt --check".to_string()]);
+        assert!(summary.error().is_none());
+        assert!(summary.opted_out_count() == 1);
+        assert!(summary.opt_out_file() == Some(path.display().to_string().as_str()));
+        let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
+        assert!(lines.contains("total: 1 attempted, 1 skipped (1 opted out), 0 failed"));
+        assert!(lines.contains(&format!(
+            "sequential command #2: opted out ({})",
+            path.display()
+        )));
+    }
+
+    #[test]
+    fn given_used_up_budget_when_running_then_remaining_commands_are_skipped_with_a_warning() {
+        let hooks = vec![
+            HookDefinition {
+                command: "cargo fmt --check".to_string(),
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "cargo clippy".to_string(),
+                parallel_execution_allowed: true,
+                ..Default::default()
+            },
+        ];
+        let context = RunContext {
+            budget: Some(scheduler::PhaseBudget {
+                deadline: std::time::Instant::now(),
+                max_duration: Duration::from_secs(60),
+                over_budget: OverBudget::Skip,
+            }),
+            ..Default::default()
+        };
+        let runner = FakeRunner::with_default_outcomes(Vec::new());
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert!(runner.calls().is_empty());
+        assert!(summary.error().is_none());
+        assert!(summary.over_budget_count() == 2);
+        let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
+        assert!(lines.contains("total: 0 attempted, 2 skipped, 0 failed"));
+        assert!(lines.contains(
+            "warning: phase time budget of 60s used up; not started: sequential command #1, parallel command #1"
+        ));
+    }
+
+    #[test]
+    fn given_used_up_budget_and_over_budget_fail_when_running_then_phase_fails() {
+        let hooks = vec![
+            HookDefinition {
+                command: "cargo fmt --check".to_string(),
+                severity: Severity::Warning,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "cargo test".to_string(),
+                ..Default::default()
+            },
+        ];
+        let context = RunContext {
+            budget: Some(scheduler::PhaseBudget {
+                deadline: std::time::Instant::now(),
+                max_duration: Duration::from_secs(60),
+                over_budget: OverBudget::Fail,
+            }),
+            ..Default::default()
+        };
+        let runner = FakeRunner::with_default_outcomes(Vec::new());
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert!(runner.calls().is_empty());
+        assert!(matches!(
+            summary.error(),
+            Some(Error::PhaseBudgetExceeded { .. })
+        ));
+        assert!(summary.command_runs().len() == 1);
+        assert!(summary.command_runs()[0].outcome.kind() == "budget_exceeded");
+    }
+
+    #[test]
+    fn given_simulated_failure_when_running_then_command_fails_without_running() {
+        let hooks = vec![
+            HookDefinition {
+                name: Some("fmt".to_string()),
+                command: "cargo fmt --check".to_string(),
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "cargo test".to_string(),
+                severity: Severity::Warning,
+                ..Default::default()
+            },
+        ];
+        let simulated_failures = ["hook-2".to_string()];
+        let context = RunContext {
+            simulated_failures: &simulated_failures,
+            ..Default::default()
+        };
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert_eq!(runner.calls(), vec!["cargo fmt --check".to_string()]);
+        assert!(summary.error().is_none());
+        let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
+        assert!(lines.contains("sequential command #2: simulated failure (warning) in"));
+
+        let simulated_failures = ["fmt".to_string()];
+        let context = RunContext {
+            simulated_failures: &simulated_failures,
+            ..Default::default()
+        };
+        let summary = run_hooks_in_context(&hooks[..1], &runner, &[], None, &context);
+
+        assert!(matches!(
+            summary.error(),
+            Some(Error::SimulatedFailure(label)) if label == "fmt"
+        ));
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn given_hook_in_soft_launch_window_when_it_fails_then_it_is_a_dated_warning() {
+        let hooks = vec![HookDefinition {
+            command: "new-check".to_string(),
+            enforce_after: CalendarDate::new(2025, 7, 1),
+            ..Default::default()
+        }];
+        let before = RunContext {
+            today: CalendarDate::new(2025, 6, 15),
+            ..Default::default()
+        };
+        let after = RunContext {
+            today: CalendarDate::new(2025, 7, 1),
+            ..Default::default()
+        };
+        let runner = FakeRunner::with_default_outcomes(vec![
+            PlannedResult::Exit(Some(1)),
+            PlannedResult::Exit(Some(1)),
+        ]);
+
+        let soft = run_hooks_in_context(&hooks, &runner, &[], None, &before);
+        let enforced = run_hooks_in_context(&hooks, &runner, &[], None, &after);
+
+        assert!(soft.error().is_none());
+        let lines = soft.text_lines(LifeCyclePhase::PreCommit).join("\n");
+        assert!(lines.contains("failed with code 1 (warning until 2025-07-01)"));
+        assert!(lines.contains("(enforce_after = \"2025-07-01\", not blocking)"));
+        assert!(matches!(enforced.error(), Some(Error::ExecutionFailed(1))));
+    }
+
+    #[test]
+    fn given_bwrap_sandbox_when_wrapping_then_repo_is_writable_and_command_env_and_dir_are_kept() {
+        let sandbox = super::sandbox::Sandbox::bwrap_for_test(
+            std::path::Path::new("/work/repo"),
+            vec!["/home/dev/.cargo/bin".into()],
+        );
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "cargo test"])
+            .env("GIT_SMEE_ACTIVE", "pre-commit")
+            .current_dir("/work/repo/crates");
+
+        let wrapped = sandbox.wrap(&command, false);
+
+        assert!(wrapped.get_program() == "/usr/bin/bwrap");
+        let args: Vec<_> = wrapped
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let joined = args.join(" ");
+        assert!(args[0] == "--die-with-parent");
+        assert!(joined.contains("--ro-bind-try /usr /usr"));
+        assert!(joined.contains("--ro-bind-try /home/dev/.cargo/bin /home/dev/.cargo/bin"));
+        assert!(joined.contains("--tmpfs /tmp"));
+        assert!(joined.contains("--bind /work/repo /work/repo"));
+        assert!(!joined.contains("--unshare-net"));
+        assert!(joined.ends_with("--chdir /work/repo/crates -- sh -c cargo test"));
+        assert!(
+            wrapped.get_envs().any(
+                |(key, value)| key == "GIT_SMEE_ACTIVE" && value == Some("pre-commit".as_ref())
+            )
+        );
+        assert!(wrapped.get_current_dir() == Some(std::path::Path::new("/work/repo/crates")));
+    }
+
+    #[test]
+    fn given_sandbox_exec_sandbox_when_wrapping_then_profile_limits_writes_and_home_reads() {
+        let sandbox = super::sandbox::Sandbox::sandbox_exec_for_test(
+            std::path::Path::new("/Users/dev/my \"repo\""),
+            std::path::Path::new("/Users/dev"),
+        );
+
+        let wrapped = sandbox.wrap(&Command::new("sh"), true);
+
+        let args: Vec<_> = wrapped
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert!(args[0] == "-p");
+        assert!(args[2] == "sh");
+        let profile = &args[1];
+        assert!(profile.contains("(deny file-write*)"));
+        assert!(profile.contains(r#"(allow file-write* (subpath "/Users/dev/my \"repo\"")"#));
+        assert!(profile.contains(r#"(deny file-read* (subpath "/Users/dev"))"#));
+        assert!(profile.contains(r#"(subpath "/Users/dev/bin")"#));
+        assert!(profile.contains("(deny network-outbound (remote ip))"));
+    }
+
+    #[test]
+    fn given_offline_bwrap_sandbox_when_wrapping_then_network_is_unshared() {
+        let sandbox =
+            super::sandbox::Sandbox::bwrap_for_test(std::path::Path::new("/work/repo"), vec![]);
+
+        let wrapped = sandbox.wrap(&Command::new("sh"), true);
+
+        assert!(wrapped.get_args().any(|arg| arg == "--unshare-net"));
+    }
+
+    #[test]
+    fn given_unshare_network_block_when_applying_then_command_runs_in_new_network_namespace() {
+        let network_block = super::network::NetworkBlock::Unshare {
+            program: "/usr/bin/unshare".into(),
+        };
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "cargo test"])
+            .env("GIT_SMEE_ACTIVE", "pre-commit")
+            .current_dir("/work/repo");
+
+        let blocked = network_block.apply(command);
+
+        assert!(blocked.get_program() == "/usr/bin/unshare");
+        let args: Vec<_> = blocked
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            args,
+            ["--net", "--map-root-user", "--", "sh", "-c", "cargo test"]
+        );
+        assert!(
+            blocked.get_envs().any(
+                |(key, value)| key == "GIT_SMEE_ACTIVE" && value == Some("pre-commit".as_ref())
+            )
+        );
+        assert!(blocked.get_current_dir() == Some(std::path::Path::new("/work/repo")));
+    }
+
+    #[test]
+    fn given_proxy_env_network_block_when_applying_then_proxies_point_nowhere() {
+        let mut command = Command::new("sh");
+        command.env("NO_PROXY", "example.com");
+
+        let blocked = super::network::NetworkBlock::ProxyEnv.apply(command);
+
+        assert!(blocked.get_program() == "sh");
+        let envs: HashMap<_, _> = blocked.get_envs().collect();
+        assert!(envs[std::ffi::OsStr::new("https_proxy")] == Some("http://127.0.0.1:9".as_ref()));
+        assert!(envs[std::ffi::OsStr::new("HTTP_PROXY")] == Some("http://127.0.0.1:9".as_ref()));
+        assert!(envs[std::ffi::OsStr::new("NO_PROXY")].is_none());
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn given_event_sink_when_running_then_phase_and_command_progress_is_streamed_as_json_lines() {
+        let buffer = SharedBuffer::default();
+        let events = EventSink::from_writer(buffer.clone());
+        let context = RunContext {
+            run_name: Some("pre-commit".to_string()),
+            events: Some(&events),
+            ..Default::default()
+        };
+        let hooks = vec![
+            HookDefinition {
+                name: Some("fmt".to_string()),
+                command: "cargo fmt --check".to_string(),
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "cargo test".to_string(),
+                ..Default::default()
+            },
+        ];
+        let runner = FakeRunner::with_default_outcomes(vec![
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(1)),
+        ]);
+
+        run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let kinds: Vec<_> = events.iter().map(|event| event["event"].clone()).collect();
+        assert!(
+            kinds
+                == [
+                    "phase_started",
+                    "command_started",
+                    "command_finished",
+                    "command_started",
+                    "command_finished",
+                    "phase_finished"
+                ]
+        );
+        assert!(events[0]["run"] == "pre-commit");
+        assert!(events[0]["commands"] == 2);
+        assert!(events[1]["label"] == "fmt");
+        assert!(events[1]["command"] == redact_command("cargo fmt --check"));
+        assert!(events[2]["outcome"] == "success");
+        assert!(events[4]["label"] == "hook-2");
+        assert!(events[4]["outcome"] == "exit");
+        assert!(events[4]["blocking"] == true);
+        assert!(events[5]["success"] == false);
+    }
+
+    #[test]
+    fn given_closed_event_connection_when_emitting_then_events_are_dropped_without_error() {
+        struct Closed;
+        impl io::Write for Closed {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let events = EventSink::from_writer(Closed);
+
+        events.emit(&Event::RunFinished { success: true });
+        events.emit(&Event::RunFinished { success: true });
+    }
+
+    #[test]
+    fn given_journal_with_passed_command_when_resuming_then_only_remaining_commands_run() {
+        let runs_dir = tempfile::tempdir().unwrap();
+        let state_path = runs_dir.path().join("run.json");
+        let journal = RunJournal::at_path(state_path.clone(), &[LifeCyclePhase::PreCommit]);
+        let hooks = vec![
+            HookDefinition {
+                command: "fmt".to_string(),
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "test".to_string(),
+                ..Default::default()
+            },
+        ];
+        let context = RunContext {
+            journal: Some((&journal, LifeCyclePhase::PreCommit)),
+            ..Default::default()
+        };
+        let runner = FakeRunner::with_command_outcomes(vec![
+            ("fmt", vec![PlannedResult::Exit(Some(0))]),
+            (
+                "test",
+                vec![PlannedResult::Exit(Some(1)), PlannedResult::Exit(Some(0))],
+            ),
+        ]);
+
+        let interrupted = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+        let resumed = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert!(matches!(
+            interrupted.error(),
+            Some(Error::ExecutionFailed(1))
+        ));
+        assert!(resumed.error().is_none());
+        assert!(resumed.attempted_count() == 1);
+        assert!(resumed.skipped_count() == 1);
+        assert!(runner.calls() == vec!["fmt", "test", "test"]);
+        assert!(journal.has_passed(LifeCyclePhase::PreCommit, 1, "test"));
+        assert!(!journal.has_passed(LifeCyclePhase::PreCommit, 1, "edited test"));
+        assert!(
+            std::fs::read_to_string(&state_path)
+                .unwrap()
+                .contains("\"test\"")
+        );
+        journal.finish().unwrap();
+        assert!(!state_path.exists());
+    }
+
+    struct OverlapRunner {
+        active: Mutex<HashMap<String, usize>>,
+        max_active: Mutex<HashMap<String, usize>>,
+    }
+
+    impl CommandRunner for OverlapRunner {
+        fn run(
+            &self,
+            command: &str,
+            _hook_args: &[String],
+            _stdin_payload: Option<&[u8]>,
+            _options: &CommandOptions,
+        ) -> Result<CommandCompletion, io::Error> {
+            let group = command.split(':').next().unwrap().to_string();
+            {
+                let mut active = self.active.lock().unwrap();
+                let count = active.entry(group.clone()).or_default();
+                *count += 1;
+                let mut max_active = self.max_active.lock().unwrap();
+                let max = max_active.entry(group.clone()).or_default();
+                *max = (*max).max(*count);
+            }
+            std::thread::sleep(Duration::from_millis(30));
+            *self.active.lock().unwrap().get_mut(&group).unwrap() -= 1;
+            Ok(CommandExit::Exited(Some(0)).into())
+        }
+
+        fn shell_display(&self, _windows_shell: WindowsShell) -> &'static str {
+            "test-shell -c"
+        }
+    }
+
+    #[test]
+    fn given_parallel_hooks_sharing_a_mutex_when_running_then_they_never_overlap() {
+        let lock_dir = tempfile::tempdir().unwrap();
+        let hook = |command: &str, mutex: Option<&str>| HookDefinition {
+            command: command.to_string(),
+            parallel_execution_allowed: true,
+            mutex: mutex.map(str::to_string),
+            ..Default::default()
+        };
+        let hooks = vec![
+            hook("cargo:build", Some("cargo-target")),
+            hook("cargo:clippy", Some("cargo-target")),
+            hook("cargo:test", Some("cargo-target")),
+            hook("free:a", None),
+            hook("free:b", None),
+        ];
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(5)
+            .build()
+            .unwrap();
+        let context = RunContext {
+            thread_pool: Some(&thread_pool),
+            mutexes: locks::HookMutexes::new(Some(lock_dir.path().to_path_buf())),
+            ..Default::default()
+        };
+        let runner = OverlapRunner {
+            active: Mutex::default(),
+            max_active: Mutex::default(),
+        };
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert!(summary.error().is_none());
+        assert!(runner.max_active.lock().unwrap()["cargo"] == 1);
+        assert!(lock_dir.path().join("cargo-target.lock").exists());
+    }
+
+    #[test]
+    fn given_concurrency_groups_when_running_parallel_hooks_then_each_group_stays_within_its_limit()
+    {
+        let hook = |command: &str, group: &str| HookDefinition {
+            command: command.to_string(),
+            parallel_execution_allowed: true,
+            group: Some(group.to_string()),
+            ..Default::default()
+        };
+        let hooks = vec![
+            hook("cpu:build", "cpu"),
+            hook("cpu:test", "cpu"),
+            hook("io:a", "io"),
+            hook("io:b", "io"),
+            hook("io:c", "io"),
+            hook("io:d", "io"),
+        ];
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(6)
+            .build()
+            .unwrap();
+        let context = RunContext {
+            thread_pool: Some(&thread_pool),
+            groups: locks::ConcurrencyGroups::new(
+                [("cpu".to_string(), 1), ("io".to_string(), 2)].into(),
+            ),
+            ..Default::default()
+        };
+        let runner = OverlapRunner {
+            active: Mutex::default(),
+            max_active: Mutex::default(),
+        };
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert!(summary.error().is_none());
+        assert_eq!(summary.attempted_count(), 6);
+        let max_active = runner.max_active.lock().unwrap();
+        assert_eq!(max_active["cpu"], 1);
+        assert_eq!(max_active["io"], 2);
+    }
+
+    #[test]
+    fn given_run_ids_when_validating_then_only_generated_ids_are_accepted() {
+        assert!(journal::is_valid_run_id("19a2b3c4d5e-1f2a"));
+        assert!(!journal::is_valid_run_id("../../config"));
+        assert!(!journal::is_valid_run_id(""));
+    }
+
+    #[test]
+    fn given_requirements_when_probing_then_missing_tools_and_old_versions_are_reported() {
+        let requirement = |text: &str| text.parse::<crate::config::Requirement>().unwrap();
+
+        assert!(probe::unmet_reason(&requirement("git")).is_none());
+        assert!(probe::unmet_reason(&requirement("git >= 1.0")).is_none());
+        assert!(
+            probe::unmet_reason(&requirement("git-smee-no-such-tool"))
+                == Some("not found on PATH".to_string())
+        );
+        let Some(reason) = probe::unmet_reason(&requirement("git >= 999")) else {
+            panic!("git 999 should not be installed");
+        };
+        assert!(reason.starts_with("found "));
+    }
+
+    #[test]
+    fn given_required_tools_when_taking_environment_snapshot_then_each_tool_is_listed_once() {
+        let hooks = vec![
+            HookDefinition {
+                requires: vec!["git >= 1".parse().unwrap()],
+                ..Default::default()
+            },
+            HookDefinition {
+                requires: vec![
+                    "git".parse().unwrap(),
+                    "git-smee-no-such-tool".parse().unwrap(),
+                ],
+                ..Default::default()
+            },
+        ];
+
+        let lines = environment_snapshot(&hooks).text_lines();
+
+        assert_eq!(lines[0], "Environment snapshot:");
+        assert!(lines[1].starts_with("  platform: "));
+        assert!(lines[2].starts_with("  git: ") && !lines[2].ends_with("not found on PATH"));
+        assert!(lines[3].starts_with("  PATH: "));
+        assert_eq!(lines.len(), 6);
+        assert!(lines[4].starts_with("  git: ") && lines[4].contains(" at "));
+        assert_eq!(lines[5], "  git-smee-no-such-tool: not found on PATH");
+    }
+
+    #[test]
+    fn given_unmet_requirement_when_running_then_command_fails_with_missing_prerequisite() {
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+        let hooks = vec![HookDefinition {
+            command: "node lint.js".to_string(),
+            requires: vec!["git-smee-no-such-tool >= 18".parse().unwrap()],
+            ..Default::default()
+        }];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(runner.calls().is_empty());
+        assert!(summary.failed_count() == 1);
+        let Some(Error::MissingPrerequisite {
+            requirement,
+            reason,
+        }) = summary.error()
+        else {
+            panic!("expected a missing prerequisite error");
+        };
+        assert!(requirement == "git-smee-no-such-tool >= 18");
+        assert!(reason == "not found on PATH");
+        assert!(
+            summary.first_failure().unwrap().failure_display()
+                == "sequential command #1 is missing prerequisite git-smee-no-such-tool >= 18 (not found on PATH)"
+        );
+    }
+
+    #[test]
+    fn given_hook_settings_when_explaining_then_verdict_names_the_deciding_setting() {
+        let today = CalendarDate::new(2026, 3, 1).unwrap();
+        let verdict = |hook: HookDefinition| super::explain::command_verdict(&hook, None, today);
+        let missing = || vec!["git-smee-no-such-tool".parse().unwrap()];
+
+        assert!(
+            verdict(HookDefinition {
+                command: "cargo test".to_string(),
+                ..Default::default()
+            }) == Verdict::Run { note: None }
+        );
+        assert!(
+            verdict(HookDefinition {
+                command: "node lint.js".to_string(),
+                requires: missing(),
+                on_missing: crate::config::MissingRequirement::Skip,
+                ..Default::default()
+            }) == Verdict::Skip {
+                reason: "requires git-smee-no-such-tool: not found on PATH (on_missing = \"skip\")"
+                    .to_string()
+            }
+        );
+        assert!(
+            verdict(HookDefinition {
+                command: "node lint.js".to_string(),
+                requires: missing(),
+                ..Default::default()
+            }) == Verdict::Fail {
+                reason: "requires git-smee-no-such-tool: not found on PATH".to_string()
+            }
+        );
+        assert!(
+            verdict(HookDefinition {
+                command: "cargo deny check".to_string(),
+                enforce_after: CalendarDate::new(2026, 4, 1),
+                ..Default::default()
+            }) == Verdict::Run {
+                note: Some("failures only warn until 2026-04-01 (enforce_after)".to_string())
+            }
+        );
+        assert!(
+            verdict(HookDefinition {
+                command: "cargo deny check".to_string(),
+                severity: Severity::Warning,
+                ..Default::default()
+            }) == Verdict::Run {
+                note: Some("failures only warn (severity = \"warning\")".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn given_unmet_requirement_with_skip_when_running_then_command_is_skipped_and_run_continues() {
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+        let hooks = vec![
+            HookDefinition {
+                command: "node lint.js".to_string(),
+                requires: vec!["git-smee-no-such-tool".parse().unwrap()],
+                on_missing: crate::config::MissingRequirement::Skip,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "git diff --check".to_string(),
+                requires: vec!["git".parse().unwrap()],
+                ..Default::default()
+            },
+        ];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(runner.calls() == ["git diff --check"]);
+        assert!(summary.error().is_none());
+        assert!(summary.attempted_count() == 1);
+        assert!(summary.skipped_count() == 1);
+        assert!(
+            summary.command_runs()[0].status_display()
+                == "skipped, missing prerequisite git-smee-no-such-tool (not found on PATH)"
+        );
+    }
+
+    #[test]
+    fn given_warning_and_error_failures_when_running_in_parallel_then_only_error_blocks() {
+        let runner = FakeRunner::with_command_outcomes(vec![
+            ("warn-only", vec![PlannedResult::Exit(Some(1))]),
+            ("must-pass", vec![PlannedResult::Exit(Some(3))]),
+        ]);
+        let hooks = vec![
+            HookDefinition {
+                command: "warn-only".to_string(),
+                parallel_execution_allowed: true,
+                severity: Severity::Warning,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "must-pass".to_string(),
+                parallel_execution_allowed: true,
+                ..Default::default()
+            },
+        ];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(matches!(summary.error(), Some(Error::ExecutionFailed(3))));
+        assert!(summary.failed_count() == 1);
+    }
+
+    #[test]
+    fn given_parallel_hooks_when_running_then_output_is_labelled_by_name_or_position() {
+        let runner = FakeRunner::with_default_outcomes(vec![
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(0)),
+        ]);
+        let hooks = vec![
+            HookDefinition {
+                command: "sequential".to_string(),
+                name: Some("setup".to_string()),
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "cargo fmt --check".to_string(),
+                name: Some("fmt".to_string()),
+                parallel_execution_allowed: true,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "cargo clippy".to_string(),
+                parallel_execution_allowed: true,
+                ..Default::default()
+            },
+        ];
+
+        let _ = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(runner.output_label("sequential").is_none());
+        assert!(runner.output_label("cargo fmt --check").as_deref() == Some("fmt"));
+        assert!(runner.output_label("cargo clippy").as_deref() == Some("hook-3"));
+    }
+
+    #[test]
+    fn given_output_lines_when_prefixing_then_each_line_is_tagged_and_newline_terminated() {
+        let mut lines = Vec::new();
+
+        copy_lines(
+            &b"first\nsecond\r\nno newline"[..],
+            Some("clippy"),
+            None,
+            |line| {
+                lines.push(String::from_utf8_lossy(line).to_string());
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert!(
+            lines
+                == vec![
+                    "[clippy] first\n".to_string(),
+                    "[clippy] second\r\n".to_string(),
+                    "[clippy] no newline\n".to_string(),
+                ]
+        );
+    }
+
+    #[test]
+    fn given_output_over_cap_when_copying_then_head_and_tail_are_kept_around_a_marker() {
+        let input: String = (1..=20).map(|n| format!("line {n:02}\n")).collect();
+        let mut lines = Vec::new();
+
+        let truncated = copy_lines(input.as_bytes(), Some("lint"), Some(40), |line| {
+            lines.push(String::from_utf8_lossy(line).to_string());
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(truncated == 128);
+        assert!(
+            lines
+                == vec![
+                    "[lint] line 01\n".to_string(),
+                    "[lint] line 02\n".to_string(),
+                    "[lint] ... output truncated (128 bytes) ...\n".to_string(),
+                    "[lint] line 19\n".to_string(),
+                    "[lint] line 20\n".to_string(),
+                ]
+        );
+    }
+
+    #[test]
+    fn given_output_within_cap_when_copying_unlabeled_then_it_passes_through_unchanged() {
+        let mut output = Vec::new();
+
+        let truncated = copy_lines(&b"one\ntwo"[..], None, Some(64), |line| {
+            output.extend_from_slice(line);
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(truncated == 0);
+        assert!(output == b"one\ntwo");
+    }
+
+    #[test]
+    fn given_unterminated_line_over_cap_when_copying_unlabeled_then_marker_starts_a_new_line() {
+        let mut output = Vec::new();
+
+        let truncated = copy_lines(&b"abcdefghij"[..], None, Some(4), |line| {
+            output.extend_from_slice(line);
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(truncated == 10);
+        assert!(output == b"... output truncated (10 bytes) ...\n");
+    }
+
+    #[test]
+    fn given_executor_config_when_running_parallel_hooks_then_its_named_pool_is_used() {
+        let mut hooks_map = HashMap::new();
+        hooks_map.insert(
+            LifeCyclePhase::PreCommit,
+            vec![
+                HookDefinition {
+                    command: "sequential".to_string(),
+                    ..Default::default()
+                },
+                HookDefinition {
+                    command: "parallel-1".to_string(),
+                    parallel_execution_allowed: true,
+                    ..Default::default()
+                },
+                HookDefinition {
+                    command: "parallel-2".to_string(),
+                    parallel_execution_allowed: true,
+                    ..Default::default()
+                },
+            ],
+        );
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
+        let runner = FakeRunner::with_default_outcomes(vec![
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(0)),
+        ]);
+        let executor_config = ExecutorConfig::new()
+            .with_num_threads(1)
+            .with_thread_name_prefix("embedder-hooks");
+
+        let summary = execute_hook_with_runner_and_executor_config(
+            &config,
+            LifeCyclePhase::PreCommit,
+            &runner,
+            &[],
+            None,
+            &executor_config,
+            None,
+        )
+        .unwrap();
+
+        assert!(summary.error().is_none());
+        let calls = runner.calls();
+        let thread_names = runner.thread_names();
+        for (call, thread_name) in calls.iter().zip(&thread_names) {
+            if call.starts_with("parallel") {
+                assert!(thread_name.as_deref() == Some("embedder-hooks-0"));
+            } else {
+                assert!(thread_name.as_deref() != Some("embedder-hooks-0"));
+            }
+        }
+    }
+
+    #[test]
+    fn given_stdout_is_not_a_terminal_when_executing_then_parallel_hooks_run_sequentially_unless_opted_out()
+     {
+        let mut config = SmeeConfig::default();
+        config.hooks.insert(
+            LifeCyclePhase::PreCommit,
+            ["parallel-1", "sequential", "parallel-2"]
+                .into_iter()
+                .map(|command| HookDefinition {
+                    command: command.to_string(),
+                    parallel_execution_allowed: command.starts_with("parallel"),
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        let executor_config = ExecutorConfig::new().with_stdout_terminal(false);
+        let run = |config: &SmeeConfig| {
+            let runner = FakeRunner::with_default_outcomes(
+                (0..3).map(|_| PlannedResult::Exit(Some(0))).collect(),
+            );
+            let summary = execute_hook_with_runner_and_executor_config(
+                config,
+                LifeCyclePhase::PreCommit,
+                &runner,
+                &[],
+                None,
+                &executor_config,
+                None,
+            )
+            .unwrap();
+            (summary, runner.calls())
+        };
+
+        let (summary, calls) = run(&config);
+        assert_eq!(calls, ["parallel-1", "sequential", "parallel-2"]);
+        assert_eq!(summary.phase_attempted_count(CommandPhase::Sequential), 3);
+        assert_eq!(summary.phase_attempted_count(CommandPhase::Parallel), 0);
+
+        config.settings.parallel_without_tty = true;
+        let (summary, calls) = run(&config);
+        assert_eq!(calls[0], "sequential");
+        assert_eq!(summary.phase_attempted_count(CommandPhase::Parallel), 2);
+    }
+
+    #[test]
+    fn given_executor_config_when_pool_is_requested_twice_then_it_is_built_once() {
+        let executor_config = ExecutorConfig::new().with_num_threads(2);
+
+        let first = executor_config.thread_pool().unwrap() as *const rayon::ThreadPool;
+        let second = executor_config.thread_pool().unwrap() as *const rayon::ThreadPool;
+
+        assert!(first == second);
+        assert!(executor_config.thread_pool().unwrap().current_num_threads() == 2);
+        assert!(executor_config.thread_name_prefix() == DEFAULT_THREAD_NAME_PREFIX);
+    }
+
+    #[test]
+    fn given_isolate_tmp_hooks_when_running_in_parallel_then_each_gets_its_own_cleaned_tmpdir() {
+        let tmp_root = tempfile::tempdir().unwrap();
+        let runner = FakeRunner::with_default_outcomes(vec![
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(0)),
+            PlannedResult::Exit(Some(0)),
+        ]);
+        let hooks = vec![
+            HookDefinition {
+                command: "parallel-a".to_string(),
+                parallel_execution_allowed: true,
+                isolate_tmp: true,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "shared-tmp".to_string(),
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "parallel-b".to_string(),
+                parallel_execution_allowed: true,
+                isolate_tmp: true,
+                ..Default::default()
+            },
+        ];
+        let context = RunContext {
+            tmp_root: Some(tmp_root.path().join("pre-commit")),
+            ..Default::default()
+        };
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert!(summary.error().is_none());
+        let mut tmp_dirs = runner.tmp_dir_calls();
+        tmp_dirs.sort();
+        assert!(
+            tmp_dirs
+                == vec![
+                    None,
+                    Some(tmp_root.path().join("pre-commit").join("hook-1")),
+                    Some(tmp_root.path().join("pre-commit").join("hook-3")),
+                ]
+        );
+        assert!(!tmp_root.path().join("pre-commit").join("hook-1").exists());
+        assert!(!tmp_root.path().join("pre-commit").join("hook-3").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_artifacts_dir_hook_when_running_then_its_outputs_are_kept_in_a_per_hook_dir() {
+        let artifacts_root = tempfile::tempdir().unwrap();
+        let platform = Platform::Unix;
+        let runner = PlatformCommandRunner {
+            platform: &platform,
+        };
+        let run_root = artifacts::run_artifacts_root(artifacts_root.path(), "run-2", "pre-commit");
+        let hooks = vec![
+            HookDefinition {
+                name: Some("cover/age".to_string()),
+                command: "echo 91% > \"$SMEE_ARTIFACTS_DIR/coverage.txt\"".to_string(),
+                artifacts_dir: true,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "test -z \"$SMEE_ARTIFACTS_DIR\"".to_string(),
+                ..Default::default()
+            },
+        ];
+        let context = RunContext {
+            artifacts_root: Some(run_root.clone()),
+            ..Default::default()
+        };
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert!(summary.error().is_none());
+        assert!(run_root == artifacts_root.path().join("run-2").join("pre-commit"));
+        assert!(
+            std::fs::read_to_string(run_root.join("cover_age").join("coverage.txt")).unwrap()
+                == "91%\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_more_runs_than_kept_when_starting_a_run_then_oldest_artifacts_are_pruned() {
+        let artifacts_root = tempfile::tempdir().unwrap();
+        for run in 0..KEPT_ARTIFACT_RUNS + 2 {
+            let dir = artifacts_root.path().join(format!("run-{run:02}"));
+            std::fs::create_dir(&dir).unwrap();
+            let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000 + run as u64);
+            std::fs::File::open(&dir)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        artifacts::run_artifacts_root(artifacts_root.path(), "run-new", "pre-commit");
+
+        let mut kept: Vec<_> = std::fs::read_dir(artifacts_root.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        kept.sort();
+        assert!(kept.len() == KEPT_ARTIFACT_RUNS - 1);
+        assert!(kept[0] == "run-03");
+    }
+
+    /// Writes `bytes` bytes to `file` below the state entry `entry`, or to
+    /// `entry` itself when `file` is empty, and dates everything in the entry
+    /// `modified_secs` after the epoch.
+    fn write_state_entry(entry: &std::path::Path, file: &str, bytes: usize, modified_secs: u64) {
+        let path = match file {
+            "" => entry.to_path_buf(),
+            file => entry.join(file),
+        };
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, vec![b'x'; bytes]).unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(modified_secs);
+        for dated in path
+            .ancestors()
+            .take_while(|dated| dated.starts_with(entry))
+        {
+            std::fs::File::options()
+                .write(dated.is_file())
+                .read(dated.is_dir())
+                .open(dated)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn given_expired_entries_when_pruning_state_then_they_and_older_audit_lines_are_removed() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let old_run = state_dir.path().join("runs/old.json");
+        let new_artifact = state_dir.path().join("artifacts/new");
+        write_state_entry(&old_run, "", 10, 1_000);
+        write_state_entry(&new_artifact, "pre-commit/lint/report.json", 10, 3_000);
+        write_state_entry(&state_dir.path().join("locks/fmt.lock"), "", 0, 1_000);
+        std::fs::write(
+            state_dir.path().join("audit.jsonl"),
+            "{\"started_at_unix_ms\":1000000}\n{\"started_at_unix_ms\":3000000}\n",
+        )
+        .unwrap();
+        let limits = PruneLimits {
+            expire_before: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(2_000)),
+            max_size: None,
+        };
+
+        let pruned = prune_state(state_dir.path(), &limits, false).unwrap();
+
+        assert!(pruned.removed == vec![old_run.clone()]);
+        assert!(pruned.removed_log_lines == 1);
+        assert!(!old_run.exists());
+        assert!(new_artifact.exists());
+        assert!(state_dir.path().join("locks/fmt.lock").exists());
+        assert!(
+            std::fs::read_to_string(state_dir.path().join("audit.jsonl")).unwrap()
+                == "{\"started_at_unix_ms\":3000000}\n"
+        );
+    }
+
+    #[test]
+    fn given_state_over_max_size_when_pruning_then_oldest_entries_go_first_unless_dry_run() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let oldest = state_dir.path().join("tmp/pre-commit");
+        let middle = state_dir.path().join("artifacts/middle");
+        let newest = state_dir.path().join("runs/newest.json");
+        write_state_entry(&oldest, "hook-1/scratch", 100, 1_000);
+        write_state_entry(&middle, "pre-push/hook-1/report", 100, 2_000);
+        write_state_entry(&newest, "", 100, 3_000);
+        let limits = PruneLimits {
+            expire_before: None,
+            max_size: Some(250),
+        };
+
+        let dry_run = prune_state(state_dir.path(), &limits, true).unwrap();
+        assert!(dry_run.removed == vec![oldest.clone()]);
+        assert!(oldest.exists());
+
+        let pruned = prune_state(state_dir.path(), &limits, false).unwrap();
+        assert!(pruned.removed == vec![oldest.clone()]);
+        assert!((pruned.freed_bytes, pruned.kept_bytes) == (100, 200));
+        assert!(!oldest.exists());
+        assert!(middle.exists() && newest.exists());
+
+        let emptied = prune_state(state_dir.path(), &PruneLimits::everything(), false).unwrap();
+        assert!(emptied.removed == vec![middle.clone(), newest.clone()]);
+        assert!(!middle.exists() && !newest.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_isolate_tmp_hook_when_running_then_tmpdir_env_points_at_private_dir() {
+        let tmp_root = tempfile::tempdir().unwrap();
+        let platform = Platform::Unix;
+        let runner = PlatformCommandRunner {
+            platform: &platform,
+        };
+        let expected = tmp_root.path().join("pre-commit").join("hook-1");
+        let hooks = vec![HookDefinition {
+            command: format!(
+                "test \"$TMPDIR\" = '{}' && touch \"$TMPDIR/scratch\"",
+                expected.display()
+            ),
+            isolate_tmp: true,
+            ..Default::default()
+        }];
+        let context = RunContext {
+            tmp_root: Some(tmp_root.path().join("pre-commit")),
+            ..Default::default()
+        };
+
+        let summary = run_hooks_in_context(&hooks, &runner, &[], None, &context);
+
+        assert!(summary.error().is_none());
+        assert!(!expected.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_no_shell_hook_when_running_then_quoted_words_reach_the_program_intact() {
+        let platform = Platform::Unix;
+        let runner = PlatformCommandRunner {
+            platform: &platform,
+        };
+        let hooks = vec![HookDefinition {
+            command: r#"sh -c 'test "$1" = "a b" && test "$2" = arg && exit 3' name "a b""#
+                .to_string(),
+            no_shell: true,
+            ..Default::default()
+        }];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &["arg".into()], None);
+
+        assert!(summary.command_runs()[0].exit_code() == Some(3));
+    }
+
+    #[test]
+    fn given_no_shell_hook_with_missing_program_when_running_then_spawn_error_names_it() {
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+        let hooks = vec![HookDefinition {
+            command: "'git-smee no such tool' --check".to_string(),
+            no_shell: true,
+            ..Default::default()
+        }];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(runner.calls().is_empty());
+        assert!(
+            summary.first_failure().unwrap().failure_display()
+                == "sequential command #1 failed to spawn 'git-smee no such tool <args redacted>' via 'direct exec': executable 'git-smee no such tool' not found on PATH"
+        );
+    }
+
+    #[test]
+    fn given_stopped_command_with_partial_output_when_rendering_summary_then_it_is_marked_partial()
+    {
+        let summary = HookRunSummary {
+            total_configured: 1,
+            total_duration: Duration::from_secs(1),
+            sequential_duration: Duration::ZERO,
+            parallel_duration: Duration::from_secs(1),
+            command_runs: vec![CommandRun {
+                phase: CommandPhase::Parallel,
+                index: 0,
+                config_index: 0,
+                started_at: SystemTime::UNIX_EPOCH,
+                duration: Duration::from_secs(1),
+                resources: None,
+                output_truncated_bytes: 0,
+                partial_output: vec![
+                    "[e2e] compiling".to_string(),
+                    "[e2e] running 12 tests".to_string(),
+                ],
+                severity: Severity::Error,
+                enforce_after: None,
+                description_url: None,
+                outcome: CommandOutcome::Stopped {
+                    reason: StopReason::Timeout(Duration::from_secs(1)),
+                    signal: StopSignal::Terminate,
+                },
+            }],
+            disabled_by: None,
+            nested_in: None,
+        };
+
+        let lines = summary.text_lines(LifeCyclePhase::PrePush).join("\n");
+
+        assert!(lines.ends_with(
+            "  partial output of parallel command #1 before it was stopped (last 2 lines):\n    | [e2e] compiling\n    | [e2e] running 12 tests"
+        ));
+    }
+
+    #[test]
+    fn given_resource_usage_when_rendering_summary_then_only_verbose_output_shows_it() {
+        let summary = HookRunSummary {
+            total_configured: 1,
+            total_duration: Duration::from_millis(40),
+            sequential_duration: Duration::from_millis(40),
+            parallel_duration: Duration::ZERO,
+            command_runs: vec![CommandRun {
+                phase: CommandPhase::Sequential,
+                index: 0,
+                config_index: 0,
+                started_at: SystemTime::UNIX_EPOCH,
+                duration: Duration::from_millis(40),
+                resources: Some(ResourceUsage {
+                    peak_rss_bytes: 48 * 1024 * 1024,
+                    user_cpu: Duration::from_millis(25),
+                    system_cpu: Duration::from_millis(5),
+                }),
+                output_truncated_bytes: 0,
+                partial_output: Vec::new(),
+                severity: Severity::Error,
+                enforce_after: None,
+                description_url: None,
+                outcome: CommandOutcome::Success,
+            }],
+            disabled_by: None,
+            nested_in: None,
+        };
+
+        insta::assert_snapshot!(
+            "summary_text_lines",
+            summary.text_lines(LifeCyclePhase::PreCommit).join("\n")
+        );
+        insta::assert_snapshot!(
+            "summary_verbose_text_lines",
+            summary
+                .verbose_text_lines(LifeCyclePhase::PreCommit)
+                .join("\n")
+        );
+    }
+
+    #[test]
+    fn given_truncated_output_when_rendering_summary_then_dropped_bytes_are_reported() {
+        let summary = HookRunSummary {
+            total_configured: 1,
+            total_duration: Duration::from_millis(40),
+            sequential_duration: Duration::from_millis(40),
+            parallel_duration: Duration::ZERO,
+            command_runs: vec![CommandRun {
+                phase: CommandPhase::Sequential,
+                index: 0,
+                config_index: 0,
+                started_at: SystemTime::UNIX_EPOCH,
+                duration: Duration::from_millis(40),
+                resources: None,
+                output_truncated_bytes: 5_000,
+                partial_output: Vec::new(),
+                severity: Severity::Error,
+                enforce_after: None,
+                description_url: None,
+                outcome: CommandOutcome::Exit(1),
+            }],
+            disabled_by: None,
+            nested_in: None,
+        };
+
+        let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
+
+        assert!(lines.contains(
+            "sequential command #1: failed with code 1 in 40ms, output truncated (5000 bytes)"
+        ));
+    }
+
+    #[test]
+    fn given_executed_commands_when_auditing_then_each_run_is_appended_as_a_json_line() {
+        let hooks = vec![
+            HookDefinition {
+                name: Some("deploy".to_string()),
+                command: "API_TOKEN=abc123 ./deploy".to_string(),
+                isolate_tmp: true,
+                ..Default::default()
+            },
+            HookDefinition {
+                command: "cargo test".to_string(),
+                severity: Severity::Warning,
+                ..Default::default()
+            },
+        ];
+        let summary = HookRunSummary {
+            total_configured: 2,
+            total_duration: Duration::ZERO,
+            sequential_duration: Duration::ZERO,
+            parallel_duration: Duration::ZERO,
+            command_runs: vec![
+                CommandRun {
+                    phase: CommandPhase::Sequential,
+                    index: 0,
+                    config_index: 0,
+                    started_at: SystemTime::UNIX_EPOCH + Duration::from_millis(1_500),
+                    duration: Duration::from_millis(12),
+                    resources: None,
+                    output_truncated_bytes: 0,
+                    partial_output: Vec::new(),
+                    severity: Severity::Error,
+                    enforce_after: None,
+                    description_url: None,
+                    outcome: CommandOutcome::Success,
+                },
+                CommandRun {
+                    phase: CommandPhase::Sequential,
+                    index: 1,
+                    config_index: 1,
+                    started_at: SystemTime::UNIX_EPOCH,
+                    duration: Duration::from_millis(7),
+                    resources: None,
+                    output_truncated_bytes: 0,
+                    partial_output: Vec::new(),
+                    severity: Severity::Warning,
+                    enforce_after: None,
+                    description_url: None,
+                    outcome: CommandOutcome::Exit(3),
+                },
+            ],
+            disabled_by: None,
+            nested_in: None,
+        };
+        let log_dir = tempfile::tempdir().unwrap();
+        let log_path = log_dir.path().join("smee").join("audit.jsonl");
+
+        let records = audit::audit_records(
+            LifeCyclePhase::PrePush.as_str(),
+            &hooks,
+            &summary,
+            2,
+            std::path::Path::new("/repo"),
+        );
+        RecordLog::at(&log_path).append(&records).unwrap();
+        RecordLog::at(&log_path).append(&records[..1]).unwrap();
 
-        let result =
-            execute_hook_with_runner(&config, LifeCyclePhase::PreCommit, &runner, &[], None);
-        assert!(result.is_ok());
-        assert_eq!(runner.calls(), vec!["run-pre-commit"]);
+        let contents = std::fs::read_to_string(&log_path).unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert!(lines.len() == 3);
+        assert!(
+            lines[0]
+                == serde_json::json!({
+                    "started_at_unix_ms": 1500,
+                    "phase": "pre-push",
+                    "hook": "deploy",
+                    "command": "API_TOKEN=<redacted> ./deploy",
+                    "cwd": "/repo",
+                    "env_keys": [
+                        "GIT_SMEE_HOOK_ARGC",
+                        "GIT_SMEE_HOOK_ARG_1",
+                        "GIT_SMEE_HOOK_ARG_2",
+                        "TMPDIR",
+                        "TMP",
+                        "TEMP"
+                    ],
+                    "duration_ms": 12,
+                    "status": "success",
+                    "exit_code": 0,
+                    "severity": "error"
+                })
+        );
+        assert!(lines[1]["hook"] == "hook-2");
+        assert!(lines[1]["status"] == "exit");
+        assert!(lines[1]["exit_code"] == 3);
+        assert!(lines[1]["severity"] == "warning");
+        assert!(lines[2] == lines[0]);
     }
 
     #[test]
-    fn given_hook_args_when_executing_then_all_commands_receive_forwarded_args() {
-        let mut hooks_map = std::collections::HashMap::new();
-        hooks_map.insert(
-            LifeCyclePhase::CommitMsg,
-            vec![crate::config::HookDefinition {
-                command: "check-commit-message".to_string(),
-                parallel_execution_allowed: false,
+    fn given_failed_run_when_building_report_then_it_lists_each_command_and_fails() {
+        let hooks = vec![HookDefinition {
+            name: Some("lint".to_string()),
+            command: "API_TOKEN=abc123 ./lint".to_string(),
+            ..Default::default()
+        }];
+        let summary = HookRunSummary {
+            total_configured: 1,
+            total_duration: Duration::from_millis(40),
+            sequential_duration: Duration::from_millis(40),
+            parallel_duration: Duration::ZERO,
+            command_runs: vec![CommandRun {
+                phase: CommandPhase::Sequential,
+                index: 0,
+                config_index: 0,
+                started_at: SystemTime::UNIX_EPOCH,
+                duration: Duration::from_millis(38),
+                resources: Some(ResourceUsage {
+                    peak_rss_bytes: 8 * 1024 * 1024,
+                    user_cpu: Duration::from_millis(20),
+                    system_cpu: Duration::from_millis(4),
+                }),
+                output_truncated_bytes: 0,
+                partial_output: Vec::new(),
+                severity: Severity::Error,
+                enforce_after: None,
+                description_url: None,
+                outcome: CommandOutcome::Exit(2),
             }],
-        );
-        let config = SmeeConfig { hooks: hooks_map };
-        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
-        let hook_args = vec!["COMMIT_EDITMSG".to_string(), "message".to_string()];
+            disabled_by: None,
+            nested_in: None,
+        };
 
-        let result = execute_hook_with_runner(
-            &config,
-            LifeCyclePhase::CommitMsg,
-            &runner,
-            &hook_args,
-            None,
-        );
+        let report = report::run_report("pre-commit", &hooks, &summary);
 
-        assert!(result.is_ok());
-        assert_eq!(runner.calls(), vec!["check-commit-message"]);
-        assert_eq!(runner.hook_args_calls(), vec![hook_args]);
+        let mut json = serde_json::to_value(&report).unwrap();
+        assert!(json["finished_at_unix_ms"].as_u64().unwrap() > 0);
+        json.as_object_mut().unwrap().remove("finished_at_unix_ms");
+        assert!(
+            json == serde_json::json!({
+                "phase": "pre-commit",
+                "status": "failed",
+                "duration_ms": 40,
+                "attempted": 1,
+                "failed": 1,
+                "warnings": 0,
+                "skipped": 0,
+                "hooks": [{
+                    "hook": "lint",
+                    "command": "API_TOKEN=<redacted> ./lint",
+                    "status": "exit",
+                    "exit_code": 2,
+                    "duration_ms": 38,
+                    "severity": "error",
+                    "cpu_ms": 24,
+                    "peak_rss_bytes": 8_388_608
+                }]
+            })
+        );
     }
 
     #[test]
-    fn given_windows_command_script_when_building_then_command_can_read_batch_parameters() {
-        let script = windows_command_script("if \"%1\"==\"alpha\" exit /b 0");
+    fn given_record_log_when_appending_loading_and_resetting_then_records_round_trip() {
+        let log_dir = tempfile::tempdir().unwrap();
+        let log = RecordLog::at(log_dir.path().join("smee").join("analytics.jsonl"));
+        let record = AnalyticsRecord {
+            started_at_unix_ms: 1_500,
+            phase: "pre-commit".to_string(),
+            hook: "fmt".to_string(),
+            duration_ms: 12,
+            status: "success".to_string(),
+        };
 
-        assert_eq!(script, "@echo off\r\nif \"%1\"==\"alpha\" exit /b 0\r\n");
+        log.append(&[record.clone(), record.clone()]).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .and_then(|mut file| std::io::Write::write_all(&mut file, b"{\"phase\":"))
+            .unwrap();
+        let loaded = log.load::<AnalyticsRecord>().unwrap();
+
+        assert!(loaded.records == [record.clone(), record]);
+        assert!(loaded.skipped_lines == 1);
+        assert!(log.reset().unwrap() == 3);
+        assert!(!log.path().exists());
+        assert!(log.reset().unwrap() == 0);
+        assert!(log.load::<AnalyticsRecord>().unwrap().records.is_empty());
     }
 
     #[test]
-    fn given_windows_hook_arg_with_cmd_metachar_when_quoting_then_it_is_wrapped() {
-        assert_eq!(
-            windows_cmd_quote_hook_arg("caret^bang!percent%"),
-            "\"caret^^bang^!percent%%\""
-        );
-        assert_eq!(windows_cmd_quote_hook_arg("plain-ref"), "plain-ref");
+    fn given_audit_log_when_aggregating_stats_then_prometheus_text_has_counters_and_histogram() {
+        let log = [
+            r#"{"phase":"pre-commit","hook":"lint","duration_ms":200,"status":"success"}"#,
+            r#"{"phase":"pre-commit","hook":"lint","duration_ms":4000,"status":"exit"}"#,
+            r#"{"phase":"pre-commit","hook":"lint","duration_ms":0,"status":"prerequisite_skipped"}"#,
+            r#"{"phase":"task:say \"hi\"","hook":"hook-1","duration_ms":50,"status":"accepted_exit"}"#,
+            r#"{"phase":"pre-commit","hook":"lint","dura"#,
+        ]
+        .join("\n");
+
+        let stats = HookStatistics::from_log(&log);
+
+        assert!(stats.skipped_lines == 1);
+        assert!(stats.hooks.len() == 2);
+        let lint = &stats.hooks[0];
+        assert!(lint.runs() == 3);
+        assert!(lint.failures == 1);
+        assert!(lint.executed == 2);
+        assert!(lint.mean_duration() == Duration::from_millis(2100));
+        assert!(lint.max_duration == Duration::from_secs(4));
+        let text = stats.to_prometheus();
+        for line in [
+            "# TYPE git_smee_hook_runs_total counter",
+            r#"git_smee_hook_runs_total{phase="pre-commit",hook="lint",status="exit"} 1"#,
+            r#"git_smee_hook_runs_total{phase="pre-commit",hook="lint",status="prerequisite_skipped"} 1"#,
+            r#"git_smee_hook_failures_total{phase="pre-commit",hook="lint"} 1"#,
+            r#"git_smee_hook_failures_total{phase="task:say \"hi\"",hook="hook-1"} 0"#,
+            "# TYPE git_smee_hook_duration_seconds histogram",
+            r#"git_smee_hook_duration_seconds_bucket{phase="pre-commit",hook="lint",le="0.1"} 0"#,
+            r#"git_smee_hook_duration_seconds_bucket{phase="pre-commit",hook="lint",le="0.25"} 1"#,
+            r#"git_smee_hook_duration_seconds_bucket{phase="pre-commit",hook="lint",le="2.5"} 1"#,
+            r#"git_smee_hook_duration_seconds_bucket{phase="pre-commit",hook="lint",le="5"} 2"#,
+            r#"git_smee_hook_duration_seconds_bucket{phase="pre-commit",hook="lint",le="+Inf"} 2"#,
+            r#"git_smee_hook_duration_seconds_sum{phase="pre-commit",hook="lint"} 4.2"#,
+            r#"git_smee_hook_duration_seconds_count{phase="pre-commit",hook="lint"} 2"#,
+        ] {
+            assert!(
+                text.lines().any(|text_line| text_line == line),
+                "{line}\n{text}"
+            );
+        }
+    }
+
+    /// Serves one canned HTTP status per incoming request and returns the
+    /// webhook URL with the handle that yields the received requests.
+    fn serve_statuses(statuses: Vec<u16>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/report", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            statuses
+                .into_iter()
+                .map(|status| {
+                    let (stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream);
+                    let mut request = String::new();
+                    let mut content_length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if let Some(length) =
+                            line.to_ascii_lowercase().strip_prefix("content-length:")
+                        {
+                            content_length = length.trim().parse().unwrap();
+                        }
+                        request.push_str(&line);
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                    }
+                    let mut body = vec![0; content_length];
+                    reader.read_exact(&mut body).unwrap();
+                    request.push_str(&String::from_utf8_lossy(&body));
+                    write!(
+                        reader.get_mut(),
+                        "HTTP/1.1 {status} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    )
+                    .unwrap();
+                    request
+                })
+                .collect()
+        });
+        (url, server)
+    }
+
+    fn curl_available() -> bool {
+        Command::new("curl").arg("--version").output().is_ok()
     }
 
     #[test]
-    fn given_windows_hook_arg_with_expansion_syntax_when_quoting_then_it_stays_literal() {
-        assert_eq!(windows_cmd_quote_hook_arg("%PATH%"), "\"%%PATH%%\"");
-        assert_eq!(windows_cmd_quote_hook_arg("!PATH!"), "\"^!PATH^!\"");
+    fn given_server_error_when_posting_report_then_it_is_retried_until_accepted() {
+        if !curl_available() {
+            return;
+        }
+        let (url, server) = serve_statuses(vec![503, 500, 204]);
+        let webhook = crate::webhook::Webhook::new(url)
+            .unwrap()
+            .with_header("X-Git-Smee-Secret", "s3cret")
+            .unwrap();
+
+        let result =
+            report::post_with_retries(&webhook, b"{\"phase\":\"pre-commit\"}", 2, Duration::ZERO);
+
+        assert!(result.is_ok(), "{result:?}");
+        let requests = server.join().unwrap();
+        assert!(requests.len() == 3);
+        for request in requests {
+            assert!(request.starts_with("POST /report "));
+            assert!(request.contains("X-Git-Smee-Secret: s3cret"));
+            assert!(request.ends_with("{\"phase\":\"pre-commit\"}"));
+        }
     }
 
     #[test]
-    fn given_windows_hook_arg_with_space_when_quoting_then_it_is_wrapped() {
-        assert_eq!(windows_cmd_quote_hook_arg("space value"), "\"space value\"");
+    fn given_retries_exhausted_when_posting_report_then_last_failure_is_returned() {
+        if !curl_available() {
+            return;
+        }
+        let (url, server) = serve_statuses(vec![500, 500]);
+        let webhook = crate::webhook::Webhook::new(url).unwrap();
+
+        let result = report::post_with_retries(&webhook, b"{}", 1, Duration::ZERO);
+
+        assert!(matches!(
+            result,
+            Err(crate::webhook::Error::RequestFailed { .. })
+        ));
+        assert!(server.join().unwrap().len() == 2);
     }
 
     #[test]
-    fn given_summary_success_when_rendering_then_counts_phases_and_durations() {
-        let hooks = vec![
-            HookDefinition {
-                command: "seq-ok".to_string(),
-                parallel_execution_allowed: false,
-            },
-            HookDefinition {
-                command: "parallel-ok".to_string(),
-                parallel_execution_allowed: true,
-            },
-        ];
-        let runner = FakeRunner::with_command_outcomes(vec![
-            ("seq-ok", vec![PlannedResult::Exit(Some(0))]),
-            ("parallel-ok", vec![PlannedResult::Exit(Some(0))]),
-        ]);
+    fn given_spawn_failure_summary_when_cloning_then_clone_is_equal_and_keeps_error() {
+        let hooks = vec![HookDefinition {
+            command: "deploy --token secret".to_string(),
+            ..Default::default()
+        }];
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::SpawnError(
+            io::ErrorKind::PermissionDenied,
+        )]);
 
         let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+        let cloned = summary.clone();
 
-        assert_eq!(summary.total_configured(), 2);
-        assert_eq!(summary.attempted_count(), 2);
-        assert_eq!(summary.skipped_count(), 0);
-        assert_eq!(summary.failed_count(), 0);
-        assert_eq!(summary.phase_attempted_count(CommandPhase::Sequential), 1);
-        assert_eq!(summary.phase_failed_count(CommandPhase::Sequential), 0);
-        assert_eq!(summary.phase_attempted_count(CommandPhase::Parallel), 1);
-        assert_eq!(summary.phase_failed_count(CommandPhase::Parallel), 0);
-        assert!(summary.first_failure().is_none());
-        assert!(summary.error().is_none());
-
-        let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
-        assert!(lines.contains("Hook summary: pre-commit"));
-        assert!(lines.contains("total: 2 attempted, 0 skipped, 0 failed in"));
-        assert!(lines.contains("sequential: 1 attempted, 0 failed in"));
-        assert!(lines.contains("parallel: 1 attempted, 0 failed in"));
-        assert!(lines.contains("sequential command #1: ok in"));
-        assert!(lines.contains("parallel command #1: ok in"));
+        assert!(cloned == summary);
+        let Some(Error::CommandSpawnFailed { source, .. }) = cloned.error() else {
+            panic!("expected a spawn failure");
+        };
+        assert!(source.kind() == io::ErrorKind::PermissionDenied);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn given_summary_sequential_failure_when_rendering_then_reports_skipped_and_first_failure() {
-        let hooks = vec![
-            HookDefinition {
-                command: "seq-fail".to_string(),
-                parallel_execution_allowed: false,
-            },
-            HookDefinition {
-                command: "seq-skipped".to_string(),
-                parallel_execution_allowed: false,
-            },
-            HookDefinition {
-                command: "parallel-skipped".to_string(),
-                parallel_execution_allowed: true,
-            },
-        ];
-        let runner = FakeRunner::with_command_outcomes(vec![(
-            "seq-fail",
-            vec![PlannedResult::Exit(Some(9))],
-        )]);
+    fn given_real_command_when_running_then_resource_usage_is_collected() {
+        let platform = Platform::Unix;
+        let runner = PlatformCommandRunner {
+            platform: &platform,
+        };
+        let hooks = vec![HookDefinition {
+            command: "true".to_string(),
+            ..Default::default()
+        }];
 
         let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
 
-        assert_eq!(summary.total_configured(), 3);
-        assert_eq!(summary.attempted_count(), 1);
-        assert_eq!(summary.skipped_count(), 2);
-        assert_eq!(summary.failed_count(), 1);
-        assert_eq!(summary.phase_attempted_count(CommandPhase::Sequential), 1);
-        assert_eq!(summary.phase_failed_count(CommandPhase::Sequential), 1);
-        assert_eq!(summary.phase_attempted_count(CommandPhase::Parallel), 0);
-        assert!(matches!(summary.error(), Some(Error::ExecutionFailed(9))));
+        let resources = summary.command_runs()[0]
+            .resources()
+            .expect("unix runs report resource usage");
+        assert!(resources.peak_rss_bytes > 0);
+    }
 
-        let first_failure = summary.first_failure().expect("missing first failure");
-        assert_eq!(first_failure.status_display(), "failed with code 9");
-        assert_eq!(
-            first_failure.failure_display(),
-            "sequential command #1 exited with code 9"
+    #[cfg(unix)]
+    #[test]
+    fn given_parallel_hook_stopped_by_timeout_when_running_then_partial_output_is_kept() {
+        let platform = Platform::Unix;
+        let runner = PlatformCommandRunner {
+            platform: &platform,
+        };
+        let hooks = vec![HookDefinition {
+            name: Some("slow".to_string()),
+            command: "echo started; printf 'no newline'; sleep 5".to_string(),
+            parallel_execution_allowed: true,
+            timeout: Some(HookTimeout::Seconds(1)),
+            ..Default::default()
+        }];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(matches!(
+            summary.error(),
+            Some(Error::ExecutionStopped { .. })
+        ));
+        assert!(
+            summary.command_runs()[0].partial_output() == ["[slow] started", "[slow] no newline"]
         );
     }
 
+    #[cfg(unix)]
     #[test]
-    fn given_summary_parallel_failures_when_rendering_then_first_failure_is_phase_ordered() {
-        let summary = HookRunSummary {
-            total_configured: 2,
-            total_duration: Duration::ZERO,
-            sequential_duration: Duration::ZERO,
-            parallel_duration: Duration::ZERO,
-            command_runs: vec![
-                CommandRun {
-                    phase: CommandPhase::Parallel,
-                    index: 1,
-                    duration: Duration::ZERO,
-                    outcome: CommandOutcome::Exit(7),
-                },
-                CommandRun {
-                    phase: CommandPhase::Parallel,
-                    index: 0,
-                    duration: Duration::ZERO,
-                    outcome: CommandOutcome::Exit(5),
-                },
-            ],
+    fn given_hook_with_max_output_when_it_prints_more_then_truncated_bytes_are_recorded() {
+        let platform = Platform::Unix;
+        let runner = PlatformCommandRunner {
+            platform: &platform,
         };
+        let hooks = vec![HookDefinition {
+            command: "i=0; while [ $i -lt 200 ]; do echo 0123456789; i=$((i+1)); done".to_string(),
+            max_output_bytes: Some(110),
+            ..Default::default()
+        }];
 
-        assert_eq!(summary.failed_count(), 2);
-        assert_eq!(summary.phase_failed_count(CommandPhase::Parallel), 2);
-        let first_failure = summary.first_failure().expect("missing first failure");
-        assert_eq!(
-            first_failure.failure_display(),
-            "parallel command #1 exited with code 5"
-        );
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(summary.error().is_none());
+        assert!(summary.command_runs()[0].output_truncated_bytes() == 2_090);
+    }
+
+    #[test]
+    fn given_disabled_summary_when_rendering_then_toggle_key_is_reported_without_error() {
+        let summary = HookRunSummary::disabled(3, "smee.pre-commit.disabled".to_string());
+
+        assert_eq!(summary.disabled_by(), Some("smee.pre-commit.disabled"));
+        assert_eq!(summary.skipped_count(), 3);
+        assert!(summary.error().is_none());
         let lines = summary.text_lines(LifeCyclePhase::PreCommit).join("\n");
-        assert!(lines.contains("first failure: parallel command #1 exited with code 5"));
+        assert!(lines.contains("disabled by git config smee.pre-commit.disabled; 3 skipped"));
     }
 
     #[test]
@@ -450,6 +3129,7 @@ mod tests {
         let hooks = vec![HookDefinition {
             command: "SECRET=value deploy --token super-secret-value".to_string(),
             parallel_execution_allowed: false,
+            ..Default::default()
         }];
         let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::SpawnError(
             io::ErrorKind::NotFound,
@@ -481,10 +3161,12 @@ mod tests {
             HookDefinition {
                 command: "first".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             },
             HookDefinition {
                 command: "second".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             },
         ];
         let runner = FakeRunner::with_command_outcomes(vec![
@@ -626,9 +3308,16 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "hook command".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
-        let config = SmeeConfig { hooks: hooks_map };
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
         let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(127))]);
 
         let result =
@@ -707,6 +3396,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn given_secret_assignments_when_redacting_values_then_only_secret_values_are_masked() {
+        let redacted = redact_secret_values(
+            "GITHUB_TOKEN=ghp_abc  deploy --password=hunter2 --mode=fast LANG=C.UTF-8",
+        );
+
+        assert!(
+            redacted
+                == "GITHUB_TOKEN=<redacted>  deploy --password=<redacted> --mode=fast LANG=C.UTF-8"
+        );
+    }
+
+    #[test]
+    fn given_command_when_describing_then_exec_cwd_and_env_are_listed_with_secrets_masked() {
+        let mut shell_command = Command::new("sh");
+        shell_command
+            .arg("-c")
+            .arg("API_TOKEN=abc123 ./deploy")
+            .current_dir("/srv/repo")
+            .env("GIT_SMEE_HOOK_ARGC", "0")
+            .env("NPM_TOKEN", "npm_secret")
+            .env_remove("GIT_SMEE_HOOK_ARG_1");
+
+        let description = describe_command(
+            "sequential command #1 (hook-1)",
+            "API_TOKEN=abc123 ./deploy",
+            &shell_command,
+        );
+
+        assert!(
+            description
+                == [
+                    "git-smee: running sequential command #1 (hook-1)",
+                    "  command: API_TOKEN=<redacted> ./deploy",
+                    "  exec: sh \"-c\" \"API_TOKEN=<redacted> ./deploy\"",
+                    "  cwd: /srv/repo",
+                    "  env: GIT_SMEE_HOOK_ARGC=\"0\" NPM_TOKEN=\"<redacted>\"",
+                ]
+                .join("\n")
+        );
+    }
+
     #[test]
     fn given_command_without_env_prefix_when_redacting_then_executable_is_preserved() {
         let redacted = redact_command("deploy --token super-secret-value");
@@ -765,6 +3496,102 @@ mod tests {
         assert!(matches!(result, Err(Error::ExecutionTerminatedBySignal)));
     }
 
+    #[test]
+    fn given_stopped_command_when_executing_then_reason_and_signal_are_reported() {
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Stopped(
+            StopReason::Timeout(Duration::from_secs(3)),
+            StopSignal::Kill,
+        )]);
+        let hooks = vec![HookDefinition {
+            command: "slow-hook".to_string(),
+            timeout: Some(HookTimeout::Seconds(3)),
+            grace_period: Some(1),
+            ..Default::default()
+        }];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(
+            runner.termination_calls()
+                == vec![TerminationPolicy {
+                    timeout: Some(Duration::from_secs(3)),
+                    grace_period: Duration::from_secs(1),
+                    configured: true,
+                }]
+        );
+        let lines = summary.text_lines(LifeCyclePhase::PrePush).join("\n");
+        assert!(lines.contains("sequential command #1: timed out after 3s; stopped by SIGKILL"));
+        assert!(
+            summary.error().unwrap().to_string()
+                == "Hook execution timed out after 3s and was stopped by SIGKILL"
+        );
+    }
+
+    #[test]
+    fn given_hook_without_limits_when_running_then_default_grace_period_is_used() {
+        let runner = FakeRunner::with_default_outcomes(vec![PlannedResult::Exit(Some(0))]);
+        let hooks = vec![HookDefinition {
+            command: "quick-hook".to_string(),
+            ..Default::default()
+        }];
+
+        let _ = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(runner.termination_calls() == vec![TerminationPolicy::default()]);
+        assert!(TerminationPolicy::default().grace_period == DEFAULT_GRACE_PERIOD);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_hook_ignoring_sigterm_when_timed_out_then_it_is_killed_after_grace_period() {
+        let platform = Platform::Unix;
+        let runner = PlatformCommandRunner {
+            platform: &platform,
+        };
+        let hooks = vec![HookDefinition {
+            command: "trap '' TERM; sleep 30".to_string(),
+            timeout: Some(HookTimeout::Seconds(1)),
+            grace_period: Some(0),
+            ..Default::default()
+        }];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(matches!(
+            summary.error(),
+            Some(Error::ExecutionStopped {
+                reason: StopReason::Timeout(_),
+                signal: StopSignal::Kill,
+            })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_hook_with_cleanup_trap_when_timed_out_then_sigterm_lets_it_exit() {
+        let platform = Platform::Unix;
+        let runner = PlatformCommandRunner {
+            platform: &platform,
+        };
+        let hooks = vec![HookDefinition {
+            command: "trap 'exit 0' TERM; sleep 30 & wait".to_string(),
+            timeout: Some(HookTimeout::Seconds(1)),
+            grace_period: Some(10),
+            ..Default::default()
+        }];
+
+        let summary = run_hooks_with_runner_with_summary(&hooks, &runner, &[], None);
+
+        assert!(matches!(
+            summary.error(),
+            Some(Error::ExecutionStopped {
+                reason: StopReason::Timeout(_),
+                signal: StopSignal::Terminate,
+            })
+        ));
+        assert!(summary.total_duration < Duration::from_secs(10));
+    }
+
     #[test]
     fn given_multiple_commands_when_parallel_execution_then_execution_succeeds() {
         let mut hooks_map = HashMap::new();
@@ -775,10 +3602,17 @@ mod tests {
                 .map(|command| HookDefinition {
                     command: command.to_string(),
                     parallel_execution_allowed: true,
+                    ..Default::default()
                 })
                 .collect(),
         );
-        let config = SmeeConfig { hooks: hooks_map };
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
         let runner = FakeRunner::with_command_outcomes(vec![
             ("parallel-1", vec![PlannedResult::Exit(Some(0))]),
             ("parallel-2", vec![PlannedResult::Exit(Some(0))]),
@@ -811,19 +3645,28 @@ mod tests {
             .map(|command| HookDefinition {
                 command: command.to_string(),
                 parallel_execution_allowed: true,
+                ..Default::default()
             })
             .collect();
         hook_definitions.push(HookDefinition {
             command: "sequential-1".to_string(),
             parallel_execution_allowed: false,
+            ..Default::default()
         });
         hook_definitions.push(HookDefinition {
             command: "sequential-2".to_string(),
             parallel_execution_allowed: false,
+            ..Default::default()
         });
 
         hooks_map.insert(LifeCyclePhase::PreCommit, hook_definitions);
-        let config = SmeeConfig { hooks: hooks_map };
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
         let runner = FakeRunner::with_command_outcomes(vec![
             ("sequential-1", vec![PlannedResult::Exit(Some(0))]),
             ("sequential-2", vec![PlannedResult::Exit(Some(0))]),
@@ -859,10 +3702,12 @@ mod tests {
             HookDefinition {
                 command: "sequential".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             },
             HookDefinition {
                 command: "parallel".to_string(),
                 parallel_execution_allowed: true,
+                ..Default::default()
             },
         ];
         let runner = FakeRunner::with_command_outcomes(vec![
@@ -883,14 +3728,17 @@ mod tests {
             HookDefinition {
                 command: "sequential".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             },
             HookDefinition {
                 command: "parallel-ok".to_string(),
                 parallel_execution_allowed: true,
+                ..Default::default()
             },
             HookDefinition {
                 command: "parallel-fail".to_string(),
                 parallel_execution_allowed: true,
+                ..Default::default()
             },
         ];
         let runner = FakeRunner::with_command_outcomes(vec![
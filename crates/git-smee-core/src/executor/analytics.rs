@@ -0,0 +1,99 @@
+//! Local-only analytics: run counts and durations for `git smee stats` and
+//! the metrics file.
+//!
+//! When the git config sets `smee.analytics = true`, or `smee.metricsFile`,
+//! every command git-smee runs appends one JSON line to
+//! `.git/smee/analytics.jsonl` with its phase, hook label, start time,
+//! duration, and status. Unlike the audit log it holds no command lines,
+//! directories, or environment. git-smee never sends it anywhere; it stays in
+//! the clone until pruned with the rest of `.git/smee` or removed with
+//! `git smee stats reset`.
+
+use std::{path::Path, time::UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config::HookDefinition, repository};
+
+use super::{Error, hook_label, metrics, store::RecordLog, summary::HookRunSummary};
+
+/// Git config key that turns the analytics store on.
+pub const ANALYTICS_KEY: &str = "smee.analytics";
+
+/// Location of the analytics store, relative to the git directory.
+pub const ANALYTICS_GIT_PATH: &str = "smee/analytics.jsonl";
+
+/// One line of the analytics store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalyticsRecord {
+    pub started_at_unix_ms: u64,
+    /// Phase name, or `task:<name>` for a task.
+    pub phase: String,
+    pub hook: String,
+    pub duration_ms: u64,
+    /// Audit status of the run, e.g. `success` or `exit`.
+    pub status: String,
+}
+
+/// The repository's analytics store, whether or not it is turned on.
+pub fn analytics_log(repository_root: &Path) -> Result<RecordLog, repository::Error> {
+    repository::resolve_git_path(repository_root, ANALYTICS_GIT_PATH).map(RecordLog::at)
+}
+
+/// Returns where analytics records go, or `None` when the store is off. With
+/// `required` it is kept regardless of `smee.analytics`, because the metrics
+/// file is computed from it.
+pub(super) fn enabled_analytics_log(
+    repository_root: &Path,
+    required: bool,
+) -> Result<Option<RecordLog>, repository::Error> {
+    if !required && repository::git_config_bool(repository_root, ANALYTICS_KEY)? != Some(true) {
+        return Ok(None);
+    }
+    analytics_log(repository_root).map(Some)
+}
+
+/// Builds one record per command that was attempted in `summary`.
+pub(super) fn analytics_records(
+    phase: &str,
+    hooks: &[HookDefinition],
+    summary: &HookRunSummary,
+) -> Vec<AnalyticsRecord> {
+    summary
+        .command_runs()
+        .iter()
+        .filter_map(|run| {
+            let hook = hooks.get(run.config_index)?;
+            Some(AnalyticsRecord {
+                started_at_unix_ms: run
+                    .started_at
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_millis() as u64),
+                phase: phase.to_string(),
+                hook: hook_label(run.config_index, hook),
+                duration_ms: run.duration.as_millis() as u64,
+                status: run.outcome.kind().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Removes the repository's analytics store and returns how many records it
+/// held. A configured metrics file is rewritten, now without runs.
+pub fn reset_analytics(repository_root: &Path) -> Result<usize, Error> {
+    let log = analytics_log(repository_root)?;
+    let write_error = |source| Error::AnalyticsWriteFailed {
+        path: log.path().display().to_string(),
+        source,
+    };
+    let removed = log.reset().map_err(write_error)?;
+    if let Some(metrics_file) = metrics::metrics_file_path(repository_root)? {
+        metrics::write_metrics_file(log.path(), &metrics_file).map_err(|source| {
+            Error::MetricsWriteFailed {
+                path: metrics_file.display().to_string(),
+                source,
+            }
+        })?;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,76 @@
+//! Per-command directories for outputs worth keeping after a run, such as
+//! coverage reports or lint JSON.
+//!
+//! A command with `artifacts_dir = true` gets
+//! `.git/smee/artifacts/<run-id>/<phase>/<hook>/`, exported to it as
+//! [`ARTIFACTS_DIR_ENV`]. Unlike `isolate_tmp` directories they are kept after
+//! the command finishes; only the most recent [`KEPT_ARTIFACT_RUNS`] runs are,
+//! older ones are pruned when a new run creates its directory.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::config::HookDefinition;
+
+/// Location of the artifacts directories, relative to the git directory.
+pub const ARTIFACTS_GIT_PATH: &str = "smee/artifacts";
+
+/// Env var pointing a command at its artifacts directory.
+pub const ARTIFACTS_DIR_ENV: &str = "SMEE_ARTIFACTS_DIR";
+
+/// Runs whose artifacts are kept; older runs are removed.
+pub const KEPT_ARTIFACT_RUNS: usize = 10;
+
+pub(super) fn any_hook_keeps_artifacts(hooks: &[HookDefinition]) -> bool {
+    hooks.iter().any(|hook| hook.artifacts_dir)
+}
+
+/// Returns the directory of `run_name`, e.g. `pre-commit`, in run `run_id`,
+/// after pruning the runs beyond the most recent [`KEPT_ARTIFACT_RUNS`].
+/// Pruning is best effort: a run directory that cannot be removed stays.
+pub(super) fn run_artifacts_root(artifacts_root: &Path, run_id: &str, run_name: &str) -> PathBuf {
+    let run_dir = artifacts_root.join(run_id);
+    let mut earlier_runs: Vec<_> = fs::read_dir(artifacts_root)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path() != run_dir && entry.path().is_dir())
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+        .collect();
+    earlier_runs.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+    for (path, _) in earlier_runs.into_iter().skip(KEPT_ARTIFACT_RUNS - 1) {
+        let _ = fs::remove_dir_all(path);
+    }
+    run_dir.join(run_name)
+}
+
+/// Creates the artifacts directory of the command labelled `label`. Files a
+/// resumed run left there are kept.
+///
+/// Characters other than ASCII letters, digits, `-`, and `_` in the label are
+/// replaced with `_`, so a `name` cannot point outside the run directory.
+pub(super) fn create_hook_dir(run_root: &Path, label: &str) -> io::Result<PathBuf> {
+    let dir_name: String = label
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path = run_root.join(dir_name);
+    fs::create_dir_all(&path).map_err(|error| {
+        io::Error::new(
+            error.kind(),
+            format!(
+                "failed to create artifacts directory {}: {error}",
+                path.display()
+            ),
+        )
+    })?;
+    Ok(path)
+}
@@ -0,0 +1,77 @@
+//! Opt-in record of what each hook actually executed.
+//!
+//! When the repository's git config sets `smee.audit = true`, every command
+//! git-smee runs appends one JSON line to `.git/smee/audit.jsonl` with the
+//! resolved (redacted) command, working directory, injected env keys, duration,
+//! and exit status. Run counts and durations for `git smee stats` are kept
+//! separately, in the analytics store.
+
+use std::{path::Path, time::UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::{config::HookDefinition, redaction::redact_secret_values, repository};
+
+use super::{hook_label, runner::injected_env_keys, store::RecordLog, summary::HookRunSummary};
+
+/// Git config key that turns the audit log on.
+pub const AUDIT_KEY: &str = "smee.audit";
+
+/// Location of the audit log, relative to the git directory.
+pub const AUDIT_LOG_GIT_PATH: &str = "smee/audit.jsonl";
+
+/// One line of the audit log.
+#[derive(Debug, Serialize)]
+pub(super) struct AuditRecord {
+    started_at_unix_ms: u64,
+    phase: String,
+    hook: String,
+    command: String,
+    cwd: String,
+    env_keys: Vec<String>,
+    duration_ms: u64,
+    status: &'static str,
+    exit_code: Option<i32>,
+    severity: &'static str,
+}
+
+/// Returns where audit records go, or `None` when auditing is off.
+pub(super) fn audit_log(repository_root: &Path) -> Result<Option<RecordLog>, repository::Error> {
+    if repository::git_config_bool(repository_root, AUDIT_KEY)? != Some(true) {
+        return Ok(None);
+    }
+    repository::resolve_git_path(repository_root, AUDIT_LOG_GIT_PATH)
+        .map(|path| Some(RecordLog::at(path)))
+}
+
+/// Builds one record per command that was attempted in `summary`.
+pub(super) fn audit_records(
+    phase: &str,
+    hooks: &[HookDefinition],
+    summary: &HookRunSummary,
+    hook_arg_count: usize,
+    cwd: &Path,
+) -> Vec<AuditRecord> {
+    summary
+        .command_runs()
+        .iter()
+        .filter_map(|run| {
+            let hook = hooks.get(run.config_index)?;
+            Some(AuditRecord {
+                started_at_unix_ms: run
+                    .started_at
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_millis() as u64),
+                phase: phase.to_string(),
+                hook: hook_label(run.config_index, hook),
+                command: redact_secret_values(&hook.command),
+                cwd: cwd.display().to_string(),
+                env_keys: injected_env_keys(hook_arg_count, hook),
+                duration_ms: run.duration.as_millis() as u64,
+                status: run.outcome.kind(),
+                exit_code: run.exit_code(),
+                severity: run.severity.as_str(),
+            })
+        })
+        .collect()
+}
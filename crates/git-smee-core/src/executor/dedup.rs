@@ -0,0 +1,75 @@
+//! Session memo that keeps one invocation from running the same command twice
+//! when it runs several phases, e.g. `cargo fmt --check` configured in both
+//! `pre-commit` and `pre-push` and run with `git smee run pre-commit pre-push`.
+//!
+//! A command is skipped as deduplicated when an identical one already passed
+//! in an earlier phase of the session. Identical means the same command text,
+//! `no_shell` and `network` settings, and hook arguments. Duplicates within a
+//! single phase still run; the config asked for them explicitly.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::config::HookDefinition;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CommandKey {
+    command: String,
+    no_shell: bool,
+    network: Option<bool>,
+    hook_args: Vec<String>,
+}
+
+impl CommandKey {
+    fn new(hook: &HookDefinition, hook_args: &[String]) -> Self {
+        Self {
+            command: hook.command.trim().to_string(),
+            no_shell: hook.no_shell,
+            network: hook.network,
+            hook_args: hook_args.to_vec(),
+        }
+    }
+}
+
+/// Commands that passed during the session, with the run they passed in.
+#[derive(Debug, Default)]
+pub(super) struct CommandMemo {
+    passed: Mutex<HashMap<CommandKey, String>>,
+}
+
+impl CommandMemo {
+    /// The earlier run, e.g. `pre-commit`, in which an identical command
+    /// already passed.
+    pub(super) fn passed_before(
+        &self,
+        hook: &HookDefinition,
+        hook_args: &[String],
+        run_name: &str,
+    ) -> Option<String> {
+        self.lock()
+            .get(&CommandKey::new(hook, hook_args))
+            .filter(|passed_in| passed_in.as_str() != run_name)
+            .cloned()
+    }
+
+    /// Remembers the first run a command passed in.
+    pub(super) fn record_passed(
+        &self,
+        hook: &HookDefinition,
+        hook_args: &[String],
+        run_name: &str,
+    ) {
+        self.lock()
+            .entry(CommandKey::new(hook, hook_args))
+            .or_insert_with(|| run_name.to_string());
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<CommandKey, String>> {
+        match self.passed.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Live progress of a run as JSON lines, for IDE plugins and GUI clients that
+//! start commits from their own UI and want to show which hook is running.
+//!
+//! The client listens on a Unix domain socket (a named pipe such as
+//! `\\.\pipe\my-ide` on Windows) and git-smee connects to it when a run starts.
+//! Every event is one JSON object on its own line, tagged by `event`:
+//!
+//! ```text
+//! {"event":"phase_started","run":"pre-commit","commands":2}
+//! {"event":"command_started","run":"pre-commit","index":0,"label":"fmt","command":"cargo <args redacted>","parallel":false}
+//! {"event":"command_finished","run":"pre-commit","index":0,"label":"fmt","outcome":"success","blocking":false,"duration_ms":812}
+//! ```
+//!
+//! Events are best effort: when the client goes away, further events are
+//! dropped and the run carries on.
+
+use std::{
+    fmt, io,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::Serialize;
+
+/// Env var naming the socket to send events to, for runs started by Git.
+pub const EVENTS_SOCKET_ENV: &str = "GIT_SMEE_EVENTS_SOCKET";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Event {
+    RunStarted {
+        /// ID of a resumable run, see [`super::RunJournal`].
+        #[serde(skip_serializing_if = "Option::is_none")]
+        run_id: Option<String>,
+        phases: Vec<String>,
+    },
+    /// A phase or task started. `run` is the phase name or `task:<name>`.
+    PhaseStarted {
+        run: String,
+        commands: usize,
+    },
+    CommandStarted {
+        run: String,
+        /// Position of the entry in the phase's config, from 0.
+        index: usize,
+        label: String,
+        /// The command with secrets redacted.
+        command: String,
+        parallel: bool,
+    },
+    CommandFinished {
+        run: String,
+        index: usize,
+        label: String,
+        /// Machine-readable outcome such as `success`, `exit`, or `stopped`.
+        outcome: String,
+        /// Whether the outcome fails the phase.
+        blocking: bool,
+        duration_ms: u64,
+    },
+    PhaseFinished {
+        run: String,
+        success: bool,
+        duration_ms: u64,
+    },
+    RunFinished {
+        success: bool,
+    },
+}
+
+/// Connection events are written to.
+pub struct EventSink {
+    path: Option<PathBuf>,
+    writer: Mutex<Option<Box<dyn Write + Send>>>,
+}
+
+impl fmt::Debug for EventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventSink")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EventSink {
+    /// Connects to the socket or named pipe at `path`, which the client must
+    /// already be listening on.
+    pub fn connect(path: &Path) -> io::Result<Self> {
+        #[cfg(unix)]
+        let writer = std::os::unix::net::UnixStream::connect(path)?;
+        #[cfg(not(unix))]
+        let writer = std::fs::OpenOptions::new().write(true).open(path)?;
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            writer: Mutex::new(Some(Box::new(writer))),
+        })
+    }
+
+    /// Sends events to `writer`, e.g. an in-memory buffer in tests.
+    pub fn from_writer(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            path: None,
+            writer: Mutex::new(Some(Box::new(writer))),
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Writes `event` as one JSON line. After the first failed write the
+    /// connection is dropped and later events are discarded.
+    pub fn emit(&self, event: &Event) {
+        let Ok(mut line) = serde_json::to_vec(event) else {
+            return;
+        };
+        line.push(b'\n');
+        let mut writer = match self.writer.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(connection) = writer.as_mut()
+            && connection
+                .write_all(&line)
+                .and_then(|()| connection.flush())
+                .is_err()
+        {
+            *writer = None;
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Dry evaluation of a phase: which of its commands a run would start right
+//! now, and why the others would be skipped or fail, without running any of
+//! them.
+
+use std::path::Path;
+
+use crate::{
+    SmeeConfig,
+    config::{CalendarDate, HookDefinition, LifeCyclePhase, MissingRequirement, NestedRuns},
+    redaction::redact_command,
+};
+
+use super::{ACTIVE_RUN_ENV, Error, active_run, hook_label, probe};
+
+/// What a run of a phase would do in the current repository state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseExplanation {
+    pub phase: LifeCyclePhase,
+    /// Why the whole phase would not run, if it would not.
+    pub phase_verdict: Option<Verdict>,
+    /// One entry per configured command, task references expanded.
+    pub commands: Vec<CommandExplanation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandExplanation {
+    /// `name` of the entry, or `hook-<n>`.
+    pub label: String,
+    /// The command with secrets redacted.
+    pub command: String,
+    pub verdict: Verdict,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Would run. `note` qualifies how, e.g. that failures only warn.
+    Run { note: Option<String> },
+    /// Would not run and would not fail the phase.
+    Skip { reason: String },
+    /// Would fail without running.
+    Fail { reason: String },
+}
+
+/// Evaluates `phase` against the repository at `repository_root`: the phase
+/// toggles, [`ACTIVE_RUN_ENV`], the machine policy, and each command's
+/// `requires`, `severity`, and `enforce_after`.
+pub fn explain_phase(
+    smee_config: &SmeeConfig,
+    phase: LifeCyclePhase,
+    repository_root: &Path,
+) -> Result<PhaseExplanation, Error> {
+    let hooks = smee_config.phase_entries(phase).unwrap_or_default();
+    let phase_verdict = if hooks.is_empty() {
+        Some(Verdict::Skip {
+            reason: format!("the config has no {phase} entries"),
+        })
+    } else if let Some(disabled_by) = crate::toggle::disabled_by(repository_root, phase)? {
+        Some(Verdict::Skip {
+            reason: format!(
+                "disabled by git config {disabled_by} (run git smee enable to re-enable)"
+            ),
+        })
+    } else if let Some(active) = active_run() {
+        match smee_config.settings.nested_runs {
+            NestedRuns::Skip => Some(Verdict::Skip {
+                reason: format!(
+                    "running inside the {active} hooks ({ACTIVE_RUN_ENV} is set) and settings.nested_runs is \"skip\""
+                ),
+            }),
+            NestedRuns::Fail => Some(Verdict::Fail {
+                reason: format!(
+                    "running inside the {active} hooks ({ACTIVE_RUN_ENV} is set) and settings.nested_runs is \"fail\""
+                ),
+            }),
+            NestedRuns::Allow => None,
+        }
+    } else {
+        None
+    };
+    let policy = crate::policy::CommandPolicy::load_machine_policy()?;
+    let today = CalendarDate::today_utc();
+    let commands = hooks
+        .iter()
+        .enumerate()
+        .map(|(config_index, hook)| CommandExplanation {
+            label: hook_label(config_index, hook),
+            command: redact_command(&hook.command),
+            verdict: command_verdict(hook, policy.as_ref(), today),
+        })
+        .collect();
+    Ok(PhaseExplanation {
+        phase,
+        phase_verdict,
+        commands,
+    })
+}
+
+pub(super) fn command_verdict(
+    hook: &HookDefinition,
+    policy: Option<&crate::policy::CommandPolicy>,
+    today: CalendarDate,
+) -> Verdict {
+    if let Some(Err(error)) = policy.map(|policy| policy.check_command(&hook.command)) {
+        return Verdict::Fail {
+            reason: error.to_string(),
+        };
+    }
+    let unmet = hook.requires.iter().find_map(|requirement| {
+        probe::unmet_reason(requirement).map(|reason| format!("requires {requirement}: {reason}"))
+    });
+    if let Some(unmet) = unmet {
+        return match hook.on_missing {
+            MissingRequirement::Fail => Verdict::Fail { reason: unmet },
+            MissingRequirement::Skip => Verdict::Skip {
+                reason: format!("{unmet} (on_missing = \"skip\")"),
+            },
+        };
+    }
+    let note = match hook.enforce_after {
+        Some(enforce_after) if hook.severity.is_error() && today < enforce_after => Some(format!(
+            "failures only warn until {enforce_after} (enforce_after)"
+        )),
+        _ if !hook.severity.is_error() => {
+            Some("failures only warn (severity = \"warning\")".to_string())
+        }
+        _ => None,
+    };
+    Verdict::Run { note }
+}
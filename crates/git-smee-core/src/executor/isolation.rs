@@ -0,0 +1,59 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::config::HookDefinition;
+
+/// Git path (relative to the git dir) under which isolated temp dirs live.
+pub const ISOLATED_TMP_GIT_PATH: &str = "smee/tmp";
+
+/// Returns the per-run root for isolated temp dirs, e.g.
+/// `.git/smee/tmp/pre-commit` or `.git/smee/tmp/task-lint-all`.
+pub(super) fn phase_tmp_root(smee_tmp_root: &Path, run_name: &str) -> PathBuf {
+    smee_tmp_root.join(run_name)
+}
+
+pub(super) fn any_hook_isolates_tmp(hooks: &[HookDefinition]) -> bool {
+    hooks.iter().any(|hook| hook.isolate_tmp)
+}
+
+/// A private `TMPDIR` for one hook command, removed again when dropped.
+pub(super) struct IsolatedTmpDir {
+    path: PathBuf,
+}
+
+impl IsolatedTmpDir {
+    /// Creates an empty directory for the hook at `config_index` (zero-based)
+    /// below `phase_root`. Leftovers from an interrupted earlier run are
+    /// discarded first so every run starts clean.
+    pub(super) fn create(phase_root: &Path, config_index: usize) -> io::Result<Self> {
+        let path = phase_root.join(format!("hook-{}", config_index + 1));
+        let prepare = || {
+            if path.exists() {
+                fs::remove_dir_all(&path)?;
+            }
+            fs::create_dir_all(&path)
+        };
+        prepare().map_err(|error| {
+            io::Error::new(
+                error.kind(),
+                format!(
+                    "failed to create isolated TMPDIR {}: {error}",
+                    path.display()
+                ),
+            )
+        })?;
+        Ok(Self { path })
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for IsolatedTmpDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,240 @@
+//! Persistent progress of a `git smee run` invocation, so an interrupted run
+//! (Ctrl-C, a timeout, a failure) can be resumed without repeating the
+//! commands that already passed.
+//!
+//! Each run gets a run ID and a state file at `.git/smee/runs/<run-id>.json`.
+//! The file is rewritten whenever a command passes and removed once every
+//! phase of the run has succeeded.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config::LifeCyclePhase, repository};
+
+use super::Error;
+
+/// Location of run state files, relative to the git directory.
+pub const RUNS_GIT_PATH: &str = "smee/runs";
+
+/// State files older than this are removed when a new run starts.
+const STALE_RUN_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RunState {
+    run_id: String,
+    phases: Vec<LifeCyclePhase>,
+    hook_args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stdin: Option<Vec<u8>>,
+    /// Commands that passed, per phase. The command text is kept next to its
+    /// position so an edited config does not skip a different command.
+    #[serde(default)]
+    passed: HashMap<LifeCyclePhase, Vec<PassedCommand>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PassedCommand {
+    config_index: usize,
+    command: String,
+}
+
+/// Progress of one run, shared by every phase it executes.
+#[derive(Debug)]
+pub struct RunJournal {
+    path: PathBuf,
+    state: Mutex<RunState>,
+}
+
+impl RunJournal {
+    /// Starts a new run with a fresh run ID and writes its initial state.
+    pub fn start(
+        repository_root: &Path,
+        phases: &[LifeCyclePhase],
+        hook_args: &[String],
+        stdin_payload: Option<&[u8]>,
+    ) -> Result<Self, Error> {
+        let runs_dir = repository::resolve_git_path(repository_root, RUNS_GIT_PATH)?;
+        remove_stale_runs(&runs_dir);
+        let run_id = new_run_id();
+        let journal = Self {
+            path: state_path(&runs_dir, &run_id),
+            state: Mutex::new(RunState {
+                run_id,
+                phases: phases.to_vec(),
+                hook_args: hook_args.to_vec(),
+                stdin: stdin_payload.map(<[u8]>::to_vec),
+                passed: HashMap::new(),
+            }),
+        };
+        journal.save()?;
+        Ok(journal)
+    }
+
+    /// Opens the state of an earlier run. Without a `run_id`, the most recent
+    /// unfinished run is opened.
+    pub fn open(repository_root: &Path, run_id: Option<&str>) -> Result<Self, Error> {
+        let runs_dir = repository::resolve_git_path(repository_root, RUNS_GIT_PATH)?;
+        let path = match run_id {
+            Some(run_id) if !is_valid_run_id(run_id) => {
+                return Err(Error::RunNotFound(run_id.to_string()));
+            }
+            Some(run_id) => state_path(&runs_dir, run_id),
+            None => latest_state_path(&runs_dir).ok_or(Error::NoRunToResume)?,
+        };
+        let contents = fs::read(&path).map_err(|source| match source.kind() {
+            io::ErrorKind::NotFound => Error::RunNotFound(
+                run_id.map_or_else(|| path.display().to_string(), str::to_string),
+            ),
+            _ => Error::RunStateAccessFailed {
+                path: path.display().to_string(),
+                source,
+            },
+        })?;
+        let state =
+            serde_json::from_slice(&contents).map_err(|source| Error::RunStateAccessFailed {
+                path: path.display().to_string(),
+                source: source.into(),
+            })?;
+        Ok(Self {
+            path,
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn run_id(&self) -> String {
+        self.lock().run_id.clone()
+    }
+
+    pub fn phases(&self) -> Vec<LifeCyclePhase> {
+        self.lock().phases.clone()
+    }
+
+    pub fn hook_args(&self) -> Vec<String> {
+        self.lock().hook_args.clone()
+    }
+
+    pub fn stdin_payload(&self) -> Option<Vec<u8>> {
+        self.lock().stdin.clone()
+    }
+
+    /// Whether `command` at `config_index` already passed in this run.
+    pub fn has_passed(&self, phase: LifeCyclePhase, config_index: usize, command: &str) -> bool {
+        self.lock().passed.get(&phase).is_some_and(|passed| {
+            passed
+                .iter()
+                .any(|entry| entry.config_index == config_index && entry.command == command)
+        })
+    }
+
+    /// Removes the state file once the whole run has succeeded.
+    pub fn finish(self) -> Result<(), Error> {
+        match fs::remove_file(&self.path) {
+            Err(source) if source.kind() != io::ErrorKind::NotFound => {
+                Err(Error::RunStateAccessFailed {
+                    path: self.path.display().to_string(),
+                    source,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn at_path(path: PathBuf, phases: &[LifeCyclePhase]) -> Self {
+        Self {
+            path,
+            state: Mutex::new(RunState {
+                run_id: new_run_id(),
+                phases: phases.to_vec(),
+                hook_args: Vec::new(),
+                stdin: None,
+                passed: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Records a passed command. Progress is best effort: a state file that
+    /// cannot be written only means a later resume repeats the command.
+    pub(super) fn record_passed(&self, phase: LifeCyclePhase, config_index: usize, command: &str) {
+        self.lock()
+            .passed
+            .entry(phase)
+            .or_default()
+            .push(PassedCommand {
+                config_index,
+                command: command.to_string(),
+            });
+        let _ = self.save();
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let write = || {
+            let contents = serde_json::to_vec_pretty(&*self.lock())?;
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&self.path, contents)
+        };
+        write().map_err(|source| Error::RunStateAccessFailed {
+            path: self.path.display().to_string(),
+            source,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RunState> {
+        match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+pub(super) fn new_run_id() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    format!("{now:x}-{:x}", std::process::id())
+}
+
+pub(super) fn is_valid_run_id(run_id: &str) -> bool {
+    !run_id.is_empty() && run_id.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+fn state_path(runs_dir: &Path, run_id: &str) -> PathBuf {
+    runs_dir.join(format!("{run_id}.json"))
+}
+
+fn state_files(runs_dir: &Path) -> impl Iterator<Item = (PathBuf, SystemTime)> {
+    fs::read_dir(runs_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+}
+
+fn latest_state_path(runs_dir: &Path) -> Option<PathBuf> {
+    state_files(runs_dir)
+        .max_by_key(|(_, modified)| *modified)
+        .map(|(path, _)| path)
+}
+
+fn remove_stale_runs(runs_dir: &Path) {
+    let now = SystemTime::now();
+    for (path, modified) in state_files(runs_dir) {
+        if now
+            .duration_since(modified)
+            .is_ok_and(|age| age > STALE_RUN_AGE)
+        {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Named mutexes for hooks that share an expensive resource, e.g. several
+//! `cargo` commands fighting over the same `target/` directory lock.
+//!
+//! Commands with the same `mutex` name never run at the same time: within a
+//! run the scheduler serializes them, and across concurrent git-smee processes
+//! an advisory file lock under `.git/smee/locks/<name>.lock` does. The file
+//! lock is only available on Unix; elsewhere the mutex covers a single run.
+//!
+//! Concurrency groups generalize this within a run: at most the limit of a
+//! group (`[settings.groups]`) of its commands run at the same time, e.g. many
+//! IO-bound checks side by side but only one CPU-heavy build.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+    time::Duration,
+};
+
+use super::termination::cancellation_requested;
+
+/// Location of the lock files, relative to the git directory.
+pub const MUTEX_LOCK_GIT_PATH: &str = "smee/locks";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The mutexes of one run.
+#[derive(Debug, Default)]
+pub(super) struct HookMutexes {
+    /// Directory for cross-process lock files. Without one, mutexes only
+    /// serialize commands within this run.
+    lock_dir: Option<PathBuf>,
+    held: Mutex<HashSet<String>>,
+}
+
+impl HookMutexes {
+    pub(super) fn new(lock_dir: Option<PathBuf>) -> Self {
+        Self {
+            lock_dir,
+            held: Mutex::default(),
+        }
+    }
+
+    /// Blocks until `name` is free in this run and in every other git-smee
+    /// process of the repository. Waiting is abandoned on cancellation.
+    pub(super) fn acquire(&self, name: &str) -> io::Result<HeldMutex<'_>> {
+        let mut announced = false;
+        let what = format!("mutex '{name}'");
+        while !self.lock_held().insert(name.to_string()) {
+            announce_wait(name, &mut announced);
+            wait_or_cancel(&what)?;
+        }
+        // Built before the file lock so an error below releases the name again.
+        let mut held = HeldMutex {
+            mutexes: self,
+            name: name.to_string(),
+            file: None,
+        };
+        let Some(lock_dir) = &self.lock_dir else {
+            return Ok(held);
+        };
+        let file = open_lock_file(lock_dir, name)?;
+        while !try_lock_file(&file)? {
+            announce_wait(name, &mut announced);
+            wait_or_cancel(&what)?;
+        }
+        held.file = Some(file);
+        Ok(held)
+    }
+
+    fn lock_held(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        match self.held.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Releases the mutex when dropped. Closing the lock file drops the file lock.
+pub(super) struct HeldMutex<'a> {
+    mutexes: &'a HookMutexes,
+    name: String,
+    file: Option<fs::File>,
+}
+
+impl Drop for HeldMutex<'_> {
+    fn drop(&mut self) {
+        self.file.take();
+        self.mutexes.lock_held().remove(&self.name);
+    }
+}
+
+/// The concurrency groups of one run and how many of their commands are
+/// running.
+#[derive(Debug, Default)]
+pub(super) struct ConcurrencyGroups {
+    limits: BTreeMap<String, usize>,
+    running: Mutex<HashMap<String, usize>>,
+}
+
+impl ConcurrencyGroups {
+    pub(super) fn new(limits: BTreeMap<String, usize>) -> Self {
+        Self {
+            limits,
+            running: Mutex::default(),
+        }
+    }
+
+    /// Blocks until fewer than the limit of `name` of its commands are
+    /// running. A group without a limit never blocks. Waiting is abandoned on
+    /// cancellation.
+    pub(super) fn acquire(&self, name: &str) -> io::Result<GroupSlot<'_>> {
+        let limit = self.limits.get(name).copied().unwrap_or(usize::MAX);
+        let what = format!("a slot in group '{name}'");
+        loop {
+            {
+                let mut running = self.lock_running();
+                let count = running.entry(name.to_string()).or_default();
+                if *count < limit {
+                    *count += 1;
+                    return Ok(GroupSlot {
+                        groups: self,
+                        name: name.to_string(),
+                    });
+                }
+            }
+            wait_or_cancel(&what)?;
+        }
+    }
+
+    fn lock_running(&self) -> std::sync::MutexGuard<'_, HashMap<String, usize>> {
+        match self.running.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Frees the slot in its group when dropped.
+pub(super) struct GroupSlot<'a> {
+    groups: &'a ConcurrencyGroups,
+    name: String,
+}
+
+impl Drop for GroupSlot<'_> {
+    fn drop(&mut self) {
+        if let Some(count) = self.groups.lock_running().get_mut(&self.name) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+fn announce_wait(name: &str, announced: &mut bool) {
+    if !*announced {
+        eprintln!("git-smee: waiting for mutex '{name}' held by another hook");
+        *announced = true;
+    }
+}
+
+fn wait_or_cancel(what: &str) -> io::Result<()> {
+    if cancellation_requested() {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            format!("cancelled while waiting for {what}"),
+        ));
+    }
+    thread::sleep(POLL_INTERVAL);
+    Ok(())
+}
+
+fn open_lock_file(lock_dir: &Path, name: &str) -> io::Result<fs::File> {
+    fs::create_dir_all(lock_dir)?;
+    fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_dir.join(format!("{name}.lock")))
+}
+
+#[cfg(unix)]
+fn try_lock_file(file: &fs::File) -> io::Result<bool> {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: `flock` only reads the descriptor, which `file` keeps open.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    let error = io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::EWOULDBLOCK) => Ok(false),
+        _ => Err(error),
+    }
+}
+
+#[cfg(not(unix))]
+fn try_lock_file(_file: &fs::File) -> io::Result<bool> {
+    Ok(true)
+}
@@ -0,0 +1,240 @@
+//! Hook statistics aggregated from the analytics store, for `git smee stats`
+//! and the Prometheus text file that shared CI runners can scrape.
+//!
+//! When the git config sets `smee.metricsFile`, every run also records to the
+//! analytics store and then rewrites that file in the Prometheus text
+//! exposition format, e.g. for node_exporter's textfile collector.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::repository;
+
+/// Git config key naming the file metrics are written to after each run.
+pub const METRICS_FILE_KEY: &str = "smee.metricsFile";
+
+/// Upper bounds, in seconds, of the duration histogram buckets.
+pub const DURATION_BUCKETS: [f64; 10] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// Statuses of audit records that are neither a failure nor a run.
+const NOT_RUN_STATUSES: [&str; 5] = [
+    "prerequisite_skipped",
+    "deduplicated",
+    "opted_out",
+    "no_changed_files",
+    "budget_skipped",
+];
+const PASSING_STATUSES: [&str; 2] = ["success", "accepted_exit"];
+
+/// The part of an analytics or audit record the statistics use.
+#[derive(Debug, Deserialize)]
+struct RecordedRun {
+    phase: String,
+    hook: String,
+    duration_ms: u64,
+    status: String,
+}
+
+/// Statistics of one command, identified by its phase and hook label.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HookStats {
+    pub phase: String,
+    pub hook: String,
+    /// Runs by audit status, e.g. `success` or `exit`.
+    pub statuses: BTreeMap<String, u64>,
+    pub failures: u64,
+    /// Runs that executed; skipped runs have no duration.
+    pub executed: u64,
+    #[serde(serialize_with = "serialize_millis")]
+    pub total_duration: Duration,
+    #[serde(serialize_with = "serialize_millis")]
+    pub max_duration: Duration,
+    /// Executed runs per [`DURATION_BUCKETS`] bound, not cumulative.
+    #[serde(skip)]
+    bucket_counts: [u64; DURATION_BUCKETS.len()],
+}
+
+impl HookStats {
+    pub fn runs(&self) -> u64 {
+        self.statuses.values().sum()
+    }
+
+    pub fn mean_duration(&self) -> Duration {
+        match self.executed {
+            0 => Duration::ZERO,
+            executed => self.total_duration / executed as u32,
+        }
+    }
+
+    fn record(&mut self, entry: &RecordedRun) {
+        *self.statuses.entry(entry.status.clone()).or_default() += 1;
+        if NOT_RUN_STATUSES.contains(&entry.status.as_str()) {
+            return;
+        }
+        if !PASSING_STATUSES.contains(&entry.status.as_str()) {
+            self.failures += 1;
+        }
+        let duration = Duration::from_millis(entry.duration_ms);
+        self.executed += 1;
+        self.total_duration += duration;
+        self.max_duration = self.max_duration.max(duration);
+        if let Some(bucket) = DURATION_BUCKETS
+            .iter()
+            .position(|bound| duration.as_secs_f64() <= *bound)
+        {
+            self.bucket_counts[bucket] += 1;
+        }
+    }
+}
+
+fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+/// Statistics of every command in the analytics store.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HookStatistics {
+    pub hooks: Vec<HookStats>,
+    /// Lines that could not be read, e.g. a record cut off by a crash.
+    pub skipped_lines: usize,
+}
+
+impl HookStatistics {
+    /// Aggregates the JSON lines of the analytics store. Hooks are ordered by
+    /// phase and label.
+    pub fn from_log(contents: &str) -> Self {
+        let mut hooks: BTreeMap<(String, String), HookStats> = BTreeMap::new();
+        let mut skipped_lines = 0;
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let Ok(entry) = serde_json::from_str::<RecordedRun>(line) else {
+                skipped_lines += 1;
+                continue;
+            };
+            hooks
+                .entry((entry.phase.clone(), entry.hook.clone()))
+                .or_insert_with(|| HookStats {
+                    phase: entry.phase.clone(),
+                    hook: entry.hook.clone(),
+                    ..Default::default()
+                })
+                .record(&entry);
+        }
+        Self {
+            hooks: hooks.into_values().collect(),
+            skipped_lines,
+        }
+    }
+
+    /// Reads the analytics store at `path`; a missing store has no
+    /// statistics.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(Self::from_log(&contents)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Renders the statistics in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut text = String::new();
+        text.push_str(
+            "# HELP git_smee_hook_runs_total Hook command runs recorded in the analytics store, by status.\n\
+             # TYPE git_smee_hook_runs_total counter\n",
+        );
+        for stats in &self.hooks {
+            for (status, count) in &stats.statuses {
+                let _ = writeln!(
+                    text,
+                    "git_smee_hook_runs_total{{{},status=\"{}\"}} {count}",
+                    labels(stats),
+                    escape_label(status)
+                );
+            }
+        }
+        text.push_str(
+            "# HELP git_smee_hook_failures_total Hook command runs that failed.\n\
+             # TYPE git_smee_hook_failures_total counter\n",
+        );
+        for stats in &self.hooks {
+            let _ = writeln!(
+                text,
+                "git_smee_hook_failures_total{{{}}} {}",
+                labels(stats),
+                stats.failures
+            );
+        }
+        text.push_str(
+            "# HELP git_smee_hook_duration_seconds Wall-clock duration of hook command runs.\n\
+             # TYPE git_smee_hook_duration_seconds histogram\n",
+        );
+        for stats in &self.hooks {
+            let labels = labels(stats);
+            let mut cumulative = 0;
+            for (bound, count) in DURATION_BUCKETS.iter().zip(stats.bucket_counts) {
+                cumulative += count;
+                let _ = writeln!(
+                    text,
+                    "git_smee_hook_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                text,
+                "git_smee_hook_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}\n\
+                 git_smee_hook_duration_seconds_sum{{{labels}}} {}\n\
+                 git_smee_hook_duration_seconds_count{{{labels}}} {}",
+                stats.executed,
+                stats.total_duration.as_secs_f64(),
+                stats.executed
+            );
+        }
+        text
+    }
+}
+
+fn labels(stats: &HookStats) -> String {
+    format!(
+        "phase=\"{}\",hook=\"{}\"",
+        escape_label(&stats.phase),
+        escape_label(&stats.hook)
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Returns where metrics go after each run, or `None` when not configured.
+pub(super) fn metrics_file_path(
+    repository_root: &Path,
+) -> Result<Option<PathBuf>, repository::Error> {
+    repository::git_config_path(repository_root, METRICS_FILE_KEY)
+}
+
+/// Rewrites `metrics_file` from the analytics store. The file is replaced in
+/// one rename, so a scraper never reads it half-written.
+pub(super) fn write_metrics_file(analytics_log: &Path, metrics_file: &Path) -> io::Result<()> {
+    let text = HookStatistics::load(analytics_log)?.to_prometheus();
+    let directory = metrics_file
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(directory)?;
+    let mut temp = tempfile::NamedTempFile::new_in(directory)?;
+    io::Write::write_all(&mut temp, text.as_bytes())?;
+    temp.persist(metrics_file).map_err(|error| error.error)?;
+    Ok(())
+}
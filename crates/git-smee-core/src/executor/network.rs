@@ -0,0 +1,99 @@
+//! `network = false`: runs a hook command without network access, so tests
+//! that quietly reach the network fail during the commit check instead of
+//! flaking later in CI.
+//!
+//! - On Linux the command runs in a fresh network namespace, through `bwrap
+//!   --unshare-net` (which keeps a working loopback interface) or, without
+//!   bubblewrap, `unshare --net --map-root-user` (where even loopback is down).
+//! - Elsewhere, or where neither tool is available, the command runs with its
+//!   proxy env vars pointing at a closed local port. That stops HTTP clients
+//!   that honor them, but nothing else, so a warning says so.
+
+use std::{ffi::OsStr, path::PathBuf, process::Command};
+
+use super::probe;
+
+/// Proxy URL the best-effort block points every client at. Port 9 (discard)
+/// has nothing listening on a typical machine.
+const BLOCKING_PROXY: &str = "http://127.0.0.1:9";
+
+const PROXY_ENV_KEYS: [&str; 6] = [
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+];
+
+const NO_PROXY_ENV_KEYS: [&str; 2] = ["no_proxy", "NO_PROXY"];
+
+/// How commands with `network = false` are cut off from the network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum NetworkBlock {
+    /// `bwrap --unshare-net` with the whole file system visible.
+    Bwrap { program: PathBuf },
+    /// `unshare --net --map-root-user`.
+    Unshare { program: PathBuf },
+    /// Proxy env vars only; see the module docs.
+    ProxyEnv,
+}
+
+impl NetworkBlock {
+    /// The network namespace tool available on this machine or, when there
+    /// is none, why only the proxy env vars are set.
+    pub(super) fn detect() -> Result<Self, String> {
+        if !cfg!(target_os = "linux") {
+            return Err("network namespaces are only available on Linux".to_string());
+        }
+        if let Some(program) = probe::find_executable("bwrap") {
+            return Ok(Self::Bwrap { program });
+        }
+        probe::find_executable("unshare")
+            .map(|program| Self::Unshare { program })
+            .ok_or_else(|| "neither bwrap nor unshare is installed or on PATH".to_string())
+    }
+
+    /// Rewrites `command` to run without network access, keeping its
+    /// arguments, env, and working directory. Stdio and process settings are
+    /// left for the caller to apply to the returned command.
+    pub(super) fn apply(&self, mut command: Command) -> Command {
+        let (program, args): (&PathBuf, &[&str]) = match self {
+            Self::Bwrap { program } => (
+                program,
+                &[
+                    "--dev-bind",
+                    "/",
+                    "/",
+                    "--unshare-net",
+                    "--die-with-parent",
+                    "--",
+                ],
+            ),
+            Self::Unshare { program } => (program, &["--net", "--map-root-user", "--"]),
+            Self::ProxyEnv => {
+                for key in PROXY_ENV_KEYS {
+                    command.env(key, BLOCKING_PROXY);
+                }
+                for key in NO_PROXY_ENV_KEYS {
+                    command.env_remove(key);
+                }
+                return command;
+            }
+        };
+        let mut blocked = Command::new(program);
+        blocked.args(args.iter().map(OsStr::new));
+        blocked.arg(command.get_program());
+        blocked.args(command.get_args());
+        for (key, value) in command.get_envs() {
+            match value {
+                Some(value) => blocked.env(key, value),
+                None => blocked.env_remove(key),
+            };
+        }
+        if let Some(current_dir) = command.get_current_dir() {
+            blocked.current_dir(current_dir);
+        }
+        blocked
+    }
+}
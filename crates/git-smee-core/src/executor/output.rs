@@ -0,0 +1,234 @@
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Read, Write},
+    process::Child,
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Longest piece of a line held in memory at once. Longer lines are forwarded
+/// in pieces of this size, so a hook printing one huge line cannot exhaust
+/// memory.
+const MAX_LINE_BYTES: u64 = 64 * 1024;
+
+/// Most recent output lines kept for the report of a stopped command.
+const PARTIAL_OUTPUT_LINES: usize = 20;
+
+/// Longest line kept for that report; longer lines are cut off.
+const PARTIAL_OUTPUT_LINE_BYTES: usize = 512;
+
+/// How long a stopped command's output may take to drain. Processes that left
+/// the hook's process group can keep the pipes open indefinitely; their
+/// output is abandoned after this.
+const STOPPED_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Forwards a child's stdout and stderr line by line, optionally tagging each
+/// line with `[label] ` so output from concurrently running hooks stays
+/// attributable, and optionally capping how much of each stream is forwarded.
+pub(super) struct ForwardedOutput {
+    forwarders: Vec<JoinHandle<u64>>,
+    recent: Arc<Mutex<VecDeque<String>>>,
+}
+
+/// What forwarding observed once the command has ended.
+#[derive(Debug, Default)]
+pub(super) struct ForwardedSummary {
+    /// Bytes dropped by the `max_output_bytes` cap.
+    pub(super) truncated_bytes: u64,
+    /// Last lines forwarded before the command was stopped. Empty unless it
+    /// was stopped.
+    pub(super) partial_output: Vec<String>,
+}
+
+impl ForwardedOutput {
+    /// Starts forwarding the piped stdout/stderr of `child`. Both streams must
+    /// have been configured as [`std::process::Stdio::piped`].
+    pub(super) fn forward(child: &mut Child, label: Option<&str>, max_bytes: Option<u64>) -> Self {
+        let recent = Arc::new(Mutex::new(VecDeque::new()));
+        let mut forwarders = Vec::with_capacity(2);
+        if let Some(stdout) = child.stdout.take() {
+            let label = label.map(str::to_string);
+            let recent = Arc::clone(&recent);
+            forwarders.push(thread::spawn(move || {
+                copy_lines(stdout, label.as_deref(), max_bytes, |line| {
+                    remember_line(&recent, line);
+                    let mut out = io::stdout().lock();
+                    out.write_all(line)?;
+                    out.flush()
+                })
+                .unwrap_or_default()
+            }));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            let label = label.map(str::to_string);
+            let recent = Arc::clone(&recent);
+            forwarders.push(thread::spawn(move || {
+                copy_lines(stderr, label.as_deref(), max_bytes, |line| {
+                    remember_line(&recent, line);
+                    io::stderr().lock().write_all(line)
+                })
+                .unwrap_or_default()
+            }));
+        }
+        Self { forwarders, recent }
+    }
+
+    /// Waits until both streams reach end of file, so everything the command
+    /// printed is flushed, including a last line without a newline.
+    ///
+    /// When the command was `stopped` by a timeout or cancellation, waiting is
+    /// limited to [`STOPPED_DRAIN_TIMEOUT`] and the most recent lines are
+    /// returned as partial output.
+    pub(super) fn finish(self, stopped: bool) -> ForwardedSummary {
+        let Self { forwarders, recent } = self;
+        if stopped {
+            let started = Instant::now();
+            while started.elapsed() < STOPPED_DRAIN_TIMEOUT
+                && !forwarders.iter().all(JoinHandle::is_finished)
+            {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let truncated_bytes = forwarders
+            .into_iter()
+            .filter(|forwarder| !stopped || forwarder.is_finished())
+            .map(|forwarder| forwarder.join().unwrap_or_default())
+            .sum();
+        let partial_output = if stopped {
+            let mut recent = match recent.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            recent.drain(..).collect()
+        } else {
+            Vec::new()
+        };
+        ForwardedSummary {
+            truncated_bytes,
+            partial_output,
+        }
+    }
+}
+
+/// Keeps `line` among the last [`PARTIAL_OUTPUT_LINES`] forwarded lines.
+fn remember_line(recent: &Mutex<VecDeque<String>>, line: &[u8]) {
+    let line = &line[..line.len().min(PARTIAL_OUTPUT_LINE_BYTES)];
+    let line = String::from_utf8_lossy(line).trim_end().to_string();
+    let mut recent = match recent.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if recent.len() == PARTIAL_OUTPUT_LINES {
+        recent.pop_front();
+    }
+    recent.push_back(line);
+}
+
+/// Reads `reader` to the end and hands each line to `write_line` as a single
+/// buffer so concurrent writers never interleave within a line.
+///
+/// With a `label`, each line is prefixed with `[label] ` and terminated by a
+/// newline. With `max_bytes`, the first half of the budget is forwarded as it
+/// arrives and the last half is held back until end of file; whole lines in
+/// between are dropped and replaced by an `output truncated (N bytes)` marker.
+/// Returns the number of dropped bytes.
+pub(super) fn copy_lines(
+    reader: impl Read,
+    label: Option<&str>,
+    max_bytes: Option<u64>,
+    mut write_line: impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<u64> {
+    let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+    let at_line_start = Cell::new(true);
+    let mut emit = |line: &[u8]| {
+        let mut tagged = Vec::with_capacity(prefix.len() + line.len() + 1);
+        tagged.extend_from_slice(prefix.as_bytes());
+        tagged.extend_from_slice(line);
+        if label.is_some() && !line.ends_with(b"\n") {
+            tagged.push(b'\n');
+        }
+        at_line_start.set(tagged.ends_with(b"\n"));
+        write_line(&tagged)
+    };
+    let mut head_tail = max_bytes.map(HeadTail::new);
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if (&mut reader)
+            .take(MAX_LINE_BYTES)
+            .read_until(b'\n', &mut line)?
+            == 0
+        {
+            break;
+        }
+        let forward_now = head_tail
+            .as_mut()
+            .is_none_or(|head_tail| head_tail.admit(&line));
+        if forward_now {
+            emit(&line)?;
+        }
+    }
+    let Some(head_tail) = head_tail else {
+        return Ok(0);
+    };
+    if head_tail.dropped > 0 {
+        let marker = format!(
+            "{}... output truncated ({} bytes) ...\n",
+            if at_line_start.get() { "" } else { "\n" },
+            head_tail.dropped
+        );
+        emit(marker.as_bytes())?;
+    }
+    for line in &head_tail.tail {
+        emit(line)?;
+    }
+    Ok(head_tail.dropped)
+}
+
+/// Splits a byte budget into a head that is forwarded right away and a tail
+/// of the most recent lines that is kept until the stream ends.
+struct HeadTail {
+    head_remaining: u64,
+    tail_capacity: u64,
+    tail: VecDeque<Vec<u8>>,
+    tail_bytes: u64,
+    dropped: u64,
+}
+
+impl HeadTail {
+    fn new(max_bytes: u64) -> Self {
+        let head = max_bytes.div_ceil(2);
+        Self {
+            head_remaining: head,
+            tail_capacity: max_bytes - head,
+            tail: VecDeque::new(),
+            tail_bytes: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Whether `line` still fits the head and should be forwarded now.
+    /// Otherwise it is held in the tail, evicting the oldest tail lines that
+    /// no longer fit.
+    fn admit(&mut self, line: &[u8]) -> bool {
+        let len = line.len() as u64;
+        if self.tail.is_empty() && self.dropped == 0 && len <= self.head_remaining {
+            self.head_remaining -= len;
+            return true;
+        }
+        self.head_remaining = 0;
+        self.tail.push_back(line.to_vec());
+        self.tail_bytes += len;
+        while self.tail_bytes > self.tail_capacity {
+            let Some(evicted) = self.tail.pop_front() else {
+                break;
+            };
+            self.tail_bytes -= evicted.len() as u64;
+            self.dropped += evicted.len() as u64;
+        }
+        false
+    }
+}
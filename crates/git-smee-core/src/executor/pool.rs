@@ -0,0 +1,194 @@
+use std::sync::{Arc, OnceLock};
+
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+use crate::opt_out::OptOutList;
+
+use super::{dedup::CommandMemo, events::EventSink};
+
+/// Default prefix for the names of threads running parallel hook commands.
+pub const DEFAULT_THREAD_NAME_PREFIX: &str = "git-smee-hook";
+
+/// Settings for how hook commands are executed, most notably the thread pool
+/// that runs `parallel_execution_allowed` commands.
+///
+/// The pool is private to this configuration and is only built the first time
+/// a phase with parallel commands runs, so git-smee never touches rayon's
+/// global pool. Reuse one `ExecutorConfig` across runs to reuse its threads.
+///
+/// ```rust
+/// use git_smee_core::executor::ExecutorConfig;
+///
+/// let executor_config = ExecutorConfig::new()
+///     .with_num_threads(2)
+///     .with_thread_name_prefix("my-tool-hooks");
+/// assert_eq!(executor_config.num_threads(), Some(2));
+/// ```
+#[derive(Debug)]
+pub struct ExecutorConfig {
+    num_threads: Option<usize>,
+    thread_name_prefix: String,
+    show_commands: bool,
+    stdout_is_terminal: bool,
+    events: Option<Arc<EventSink>>,
+    simulated_failures: Vec<String>,
+    opt_out: Option<OptOutList>,
+    dedup: Option<CommandMemo>,
+    pool: OnceLock<ThreadPool>,
+}
+
+/// Clones the settings only; the clone builds its own thread pool on first use
+/// and starts a fresh deduplication session.
+impl Clone for ExecutorConfig {
+    fn clone(&self) -> Self {
+        Self {
+            num_threads: self.num_threads,
+            thread_name_prefix: self.thread_name_prefix.clone(),
+            show_commands: self.show_commands,
+            stdout_is_terminal: self.stdout_is_terminal,
+            events: self.events.clone(),
+            simulated_failures: self.simulated_failures.clone(),
+            opt_out: self.opt_out.clone(),
+            dedup: self.dedup.as_ref().map(|_| CommandMemo::default()),
+            pool: OnceLock::new(),
+        }
+    }
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExecutorConfig {
+    pub fn new() -> Self {
+        Self {
+            num_threads: None,
+            thread_name_prefix: DEFAULT_THREAD_NAME_PREFIX.to_string(),
+            show_commands: false,
+            stdout_is_terminal: true,
+            events: None,
+            simulated_failures: Vec::new(),
+            opt_out: None,
+            dedup: None,
+            pool: OnceLock::new(),
+        }
+    }
+
+    /// Bounds the number of parallel commands running at once. Unset (or zero)
+    /// lets rayon pick one thread per logical CPU.
+    pub fn with_num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = (num_threads > 0).then_some(num_threads);
+        self
+    }
+
+    /// Names pool threads `<prefix>-<index>`.
+    pub fn with_thread_name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.thread_name_prefix = prefix.into();
+        self
+    }
+
+    /// Prints the resolved command line, working directory, and injected env
+    /// (with secret-looking values redacted) to stderr before each command.
+    pub fn with_show_commands(mut self, show_commands: bool) -> Self {
+        self.show_commands = show_commands;
+        self
+    }
+
+    /// Tells the executor whether its stdout is a terminal. Unless the
+    /// config sets `settings.parallel_without_tty`, commands run sequentially
+    /// without one, so logs read by CI or GUI clients are plainly ordered.
+    /// Assumed to be a terminal by default.
+    pub fn with_stdout_terminal(mut self, stdout_is_terminal: bool) -> Self {
+        self.stdout_is_terminal = stdout_is_terminal;
+        self
+    }
+
+    /// Reports the progress of every phase and command to `events`.
+    pub fn with_event_sink(mut self, events: Arc<EventSink>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Treats the commands with these labels (`name`, or `hook-<n>`) as
+    /// failed without running them, to try out how a failure is handled and
+    /// reported.
+    pub fn with_simulated_failures(mut self, labels: Vec<String>) -> Self {
+        self.simulated_failures = labels;
+        self
+    }
+
+    /// Skips the commands on the user's opt-out list instead of running them.
+    pub fn with_opt_out(mut self, opt_out: OptOutList) -> Self {
+        self.opt_out = Some(opt_out);
+        self
+    }
+
+    /// Makes this config a session: a command that passed in one phase is
+    /// skipped as deduplicated when an identical command comes up in a later
+    /// phase run with this config.
+    pub fn with_deduplication(mut self) -> Self {
+        self.dedup = Some(CommandMemo::default());
+        self
+    }
+
+    pub fn num_threads(&self) -> Option<usize> {
+        self.num_threads
+    }
+
+    pub fn thread_name_prefix(&self) -> &str {
+        &self.thread_name_prefix
+    }
+
+    pub fn show_commands(&self) -> bool {
+        self.show_commands
+    }
+
+    pub fn stdout_is_terminal(&self) -> bool {
+        self.stdout_is_terminal
+    }
+
+    pub fn event_sink(&self) -> Option<&EventSink> {
+        self.events.as_deref()
+    }
+
+    pub fn simulated_failures(&self) -> &[String] {
+        &self.simulated_failures
+    }
+
+    pub fn opt_out(&self) -> Option<&OptOutList> {
+        self.opt_out.as_ref()
+    }
+
+    pub fn deduplicates(&self) -> bool {
+        self.dedup.is_some()
+    }
+
+    pub(super) fn command_memo(&self) -> Option<&CommandMemo> {
+        self.dedup.as_ref()
+    }
+
+    /// Returns the pool, building it on first use.
+    pub(super) fn thread_pool(&self) -> Result<&ThreadPool, ThreadPoolBuildError> {
+        if let Some(pool) = self.pool.get() {
+            return Ok(pool);
+        }
+        let prefix = self.thread_name_prefix.clone();
+        let mut builder =
+            ThreadPoolBuilder::new().thread_name(move |index| format!("{prefix}-{index}"));
+        if let Some(num_threads) = self.num_threads {
+            builder = builder.num_threads(num_threads);
+        }
+        let pool = builder.build()?;
+        // A concurrent caller may have won the race; either pool is equivalent.
+        Ok(self.pool.get_or_init(|| pool))
+    }
+}
+
+/// The configuration used by the `execute_hook*` functions that do not take
+/// an [`ExecutorConfig`].
+pub(super) fn default_executor_config() -> &'static ExecutorConfig {
+    static DEFAULT: OnceLock<ExecutorConfig> = OnceLock::new();
+    DEFAULT.get_or_init(ExecutorConfig::new)
+}
@@ -0,0 +1,129 @@
+//! Checks a hook's `requires` before its command runs, so a missing or
+//! outdated tool is reported as such instead of surfacing as whatever error
+//! the command produces without it.
+//!
+//! Tools are looked up on `PATH`; versions come from the first version-like
+//! token `<tool> --version` prints. Both are cached for the lifetime of the
+//! process, so a tool shared by many hooks is only probed once.
+
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Mutex, OnceLock},
+};
+
+use crate::config::{Requirement, parse_version};
+
+/// What was found for a tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ToolProbe {
+    path: PathBuf,
+    /// Reported version, probed on first use by a version constraint.
+    version: Option<Option<String>>,
+}
+
+static PROBES: OnceLock<Mutex<HashMap<String, Option<ToolProbe>>>> = OnceLock::new();
+
+/// Why `requirement` is not met, e.g. `not found on PATH` or `found 16.2.0`,
+/// or `None` when it is.
+pub(super) fn unmet_reason(requirement: &Requirement) -> Option<String> {
+    let mut probes = match PROBES.get_or_init(Mutex::default).lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let probe = probes
+        .entry(requirement.tool().to_string())
+        .or_insert_with(|| {
+            find_executable(requirement.tool()).map(|path| ToolProbe {
+                path,
+                version: None,
+            })
+        });
+    let Some(probe) = probe else {
+        return Some("not found on PATH".to_string());
+    };
+    if !requirement.has_version_constraint() {
+        return None;
+    }
+    let version = probe
+        .version
+        .get_or_insert_with(|| probe_version(&probe.path));
+    match version {
+        None => Some(format!(
+            "could not read the version of {}",
+            probe.path.display()
+        )),
+        Some(version) => match parse_version(version) {
+            Some(parts) if requirement.accepts_version(&parts) => None,
+            _ => Some(format!("found {version}")),
+        },
+    }
+}
+
+/// Resolves `tool` the way a shell would: names containing a path separator
+/// are used as given, anything else is searched for on `PATH`.
+pub(crate) fn find_executable(tool: &str) -> Option<PathBuf> {
+    if tool.contains(['/', '\\']) {
+        let path = PathBuf::from(tool);
+        return is_executable(&path).then_some(path);
+    }
+    let extensions = executable_extensions();
+    env::split_paths(&env::var_os("PATH")?)
+        .flat_map(|dir| {
+            extensions
+                .iter()
+                .map(move |extension| dir.join(format!("{tool}{extension}")))
+        })
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(windows)]
+fn executable_extensions() -> Vec<String> {
+    let pathext = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    std::iter::once(String::new())
+        .chain(
+            pathext
+                .split(';')
+                .filter(|ext| !ext.is_empty())
+                .map(str::to_string),
+        )
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn executable_extensions() -> Vec<String> {
+    vec![String::new()]
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+pub(crate) fn probe_version(path: &Path) -> Option<String> {
+    let output = Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    let text = [output.stdout, output.stderr].concat();
+    version_token(&String::from_utf8_lossy(&text)).map(str::to_string)
+}
+
+/// First dotted number in `text`, e.g. `2.43.0` in `git version 2.43.0`.
+fn version_token(text: &str) -> Option<&str> {
+    text.split(|c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')' | '-' | '+'))
+        .map(|word| word.strip_prefix('v').unwrap_or(word))
+        .map(|word| word.trim_end_matches('.'))
+        .find(|word| parse_version(word).is_some())
+}
@@ -0,0 +1,155 @@
+//! Opt-in reporter that posts each run's result to `[settings.report] url`,
+//! so hook telemetry can feed a dashboard.
+//!
+//! A report is one JSON document per phase or task run: its status, duration,
+//! and every command with its outcome. Posting happens after the run and
+//! never changes its result: when the endpoint stays unreachable after the
+//! configured retries, git-smee prints a warning and moves on.
+
+use std::{
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    config::{HookDefinition, ReportSettings},
+    redaction::redact_secret_values,
+    webhook::{self, Webhook},
+};
+
+use super::{hook_label, summary::HookRunSummary};
+
+/// Wait before the first retry; each further retry waits twice as long.
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Time allowed for each attempt. Reports are sent while Git waits for the
+/// hook, so this is kept well below the webhook default.
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The JSON document posted for one run.
+#[derive(Debug, Serialize)]
+pub(super) struct RunReport {
+    phase: String,
+    status: &'static str,
+    finished_at_unix_ms: u64,
+    duration_ms: u64,
+    attempted: usize,
+    failed: usize,
+    warnings: usize,
+    skipped: usize,
+    hooks: Vec<HookReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct HookReport {
+    hook: String,
+    command: String,
+    status: &'static str,
+    exit_code: Option<i32>,
+    duration_ms: u64,
+    severity: &'static str,
+    /// User plus system CPU time, where the platform reports it.
+    cpu_ms: Option<u64>,
+    peak_rss_bytes: Option<u64>,
+}
+
+pub(super) fn run_report(
+    phase: &str,
+    hooks: &[HookDefinition],
+    summary: &HookRunSummary,
+) -> RunReport {
+    RunReport {
+        phase: phase.to_string(),
+        status: if summary.error().is_some() {
+            "failed"
+        } else {
+            "passed"
+        },
+        finished_at_unix_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64),
+        duration_ms: summary.total_duration.as_millis() as u64,
+        attempted: summary.attempted_count(),
+        failed: summary.failed_count(),
+        warnings: summary.warning_count(),
+        skipped: summary.skipped_count(),
+        hooks: summary
+            .command_runs()
+            .iter()
+            .filter_map(|run| {
+                let hook = hooks.get(run.config_index)?;
+                Some(HookReport {
+                    hook: hook_label(run.config_index, hook),
+                    command: redact_secret_values(&hook.command),
+                    status: run.outcome.kind(),
+                    exit_code: run.exit_code(),
+                    duration_ms: run.duration.as_millis() as u64,
+                    severity: run.severity.as_str(),
+                    cpu_ms: run
+                        .resources
+                        .map(|resources| resources.total_cpu().as_millis() as u64),
+                    peak_rss_bytes: run.resources.map(|resources| resources.peak_rss_bytes),
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Posts `report` as configured, warning on stderr when it cannot be
+/// delivered. With `secret_env` set but the variable unset, nothing is sent:
+/// that keeps reporting to machines that were given the secret, such as CI.
+pub(super) fn send_report(settings: &ReportSettings, report: &RunReport) {
+    let secret = match &settings.secret_env {
+        Some(name) => match std::env::var(name) {
+            Ok(secret) if !secret.is_empty() => Some(secret),
+            _ => return,
+        },
+        None => None,
+    };
+    let result = serde_json::to_vec(report)
+        .map_err(|error| error.to_string())
+        .and_then(|body| {
+            let mut webhook = Webhook::new(settings.url.clone())
+                .map_err(|error| error.to_string())?
+                .with_timeout(ATTEMPT_TIMEOUT);
+            if let Some(secret) = secret {
+                webhook = webhook
+                    .with_header(settings.secret_header(), secret)
+                    .map_err(|_| {
+                        "the report secret must be a single line; the report was not sent"
+                            .to_string()
+                    })?;
+            }
+            post_with_retries(&webhook, &body, settings.retries(), RETRY_BACKOFF)
+                .map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        eprintln!(
+            "git-smee: warning: could not report the {} run: {error}",
+            report.phase
+        );
+    }
+}
+
+/// Posts `body`, retrying failed requests up to `retries` more times with
+/// exponential backoff. Errors that a retry cannot fix, like a missing
+/// `curl`, end the attempts right away.
+pub(super) fn post_with_retries(
+    webhook: &Webhook,
+    body: &[u8],
+    retries: u32,
+    backoff: Duration,
+) -> Result<(), webhook::Error> {
+    let mut attempt = 0;
+    loop {
+        match webhook.post_json(body) {
+            Err(webhook::Error::RequestFailed { .. }) if attempt < retries => {
+                thread::sleep(backoff * 2u32.saturating_pow(attempt));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::{io, process::Child, time::Duration};
+
+/// CPU time and peak memory consumed by a hook command and the processes it
+/// waited for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub peak_rss_bytes: u64,
+    pub user_cpu: Duration,
+    pub system_cpu: Duration,
+}
+
+impl ResourceUsage {
+    pub fn total_cpu(&self) -> Duration {
+        self.user_cpu + self.system_cpu
+    }
+}
+
+/// Exit information collected when a child process is reaped.
+pub(super) struct Reaped {
+    pub(super) code: Option<i32>,
+    /// The command was killed by `SIGINT`, i.e. Ctrl-C.
+    pub(super) interrupted: bool,
+    pub(super) resources: Option<ResourceUsage>,
+}
+
+/// Reaps `child` if it has exited, without blocking.
+#[cfg(unix)]
+pub(super) fn try_reap(child: &mut Child) -> io::Result<Option<Reaped>> {
+    wait4(child, libc::WNOHANG)
+}
+
+/// Blocks until `child` exits and reaps it.
+#[cfg(unix)]
+pub(super) fn reap(child: &mut Child) -> io::Result<Reaped> {
+    wait4(child, 0)?.ok_or_else(|| io::Error::other("wait4 returned without a child status"))
+}
+
+#[cfg(unix)]
+fn wait4(child: &mut Child, options: libc::c_int) -> io::Result<Option<Reaped>> {
+    let pid = libc::pid_t::try_from(child.id()).map_err(io::Error::other)?;
+    let mut status: libc::c_int = 0;
+    // SAFETY: `rusage` is a plain C struct for which all-zero bytes are valid.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: both out-pointers reference live, writable stack values.
+        let reaped = unsafe { libc::wait4(pid, &mut status, options, &mut usage) };
+        if reaped == 0 {
+            return Ok(None);
+        }
+        if reaped == pid {
+            break;
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    let code = libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status));
+    Ok(Some(Reaped {
+        code,
+        interrupted: libc::WIFSIGNALED(status) && libc::WTERMSIG(status) == libc::SIGINT,
+        resources: Some(ResourceUsage {
+            peak_rss_bytes: max_rss_bytes(usage.ru_maxrss),
+            user_cpu: timeval_duration(usage.ru_utime),
+            system_cpu: timeval_duration(usage.ru_stime),
+        }),
+    }))
+}
+
+#[cfg(unix)]
+fn max_rss_bytes(max_rss: libc::c_long) -> u64 {
+    let max_rss = u64::try_from(max_rss).unwrap_or_default();
+    // Darwin reports `ru_maxrss` in bytes, every other Unix in kilobytes.
+    if cfg!(any(target_os = "macos", target_os = "ios")) {
+        max_rss
+    } else {
+        max_rss.saturating_mul(1024)
+    }
+}
+
+#[cfg(unix)]
+fn timeval_duration(time: libc::timeval) -> Duration {
+    let seconds = u64::try_from(time.tv_sec).unwrap_or_default();
+    let micros = u32::try_from(time.tv_usec).unwrap_or_default();
+    Duration::from_secs(seconds) + Duration::from_micros(u64::from(micros))
+}
+
+#[cfg(not(unix))]
+pub(super) fn try_reap(child: &mut Child) -> io::Result<Option<Reaped>> {
+    Ok(child.try_wait()?.map(|status| Reaped {
+        code: status.code(),
+        interrupted: false,
+        resources: None,
+    }))
+}
+
+#[cfg(not(unix))]
+pub(super) fn reap(child: &mut Child) -> io::Result<Reaped> {
+    child.wait().map(|status| Reaped {
+        code: status.code(),
+        interrupted: false,
+        resources: None,
+    })
+}
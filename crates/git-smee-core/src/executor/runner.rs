@@ -1,16 +1,70 @@
 use std::{
     env,
     io::{self, ErrorKind, Write},
+    path::PathBuf,
     process::Stdio,
     thread,
 };
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
-#[cfg(windows)]
-use std::path::PathBuf;
+#[cfg(unix)]
+use std::{io::IsTerminal as _, os::unix::process::CommandExt as _};
+
+use crate::{
+    config::{HookDefinition, WindowsShell},
+    platform::Platform,
+    redaction::{REDACTED_VALUE, is_secret_name, redact_secret_values},
+};
+
+#[cfg(unix)]
+use super::terminal::TerminalLoan;
+use super::{
+    artifacts::ARTIFACTS_DIR_ENV,
+    network::NetworkBlock,
+    output::ForwardedOutput,
+    sandbox::Sandbox,
+    termination::{CommandCompletion, CommandExit, TerminationPolicy, wait_with_termination},
+};
+
+/// Env var set for hook commands to the name of the run they belong to, e.g.
+/// `pre-commit` or `task-lint`. A git-smee run that starts with it set was
+/// triggered from inside another run.
+pub const ACTIVE_RUN_ENV: &str = "GIT_SMEE_ACTIVE";
 
-use crate::platform::Platform;
+/// Env vars pointed at a hook's private temp directory.
+pub(super) const TMP_DIR_ENV_KEYS: [&str; 3] = ["TMPDIR", "TMP", "TEMP"];
+
+/// Per-command execution settings derived from the hook definition.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct CommandOptions {
+    pub(super) termination: TerminationPolicy,
+    /// Private temp directory exported as `TMPDIR` (and `TMP`/`TEMP`).
+    pub(super) tmp_dir: Option<PathBuf>,
+    /// Directory for kept outputs, exported as [`ARTIFACTS_DIR_ENV`].
+    pub(super) artifacts_dir: Option<PathBuf>,
+    /// When set, stdout and stderr are streamed line by line with a
+    /// `[label] ` prefix instead of being inherited.
+    pub(super) output_label: Option<String>,
+    /// When set, at most this many bytes of stdout and of stderr are
+    /// forwarded; see [`super::output::copy_lines`].
+    pub(super) max_output_bytes: Option<u64>,
+    /// When set, the resolved command line, working directory, and injected
+    /// env are printed to stderr under this title before spawning.
+    pub(super) show_command: Option<String>,
+    /// Program and arguments of a `no_shell` command. When set, they are
+    /// executed directly instead of passing the command text to the shell.
+    pub(super) argv: Option<(PathBuf, Vec<String>)>,
+    /// Exported as [`ACTIVE_RUN_ENV`].
+    pub(super) active_run: Option<String>,
+    /// Runs the command inside this sandbox.
+    pub(super) sandbox: Option<Sandbox>,
+    /// Cuts the command off from the network, for `network = false`. Inside
+    /// a sandbox, the sandbox does that instead.
+    pub(super) network_block: Option<NetworkBlock>,
+    /// Shell of commands run on Windows, from `settings.windows_shell`.
+    pub(super) windows_shell: WindowsShell,
+}
 
 pub(super) trait CommandRunner: Sync {
     fn run(
@@ -18,8 +72,9 @@ pub(super) trait CommandRunner: Sync {
         command: &str,
         hook_args: &[String],
         stdin_payload: Option<&[u8]>,
-    ) -> Result<Option<i32>, std::io::Error>;
-    fn shell_display(&self) -> &'static str;
+        options: &CommandOptions,
+    ) -> Result<CommandCompletion, std::io::Error>;
+    fn shell_display(&self, windows_shell: WindowsShell) -> &'static str;
 }
 
 pub(super) struct PlatformCommandRunner<'a> {
@@ -32,17 +87,43 @@ impl CommandRunner for PlatformCommandRunner<'_> {
         command: &str,
         hook_args: &[String],
         stdin_payload: Option<&[u8]>,
-    ) -> Result<Option<i32>, std::io::Error> {
-        let mut shell_command = self.platform.create_command();
+        options: &CommandOptions,
+    ) -> Result<CommandCompletion, std::io::Error> {
+        let termination = &options.termination;
+        let mut shell_command = match &options.argv {
+            Some((program, _)) => std::process::Command::new(program),
+            None => self.platform.create_shell_command(options.windows_shell),
+        };
         apply_hook_arg_env(&mut shell_command, hook_args);
+        if let Some(tmp_dir) = &options.tmp_dir {
+            for key in TMP_DIR_ENV_KEYS {
+                shell_command.env(key, tmp_dir);
+            }
+        }
+        if let Some(artifacts_dir) = &options.artifacts_dir {
+            shell_command.env(ARTIFACTS_DIR_ENV, artifacts_dir);
+        }
+        if let Some(active_run) = &options.active_run {
+            shell_command.env(ACTIVE_RUN_ENV, active_run);
+        }
         let mut _windows_command_script = None;
-        match self.platform {
-            Platform::Unix => {
+        match (self.platform, &options.argv) {
+            (_, Some((_, args))) => {
+                shell_command.args(args);
+                shell_command.args(hook_args);
+            }
+            (Platform::Unix, None) => {
                 shell_command.arg(command);
                 shell_command.arg("--");
                 shell_command.args(hook_args);
             }
-            Platform::Windows => {
+            (Platform::Windows, None) if !options.windows_shell.is_cmd() => {
+                let command_script = create_powershell_script(command)?;
+                shell_command.arg(&command_script);
+                shell_command.args(hook_args);
+                _windows_command_script = Some(command_script);
+            }
+            (Platform::Windows, None) => {
                 let command_script = create_windows_command_script(command)?;
                 shell_command.arg(&command_script);
                 #[cfg(windows)]
@@ -52,54 +133,116 @@ impl CommandRunner for PlatformCommandRunner<'_> {
                 _windows_command_script = Some(command_script);
             }
         }
+        match (&options.sandbox, &options.network_block) {
+            (Some(sandbox), network_block) => {
+                shell_command = sandbox.wrap(&shell_command, network_block.is_some());
+            }
+            (None, Some(network_block)) => shell_command = network_block.apply(shell_command),
+            (None, None) => {}
+        }
         if stdin_payload.is_some() {
             shell_command.stdin(Stdio::piped());
         }
+        if options.output_label.is_some() || options.max_output_bytes.is_some() {
+            shell_command.stdout(Stdio::piped());
+            shell_command.stderr(Stdio::piped());
+        }
+        // Run each hook as its own process group so a timeout or cancellation
+        // can stop everything the hook script spawned. That group must own
+        // the terminal, or reading it stops the hook with SIGTTIN. Hooks with
+        // a timeout and no terminal on stdin stay in the background, where
+        // Ctrl-C reaches git-smee and stops them with their grace period.
+        #[cfg(unix)]
+        shell_command.process_group(0);
+        #[cfg(unix)]
+        let _terminal_loan = (!termination.configured
+            || (stdin_payload.is_none() && io::stdin().is_terminal()))
+        .then(|| TerminalLoan::lend_to(&mut shell_command))
+        .flatten();
 
         #[cfg(windows)]
         if let Some(current_dir) = cmd_compatible_current_dir()? {
             shell_command.current_dir(current_dir);
         }
 
+        if let Some(title) = &options.show_command {
+            eprintln!("{}", describe_command(title, command, &shell_command));
+        }
+
         let mut child = shell_command.spawn()?;
-        if let Some(stdin_payload) = stdin_payload {
-            let Some(mut stdin) = child.stdin.take() else {
-                return child.wait().map(|status| status.code());
-            };
-            let stdin_payload = stdin_payload.to_vec();
-            let stdin_writer = thread::spawn(move || {
-                match stdin.write_all(&stdin_payload) {
-                    // Hook commands are allowed to ignore or close stdin early. If the
-                    // command exits successfully, a broken pipe while replaying the
-                    // buffered hook payload should not fail the hook run.
-                    Err(error) if error.kind() == ErrorKind::BrokenPipe => Ok(()),
-                    result => result,
-                }
+        let forwarded_output =
+            (options.output_label.is_some() || options.max_output_bytes.is_some()).then(|| {
+                ForwardedOutput::forward(
+                    &mut child,
+                    options.output_label.as_deref(),
+                    options.max_output_bytes,
+                )
             });
-            let wait_result = child.wait().map(|status| status.code());
-            let stdin_result = stdin_writer
-                .join()
-                .unwrap_or_else(|_| Err(io::Error::other("stdin writer thread panicked")));
-            stdin_result?;
-            wait_result
-        } else {
-            child.wait().map(|status| status.code())
+        let mut result = wait_for_child(&mut child, stdin_payload, termination);
+        if let Some(forwarded_output) = forwarded_output {
+            let stopped = matches!(
+                &result,
+                Ok(completion) if matches!(completion.exit, CommandExit::Stopped { .. })
+            );
+            let forwarded = forwarded_output.finish(stopped);
+            if let Ok(completion) = &mut result {
+                completion.output_truncated_bytes = forwarded.truncated_bytes;
+                completion.partial_output = forwarded.partial_output;
+            }
         }
+        result
     }
 
-    fn shell_display(&self) -> &'static str {
-        self.platform.shell_display()
+    fn shell_display(&self, windows_shell: WindowsShell) -> &'static str {
+        self.platform.shell_display_for(windows_shell)
     }
 }
 
+fn wait_for_child(
+    child: &mut std::process::Child,
+    stdin_payload: Option<&[u8]>,
+    termination: &TerminationPolicy,
+) -> Result<CommandCompletion, std::io::Error> {
+    let Some(stdin_payload) = stdin_payload else {
+        return wait_with_termination(child, termination);
+    };
+    let Some(mut stdin) = child.stdin.take() else {
+        return wait_with_termination(child, termination);
+    };
+    let stdin_payload = stdin_payload.to_vec();
+    let stdin_writer = thread::spawn(move || {
+        match stdin.write_all(&stdin_payload) {
+            // Hook commands are allowed to ignore or close stdin early. If the
+            // command exits successfully, a broken pipe while replaying the
+            // buffered hook payload should not fail the hook run.
+            Err(error) if error.kind() == ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        }
+    });
+    let wait_result = wait_with_termination(child, termination);
+    let stdin_result = stdin_writer
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("stdin writer thread panicked")));
+    stdin_result?;
+    wait_result
+}
+
 pub(super) fn create_windows_command_script(
     command: &str,
 ) -> Result<tempfile::TempPath, io::Error> {
+    create_command_script(".cmd", &windows_command_script(command))
+}
+
+fn create_powershell_script(command: &str) -> Result<tempfile::TempPath, io::Error> {
+    create_command_script(".ps1", &powershell_script(command))
+}
+
+fn create_command_script(suffix: &str, contents: &str) -> Result<tempfile::TempPath, io::Error> {
     let mut script = tempfile::Builder::new()
         .prefix("git-smee-command-")
-        .suffix(".cmd")
+        .suffix(suffix)
         .tempfile()?;
-    script.write_all(windows_command_script(command).as_bytes())?;
+    script.write_all(contents.as_bytes())?;
     script.flush()?;
     Ok(script.into_temp_path())
 }
@@ -108,42 +251,82 @@ pub(super) fn create_windows_command_script(
 fn append_windows_hook_args(shell_command: &mut std::process::Command, hook_args: &[String]) {
     for arg in hook_args {
         shell_command.raw_arg(" ");
-        shell_command.raw_arg(windows_cmd_quote_hook_arg(arg));
+        shell_command.raw_arg(crate::shell_quote::cmd_quote(arg));
     }
 }
 
-#[cfg_attr(not(windows), allow(dead_code))]
-pub(super) fn windows_cmd_quote_hook_arg(arg: &str) -> String {
-    let needs_quotes = arg.is_empty()
-        || arg.chars().any(|ch| {
-            matches!(
-                ch,
-                ' ' | '\t' | '&' | '|' | '^' | '<' | '>' | '(' | ')' | '!' | '%'
-            )
-        });
-    if !needs_quotes {
-        return arg.to_string();
-    }
+pub(super) fn windows_command_script(command: &str) -> String {
+    format!("@echo off\r\n{command}\r\n")
+}
 
-    let escaped = escape_windows_cmd_arg(arg).replace('"', "\"\"");
-    format!("\"{escaped}\"")
+/// PowerShell script running `command`. Errors of cmdlets stop it with exit
+/// code 1, and a failing native program's exit code is passed on, since
+/// `-File` would otherwise exit 0 once the script completes.
+pub(super) fn powershell_script(command: &str) -> String {
+    format!(
+        "$ErrorActionPreference = 'Stop'\r\n{command}\r\nif ($LASTEXITCODE) {{ exit $LASTEXITCODE }}\r\n"
+    )
 }
 
-fn escape_windows_cmd_arg(arg: &str) -> String {
-    let mut escaped = String::with_capacity(arg.len());
-    for ch in arg.chars() {
-        match ch {
-            '^' => escaped.push_str("^^"),
-            '%' => escaped.push_str("%%"),
-            '!' => escaped.push_str("^!"),
-            _ => escaped.push(ch),
-        }
+/// Renders what is about to be executed, with secret-looking values masked.
+pub(super) fn describe_command(
+    title: &str,
+    command: &str,
+    shell_command: &std::process::Command,
+) -> String {
+    let mut exec = vec![shell_command.get_program().to_string_lossy().to_string()];
+    exec.extend(
+        shell_command
+            .get_args()
+            .map(|arg| format!("{:?}", redact_secret_values(&arg.to_string_lossy()))),
+    );
+    let cwd = match shell_command.get_current_dir() {
+        Some(dir) => dir.display().to_string(),
+        None => env::current_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_else(|error| format!("<unavailable: {error}>")),
+    };
+    let mut env: Vec<_> = shell_command
+        .get_envs()
+        .filter_map(|(key, value)| {
+            let key = key.to_string_lossy();
+            let value = value?.to_string_lossy();
+            let value = if is_secret_name(&key) {
+                REDACTED_VALUE.to_string()
+            } else {
+                redact_secret_values(&value)
+            };
+            Some(format!("{key}={value:?}"))
+        })
+        .collect();
+    env.sort();
+
+    let mut lines = vec![
+        format!("git-smee: running {title}"),
+        format!("  command: {}", redact_secret_values(command)),
+        format!("  exec: {}", exec.join(" ")),
+        format!("  cwd: {cwd}"),
+    ];
+    if env.is_empty() {
+        lines.push("  env: (inherited only)".to_string());
+    } else {
+        lines.push(format!("  env: {}", env.join(" ")));
     }
-    escaped
+    lines.join("\n")
 }
 
-pub(super) fn windows_command_script(command: &str) -> String {
-    format!("@echo off\r\n{command}\r\n")
+/// Names of the env vars git-smee sets for a command, in the order they are
+/// applied.
+pub(super) fn injected_env_keys(hook_arg_count: usize, hook: &HookDefinition) -> Vec<String> {
+    let mut keys = vec!["GIT_SMEE_HOOK_ARGC".to_string()];
+    keys.extend((1..=hook_arg_count).map(|index| format!("GIT_SMEE_HOOK_ARG_{index}")));
+    if hook.isolate_tmp {
+        keys.extend(TMP_DIR_ENV_KEYS.map(str::to_string));
+    }
+    if hook.artifacts_dir {
+        keys.push(ARTIFACTS_DIR_ENV.to_string());
+    }
+    keys
 }
 
 pub(super) fn apply_hook_arg_env(shell_command: &mut std::process::Command, hook_args: &[String]) {
@@ -0,0 +1,202 @@
+//! Opt-in sandbox for hook commands, for users who run hooks from
+//! repositories they do not fully trust.
+//!
+//! The sandbox is a choice of the person running the hooks, not of the
+//! repository: it is switched on with `git config smee.sandbox true` (in any
+//! scope, e.g. `--global`) or `GIT_SMEE_SANDBOX=1`, never from `.git-smee.toml`.
+//!
+//! - On Linux, commands run under `bwrap` (bubblewrap) and see only the
+//!   repository (writable), the usual system directories and the directories
+//!   on `PATH` (read-only), a private `/tmp`, `/dev`, and `/proc`.
+//! - On macOS, commands run under `sandbox-exec` with a profile that only
+//!   allows writes to the repository and the temp directories and denies
+//!   reads from the home directory outside the repository and `PATH`.
+//!
+//! Where neither tool is available the hooks run unsandboxed after a warning.
+//! Commands with `network = false` additionally lose network access inside
+//! the sandbox.
+
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::repository;
+
+use super::probe;
+
+/// Git config key that turns the sandbox on.
+pub const SANDBOX_KEY: &str = "smee.sandbox";
+
+/// Env var that turns the sandbox on, e.g. `GIT_SMEE_SANDBOX=1`.
+pub const SANDBOX_ENV: &str = "GIT_SMEE_SANDBOX";
+
+/// System directories made readable inside the `bwrap` sandbox when present.
+const BWRAP_READ_ONLY_DIRS: [&str; 9] = [
+    "/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt", "/nix",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SandboxKind {
+    Bwrap,
+    SandboxExec,
+}
+
+/// How hook commands of one run are confined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Sandbox {
+    kind: SandboxKind,
+    program: PathBuf,
+    repository_root: PathBuf,
+    path_dirs: Vec<PathBuf>,
+    home: Option<PathBuf>,
+}
+
+/// Whether the user switched the sandbox on for `repository_root`.
+pub(super) fn sandbox_requested(repository_root: &Path) -> Result<bool, repository::Error> {
+    if env::var(SANDBOX_ENV).is_ok_and(|value| {
+        matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    }) {
+        return Ok(true);
+    }
+    Ok(repository::git_config_bool(repository_root, SANDBOX_KEY)? == Some(true))
+}
+
+impl Sandbox {
+    /// The sandbox available on this machine, or why there is none.
+    pub(super) fn detect(repository_root: &Path) -> Result<Self, String> {
+        let (kind, tool) = if cfg!(target_os = "linux") {
+            (SandboxKind::Bwrap, "bwrap")
+        } else if cfg!(target_os = "macos") {
+            (SandboxKind::SandboxExec, "sandbox-exec")
+        } else {
+            return Err("sandboxing is not supported on this platform".to_string());
+        };
+        let program = probe::find_executable(tool)
+            .ok_or_else(|| format!("{tool} is not installed or not on PATH"))?;
+        Ok(Self {
+            kind,
+            program,
+            repository_root: repository_root.to_path_buf(),
+            path_dirs: env::var_os("PATH")
+                .map(|path| env::split_paths(&path).collect())
+                .unwrap_or_default(),
+            home: env::var_os("HOME").map(PathBuf::from),
+        })
+    }
+
+    /// Rewrites `command` to run inside the sandbox, keeping its arguments,
+    /// env, and working directory. Stdio and process settings are left for
+    /// the caller to apply to the returned command. `offline` also cuts the
+    /// command off from the network.
+    pub(super) fn wrap(&self, command: &Command, offline: bool) -> Command {
+        let mut sandboxed = Command::new(&self.program);
+        sandboxed.args(self.sandbox_args(command, offline));
+        sandboxed.arg(command.get_program());
+        sandboxed.args(command.get_args());
+        for (key, value) in command.get_envs() {
+            match value {
+                Some(value) => sandboxed.env(key, value),
+                None => sandboxed.env_remove(key),
+            };
+        }
+        if let Some(current_dir) = command.get_current_dir() {
+            sandboxed.current_dir(current_dir);
+        }
+        sandboxed
+    }
+
+    fn sandbox_args(&self, command: &Command, offline: bool) -> Vec<OsString> {
+        match self.kind {
+            SandboxKind::Bwrap => self.bwrap_args(command, offline),
+            SandboxKind::SandboxExec => {
+                vec!["-p".into(), self.sandbox_exec_profile(offline).into()]
+            }
+        }
+    }
+
+    fn bwrap_args(&self, command: &Command, offline: bool) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["--die-with-parent".into()];
+        if offline {
+            args.push("--unshare-net".into());
+        }
+        let read_only = BWRAP_READ_ONLY_DIRS
+            .iter()
+            .map(PathBuf::from)
+            .chain(self.path_dirs.iter().cloned());
+        for dir in read_only {
+            args.extend(["--ro-bind-try".into(), dir.clone().into(), dir.into()]);
+        }
+        args.extend(["--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp"].map(OsString::from));
+        args.extend([
+            "--bind".into(),
+            self.repository_root.clone().into(),
+            self.repository_root.clone().into(),
+        ]);
+        let current_dir = command
+            .get_current_dir()
+            .map(Path::to_path_buf)
+            .or_else(|| env::current_dir().ok());
+        if let Some(current_dir) = current_dir {
+            args.extend(["--chdir".into(), current_dir.into()]);
+        }
+        args.push("--".into());
+        args
+    }
+
+    fn sandbox_exec_profile(&self, offline: bool) -> String {
+        let repository = profile_string(&self.repository_root);
+        let mut profile = format!(
+            "(version 1)\n(allow default)\n(deny file-write*)\n(allow file-write* (subpath {repository}) (subpath \"/private/tmp\") (subpath \"/private/var/folders\") (literal \"/dev/null\") (literal \"/dev/tty\") (regex #\"^/dev/fd/\"))\n"
+        );
+        if let Some(home) = &self.home {
+            profile.push_str(&format!(
+                "(deny file-read* (subpath {}))\n",
+                profile_string(home)
+            ));
+            let readable = std::iter::once(&self.repository_root)
+                .chain(self.path_dirs.iter())
+                .map(|dir| format!(" (subpath {})", profile_string(dir)))
+                .collect::<String>();
+            profile.push_str(&format!("(allow file-read*{readable})\n"));
+        }
+        if offline {
+            profile.push_str("(deny network-outbound (remote ip))\n");
+        }
+        profile
+    }
+}
+
+/// `path` as a quoted string literal of the sandbox profile language.
+fn profile_string(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+impl Sandbox {
+    pub(super) fn bwrap_for_test(repository_root: &Path, path_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            kind: SandboxKind::Bwrap,
+            program: PathBuf::from("/usr/bin/bwrap"),
+            repository_root: repository_root.to_path_buf(),
+            path_dirs,
+            home: None,
+        }
+    }
+
+    pub(super) fn sandbox_exec_for_test(repository_root: &Path, home: &Path) -> Self {
+        Self {
+            kind: SandboxKind::SandboxExec,
+            program: PathBuf::from("/usr/bin/sandbox-exec"),
+            repository_root: repository_root.to_path_buf(),
+            path_dirs: vec![home.join("bin")],
+            home: Some(home.to_path_buf()),
+        }
+    }
+}
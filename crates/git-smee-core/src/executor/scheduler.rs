@@ -1,22 +1,103 @@
 use std::{
+    borrow::Cow,
+    io,
+    path::PathBuf,
     sync::Mutex,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
+use rayon::ThreadPool;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::prelude::*;
 
-use crate::config::HookDefinition;
+use crate::{
+    config::{
+        CHANGED_FILES_PLACEHOLDER, CalendarDate, HookDefinition, LifeCyclePhase,
+        MissingRequirement, OverBudget, Severity, WindowsShell, hook_label,
+    },
+    opt_out::OptOutList,
+    platform::Platform,
+    redaction::{redact_command, redact_secret_values},
+    shell_words,
+};
 
+#[cfg(test)]
+use super::Error;
 use super::{
-    Error,
-    redaction::redact_command,
-    runner::CommandRunner,
+    artifacts,
+    dedup::CommandMemo,
+    events::{Event, EventSink},
+    isolation::IsolatedTmpDir,
+    journal::RunJournal,
+    locks::{ConcurrencyGroups, HookMutexes},
+    network::NetworkBlock,
+    probe,
+    runner::{CommandOptions, CommandRunner},
+    sandbox::Sandbox,
     summary::{CommandOutcome, CommandPhase, CommandRun, HookRunSummary},
+    termination::{CommandExit, TerminationPolicy, cancellation_requested},
 };
 
 type IndexedHook<'a> = (usize, &'a HookDefinition);
 
+/// A phase's `max_duration`, counted from when the phase started.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct PhaseBudget {
+    pub(super) deadline: Instant,
+    pub(super) max_duration: Duration,
+    pub(super) over_budget: OverBudget,
+}
+
+/// Per-phase settings the scheduler needs beyond the hook list itself.
+#[derive(Debug, Default)]
+pub(super) struct RunContext<'a> {
+    /// Directory under which hooks with `isolate_tmp = true` get their own
+    /// `TMPDIR`, e.g. `.git/smee/tmp/pre-commit`.
+    pub(super) tmp_root: Option<PathBuf>,
+    /// Directory of this run under which hooks with `artifacts_dir = true`
+    /// get theirs, e.g. `.git/smee/artifacts/<run-id>/pre-commit`.
+    pub(super) artifacts_root: Option<PathBuf>,
+    /// Pool for the parallel phase. Without one, rayon's current pool is used.
+    pub(super) thread_pool: Option<&'a ThreadPool>,
+    /// Date used to evaluate `enforce_after`. Defaults to today in UTC.
+    pub(super) today: Option<CalendarDate>,
+    /// Echo each resolved command before running it.
+    pub(super) show_commands: bool,
+    /// Run every command sequentially in config order, including those
+    /// with `parallel = true`.
+    pub(super) sequential_only: bool,
+    /// Shell of commands run on Windows, from `settings.windows_shell`.
+    pub(super) windows_shell: WindowsShell,
+    /// Progress of a resumable run: commands it lists as passed are skipped,
+    /// and newly passed ones are recorded.
+    pub(super) journal: Option<(&'a RunJournal, LifeCyclePhase)>,
+    /// Named mutexes that keep commands with the same `mutex` apart.
+    pub(super) mutexes: HookMutexes,
+    /// Limits of the concurrency groups commands join with `group`.
+    pub(super) groups: ConcurrencyGroups,
+    /// Name of the run, exported to commands as
+    /// [`super::runner::ACTIVE_RUN_ENV`].
+    pub(super) run_name: Option<String>,
+    /// Where progress events go, if anywhere.
+    pub(super) events: Option<&'a EventSink>,
+    /// Confinement for every command, when the user asked for one.
+    pub(super) sandbox: Option<Sandbox>,
+    /// How `network = false` commands are cut off from the network.
+    pub(super) network_block: Option<NetworkBlock>,
+    /// Labels of commands to report as failed without running them.
+    pub(super) simulated_failures: &'a [String],
+    /// The user's opt-out list, whose commands are skipped.
+    pub(super) opt_out: Option<&'a OptOutList>,
+    /// Commands that passed earlier in the session, which are not run again.
+    pub(super) dedup: Option<&'a CommandMemo>,
+    /// Commands that would start after the deadline are not run.
+    pub(super) budget: Option<PhaseBudget>,
+    /// Files the push changes, filled into [`CHANGED_FILES_PLACEHOLDER`].
+    /// Commands using the placeholder are skipped when there are none.
+    pub(super) changed_files: Option<Vec<PathBuf>>,
+}
+
+#[cfg(test)]
 pub(super) fn run_hooks_with_runner<R: CommandRunner>(
     hooks: &[HookDefinition],
     runner: &R,
@@ -30,58 +111,97 @@ pub(super) fn run_hooks_with_runner<R: CommandRunner>(
     }
 }
 
+#[cfg(test)]
 pub(super) fn run_hooks_with_runner_with_summary<R: CommandRunner>(
     hooks: &[HookDefinition],
     runner: &R,
     hook_args: &[String],
     stdin_payload: Option<&[u8]>,
+) -> HookRunSummary {
+    run_hooks_in_context(
+        hooks,
+        runner,
+        hook_args,
+        stdin_payload,
+        &RunContext::default(),
+    )
+}
+
+pub(super) fn run_hooks_in_context<R: CommandRunner>(
+    hooks: &[HookDefinition],
+    runner: &R,
+    hook_args: &[String],
+    stdin_payload: Option<&[u8]>,
+    context: &RunContext<'_>,
 ) -> HookRunSummary {
     let started = Instant::now();
+    let run_name = context.run_name.clone().unwrap_or_default();
+    if let Some(events) = context.events {
+        events.emit(&Event::PhaseStarted {
+            run: run_name.clone(),
+            commands: hooks.len(),
+        });
+    }
     let (parallel_hooks, sequential_hooks): (Vec<IndexedHook<'_>>, Vec<IndexedHook<'_>>) = hooks
         .iter()
         .enumerate()
-        .partition(|(_, hook)| hook.parallel_execution_allowed);
+        .filter(|(config_index, hook)| {
+            context.journal.is_none_or(|(journal, phase)| {
+                !journal.has_passed(phase, *config_index, &hook.command)
+            })
+        })
+        .partition(|(_, hook)| hook.parallel_execution_allowed && !context.sequential_only);
 
     let mut command_runs = Vec::new();
     let mut failed = false;
     let sequential_started = Instant::now();
-    for (phase_index, (_, hook)) in sequential_hooks.into_iter().enumerate() {
-        if failed {
+    for (phase_index, (config_index, hook)) in sequential_hooks.into_iter().enumerate() {
+        if failed || cancellation_requested() {
             break;
         }
         let run = execute_command_record(
             CommandPhase::Sequential,
             phase_index,
-            &hook.command,
+            (config_index, hook),
             runner,
             hook_args,
             stdin_payload,
+            context,
         );
-        failed = run.outcome.is_failure();
+        failed = run.is_blocking_failure();
         command_runs.push(run);
     }
     let sequential_duration = sequential_started.elapsed();
 
     let mut parallel_duration = Duration::ZERO;
-    if !failed {
+    if !failed && !cancellation_requested() {
         let parallel_started = Instant::now();
         let parallel_runs = Mutex::new(Vec::new());
-        let _ = parallel_hooks
-            .par_iter()
-            .enumerate()
-            .try_for_each(|(phase_index, (_, hook))| {
-                let run = execute_command_record(
-                    CommandPhase::Parallel,
-                    phase_index,
-                    &hook.command,
-                    runner,
-                    hook_args,
-                    stdin_payload,
-                );
-                let failed = run.outcome.is_failure();
-                lock_command_runs(&parallel_runs).push(run);
-                if failed { Err(()) } else { Ok(()) }
-            });
+        let run_parallel_hooks = || {
+            parallel_hooks.par_iter().enumerate().try_for_each(
+                |(phase_index, (config_index, hook))| {
+                    if cancellation_requested() {
+                        return Err(());
+                    }
+                    let run = execute_command_record(
+                        CommandPhase::Parallel,
+                        phase_index,
+                        (*config_index, hook),
+                        runner,
+                        hook_args,
+                        stdin_payload,
+                        context,
+                    );
+                    let failed = run.is_blocking_failure();
+                    lock_command_runs(&parallel_runs).push(run);
+                    if failed { Err(()) } else { Ok(()) }
+                },
+            )
+        };
+        let _ = match context.thread_pool {
+            Some(thread_pool) => thread_pool.install(run_parallel_hooks),
+            None => run_parallel_hooks(),
+        };
         let mut parallel_runs = match parallel_runs.into_inner() {
             Ok(runs) => runs,
             Err(poisoned) => poisoned.into_inner(),
@@ -91,13 +211,27 @@ pub(super) fn run_hooks_with_runner_with_summary<R: CommandRunner>(
         command_runs.extend(parallel_runs);
     }
 
-    HookRunSummary {
+    let summary = HookRunSummary {
         total_configured: hooks.len(),
         total_duration: started.elapsed(),
         sequential_duration,
         parallel_duration,
         command_runs,
+        disabled_by: None,
+        nested_in: None,
+    };
+    if let Some(events) = context.events {
+        events.emit(&Event::PhaseFinished {
+            run: run_name,
+            success: summary.error().is_none(),
+            duration_ms: duration_ms(summary.total_duration),
+        });
     }
+    summary
+}
+
+fn duration_ms(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
 }
 
 fn lock_command_runs(
@@ -112,32 +246,275 @@ fn lock_command_runs(
 fn execute_command_record(
     phase: CommandPhase,
     index: usize,
-    command: &str,
+    (config_index, hook): IndexedHook<'_>,
     runner: &impl CommandRunner,
     hook_args: &[String],
     stdin_payload: Option<&[u8]>,
+    context: &RunContext<'_>,
 ) -> CommandRun {
+    let started_at = SystemTime::now();
     let started = Instant::now();
-    let outcome = if command.trim().is_empty() {
+    let today = context.today.unwrap_or_else(CalendarDate::today_utc);
+    let uses_changed_files = hook.command.contains(CHANGED_FILES_PLACEHOLDER);
+    let command = match &context.changed_files {
+        Some(files) if uses_changed_files && !files.is_empty() => Cow::Owned(expand_changed_files(
+            &hook.command,
+            files,
+            hook.no_shell,
+            Platform::current(),
+            context.windows_shell,
+        )),
+        _ => Cow::Borrowed(hook.command.as_str()),
+    };
+    let command = command.as_ref();
+    let run_name = context.run_name.clone().unwrap_or_default();
+    if let Some(events) = context.events {
+        events.emit(&Event::CommandStarted {
+            run: run_name.clone(),
+            index: config_index,
+            label: hook_label(config_index, hook),
+            command: redact_command(command),
+            parallel: phase == CommandPhase::Parallel,
+        });
+    }
+    let mut resources = None;
+    let mut output_truncated_bytes = 0;
+    let mut partial_output = Vec::new();
+    let unmet = hook.requires.iter().find_map(|requirement| {
+        probe::unmet_reason(requirement).map(|reason| (requirement.to_string(), reason))
+    });
+    let argv = hook.no_shell.then(|| direct_argv(command));
+    let label = hook_label(config_index, hook);
+    let passed_before = context
+        .dedup
+        .and_then(|memo| memo.passed_before(hook, hook_args, &run_name));
+    let outcome = if context.simulated_failures.contains(&label) {
+        CommandOutcome::SimulatedFailure { label }
+    } else if let Some(opt_out) = context
+        .opt_out
+        .filter(|opt_out| opt_out.contains(&run_name, &label))
+    {
+        CommandOutcome::OptedOut {
+            path: opt_out.path().display().to_string(),
+        }
+    } else if let Some(passed_in) = passed_before {
+        CommandOutcome::Deduplicated { passed_in }
+    } else if let Some(budget) = context
+        .budget
+        .filter(|budget| Instant::now() >= budget.deadline)
+    {
+        CommandOutcome::OverBudget {
+            max_duration: budget.max_duration,
+            fail: !budget.over_budget.is_skip(),
+        }
+    } else if uses_changed_files
+        && context
+            .changed_files
+            .as_ref()
+            .is_none_or(|files| files.is_empty())
+    {
+        CommandOutcome::NoChangedFiles
+    } else if command.trim().is_empty() {
         CommandOutcome::NoCommandDefined
+    } else if let Some(Err(message)) = argv {
+        CommandOutcome::SpawnFailed {
+            command: redact_command(command),
+            shell: DIRECT_EXEC_DISPLAY.to_string(),
+            error_kind: io::ErrorKind::NotFound,
+            message,
+        }
+    } else if let Some((requirement, reason)) = unmet {
+        match hook.on_missing {
+            MissingRequirement::Fail => CommandOutcome::MissingPrerequisite {
+                requirement,
+                reason,
+            },
+            MissingRequirement::Skip => CommandOutcome::PrerequisiteSkipped {
+                requirement,
+                reason,
+            },
+        }
     } else {
-        match runner.run(command, hook_args, stdin_payload) {
-            Ok(Some(0)) => CommandOutcome::Success,
-            Ok(Some(exit_status_code)) => CommandOutcome::Exit(exit_status_code),
-            Ok(None) => CommandOutcome::Signal,
+        let tmp_dir = match (&context.tmp_root, hook.isolate_tmp) {
+            (Some(tmp_root), true) => Some(IsolatedTmpDir::create(tmp_root, config_index)),
+            _ => None,
+        }
+        .transpose();
+        let artifacts_dir = match (&context.artifacts_root, hook.artifacts_dir) {
+            (Some(artifacts_root), true) => {
+                Some(artifacts::create_hook_dir(artifacts_root, &label))
+            }
+            _ => None,
+        }
+        .transpose();
+        let result = tmp_dir.and_then(|tmp_dir| {
+            let options = CommandOptions {
+                termination: TerminationPolicy::for_hook(hook),
+                tmp_dir: tmp_dir.as_ref().map(|dir| dir.path().to_path_buf()),
+                artifacts_dir: artifacts_dir?,
+                output_label: (phase == CommandPhase::Parallel)
+                    .then(|| hook_label(config_index, hook)),
+                show_command: context.show_commands.then(|| {
+                    format!(
+                        "{} command #{} ({})",
+                        phase.as_str(),
+                        index + 1,
+                        hook_label(config_index, hook)
+                    )
+                }),
+                argv: argv.and_then(Result::ok),
+                max_output_bytes: hook.max_output_bytes,
+                active_run: context.run_name.clone(),
+                sandbox: context.sandbox.clone(),
+                network_block: if hook.network == Some(false) {
+                    context.network_block.clone()
+                } else {
+                    None
+                },
+                windows_shell: context.windows_shell,
+            };
+            // Taken before the mutex, so a command holding the mutex never
+            // waits for a group slot held by a command waiting for the mutex.
+            let _group_slot = hook
+                .group
+                .as_deref()
+                .map(|name| context.groups.acquire(name))
+                .transpose()?;
+            let _mutex = hook
+                .mutex
+                .as_deref()
+                .map(|name| context.mutexes.acquire(name))
+                .transpose()?;
+            runner.run(command, hook_args, stdin_payload, &options)
+        });
+        match result {
+            Ok(completion) => {
+                resources = completion.resources;
+                output_truncated_bytes = completion.output_truncated_bytes;
+                partial_output = completion.partial_output;
+                match completion.exit {
+                    CommandExit::Exited(Some(0)) => CommandOutcome::Success,
+                    CommandExit::Exited(Some(exit_status_code))
+                        if hook.success_codes.contains(&exit_status_code) =>
+                    {
+                        CommandOutcome::AcceptedExit(exit_status_code)
+                    }
+                    CommandExit::Exited(Some(exit_status_code)) => {
+                        CommandOutcome::Exit(exit_status_code)
+                    }
+                    CommandExit::Exited(None) => CommandOutcome::Signal,
+                    CommandExit::Stopped { reason, signal } => {
+                        CommandOutcome::Stopped { reason, signal }
+                    }
+                }
+            }
             Err(source) => CommandOutcome::SpawnFailed {
                 command: redact_command(command),
-                shell: runner.shell_display().to_string(),
-                source,
+                shell: if hook.no_shell {
+                    DIRECT_EXEC_DISPLAY
+                } else {
+                    runner.shell_display(context.windows_shell)
+                }
+                .to_string(),
+                error_kind: source.kind(),
+                message: source.to_string(),
             },
         }
     };
-    CommandRun {
+    let passed = matches!(
+        outcome,
+        CommandOutcome::Success
+            | CommandOutcome::AcceptedExit(_)
+            | CommandOutcome::Deduplicated { .. }
+    );
+    if let Some((journal, lifecycle_phase)) = context.journal
+        && passed
+    {
+        journal.record_passed(lifecycle_phase, config_index, &hook.command);
+    }
+    if let Some(memo) = context.dedup
+        && passed
+    {
+        memo.record_passed(hook, hook_args, &run_name);
+    }
+    let run = CommandRun {
         phase,
         index,
+        config_index,
+        started_at,
         duration: started.elapsed(),
+        resources,
+        output_truncated_bytes,
+        partial_output,
+        // Running out of time fails the phase whatever the command's severity.
+        severity: if matches!(outcome, CommandOutcome::OverBudget { fail: true, .. }) {
+            Severity::Error
+        } else {
+            hook.effective_severity(today)
+        },
+        enforce_after: hook
+            .enforce_after
+            .filter(|enforce_after| today < *enforce_after),
+        description_url: hook.description_url.clone(),
         outcome,
+    };
+    if let Some(events) = context.events {
+        events.emit(&Event::CommandFinished {
+            run: run_name,
+            index: config_index,
+            label: hook_label(config_index, hook),
+            outcome: run.outcome.kind().to_string(),
+            blocking: run.is_blocking_failure(),
+            duration_ms: duration_ms(run.duration),
+        });
     }
+    run
+}
+
+/// Shown in place of the shell for `no_shell` commands.
+const DIRECT_EXEC_DISPLAY: &str = "direct exec";
+
+/// Replaces [`CHANGED_FILES_PLACEHOLDER`] with `files`, each quoted as one
+/// word for the shell the command runs in on `platform`.
+pub(super) fn expand_changed_files(
+    command: &str,
+    files: &[PathBuf],
+    no_shell: bool,
+    platform: Platform,
+    windows_shell: WindowsShell,
+) -> String {
+    let quote: fn(&str) -> String = match (platform, windows_shell) {
+        _ if no_shell => crate::shell_quote::sh_quote,
+        (Platform::Windows, WindowsShell::Cmd) => crate::shell_quote::cmd_quote,
+        (Platform::Windows, WindowsShell::PowerShell | WindowsShell::Pwsh) => {
+            crate::shell_quote::powershell_quote
+        }
+        (Platform::Unix, _) => crate::shell_quote::sh_quote,
+    };
+    command.replace(
+        CHANGED_FILES_PLACEHOLDER,
+        &files
+            .iter()
+            .map(|path| quote(&path.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Splits a `no_shell` command and resolves its program on `PATH`, so a
+/// missing executable is reported by name instead of as a bare spawn error.
+fn direct_argv(command: &str) -> Result<(PathBuf, Vec<String>), String> {
+    let mut words = shell_words::split(command)
+        .map_err(|error| format!("command cannot be split into words: {error}"))?
+        .into_iter();
+    let program = words.next().unwrap_or_default();
+    let path = probe::find_executable(&program).ok_or_else(|| {
+        format!(
+            "executable '{}' not found on PATH",
+            redact_secret_values(&program)
+        )
+    })?;
+    Ok((path, words.collect()))
 }
 
 #[cfg(test)]
@@ -150,16 +527,24 @@ pub(super) fn execute_command(
     if command.trim().is_empty() {
         return Err(Error::NoCommandDefined);
     }
-    let exit_code = runner
-        .run(command, hook_args, stdin_payload)
+    let completion = runner
+        .run(
+            command,
+            hook_args,
+            stdin_payload,
+            &CommandOptions::default(),
+        )
         .map_err(|source| Error::CommandSpawnFailed {
             command: redact_command(command),
-            shell: runner.shell_display().to_string(),
+            shell: runner.shell_display(WindowsShell::default()).to_string(),
             source,
         })?;
-    match exit_code {
-        Some(0) => Ok(()),
-        Some(exit_status_code) => Err(Error::ExecutionFailed(exit_status_code)),
-        None => Err(Error::ExecutionTerminatedBySignal),
+    match completion.exit {
+        CommandExit::Exited(Some(0)) => Ok(()),
+        CommandExit::Exited(Some(exit_status_code)) => {
+            Err(Error::ExecutionFailed(exit_status_code))
+        }
+        CommandExit::Exited(None) => Err(Error::ExecutionTerminatedBySignal),
+        CommandExit::Stopped { reason, signal } => Err(Error::ExecutionStopped { reason, signal }),
     }
 }
@@ -0,0 +1,110 @@
+//! Short description of the machine a run failed on, printed under the
+//! failure summary when `settings.env_snapshot` is set, so a pasted failure
+//! shows which tools, `PATH`, and Git the hooks saw.
+//!
+//! Tools are the ones named in the failing run's `requires` entries; they are
+//! looked up the same way the `requires` check does.
+
+use std::{collections::BTreeSet, env, path::PathBuf};
+
+use crate::config::HookDefinition;
+
+use super::probe::{find_executable, probe_version};
+
+/// `PATH` entries listed before the rest are summarized as a count.
+const LISTED_PATH_ENTRIES: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvironmentSnapshot {
+    os: &'static str,
+    arch: &'static str,
+    git_version: Option<String>,
+    path_entries: Vec<PathBuf>,
+    tools: Vec<ToolSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ToolSnapshot {
+    tool: String,
+    /// Resolved executable and its reported version, if it was found.
+    found: Option<(PathBuf, Option<String>)>,
+}
+
+/// Probes Git, `PATH`, and every tool named by `hooks`' `requires`.
+pub fn environment_snapshot(hooks: &[HookDefinition]) -> EnvironmentSnapshot {
+    let tools: BTreeSet<&str> = hooks
+        .iter()
+        .flat_map(|hook| &hook.requires)
+        .map(|requirement| requirement.tool())
+        .collect();
+    EnvironmentSnapshot {
+        os: env::consts::OS,
+        arch: env::consts::ARCH,
+        git_version: find_executable("git").and_then(|git| probe_version(&git)),
+        path_entries: env::var_os("PATH")
+            .map(|path| env::split_paths(&path).collect())
+            .unwrap_or_default(),
+        tools: tools
+            .into_iter()
+            .map(|tool| ToolSnapshot {
+                tool: tool.to_string(),
+                found: find_executable(tool).map(|path| {
+                    let version = probe_version(&path);
+                    (path, version)
+                }),
+            })
+            .collect(),
+    }
+}
+
+impl EnvironmentSnapshot {
+    pub fn text_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            "Environment snapshot:".to_string(),
+            format!(
+                "  platform: {} {} (git-smee {})",
+                self.os,
+                self.arch,
+                env!("CARGO_PKG_VERSION")
+            ),
+            format!(
+                "  git: {}",
+                self.git_version.as_deref().unwrap_or("not found on PATH")
+            ),
+        ];
+        let listed: Vec<String> = self
+            .path_entries
+            .iter()
+            .take(LISTED_PATH_ENTRIES)
+            .map(|entry| {
+                if entry.is_dir() {
+                    entry.display().to_string()
+                } else {
+                    format!("{} (missing)", entry.display())
+                }
+            })
+            .collect();
+        let mut path = format!("  PATH: {} entries", self.path_entries.len());
+        if !listed.is_empty() {
+            path.push_str(&format!(": {}", listed.join(", ")));
+        }
+        if let Some(rest) = self.path_entries.len().checked_sub(LISTED_PATH_ENTRIES)
+            && rest > 0
+        {
+            path.push_str(&format!(", ... {rest} more"));
+        }
+        lines.push(path);
+        for tool in &self.tools {
+            lines.push(match &tool.found {
+                None => format!("  {}: not found on PATH", tool.tool),
+                Some((path, version)) => format!(
+                    "  {}: {} at {}",
+                    tool.tool,
+                    version.as_deref().unwrap_or("unknown version"),
+                    path.display()
+                ),
+            });
+        }
+        lines
+    }
+}
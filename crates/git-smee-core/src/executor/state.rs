@@ -0,0 +1,303 @@
+//! Housekeeping of `.git/smee/`, where git-smee keeps its state: run state
+//! files (`runs/`), artifacts (`artifacts/`), isolated temp dirs (`tmp/`), the
+//! audit log (`audit.jsonl`), and the analytics store (`analytics.jsonl`).
+//! Mutex lock files (`locks/`) are left alone; they are tiny and may be held by
+//! a running hook.
+//!
+//! Entries modified before `smee.stateExpire` (default: 30 days ago) are
+//! removed, and while the rest is larger than `smee.stateMaxSize` (default:
+//! 256 MiB) the oldest go next. Each line of the audit log and the analytics
+//! store counts as one entry, dated by when its command started. `git smee run` prunes at most once a day;
+//! `git smee clean` prunes on demand.
+
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Deserialize;
+
+use crate::repository;
+
+use super::{
+    ANALYTICS_GIT_PATH, ARTIFACTS_GIT_PATH, AUDIT_LOG_GIT_PATH, Error, ISOLATED_TMP_GIT_PATH,
+    RUNS_GIT_PATH,
+};
+
+/// Location of the state directory, relative to the git directory.
+pub const STATE_GIT_PATH: &str = "smee";
+
+/// Git config key with the expiry date of state entries, e.g. `2.weeks.ago`.
+pub const STATE_EXPIRE_KEY: &str = "smee.stateExpire";
+
+/// Git config key with the size the state is pruned to; `0` means unlimited.
+pub const STATE_MAX_SIZE_KEY: &str = "smee.stateMaxSize";
+
+/// Age after which entries expire when `smee.stateExpire` is not set.
+pub const DEFAULT_STATE_EXPIRY: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Size limit, in bytes, when `smee.stateMaxSize` is not set.
+pub const DEFAULT_STATE_MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// How often `git smee run` prunes.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// File in the state directory whose modification time records the last
+/// automatic prune.
+const PRUNE_STAMP: &str = "last-prune";
+
+/// Directories whose entries are pruned.
+const PRUNED_DIRS: [&str; 3] = [RUNS_GIT_PATH, ARTIFACTS_GIT_PATH, ISOLATED_TMP_GIT_PATH];
+
+/// JSON-lines logs whose lines are pruned one by one.
+const PRUNED_LOGS: [&str; 2] = [AUDIT_LOG_GIT_PATH, ANALYTICS_GIT_PATH];
+
+/// What pruning removes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneLimits {
+    /// Entries last modified before this are removed.
+    pub expire_before: Option<SystemTime>,
+    /// Oldest entries are removed while the state is larger than this.
+    pub max_size: Option<u64>,
+}
+
+impl PruneLimits {
+    /// Limits from `smee.stateExpire` and `smee.stateMaxSize`, with their
+    /// defaults.
+    pub fn from_git_config(repository_root: &Path) -> Result<Self, repository::Error> {
+        let expire_before =
+            match repository::git_config_expiry_date(repository_root, STATE_EXPIRE_KEY)? {
+                Some(expire_before) => Some(expire_before),
+                None => SystemTime::now().checked_sub(DEFAULT_STATE_EXPIRY),
+            };
+        let max_size = match repository::git_config_int(repository_root, STATE_MAX_SIZE_KEY)? {
+            Some(0) => None,
+            Some(max_size) => Some(max_size),
+            None => Some(DEFAULT_STATE_MAX_SIZE),
+        };
+        Ok(Self {
+            expire_before,
+            max_size,
+        })
+    }
+
+    /// Limits that remove every entry.
+    pub fn everything() -> Self {
+        Self {
+            expire_before: None,
+            max_size: Some(0),
+        }
+    }
+}
+
+/// What a prune removed, or would remove in a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrunedState {
+    /// Removed run state files and directories, oldest first.
+    pub removed: Vec<PathBuf>,
+    /// Removed lines of the audit log and the analytics store.
+    pub removed_log_lines: usize,
+    pub freed_bytes: u64,
+    pub kept_bytes: u64,
+}
+
+impl PrunedState {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.removed_log_lines == 0
+    }
+}
+
+enum Entry {
+    Path(PathBuf),
+    /// Line `.1` of the log at index `.0` of [`PRUNED_LOGS`].
+    LogLine(usize, usize),
+}
+
+struct SizedEntry {
+    entry: Entry,
+    modified: SystemTime,
+    bytes: u64,
+}
+
+/// The part of a log record pruning dates lines by.
+#[derive(Deserialize)]
+struct LogLineTime {
+    started_at_unix_ms: u64,
+}
+
+/// Prunes the state directory at `state_dir` to `limits`. With `dry_run`
+/// nothing is removed, but the result lists what would be.
+pub fn prune_state(
+    state_dir: &Path,
+    limits: &PruneLimits,
+    dry_run: bool,
+) -> io::Result<PrunedState> {
+    let mut entries = Vec::new();
+    for git_path in PRUNED_DIRS {
+        let dir = state_path(state_dir, git_path);
+        let children = match fs::read_dir(&dir) {
+            Ok(children) => children,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+        for child in children {
+            let path = child?.path();
+            let (bytes, modified) = tree_usage(&path)?;
+            entries.push(SizedEntry {
+                entry: Entry::Path(path),
+                modified,
+                bytes,
+            });
+        }
+    }
+    let mut logs = Vec::new();
+    for (log_index, git_path) in PRUNED_LOGS.into_iter().enumerate() {
+        let log = state_path(state_dir, git_path);
+        let lines: Vec<String> = match fs::read_to_string(&log) {
+            Ok(contents) => contents.lines().map(str::to_string).collect(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error),
+        };
+        entries.extend(lines.iter().enumerate().map(|(index, line)| {
+            // Unreadable lines, e.g. one cut off by a crash, go first.
+            let started_at = serde_json::from_str::<LogLineTime>(line).map_or(UNIX_EPOCH, |time| {
+                UNIX_EPOCH + Duration::from_millis(time.started_at_unix_ms)
+            });
+            SizedEntry {
+                entry: Entry::LogLine(log_index, index),
+                modified: started_at,
+                bytes: line.len() as u64 + 1,
+            }
+        }));
+        logs.push((log, lines));
+    }
+    entries.sort_by_key(|entry| entry.modified);
+
+    let mut pruned = PrunedState {
+        kept_bytes: entries.iter().map(|entry| entry.bytes).sum(),
+        ..PrunedState::default()
+    };
+    let mut removed_lines = HashSet::new();
+    for entry in entries {
+        let expired = limits
+            .expire_before
+            .is_some_and(|expire_before| entry.modified < expire_before);
+        let over_size = limits
+            .max_size
+            .is_some_and(|max_size| pruned.kept_bytes > max_size);
+        if !expired && !over_size {
+            break;
+        }
+        pruned.kept_bytes -= entry.bytes;
+        pruned.freed_bytes += entry.bytes;
+        match entry.entry {
+            Entry::Path(path) => {
+                if !dry_run {
+                    remove_path(&path)?;
+                }
+                pruned.removed.push(path);
+            }
+            Entry::LogLine(log_index, index) => {
+                removed_lines.insert((log_index, index));
+            }
+        }
+    }
+    pruned.removed_log_lines = removed_lines.len();
+    for (log_index, (log, lines)) in logs.iter().enumerate() {
+        if dry_run
+            || !removed_lines
+                .iter()
+                .any(|(removed, _)| *removed == log_index)
+        {
+            continue;
+        }
+        let kept_lines: Vec<_> = lines
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !removed_lines.contains(&(log_index, *index)))
+            .map(|(_, line)| format!("{line}\n"))
+            .collect();
+        rewrite_log(log, &kept_lines.concat())?;
+    }
+    Ok(pruned)
+}
+
+/// Prunes the repository's state to the configured limits unless that
+/// happened within the last day. Returns `None` when pruning was not due.
+pub fn prune_state_if_due(repository_root: &Path) -> Result<Option<PrunedState>, Error> {
+    let state_dir = repository::resolve_git_path(repository_root, STATE_GIT_PATH)?;
+    if !state_dir.is_dir() {
+        return Ok(None);
+    }
+    let stamp = state_dir.join(PRUNE_STAMP);
+    let pruned_recently = fs::metadata(&stamp)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|pruned_at| {
+            pruned_at
+                .elapsed()
+                .is_ok_and(|elapsed| elapsed < PRUNE_INTERVAL)
+        });
+    if pruned_recently {
+        return Ok(None);
+    }
+    let limits = PruneLimits::from_git_config(repository_root)?;
+    let state_error = |source| Error::StatePruneFailed {
+        path: state_dir.display().to_string(),
+        source,
+    };
+    let pruned = prune_state(&state_dir, &limits, false).map_err(state_error)?;
+    fs::write(&stamp, b"").map_err(state_error)?;
+    Ok(Some(pruned))
+}
+
+/// Where `git_path`, one of the `smee/...` git paths, lives in `state_dir`.
+fn state_path(state_dir: &Path, git_path: &str) -> PathBuf {
+    let relative = Path::new(git_path)
+        .strip_prefix(STATE_GIT_PATH)
+        .unwrap_or(Path::new(git_path));
+    state_dir.join(relative)
+}
+
+/// Size of the files below `path` and the latest modification time in it.
+/// Symlinks are not followed.
+fn tree_usage(path: &Path) -> io::Result<(u64, SystemTime)> {
+    let metadata = fs::symlink_metadata(path)?;
+    let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+    if !metadata.is_dir() {
+        return Ok((metadata.len(), modified));
+    }
+    let mut usage = (0, modified);
+    for child in fs::read_dir(path)? {
+        let (bytes, modified) = tree_usage(&child?.path())?;
+        usage = (usage.0 + bytes, usage.1.max(modified));
+    }
+    Ok(usage)
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    let removed = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(error) => Err(error),
+    };
+    match removed {
+        // Another run may have cleaned it up already.
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        removed => removed,
+    }
+}
+
+/// Replaces a log in one rename, so a concurrent reader never sees it
+/// half-written. An emptied log is removed.
+fn rewrite_log(log: &Path, contents: &str) -> io::Result<()> {
+    if contents.is_empty() {
+        return remove_path(log);
+    }
+    let directory = log.parent().unwrap_or(Path::new("."));
+    let mut temp = tempfile::NamedTempFile::new_in(directory)?;
+    io::Write::write_all(&mut temp, contents.as_bytes())?;
+    temp.persist(log).map_err(|error| error.error)?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Append-only JSON-lines logs under `.git/smee/`, the storage layer of the
+//! audit log and the analytics store.
+//!
+//! Each record is one line, so appending is a single write and a line cut off
+//! by a crash only loses that record: readers skip lines they cannot parse.
+//! Pruning (see [`super::prune_state`]) dates lines by their
+//! `started_at_unix_ms` field, which every record type has.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// A JSON-lines log at a fixed path. The file is created on the first append.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordLog {
+    path: PathBuf,
+}
+
+/// Records read from a [`RecordLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedRecords<T> {
+    pub records: Vec<T>,
+    /// Lines that could not be read, e.g. a record cut off by a crash.
+    pub skipped_lines: usize,
+}
+
+impl RecordLog {
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `records` as JSON lines in a single write.
+    pub fn append<T: Serialize>(&self, records: &[T]) -> io::Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(lines.as_bytes())
+    }
+
+    /// Reads every record; a missing log has none.
+    pub fn load<T: DeserializeOwned>(&self) -> io::Result<LoadedRecords<T>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error),
+        };
+        let mut loaded = LoadedRecords {
+            records: Vec::new(),
+            skipped_lines: 0,
+        };
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(record) => loaded.records.push(record),
+                Err(_) => loaded.skipped_lines += 1,
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Removes the log and returns how many lines it had.
+    pub fn reset(&self) -> io::Result<usize> {
+        let lines = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents.lines().count(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error),
+        };
+        match fs::remove_file(&self.path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(lines),
+        }
+    }
+}
@@ -1,8 +1,16 @@
-use std::{io, time::Duration};
+use std::{
+    io,
+    time::{Duration, SystemTime},
+};
 
-use crate::config::LifeCyclePhase;
+use crate::config::{CalendarDate, MaxDuration, Severity};
 
-use super::Error;
+use super::{
+    Error,
+    resources::ResourceUsage,
+    runner::ACTIVE_RUN_ENV,
+    termination::{StopReason, StopSignal},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CommandPhase {
@@ -11,7 +19,7 @@ pub enum CommandPhase {
 }
 
 impl CommandPhase {
-    const fn as_str(self) -> &'static str {
+    pub(super) const fn as_str(self) -> &'static str {
         match self {
             Self::Sequential => "sequential",
             Self::Parallel => "parallel",
@@ -19,53 +27,150 @@ impl CommandPhase {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HookRunSummary {
     pub(super) total_configured: usize,
     pub(super) total_duration: Duration,
     pub(super) sequential_duration: Duration,
     pub(super) parallel_duration: Duration,
     pub(super) command_runs: Vec<CommandRun>,
+    pub(super) disabled_by: Option<String>,
+    /// Run that was already active when this one started, if the run was
+    /// skipped as nested.
+    pub(super) nested_in: Option<String>,
 }
 
 impl HookRunSummary {
+    pub(super) fn disabled(total_configured: usize, disabled_by: String) -> Self {
+        Self {
+            total_configured,
+            total_duration: Duration::ZERO,
+            sequential_duration: Duration::ZERO,
+            parallel_duration: Duration::ZERO,
+            command_runs: Vec::new(),
+            disabled_by: Some(disabled_by),
+            nested_in: None,
+        }
+    }
+
+    pub(super) fn nested(total_configured: usize, nested_in: String) -> Self {
+        Self {
+            total_configured,
+            total_duration: Duration::ZERO,
+            sequential_duration: Duration::ZERO,
+            parallel_duration: Duration::ZERO,
+            command_runs: Vec::new(),
+            disabled_by: None,
+            nested_in: Some(nested_in),
+        }
+    }
+
+    /// Git config key that switched this phase off, if the run was skipped.
+    pub fn disabled_by(&self) -> Option<&str> {
+        self.disabled_by.as_deref()
+    }
+
+    /// Name of the enclosing git-smee run, e.g. `pre-commit`, if this run
+    /// was skipped because it started inside it.
+    pub fn nested_in(&self) -> Option<&str> {
+        self.nested_in.as_deref()
+    }
+
     pub fn total_configured(&self) -> usize {
         self.total_configured
     }
 
+    pub fn command_runs(&self) -> &[CommandRun] {
+        &self.command_runs
+    }
+
+    /// Number of commands that ran. Commands skipped because a `requires`
+    /// entry was not met count as skipped.
     pub fn attempted_count(&self) -> usize {
-        self.command_runs.len()
+        self.command_runs
+            .iter()
+            .filter(|run| run.was_attempted())
+            .count()
     }
 
     pub fn skipped_count(&self) -> usize {
         self.total_configured.saturating_sub(self.attempted_count())
     }
 
+    /// Commands skipped because the user opted out of them.
+    pub fn opted_out_count(&self) -> usize {
+        self.command_runs
+            .iter()
+            .filter(|run| matches!(run.outcome, CommandOutcome::OptedOut { .. }))
+            .count()
+    }
+
+    /// The opt-out file that skipped commands of this run, if any.
+    pub fn opt_out_file(&self) -> Option<&str> {
+        self.command_runs.iter().find_map(|run| match &run.outcome {
+            CommandOutcome::OptedOut { path } => Some(path.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Commands skipped because the phase's time budget was used up.
+    pub fn over_budget_count(&self) -> usize {
+        self.command_runs
+            .iter()
+            .filter(|run| run.was_skipped_over_budget())
+            .count()
+    }
+
+    /// Skipped commands that already passed earlier in the session.
+    pub fn deduplicated_count(&self) -> usize {
+        self.command_runs
+            .iter()
+            .filter(|run| run.was_deduplicated())
+            .count()
+    }
+
+    /// Number of blocking failures. Failing `severity = "warning"` commands are
+    /// counted by [`Self::warning_count`] instead.
     pub fn failed_count(&self) -> usize {
         self.command_runs
             .iter()
-            .filter(|run| run.outcome.is_failure())
+            .filter(|run| run.is_blocking_failure())
             .count()
     }
 
+    pub fn warning_count(&self) -> usize {
+        self.warnings().count()
+    }
+
+    /// Failed commands marked `severity = "warning"`, in report order.
+    pub fn warnings(&self) -> impl Iterator<Item = &CommandRun> {
+        let mut warnings: Vec<_> = self
+            .command_runs
+            .iter()
+            .filter(|run| run.is_warning())
+            .collect();
+        warnings.sort_by_key(|run| (run.phase_sort_key(), run.index));
+        warnings.into_iter()
+    }
+
     pub fn phase_attempted_count(&self, phase: CommandPhase) -> usize {
         self.command_runs
             .iter()
-            .filter(|run| run.phase == phase)
+            .filter(|run| run.phase == phase && run.was_attempted())
             .count()
     }
 
     pub fn phase_failed_count(&self, phase: CommandPhase) -> usize {
         self.command_runs
             .iter()
-            .filter(|run| run.phase == phase && run.outcome.is_failure())
+            .filter(|run| run.phase == phase && run.is_blocking_failure())
             .count()
     }
 
     pub fn first_failure(&self) -> Option<&CommandRun> {
         self.command_runs
             .iter()
-            .filter(|run| run.outcome.is_failure())
+            .filter(|run| run.is_blocking_failure())
             .min_by_key(|run| (run.phase_sort_key(), run.index))
     }
 
@@ -73,14 +178,60 @@ impl HookRunSummary {
         self.first_failure().and_then(CommandRun::to_error)
     }
 
-    pub fn text_lines(&self, phase: LifeCyclePhase) -> Vec<String> {
+    /// The compact summary printed after a run.
+    pub fn text_lines(&self, phase: impl std::fmt::Display) -> Vec<String> {
+        self.render_text_lines(phase, false)
+    }
+
+    /// Like [`Self::text_lines`], with each command's CPU time and peak RSS
+    /// where the platform reports them.
+    pub fn verbose_text_lines(&self, phase: impl std::fmt::Display) -> Vec<String> {
+        self.render_text_lines(phase, true)
+    }
+
+    fn render_text_lines(
+        &self,
+        phase: impl std::fmt::Display,
+        with_resources: bool,
+    ) -> Vec<String> {
+        if let Some(disabled_by) = &self.disabled_by {
+            return vec![
+                format!("Hook summary: {phase}"),
+                format!(
+                    "  disabled by git config {disabled_by}; {} skipped (run git smee enable to re-enable)",
+                    self.skipped_count()
+                ),
+            ];
+        }
+        if let Some(nested_in) = &self.nested_in {
+            return vec![
+                format!("Hook summary: {phase}"),
+                format!(
+                    "  skipped inside the running {nested_in} hooks ({ACTIVE_RUN_ENV} is set); {} skipped (set settings.nested_runs = \"allow\" to run them)",
+                    self.skipped_count()
+                ),
+            ];
+        }
         let mut lines = vec![
             format!("Hook summary: {phase}"),
             format!(
-                "  total: {} attempted, {} skipped, {} failed in {}",
+                "  total: {} attempted, {} skipped{}, {} failed{} in {}",
                 self.attempted_count(),
                 self.skipped_count(),
+                match (self.deduplicated_count(), self.opted_out_count()) {
+                    (0, 0) => String::new(),
+                    (deduplicated, 0) => format!(" ({deduplicated} deduplicated)"),
+                    (0, opted_out) => format!(" ({opted_out} opted out)"),
+                    (deduplicated, opted_out) => {
+                        format!(" ({deduplicated} deduplicated, {opted_out} opted out)")
+                    }
+                },
                 self.failed_count(),
+                match self.warning_count() {
+                    0 => String::new(),
+                    1 => ", 1 warning".to_string(),
+                    count => format!(", {count} warnings"),
+                },
                 format_duration(self.total_duration),
             ),
             format!(
@@ -97,12 +248,54 @@ impl HookRunSummary {
             ),
         ];
         for run in &self.command_runs {
-            lines.push(format!(
+            let mut line = format!(
                 "  - {} command #{}: {} in {}",
                 run.phase.as_str(),
                 run.index + 1,
                 run.status_display(),
                 format_duration(run.duration),
+            );
+            if let Some(resources) = run.resources.as_ref().filter(|_| with_resources) {
+                line.push_str(&format!(
+                    " (cpu {}, peak rss {})",
+                    format_duration(resources.total_cpu()),
+                    format_bytes(resources.peak_rss_bytes),
+                ));
+            }
+            if run.output_truncated_bytes > 0 {
+                line.push_str(&format!(
+                    ", output truncated ({} bytes)",
+                    run.output_truncated_bytes
+                ));
+            }
+            lines.push(line);
+        }
+        for warning in self.warnings() {
+            let reason = match warning.enforce_after {
+                Some(enforce_after) => format!("enforce_after = \"{enforce_after}\""),
+                None => "severity = \"warning\"".to_string(),
+            };
+            lines.push(format!(
+                "  warning: {} ({reason}, not blocking)",
+                warning.failure_display()
+            ));
+        }
+        let over_budget: Vec<_> = self
+            .command_runs
+            .iter()
+            .filter(|run| run.was_skipped_over_budget())
+            .collect();
+        if let Some(CommandOutcome::OverBudget { max_duration, .. }) =
+            over_budget.first().map(|run| &run.outcome)
+        {
+            lines.push(format!(
+                "  warning: phase time budget of {} used up; not started: {}",
+                MaxDuration(*max_duration),
+                over_budget
+                    .iter()
+                    .map(|run| format!("{} command #{}", run.phase.as_str(), run.index + 1))
+                    .collect::<Vec<_>>()
+                    .join(", ")
             ));
         }
         if let Some(first_failure) = self.first_failure() {
@@ -111,19 +304,162 @@ impl HookRunSummary {
                 first_failure.failure_display()
             ));
         }
+        let mut documented: Vec<_> = self
+            .command_runs
+            .iter()
+            .filter(|run| run.outcome.is_failure())
+            .filter_map(|run| Some((run, run.description_url.as_deref()?)))
+            .collect();
+        documented.sort_by_key(|(run, _)| (run.phase_sort_key(), run.index));
+        for (run, url) in documented {
+            lines.push(format!(
+                "  see {url} for how to fix {} command #{}",
+                run.phase.as_str(),
+                run.index + 1
+            ));
+        }
+        for run in &self.command_runs {
+            if run.partial_output.is_empty() {
+                continue;
+            }
+            lines.push(format!(
+                "  partial output of {} command #{} before it was stopped (last {} lines):",
+                run.phase.as_str(),
+                run.index + 1,
+                run.partial_output.len()
+            ));
+            lines.extend(
+                run.partial_output
+                    .iter()
+                    .map(|line| format!("    | {line}")),
+            );
+        }
         lines
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CommandRun {
     pub(super) phase: CommandPhase,
     pub(super) index: usize,
+    /// Position of the command in the phase's config, across both phases.
+    pub(super) config_index: usize,
+    pub(super) started_at: SystemTime,
     pub(super) duration: Duration,
+    pub(super) resources: Option<ResourceUsage>,
+    /// Output bytes dropped by the command's `max_output_bytes` cap.
+    pub(super) output_truncated_bytes: u64,
+    /// Last output lines of a command stopped by a timeout or cancellation.
+    pub(super) partial_output: Vec<String>,
+    pub(super) severity: Severity,
+    /// Set while the command is inside its `enforce_after` soft-launch window.
+    pub(super) enforce_after: Option<CalendarDate>,
+    /// The command's `description_url`, printed when it fails.
+    pub(super) description_url: Option<String>,
     pub(super) outcome: CommandOutcome,
 }
 
 impl CommandRun {
+    pub fn phase(&self) -> CommandPhase {
+        self.phase
+    }
+
+    /// Zero-based position of the command within its scheduling phase.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Zero-based position of the command in the phase's config.
+    pub fn config_index(&self) -> usize {
+        self.config_index
+    }
+
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Exit code of the command, if it exited on its own.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self.outcome {
+            CommandOutcome::Success => Some(0),
+            CommandOutcome::Exit(code) | CommandOutcome::AcceptedExit(code) => Some(code),
+            _ => None,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// The `enforce_after` date when the command's failures are still being
+    /// downgraded to warnings.
+    pub fn enforce_after(&self) -> Option<CalendarDate> {
+        self.enforce_after
+    }
+
+    /// Runbook or docs page on fixing a failure of the command.
+    pub fn description_url(&self) -> Option<&str> {
+        self.description_url.as_deref()
+    }
+
+    /// Whether the command failed in a way that fails the whole run.
+    pub fn is_blocking_failure(&self) -> bool {
+        self.outcome.is_failure() && self.severity.is_error()
+    }
+
+    /// Whether the command ran, as opposed to being skipped over an unmet
+    /// requirement or as a duplicate.
+    pub fn was_attempted(&self) -> bool {
+        !matches!(
+            self.outcome,
+            CommandOutcome::PrerequisiteSkipped { .. }
+                | CommandOutcome::Deduplicated { .. }
+                | CommandOutcome::OptedOut { .. }
+                | CommandOutcome::NoChangedFiles
+                | CommandOutcome::OverBudget { .. }
+        )
+    }
+
+    /// Whether the command was skipped because the phase's time budget was
+    /// used up, with `over_budget = "skip"`.
+    pub fn was_skipped_over_budget(&self) -> bool {
+        matches!(self.outcome, CommandOutcome::OverBudget { fail: false, .. })
+    }
+
+    /// Whether the command was skipped because an identical one already
+    /// passed earlier in the session.
+    pub fn was_deduplicated(&self) -> bool {
+        matches!(self.outcome, CommandOutcome::Deduplicated { .. })
+    }
+
+    /// Whether the command failed but is marked `severity = "warning"`.
+    pub fn is_warning(&self) -> bool {
+        self.outcome.is_failure() && !self.severity.is_error()
+    }
+
+    /// CPU time and peak RSS of the command, when the platform reports them.
+    pub fn resources(&self) -> Option<ResourceUsage> {
+        self.resources
+    }
+
+    /// Bytes of output dropped because the command exceeded its
+    /// `max_output_bytes` cap; zero when nothing was truncated.
+    pub fn output_truncated_bytes(&self) -> u64 {
+        self.output_truncated_bytes
+    }
+
+    /// Last lines the command printed before it was stopped by a timeout or
+    /// cancellation. Only collected for commands whose output is forwarded
+    /// through a pipe: parallel commands and commands with
+    /// `max_output_bytes`.
+    pub fn partial_output(&self) -> &[String] {
+        &self.partial_output
+    }
+
     const fn phase_sort_key(&self) -> usize {
         match self.phase {
             CommandPhase::Sequential => 0,
@@ -132,12 +468,44 @@ impl CommandRun {
     }
 
     pub(super) fn status_display(&self) -> String {
+        let status = self.outcome_display();
+        match (self.is_warning(), self.enforce_after) {
+            (true, Some(enforce_after)) => format!("{status} (warning until {enforce_after})"),
+            (true, None) => format!("{status} (warning)"),
+            (false, _) => status,
+        }
+    }
+
+    fn outcome_display(&self) -> String {
         match &self.outcome {
             CommandOutcome::Success => "ok".to_string(),
+            CommandOutcome::AcceptedExit(code) => format!("ok (exit code {code} accepted)"),
             CommandOutcome::Exit(code) => format!("failed with code {code}"),
             CommandOutcome::Signal => "terminated by signal".to_string(),
+            CommandOutcome::Stopped { reason, signal } => {
+                format!("{reason}; stopped by {signal}")
+            }
             CommandOutcome::SpawnFailed { .. } => "spawn failed".to_string(),
             CommandOutcome::NoCommandDefined => "no command defined".to_string(),
+            CommandOutcome::MissingPrerequisite {
+                requirement,
+                reason,
+            } => format!("missing prerequisite {requirement} ({reason})"),
+            CommandOutcome::PrerequisiteSkipped {
+                requirement,
+                reason,
+            } => format!("skipped, missing prerequisite {requirement} ({reason})"),
+            CommandOutcome::SimulatedFailure { .. } => "simulated failure".to_string(),
+            CommandOutcome::Deduplicated { passed_in } => {
+                format!("deduplicated (already passed in {passed_in})")
+            }
+            CommandOutcome::OptedOut { path } => format!("opted out ({path})"),
+            CommandOutcome::NoChangedFiles => "skipped (no changed files)".to_string(),
+            CommandOutcome::OverBudget { max_duration, fail } => format!(
+                "{}, phase time budget of {} used up",
+                if *fail { "not started" } else { "skipped" },
+                MaxDuration(*max_duration)
+            ),
         }
     }
 
@@ -145,54 +513,185 @@ impl CommandRun {
         let prefix = format!("{} command #{}", self.phase.as_str(), self.index + 1);
         match &self.outcome {
             CommandOutcome::Success => format!("{prefix} succeeded"),
+            CommandOutcome::AcceptedExit(code) => {
+                format!("{prefix} exited with code {code}, listed in success_codes")
+            }
             CommandOutcome::Exit(code) => format!("{prefix} exited with code {code}"),
             CommandOutcome::Signal => format!("{prefix} was terminated by a signal"),
+            CommandOutcome::Stopped { reason, signal } => {
+                format!("{prefix} {reason} and was stopped by {signal}")
+            }
             CommandOutcome::SpawnFailed {
                 command,
                 shell,
-                source,
+                message,
+                ..
             } => {
-                format!("{prefix} failed to spawn '{command}' via '{shell}': {source}")
+                format!("{prefix} failed to spawn '{command}' via '{shell}': {message}")
             }
             CommandOutcome::NoCommandDefined => format!("{prefix} had no command defined"),
+            CommandOutcome::MissingPrerequisite {
+                requirement,
+                reason,
+            } => format!("{prefix} is missing prerequisite {requirement} ({reason})"),
+            CommandOutcome::PrerequisiteSkipped {
+                requirement,
+                reason,
+            } => format!("{prefix} was skipped: missing prerequisite {requirement} ({reason})"),
+            CommandOutcome::SimulatedFailure { .. } => {
+                format!("{prefix} failed (simulated by --simulate-failure)")
+            }
+            CommandOutcome::Deduplicated { passed_in } => {
+                format!("{prefix} was skipped: an identical command already passed in {passed_in}")
+            }
+            CommandOutcome::OptedOut { path } => {
+                format!("{prefix} was skipped: you opted out of it in {path}")
+            }
+            CommandOutcome::NoChangedFiles => {
+                format!("{prefix} was skipped: the push changes no files")
+            }
+            CommandOutcome::OverBudget { max_duration, .. } => format!(
+                "{prefix} was not started: the phase time budget of {} was used up",
+                MaxDuration(*max_duration)
+            ),
         }
     }
 
     pub(super) fn to_error(&self) -> Option<Error> {
         match &self.outcome {
-            CommandOutcome::Success => None,
+            CommandOutcome::Success | CommandOutcome::AcceptedExit(_) => None,
             CommandOutcome::Exit(code) => Some(Error::ExecutionFailed(*code)),
             CommandOutcome::Signal => Some(Error::ExecutionTerminatedBySignal),
+            CommandOutcome::Stopped { reason, signal } => Some(Error::ExecutionStopped {
+                reason: *reason,
+                signal: *signal,
+            }),
             CommandOutcome::SpawnFailed {
                 command,
                 shell,
-                source,
+                error_kind,
+                message,
             } => Some(Error::CommandSpawnFailed {
                 command: command.clone(),
                 shell: shell.clone(),
-                source: io::Error::new(source.kind(), source.to_string()),
+                source: io::Error::new(*error_kind, message.clone()),
             }),
             CommandOutcome::NoCommandDefined => Some(Error::NoCommandDefined),
+            CommandOutcome::MissingPrerequisite {
+                requirement,
+                reason,
+            } => Some(Error::MissingPrerequisite {
+                requirement: requirement.clone(),
+                reason: reason.clone(),
+            }),
+            CommandOutcome::PrerequisiteSkipped { .. }
+            | CommandOutcome::Deduplicated { .. }
+            | CommandOutcome::OptedOut { .. }
+            | CommandOutcome::NoChangedFiles
+            | CommandOutcome::OverBudget { fail: false, .. } => None,
+            CommandOutcome::OverBudget {
+                max_duration,
+                fail: true,
+            } => Some(Error::PhaseBudgetExceeded {
+                max_duration: MaxDuration(*max_duration).to_string(),
+            }),
+            CommandOutcome::SimulatedFailure { label } => {
+                Some(Error::SimulatedFailure(label.clone()))
+            }
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) enum CommandOutcome {
     Success,
+    /// Nonzero exit code listed in the hook's `success_codes`.
+    AcceptedExit(i32),
     Exit(i32),
     Signal,
+    Stopped {
+        reason: StopReason,
+        signal: StopSignal,
+    },
     SpawnFailed {
         command: String,
         shell: String,
-        source: io::Error,
+        /// Kind and message of the spawn error, kept instead of the
+        /// `io::Error` itself so outcomes stay `Clone` and comparable.
+        error_kind: io::ErrorKind,
+        message: String,
     },
     NoCommandDefined,
+    /// A `requires` entry was not met; `reason` says what was found instead.
+    MissingPrerequisite {
+        requirement: String,
+        reason: String,
+    },
+    /// Like [`Self::MissingPrerequisite`], for a hook with
+    /// `on_missing = "skip"`. The command did not run and did not fail.
+    PrerequisiteSkipped {
+        requirement: String,
+        reason: String,
+    },
+    /// Treated as failed without running, see
+    /// [`super::ExecutorConfig::with_simulated_failures`].
+    SimulatedFailure {
+        label: String,
+    },
+    /// Not run because an identical command already passed in `passed_in`
+    /// earlier in the session, see
+    /// [`super::ExecutorConfig::with_deduplication`].
+    Deduplicated {
+        passed_in: String,
+    },
+    /// Skipped because it takes `{changed_files}` and the push changes no
+    /// files, or the run has no pushed refs to read them from.
+    NoChangedFiles,
+    /// Skipped because the user's opt-out file at `path` lists it.
+    OptedOut {
+        path: String,
+    },
+    /// Not started because the phase's `max_duration` was used up. With
+    /// `fail`, from `over_budget = "fail"`, this fails the phase.
+    OverBudget {
+        max_duration: Duration,
+        fail: bool,
+    },
 }
 
 impl CommandOutcome {
     pub(super) const fn is_failure(&self) -> bool {
-        !matches!(self, Self::Success)
+        !matches!(
+            self,
+            Self::Success
+                | Self::AcceptedExit(_)
+                | Self::PrerequisiteSkipped { .. }
+                | Self::Deduplicated { .. }
+                | Self::OptedOut { .. }
+                | Self::NoChangedFiles
+                | Self::OverBudget { fail: false, .. }
+        )
+    }
+
+    /// Stable, machine-readable name of the outcome.
+    pub(super) const fn kind(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::AcceptedExit(_) => "accepted_exit",
+            Self::Exit(_) => "exit",
+            Self::Signal => "signal",
+            Self::Stopped { .. } => "stopped",
+            Self::SpawnFailed { .. } => "spawn_failed",
+            Self::NoCommandDefined => "no_command",
+            Self::MissingPrerequisite { .. } => "missing_prerequisite",
+            Self::PrerequisiteSkipped { .. } => "prerequisite_skipped",
+            Self::SimulatedFailure { .. } => "simulated_failure",
+            Self::Deduplicated { .. } => "deduplicated",
+            Self::OptedOut { .. } => "opted_out",
+            Self::NoChangedFiles => "no_changed_files",
+            Self::OverBudget { fail: false, .. } => "budget_skipped",
+            Self::OverBudget { fail: true, .. } => "budget_exceeded",
+        }
     }
 }
 
@@ -203,3 +702,15 @@ fn format_duration(duration: Duration) -> String {
         format!("{}ms", duration.as_millis())
     }
 }
+
+fn format_bytes(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = KIB * 1024;
+    const GIB: u64 = MIB * 1024;
+    match bytes {
+        bytes if bytes >= GIB => format!("{:.1} GiB", bytes as f64 / GIB as f64),
+        bytes if bytes >= MIB => format!("{:.1} MiB", bytes as f64 / MIB as f64),
+        bytes if bytes >= KIB => format!("{:.1} KiB", bytes as f64 / KIB as f64),
+        bytes => format!("{bytes} B"),
+    }
+}
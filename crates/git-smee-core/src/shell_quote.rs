@@ -0,0 +1,221 @@
+//! Quoting of values that git-smee splices into shell text: paths embedded in
+//! hook wrappers, hook arguments appended to `cmd.exe` command lines, changed
+//! files substituted into hook commands, and commands written into bootstrap
+//! scripts.
+//!
+//! Every function returns text that the named shell reads back as exactly
+//! one word holding the original value, whatever it contains: spaces, quotes,
+//! `$`, backticks, globs, newlines, or non-ASCII characters. This is the
+//! inverse of [`crate::shell_words::split`] for `sh`.
+
+use std::path::Path;
+
+/// Quotes `word` for `sh`, leaving words made only of characters that need no
+/// quoting (letters, digits, and `_@%+=:,./-`) bare. Use it where the result
+/// is shown to people, e.g. in a generated script line.
+pub fn sh_quote(word: &str) -> String {
+    let is_safe = |ch: char| {
+        ch.is_ascii_alphanumeric()
+            || matches!(
+                ch,
+                '_' | '@' | '%' | '+' | '=' | ':' | ',' | '.' | '/' | '-'
+            )
+    };
+    if !word.is_empty() && word.chars().all(is_safe) {
+        return word.to_string();
+    }
+    sh_single_quote(word)
+}
+
+/// Wraps `word` in single quotes for `sh`, closing and reopening the quotes
+/// around each embedded `'`.
+pub fn sh_single_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\"'\"'"))
+}
+
+/// Quotes `path` for `sh`. A Unix path that is not valid UTF-8 is rebuilt
+/// byte for byte with `printf '%b'` and POSIX `\0ddd` octal escapes, so it
+/// survives a UTF-8 script file unchanged. The command substitution is
+/// double-quoted so spaces in such a path do not split it into words.
+#[cfg(unix)]
+pub fn sh_quote_path(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    match path.as_os_str().to_str() {
+        Some(path) => sh_single_quote(path),
+        None => {
+            let escaped = path
+                .as_os_str()
+                .as_bytes()
+                .iter()
+                .map(|byte| format!(r"\0{byte:03o}"))
+                .collect::<String>();
+            format!(r#""$(printf '%b' '{escaped}')""#)
+        }
+    }
+}
+
+/// Quotes `path` for `sh`.
+#[cfg(not(unix))]
+pub fn sh_quote_path(path: &Path) -> String {
+    sh_single_quote(&path.to_string_lossy())
+}
+
+/// Quotes `arg` for a `cmd.exe` command line. Arguments with whitespace or
+/// `cmd` metacharacters are wrapped in double quotes, and `^`, `%`, and `!`
+/// are escaped so variable expansion cannot rewrite them.
+pub fn cmd_quote(arg: &str) -> String {
+    let needs_quotes = arg.is_empty()
+        || arg.chars().any(|ch| {
+            matches!(
+                ch,
+                ' ' | '\t' | '&' | '|' | '^' | '<' | '>' | '(' | ')' | '!' | '%'
+            )
+        });
+    if !needs_quotes {
+        return arg.to_string();
+    }
+
+    let mut escaped = String::with_capacity(arg.len() + 2);
+    for ch in arg.chars() {
+        match ch {
+            '^' => escaped.push_str("^^"),
+            '%' => escaped.push_str("%%"),
+            '!' => escaped.push_str("^!"),
+            '"' => escaped.push_str("\"\""),
+            _ => escaped.push(ch),
+        }
+    }
+    format!("\"{escaped}\"")
+}
+
+/// Quotes `word` for PowerShell as a single-quoted string, in which `$` and
+/// backticks are literal. Embedded single quotes, including the typographic
+/// ones PowerShell also accepts as quotes, are doubled.
+pub fn powershell_quote(word: &str) -> String {
+    let mut quoted = String::with_capacity(word.len() + 2);
+    quoted.push('\'');
+    for ch in word.chars() {
+        if matches!(ch, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            quoted.push(ch);
+        }
+        quoted.push(ch);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::shell_words;
+
+    /// Values that break naive interpolation.
+    const ADVERSARIAL_WORDS: [&str; 14] = [
+        "",
+        "with space",
+        "it's",
+        "\"double\"",
+        "$HOME",
+        "$(touch pwned)",
+        "`touch pwned`",
+        "a;b&&c|d",
+        "*.rs",
+        "-n",
+        "line\nbreak",
+        "back\\slash",
+        "ünïcødé ☃",
+        "'\"'\"'",
+    ];
+
+    #[test]
+    fn given_safe_word_when_sh_quoting_then_it_stays_bare() {
+        assert_eq!(sh_quote("hooks"), "hooks");
+        assert_eq!(sh_quote(".git-smee/hooks"), ".git-smee/hooks");
+        assert_eq!(sh_quote("my hooks"), "'my hooks'");
+        assert_eq!(sh_quote(""), "''");
+        assert_eq!(sh_quote("it's"), "'it'\"'\"'s'");
+    }
+
+    #[test]
+    fn given_adversarial_words_when_sh_quoting_then_split_returns_them_unchanged() {
+        for word in ADVERSARIAL_WORDS {
+            assert_eq!(
+                shell_words::split(&sh_quote(word)),
+                Ok(vec![word.to_string()])
+            );
+            assert_eq!(
+                shell_words::split(&sh_single_quote(word)),
+                Ok(vec![word.to_string()])
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_adversarial_words_when_sh_quoting_then_sh_prints_them_literally() {
+        let dir = tempfile::tempdir().unwrap();
+        for word in ADVERSARIAL_WORDS {
+            let output = std::process::Command::new("sh")
+                .current_dir(dir.path())
+                .arg("-c")
+                .arg(format!("printf '%s' {}", sh_quote(word)))
+                .output()
+                .unwrap();
+
+            assert!(output.status.success(), "sh failed for {word:?}");
+            assert_eq!(String::from_utf8_lossy(&output.stdout), word);
+        }
+        assert!(!dir.path().join("pwned").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_non_utf8_path_when_sh_quoting_then_sh_rebuilds_the_original_bytes() {
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+        let bytes = b"/tmp/it's \xFF\xFE/config.toml".to_vec();
+        let path = std::path::PathBuf::from(OsString::from_vec(bytes.clone()));
+
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("printf '%s' {}", sh_quote_path(&path)))
+            .output()
+            .unwrap();
+
+        assert_eq!(output.stdout, bytes);
+    }
+
+    #[test]
+    fn given_cmd_metacharacters_when_cmd_quoting_then_they_are_escaped_inside_quotes() {
+        assert_eq!(
+            cmd_quote("caret^bang!percent%"),
+            "\"caret^^bang^!percent%%\""
+        );
+        assert_eq!(cmd_quote("plain-ref"), "plain-ref");
+        assert_eq!(cmd_quote("%PATH%"), "\"%%PATH%%\"");
+        assert_eq!(cmd_quote("!PATH!"), "\"^!PATH^!\"");
+        assert_eq!(cmd_quote("space value"), "\"space value\"");
+        assert_eq!(cmd_quote("say \"hi\" & exit"), "\"say \"\"hi\"\" & exit\"");
+        assert_eq!(cmd_quote(""), "\"\"");
+    }
+
+    #[test]
+    fn given_path_with_dollar_and_quote_when_powershell_quoting_then_it_is_one_literal_string() {
+        assert_eq!(
+            powershell_quote("src/$env:HOME/it's `n 100%.ps1"),
+            "'src/$env:HOME/it''s `n 100%.ps1'"
+        );
+        assert_eq!(powershell_quote("it\u{2019}s"), "'it\u{2019}\u{2019}s'");
+        assert_eq!(powershell_quote(""), "''");
+    }
+
+    proptest! {
+        #[test]
+        fn sh_quote_round_trips_through_split(word in any::<String>()) {
+            prop_assert_eq!(shell_words::split(&sh_quote(&word)), Ok(vec![word]));
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Splitting of command lines into words the way a POSIX shell does, without
+//! running a shell.
+//!
+//! Used for `no_shell = true` hooks, which are executed directly, and to find
+//! the executable of a command for diagnostics. Quotes and backslash escapes
+//! follow `sh` rules; nothing is expanded, so `$HOME`, `*.rs`, `|`, and `&&`
+//! are passed through as literal text.
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("unterminated single quote")]
+    UnterminatedSingleQuote,
+    #[error("unterminated double quote")]
+    UnterminatedDoubleQuote,
+    #[error("trailing backslash")]
+    TrailingBackslash,
+}
+
+/// Splits `command` into words.
+///
+/// - Unquoted whitespace separates words; `#` at the start of a word begins a
+///   comment that runs to the end of the line.
+/// - `'...'` is taken literally.
+/// - Inside `"..."`, a backslash only escapes `$`, `` ` ``, `"`, `\`, and a
+///   newline; elsewhere it is kept.
+/// - An unquoted backslash escapes the next character, and a backslash before
+///   a newline joins the lines.
+///
+/// `''` and `""` produce an empty word.
+pub fn split(command: &str) -> Result<Vec<String>, Error> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // Whether a word has started, so quoted empty strings are kept.
+    let mut in_word = false;
+    let mut chars = command.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            ch if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '#' if !in_word => {
+                for ch in chars.by_ref() {
+                    if ch == '\n' {
+                        break;
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                None => return Err(Error::TrailingBackslash),
+                Some('\n') => {}
+                Some(escaped) => {
+                    word.push(escaped);
+                    in_word = true;
+                }
+            },
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        None => return Err(Error::UnterminatedSingleQuote),
+                        Some('\'') => break,
+                        Some(ch) => word.push(ch),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        None => return Err(Error::UnterminatedDoubleQuote),
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            None => return Err(Error::UnterminatedDoubleQuote),
+                            Some('\n') => {}
+                            Some(escaped @ ('$' | '`' | '"' | '\\')) => word.push(escaped),
+                            Some(other) => {
+                                word.push('\\');
+                                word.push(other);
+                            }
+                        },
+                        Some(ch) => word.push(ch),
+                    }
+                }
+            }
+            ch => {
+                word.push(ch);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::assert;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::shell_quote::sh_single_quote;
+
+    fn words(command: &str) -> Vec<String> {
+        split(command).unwrap()
+    }
+
+    #[test]
+    fn given_quoted_executable_when_splitting_then_quotes_are_removed_from_first_word() {
+        assert!(
+            words(r#""/opt/my tools/lint" --fix 'a b' c\ d"#)
+                == ["/opt/my tools/lint", "--fix", "a b", "c d"]
+        );
+        assert!(words(r"'it'\''s'") == ["it's"]);
+    }
+
+    #[test]
+    fn given_backslashes_in_double_quotes_when_splitting_then_only_special_characters_are_escaped()
+    {
+        assert!(words(r#""\$HOME \"x\" \\ \n""#) == [r#"$HOME "x" \ \n"#]);
+    }
+
+    #[test]
+    fn given_empty_quotes_and_comments_when_splitting_then_empty_words_are_kept_and_comments_dropped()
+     {
+        assert!(words("printf '' \"\" # trailing comment\nnext") == ["printf", "", "", "next"]);
+        assert!(words("a#b") == ["a#b"]);
+        assert!(words("  \t\n").is_empty());
+    }
+
+    #[test]
+    fn given_line_continuation_when_splitting_then_lines_are_joined() {
+        assert!(words("cargo \\\n  test") == ["cargo", "test"]);
+        assert!(words("car\\\ngo") == ["cargo"]);
+    }
+
+    #[test]
+    fn given_malformed_command_when_splitting_then_error_names_the_problem() {
+        assert!(split("echo 'open") == Err(Error::UnterminatedSingleQuote));
+        assert!(split("echo \"open") == Err(Error::UnterminatedDoubleQuote));
+        assert!(split("echo \"open\\") == Err(Error::UnterminatedDoubleQuote));
+        assert!(split("echo \\") == Err(Error::TrailingBackslash));
+    }
+
+    #[test]
+    fn given_shell_operators_when_splitting_then_they_are_literal_words() {
+        assert!(words("a && b | c $HOME *.rs") == ["a", "&&", "b", "|", "c", "$HOME", "*.rs"]);
+    }
+
+    proptest! {
+        #[test]
+        fn split_never_panics_for_arbitrary_input(command in any::<String>()) {
+            let _ = split(&command);
+        }
+
+        #[test]
+        fn split_inverts_single_quoting(original in proptest::collection::vec(any::<String>(), 0..6)) {
+            let command = original.iter().map(|word| sh_single_quote(word)).collect::<Vec<_>>().join(" ");
+            prop_assert_eq!(split(&command), Ok(original));
+        }
+
+        #[test]
+        fn split_inverts_backslash_escaping(
+            original in proptest::collection::vec("[^\n]{1,12}", 1..6)
+        ) {
+            let command = original
+                .iter()
+                .map(|word| word.chars().flat_map(|ch| ['\\', ch]).collect::<String>())
+                .collect::<Vec<_>>()
+                .join(" ");
+            prop_assert_eq!(split(&command), Ok(original));
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Line-based unified diffs, used to preview how `install` would change an
+//! existing hook script.
+//!
+//! Hook scripts are short, so a plain longest-common-subsequence table is
+//! fast enough and keeps the output identical to `diff -u` for typical edits.
+
+/// Unchanged lines shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Renders the changes from `old` to `new` as a unified diff with `--- ` and
+/// `+++ ` headers. Returns an empty string when the texts are equal.
+///
+/// A last line without a trailing newline is followed by
+/// `\ No newline at end of file`, as in `diff -u`.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = diff_ops(&old_lines, &new_lines);
+
+    let mut output = format!("--- {old_label}\n+++ {new_label}\n");
+    // Line positions before each op, so hunk headers can be computed from
+    // any op index.
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut old_index, mut new_index) = (0, 0);
+    for op in &ops {
+        positions.push((old_index, new_index));
+        match op {
+            Op::Equal => {
+                old_index += 1;
+                new_index += 1;
+            }
+            Op::Delete => old_index += 1,
+            Op::Insert => new_index += 1,
+        }
+    }
+    positions.push((old_index, new_index));
+
+    for (first, last) in hunk_ranges(&ops) {
+        let (old_start, new_start) = positions[first];
+        let (old_end, new_end) = positions[last + 1];
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_end - old_start),
+            hunk_range(new_start, new_end - new_start)
+        ));
+        for (op, (old_index, new_index)) in ops[first..=last].iter().zip(&positions[first..]) {
+            let (prefix, line) = match op {
+                Op::Equal => (' ', old_lines[*old_index]),
+                Op::Delete => ('-', old_lines[*old_index]),
+                Op::Insert => ('+', new_lines[*new_index]),
+            };
+            output.push(prefix);
+            output.push_str(line);
+            if !line.ends_with('\n') {
+                output.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    output
+}
+
+/// `start,count` in hunk header notation, where `start` is 1-based and an
+/// empty range names the line before it.
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        count => format!("{},{count}", start + 1),
+    }
+}
+
+/// Edit script turning `old` into `new`, with deletions before insertions
+/// at each point of change.
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<Op> {
+    // common[i][j]: length of the longest common subsequence of old[i..] and
+    // new[j..].
+    let mut common = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push(Op::Equal);
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            ops.push(Op::Delete);
+            i += 1;
+        } else {
+            ops.push(Op::Insert);
+            j += 1;
+        }
+    }
+    ops
+}
+
+/// Inclusive op ranges of the hunks: each change with up to
+/// [`CONTEXT_LINES`] equal lines around it, merging changes whose context
+/// would overlap.
+fn hunk_ranges(ops: &[Op]) -> Vec<(usize, usize)> {
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| **op != Op::Equal)
+        .map(|(index, _)| index)
+        .collect();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for change in changes {
+        let first = change.saturating_sub(CONTEXT_LINES);
+        let last = (change + CONTEXT_LINES).min(ops.len() - 1);
+        match ranges.last_mut() {
+            Some((_, previous_last)) if first <= *previous_last + 1 => *previous_last = last,
+            _ => ranges.push((first, last)),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::assert;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn given_equal_texts_when_diffing_then_diff_is_empty() {
+        assert!(unified_diff("a\nb\n", "a\nb\n", "old", "new").is_empty());
+    }
+
+    #[test]
+    fn given_changed_line_when_diffing_then_hunk_has_context_and_header() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n";
+
+        assert!(
+            unified_diff(old, new, "a/pre-commit", "b/pre-commit")
+                == "--- a/pre-commit\n+++ b/pre-commit\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+        );
+    }
+
+    #[test]
+    fn given_distant_changes_when_diffing_then_they_get_separate_hunks() {
+        let old: String = (1..=20).map(|n| format!("line {n}\n")).collect();
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 19\n", "line nineteen\n");
+
+        let diff = unified_diff(&old, &new, "old", "new");
+        assert!(diff.matches("@@ ").count() == 2);
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n line 1\n-line 2\n+line two\n"));
+        assert!(diff.contains("@@ -16,5 +16,5 @@\n"));
+    }
+
+    #[test]
+    fn given_empty_old_text_when_diffing_then_every_line_is_added() {
+        assert!(
+            unified_diff("", "a\nb\n", "old", "new")
+                == "--- old\n+++ new\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+
+    #[test]
+    fn given_missing_trailing_newline_when_diffing_then_it_is_marked() {
+        assert!(
+            unified_diff("a\nb", "a\nb\n", "old", "new")
+                == "--- old\n+++ new\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn diff_lines_rebuild_both_texts(
+            old in proptest::collection::vec("[abc]{0,2}\n", 0..12),
+            new in proptest::collection::vec("[abc]{0,2}\n", 0..12),
+        ) {
+            let old = old.concat();
+            let new = new.concat();
+            let ops = diff_ops(
+                &old.split_inclusive('\n').collect::<Vec<_>>(),
+                &new.split_inclusive('\n').collect::<Vec<_>>(),
+            );
+            prop_assert_eq!(
+                ops.iter().filter(|op| **op != Op::Insert).count(),
+                old.split_inclusive('\n').count()
+            );
+            prop_assert_eq!(
+                ops.iter().filter(|op| **op != Op::Delete).count(),
+                new.split_inclusive('\n').count()
+            );
+            prop_assert_eq!(unified_diff(&old, &new, "a", "b").is_empty(), old == new);
+        }
+    }
+}
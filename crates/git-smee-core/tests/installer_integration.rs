@@ -6,8 +6,10 @@ use std::{
 
 use git_smee_core::{
     DEFAULT_CONFIG_FILE_NAME, SmeeConfig,
+    config::LifeCyclePhase,
     installer::{
-        self, Error, FileSystemHookInstaller, HookInstaller, HookScriptOptions, MANAGED_FILE_MARKER,
+        self, Error, FileSystemHookInstaller, HookDriftKind, HookInstallState, HookInstaller,
+        HookScriptOptions, HooksPathConflict, MANAGED_FILE_MARKER, PlannedHookAction,
     },
 };
 
@@ -32,6 +34,73 @@ fn given_standard_repo_when_installing_hooks_then_hooks_are_written_to_git_hooks
     assert!(hooks_path.join("pre-push").exists());
 }
 
+#[test]
+fn given_hooks_path_setting_when_installing_hooks_then_hooks_are_written_there_and_git_uses_them() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    let mut config = pre_commit_only_config();
+    config.settings.hooks_path = Some(".git-smee/hooks".to_string());
+    let installer = FileSystemHookInstaller::from_path_with_hooks_path(
+        repo.clone(),
+        config.settings.hooks_path.as_deref(),
+        false,
+    )
+    .unwrap();
+
+    installer::install_hooks(&config, &installer).unwrap();
+
+    assert!(repo.join(".git-smee/hooks/pre-commit").exists());
+    assert_eq!(
+        git_output(&repo, &["config", "--local", "core.hooksPath"]).trim(),
+        ".git-smee/hooks"
+    );
+    assert_eq!(
+        normalize_path_for_compare(&resolve_hooks_path_with_git(&repo)),
+        normalize_path_for_compare(&repo.join(".git-smee/hooks"))
+    );
+}
+
+#[test]
+fn given_core_hooks_path_when_checking_conflicts_then_mismatch_with_hooks_path_setting_is_reported()
+{
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    let mut config = pre_commit_only_config();
+
+    assert_eq!(
+        installer::hooks_path_conflict(&repo, &config).unwrap(),
+        None
+    );
+    git(&repo, &["config", "core.hooksPath", ".husky"]);
+    assert_eq!(
+        installer::hooks_path_conflict(&repo, &config).unwrap(),
+        Some(HooksPathConflict::SetOutsideConfig {
+            actual: repo.join(".husky"),
+            scope: Some("local".to_string()),
+        })
+    );
+
+    config.settings.hooks_path = Some(".git-smee/hooks".to_string());
+    assert!(matches!(
+        installer::hooks_path_conflict(&repo, &config).unwrap(),
+        Some(HooksPathConflict::PointsElsewhere { .. })
+    ));
+    git(&repo, &["config", "--unset", "core.hooksPath"]);
+    assert_eq!(
+        installer::hooks_path_conflict(&repo, &config).unwrap(),
+        Some(HooksPathConflict::NotPointedAtConfigured {
+            configured: repo.join(".git-smee/hooks"),
+        })
+    );
+    git(&repo, &["config", "core.hooksPath", ".git-smee/hooks"]);
+    assert_eq!(
+        installer::hooks_path_conflict(&repo, &config).unwrap(),
+        None
+    );
+}
+
 #[cfg(unix)]
 #[test]
 fn given_stale_embedded_binary_when_running_installed_hook_then_path_fallback_is_not_used() {
@@ -135,6 +204,57 @@ fn given_non_utf8_unix_paths_when_running_installed_hook_then_original_bytes_are
     );
 }
 
+#[cfg(unix)]
+#[test]
+fn given_adversarial_paths_when_running_installed_hook_then_paths_reach_git_smee_intact() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    write_config_fixture(&repo);
+
+    let adversarial_dir = temp_dir
+        .path()
+        .join("it's a \"$(touch pwned)\" `x` ünïcødé ☃ *");
+    fs::create_dir(&adversarial_dir).unwrap();
+    let observed_config_arg = temp_dir.path().join("observed-config-arg");
+    let fake_git_smee = adversarial_dir.join("git smee");
+    fs::write(
+        &fake_git_smee,
+        format!(
+            "#!/usr/bin/env sh\nprintf '%s' \"$2\" > '{}'\n",
+            observed_config_arg.display()
+        ),
+    )
+    .unwrap();
+    let mut permissions = fs::metadata(&fake_git_smee).unwrap().permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_git_smee, permissions).unwrap();
+
+    let config_path = adversarial_dir.join("config; rm -rf $HOME.toml");
+    let config = read_config_from_repo(&repo);
+    let installer = FileSystemHookInstaller::from_path(repo.clone()).unwrap();
+    let options = HookScriptOptions::new(fake_git_smee, config_path.clone());
+    installer::install_hooks_with_options(&config, &installer, &options).unwrap();
+
+    let output = Command::new(resolve_hooks_path_with_git(&repo).join("pre-commit"))
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "hook failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(observed_config_arg).unwrap(),
+        config_path.to_str().unwrap()
+    );
+    assert!(!repo.join("pwned").exists());
+}
+
 #[cfg(unix)]
 #[test]
 fn given_managed_hook_when_reinstalling_then_hook_file_is_atomically_replaced() {
@@ -270,6 +390,54 @@ fn given_missing_custom_hooks_path_when_creating_installer_then_directory_is_cre
     );
 }
 
+#[test]
+fn given_repository_without_hooks_dir_when_installing_then_directory_is_created_and_hooks_written()
+{
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    write_config_fixture(&repo);
+    let hooks_dir = resolve_hooks_path_with_git(&repo);
+    fs::remove_dir_all(&hooks_dir).unwrap();
+
+    let config = read_config_from_repo(&repo);
+    let installer = FileSystemHookInstaller::from_path(repo.clone()).unwrap();
+    installer::install_hooks(&config, &installer).unwrap();
+
+    assert!(hooks_dir.is_dir());
+    assert!(hooks_dir.join("pre-commit").exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        // The remaining bits depend on the umask, as they do for `git init`.
+        let mode = fs::metadata(&hooks_dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o700, 0o700);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn given_hooks_dir_symlink_to_missing_target_when_creating_installer_then_error_names_the_target() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    let hooks_dir = resolve_hooks_path_with_git(&repo);
+    fs::remove_dir_all(&hooks_dir).unwrap();
+    let target = temp_dir.path().join("shared-hooks");
+    std::os::unix::fs::symlink(&target, &hooks_dir).unwrap();
+
+    let result = FileSystemHookInstaller::from_path(repo.clone());
+
+    match result {
+        Err(Error::HooksDirSymlinkTargetMissing {
+            target: reported, ..
+        }) => assert_eq!(PathBuf::from(reported), target),
+        Ok(_) => panic!("expected a missing symlink target error"),
+        Err(error) => panic!("unexpected error: {error}"),
+    }
+    assert!(!target.exists());
+}
+
 #[test]
 fn given_non_directory_custom_hooks_path_when_creating_installer_then_error_includes_resolved_path()
 {
@@ -282,13 +450,13 @@ fn given_non_directory_custom_hooks_path_when_creating_installer_then_error_incl
     let result = FileSystemHookInstaller::from_path(repo.clone());
 
     match result {
-        Err(Error::HooksDirNotFound(path)) => {
+        Err(Error::HooksPathNotDirectory(path)) => {
             assert_eq!(
                 normalize_path_for_compare(&PathBuf::from(path)),
                 normalize_path_for_compare(&repo.join(".not-a-directory"))
             )
         }
-        Ok(_) => panic!("expected hooks-dir-not-found error"),
+        Ok(_) => panic!("expected hooks-path-not-directory error"),
         Err(error) => panic!("unexpected error: {error}"),
     }
 }
@@ -436,6 +604,132 @@ fn given_managed_hook_phase_removed_when_reinstalling_then_obsolete_hook_is_prun
     assert!(!pre_push.exists());
 }
 
+#[test]
+fn given_drifted_hooks_when_verifying_then_missing_extra_modified_and_permissions_are_reported() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    write_config_fixture(&repo);
+    let config = read_config_from_repo(&repo);
+    let installer = FileSystemHookInstaller::from_path(repo.clone()).unwrap();
+    let options = HookScriptOptions::new(
+        PathBuf::from("/opt/git-smee"),
+        PathBuf::from(DEFAULT_CONFIG_FILE_NAME),
+    );
+    let hooks_path = resolve_hooks_path_with_git(&repo);
+
+    let report = installer::verify_hooks(&config, &hooks_path, &options);
+    assert_eq!(
+        report
+            .drift
+            .iter()
+            .map(|drift| (drift.phase.as_str(), drift.kind.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("pre-commit", HookDriftKind::Missing),
+            ("pre-push", HookDriftKind::Missing)
+        ]
+    );
+
+    installer::install_hooks_with_options(&config, &installer, &options).unwrap();
+    assert!(installer::verify_hooks(&config, &hooks_path, &options).is_clean());
+
+    let pre_commit = hooks_path.join("pre-commit");
+    let mut content = fs::read_to_string(&pre_commit).unwrap();
+    content.push_str("echo tampered\n");
+    fs::write(&pre_commit, content).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&pre_commit, fs::Permissions::from_mode(0o644)).unwrap();
+    }
+
+    let report = installer::verify_hooks(&pre_commit_only_config(), &hooks_path, &options);
+
+    let kinds: Vec<_> = report
+        .drift
+        .iter()
+        .map(|drift| (drift.phase.as_str(), &drift.kind))
+        .collect();
+    assert!(matches!(
+        kinds[0],
+        ("pre-commit", HookDriftKind::Modified { diff, .. }) if diff.contains("-echo tampered")
+    ));
+    #[cfg(unix)]
+    assert_eq!(
+        kinds[1],
+        (
+            "pre-commit",
+            &HookDriftKind::WrongPermissions { mode: 0o644 }
+        )
+    );
+    assert_eq!(kinds.last(), Some(&("pre-push", &HookDriftKind::Extra)));
+    assert!(
+        report
+            .for_phase(git_smee_core::config::LifeCyclePhase::PrePush)
+            .all(|drift| drift.path == hooks_path.join("pre-push"))
+    );
+}
+
+#[test]
+fn given_installed_hooks_when_planning_install_then_changes_are_reported_without_writing() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    write_config_fixture(&repo);
+    let installer = FileSystemHookInstaller::from_path(repo.clone()).unwrap();
+    let old_options = HookScriptOptions::new(
+        PathBuf::from("/opt/old/git-smee"),
+        PathBuf::from(DEFAULT_CONFIG_FILE_NAME),
+    );
+    let new_options = HookScriptOptions::new(
+        PathBuf::from("/opt/new/git-smee"),
+        PathBuf::from(DEFAULT_CONFIG_FILE_NAME),
+    );
+
+    let planned =
+        installer::plan_hooks_with_options(&read_config_from_repo(&repo), &installer, &old_options)
+            .unwrap();
+    assert!(
+        planned
+            .iter()
+            .all(|change| change.action == PlannedHookAction::Create)
+    );
+    installer::install_hooks_with_options(&read_config_from_repo(&repo), &installer, &old_options)
+        .unwrap();
+
+    let hooks_path = resolve_hooks_path_with_git(&repo);
+    let pre_commit_before = fs::read_to_string(hooks_path.join("pre-commit")).unwrap();
+    let planned =
+        installer::plan_hooks_with_options(&pre_commit_only_config(), &installer, &new_options)
+            .unwrap();
+
+    let actions: Vec<_> = planned
+        .iter()
+        .map(|change| (change.hook_name.as_str(), &change.action))
+        .collect();
+    assert_eq!(actions.len(), 2);
+    assert_eq!(actions[0].0, "pre-commit");
+    let PlannedHookAction::Update { diff } = actions[0].1 else {
+        panic!("expected pre-commit to be updated, got {:?}", actions[0].1);
+    };
+    assert!(diff.starts_with("--- a/pre-commit\n+++ b/pre-commit\n@@ "));
+    assert!(
+        diff.lines()
+            .any(|line| line.starts_with('-') && line.contains("/opt/old/git-smee"))
+    );
+    assert!(
+        diff.lines()
+            .any(|line| line.starts_with('+') && line.contains("/opt/new/git-smee"))
+    );
+    assert_eq!(actions[1], ("pre-push", &PlannedHookAction::Remove));
+    assert_eq!(
+        fs::read_to_string(hooks_path.join("pre-commit")).unwrap(),
+        pre_commit_before
+    );
+    assert!(hooks_path.join("pre-push").exists());
+}
+
 #[test]
 fn given_unmanaged_hook_for_removed_phase_when_reinstalling_then_hook_is_preserved() {
     let temp_dir = tempfile::tempdir().unwrap();
@@ -673,6 +967,200 @@ fn given_existing_config_with_marker_only_in_body_when_initializing_without_forc
     ));
 }
 
+#[test]
+fn given_managed_and_unmanaged_hooks_when_uninstalling_then_only_managed_hooks_are_removed() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    write_config_fixture(&repo);
+    let config = read_config_from_repo(&repo);
+    let installer = FileSystemHookInstaller::from_path(repo.clone()).unwrap();
+    installer::install_hooks(&config, &installer).unwrap();
+    let hooks_path = installer.effective_hooks_dir().clone();
+    fs::write(hooks_path.join("post-merge"), "#!/bin/sh\necho custom\n").unwrap();
+
+    let mut removed = installer::uninstall_hooks(&installer).unwrap();
+    removed.sort();
+
+    assert_eq!(
+        removed,
+        [hooks_path.join("pre-commit"), hooks_path.join("pre-push")]
+    );
+    assert!(!hooks_path.join("pre-commit").exists());
+    assert!(hooks_path.join("post-merge").exists());
+    assert!(installer::uninstall_hooks(&installer).unwrap().is_empty());
+}
+
+#[test]
+fn given_unmanaged_hook_when_installing_with_chain_then_it_is_kept_and_restored_on_uninstall() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    write_config_fixture(&repo);
+    let config = read_config_from_repo(&repo);
+    let installer = FileSystemHookInstaller::from_path(repo.clone())
+        .unwrap()
+        .with_chain_existing(true);
+    let hooks_path = installer.effective_hooks_dir().clone();
+    let original = "#!/bin/sh\necho hand-written\n";
+    fs::write(hooks_path.join("pre-commit"), original).unwrap();
+    let options =
+        HookScriptOptions::new(PathBuf::from("git-smee"), PathBuf::from(".git-smee.toml"));
+
+    installer::install_hooks_with_options(&config, &installer, &options).unwrap();
+
+    let backup = hooks_path.join("pre-commit.pre-smee");
+    assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+    let wrapper = fs::read_to_string(hooks_path.join("pre-commit")).unwrap();
+    assert!(wrapper.contains(MANAGED_FILE_MARKER));
+    assert!(wrapper.contains("/pre-commit.pre-smee\""));
+    let pre_push = fs::read_to_string(hooks_path.join("pre-push")).unwrap();
+    assert!(!pre_push.contains(".pre-smee"));
+    let plain_installer = FileSystemHookInstaller::from_path(repo.clone()).unwrap();
+    assert!(installer::verify_hooks(&config, &hooks_path, &options).is_clean());
+    installer::install_hooks_with_options(&config, &plain_installer, &options).unwrap();
+    assert_eq!(
+        fs::read_to_string(hooks_path.join("pre-commit")).unwrap(),
+        wrapper
+    );
+
+    installer::uninstall_hooks(&installer).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(hooks_path.join("pre-commit")).unwrap(),
+        original
+    );
+    assert!(!backup.exists());
+}
+
+#[test]
+fn given_selected_phases_when_installing_then_other_hook_files_are_left_alone() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    write_config_fixture(&repo);
+    let config = read_config_from_repo(&repo);
+    let installer = FileSystemHookInstaller::from_path(repo.clone())
+        .unwrap()
+        .with_only_phases(vec![LifeCyclePhase::PreCommit]);
+    let hooks_path = installer.effective_hooks_dir().clone();
+    let husky_pre_push = "#!/bin/sh\nnpx husky run pre-push\n";
+    fs::write(hooks_path.join("pre-push"), husky_pre_push).unwrap();
+    let stale_wrapper = format!("#!/bin/sh\n# {MANAGED_FILE_MARKER}\n");
+    fs::write(hooks_path.join("post-merge"), &stale_wrapper).unwrap();
+    let options =
+        HookScriptOptions::new(PathBuf::from("git-smee"), PathBuf::from(".git-smee.toml"));
+
+    let planned = installer::plan_hooks_with_options(&config, &installer, &options).unwrap();
+    installer::install_hooks_with_options(&config, &installer, &options).unwrap();
+
+    assert_eq!(
+        planned
+            .iter()
+            .map(|change| change.hook_name.as_str())
+            .collect::<Vec<_>>(),
+        ["pre-commit"]
+    );
+    assert!(
+        fs::read_to_string(hooks_path.join("pre-commit"))
+            .unwrap()
+            .contains(MANAGED_FILE_MARKER)
+    );
+    assert_eq!(
+        fs::read_to_string(hooks_path.join("pre-push")).unwrap(),
+        husky_pre_push
+    );
+    assert_eq!(
+        fs::read_to_string(hooks_path.join("post-merge")).unwrap(),
+        stale_wrapper
+    );
+}
+
+#[test]
+fn given_selected_phase_without_hooks_when_installing_then_it_is_rejected() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    let mut config = pre_commit_only_config();
+    config.settings.never_install = vec![LifeCyclePhase::PreCommit];
+    let select = |phase| {
+        FileSystemHookInstaller::from_path(repo.clone())
+            .unwrap()
+            .with_only_phases(vec![phase])
+    };
+
+    let unconfigured = installer::install_hooks(&config, &select(LifeCyclePhase::PostMerge));
+    let never_installed = installer::install_hooks(&config, &select(LifeCyclePhase::PreCommit));
+
+    assert!(matches!(
+        unconfigured,
+        Err(Error::PhaseNotConfigured(LifeCyclePhase::PostMerge))
+    ));
+    assert!(matches!(
+        never_installed,
+        Err(Error::PhaseNeverInstalled(LifeCyclePhase::PreCommit))
+    ));
+}
+
+#[test]
+fn given_existing_backup_when_installing_with_chain_then_unmanaged_hook_is_not_overwritten() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    let config = pre_commit_only_config();
+    let installer = FileSystemHookInstaller::from_path(repo.clone())
+        .unwrap()
+        .with_chain_existing(true);
+    let hooks_path = installer.effective_hooks_dir().clone();
+    fs::write(hooks_path.join("pre-commit"), "#!/bin/sh\necho current\n").unwrap();
+    fs::write(
+        hooks_path.join("pre-commit.pre-smee"),
+        "#!/bin/sh\necho older\n",
+    )
+    .unwrap();
+
+    let result = installer::install_hooks(&config, &installer);
+
+    assert!(matches!(result, Err(Error::ChainedHookExists { .. })));
+    assert_eq!(
+        fs::read_to_string(hooks_path.join("pre-commit")).unwrap(),
+        "#!/bin/sh\necho current\n"
+    );
+}
+
+#[test]
+fn given_installed_removed_and_replaced_hooks_when_listing_statuses_then_each_phase_has_its_state()
+{
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo = temp_dir.path().join("repo");
+    init_repo(&repo);
+    let config = SmeeConfig::from_toml_str(
+        "[settings]\nnever_install = [\"post-checkout\"]\n\n[[pre-commit]]\ncommand = \"cargo fmt --check\"\n\n[[pre-push]]\ncommand = \"cargo test\"\n\n[[post-merge]]\ncommand = \"echo merged\"\n\n[[post-checkout]]\ncommand = \"echo checked out\"\n",
+    )
+    .unwrap();
+    let installer = FileSystemHookInstaller::from_path(repo.clone()).unwrap();
+    installer::install_hooks(&config, &installer).unwrap();
+    let hooks_path = installer.effective_hooks_dir().clone();
+    fs::remove_file(hooks_path.join("pre-push")).unwrap();
+    fs::write(hooks_path.join("post-merge"), "#!/bin/sh\necho custom\n").unwrap();
+
+    let statuses = installer::phase_install_statuses(&config, &hooks_path);
+
+    assert_eq!(
+        statuses
+            .iter()
+            .map(|status| (status.phase.as_str(), status.state.clone()))
+            .collect::<Vec<_>>(),
+        [
+            ("pre-commit", HookInstallState::Installed),
+            ("post-checkout", HookInstallState::NeverInstalled),
+            ("post-merge", HookInstallState::Unmanaged),
+            ("pre-push", HookInstallState::NotInstalled),
+        ]
+    );
+    assert_eq!(statuses[0].entries[0].command, "cargo fmt --check");
+}
+
 #[test]
 fn given_existing_config_when_initializing_with_force_then_overwrites() {
     let temp_dir = tempfile::tempdir().unwrap();
@@ -731,9 +1219,16 @@ fn pre_commit_only_config() -> SmeeConfig {
         vec![git_smee_core::config::HookDefinition {
             command: "echo pre-commit".to_string(),
             parallel_execution_allowed: false,
+            ..Default::default()
         }],
     );
-    SmeeConfig { hooks }
+    SmeeConfig {
+        hooks,
+        settings: Default::default(),
+        tasks: Default::default(),
+        defaults: Default::default(),
+        install: Default::default(),
+    }
 }
 
 fn read_config_from_repo(repo: &Path) -> SmeeConfig {
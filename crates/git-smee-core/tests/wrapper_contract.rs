@@ -0,0 +1,337 @@
+//! Contract tests for the generated Unix hook wrappers: they run the rendered
+//! scripts under `sh` against a fake `git-smee` on `PATH` that records its
+//! argv and stdin, so template changes cannot silently mangle what reaches
+//! `git smee run`.
+#![cfg(unix)]
+
+use std::{
+    ffi::OsString,
+    fs,
+    io::Write,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+use git_smee_core::{
+    DEFAULT_CONFIG_FILE_NAME, SmeeConfig,
+    config::{HookDefinition, LifeCyclePhase, PhaseInstall},
+    installer::{self, FileSystemHookInstaller, HookScriptOptions},
+};
+
+/// A temp repository with wrappers that call a fake `git-smee` found on
+/// `PATH`. Each call of the fake records its arguments (NUL-separated) and
+/// stdin under `capture/<phase>.argv` and `capture/<phase>.stdin`.
+struct WrapperHarness {
+    _temp_dir: tempfile::TempDir,
+    repo: PathBuf,
+    bin_dir: PathBuf,
+    capture_dir: PathBuf,
+}
+
+impl WrapperHarness {
+    fn new() -> Self {
+        Self::with_fake_exit_code(0)
+    }
+
+    fn with_fake_exit_code(exit_code: i32) -> Self {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "--quiet"]);
+        let bin_dir = temp_dir.path().join("bin");
+        let capture_dir = temp_dir.path().join("capture");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::create_dir_all(&capture_dir).unwrap();
+        let fake = bin_dir.join("git-smee");
+        fs::write(
+            &fake,
+            format!(
+                "#!/usr/bin/env sh\ncapture='{}'/\"$4\"\n: > \"$capture.argv\"\nfor arg in \"$@\"; do printf '%s\\0' \"$arg\" >> \"$capture.argv\"; done\ncat > \"$capture.stdin\"\nexit {exit_code}\n",
+                capture_dir.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&fake, fs::Permissions::from_mode(0o755)).unwrap();
+        Self {
+            _temp_dir: temp_dir,
+            repo,
+            bin_dir,
+            capture_dir,
+        }
+    }
+
+    fn options() -> HookScriptOptions {
+        HookScriptOptions::new(
+            PathBuf::from("git-smee"),
+            PathBuf::from(DEFAULT_CONFIG_FILE_NAME),
+        )
+    }
+
+    fn path_env(&self) -> OsString {
+        let original_path = std::env::var_os("PATH").unwrap_or_default();
+        std::env::join_paths(
+            std::iter::once(self.bin_dir.clone()).chain(std::env::split_paths(&original_path)),
+        )
+        .unwrap()
+    }
+
+    /// Renders the wrapper for `phase` and runs it under `sh` with `args`
+    /// and `stdin`, the way Git would.
+    fn run_wrapper(&self, phase: LifeCyclePhase, args: &[&str], stdin: &[u8]) -> Output {
+        self.run_config_wrapper(&SmeeConfig::default(), phase, args, stdin)
+    }
+
+    /// Like [`Self::run_wrapper`], with the wrapper `config` installs.
+    fn run_config_wrapper(
+        &self,
+        config: &SmeeConfig,
+        phase: LifeCyclePhase,
+        args: &[&str],
+        stdin: &[u8],
+    ) -> Output {
+        let wrapper = self.repo.join(format!("{phase}.wrapper"));
+        fs::write(
+            &wrapper,
+            installer::render_hook_script(config, phase, &Self::options()),
+        )
+        .unwrap();
+        let mut child = Command::new("sh")
+            .arg(&wrapper)
+            .args(args)
+            .env("PATH", self.path_env())
+            .current_dir(&self.repo)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(stdin).unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    fn captured_argv(&self, phase: LifeCyclePhase) -> Vec<String> {
+        let argv = fs::read(self.capture_dir.join(format!("{phase}.argv"))).unwrap();
+        argv.split(|byte| *byte == 0)
+            .take(argv.iter().filter(|byte| **byte == 0).count())
+            .map(|arg| String::from_utf8(arg.to_vec()).unwrap())
+            .collect()
+    }
+
+    fn captured_stdin(&self, phase: LifeCyclePhase) -> Vec<u8> {
+        fs::read(self.capture_dir.join(format!("{phase}.stdin"))).unwrap()
+    }
+}
+
+#[test]
+fn given_every_phase_when_running_its_wrapper_then_git_smee_runs_that_phase_with_the_config() {
+    let harness = WrapperHarness::new();
+
+    for &phase in LifeCyclePhase::all() {
+        let output = harness.run_wrapper(phase, &[], b"");
+
+        assert!(
+            output.status.success(),
+            "{phase} wrapper failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(
+            harness.captured_argv(phase),
+            [
+                "--config",
+                DEFAULT_CONFIG_FILE_NAME,
+                "run",
+                phase.as_str(),
+                "--"
+            ]
+        );
+    }
+}
+
+#[test]
+fn given_awkward_hook_args_when_running_wrapper_then_they_reach_git_smee_run_intact() {
+    let harness = WrapperHarness::new();
+    let args = [
+        ".git/COMMIT_EDITMSG",
+        "",
+        "two words",
+        "--config",
+        "-",
+        "it's \"quoted\"",
+        "$(touch pwned) `x` $HOME",
+        "*",
+        "line one\nline two",
+        "ünïcødé ☃",
+    ];
+
+    let output = harness.run_wrapper(LifeCyclePhase::CommitMsg, &args, b"");
+
+    assert!(
+        output.status.success(),
+        "wrapper failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let argv = harness.captured_argv(LifeCyclePhase::CommitMsg);
+    assert_eq!(
+        argv[..5],
+        [
+            "--config",
+            DEFAULT_CONFIG_FILE_NAME,
+            "run",
+            "commit-msg",
+            "--"
+        ]
+    );
+    assert_eq!(argv[5..], args);
+    assert!(!harness.repo.join("pwned").exists());
+}
+
+#[test]
+fn given_hook_stdin_when_running_wrapper_then_git_smee_reads_it_byte_for_byte() {
+    let harness = WrapperHarness::new();
+    let stdin = b"refs/heads/main 1111111 refs/heads/main 0000000\n\
+refs/tags/v1 2222222 refs/tags/v1 0000000\n\x00\xff no trailing newline";
+
+    let output = harness.run_wrapper(
+        LifeCyclePhase::PrePush,
+        &["origin", "git@example.com:repo.git"],
+        stdin,
+    );
+
+    assert!(output.status.success());
+    assert_eq!(harness.captured_stdin(LifeCyclePhase::PrePush), stdin);
+    assert_eq!(
+        harness.captured_argv(LifeCyclePhase::PrePush)[5..],
+        ["origin", "git@example.com:repo.git"]
+    );
+}
+
+#[test]
+fn given_failing_git_smee_when_running_wrapper_then_its_exit_status_is_the_hooks() {
+    let harness = WrapperHarness::with_fake_exit_code(3);
+
+    let output = harness.run_wrapper(LifeCyclePhase::PreCommit, &[], b"");
+
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn given_prepend_lines_when_running_wrapper_then_they_run_before_git_smee_and_can_stop_it() {
+    let harness = WrapperHarness::new();
+    let mut config = config_with_phases(&[LifeCyclePhase::PreCommit]);
+    config.install.insert(
+        LifeCyclePhase::PreCommit,
+        PhaseInstall {
+            prepend: Some("echo \"$0\" > prepared.txt\nexport PREPARED=1\n".to_string()),
+        },
+    );
+
+    let output = harness.run_config_wrapper(&config, LifeCyclePhase::PreCommit, &[], b"");
+
+    assert!(
+        output.status.success(),
+        "wrapper failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(harness.repo.join("prepared.txt").exists());
+    assert_eq!(
+        harness.captured_argv(LifeCyclePhase::PreCommit),
+        [
+            "--config",
+            DEFAULT_CONFIG_FILE_NAME,
+            "run",
+            "pre-commit",
+            "--"
+        ]
+    );
+
+    config.install.insert(
+        LifeCyclePhase::CommitMsg,
+        PhaseInstall {
+            prepend: Some("false".to_string()),
+        },
+    );
+    let output = harness.run_config_wrapper(&config, LifeCyclePhase::CommitMsg, &[], b"");
+    assert!(!output.status.success());
+    assert!(
+        !harness
+            .capture_dir
+            .join(format!("{}.argv", LifeCyclePhase::CommitMsg))
+            .exists()
+    );
+}
+
+#[test]
+fn given_installed_wrappers_when_git_commits_then_git_smee_gets_each_phase_and_its_args() {
+    let harness = WrapperHarness::new();
+    let config = config_with_phases(&[LifeCyclePhase::PreCommit, LifeCyclePhase::CommitMsg]);
+    let installer = FileSystemHookInstaller::from_path(harness.repo.clone()).unwrap();
+    installer::install_hooks_with_options(&config, &installer, &WrapperHarness::options()).unwrap();
+
+    let output = Command::new("git")
+        .args([
+            "-c",
+            "user.name=Wrapper Test",
+            "-c",
+            "user.email=wrapper@example.com",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "contract",
+        ])
+        .env("PATH", harness.path_env())
+        .current_dir(&harness.repo)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "commit failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        harness.captured_argv(LifeCyclePhase::PreCommit),
+        [
+            "--config",
+            DEFAULT_CONFIG_FILE_NAME,
+            "run",
+            "pre-commit",
+            "--"
+        ]
+    );
+    let commit_msg_argv = harness.captured_argv(LifeCyclePhase::CommitMsg);
+    assert_eq!(commit_msg_argv.len(), 6);
+    assert!(Path::new(&commit_msg_argv[5]).ends_with("COMMIT_EDITMSG"));
+}
+
+fn config_with_phases(phases: &[LifeCyclePhase]) -> SmeeConfig {
+    let hooks = phases
+        .iter()
+        .map(|&phase| {
+            (
+                phase,
+                vec![HookDefinition {
+                    command: "true".to_string(),
+                    ..Default::default()
+                }],
+            )
+        })
+        .collect();
+    SmeeConfig {
+        hooks,
+        settings: Default::default(),
+        tasks: Default::default(),
+        defaults: Default::default(),
+        install: Default::default(),
+    }
+}
+
+fn git(repo: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}